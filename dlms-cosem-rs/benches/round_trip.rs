@@ -0,0 +1,83 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use dlms_cosem::client::Client;
+use dlms_cosem::cosem::CosemAttributeDescriptor;
+use dlms_cosem::cosem_object::CosemObject;
+use dlms_cosem::hdlc::{HdlcAddress, HdlcFrame};
+use dlms_cosem::register::Register;
+use dlms_cosem::server::Server;
+use dlms_cosem::testing::{DetachedTransport, LoopbackTransport};
+use dlms_cosem::types::CosemData;
+use dlms_cosem::xdlms::{GetRequest, GetRequestNormal, SetRequest, SetRequestNormal};
+
+const ENERGY: CosemAttributeDescriptor = CosemAttributeDescriptor {
+    class_id: 3,
+    instance_id: [1, 0, 1, 8, 0, 255],
+    attribute_id: 2,
+};
+
+fn associated_client() -> Client<LoopbackTransport> {
+    let mut server = Server::new(1, DetachedTransport, None, None);
+    let mut register = Register::new();
+    register
+        .set_attribute(2, CosemData::DoubleLongUnsigned(10))
+        .unwrap();
+    server.register_object(ENERGY.instance_id, Box::new(register));
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+    client
+}
+
+fn get_round_trip(c: &mut Criterion) {
+    let mut client = associated_client();
+    c.bench_function("get_round_trip", |b| {
+        b.iter(|| {
+            client
+                .send_get_request(GetRequest::Normal(GetRequestNormal {
+                    invoke_id_and_priority: 1,
+                    cosem_attribute_descriptor: ENERGY,
+                    access_selection: None,
+                }))
+                .expect("get failed")
+        })
+    });
+}
+
+fn set_round_trip(c: &mut Criterion) {
+    let mut client = associated_client();
+    c.bench_function("set_round_trip", |b| {
+        b.iter(|| {
+            client
+                .send_set_request(SetRequest::Normal(SetRequestNormal {
+                    invoke_id_and_priority: 1,
+                    cosem_attribute_descriptor: ENERGY,
+                    access_selection: None,
+                    value: CosemData::DoubleLongUnsigned(black_box(11)),
+                }))
+                .expect("set failed")
+        })
+    });
+}
+
+fn hdlc_framing(c: &mut Criterion) {
+    let frame = HdlcFrame {
+        destination: HdlcAddress::new(1, 17),
+        address: 0x21,
+        control: 0x10,
+        // Flags and escapes in the payload exercise the byte stuffing.
+        information: (0..=255u8).collect(),
+    };
+    let bytes = frame.to_bytes().unwrap();
+    c.bench_function("hdlc_encode", |b| {
+        let mut buffer = Vec::with_capacity(bytes.len());
+        b.iter(|| {
+            buffer.clear();
+            black_box(&frame).encode_into(&mut buffer).unwrap();
+        })
+    });
+    c.bench_function("hdlc_decode", |b| {
+        b.iter(|| HdlcFrame::from_bytes(black_box(&bytes)).unwrap())
+    });
+}
+
+criterion_group!(benches, get_round_trip, set_round_trip, hdlc_framing);
+criterion_main!(benches);
@@ -0,0 +1,298 @@
+//! Code generation for COSEM interface classes of the `dlms-cosem-rs` crate.
+//!
+//! ```ignore
+//! #[cosem_object(class_id = 3, methods(1 = reset))]
+//! pub struct Register {
+//!     #[attribute(2, read_write)]
+//!     value: CosemData,
+//!     #[attribute(3, read)]
+//!     scaler_unit: CosemData,
+//!     #[callbacks]
+//!     callbacks: Arc<CosemObjectCallbackHandlers>,
+//! }
+//! ```
+//!
+//! generates the `CosemObject` impl: class id and version, the access rights
+//! tables, `get_attribute`/`set_attribute` over the annotated fields, and
+//! `invoke_method` dispatching to inherent methods with the signature
+//! `fn reset(&mut self, data: CosemData) -> Option<CosemData>`. With
+//! `specs = attribute_specs` the attribute specs come from the inherent
+//! `fn attribute_specs(&self) -> Vec<AttributeSpec>`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitInt, Result, Token};
+
+#[proc_macro_attribute]
+pub fn cosem_object(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as ObjectArgs);
+    let input = parse_macro_input!(item as DeriveInput);
+    expand(args, input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct MethodBinding {
+    id: LitInt,
+    handler: Ident,
+}
+
+impl Parse for MethodBinding {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let id = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let handler = input.parse()?;
+        Ok(MethodBinding { id, handler })
+    }
+}
+
+// class_id = <u16>[, version = <u8>][, methods(<id> = <handler>, ...)]
+// [, specs = <handler>]
+struct ObjectArgs {
+    class_id: LitInt,
+    version: Option<LitInt>,
+    methods: Vec<MethodBinding>,
+    specs: Option<Ident>,
+}
+
+impl Parse for ObjectArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut class_id = None;
+        let mut version = None;
+        let mut methods = Vec::new();
+        let mut specs = None;
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "class_id" => {
+                    input.parse::<Token![=]>()?;
+                    class_id = Some(input.parse()?);
+                }
+                "version" => {
+                    input.parse::<Token![=]>()?;
+                    version = Some(input.parse()?);
+                }
+                "specs" => {
+                    input.parse::<Token![=]>()?;
+                    specs = Some(input.parse()?);
+                }
+                "methods" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    methods.extend(Punctuated::<MethodBinding, Token![,]>::parse_terminated(
+                        &content,
+                    )?);
+                }
+                _ => return Err(Error::new(key.span(), "unknown cosem_object argument")),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        let class_id = class_id.ok_or_else(|| input.error("missing class_id"))?;
+        Ok(ObjectArgs {
+            class_id,
+            version,
+            methods,
+            specs,
+        })
+    }
+}
+
+// #[attribute(<id>, <access>)] with access one of no_access, read, write,
+// read_write.
+struct AttributeArgs {
+    id: LitInt,
+    access: Ident,
+}
+
+impl Parse for AttributeArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let id = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let access: Ident = input.parse()?;
+        if !matches!(
+            access.to_string().as_str(),
+            "no_access" | "read" | "write" | "read_write"
+        ) {
+            return Err(Error::new(
+                access.span(),
+                "expected no_access, read, write or read_write",
+            ));
+        }
+        Ok(AttributeArgs { id, access })
+    }
+}
+
+struct AttributeField {
+    field: Ident,
+    args: AttributeArgs,
+}
+
+fn expand(args: ObjectArgs, mut input: DeriveInput) -> Result<TokenStream2> {
+    let Data::Struct(data) = &mut input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "cosem_object expects a struct",
+        ));
+    };
+    let Fields::Named(fields) = &mut data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "cosem_object expects named fields",
+        ));
+    };
+
+    let mut attributes = Vec::new();
+    let mut callbacks = None;
+    for field in fields.named.iter_mut() {
+        let name = field.ident.clone().expect("named field");
+        let mut kept = Vec::new();
+        for attr in field.attrs.drain(..) {
+            if attr.path().is_ident("attribute") {
+                attributes.push(AttributeField {
+                    field: name.clone(),
+                    args: attr.parse_args()?,
+                });
+            } else if attr.path().is_ident("callbacks") {
+                callbacks = Some(name.clone());
+            } else {
+                kept.push(attr);
+            }
+        }
+        field.attrs = kept;
+    }
+
+    let krate = quote!(::dlms_cosem);
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let class_id = &args.class_id;
+    let version = args
+        .version
+        .as_ref()
+        .map(|version| quote!(#version))
+        .unwrap_or_else(|| quote!(0));
+
+    let access_rights = attributes.iter().map(|attribute| {
+        let id = &attribute.args.id;
+        let mode = match attribute.args.access.to_string().as_str() {
+            "no_access" => format_ident!("NoAccess"),
+            "read" => format_ident!("Read"),
+            "write" => format_ident!("Write"),
+            _ => format_ident!("ReadWrite"),
+        };
+        quote! {
+            #krate::cosem_object::AttributeAccessDescriptor::new(
+                #id,
+                #krate::cosem_object::AttributeAccessMode::#mode,
+            )
+        }
+    });
+    let getters = attributes.iter().map(|attribute| {
+        let (id, field) = (&attribute.args.id, &attribute.field);
+        quote!(#id => Some(::core::clone::Clone::clone(&self.#field)),)
+    });
+    let setters = attributes.iter().map(|attribute| {
+        let (id, field) = (&attribute.args.id, &attribute.field);
+        quote!(#id => self.#field = data,)
+    });
+    let method_rights = args.methods.iter().map(|method| {
+        let id = &method.id;
+        quote! {
+            #krate::cosem_object::MethodAccessDescriptor::new(
+                #id,
+                #krate::cosem_object::MethodAccessMode::Access,
+            )
+        }
+    });
+    let methods = args.methods.iter().map(|method| {
+        let (id, handler) = (&method.id, &method.handler);
+        quote!(#id => self.#handler(data),)
+    });
+    let specs = args.specs.as_ref().map(|handler| {
+        quote! {
+            fn attribute_specs(
+                &self,
+            ) -> ::std::vec::Vec<#krate::cosem_object::AttributeSpec> {
+                self.#handler()
+            }
+        }
+    });
+    let callbacks = callbacks.map(|field| {
+        quote! {
+            fn callbacks(
+                &self,
+            ) -> Option<::std::sync::Arc<#krate::cosem_object::CosemObjectCallbackHandlers>> {
+                Some(::std::sync::Arc::clone(&self.#field))
+            }
+        }
+    });
+
+    Ok(quote! {
+        #input
+
+        impl #impl_generics #krate::cosem_object::CosemObject for #ident #type_generics #where_clause {
+            fn class_id(&self) -> u16 {
+                #class_id
+            }
+
+            fn version(&self) -> u8 {
+                #version
+            }
+
+            fn attribute_access_rights(
+                &self,
+            ) -> ::std::vec::Vec<#krate::cosem_object::AttributeAccessDescriptor> {
+                ::std::vec![#(#access_rights),*]
+            }
+
+            fn method_access_rights(
+                &self,
+            ) -> ::std::vec::Vec<#krate::cosem_object::MethodAccessDescriptor> {
+                ::std::vec![#(#method_rights),*]
+            }
+
+            fn get_attribute(
+                &self,
+                attribute_id: #krate::cosem::CosemObjectAttributeId,
+            ) -> Option<#krate::types::CosemData> {
+                match attribute_id {
+                    #(#getters)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_code)]
+            fn set_attribute(
+                &mut self,
+                attribute_id: #krate::cosem::CosemObjectAttributeId,
+                data: #krate::types::CosemData,
+            ) -> Option<()> {
+                match attribute_id {
+                    #(#setters)*
+                    _ => return None,
+                }
+                Some(())
+            }
+
+            #[allow(unused_variables)]
+            fn invoke_method(
+                &mut self,
+                method_id: #krate::cosem::CosemObjectMethodId,
+                data: #krate::types::CosemData,
+            ) -> Option<#krate::types::CosemData> {
+                match method_id {
+                    #(#methods)*
+                    _ => None,
+                }
+            }
+
+            #specs
+
+            #callbacks
+        }
+    })
+}
@@ -0,0 +1,424 @@
+// Meter reader over the wrapper on TCP or HDLC on a serial line, run with
+// `cargo run --example dlms-cli --features cli -- ARGS`. Values given on the
+// command line and printed with `--json` use the JSON form of `CosemData`.
+
+use dlms_cosem::client::{Client, ClientError};
+use dlms_cosem::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
+use dlms_cosem::hdlc::HdlcAddress;
+use dlms_cosem::hdlc_transport::HdlcTransport;
+use dlms_cosem::object_model::{format_logical_name, parse_logical_name};
+use dlms_cosem::transport::Transport;
+use dlms_cosem::types::CosemData;
+use dlms_cosem::wrapper_transport::WrapperTransport;
+use dlms_cosem::xdlms::{
+    ActionRequest, ActionRequestNormal, ActionResponse, ActionResult, DataAccessResult,
+    GetDataResult, GetRequest, GetRequestNormal, GetResponse, SetRequest, SetRequestNormal,
+    SetResponse,
+};
+use std::env;
+use std::fmt::{self, Debug};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: dlms-cli (--tcp HOST:PORT | --serial DEVICE) [options] COMMAND
+
+options:
+  --client SAP          client address (default 16, the public client)
+  --server LOGICAL[/PHYSICAL]
+                        server address (default all stations)
+  --password TEXT       LLS password
+  --json                print values as JSON
+
+commands:
+  read CLASS OBIS ATTRIBUTE
+  write CLASS OBIS ATTRIBUTE VALUE
+  execute CLASS OBIS METHOD [VALUE]
+  browse [OBIS]         list the objects of the association (default the
+                        current association 0.0.40.0.0.255)
+  profile OBIS          print the buffer of a profile generic
+
+OBIS codes are written 1.0.1.8.0.255 or 1-0:1.8.0*255, values as JSON, e.g.
+'{\"long-unsigned\": 5}'. The serial line has to be configured beforehand,
+e.g. with stty.";
+
+// Logical name of the current association object.
+const CURRENT_ASSOCIATION_LN: [u8; 6] = [0, 0, 40, 0, 0, 255];
+
+struct Options {
+    tcp: Option<String>,
+    serial: Option<String>,
+    client: u16,
+    server: Option<HdlcAddress>,
+    password: Option<Vec<u8>>,
+    json: bool,
+    command: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let result = match (&options.tcp, &options.serial) {
+        (Some(address), None) => TcpStream::connect(address)
+            .map_err(|err| format!("cannot connect to {address}: {err}"))
+            .and_then(|stream| session(&options, WrapperTransport::new(stream), &mut io::stdout())),
+        (None, Some(device)) => OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(device)
+            .map_err(|err| format!("cannot open {device}: {err}"))
+            .and_then(|port| session(&options, HdlcTransport::new(port), &mut io::stdout())),
+        _ => {
+            eprintln!("either --tcp or --serial is required\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        tcp: None,
+        serial: None,
+        client: 16,
+        server: None,
+        password: None,
+        json: false,
+        command: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "--tcp" => options.tcp = Some(value("--tcp")?),
+            "--serial" => options.serial = Some(value("--serial")?),
+            "--client" => options.client = number(&value("--client")?)?,
+            "--server" => {
+                let server = value("--server")?;
+                let (logical, physical) = server.split_once('/').unwrap_or((&server, "0"));
+                options.server = Some(HdlcAddress::new(number(logical)?, number(physical)?));
+            }
+            "--password" => options.password = Some(value("--password")?.into_bytes()),
+            "--json" => options.json = true,
+            "--help" | "-h" => return Err(String::from("dlms-cli: DLMS/COSEM meter reader")),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => {
+                options.command.push(arg);
+                options.command.extend(args);
+                break;
+            }
+        }
+    }
+    if options.command.is_empty() {
+        return Err(String::from("no command given"));
+    }
+    Ok(options)
+}
+
+fn number<N: std::str::FromStr>(text: &str) -> Result<N, String> {
+    text.parse().map_err(|_| format!("invalid number {text}"))
+}
+
+fn obis(text: &str) -> Result<[u8; 6], String> {
+    let dotted = text.replace(['-', ':', '*'], ".");
+    parse_logical_name(&dotted).map_err(|_| format!("invalid OBIS code {text}"))
+}
+
+fn value(text: &str) -> Result<CosemData, String> {
+    serde_json::from_str(text).map_err(|err| format!("invalid value {text}: {err}"))
+}
+
+fn failure<E: Debug>(err: ClientError<E>) -> String {
+    format!("request failed: {err:?}")
+}
+
+// Associates, runs the command and releases the association again.
+fn session<T: Transport>(
+    options: &Options,
+    transport: T,
+    out: &mut impl Write,
+) -> Result<(), String>
+where
+    T::Error: Debug,
+{
+    let mut client = Client::new(options.client, transport, options.password.clone(), None);
+    if let Some(server) = options.server {
+        client.set_server_address(server);
+    }
+    client
+        .associate()
+        .map_err(|err| format!("association failed: {err:?}"))?;
+    let result = run(&mut client, options, out);
+    // A failed command is reported rather than the release that follows it.
+    let released = client.release().map_err(failure);
+    result.and(released)
+}
+
+fn run<T: Transport>(
+    client: &mut Client<T>,
+    options: &Options,
+    out: &mut impl Write,
+) -> Result<(), String>
+where
+    T::Error: Debug,
+{
+    let json = options.json;
+    let args: Vec<&str> = options.command.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["read", class_id, logical_name, attribute_id] => {
+            let descriptor = CosemAttributeDescriptor {
+                class_id: number(class_id)?,
+                instance_id: obis(logical_name)?,
+                attribute_id: number(attribute_id)?,
+            };
+            print(out, json, &read(client, descriptor)?)?;
+        }
+        ["write", class_id, logical_name, attribute_id, data] => {
+            let request = SetRequest::Normal(SetRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: number(class_id)?,
+                    instance_id: obis(logical_name)?,
+                    attribute_id: number(attribute_id)?,
+                },
+                access_selection: None,
+                value: value(data)?,
+            });
+            let result = match client.send_set_request(request).map_err(failure)? {
+                SetResponse::Normal(response) => response.result,
+                SetResponse::LastDatablock(response) => response.result,
+                other => return Err(format!("unexpected response {other:?}")),
+            };
+            if result != DataAccessResult::Success {
+                return Err(format!("write refused: {result:?}"));
+            }
+        }
+        ["execute", class_id, logical_name, method_id, rest @ ..] if rest.len() <= 1 => {
+            let parameters = rest.first().map(|data| value(data)).transpose()?;
+            let request = ActionRequest::Normal(ActionRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_method_descriptor: CosemMethodDescriptor {
+                    class_id: number(class_id)?,
+                    instance_id: obis(logical_name)?,
+                    method_id: number(method_id)?,
+                },
+                method_invocation_parameters: parameters,
+            });
+            let ActionResponse::Normal(response) =
+                client.send_action_request(request).map_err(failure)?
+            else {
+                return Err(String::from("unexpected response"));
+            };
+            let response = response.single_response;
+            if response.result != ActionResult::Success {
+                return Err(format!("method refused: {:?}", response.result));
+            }
+            if let Some(data) = response.return_data() {
+                print(out, json, data)?;
+            }
+        }
+        ["browse", rest @ ..] if rest.len() <= 1 => {
+            let instance_id = match rest.first() {
+                Some(logical_name) => obis(logical_name)?,
+                None => CURRENT_ASSOCIATION_LN,
+            };
+            let descriptor = CosemAttributeDescriptor {
+                class_id: 15,
+                instance_id,
+                attribute_id: 2,
+            };
+            let CosemData::Array(entries) = read(client, descriptor)? else {
+                return Err(String::from("object list is not an array"));
+            };
+            for entry in entries {
+                let CosemData::Structure(fields) = &entry else {
+                    continue;
+                };
+                if let [CosemData::LongUnsigned(class_id), CosemData::Unsigned(version), CosemData::OctetString(logical_name), ..] =
+                    fields.as_slice()
+                {
+                    let logical_name = <[u8; 6]>::try_from(logical_name.as_slice())
+                        .map(|logical_name| format_logical_name(&logical_name))
+                        .unwrap_or_default();
+                    line(out, format_args!("{class_id}\t{version}\t{logical_name}"))?;
+                }
+            }
+        }
+        ["profile", logical_name] => {
+            let descriptor = |attribute_id| CosemAttributeDescriptor {
+                class_id: 7,
+                instance_id: [0; 6],
+                attribute_id,
+            };
+            let instance_id = obis(logical_name)?;
+            let capture_objects = read(
+                client,
+                CosemAttributeDescriptor {
+                    instance_id,
+                    ..descriptor(3)
+                },
+            )?;
+            let buffer = read(
+                client,
+                CosemAttributeDescriptor {
+                    instance_id,
+                    ..descriptor(2)
+                },
+            )?;
+            if let CosemData::Array(columns) = capture_objects {
+                let header: Vec<String> = columns.iter().map(column_name).collect();
+                line(out, format_args!("{}", header.join("\t")))?;
+            }
+            let CosemData::Array(rows) = buffer else {
+                return Err(String::from("buffer is not an array"));
+            };
+            for row in rows {
+                match row {
+                    CosemData::Structure(values) if !json => {
+                        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+                        line(out, format_args!("{}", values.join("\t")))?;
+                    }
+                    row => print(out, json, &row)?,
+                }
+            }
+        }
+        _ => return Err(format!("invalid command\n\n{USAGE}")),
+    }
+    Ok(())
+}
+
+fn line(out: &mut impl Write, args: fmt::Arguments) -> Result<(), String> {
+    writeln!(out, "{args}").map_err(|err| format!("cannot write output: {err}"))
+}
+
+fn print(out: &mut impl Write, json: bool, data: &CosemData) -> Result<(), String> {
+    if json {
+        line(out, format_args!("{}", data.to_json()))
+    } else {
+        line(out, format_args!("{data}"))
+    }
+}
+
+fn read<T: Transport>(
+    client: &mut Client<T>,
+    descriptor: CosemAttributeDescriptor,
+) -> Result<CosemData, String>
+where
+    T::Error: Debug,
+{
+    let request = GetRequest::Normal(GetRequestNormal {
+        invoke_id_and_priority: 0xC1,
+        cosem_attribute_descriptor: descriptor,
+        access_selection: None,
+    });
+    match client.send_get_request(request).map_err(failure)? {
+        GetResponse::Normal(response) => match response.result {
+            GetDataResult::Data(data) => Ok(data),
+            GetDataResult::DataAccessResult(result) => Err(format!("read refused: {result:?}")),
+        },
+        other => Err(format!("unexpected response {other:?}")),
+    }
+}
+
+// Capture object definitions as class/OBIS/attribute.
+fn column_name(definition: &CosemData) -> String {
+    match definition {
+        CosemData::Structure(fields) => match fields.as_slice() {
+            [CosemData::LongUnsigned(class_id), logical_name, CosemData::Integer(attribute_id), ..] =>
+            {
+                format!("{class_id}/{logical_name}/{attribute_id}")
+            }
+            _ => definition.to_string(),
+        },
+        _ => definition.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlms_cosem::cosem_object::CosemObject;
+    use dlms_cosem::register::Register;
+    use dlms_cosem::server::{ObjectVisibility, Server};
+    use dlms_cosem::server_listener::ServerListener;
+    use dlms_cosem::testing::DetachedTransport;
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+
+    fn start_meter() -> SocketAddr {
+        let mut server = Server::new(1, DetachedTransport, None, None);
+        let mut register = Register::new();
+        register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
+        server.register_object_with_visibility(
+            [1, 0, 1, 8, 0, 255],
+            Box::new(register),
+            ObjectVisibility::Public,
+        );
+        let mut listener = ServerListener::new(TcpListener::bind("127.0.0.1:0").unwrap(), server);
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || listener.run());
+        addr
+    }
+
+    fn cli(addr: SocketAddr, args: &[&str]) -> Result<String, String> {
+        let addr_text = addr.to_string();
+        let args = ["--tcp", &addr_text, "--client", "1"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(String::from);
+        let options = parse_options(args)?;
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut out = Vec::new();
+        session(&options, WrapperTransport::new(stream), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn reads_writes_and_browses_a_meter_over_tcp() {
+        let addr = start_meter();
+
+        assert_eq!(
+            cli(addr, &["read", "3", "1-0:1.8.0*255", "2"]).unwrap(),
+            "10\n"
+        );
+        assert_eq!(
+            cli(addr, &["--json", "read", "3", "1.0.1.8.0.255", "2"]).unwrap(),
+            "{\"unsigned\":10}\n"
+        );
+        cli(
+            addr,
+            &["write", "3", "1.0.1.8.0.255", "2", "{\"unsigned\": 42}"],
+        )
+        .unwrap();
+        assert_eq!(
+            cli(addr, &["read", "3", "1.0.1.8.0.255", "2"]).unwrap(),
+            "42\n"
+        );
+
+        let objects = cli(addr, &["browse", "0.0.40.0.1.255"]).unwrap();
+        assert!(
+            objects.lines().any(|line| line == "3\t0\t1.0.1.8.0.255"),
+            "{objects}"
+        );
+
+        let error = cli(
+            addr,
+            &["write", "3", "1.0.1.8.0.255", "2", "{\"unsigned\": 1.5}"],
+        )
+        .unwrap_err();
+        assert!(error.starts_with("invalid value"), "{error}");
+        assert!(parse_options(["--tcp", "localhost:4059"].map(String::from).into_iter()).is_err());
+    }
+}
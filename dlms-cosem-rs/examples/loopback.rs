@@ -0,0 +1,83 @@
+// A meter and its reader in one process, with nothing to set up: the client
+// hands its frames straight to the server over a loopback transport. Run with
+// `cargo run --example loopback --features std`.
+
+use dlms_cosem::client::Client;
+use dlms_cosem::cosem::CosemAttributeDescriptor;
+use dlms_cosem::cosem_object::{AttributeAccessMode, CosemObject};
+use dlms_cosem::data::Data;
+use dlms_cosem::register::Register;
+use dlms_cosem::server::ServerBuilder;
+use dlms_cosem::testing::{DetachedTransport, LoopbackTransport};
+use dlms_cosem::types::CosemData;
+use dlms_cosem::xdlms::{
+    GetDataResult, GetRequest, GetRequestNormal, GetResponse, SetRequest, SetRequestNormal,
+    SetResponse,
+};
+
+const ENERGY_LN: [u8; 6] = [1, 0, 1, 8, 0, 255];
+const NOTE_LN: [u8; 6] = [0, 0, 96, 1, 0, 255];
+
+fn main() {
+    let mut energy = Register::new();
+    energy
+        .set_attribute(2, CosemData::DoubleLongUnsigned(12_345))
+        .unwrap();
+    let meter = ServerBuilder::new(1, DetachedTransport)
+        .object(ENERGY_LN, Box::new(energy))
+        .object(
+            NOTE_LN,
+            Box::new(Data::with_access(
+                CosemData::OctetString(Vec::new()),
+                AttributeAccessMode::ReadWrite,
+            )),
+        )
+        .build();
+
+    let mut client = Client::new(1, LoopbackTransport::new(meter), None, None);
+    client.associate().expect("association failed");
+
+    let note = CosemAttributeDescriptor {
+        class_id: 1,
+        instance_id: NOTE_LN,
+        attribute_id: 2,
+    };
+    let response = client
+        .send_set_request(SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: note.clone(),
+            access_selection: None,
+            value: CosemData::OctetString(b"read over loopback".to_vec()),
+        }))
+        .expect("set failed");
+    if let SetResponse::Normal(response) = response {
+        println!("write {:?}: {:?}", NOTE_LN, response.result);
+    }
+
+    let energy = CosemAttributeDescriptor {
+        class_id: 3,
+        instance_id: ENERGY_LN,
+        attribute_id: 2,
+    };
+    for descriptor in [energy, note] {
+        let response = client
+            .send_get_request(GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: descriptor.clone(),
+                access_selection: None,
+            }))
+            .expect("get failed");
+        if let GetResponse::Normal(response) = response {
+            match response.result {
+                GetDataResult::Data(value) => {
+                    println!("read {:?}: {:?}", descriptor.instance_id, value)
+                }
+                GetDataResult::DataAccessResult(result) => {
+                    println!("read {:?}: {:?}", descriptor.instance_id, result)
+                }
+            }
+        }
+    }
+
+    client.release().expect("release failed");
+}
@@ -1,11 +1,14 @@
+use crate::axdr::ParseMode;
 use crate::error::DlmsError;
+use crate::xdlms::{ReleaseRequestInformation, ReleaseResponseInformation};
 use nom::bytes::complete::{tag, take};
 use nom::error::ErrorKind;
 use nom::number::complete::u8 as parse_u8;
 use nom::{Err, IResult, Parser};
 use std::vec::Vec;
 
-fn parse_length(input: &[u8]) -> IResult<&[u8], usize> {
+// Strict mode only accepts the shortest encoding of a length.
+fn parse_length(input: &[u8], mode: ParseMode) -> IResult<&[u8], usize> {
     let (input, first_byte) = parse_u8(input)?;
     if first_byte & 0x80 == 0 {
         Ok((input, first_byte as usize))
@@ -17,12 +20,20 @@ fn parse_length(input: &[u8]) -> IResult<&[u8], usize> {
                 ErrorKind::LengthValue,
             )));
         }
-        let (input, len_bytes) = take(num_bytes)(input)?;
+        let (rest, len_bytes) = take(num_bytes)(input)?;
+        if mode == ParseMode::Strict
+            && (len_bytes[0] == 0 || (num_bytes == 1 && len_bytes[0] < 0x80))
+        {
+            return Err(Err::Error(nom::error::Error::new(
+                input,
+                ErrorKind::LengthValue,
+            )));
+        }
         let mut length = 0usize;
         for &byte in len_bytes {
             length = (length << 8) | byte as usize;
         }
-        Ok((input, length))
+        Ok((rest, length))
     }
 }
 
@@ -42,27 +53,109 @@ fn encode_length(buf: &mut Vec<u8>, length: usize) {
     }
 }
 
-fn parse_optional(input: &[u8], tag_byte: u8) -> IResult<&[u8], Option<&[u8]>> {
-    if let Some(&first) = input.first() {
-        if first == tag_byte {
-            let (input, _) = tag(&[tag_byte][..]).parse(input)?;
-            let (input, length) = parse_length(input)?;
-            let (input, value) = take(length)(input)?;
-            Ok((input, Some(value)))
-        } else {
-            Ok((input, None))
+// Splits the content of an APDU into its tagged components, given the tags
+// it may hold in order. Lenient mode skips unknown components and accepts any
+// order; strict mode rejects both.
+fn parse_components<'a>(
+    mut content: &'a [u8],
+    known: &[u8],
+    mode: ParseMode,
+) -> IResult<&'a [u8], Vec<(u8, &'a [u8])>> {
+    let mut components = Vec::new();
+    let mut next_known = 0;
+    while !content.is_empty() {
+        let (input, tag_byte) = parse_u8(content)?;
+        let (input, length) = parse_length(input, mode)?;
+        let (input, value) = take(length)(input)?;
+        match known.iter().position(|&known| known == tag_byte) {
+            Some(position) if mode == ParseMode::Lenient || position >= next_known => {
+                next_known = position + 1;
+                components.push((tag_byte, value));
+            }
+            None if mode == ParseMode::Lenient => {}
+            _ => return Err(Err::Error(nom::error::Error::new(content, ErrorKind::Tag))),
         }
-    } else {
-        Ok((input, None))
+        content = input;
+    }
+    Ok((content, components))
+}
+
+fn component<'a>(components: &[(u8, &'a [u8])], tag_byte: u8) -> Option<&'a [u8]> {
+    components
+        .iter()
+        .find(|(tag, _)| *tag == tag_byte)
+        .map(|(_, value)| *value)
+}
+
+fn required_component<'a>(
+    input: &'a [u8],
+    components: &[(u8, &'a [u8])],
+    tag_byte: u8,
+) -> Result<&'a [u8], Err<nom::error::Error<&'a [u8]>>> {
+    component(components, tag_byte)
+        .ok_or_else(|| Err::Error(nom::error::Error::new(input, ErrorKind::Tag)))
+}
+
+// Single byte value of a component such as a result or a reason. Lenient mode
+// reads the first byte of a longer value.
+fn component_byte(value: &[u8], mode: ParseMode) -> IResult<&[u8], u8> {
+    match (value, mode) {
+        ([byte], _) | ([byte, ..], ParseMode::Lenient) => Ok((&value[1..], *byte)),
+        _ => Err(Err::Error(nom::error::Error::new(
+            value,
+            ErrorKind::LengthValue,
+        ))),
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+// Splits an APDU into its content and the bytes following it, which strict
+// mode rejects.
+fn parse_apdu(bytes: &[u8], apdu_tag: u8, mode: ParseMode) -> IResult<&[u8], &[u8]> {
+    let (input, _) = tag(&[apdu_tag][..]).parse(bytes)?;
+    let (input, length) = parse_length(input, mode)?;
+    let (rest, content) = take(length)(input)?;
+    if mode == ParseMode::Strict && !rest.is_empty() {
+        return Err(Err::Error(nom::error::Error::new(rest, ErrorKind::Eof)));
+    }
+    Ok((rest, content))
+}
+
+fn encode_optional(buf: &mut Vec<u8>, tag_byte: u8, value: &Option<Vec<u8>>) {
+    if let Some(value) = value {
+        buf.push(tag_byte);
+        encode_length(buf, value.len());
+        buf.extend_from_slice(value);
+    }
+}
+
+// AP titles are carried as the value of an octet-string (the system title).
+pub fn ap_title(system_title: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x04];
+    encode_length(&mut bytes, system_title.len());
+    bytes.extend_from_slice(system_title);
+    bytes
+}
+
+// The system title in an AP title, `None` if it is not an octet-string.
+pub fn system_title(ap_title: &[u8]) -> Option<&[u8]> {
+    let (content, length) =
+        parse_length(ap_title.strip_prefix(&[0x04])?, ParseMode::Strict).ok()?;
+    content.get(..length).filter(|_| content.len() == length)
+}
+
+// The optional fields hold the encoded contents of their tagged component.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct AarqApdu {
+    pub protocol_version: Option<Vec<u8>>,
     pub application_context_name: Vec<u8>,
+    pub called_ap_title: Option<Vec<u8>>,
+    pub called_ae_qualifier: Option<Vec<u8>>,
+    pub calling_ap_title: Option<Vec<u8>>,
+    pub calling_ae_qualifier: Option<Vec<u8>>,
     pub sender_acse_requirements: u8,
     pub mechanism_name: Option<Vec<u8>>,
     pub calling_authentication_value: Option<Vec<u8>>,
+    pub implementation_information: Option<Vec<u8>>,
     pub user_information: Vec<u8>,
 }
 
@@ -72,9 +165,14 @@ impl AarqApdu {
         bytes.push(0x60);
 
         let mut content = Vec::new();
+        encode_optional(&mut content, 0x80, &self.protocol_version);
         content.push(0xA1);
         encode_length(&mut content, self.application_context_name.len());
         content.extend_from_slice(&self.application_context_name);
+        encode_optional(&mut content, 0xA2, &self.called_ap_title);
+        encode_optional(&mut content, 0xA3, &self.called_ae_qualifier);
+        encode_optional(&mut content, 0xA6, &self.calling_ap_title);
+        encode_optional(&mut content, 0xA7, &self.calling_ae_qualifier);
         content.push(0x8A);
         encode_length(&mut content, 1);
         content.push(self.sender_acse_requirements);
@@ -91,6 +189,8 @@ impl AarqApdu {
             content.extend_from_slice(calling_authentication_value);
         }
 
+        encode_optional(&mut content, 0x9D, &self.implementation_information);
+
         content.push(0xBE);
         encode_length(&mut content, self.user_information.len());
         content.extend_from_slice(&self.user_information);
@@ -101,47 +201,111 @@ impl AarqApdu {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Self> {
-        let (i, _aarq_tag) = tag(&[0x60u8][..]).parse(bytes)?;
-        let (i, length) = parse_length(i)?;
-        let (i, content) = take(length)(i)?;
-        let (content, _acn_tag) = tag(&[0xA1u8][..]).parse(content)?;
-        let (content, acn_len) = parse_length(content)?;
-        let (content, acn) = take(acn_len)(content)?;
-        let (content, _sar_tag) = tag(&[0x8Au8][..]).parse(content)?;
-        let (content, sar_len) = parse_length(content)?;
-        let (content, sar) = take(sar_len)(content)?;
-        let (content, mn) = parse_optional(content, 0x8B)?;
-        let (content, cav) = parse_optional(content, 0xAC)?;
-        let (content, _ui_tag) = tag(&[0xBEu8][..]).parse(content)?;
-        let (content, ui_len) = parse_length(content)?;
-        let (_content, ui) = take(ui_len)(content)?;
-
-        let mut aarq = AarqApdu {
-            application_context_name: acn.to_vec(),
-            sender_acse_requirements: sar[0],
-            mechanism_name: None,
-            calling_authentication_value: None,
-            user_information: ui.to_vec(),
-        };
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> IResult<&[u8], Self> {
+        let (i, content) = parse_apdu(bytes, 0x60, mode)?;
+        let (_, components) = parse_components(
+            content,
+            &[
+                0x80, 0xA1, 0xA2, 0xA3, 0xA6, 0xA7, 0x8A, 0x8B, 0xAC, 0x9D, 0xBE,
+            ],
+            mode,
+        )?;
+        let acn = required_component(content, &components, 0xA1)?;
+        let sar = required_component(content, &components, 0x8A)?;
+        let (_, sender_acse_requirements) = component_byte(sar, mode)?;
+        let ui = required_component(content, &components, 0xBE)?;
+        let optional = |tag_byte| component(&components, tag_byte).map(|value| value.to_vec());
 
-        if let Some(mn_val) = mn {
-            aarq.mechanism_name = Some(mn_val.to_vec());
+        Ok((
+            i,
+            AarqApdu {
+                protocol_version: optional(0x80),
+                application_context_name: acn.to_vec(),
+                called_ap_title: optional(0xA2),
+                called_ae_qualifier: optional(0xA3),
+                calling_ap_title: optional(0xA6),
+                calling_ae_qualifier: optional(0xA7),
+                sender_acse_requirements,
+                mechanism_name: optional(0x8B),
+                calling_authentication_value: optional(0xAC),
+                implementation_information: optional(0x9D),
+                user_information: ui.to_vec(),
+            },
+        ))
+    }
+}
+
+// Associate-source-diagnostic ::= CHOICE {
+//     acse-service-user [1] INTEGER, acse-service-provider [2] INTEGER }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssociateSourceDiagnostic {
+    ServiceUser(u8),
+    ServiceProvider(u8),
+}
+
+impl AssociateSourceDiagnostic {
+    pub const NULL: Self = Self::ServiceUser(0);
+    pub const NO_REASON_GIVEN: Self = Self::ServiceUser(1);
+    pub const APPLICATION_CONTEXT_NAME_NOT_SUPPORTED: Self = Self::ServiceUser(2);
+    pub const AUTHENTICATION_MECHANISM_NAME_NOT_RECOGNISED: Self = Self::ServiceUser(11);
+    pub const AUTHENTICATION_MECHANISM_NAME_REQUIRED: Self = Self::ServiceUser(12);
+    pub const AUTHENTICATION_FAILURE: Self = Self::ServiceUser(13);
+    pub const AUTHENTICATION_REQUIRED: Self = Self::ServiceUser(14);
+    pub const NO_COMMON_ACSE_VERSION: Self = Self::ServiceProvider(2);
+
+    pub fn value(self) -> u8 {
+        match self {
+            Self::ServiceUser(value) | Self::ServiceProvider(value) => value,
         }
+    }
+
+    fn encode(self, buf: &mut Vec<u8>) {
+        let choice = match self {
+            Self::ServiceUser(_) => 0xA1,
+            Self::ServiceProvider(_) => 0xA2,
+        };
+        buf.extend_from_slice(&[choice, 0x03, 0x02, 0x01, self.value()]);
+    }
 
-        if let Some(cav_val) = cav {
-            aarq.calling_authentication_value = Some(cav_val.to_vec());
+    // Lenient mode also takes a bare octet, read as a service-user value.
+    fn parse(value: &[u8], mode: ParseMode) -> IResult<&[u8], Self> {
+        if let ([byte], ParseMode::Lenient) = (value, mode) {
+            return Ok((&value[1..], Self::ServiceUser(*byte)));
         }
+        let (input, choice) = parse_u8(value)?;
+        let (input, length) = parse_length(input, mode)?;
+        let (rest, integer) = take(length)(input)?;
+        let (integer, _) = tag(&[0x02][..]).parse(integer)?;
+        let (integer, integer_length) = parse_length(integer, mode)?;
+        let (_, integer) = take(integer_length)(integer)?;
+        let (_, diagnostic) = component_byte(integer, mode)?;
+        match choice {
+            0xA1 => Ok((rest, Self::ServiceUser(diagnostic))),
+            0xA2 => Ok((rest, Self::ServiceProvider(diagnostic))),
+            _ => Err(Err::Error(nom::error::Error::new(value, ErrorKind::Tag))),
+        }
+    }
+}
 
-        Ok((i, aarq))
+impl Default for AssociateSourceDiagnostic {
+    fn default() -> Self {
+        Self::NULL
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct AareApdu {
+    pub protocol_version: Option<Vec<u8>>,
     pub application_context_name: Vec<u8>,
     pub result: u8,
-    pub result_source_diagnostic: u8,
+    pub result_source_diagnostic: AssociateSourceDiagnostic,
+    pub responding_ap_title: Option<Vec<u8>>,
+    pub responding_ae_qualifier: Option<Vec<u8>>,
     pub responding_authentication_value: Option<Vec<u8>>,
+    pub implementation_information: Option<Vec<u8>>,
     pub user_information: Vec<u8>,
 }
 
@@ -151,6 +315,7 @@ impl AareApdu {
         bytes.push(0x61);
 
         let mut content = Vec::new();
+        encode_optional(&mut content, 0x80, &self.protocol_version);
         content.push(0xA1);
         encode_length(&mut content, self.application_context_name.len());
         content.extend_from_slice(&self.application_context_name);
@@ -158,8 +323,10 @@ impl AareApdu {
         encode_length(&mut content, 1);
         content.push(self.result);
         content.push(0xA3);
-        encode_length(&mut content, 1);
-        content.push(self.result_source_diagnostic);
+        encode_length(&mut content, 5);
+        self.result_source_diagnostic.encode(&mut content);
+        encode_optional(&mut content, 0xA4, &self.responding_ap_title);
+        encode_optional(&mut content, 0xA5, &self.responding_ae_qualifier);
 
         if let Some(responding_authentication_value) = &self.responding_authentication_value {
             content.push(0xAC);
@@ -167,6 +334,8 @@ impl AareApdu {
             content.extend_from_slice(responding_authentication_value);
         }
 
+        encode_optional(&mut content, 0x9D, &self.implementation_information);
+
         content.push(0xBE);
         encode_length(&mut content, self.user_information.len());
         content.extend_from_slice(&self.user_information);
@@ -177,43 +346,46 @@ impl AareApdu {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Self> {
-        let (i, _aare_tag) = tag(&[0x61u8][..]).parse(bytes)?;
-        let (i, length) = parse_length(i)?;
-        let (i, content) = take(length)(i)?;
-        let (content, _acn_tag) = tag(&[0xA1u8][..]).parse(content)?;
-        let (content, acn_len) = parse_length(content)?;
-        let (content, acn) = take(acn_len)(content)?;
-        let (content, _res_tag) = tag(&[0xA2u8][..]).parse(content)?;
-        let (content, res_len) = parse_length(content)?;
-        let (content, res) = take(res_len)(content)?;
-        let (content, _rsd_tag) = tag(&[0xA3u8][..]).parse(content)?;
-        let (content, rsd_len) = parse_length(content)?;
-        let (content, rsd) = take(rsd_len)(content)?;
-        let (content, rav) = parse_optional(content, 0xAC)?;
-        let (content, _ui_tag) = tag(&[0xBEu8][..]).parse(content)?;
-        let (content, ui_len) = parse_length(content)?;
-        let (_content, ui) = take(ui_len)(content)?;
-
-        let mut aare = AareApdu {
-            application_context_name: acn.to_vec(),
-            result: res[0],
-            result_source_diagnostic: rsd[0],
-            responding_authentication_value: None,
-            user_information: ui.to_vec(),
-        };
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
 
-        if let Some(rav_val) = rav {
-            aare.responding_authentication_value = Some(rav_val.to_vec());
-        }
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> IResult<&[u8], Self> {
+        let (i, content) = parse_apdu(bytes, 0x61, mode)?;
+        let (_, components) = parse_components(
+            content,
+            &[0x80, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xAC, 0x9D, 0xBE],
+            mode,
+        )?;
+        let acn = required_component(content, &components, 0xA1)?;
+        let (_, result) = component_byte(required_component(content, &components, 0xA2)?, mode)?;
+        let (_, result_source_diagnostic) = AssociateSourceDiagnostic::parse(
+            required_component(content, &components, 0xA3)?,
+            mode,
+        )?;
+        let ui = required_component(content, &components, 0xBE)?;
+        let optional = |tag_byte| component(&components, tag_byte).map(|value| value.to_vec());
 
-        Ok((i, aare))
+        Ok((
+            i,
+            AareApdu {
+                protocol_version: optional(0x80),
+                application_context_name: acn.to_vec(),
+                result,
+                result_source_diagnostic,
+                responding_ap_title: optional(0xA4),
+                responding_ae_qualifier: optional(0xA5),
+                responding_authentication_value: optional(0xAC),
+                implementation_information: optional(0x9D),
+                user_information: ui.to_vec(),
+            },
+        ))
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ArlrqApdu {
     pub reason: Option<u8>,
-    pub user_information: Option<Vec<u8>>,
+    pub user_information: Option<ReleaseRequestInformation>,
 }
 
 impl ArlrqApdu {
@@ -230,9 +402,10 @@ impl ArlrqApdu {
         }
 
         if let Some(user_information) = &self.user_information {
+            let user_information = user_information.to_user_information()?;
             content.push(0xBE);
             encode_length(&mut content, user_information.len());
-            content.extend_from_slice(user_information);
+            content.extend_from_slice(&user_information);
         }
 
         encode_length(&mut bytes, content.len());
@@ -241,22 +414,22 @@ impl ArlrqApdu {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Self> {
-        let (i, _arlrq_tag) = tag(&[0x62u8][..]).parse(bytes)?;
-        let (i, length) = parse_length(i)?;
-        let (i, content) = take(length)(i)?;
-        let (content, reason) = parse_optional(content, 0x80)?;
-        let (_content, user_information) = parse_optional(content, 0xBE)?;
-
-        let reason = match reason {
-            Some(bytes) => {
-                if bytes.len() != 1 {
-                    return Err(Err::Error(nom::error::Error::new(
-                        bytes,
-                        ErrorKind::LengthValue,
-                    )));
-                }
-                Some(bytes[0])
-            }
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> IResult<&[u8], Self> {
+        let (i, content) = parse_apdu(bytes, 0x62, mode)?;
+        let (_, components) = parse_components(content, &[0x80, 0xBE], mode)?;
+        let reason = match component(&components, 0x80) {
+            Some(value) => Some(component_byte(value, mode)?.1),
+            None => None,
+        };
+
+        let user_information = match component(&components, 0xBE) {
+            Some(value) => Some(
+                ReleaseRequestInformation::from_user_information_with(value, mode)
+                    .map_err(|_| Err::Error(nom::error::Error::new(value, ErrorKind::Verify)))?,
+            ),
             None => None,
         };
 
@@ -264,16 +437,16 @@ impl ArlrqApdu {
             i,
             ArlrqApdu {
                 reason,
-                user_information: user_information.map(|ui| ui.to_vec()),
+                user_information,
             },
         ))
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ArlreApdu {
     pub reason: Option<u8>,
-    pub user_information: Option<Vec<u8>>,
+    pub user_information: Option<ReleaseResponseInformation>,
 }
 
 impl ArlreApdu {
@@ -290,9 +463,10 @@ impl ArlreApdu {
         }
 
         if let Some(user_information) = &self.user_information {
+            let user_information = user_information.to_user_information()?;
             content.push(0xBE);
             encode_length(&mut content, user_information.len());
-            content.extend_from_slice(user_information);
+            content.extend_from_slice(&user_information);
         }
 
         encode_length(&mut bytes, content.len());
@@ -301,22 +475,22 @@ impl ArlreApdu {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> IResult<&[u8], Self> {
-        let (i, _arlre_tag) = tag(&[0x63u8][..]).parse(bytes)?;
-        let (i, length) = parse_length(i)?;
-        let (i, content) = take(length)(i)?;
-        let (content, reason) = parse_optional(content, 0x80)?;
-        let (_content, user_information) = parse_optional(content, 0xBE)?;
-
-        let reason = match reason {
-            Some(bytes) => {
-                if bytes.len() != 1 {
-                    return Err(Err::Error(nom::error::Error::new(
-                        bytes,
-                        ErrorKind::LengthValue,
-                    )));
-                }
-                Some(bytes[0])
-            }
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> IResult<&[u8], Self> {
+        let (i, content) = parse_apdu(bytes, 0x63, mode)?;
+        let (_, components) = parse_components(content, &[0x80, 0xBE], mode)?;
+        let reason = match component(&components, 0x80) {
+            Some(value) => Some(component_byte(value, mode)?.1),
+            None => None,
+        };
+
+        let user_information = match component(&components, 0xBE) {
+            Some(value) => Some(
+                ReleaseResponseInformation::from_user_information_with(value, mode)
+                    .map_err(|_| Err::Error(nom::error::Error::new(value, ErrorKind::Verify)))?,
+            ),
             None => None,
         };
 
@@ -324,7 +498,7 @@ impl ArlreApdu {
             i,
             ArlreApdu {
                 reason,
-                user_information: user_information.map(|ui| ui.to_vec()),
+                user_information,
             },
         ))
     }
@@ -334,6 +508,7 @@ impl ArlreApdu {
 mod tests {
     extern crate std;
     use super::*;
+    use crate::xdlms::{AssociationParameters, Conformance};
 
     #[test]
     fn test_aarq_apdu_serialization_deserialization() {
@@ -343,6 +518,7 @@ mod tests {
             mechanism_name: None,
             calling_authentication_value: None,
             user_information: b"user_info".to_vec(),
+            ..Default::default()
         };
 
         let bytes = aarq.to_bytes().unwrap();
@@ -359,6 +535,7 @@ mod tests {
             mechanism_name: Some(b"auth".to_vec()),
             calling_authentication_value: Some(b"pass".to_vec()),
             user_information: b"user_info".to_vec(),
+            ..Default::default()
         };
 
         let bytes = aarq.to_bytes().unwrap();
@@ -378,6 +555,7 @@ mod tests {
             mechanism_name: Some(mechanism_name.clone()),
             calling_authentication_value: Some(calling_authentication_value.clone()),
             user_information: b"user_info".to_vec(),
+            ..Default::default()
         };
 
         let bytes = aarq.to_bytes().unwrap();
@@ -390,14 +568,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_aarq_apdu_with_titles_roundtrip() {
+        let aarq = AarqApdu {
+            protocol_version: Some(vec![0x07, 0x80]),
+            application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
+            called_ap_title: Some(ap_title(b"SERVER01")),
+            calling_ap_title: Some(ap_title(b"CLIENT01")),
+            calling_ae_qualifier: Some(vec![0x04, 0x01, 0x00]),
+            sender_acse_requirements: 0x80,
+            mechanism_name: Some(b"HLS".to_vec()),
+            calling_authentication_value: Some(b"challenge".to_vec()),
+            implementation_information: Some(b"dlms-cosem-rs".to_vec()),
+            user_information: b"user_info".to_vec(),
+            ..Default::default()
+        };
+
+        let bytes = aarq.to_bytes().unwrap();
+        assert_eq!(&bytes[2..6], &[0x80, 0x02, 0x07, 0x80]);
+        let calling = bytes.windows(4).position(|w| w == [0xA6, 0x0A, 0x04, 0x08]);
+        assert!(calling.is_some());
+        assert_eq!(AarqApdu::from_bytes(&bytes).unwrap().1, aarq);
+    }
+
     #[test]
     fn test_aare_apdu_serialization_deserialization() {
         let aare = AareApdu {
             application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
             result: 0,
-            result_source_diagnostic: 0,
+            result_source_diagnostic: AssociateSourceDiagnostic::NULL,
             responding_authentication_value: None,
             user_information: b"user_info".to_vec(),
+            ..Default::default()
         };
 
         let bytes = aare.to_bytes().unwrap();
@@ -410,15 +612,75 @@ mod tests {
         let aare = AareApdu {
             application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
             result: 0,
-            result_source_diagnostic: 0,
+            result_source_diagnostic: AssociateSourceDiagnostic::NULL,
             responding_authentication_value: Some(b"pass".to_vec()),
             user_information: b"user_info".to_vec(),
+            ..Default::default()
         };
 
         let bytes = aare.to_bytes().unwrap();
         assert!(!bytes.is_empty());
     }
 
+    #[test]
+    fn test_aare_apdu_with_responding_title_roundtrip() {
+        let aare = AareApdu {
+            application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
+            responding_ap_title: Some(ap_title(b"SERVER01")),
+            responding_ae_qualifier: Some(vec![0x04, 0x01, 0x00]),
+            implementation_information: Some(b"meter".to_vec()),
+            user_information: b"user_info".to_vec(),
+            ..Default::default()
+        };
+
+        let bytes = aare.to_bytes().unwrap();
+        assert_eq!(AareApdu::from_bytes(&bytes).unwrap().1, aare);
+    }
+
+    #[test]
+    fn test_aare_result_source_diagnostic_choice() {
+        let aare = AareApdu {
+            application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
+            result: 1,
+            result_source_diagnostic: AssociateSourceDiagnostic::AUTHENTICATION_FAILURE,
+            user_information: b"user_info".to_vec(),
+            ..Default::default()
+        };
+        let bytes = aare.to_bytes().unwrap();
+        let diagnostic = bytes.windows(7).position(|w| w[0] == 0xA3).unwrap();
+        assert_eq!(
+            &bytes[diagnostic..diagnostic + 7],
+            &[0xA3, 0x05, 0xA1, 0x03, 0x02, 0x01, 0x0D]
+        );
+        assert_eq!(
+            AareApdu::from_bytes_with(&bytes, ParseMode::Strict)
+                .unwrap()
+                .1,
+            aare
+        );
+
+        let provider = AareApdu {
+            result_source_diagnostic: AssociateSourceDiagnostic::NO_COMMON_ACSE_VERSION,
+            ..aare.clone()
+        };
+        let bytes = provider.to_bytes().unwrap();
+        assert_eq!(AareApdu::from_bytes(&bytes).unwrap().1, provider);
+
+        // A bare octet is only accepted leniently, as a service-user value.
+        let mut legacy = bytes[..diagnostic].to_vec();
+        legacy.extend_from_slice(&[0xA3, 0x01, 0x0E]);
+        legacy.extend_from_slice(&bytes[diagnostic + 7..]);
+        legacy[1] -= 4;
+        assert_eq!(
+            AareApdu::from_bytes(&legacy)
+                .unwrap()
+                .1
+                .result_source_diagnostic,
+            AssociateSourceDiagnostic::AUTHENTICATION_REQUIRED
+        );
+        assert!(AareApdu::from_bytes_with(&legacy, ParseMode::Strict).is_err());
+    }
+
     #[test]
     fn test_aare_apdu_with_long_optional_roundtrip() {
         let responding_authentication_value: Vec<u8> = (0..260).map(|i| (i % 200) as u8).collect();
@@ -426,9 +688,10 @@ mod tests {
         let aare = AareApdu {
             application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
             result: 0,
-            result_source_diagnostic: 0,
+            result_source_diagnostic: AssociateSourceDiagnostic::NULL,
             responding_authentication_value: Some(responding_authentication_value.clone()),
             user_information: b"user_info".to_vec(),
+            ..Default::default()
         };
 
         let bytes = aare.to_bytes().unwrap();
@@ -444,12 +707,23 @@ mod tests {
     fn arlrq_round_trip() {
         let apdu = ArlrqApdu {
             reason: Some(0),
-            user_information: Some(vec![0x01, 0x02, 0x03]),
+            user_information: Some(ReleaseRequestInformation::Ciphered(vec![0x5A; 40])),
         };
 
         let encoded = apdu.to_bytes().expect("failed to encode A-RLRQ");
         let (_, decoded) = ArlrqApdu::from_bytes(&encoded).expect("failed to decode A-RLRQ");
         assert_eq!(decoded, apdu);
+
+        // The user-information must hold an initiate PDU.
+        let mut encoded = ArlrqApdu {
+            reason: None,
+            user_information: None,
+        }
+        .to_bytes()
+        .unwrap();
+        encoded[1] += 5;
+        encoded.extend_from_slice(&[0xBE, 0x03, 0x04, 0x01, 0x0E]);
+        assert!(ArlrqApdu::from_bytes(&encoded).is_err());
     }
 
     #[test]
@@ -462,5 +736,49 @@ mod tests {
         let encoded = apdu.to_bytes().expect("failed to encode A-RLRE");
         let (_, decoded) = ArlreApdu::from_bytes(&encoded).expect("failed to decode A-RLRE");
         assert_eq!(decoded, apdu);
+
+        let apdu = ArlreApdu {
+            reason: Some(0),
+            user_information: Some(ReleaseResponseInformation::Initiate(
+                AssociationParameters::default().to_initiate_response(Conformance::GET),
+            )),
+        };
+        let encoded = apdu.to_bytes().expect("failed to encode A-RLRE");
+        let (_, decoded) = ArlreApdu::from_bytes_with(&encoded, ParseMode::Strict)
+            .expect("failed to decode A-RLRE");
+        assert_eq!(decoded, apdu);
+    }
+
+    #[test]
+    fn test_parse_mode_for_meter_quirks() {
+        let aare = AareApdu {
+            application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
+            user_information: b"user_info".to_vec(),
+            ..Default::default()
+        };
+        let bytes = aare.to_bytes().unwrap();
+
+        // responder-acse-requirements, unknown to this parser, before the
+        // user information.
+        let ui_start = bytes.len() - 11;
+        let mut quirky = bytes[..ui_start].to_vec();
+        quirky.extend([0x88, 0x02, 0x07, 0x80]);
+        quirky.extend(&bytes[ui_start..]);
+        quirky[1] += 4;
+        assert_eq!(AareApdu::from_bytes(&quirky).unwrap().1, aare);
+        assert!(AareApdu::from_bytes_with(&quirky, ParseMode::Strict).is_err());
+
+        // Long-form length for a short APDU, and padding after it.
+        let mut long_form = vec![0x61, 0x81];
+        long_form.extend(&bytes[1..]);
+        long_form.push(0x00);
+        assert_eq!(AareApdu::from_bytes(&long_form).unwrap().1, aare);
+        assert!(AareApdu::from_bytes_with(&long_form, ParseMode::Strict).is_err());
+        assert_eq!(
+            AareApdu::from_bytes_with(&bytes, ParseMode::Strict)
+                .unwrap()
+                .1,
+            aare
+        );
     }
 }
@@ -1,12 +1,64 @@
+use crate::axdr::{encode_data, encode_length};
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
 use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
     MethodAccessDescriptor, MethodAccessMode,
 };
+use crate::data_stream::CosemDataStream;
+use crate::error::DlmsError;
 use crate::types::CosemData;
+use crate::xdlms::{DataAccessResult, SelectiveAccessDescriptor};
+use std::string::String;
 use std::sync::{Arc, Mutex};
 use std::vec::Vec;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssociationStatus {
+    #[default]
+    NonAssociated = 0,
+    AssociationPending = 1,
+    Associated = 2,
+}
+
+impl From<u8> for AssociationStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => AssociationStatus::AssociationPending,
+            2 => AssociationStatus::Associated,
+            _ => AssociationStatus::NonAssociated,
+        }
+    }
+}
+
+// user_list_entry ::= structure { user_id: unsigned, user_name: visible-string }
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UserListEntry {
+    pub user_id: u8,
+    pub user_name: String,
+}
+
+impl UserListEntry {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::Unsigned(self.user_id),
+            CosemData::VisibleString(self.user_name.clone()),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::Unsigned(user_id), CosemData::VisibleString(user_name)] => Some(Self {
+                user_id: *user_id,
+                user_name: user_name.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ObjectListEntry {
     pub class_id: u16,
@@ -57,6 +109,52 @@ impl ObjectListEntry {
     }
 }
 
+// Streams the object list (attribute 2) one entry at a time, as the list of a
+// real meter exceeds any PDU. The number of entries is fixed when the stream
+// starts; objects registered meanwhile are not part of the value.
+#[derive(Debug)]
+pub struct ObjectListStream {
+    object_list: Arc<Mutex<Vec<ObjectListEntry>>>,
+    header_written: bool,
+    next_entry: usize,
+    total: usize,
+}
+
+impl ObjectListStream {
+    pub fn new(object_list: Arc<Mutex<Vec<ObjectListEntry>>>) -> Option<Self> {
+        let total = object_list.lock().ok()?.len();
+        Some(Self {
+            object_list,
+            header_written: false,
+            next_entry: 0,
+            total,
+        })
+    }
+}
+
+impl CosemDataStream for ObjectListStream {
+    fn encode_next(
+        &mut self,
+        _object: &dyn CosemObject,
+        out: &mut Vec<u8>,
+        target: usize,
+    ) -> Result<bool, DlmsError> {
+        let entries = self.object_list.lock().map_err(|_| DlmsError::Cosem)?;
+        if !self.header_written {
+            out.push(1);
+            encode_length(self.total, out);
+            self.header_written = true;
+        }
+        while out.len() < target && self.next_entry < self.total {
+            // Objects removed meanwhile abort the transfer.
+            let entry = entries.get(self.next_entry).ok_or(DlmsError::Cosem)?;
+            encode_data(&entry.to_cosem_data(), out)?;
+            self.next_entry += 1;
+        }
+        Ok(self.next_entry >= self.total)
+    }
+}
+
 /// Association LN (Class ID 15)
 #[derive(Debug, Clone)]
 pub struct AssociationLN {
@@ -75,6 +173,16 @@ pub struct AssociationLN {
     // Attribute 6: The name of the authentication mechanism (e.g., Low, High).
     // An OID encoded as an octet-string.
     authentication_mechanism_name: Vec<u8>,
+    // Attribute 7: The LLS password or HLS secret. Write-only.
+    secret: Vec<u8>,
+    // Attribute 8: The state of the association (enum).
+    association_status: AssociationStatus,
+    // Attribute 9: Logical name of the Security setup object used by the association.
+    security_setup_reference: [u8; 6],
+    // Attribute 10: Users allowed to use the association.
+    user_list: Vec<UserListEntry>,
+    // Attribute 11: The user of the current association.
+    current_user: UserListEntry,
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
@@ -92,10 +200,83 @@ impl AssociationLN {
             application_context_name,
             xdlms_context_info,
             authentication_mechanism_name,
+            secret: Vec::new(),
+            association_status: AssociationStatus::NonAssociated,
+            security_setup_reference: [0; 6],
+            user_list: Vec::new(),
+            current_user: UserListEntry::default(),
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
 
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    pub fn set_secret(&mut self, secret: Vec<u8>) {
+        self.secret = secret;
+    }
+
+    pub fn association_status(&self) -> AssociationStatus {
+        self.association_status
+    }
+
+    pub fn set_association_status(&mut self, status: AssociationStatus) {
+        self.association_status = status;
+    }
+
+    pub fn security_setup_reference(&self) -> [u8; 6] {
+        self.security_setup_reference
+    }
+
+    pub fn set_security_setup_reference(&mut self, logical_name: [u8; 6]) {
+        self.security_setup_reference = logical_name;
+    }
+
+    pub fn user_list(&self) -> &[UserListEntry] {
+        &self.user_list
+    }
+
+    // Replaces an entry with the same user_id, otherwise appends.
+    pub fn add_user(&mut self, user: UserListEntry) {
+        match self
+            .user_list
+            .iter_mut()
+            .find(|entry| entry.user_id == user.user_id)
+        {
+            Some(entry) => *entry = user,
+            None => self.user_list.push(user),
+        }
+    }
+
+    pub fn remove_user(&mut self, user_id: u8) -> bool {
+        let before = self.user_list.len();
+        self.user_list.retain(|entry| entry.user_id != user_id);
+        self.user_list.len() != before
+    }
+
+    pub fn current_user(&self) -> &UserListEntry {
+        &self.current_user
+    }
+
+    pub fn set_current_user(&mut self, user: UserListEntry) {
+        self.current_user = user;
+    }
+
+    // Attribute 2, shared with the clones made of this association.
+    pub fn object_list(&self) -> Arc<Mutex<Vec<ObjectListEntry>>> {
+        Arc::clone(&self.object_list)
+    }
+
+    // A copy of attribute 2 as it stands, for hosts that should not hold the
+    // lock the server updates it under.
+    pub fn object_list_snapshot(&self) -> Vec<ObjectListEntry> {
+        self.object_list
+            .lock()
+            .map(|object_list| object_list.clone())
+            .unwrap_or_default()
+    }
+
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
@@ -110,6 +291,26 @@ impl AssociationLN {
             None
         }
     }
+
+    fn change_hls_secret(&mut self, data: CosemData) -> Option<CosemData> {
+        let CosemData::OctetString(secret) = data else {
+            return None;
+        };
+        self.secret = secret;
+        Some(CosemData::NullData)
+    }
+
+    fn invoke_add_user(&mut self, data: CosemData) -> Option<CosemData> {
+        let user = UserListEntry::from_cosem_data(&data)?;
+        self.add_user(user);
+        Some(CosemData::NullData)
+    }
+
+    fn invoke_remove_user(&mut self, data: CosemData) -> Option<CosemData> {
+        let user = UserListEntry::from_cosem_data(&data)?;
+        self.remove_user(user.user_id)
+            .then_some(CosemData::NullData)
+    }
 }
 
 impl Default for AssociationLN {
@@ -129,18 +330,39 @@ impl CosemObject for AssociationLN {
         15
     }
 
+    fn version(&self) -> u8 {
+        3
+    }
+
     fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
         vec![
-            AttributeAccessDescriptor::new(2, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::with_selective_access(
+                2,
+                AttributeAccessMode::Read,
+                Some(CosemData::Array(vec![
+                    CosemData::Integer(1),
+                    CosemData::Integer(2),
+                ])),
+            ),
             AttributeAccessDescriptor::new(3, AttributeAccessMode::ReadWrite),
             AttributeAccessDescriptor::new(4, AttributeAccessMode::ReadWrite),
             AttributeAccessDescriptor::new(5, AttributeAccessMode::ReadWrite),
             AttributeAccessDescriptor::new(6, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(7, AttributeAccessMode::Write),
+            AttributeAccessDescriptor::new(8, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(9, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(10, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(11, AttributeAccessMode::Read),
         ]
     }
 
     fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
-        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+        vec![
+            MethodAccessDescriptor::new(1, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(2, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(5, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(6, MethodAccessMode::Access),
+        ]
     }
 
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
@@ -158,10 +380,100 @@ impl CosemObject for AssociationLN {
             6 => Some(CosemData::OctetString(
                 self.authentication_mechanism_name.clone(),
             )),
+            // Attribute 7 (secret) is write-only.
+            8 => Some(CosemData::Enum(self.association_status as u8)),
+            9 => Some(CosemData::OctetString(
+                self.security_setup_reference.to_vec(),
+            )),
+            10 => Some(CosemData::Array(
+                self.user_list
+                    .iter()
+                    .map(UserListEntry::to_cosem_data)
+                    .collect(),
+            )),
+            11 => Some(self.current_user.to_cosem_data()),
             _ => None,
         }
     }
 
+    // A poisoned list is not streamed, so that the read below reports it.
+    fn attribute_stream(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Option<Box<dyn CosemDataStream>> {
+        if attribute_id != 2 {
+            return None;
+        }
+        let stream = ObjectListStream::new(Arc::clone(&self.object_list))?;
+        Some(Box::new(stream))
+    }
+
+    // The object list is shared with the server; a poisoned lock is reported
+    // as a temporary failure rather than a missing object.
+    fn read_attribute(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Result<CosemData, DataAccessResult> {
+        if attribute_id == 2 && self.object_list.is_poisoned() {
+            return Err(DataAccessResult::TemporaryFailure);
+        }
+        self.get_attribute(attribute_id)
+            .ok_or(DataAccessResult::ObjectUnavailable)
+    }
+
+    // Selector 1 lists the objects of the given classes, selector 2 the given
+    // objects, each as {class_id, logical_name}.
+    fn read_attribute_selective(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+        selection: &SelectiveAccessDescriptor,
+    ) -> Result<CosemData, DataAccessResult> {
+        if attribute_id != 2 {
+            return Err(DataAccessResult::ScopeOfAccessViolated);
+        }
+        let CosemData::Array(selected) = &selection.access_parameters else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        let selected = match selection.access_selector {
+            1 => selected
+                .iter()
+                .map(|class_id| match class_id {
+                    CosemData::LongUnsigned(class_id) => Ok((*class_id, None)),
+                    _ => Err(DataAccessResult::TypeUnmatched),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            2 => selected
+                .iter()
+                .map(|object_id| match object_id {
+                    CosemData::Structure(fields) => match fields.as_slice() {
+                        [CosemData::LongUnsigned(class_id), CosemData::OctetString(ln)] => {
+                            let ln = <[u8; 6]>::try_from(ln.as_slice())
+                                .map_err(|_| DataAccessResult::TypeUnmatched)?;
+                            Ok((*class_id, Some(ln)))
+                        }
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    },
+                    _ => Err(DataAccessResult::TypeUnmatched),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(DataAccessResult::ScopeOfAccessViolated),
+        };
+        let entries = self
+            .object_list
+            .lock()
+            .map_err(|_| DataAccessResult::TemporaryFailure)?;
+        let list = entries
+            .iter()
+            .filter(|entry| {
+                selected.iter().any(|(class_id, ln)| {
+                    *class_id == entry.class_id && ln.is_none_or(|ln| ln == entry.logical_name)
+                })
+            })
+            .map(ObjectListEntry::to_cosem_data)
+            .collect();
+        Ok(CosemData::Array(list))
+    }
+
     fn set_attribute(
         &mut self,
         attribute_id: CosemObjectAttributeId,
@@ -201,6 +513,22 @@ impl CosemObject for AssociationLN {
                     None
                 }
             }
+            7 => {
+                if let CosemData::OctetString(secret) = data {
+                    self.secret = secret;
+                    Some(())
+                } else {
+                    None
+                }
+            }
+            9 => {
+                if let CosemData::OctetString(name) = data {
+                    self.security_setup_reference = name.try_into().ok()?;
+                    Some(())
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -212,6 +540,9 @@ impl CosemObject for AssociationLN {
     ) -> Option<CosemData> {
         match method_id {
             1 => self.reply_to_hls_authentication(data),
+            2 => self.change_hls_secret(data),
+            5 => self.invoke_add_user(data),
+            6 => self.invoke_remove_user(data),
             _ => None,
         }
     }
@@ -333,4 +664,54 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn association_ln_v3_attributes_and_user_methods() {
+        let mut association = AssociationLN::default();
+        assert_eq!(association.version(), 3);
+        assert_eq!(association.get_attribute(8), Some(CosemData::Enum(0)));
+
+        association
+            .set_attribute(7, CosemData::OctetString(b"12345678".to_vec()))
+            .expect("secret is writable");
+        assert_eq!(association.secret(), b"12345678");
+        assert_eq!(association.get_attribute(7), None);
+
+        association
+            .set_attribute(9, CosemData::OctetString(vec![0, 0, 43, 0, 0, 255]))
+            .expect("security setup reference is writable");
+        assert_eq!(
+            association.get_attribute(9),
+            Some(CosemData::OctetString(vec![0, 0, 43, 0, 0, 255]))
+        );
+        assert!(association
+            .set_attribute(9, CosemData::OctetString(vec![0, 0, 43]))
+            .is_none());
+
+        let operator = CosemData::Structure(vec![
+            CosemData::Unsigned(1),
+            CosemData::VisibleString("operator".into()),
+        ]);
+        assert_eq!(
+            association.invoke_method(5, operator.clone()),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(
+            association.get_attribute(10),
+            Some(CosemData::Array(vec![operator.clone()]))
+        );
+
+        association.set_current_user(association.user_list()[0].clone());
+        assert_eq!(association.get_attribute(11), Some(operator.clone()));
+
+        assert_eq!(
+            association.invoke_method(6, operator.clone()),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(association.invoke_method(6, operator), None);
+        assert_eq!(
+            association.get_attribute(10),
+            Some(CosemData::Array(Vec::new()))
+        );
+    }
 }
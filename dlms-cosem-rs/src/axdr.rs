@@ -1,8 +1,160 @@
 use crate::error::DlmsError;
-use crate::types::CosemData;
+use crate::types::{padding_mask, BitString, CosemData};
 use std::vec::Vec;
 
+// How strictly decoders check their input. Lenient, the default, accepts the
+// quirks real meters are known to produce: long-form lengths for short values,
+// padding after an APDU and unknown ACSE components. Strict rejects them, and
+// with them every encoding other than the one our encoders produce, so input
+// that decodes strictly re-encodes to the same bytes. Signatures can then be
+// checked over a re-encoded PDU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    #[default]
+    Lenient,
+    Strict,
+}
+
+// A-XDR length: short form below 0x80, otherwise 0x80 | n followed by n bytes.
+pub fn encode_length(len: usize, buffer: &mut Vec<u8>) {
+    if len < 0x80 {
+        buffer.push(len as u8);
+        return;
+    }
+    let bytes = len.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+    buffer.push(0x80 | (bytes.len() - skip) as u8);
+    buffer.extend_from_slice(&bytes[skip..]);
+}
+
+pub fn decode_length(buffer: &[u8]) -> Result<(usize, &[u8]), DlmsError> {
+    decode_length_with(buffer, ParseMode::Lenient)
+}
+
+// Strict mode only accepts the shortest encoding of a length.
+pub fn decode_length_with(buffer: &[u8], mode: ParseMode) -> Result<(usize, &[u8]), DlmsError> {
+    let (&first, rest) = buffer.split_first().ok_or(DlmsError::Xdlms)?;
+    if first < 0x80 {
+        return Ok((first as usize, rest));
+    }
+    let count = (first & 0x7F) as usize;
+    if count == 0 || count > core::mem::size_of::<usize>() || rest.len() < count {
+        return Err(DlmsError::Xdlms);
+    }
+    let (bytes, rest) = rest.split_at(count);
+    if mode == ParseMode::Strict && (bytes[0] == 0 || (count == 1 && bytes[0] < 0x80)) {
+        return Err(DlmsError::Xdlms);
+    }
+    let len = bytes
+        .iter()
+        .fold(0usize, |len, &byte| (len << 8) | byte as usize);
+    Ok((len, rest))
+}
+
+// Bounds on the data an encoder or decoder handles: how deep arrays and
+// structures nest and how many values they hold in total, the containers
+// included. Hostile input could otherwise exhaust the stack or the heap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataLimits {
+    pub max_depth: usize,
+    pub max_elements: usize,
+}
+
+impl Default for DataLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 32,
+            max_elements: 1 << 20,
+        }
+    }
+}
+
+// Counts the values and open containers of a walk through data.
+struct LimitTracker {
+    limits: DataLimits,
+    elements: usize,
+}
+
+impl LimitTracker {
+    fn new(limits: DataLimits) -> Self {
+        Self {
+            limits,
+            elements: 0,
+        }
+    }
+
+    fn value(&mut self) -> Result<(), DlmsError> {
+        self.elements += 1;
+        if self.elements > self.limits.max_elements {
+            return Err(DlmsError::DataLimitExceeded);
+        }
+        Ok(())
+    }
+
+    // `depth` is the number of containers open, the new one included.
+    fn container(&self, depth: usize) -> Result<(), DlmsError> {
+        if depth > self.limits.max_depth {
+            return Err(DlmsError::DataLimitExceeded);
+        }
+        Ok(())
+    }
+}
+
 pub fn encode_data(data: &CosemData, buffer: &mut Vec<u8>) -> Result<(), DlmsError> {
+    encode_data_limited(data, buffer, DataLimits::default())
+}
+
+// Encodes with an explicit stack of the containers being written, so that
+// the nesting of `data` does not use the call stack.
+pub fn encode_data_limited(
+    data: &CosemData,
+    buffer: &mut Vec<u8>,
+    limits: DataLimits,
+) -> Result<(), DlmsError> {
+    let mut tracker = LimitTracker::new(limits);
+    let mut open: Vec<core::slice::Iter<'_, CosemData>> = Vec::new();
+    let mut next = Some(data);
+    loop {
+        if let Some(data) = next.take() {
+            tracker.value()?;
+            match data {
+                CosemData::Array(elements) | CosemData::Structure(elements) => {
+                    tracker.container(open.len() + 1)?;
+                    buffer.push(if matches!(data, CosemData::Array(_)) {
+                        1
+                    } else {
+                        2
+                    });
+                    encode_length(elements.len(), buffer);
+                    // A container of scalars only, e.g. a scaler and unit, is
+                    // written out at once without growing the stack.
+                    if elements.iter().any(|element| {
+                        matches!(element, CosemData::Array(_) | CosemData::Structure(_))
+                    }) {
+                        open.push(elements.iter());
+                    } else {
+                        for element in elements {
+                            tracker.value()?;
+                            encode_scalar(element, buffer)?;
+                        }
+                    }
+                }
+                data => encode_scalar(data, buffer)?,
+            }
+        }
+        let Some(elements) = open.last_mut() else {
+            return Ok(());
+        };
+        match elements.next() {
+            Some(element) => next = Some(element),
+            None => {
+                open.pop();
+            }
+        }
+    }
+}
+
+fn encode_scalar(data: &CosemData, buffer: &mut Vec<u8>) -> Result<(), DlmsError> {
     match data {
         CosemData::NullData => buffer.push(0),
         CosemData::Boolean(val) => {
@@ -25,47 +177,132 @@ pub fn encode_data(data: &CosemData, buffer: &mut Vec<u8>) -> Result<(), DlmsErr
             buffer.push(6);
             buffer.extend_from_slice(&val.to_be_bytes());
         }
+        CosemData::DoubleLong(val) => {
+            buffer.push(5);
+            buffer.extend_from_slice(&val.to_be_bytes());
+        }
+        CosemData::Long(val) => {
+            buffer.push(16);
+            buffer.extend_from_slice(&val.to_be_bytes());
+        }
+        CosemData::Long64(val) => {
+            buffer.push(20);
+            buffer.extend_from_slice(&val.to_be_bytes());
+        }
+        CosemData::Long64Unsigned(val) => {
+            buffer.push(21);
+            buffer.extend_from_slice(&val.to_be_bytes());
+        }
         CosemData::Enum(val) => {
             buffer.push(22);
             buffer.push(*val);
         }
+        CosemData::Float32(val) => {
+            buffer.push(23);
+            buffer.extend_from_slice(&val.to_be_bytes());
+        }
+        CosemData::Float64(val) => {
+            buffer.push(24);
+            buffer.extend_from_slice(&val.to_be_bytes());
+        }
+        CosemData::BitString(val) => {
+            buffer.push(4);
+            encode_length(val.len(), buffer);
+            buffer.extend_from_slice(val.as_bytes());
+        }
         CosemData::OctetString(val) => {
             buffer.push(9);
-            buffer.push(val.len() as u8);
+            encode_length(val.len(), buffer);
             buffer.extend_from_slice(val);
         }
-        CosemData::Array(elements) => {
-            buffer.push(1);
-            buffer.push(elements.len() as u8);
-            for element in elements {
-                encode_data(element, buffer)?;
-            }
-        }
-        CosemData::Structure(elements) => {
-            buffer.push(2);
-            buffer.push(elements.len() as u8);
-            for element in elements {
-                encode_data(element, buffer)?;
-            }
-        }
         _ => return Err(DlmsError::Xdlms), // not all variants are supported yet
     }
     Ok(())
 }
 
 pub fn decode_data(buffer: &[u8]) -> Result<(CosemData, &[u8]), DlmsError> {
-    if buffer.is_empty() {
-        return Err(DlmsError::Xdlms);
+    decode_data_with(buffer, ParseMode::Lenient)
+}
+
+pub fn decode_data_with(buffer: &[u8], mode: ParseMode) -> Result<(CosemData, &[u8]), DlmsError> {
+    decode_data_limited(buffer, mode, DataLimits::default())
+}
+
+// Array or structure being decoded, with the number of elements still to come.
+struct OpenContainer {
+    is_array: bool,
+    remaining: usize,
+    elements: Vec<CosemData>,
+}
+
+// Decodes with an explicit stack of the containers being filled, so that
+// nested input cannot overflow the call stack.
+pub fn decode_data_limited(
+    buffer: &[u8],
+    mode: ParseMode,
+    limits: DataLimits,
+) -> Result<(CosemData, &[u8]), DlmsError> {
+    let mut tracker = LimitTracker::new(limits);
+    let mut open: Vec<OpenContainer> = Vec::new();
+    let mut rest = buffer;
+    loop {
+        tracker.value()?;
+        let (&tag, after_tag) = rest.split_first().ok_or(DlmsError::Xdlms)?;
+        let mut value = match tag {
+            1 | 2 => {
+                tracker.container(open.len() + 1)?;
+                let (len, after_length) = decode_length_with(after_tag, mode)?;
+                rest = after_length;
+                open.push(OpenContainer {
+                    is_array: tag == 1,
+                    remaining: len,
+                    elements: Vec::with_capacity(len.min(rest.len())),
+                });
+                None
+            }
+            _ => {
+                let (value, after_value) = decode_scalar(tag, after_tag, mode)?;
+                rest = after_value;
+                Some(value)
+            }
+        };
+        // Hands the value to its container and closes the containers it
+        // completes.
+        loop {
+            let Some(container) = open.last_mut() else {
+                match value {
+                    Some(value) => return Ok((value, rest)),
+                    None => break,
+                }
+            };
+            if let Some(value) = value.take() {
+                container.elements.push(value);
+                container.remaining -= 1;
+            }
+            if container.remaining > 0 {
+                break;
+            }
+            let container = open.pop().ok_or(DlmsError::Xdlms)?;
+            value = Some(if container.is_array {
+                CosemData::Array(container.elements)
+            } else {
+                CosemData::Structure(container.elements)
+            });
+        }
     }
+}
 
-    let (tag, rest) = buffer.split_at(1);
-    match tag[0] {
+fn decode_scalar(tag: u8, rest: &[u8], mode: ParseMode) -> Result<(CosemData, &[u8]), DlmsError> {
+    match tag {
         0 => Ok((CosemData::NullData, rest)),
         3 => {
             if rest.is_empty() {
                 return Err(DlmsError::Xdlms);
             }
             let (val, rest) = rest.split_at(1);
+            if mode == ParseMode::Strict && val[0] > 1 {
+                return Err(DlmsError::Xdlms);
+            }
             Ok((CosemData::Boolean(val[0] != 0), rest))
         }
         15 => {
@@ -102,6 +339,46 @@ pub fn decode_data(buffer: &[u8]) -> Result<(CosemData, &[u8]), DlmsError> {
                 rest,
             ))
         }
+        5 => {
+            if rest.len() < 4 {
+                return Err(DlmsError::Xdlms);
+            }
+            let (val, rest) = rest.split_at(4);
+            Ok((
+                CosemData::DoubleLong(i32::from_be_bytes(val.try_into().unwrap())),
+                rest,
+            ))
+        }
+        16 => {
+            if rest.len() < 2 {
+                return Err(DlmsError::Xdlms);
+            }
+            let (val, rest) = rest.split_at(2);
+            Ok((
+                CosemData::Long(i16::from_be_bytes(val.try_into().unwrap())),
+                rest,
+            ))
+        }
+        20 => {
+            if rest.len() < 8 {
+                return Err(DlmsError::Xdlms);
+            }
+            let (val, rest) = rest.split_at(8);
+            Ok((
+                CosemData::Long64(i64::from_be_bytes(val.try_into().unwrap())),
+                rest,
+            ))
+        }
+        21 => {
+            if rest.len() < 8 {
+                return Err(DlmsError::Xdlms);
+            }
+            let (val, rest) = rest.split_at(8);
+            Ok((
+                CosemData::Long64Unsigned(u64::from_be_bytes(val.try_into().unwrap())),
+                rest,
+            ))
+        }
         22 => {
             if rest.is_empty() {
                 return Err(DlmsError::Xdlms);
@@ -109,47 +386,214 @@ pub fn decode_data(buffer: &[u8]) -> Result<(CosemData, &[u8]), DlmsError> {
             let (val, rest) = rest.split_at(1);
             Ok((CosemData::Enum(val[0]), rest))
         }
-        9 => {
-            if rest.is_empty() {
+        23 => {
+            if rest.len() < 4 {
                 return Err(DlmsError::Xdlms);
             }
-            let (len, rest) = rest.split_at(1);
-            let len = len[0] as usize;
-            if rest.len() < len {
+            let (val, rest) = rest.split_at(4);
+            Ok((
+                CosemData::Float32(f32::from_be_bytes(val.try_into().unwrap())),
+                rest,
+            ))
+        }
+        24 => {
+            if rest.len() < 8 {
                 return Err(DlmsError::Xdlms);
             }
-            let (val, rest) = rest.split_at(len);
-            Ok((CosemData::OctetString(val.to_vec()), rest))
+            let (val, rest) = rest.split_at(8);
+            Ok((
+                CosemData::Float64(f64::from_be_bytes(val.try_into().unwrap())),
+                rest,
+            ))
         }
-        1 => {
-            if rest.is_empty() {
+        // The length counts bits. Strict mode rejects padding bits that are
+        // set, as they would not survive a re-encoding.
+        4 => {
+            let (len, rest) = decode_length_with(rest, mode)?;
+            let octets = len.div_ceil(8);
+            if rest.len() < octets {
                 return Err(DlmsError::Xdlms);
             }
-            let (len_bytes, mut rest) = rest.split_at(1);
-            let len = len_bytes[0] as usize;
-            let mut elements = Vec::with_capacity(len);
-            for _ in 0..len {
-                let (element, new_rest) = decode_data(rest)?;
-                elements.push(element);
-                rest = new_rest;
+            let (val, rest) = rest.split_at(octets);
+            if mode == ParseMode::Strict
+                && val
+                    .last()
+                    .is_some_and(|last| last & !padding_mask(len) != 0)
+            {
+                return Err(DlmsError::Xdlms);
             }
-            Ok((CosemData::Array(elements), rest))
+            let bits = BitString::new(len, val.to_vec()).ok_or(DlmsError::Xdlms)?;
+            Ok((CosemData::BitString(bits), rest))
         }
-        2 => {
-            if rest.is_empty() {
+        9 => {
+            let (len, rest) = decode_length_with(rest, mode)?;
+            if rest.len() < len {
                 return Err(DlmsError::Xdlms);
             }
-            let (len_bytes, mut rest) = rest.split_at(1);
-            let len = len_bytes[0] as usize;
-            let mut elements = Vec::with_capacity(len);
-            for _ in 0..len {
-                let (element, new_rest) = decode_data(rest)?;
-                elements.push(element);
-                rest = new_rest;
-            }
-            Ok((CosemData::Structure(elements), rest))
+            let (val, rest) = rest.split_at(len);
+            Ok((CosemData::OctetString(val.to_vec()), rest))
         }
-
         _ => Err(DlmsError::Xdlms), // not all variants are supported yet
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_signed_and_64_bit_round_trip() {
+        let data = CosemData::Structure(vec![
+            CosemData::DoubleLong(-123_456),
+            CosemData::Long(-2),
+            CosemData::Long64(i64::MIN),
+            CosemData::Long64Unsigned(u64::MAX),
+        ]);
+        let mut buffer = Vec::new();
+        encode_data(&data, &mut buffer).unwrap();
+        assert_eq!(&buffer[2..7], &[5, 0xFF, 0xFE, 0x1D, 0xC0]);
+        assert_eq!(&buffer[7..10], &[16, 0xFF, 0xFE]);
+
+        let (decoded, rest) = decode_data(&buffer).unwrap();
+        assert_eq!(decoded, data);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_float_round_trip() {
+        let data = CosemData::Structure(vec![CosemData::Float32(230.1), CosemData::Float64(-0.98)]);
+        let mut buffer = Vec::new();
+        encode_data(&data, &mut buffer).unwrap();
+        assert_eq!(&buffer[2..7], &[23, 0x43, 0x66, 0x19, 0x9A]);
+        assert_eq!(buffer[7], 24);
+        assert_eq!(buffer.len(), 16);
+
+        let (decoded, rest) = decode_data_with(&buffer, ParseMode::Strict).unwrap();
+        assert_eq!(decoded, data);
+        assert!(rest.is_empty());
+        assert!(decode_data(&[24, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_long_arrays_use_multi_byte_length() {
+        let data = CosemData::Array(vec![CosemData::Unsigned(1); 300]);
+        let mut buffer = Vec::new();
+        encode_data(&data, &mut buffer).unwrap();
+        assert_eq!(&buffer[..4], &[1, 0x82, 0x01, 0x2C]);
+        assert_eq!(decode_data(&buffer).unwrap().0, data);
+
+        let mut buffer = Vec::new();
+        encode_length(0x80, &mut buffer);
+        assert_eq!(buffer, vec![0x81, 0x80]);
+        assert!(decode_length(&[0x82, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_non_canonical_lengths() {
+        let padded = [9, 0x81, 0x02, 0xAA, 0xBB];
+        let value = CosemData::OctetString(vec![0xAA, 0xBB]);
+        assert_eq!(decode_data(&padded).unwrap().0, value);
+        assert!(decode_data_with(&padded, ParseMode::Strict).is_err());
+        assert!(decode_length_with(&[0x82, 0x00, 0x90], ParseMode::Strict).is_err());
+        assert_eq!(
+            decode_length_with(&[0x81, 0x90], ParseMode::Strict)
+                .unwrap()
+                .0,
+            0x90
+        );
+        assert_eq!(
+            decode_data_with(&[9, 0x02, 0xAA, 0xBB], ParseMode::Strict)
+                .unwrap()
+                .0,
+            value
+        );
+    }
+
+    #[test]
+    fn test_nesting_and_element_limits() {
+        // A hundred thousand nested arrays of one element.
+        let mut hostile = [1, 1].repeat(100_000);
+        hostile.push(0);
+        assert!(matches!(
+            decode_data(&hostile),
+            Err(DlmsError::DataLimitExceeded)
+        ));
+
+        let nested = |depth: usize| {
+            (0..depth).fold(CosemData::NullData, |inner, _| {
+                CosemData::Structure(vec![inner])
+            })
+        };
+        let limits = DataLimits {
+            max_depth: 3,
+            max_elements: 16,
+        };
+        let mut buffer = Vec::new();
+        encode_data_limited(&nested(3), &mut buffer, limits).unwrap();
+        assert_eq!(buffer, vec![2, 1, 2, 1, 2, 1, 0]);
+        assert_eq!(
+            decode_data_limited(&buffer, ParseMode::Lenient, limits)
+                .unwrap()
+                .0,
+            nested(3)
+        );
+        assert!(matches!(
+            encode_data_limited(&nested(4), &mut Vec::new(), limits),
+            Err(DlmsError::DataLimitExceeded)
+        ));
+        encode_data(&nested(4), &mut buffer).unwrap();
+        assert!(matches!(
+            decode_data_limited(&buffer[7..], ParseMode::Lenient, limits),
+            Err(DlmsError::DataLimitExceeded)
+        ));
+
+        // The array and its sixteen values are seventeen elements.
+        let wide = CosemData::Array(vec![CosemData::Unsigned(1); 16]);
+        let mut buffer = Vec::new();
+        encode_data(&wide, &mut buffer).unwrap();
+        assert!(matches!(
+            decode_data_limited(&buffer, ParseMode::Lenient, limits),
+            Err(DlmsError::DataLimitExceeded)
+        ));
+        assert!(matches!(
+            encode_data_limited(&wide, &mut Vec::new(), limits),
+            Err(DlmsError::DataLimitExceeded)
+        ));
+
+        // Deep data within the limits does not need the call stack.
+        let limits = DataLimits {
+            max_depth: 2_000,
+            ..DataLimits::default()
+        };
+        let mut buffer = Vec::new();
+        encode_data_limited(&nested(2_000), &mut buffer, limits).unwrap();
+        let (decoded, rest) = decode_data_limited(&buffer, ParseMode::Lenient, limits).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(decoded, nested(2_000));
+    }
+
+    #[test]
+    fn test_bit_string_round_trip() {
+        let bits = CosemData::BitString(BitString::new(12, vec![0xAB, 0xC0]).unwrap());
+        let mut buffer = Vec::new();
+        encode_data(&bits, &mut buffer).unwrap();
+        assert_eq!(buffer, vec![4, 12, 0xAB, 0xC0]);
+        assert_eq!(decode_data(&buffer).unwrap(), (bits.clone(), &[][..]));
+        assert_eq!(
+            decode_data_with(&buffer, ParseMode::Strict).unwrap().0,
+            bits
+        );
+
+        let padded = [4, 12, 0xAB, 0xCD];
+        assert_eq!(decode_data(&padded).unwrap().0, bits);
+        assert!(decode_data_with(&padded, ParseMode::Strict).is_err());
+        assert!(decode_data(&[4, 12, 0xAB]).is_err());
+    }
+
+    #[test]
+    fn test_truncated_long64_is_rejected() {
+        assert!(decode_data(&[20, 0, 0, 0]).is_err());
+        assert!(decode_data(&[21, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::cosem::CosemObjectInstanceId;
+use crate::cosem_object::CosemObject;
+use std::vec::Vec;
+
+// Billing profile, a profile generic (class 7) capturing the billing registers.
+pub const BILLING_PROFILE_LN: CosemObjectInstanceId = [1, 0, 98, 1, 0, 255];
+// Billing period counter, a data object (class 1).
+pub const BILLING_PERIOD_COUNTER_LN: CosemObjectInstanceId = [0, 0, 0, 1, 0, 255];
+// Script table holding the end of billing period script.
+pub const END_OF_BILLING_SCRIPT_TABLE_LN: CosemObjectInstanceId = [0, 0, 10, 0, 1, 255];
+
+// Objects taking part in the end of a billing period. The billing registers
+// are the capture objects of the billing profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillingConfiguration {
+    pub profile: CosemObjectInstanceId,
+    pub counter: Option<CosemObjectInstanceId>,
+    pub demand_registers: Vec<CosemObjectInstanceId>,
+}
+
+impl BillingConfiguration {
+    pub fn with_demand_register(mut self, logical_name: CosemObjectInstanceId) -> Self {
+        self.demand_registers.push(logical_name);
+        self
+    }
+}
+
+impl Default for BillingConfiguration {
+    fn default() -> Self {
+        Self {
+            profile: BILLING_PROFILE_LN,
+            counter: Some(BILLING_PERIOD_COUNTER_LN),
+            demand_registers: Vec::new(),
+        }
+    }
+}
+
+// Billing period a register instance refers to by its value group F: 255 for
+// the current value, 0..=99 for the period numbered by the billing period
+// counter (VZ), and 101..=125 for the last period ended (VZ), the one before
+// (VZ-1) and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BillingPeriod {
+    Current,
+    Numbered(u8),
+    // Periods back from the last one ended, 0 for the last.
+    Previous(u8),
+}
+
+// Periods value group F can count back from the last one ended.
+pub const MAX_PREVIOUS_BILLING_PERIODS: u8 = 25;
+
+impl BillingPeriod {
+    pub fn from_value_group_f(f: u8) -> Option<Self> {
+        match f {
+            0..=99 => Some(BillingPeriod::Numbered(f)),
+            101..=125 => Some(BillingPeriod::Previous(f - 101)),
+            255 => Some(BillingPeriod::Current),
+            _ => None,
+        }
+    }
+
+    pub fn of(logical_name: CosemObjectInstanceId) -> Option<Self> {
+        Self::from_value_group_f(logical_name[5])
+    }
+
+    pub fn value_group_f(self) -> u8 {
+        match self {
+            BillingPeriod::Current => 255,
+            BillingPeriod::Numbered(vz) => vz,
+            BillingPeriod::Previous(back) => 101 + back,
+        }
+    }
+
+    // The instance of the object named `logical_name` for this period.
+    pub fn instance(self, mut logical_name: CosemObjectInstanceId) -> CosemObjectInstanceId {
+        logical_name[5] = self.value_group_f();
+        logical_name
+    }
+
+    // Index of the entry of the period in a billing profile of `entries`
+    // entries, the oldest first, captured each time the counter advanced to
+    // the period it now holds.
+    pub fn entry_index(self, entries: usize, counter: Option<u64>) -> Option<usize> {
+        let back = match self {
+            BillingPeriod::Current => return None,
+            BillingPeriod::Previous(back) => back as usize,
+            BillingPeriod::Numbered(vz) => ((counter? % 100) as usize + 100 - vz as usize) % 100,
+        };
+        entries.checked_sub(back + 1)
+    }
+}
+
+// Advances the value of a billing period counter, wrapping to zero once the
+// integer type of the value is exhausted.
+pub fn increment_billing_counter(counter: &mut dyn CosemObject) -> Option<()> {
+    if counter.class_id() != 1 {
+        return None;
+    }
+    let value = counter.get_attribute(2)?;
+    let next = value
+        .checked_add(1)
+        .or_else(|| value.checked_add(0).map(|_| value.zero_like()))?;
+    counter.set_attribute(2, next)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::data::Data;
+    use crate::register::Register;
+    use crate::types::CosemData;
+
+    #[test]
+    fn test_billing_period_entries() {
+        let instance = [1, 0, 1, 8, 0, 102];
+        assert_eq!(
+            BillingPeriod::of(instance),
+            Some(BillingPeriod::Previous(1))
+        );
+        assert_eq!(
+            BillingPeriod::Current.instance(instance),
+            [1, 0, 1, 8, 0, 255]
+        );
+        assert_eq!(BillingPeriod::from_value_group_f(100), None);
+        assert_eq!(BillingPeriod::from_value_group_f(126), None);
+
+        // Four periods ended, the counter now at 2 after wrapping.
+        assert_eq!(BillingPeriod::Previous(0).entry_index(4, None), Some(3));
+        assert_eq!(BillingPeriod::Previous(3).entry_index(4, None), Some(0));
+        assert_eq!(BillingPeriod::Previous(4).entry_index(4, None), None);
+        assert_eq!(
+            BillingPeriod::Numbered(2).entry_index(4, Some(102)),
+            Some(3)
+        );
+        assert_eq!(
+            BillingPeriod::Numbered(99).entry_index(4, Some(102)),
+            Some(0)
+        );
+        assert_eq!(BillingPeriod::Numbered(98).entry_index(4, Some(102)), None);
+        assert_eq!(BillingPeriod::Numbered(2).entry_index(4, None), None);
+        assert_eq!(BillingPeriod::Current.entry_index(4, Some(2)), None);
+    }
+
+    #[test]
+    fn test_billing_counter_wraps() {
+        let mut counter = Data::new(CosemData::Unsigned(254));
+        increment_billing_counter(&mut counter).unwrap();
+        assert_eq!(counter.get_attribute(2), Some(CosemData::Unsigned(255)));
+        increment_billing_counter(&mut counter).unwrap();
+        assert_eq!(counter.get_attribute(2), Some(CosemData::Unsigned(0)));
+
+        let mut text = Data::new(CosemData::VisibleString("period".into()));
+        assert_eq!(increment_billing_counter(&mut text), None);
+        assert_eq!(increment_billing_counter(&mut Register::new()), None);
+    }
+}
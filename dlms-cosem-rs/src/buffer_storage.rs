@@ -0,0 +1,78 @@
+use crate::types::CosemData;
+use std::boxed::Box;
+use std::fmt;
+use std::ops::Range;
+use std::vec::Vec;
+
+// Backing store for buffer attributes (e.g. profile generic buffer). Entries are
+// kept oldest first; implementations may live outside RAM.
+pub trait BufferStorage: Send + fmt::Debug {
+    fn append(&mut self, entry: CosemData) -> Option<()>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn entries(&self, range: Range<usize>) -> Box<dyn Iterator<Item = CosemData> + '_>;
+    fn discard_oldest(&mut self, count: usize);
+    fn clear(&mut self);
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBufferStorage {
+    entries: Vec<CosemData>,
+}
+
+impl MemoryBufferStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BufferStorage for MemoryBufferStorage {
+    fn append(&mut self, entry: CosemData) -> Option<()> {
+        self.entries.push(entry);
+        Some(())
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn entries(&self, range: Range<usize>) -> Box<dyn Iterator<Item = CosemData> + '_> {
+        let end = range.end.min(self.entries.len());
+        let start = range.start.min(end);
+        Box::new(self.entries[start..end].iter().cloned())
+    }
+
+    fn discard_oldest(&mut self, count: usize) {
+        let count = count.min(self.entries.len());
+        self.entries.drain(..count);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_memory_buffer_storage() {
+        let mut storage = MemoryBufferStorage::new();
+        assert!(storage.is_empty());
+        for value in 0..5 {
+            storage.append(CosemData::Unsigned(value)).unwrap();
+        }
+        storage.discard_oldest(2);
+        assert_eq!(storage.len(), 3);
+        assert_eq!(
+            storage.entries(1..10).collect::<Vec<_>>(),
+            vec![CosemData::Unsigned(3), CosemData::Unsigned(4)]
+        );
+        storage.clear();
+        assert_eq!(storage.entries(0..1).count(), 0);
+    }
+}
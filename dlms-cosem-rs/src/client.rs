@@ -1,24 +1,120 @@
-use crate::acse::{AareApdu, AarqApdu, ArlreApdu, ArlrqApdu};
+use crate::acse::{
+    ap_title, system_title, AareApdu, AarqApdu, ArlreApdu, ArlrqApdu, AssociateSourceDiagnostic,
+};
+use crate::axdr::{decode_data_with, encode_data, ParseMode};
+use crate::clock::date_time_seconds;
+#[cfg(feature = "compress")]
+use crate::compression::{decompress_notification, Compression};
+use crate::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
+use crate::diagnostics;
 use crate::error::DlmsError;
-use crate::hdlc::HdlcFrame;
-use crate::security::{hls_decrypt, hls_encrypt, lls_authenticate, SecurityError};
+use crate::hdlc::{is_unnumbered_information, receive_ready, HdlcAddress, HdlcFrame};
+use crate::scaled_value::{Rounding, ScaledValue};
+use crate::security::{
+    generate_challenge, hls_decrypt, hls_encrypt, lls_authenticate, FrameCounter, HlsExchange,
+    HlsMechanism, SecurityError,
+};
+use crate::trace::{
+    apdu_name, CorrelationId, PhaseTimings, TraceLayer, TracePhase, TraceRecord, Tracer,
+};
 use crate::transport::Transport;
+use crate::types::{CosemData, DataType};
 use crate::xdlms::{
-    ActionRequest, ActionResponse, AssociationParameters, Conformance, GetRequest, GetResponse,
-    InitiateResponse, SetRequest, SetResponse,
+    decode_get_data_results, split_apdus, ActionRequest, ActionRequestNormal,
+    ActionRequestWithFirstPblock, ActionRequestWithPblock, ActionResponse, ActionResult,
+    AssociationParameters, ConfirmedServiceError, Conformance, DataAccessResult, DataBlockResult,
+    DataBlockSA, GetDataResult, GetRequest, GetRequestNext, GetRequestNormal, GetRequestWithList,
+    GetResponse, GetResponseNormal, GetResponseWithDatablock, GetResponseWithList, InitiateError,
+    InitiateResponse, InvokeIdAndPriority, Notification, ReleaseError, ReleaseRequestInformation,
+    SetRequest, SetRequestNormal, SetRequestWithDatablock, SetRequestWithFirstDatablock,
+    SetResponse,
 };
+use crate::MAX_PDU_SIZE;
+use std::boxed::Box;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use std::vec::Vec;
 
+const DEFAULT_INVOKE_ID_AND_PRIORITY: InvokeIdAndPriority = 0xC1;
+// Association LN object of whichever association the request comes in.
+const CURRENT_ASSOCIATION_LN: [u8; 6] = [0, 0, 40, 0, 0, 255];
+// Notifications queued for `poll_notification` by default; the oldest are
+// dropped beyond.
+const DEFAULT_NOTIFICATION_QUEUE_LIMIT: usize = 64;
+// Octets the blocks of a long GET may add up to unless configured otherwise.
+const DEFAULT_MAX_LONG_TRANSFER_SIZE: usize = 64 * 1024;
+
+type NotificationCallback = Box<dyn FnMut(&Notification) + Send>;
+type LinkStateCallback = Box<dyn FnMut(LinkState) + Send>;
+
+// Whether the HDLC link to the server answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    Up,
+    Down,
+}
+// Whether a value read back is equivalent to the value written.
+type ValueTolerance = Box<dyn Fn(&CosemData, &CosemData) -> bool + Send>;
+
+// Outcome of `Client::write_verified`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteVerification {
+    pub descriptor: CosemAttributeDescriptor,
+    pub written: CosemData,
+    pub write_result: DataAccessResult,
+    // `None` when the write failed and the attribute was not read back.
+    pub read_back: Option<GetDataResult>,
+    pub verified: bool,
+}
+
+// Tolerance for date-times, e.g. a clock that kept running between the write
+// and the read back. Values that are not complete date-times must be equal.
+pub fn date_time_within(
+    max_deviation_seconds: u64,
+) -> impl Fn(&CosemData, &CosemData) -> bool + Send + 'static {
+    move |written, read_back| {
+        let seconds = |value: &CosemData| match value {
+            CosemData::DateTime(bytes) | CosemData::OctetString(bytes) => date_time_seconds(bytes),
+            _ => None,
+        };
+        match (seconds(written), seconds(read_back)) {
+            (Some(written), Some(read_back)) => {
+                written.abs_diff(read_back) <= max_deviation_seconds
+            }
+            _ => written == read_back,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ClientError<E> {
     AcseError,
     TransportError(E),
     DlmsError(DlmsError),
     SecurityError(SecurityError),
-    AssociationRejected { result: u8, diagnostic: u8 },
+    AssociationRejected {
+        result: u8,
+        diagnostic: AssociateSourceDiagnostic,
+    },
     NegotiationFailed(&'static str),
     ReleaseRejected(u8),
+    // The user-information of the RLRE does not fit the released association.
+    ReleaseMismatch(ReleaseError),
     AssociationNotEstablished,
+    // The server refused to return an attribute the call needs.
+    DataAccessError(DataAccessResult),
+    // The AARE refused the xDLMS context with a confirmed service error.
+    InitiateRejected(InitiateError),
+    // The request needs these conformance bits, which were not negotiated.
+    ServiceNotNegotiated(Conformance),
+    // The server refused f(StoC) of HLS or answered with a wrong f(CtoS).
+    AuthenticationFailed,
+    // The APDUs of a request take `size` octets, more than the `limit` the
+    // server receives; the server would drop the frame unanswered.
+    PduTooLarge {
+        size: usize,
+        limit: usize,
+    },
 }
 
 impl<E> From<DlmsError> for ClientError<E> {
@@ -40,6 +136,33 @@ pub struct Client<T: Transport> {
     key: Option<Vec<u8>>,
     association_parameters: AssociationParameters,
     negotiated_parameters: Option<NegotiatedAssociationParameters>,
+    calling_ap_title: Option<Vec<u8>>,
+    frame_counter: Option<FrameCounter>,
+    server_address: HdlcAddress,
+    parse_mode: ParseMode,
+    notifications: VecDeque<Notification>,
+    notification_queue_limit: usize,
+    // Notifications dropped from a full queue and unsolicited frames that
+    // held no notification.
+    dropped_notifications: u64,
+    on_notification: Option<NotificationCallback>,
+    // Restores the bodies of data-notifications pushed compressed.
+    #[cfg(feature = "compress")]
+    notification_compression: Option<Box<dyn Compression>>,
+    link_state: Option<LinkState>,
+    on_link_state_change: Option<LinkStateCallback>,
+    // Idle time after which `supervise_link` polls the server.
+    link_check_interval: Option<Duration>,
+    last_link_activity: Option<Instant>,
+    // Response frames received ahead of the request they answer, when the
+    // server returned several APDUs in one frame.
+    pending_responses: VecDeque<Vec<u8>>,
+    write_tolerances: Vec<(DataType, ValueTolerance)>,
+    // Sends requests the negotiated conformance does not cover instead of
+    // failing them with `ServiceNotNegotiated`.
+    force_services: bool,
+    max_long_transfer_size: usize,
+    tracer: Tracer,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,6 +173,14 @@ pub struct NegotiatedAssociationParameters {
     pub server_max_receive_pdu_size: u16,
 }
 
+// A server that answered an address scan. `negotiated` is `None` when it
+// refused the association, e.g. for lack of authentication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredDevice {
+    pub address: HdlcAddress,
+    pub negotiated: Option<NegotiatedAssociationParameters>,
+}
+
 impl<T: Transport> Client<T> {
     pub fn new(
         address: u16,
@@ -64,9 +195,36 @@ impl<T: Transport> Client<T> {
             key,
             association_parameters: AssociationParameters::default(),
             negotiated_parameters: None,
+            calling_ap_title: None,
+            frame_counter: None,
+            server_address: HdlcAddress::default(),
+            parse_mode: ParseMode::default(),
+            notifications: VecDeque::new(),
+            notification_queue_limit: DEFAULT_NOTIFICATION_QUEUE_LIMIT,
+            dropped_notifications: 0,
+            on_notification: None,
+            #[cfg(feature = "compress")]
+            notification_compression: None,
+            link_state: None,
+            on_link_state_change: None,
+            link_check_interval: None,
+            last_link_activity: None,
+            pending_responses: VecDeque::new(),
+            write_tolerances: Vec::new(),
+            force_services: false,
+            max_long_transfer_size: DEFAULT_MAX_LONG_TRANSFER_SIZE,
+            tracer: Tracer::default(),
         }
     }
 
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
     pub fn set_association_parameters(&mut self, params: AssociationParameters) {
         self.association_parameters = params;
         self.negotiated_parameters = None;
@@ -80,43 +238,242 @@ impl<T: Transport> Client<T> {
         self.negotiated_parameters.as_ref()
     }
 
+    // Escape hatch for servers that serve more than they negotiate.
+    pub fn set_force_services(&mut self, force: bool) {
+        self.force_services = force;
+    }
+
+    // Size the blocks of a long GET may add up to; a response growing beyond
+    // it is abandoned.
+    pub fn set_max_long_transfer_size(&mut self, size: usize) {
+        self.max_long_transfer_size = size;
+    }
+
+    // Destination of the request frames; the all-station address by default,
+    // set it to reach one drop of a multi-drop bus.
+    pub fn set_server_address(&mut self, server_address: HdlcAddress) {
+        self.server_address = server_address;
+    }
+
+    // How strictly responses are decoded; lenient by default.
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    // Sent as calling-AP-title in every AARQ; some meters require it for HLS.
+    pub fn set_calling_ap_title(&mut self, system_title: Option<Vec<u8>>) {
+        self.calling_ap_title = system_title;
+    }
+
+    // Ciphers the requests with nonces of the system title and invocation
+    // counter of `frame_counter` instead of random ones.
+    pub fn set_frame_counter(&mut self, frame_counter: Option<FrameCounter>) {
+        self.frame_counter = frame_counter;
+    }
+
+    // Data-notifications and event-notifications received while waiting for a
+    // response are handed to `callback`; without one they are queued for
+    // `poll_notification`.
+    pub fn on_notification<F>(&mut self, callback: F)
+    where
+        F: FnMut(&Notification) + Send + 'static,
+    {
+        self.on_notification = Some(Box::new(callback));
+    }
+
+    // Data-notification bodies received as an octet-string are decompressed,
+    // for servers pushing them as PUSH_MESSAGE_COMPRESSED_AXDR.
+    #[cfg(feature = "compress")]
+    pub fn set_notification_compression(&mut self, compression: Option<Box<dyn Compression>>) {
+        self.notification_compression = compression;
+    }
+
+    // Notifications `poll_notification` holds at most; once full the oldest
+    // make room for new ones.
+    pub fn set_notification_queue_limit(&mut self, limit: usize) {
+        self.notification_queue_limit = limit;
+        self.trim_notifications();
+    }
+
+    // Notifications lost to a full queue, and UI frames received that could
+    // not be decoded as notifications.
+    pub fn dropped_notifications(&self) -> u64 {
+        self.dropped_notifications
+    }
+
+    // Called with a record for each frame and APDU exchanged, tagged with the
+    // correlation id of the request.
+    pub fn on_trace<F>(&mut self, callback: F)
+    where
+        F: FnMut(&TraceRecord) + Send + 'static,
+    {
+        self.tracer.set_callback(Some(Box::new(callback)));
+    }
+
+    // Correlation id of the last request, blocks of a block transfer included.
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.tracer.correlation_id()
+    }
+
+    // Time the last request spent per phase.
+    pub fn timings(&self) -> PhaseTimings {
+        self.tracer.timings()
+    }
+
+    // Called with the new state whenever the link comes up or goes down, as
+    // seen by exchanges with the server and link checks.
+    pub fn on_link_state_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(LinkState) + Send + 'static,
+    {
+        self.on_link_state_change = Some(Box::new(callback));
+    }
+
+    pub fn link_state(&self) -> Option<LinkState> {
+        self.link_state
+    }
+
+    pub fn set_link_check_interval(&mut self, interval: Option<Duration>) {
+        self.link_check_interval = interval;
+    }
+
+    // Polls the server with an RR frame, which it answers with an RR carrying
+    // the final bit. A transport error, e.g. a read timeout, means the link is
+    // down and is not returned as an error.
+    pub fn check_link(&mut self) -> Result<LinkState, ClientError<T::Error>> {
+        self.tracer.begin();
+        let poll = HdlcFrame {
+            address: self.address,
+            control: receive_ready(0),
+            information: Vec::new(),
+            destination: self.server_address,
+        };
+        let poll_bytes = poll.to_bytes()?;
+        self.tracer.mark(TracePhase::Encode);
+        self.tracer.record(
+            TraceLayer::Hdlc,
+            format_args!("RR poll to {:?}", self.server_address),
+        );
+        let state = match self.send_and_receive(&poll_bytes) {
+            Ok(_) => LinkState::Up,
+            Err(ClientError::TransportError(_)) => LinkState::Down,
+            Err(err) => return Err(err),
+        };
+        self.set_link_state(state);
+        Ok(state)
+    }
+
+    pub fn supervise_link(&mut self) -> Result<Option<LinkState>, ClientError<T::Error>> {
+        self.supervise_link_at(Instant::now())
+    }
+
+    // Checks the link once it has been idle for the link check interval.
+    // `None` if no check was due.
+    pub fn supervise_link_at(
+        &mut self,
+        now: Instant,
+    ) -> Result<Option<LinkState>, ClientError<T::Error>> {
+        let Some(interval) = self.link_check_interval else {
+            return Ok(None);
+        };
+        if self
+            .last_link_activity
+            .is_some_and(|at| now < at + interval)
+        {
+            return Ok(None);
+        }
+        let state = self.check_link()?;
+        self.last_link_activity = Some(now);
+        Ok(Some(state))
+    }
+
+    fn set_link_state(&mut self, state: LinkState) {
+        if self.link_state.replace(state) == Some(state) {
+            return;
+        }
+        if let Some(callback) = self.on_link_state_change.as_mut() {
+            callback(state);
+        }
+    }
+
+    // Returns a queued notification, or waits for the next frame from the
+    // server, which must carry a notification. Returns `None` when the
+    // notification went to the `on_notification` callback.
+    pub fn poll_notification(&mut self) -> Result<Option<Notification>, ClientError<T::Error>> {
+        if let Some(notification) = self.notifications.pop_front() {
+            return Ok(Some(notification));
+        }
+        let frame = self.receive_frame()?;
+        HdlcFrame::from_bytes(&frame)?;
+        let responses = self.dispatch_frame(frame)?;
+        self.pending_responses.extend(responses);
+        match self.notifications.pop_front() {
+            Some(notification) => Ok(Some(notification)),
+            None if self.on_notification.is_some() => Ok(None),
+            None => Err(ClientError::DlmsError(DlmsError::Xdlms)),
+        }
+    }
+
+    // Probes the server addresses in turn with an association, released right
+    // away, and returns the devices that answered. Addresses nobody answers
+    // show as transport errors, so the transport should time out quickly.
+    // The server address and association of the client are kept.
+    pub fn scan<I>(&mut self, addresses: I) -> Vec<DiscoveredDevice>
+    where
+        I: IntoIterator<Item = HdlcAddress>,
+    {
+        let server_address = self.server_address;
+        let negotiated_parameters = self.negotiated_parameters.take();
+        let mut devices = Vec::new();
+        for address in addresses {
+            self.server_address = address;
+            self.pending_responses.clear();
+            let negotiated = match self.associate() {
+                Ok(_) => {
+                    let negotiated = self.negotiated_parameters.clone();
+                    // The device was found whether or not it confirms the
+                    // release.
+                    let _ = self.release();
+                    self.negotiated_parameters = None;
+                    negotiated
+                }
+                Err(ClientError::AssociationRejected { .. })
+                | Err(ClientError::InitiateRejected(_))
+                | Err(ClientError::NegotiationFailed(_)) => None,
+                // Silence, or a reply garbled on the bus.
+                Err(_) => continue,
+            };
+            devices.push(DiscoveredDevice {
+                address,
+                negotiated,
+            });
+        }
+        self.server_address = server_address;
+        self.negotiated_parameters = negotiated_parameters;
+        devices
+    }
+
     pub fn associate(&mut self) -> Result<AareApdu, ClientError<T::Error>> {
-        let initiate_request = self.association_parameters.to_initiate_request();
+        let mut initiate_request = self.association_parameters.to_initiate_request();
+        initiate_request.client_max_receive_pdu_size = self.receive_pdu_limit() as u16;
         let user_information = initiate_request.to_user_information()?;
 
         let mut aarq = AarqApdu {
             application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
             sender_acse_requirements: 0,
+            calling_ap_title: self.calling_ap_title.as_deref().map(ap_title),
             mechanism_name: None,
             calling_authentication_value: None,
             user_information: user_information.clone(),
+            ..Default::default()
         };
         if self.password.is_some() {
             aarq.mechanism_name = Some(b"LLS".to_vec());
         }
 
-        let request_bytes = aarq.to_bytes()?;
-
-        let hdlc_frame = HdlcFrame {
-            address: self.address,
-            control: 0,
-            information: request_bytes,
-        };
-
-        let hdlc_bytes = hdlc_frame.to_bytes()?;
-        let response_hdlc_bytes = self.send_and_receive(&hdlc_bytes)?;
-        let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
-        let aare = AareApdu::from_bytes(&response_frame.information)
-            .map_err(|_| ClientError::AcseError)?
-            .1;
-        let initiate_response = InitiateResponse::from_user_information(&aare.user_information)?;
-
-        if aare.result != 0 {
-            return Err(ClientError::AssociationRejected {
-                result: aare.result,
-                diagnostic: aare.result_source_diagnostic,
-            });
-        }
+        let aare = self.send_aarq(&aarq)?;
+        let initiate_response =
+            InitiateResponse::from_user_information_with(&aare.user_information, self.parse_mode)?;
 
         let preview_negotiated = self.verify_initiate_response(&initiate_response)?;
 
@@ -128,31 +485,17 @@ impl<T: Transport> Client<T> {
             let aarq = AarqApdu {
                 application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
                 sender_acse_requirements: 0,
+                calling_ap_title: self.calling_ap_title.as_deref().map(ap_title),
                 mechanism_name: Some(b"LLS".to_vec()),
                 calling_authentication_value: Some(response),
                 user_information,
+                ..Default::default()
             };
-
-            let request_bytes = aarq.to_bytes()?;
-            let hdlc_frame = HdlcFrame {
-                address: self.address,
-                control: 0,
-                information: request_bytes,
-            };
-            let hdlc_bytes = hdlc_frame.to_bytes()?;
-            let response_hdlc_bytes = self.send_and_receive(&hdlc_bytes)?;
-            let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
-            let aare = AareApdu::from_bytes(&response_frame.information)
-                .map_err(|_| ClientError::AcseError)?
-                .1;
-            if aare.result != 0 {
-                return Err(ClientError::AssociationRejected {
-                    result: aare.result,
-                    diagnostic: aare.result_source_diagnostic,
-                });
-            }
-            let initiate_response =
-                InitiateResponse::from_user_information(&aare.user_information)?;
+            let aare = self.send_aarq(&aarq)?;
+            let initiate_response = InitiateResponse::from_user_information_with(
+                &aare.user_information,
+                self.parse_mode,
+            )?;
             let negotiated = self.verify_initiate_response(&initiate_response)?;
             self.negotiated_parameters = Some(negotiated);
             return Ok(aare);
@@ -162,48 +505,428 @@ impl<T: Transport> Client<T> {
         Ok(aare)
     }
 
+    // Associates with an HLS mechanism and authenticates in pass 3 and 4 with
+    // reply_to_HLS_authentication of the current association. SHA-256 and
+    // GMAC take the calling AP title as system title of the client.
+    pub fn authenticate_hls(
+        &mut self,
+        mechanism: HlsMechanism,
+        secret: &[u8],
+    ) -> Result<AareApdu, ClientError<T::Error>> {
+        let mut initiate_request = self.association_parameters.to_initiate_request();
+        initiate_request.client_max_receive_pdu_size = self.receive_pdu_limit() as u16;
+        let client_challenge = generate_challenge();
+        let aarq = AarqApdu {
+            application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
+            sender_acse_requirements: 0x80,
+            calling_ap_title: self.calling_ap_title.as_deref().map(ap_title),
+            mechanism_name: Some(mechanism.mechanism_name()),
+            calling_authentication_value: Some(client_challenge.clone()),
+            user_information: initiate_request.to_user_information()?,
+            ..Default::default()
+        };
+        let aare = self.send_aarq(&aarq)?;
+        let initiate_response =
+            InitiateResponse::from_user_information_with(&aare.user_information, self.parse_mode)?;
+        let negotiated = self.verify_initiate_response(&initiate_response)?;
+        let server_challenge = aare
+            .responding_authentication_value
+            .as_deref()
+            .ok_or(ClientError::AuthenticationFailed)?;
+
+        let client_system_title = self.calling_ap_title.clone().unwrap_or_default();
+        let exchange = HlsExchange {
+            secret,
+            client_system_title: &client_system_title,
+            server_system_title: aare
+                .responding_ap_title
+                .as_deref()
+                .and_then(system_title)
+                .unwrap_or_default(),
+            client_challenge: &client_challenge,
+            server_challenge,
+        };
+        let invocation_counter = match self.frame_counter.as_mut() {
+            Some(counter) => counter.next_invocation_counter()?,
+            None => 0,
+        };
+        let answer = exchange.client_answer(&mechanism, invocation_counter)?;
+
+        // Until the server verified f(StoC) the association allows nothing but
+        // this ACTION.
+        self.negotiated_parameters = Some(negotiated);
+        let response = self.send_action_request(ActionRequest::Normal(ActionRequestNormal {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 15,
+                instance_id: CURRENT_ASSOCIATION_LN,
+                method_id: 1,
+            },
+            method_invocation_parameters: Some(CosemData::OctetString(answer)),
+        }));
+        let verified = matches!(&response, Ok(ActionResponse::Normal(response))
+            if response.single_response.result == ActionResult::Success
+                && matches!(response.single_response.return_data(),
+                    Some(CosemData::OctetString(server_answer))
+                        if exchange.verify_server_answer(&mechanism, server_answer)));
+        if !verified {
+            self.negotiated_parameters = None;
+            response?;
+            return Err(ClientError::AuthenticationFailed);
+        }
+        Ok(aare)
+    }
+
+    // Sends an AARQ and returns the AARE, which must accept the association.
+    fn send_aarq(&mut self, aarq: &AarqApdu) -> Result<AareApdu, ClientError<T::Error>> {
+        self.tracer.begin();
+        let response_bytes = self.exchange_apdu(aarq.to_bytes()?)?;
+        let aare = AareApdu::from_bytes_with(&response_bytes, self.parse_mode)
+            .map_err(|_| ClientError::AcseError)?
+            .1;
+        self.tracer.mark(TracePhase::Decode);
+        if aare.result != 0 {
+            return Err(Self::association_rejected(&aare));
+        }
+        Ok(aare)
+    }
+
     pub fn send_get_request(
         &mut self,
         request: GetRequest,
     ) -> Result<GetResponse, ClientError<T::Error>> {
-        if self.negotiated_parameters.is_none() {
-            return Err(ClientError::AssociationNotEstablished);
+        let service = match &request {
+            GetRequest::Normal(request) if request.access_selection.is_some() => {
+                Conformance::GET.union(&Conformance::SELECTIVE_ACCESS)
+            }
+            GetRequest::Normal(_) => Conformance::GET,
+            GetRequest::Next(_) => {
+                Conformance::GET.union(&Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ)
+            }
+            GetRequest::WithList(_) => Conformance::GET.union(&Conformance::MULTIPLE_REFERENCES),
+        };
+        self.require_service(&service)?;
+        self.tracer.begin();
+        let with_list = matches!(request, GetRequest::WithList(_));
+        let response_bytes = self.exchange_apdu(request.to_bytes()?)?;
+        let response = GetResponse::from_bytes_with(&response_bytes, self.parse_mode)?;
+        self.tracer.mark(TracePhase::Decode);
+
+        match response {
+            GetResponse::WithDataBlock(response) => {
+                self.receive_get_datablocks(response, with_list)
+            }
+            response => Ok(response),
         }
-        let request_bytes = request.to_bytes()?;
+    }
+
+    // Requests the remaining blocks of a long get and returns the reassembled
+    // value as a get-response-normal, or the reassembled results as a
+    // get-response-with-list when the request was one.
+    fn receive_get_datablocks(
+        &mut self,
+        first: GetResponseWithDatablock,
+        with_list: bool,
+    ) -> Result<GetResponse, ClientError<T::Error>> {
+        let invoke_id_and_priority = first.invoke_id_and_priority;
+        let mut block = first.result;
+        let mut expected_block_number = 1;
+        let limit = self.receive_pdu_limit();
+        let mut raw_data = Vec::new();
+        loop {
+            let data = match block.result {
+                DataBlockResult::RawData(data) => data,
+                // The server aborted the transfer with a data-access-result.
+                DataBlockResult::DataAccessResult(dar) => {
+                    return Ok(GetResponse::Normal(GetResponseNormal {
+                        invoke_id_and_priority,
+                        result: GetDataResult::DataAccessResult(dar),
+                    }))
+                }
+            };
+            if block.block_number != expected_block_number || data.len() > limit {
+                return Err(ClientError::DlmsError(DlmsError::Xdlms));
+            }
+            raw_data.extend_from_slice(&data);
+            if raw_data.len() > self.max_long_transfer_size {
+                return Err(ClientError::DlmsError(DlmsError::Xdlms));
+            }
+            if block.last_block {
+                break;
+            }
+
+            let next = GetRequest::Next(GetRequestNext {
+                invoke_id_and_priority,
+                block_number: block.block_number,
+            });
+            let response_bytes = self.exchange_apdu(next.to_bytes()?)?;
+            block = match GetResponse::from_bytes_with(&response_bytes, self.parse_mode)? {
+                GetResponse::WithDataBlock(response) => response.result,
+                GetResponse::Normal(response) => return Ok(GetResponse::Normal(response)),
+                GetResponse::WithList(_) => return Err(ClientError::DlmsError(DlmsError::Xdlms)),
+            };
+            expected_block_number += 1;
+        }
+
+        if with_list {
+            let (result, _) = decode_get_data_results(&raw_data, self.parse_mode)?;
+            return Ok(GetResponse::WithList(GetResponseWithList {
+                invoke_id_and_priority,
+                result,
+            }));
+        }
+        let (data, _) = decode_data_with(&raw_data, self.parse_mode)?;
+        Ok(GetResponse::Normal(GetResponseNormal {
+            invoke_id_and_priority,
+            result: GetDataResult::Data(data),
+        }))
+    }
 
+    // Sends the requests in one frame without waiting for each response; the
+    // server must accept them back to back.
+    pub fn get_pipelined(
+        &mut self,
+        requests: Vec<GetRequest>,
+    ) -> Result<Vec<GetResponse>, ClientError<T::Error>> {
+        self.require_service(&Conformance::GET)?;
+        self.tracer.begin();
+        let mut information = Vec::new();
+        for request in &requests {
+            information.extend(request.to_bytes()?);
+        }
+        self.tracer.record(
+            TraceLayer::Xdlms,
+            format_args!("{} get-requests pipelined", requests.len()),
+        );
+        self.check_request_size(&information)?;
         let hdlc_frame = HdlcFrame {
             address: self.address,
             control: 0,
-            information: request_bytes,
+            information,
+            destination: self.server_address,
         };
-
         let hdlc_bytes = hdlc_frame.to_bytes()?;
-        let response_hdlc_bytes = self.send_and_receive(&hdlc_bytes)?;
-        let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
-        let response = GetResponse::from_bytes(&response_frame.information)?;
+        self.tracer.mark(TracePhase::Encode);
+        let mut response_frames = vec![self.send_and_receive(&hdlc_bytes)?];
+        for _ in 1..requests.len() {
+            response_frames.push(self.receive_response()?);
+        }
+        self.tracer.mark(TracePhase::Transport);
+        // Long gets are completed once every pipelined response is in.
+        let mut responses = Vec::with_capacity(requests.len());
+        for (request, response_hdlc_bytes) in requests.iter().zip(response_frames) {
+            let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
+            let with_list = matches!(request, GetRequest::WithList(_));
+            responses.push(
+                match GetResponse::from_bytes_with(&response_frame.information, self.parse_mode)? {
+                    GetResponse::WithDataBlock(response) => {
+                        self.receive_get_datablocks(response, with_list)?
+                    }
+                    response => response,
+                },
+            );
+            self.tracer.mark(TracePhase::Decode);
+        }
+        Ok(responses)
+    }
 
-        Ok(response)
+    pub fn get_many(
+        &mut self,
+        descriptors: Vec<CosemAttributeDescriptor>,
+    ) -> Result<Vec<(CosemAttributeDescriptor, GetDataResult)>, ClientError<T::Error>> {
+        let Some(negotiated) = &self.negotiated_parameters else {
+            return Err(ClientError::AssociationNotEstablished);
+        };
+
+        if negotiated
+            .negotiated_conformance
+            .contains(&Conformance::MULTIPLE_REFERENCES)
+        {
+            let request = GetRequest::WithList(GetRequestWithList {
+                invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+                attribute_descriptor_list: descriptors.clone(),
+            });
+            let GetResponse::WithList(response) = self.send_get_request(request)? else {
+                return Err(ClientError::DlmsError(DlmsError::Xdlms));
+            };
+            if response.result.len() != descriptors.len() {
+                return Err(ClientError::DlmsError(DlmsError::Xdlms));
+            }
+            return Ok(descriptors.into_iter().zip(response.result).collect());
+        }
+
+        let mut results = Vec::with_capacity(descriptors.len());
+        for descriptor in descriptors {
+            let request = GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+                cosem_attribute_descriptor: descriptor.clone(),
+                access_selection: None,
+            });
+            let GetResponse::Normal(response) = self.send_get_request(request)? else {
+                return Err(ClientError::DlmsError(DlmsError::Xdlms));
+            };
+            results.push((descriptor, response.result));
+        }
+        Ok(results)
+    }
+
+    // Reads the value and scaler_unit of a register (class 3), extended
+    // register (class 4) or demand register (class 5, current average value).
+    pub fn read_scaled_value(
+        &mut self,
+        class_id: u16,
+        logical_name: [u8; 6],
+    ) -> Result<ScaledValue, ClientError<T::Error>> {
+        let (value, scaler_unit) = self.read_value_and_scaler_unit(class_id, logical_name)?;
+        ScaledValue::from_cosem_data(&value, &scaler_unit)
+            .ok_or(ClientError::DlmsError(DlmsError::Xdlms))
+    }
+
+    // Same as `read_scaled_value` at the given scaler, for registers of float
+    // values too: digits the scaler does not keep are rounded.
+    pub fn read_rounded_value(
+        &mut self,
+        class_id: u16,
+        logical_name: [u8; 6],
+        scaler: i8,
+        rounding: Rounding,
+    ) -> Result<ScaledValue, ClientError<T::Error>> {
+        let (value, scaler_unit) = self.read_value_and_scaler_unit(class_id, logical_name)?;
+        ScaledValue::from_cosem_data_rounded(&value, &scaler_unit, scaler, rounding)
+            .ok_or(ClientError::DlmsError(DlmsError::Xdlms))
+    }
+
+    fn read_value_and_scaler_unit(
+        &mut self,
+        class_id: u16,
+        logical_name: [u8; 6],
+    ) -> Result<(CosemData, CosemData), ClientError<T::Error>> {
+        let scaler_unit_attribute = match class_id {
+            3 | 4 => 3,
+            5 => 4,
+            _ => return Err(ClientError::DlmsError(DlmsError::Xdlms)),
+        };
+        let descriptors = [2, scaler_unit_attribute]
+            .into_iter()
+            .map(|attribute_id| CosemAttributeDescriptor {
+                class_id,
+                instance_id: logical_name,
+                attribute_id,
+            })
+            .collect();
+        let mut values = Vec::with_capacity(2);
+        for (_, result) in self.get_many(descriptors)? {
+            match result {
+                GetDataResult::Data(data) => values.push(data),
+                GetDataResult::DataAccessResult(result) => {
+                    return Err(ClientError::DataAccessError(result))
+                }
+            }
+        }
+        let scaler_unit = values
+            .pop()
+            .ok_or(ClientError::DlmsError(DlmsError::Xdlms))?;
+        let value = values
+            .pop()
+            .ok_or(ClientError::DlmsError(DlmsError::Xdlms))?;
+        Ok((value, scaler_unit))
+    }
+
+    // Values of the data type written by `write_verified` count as verified
+    // when `equivalent(written, read_back)` holds, not only when equal.
+    pub fn set_write_tolerance<F>(&mut self, data_type: DataType, equivalent: F)
+    where
+        F: Fn(&CosemData, &CosemData) -> bool + Send + 'static,
+    {
+        self.write_tolerances
+            .retain(|(existing, _)| *existing != data_type);
+        self.write_tolerances
+            .push((data_type, Box::new(equivalent)));
+    }
+
+    // Writes the attribute and reads it back to check the server took the
+    // value. A refused write is reported, not returned as an error.
+    pub fn write_verified(
+        &mut self,
+        descriptor: CosemAttributeDescriptor,
+        value: CosemData,
+    ) -> Result<WriteVerification, ClientError<T::Error>> {
+        let request = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            cosem_attribute_descriptor: descriptor.clone(),
+            access_selection: None,
+            value: value.clone(),
+        });
+        let write_result = match self.send_set_request(request)? {
+            SetResponse::Normal(response) => response.result,
+            SetResponse::LastDatablock(response) => response.result,
+            _ => return Err(ClientError::DlmsError(DlmsError::Xdlms)),
+        };
+        let mut verification = WriteVerification {
+            descriptor: descriptor.clone(),
+            written: value,
+            write_result,
+            read_back: None,
+            verified: false,
+        };
+        if verification.write_result != DataAccessResult::Success {
+            return Ok(verification);
+        }
+
+        let request = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            cosem_attribute_descriptor: descriptor,
+            access_selection: None,
+        });
+        let GetResponse::Normal(response) = self.send_get_request(request)? else {
+            return Err(ClientError::DlmsError(DlmsError::Xdlms));
+        };
+        verification.verified = match &response.result {
+            GetDataResult::Data(read_back) => {
+                *read_back == verification.written
+                    || self
+                        .write_tolerances
+                        .iter()
+                        .find(|(data_type, _)| *data_type == verification.written.data_type())
+                        .is_some_and(|(_, equivalent)| equivalent(&verification.written, read_back))
+            }
+            GetDataResult::DataAccessResult(_) => false,
+        };
+        verification.read_back = Some(response.result);
+        Ok(verification)
     }
 
     pub fn send_set_request(
         &mut self,
         request: SetRequest,
     ) -> Result<SetResponse, ClientError<T::Error>> {
-        if self.negotiated_parameters.is_none() {
-            return Err(ClientError::AssociationNotEstablished);
-        }
+        let service = match &request {
+            SetRequest::Normal(request) if request.access_selection.is_some() => {
+                Conformance::SET.union(&Conformance::SELECTIVE_ACCESS)
+            }
+            SetRequest::Normal(_) => Conformance::SET,
+            SetRequest::WithFirstDatablock(_) | SetRequest::WithDatablock(_) => {
+                Conformance::SET.union(&Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE)
+            }
+            SetRequest::WithList(_) => Conformance::SET.union(&Conformance::MULTIPLE_REFERENCES),
+        };
+        self.require_service(&service)?;
+        let limit = self.negotiated_parameters.as_ref().map_or(0, |negotiated| {
+            negotiated.server_max_receive_pdu_size as usize
+        });
+        self.tracer.begin();
         let request_bytes = request.to_bytes()?;
 
-        let hdlc_frame = HdlcFrame {
-            address: self.address,
-            control: 0,
-            information: request_bytes,
-        };
+        if request_bytes.len() > limit {
+            if let SetRequest::Normal(request) = request {
+                self.require_service(&Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE)?;
+                return self.send_set_request_with_datablocks(request, limit);
+            }
+        }
 
-        let hdlc_bytes = hdlc_frame.to_bytes()?;
-        let response_hdlc_bytes = self.send_and_receive(&hdlc_bytes)?;
-        let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
-        let response = SetResponse::from_bytes(&response_frame.information)?;
+        let response_bytes = self.exchange_apdu(request_bytes)?;
+        let response = SetResponse::from_bytes_with(&response_bytes, self.parse_mode)?;
+        self.tracer.mark(TracePhase::Decode);
 
         Ok(response)
     }
@@ -212,21 +935,32 @@ impl<T: Transport> Client<T> {
         &mut self,
         request: ActionRequest,
     ) -> Result<ActionResponse, ClientError<T::Error>> {
-        if self.negotiated_parameters.is_none() {
-            return Err(ClientError::AssociationNotEstablished);
-        }
+        let service = match &request {
+            ActionRequest::Normal(_) => Conformance::ACTION,
+            ActionRequest::WithFirstPblock(_) | ActionRequest::WithPblock(_) => {
+                Conformance::ACTION.union(&Conformance::BLOCK_TRANSFER_WITH_ACTION)
+            }
+            ActionRequest::WithList(_) => {
+                Conformance::ACTION.union(&Conformance::MULTIPLE_REFERENCES)
+            }
+        };
+        self.require_service(&service)?;
+        let limit = self.negotiated_parameters.as_ref().map_or(0, |negotiated| {
+            negotiated.server_max_receive_pdu_size as usize
+        });
+        self.tracer.begin();
         let request_bytes = request.to_bytes()?;
 
-        let hdlc_frame = HdlcFrame {
-            address: self.address,
-            control: 0,
-            information: request_bytes,
-        };
+        if request_bytes.len() > limit {
+            if let ActionRequest::Normal(request) = request {
+                self.require_service(&Conformance::BLOCK_TRANSFER_WITH_ACTION)?;
+                return self.send_action_request_with_pblocks(request, limit);
+            }
+        }
 
-        let hdlc_bytes = hdlc_frame.to_bytes()?;
-        let response_hdlc_bytes = self.send_and_receive(&hdlc_bytes)?;
-        let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
-        let response = ActionResponse::from_bytes(&response_frame.information)?;
+        let response_bytes = self.exchange_apdu(request_bytes)?;
+        let response = ActionResponse::from_bytes_with(&response_bytes, self.parse_mode)?;
+        self.tracer.mark(TracePhase::Decode);
 
         Ok(response)
     }
@@ -235,62 +969,441 @@ impl<T: Transport> Client<T> {
         if self.negotiated_parameters.is_none() {
             return Err(ClientError::AssociationNotEstablished);
         }
+        // A ciphered association is released with a ciphered InitiateRequest,
+        // which the server answers in kind.
+        let user_information = match &self.key {
+            Some(key) => Some(ReleaseRequestInformation::ciphered(
+                &self.association_parameters.to_initiate_request(),
+                key,
+            )?),
+            None => None,
+        };
+        self.tracer.begin();
         let release_req = ArlrqApdu {
             reason: Some(0),
-            user_information: None,
-        };
-
-        let hdlc_frame = HdlcFrame {
-            address: self.address,
-            control: 0,
-            information: release_req.to_bytes()?,
+            user_information,
         };
 
-        let hdlc_bytes = hdlc_frame.to_bytes()?;
-        let response_bytes = self.send_and_receive(&hdlc_bytes)?;
-        let response_frame = HdlcFrame::from_bytes(&response_bytes)?;
-        let rlre = ArlreApdu::from_bytes(&response_frame.information)
+        let response_bytes = self.exchange_apdu(release_req.to_bytes()?)?;
+        let rlre = ArlreApdu::from_bytes_with(&response_bytes, self.parse_mode)
             .map_err(|_| ClientError::AcseError)?
             .1;
+        self.tracer.mark(TracePhase::Decode);
 
         if let Some(reason) = rlre.reason {
             if reason != 0 {
                 return Err(ClientError::ReleaseRejected(reason));
             }
         }
+        // An InitiateResponse in the RLRE has to match the association it
+        // releases.
+        if let Some(user_information) = &rlre.user_information {
+            let response = user_information
+                .initiate_response(self.key.as_deref(), self.parse_mode)
+                .map_err(ClientError::ReleaseMismatch)?;
+            if let Some(negotiated) = &self.negotiated_parameters {
+                if response.negotiated_dlms_version_number
+                    != negotiated.negotiated_dlms_version_number
+                {
+                    return Err(ClientError::ReleaseMismatch(
+                        ReleaseError::DlmsVersionMismatch,
+                    ));
+                }
+                if response.negotiated_conformance != negotiated.negotiated_conformance {
+                    return Err(ClientError::ReleaseMismatch(
+                        ReleaseError::ConformanceMismatch,
+                    ));
+                }
+            }
+        }
 
         self.negotiated_parameters = None;
         Ok(())
     }
 
-    fn send_and_receive(&mut self, data: &[u8]) -> Result<Vec<u8>, ClientError<T::Error>> {
-        if let Some(key) = &self.key {
-            let encrypted_data = hls_encrypt(data, key)?;
-            self.transport
-                .send(&encrypted_data)
-                .map_err(ClientError::TransportError)?;
-            let encrypted_response = self
-                .transport
-                .receive()
-                .map_err(ClientError::TransportError)?;
-            Ok(hls_decrypt(&encrypted_response, key)?)
-        } else {
-            self.transport
-                .send(data)
-                .map_err(ClientError::TransportError)?;
-            self.transport
-                .receive()
-                .map_err(ClientError::TransportError)
-        }
-    }
-
-    fn verify_initiate_response(
-        &self,
-        response: &InitiateResponse,
-    ) -> Result<NegotiatedAssociationParameters, ClientError<T::Error>> {
-        if response.negotiated_dlms_version_number != self.association_parameters.dlms_version {
-            return Err(ClientError::NegotiationFailed("DLMS version mismatch"));
-        }
+    // Pushes a value that does not fit into the server PDU as a sequence of
+    // set-request-with-first-datablock / set-request-with-datablock APDUs.
+    fn send_set_request_with_datablocks(
+        &mut self,
+        request: SetRequestNormal,
+        limit: usize,
+    ) -> Result<SetResponse, ClientError<T::Error>> {
+        let mut raw_data = Vec::new();
+        encode_data(&request.value, &mut raw_data)?;
+
+        let empty_block = |block_number| DataBlockSA {
+            last_block: false,
+            block_number,
+            raw_data: Vec::new(),
+        };
+        let first_overhead = SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            cosem_attribute_descriptor: request.cosem_attribute_descriptor.clone(),
+            access_selection: request.access_selection.clone(),
+            datablock: empty_block(1),
+        })
+        .to_bytes()?
+        .len();
+        let next_overhead = SetRequest::WithDatablock(SetRequestWithDatablock {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            datablock: empty_block(2),
+        })
+        .to_bytes()?
+        .len();
+
+        let mut block_number = 1u32;
+        let mut offset = 0;
+        loop {
+            let overhead = if block_number == 1 {
+                first_overhead
+            } else {
+                next_overhead
+            };
+            // The empty block already accounts for one octet-string length byte;
+            // lengths up to a u16 PDU size need at most two more.
+            let capacity = limit.saturating_sub(overhead + 2);
+            if capacity == 0 {
+                return Err(ClientError::NegotiationFailed(
+                    "server PDU size too small for block transfer",
+                ));
+            }
+
+            let end = (offset + capacity).min(raw_data.len());
+            let last_block = end == raw_data.len();
+            let datablock = DataBlockSA {
+                last_block,
+                block_number,
+                raw_data: raw_data[offset..end].to_vec(),
+            };
+            let block_request = if block_number == 1 {
+                SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+                    invoke_id_and_priority: request.invoke_id_and_priority,
+                    cosem_attribute_descriptor: request.cosem_attribute_descriptor.clone(),
+                    access_selection: request.access_selection.clone(),
+                    datablock,
+                })
+            } else {
+                SetRequest::WithDatablock(SetRequestWithDatablock {
+                    invoke_id_and_priority: request.invoke_id_and_priority,
+                    datablock,
+                })
+            };
+
+            let response_bytes = self.exchange_apdu(block_request.to_bytes()?)?;
+            match SetResponse::from_bytes_with(&response_bytes, self.parse_mode)? {
+                SetResponse::Datablock(ack) => {
+                    if last_block || ack.block_number != block_number {
+                        return Err(ClientError::DlmsError(DlmsError::Xdlms));
+                    }
+                }
+                SetResponse::LastDatablock(response) => {
+                    if response.block_number != block_number {
+                        return Err(ClientError::DlmsError(DlmsError::Xdlms));
+                    }
+                    return Ok(SetResponse::LastDatablock(response));
+                }
+                other => return Ok(other),
+            }
+
+            offset = end;
+            block_number += 1;
+        }
+    }
+
+    // Pushes method invocation parameters that do not fit into the server PDU,
+    // e.g. a whole script table or passive calendar, as a sequence of
+    // action-request-with-first-pblock / action-request-with-pblock APDUs.
+    fn send_action_request_with_pblocks(
+        &mut self,
+        request: ActionRequestNormal,
+        limit: usize,
+    ) -> Result<ActionResponse, ClientError<T::Error>> {
+        let mut raw_data = Vec::new();
+        if let Some(parameters) = &request.method_invocation_parameters {
+            encode_data(parameters, &mut raw_data)?;
+        }
+
+        let empty_block = |block_number| DataBlockSA {
+            last_block: false,
+            block_number,
+            raw_data: Vec::new(),
+        };
+        let first_overhead = ActionRequest::WithFirstPblock(ActionRequestWithFirstPblock {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            cosem_method_descriptor: request.cosem_method_descriptor.clone(),
+            pblock: empty_block(1),
+        })
+        .to_bytes()?
+        .len();
+        let next_overhead = ActionRequest::WithPblock(ActionRequestWithPblock {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            pblock: empty_block(2),
+        })
+        .to_bytes()?
+        .len();
+
+        let mut block_number = 1u32;
+        let mut offset = 0;
+        loop {
+            let overhead = if block_number == 1 {
+                first_overhead
+            } else {
+                next_overhead
+            };
+            let capacity = limit.saturating_sub(overhead + 2);
+            if capacity == 0 {
+                return Err(ClientError::NegotiationFailed(
+                    "server PDU size too small for block transfer",
+                ));
+            }
+
+            let end = (offset + capacity).min(raw_data.len());
+            let last_block = end == raw_data.len();
+            let pblock = DataBlockSA {
+                last_block,
+                block_number,
+                raw_data: raw_data[offset..end].to_vec(),
+            };
+            let block_request = if block_number == 1 {
+                ActionRequest::WithFirstPblock(ActionRequestWithFirstPblock {
+                    invoke_id_and_priority: request.invoke_id_and_priority,
+                    cosem_method_descriptor: request.cosem_method_descriptor.clone(),
+                    pblock,
+                })
+            } else {
+                ActionRequest::WithPblock(ActionRequestWithPblock {
+                    invoke_id_and_priority: request.invoke_id_and_priority,
+                    pblock,
+                })
+            };
+
+            let response_bytes = self.exchange_apdu(block_request.to_bytes()?)?;
+            match ActionResponse::from_bytes_with(&response_bytes, self.parse_mode)? {
+                ActionResponse::NextPblock(ack) => {
+                    if last_block || ack.block_number != block_number {
+                        return Err(ClientError::DlmsError(DlmsError::Xdlms));
+                    }
+                }
+                // The result of the method, or the refusal of the transfer.
+                other => return Ok(other),
+            }
+
+            offset = end;
+            block_number += 1;
+        }
+    }
+
+    // The receive size proposed to the server, never more than the crate buffers.
+    fn receive_pdu_limit(&self) -> usize {
+        (self.association_parameters.max_receive_pdu_size as usize).min(MAX_PDU_SIZE)
+    }
+
+    // The server bounds the information field of a request frame by the size
+    // negotiated at association, all APDUs of the frame together. The HDLC
+    // header, FCS and ciphering around them, and the wrapper header around
+    // the frame, are stripped before the server checks the size, so they do
+    // not count against it.
+    fn check_request_size(&self, information: &[u8]) -> Result<(), ClientError<T::Error>> {
+        let Some(negotiated) = &self.negotiated_parameters else {
+            return Ok(());
+        };
+        let limit = negotiated.server_max_receive_pdu_size as usize;
+        if information.len() > limit {
+            return Err(ClientError::PduTooLarge {
+                size: information.len(),
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    fn exchange_apdu(&mut self, apdu: Vec<u8>) -> Result<Vec<u8>, ClientError<T::Error>> {
+        self.check_request_size(&apdu)?;
+        self.tracer.record(
+            TraceLayer::of_apdu(&apdu),
+            format_args!("{} sent, {} octets", apdu_name(&apdu), apdu.len()),
+        );
+        let hdlc_frame = HdlcFrame {
+            address: self.address,
+            control: 0,
+            information: apdu,
+            destination: self.server_address,
+        };
+
+        let hdlc_bytes = hdlc_frame.to_bytes()?;
+        self.tracer.mark(TracePhase::Encode);
+        self.tracer.record(
+            TraceLayer::Hdlc,
+            format_args!(
+                "frame to {:?}, {} octets",
+                self.server_address,
+                hdlc_bytes.len()
+            ),
+        );
+        let response_hdlc_bytes = self.send_and_receive(&hdlc_bytes)?;
+        self.tracer.mark(TracePhase::Transport);
+        let response_frame = HdlcFrame::from_bytes(&response_hdlc_bytes)?;
+        self.tracer.record(
+            TraceLayer::Hdlc,
+            format_args!("frame received, {} octets", response_hdlc_bytes.len()),
+        );
+        self.tracer.record(
+            TraceLayer::of_apdu(&response_frame.information),
+            format_args!(
+                "{} received, {} octets",
+                apdu_name(&response_frame.information),
+                response_frame.information.len()
+            ),
+        );
+        Ok(response_frame.information)
+    }
+
+    // Sends a frame and returns the response frame, setting aside the
+    // notifications the server sends in between.
+    fn send_and_receive(&mut self, data: &[u8]) -> Result<Vec<u8>, ClientError<T::Error>> {
+        if let Some(key) = &self.key {
+            let encrypted_data = match self.frame_counter.as_mut() {
+                Some(frame_counter) => frame_counter.encrypt(data, key)?,
+                None => hls_encrypt(data, key)?,
+            };
+            self.transport
+                .send(&encrypted_data)
+                .map_err(ClientError::TransportError)?;
+        } else {
+            self.transport
+                .send(data)
+                .map_err(ClientError::TransportError)?;
+        }
+        self.receive_response()
+    }
+
+    fn receive_response(&mut self) -> Result<Vec<u8>, ClientError<T::Error>> {
+        loop {
+            if let Some(response) = self.pending_responses.pop_front() {
+                return Ok(response);
+            }
+            let frame = self.receive_frame()?;
+            let responses = self.dispatch_frame(frame)?;
+            self.pending_responses.extend(responses);
+        }
+    }
+
+    // Hands the notifications in a received frame to the callback, or queues
+    // them, and returns one frame per response APDU it carries. UI frames
+    // carry notifications only and never fail the response awaited.
+    fn dispatch_frame(&mut self, bytes: Vec<u8>) -> Result<Vec<Vec<u8>>, ClientError<T::Error>> {
+        let Ok(frame) = HdlcFrame::from_bytes(&bytes) else {
+            return Ok(vec![bytes]);
+        };
+        if is_unnumbered_information(frame.control) {
+            for apdu in split_apdus(&frame.information) {
+                match self.decode_notification(apdu) {
+                    Ok(notification) => self.deliver_notification(notification),
+                    Err(_) => self.dropped_notifications += 1,
+                }
+            }
+            return Ok(Vec::new());
+        }
+        let apdus = split_apdus(&frame.information);
+        // Supervisory frames carry no APDU.
+        if apdus.len() <= 1 && !Notification::is_notification(&frame.information) {
+            return Ok(vec![bytes]);
+        }
+        let mut responses = Vec::new();
+        for apdu in apdus {
+            if Notification::is_notification(apdu) {
+                let notification = self.decode_notification(apdu)?;
+                self.deliver_notification(notification);
+            } else {
+                let response = HdlcFrame {
+                    information: apdu.to_vec(),
+                    ..frame.clone()
+                };
+                responses.push(response.to_bytes()?);
+            }
+        }
+        Ok(responses)
+    }
+
+    fn decode_notification(&self, apdu: &[u8]) -> Result<Notification, DlmsError> {
+        let notification = Notification::from_bytes(apdu)?;
+        #[cfg(feature = "compress")]
+        if let Some(compression) = self.notification_compression.as_deref() {
+            return decompress_notification(notification, compression);
+        }
+        Ok(notification)
+    }
+
+    fn deliver_notification(&mut self, notification: Notification) {
+        match &mut self.on_notification {
+            Some(callback) => callback(&notification),
+            None => {
+                self.notifications.push_back(notification);
+                self.trim_notifications();
+            }
+        }
+    }
+
+    fn trim_notifications(&mut self) {
+        let excess = self
+            .notifications
+            .len()
+            .saturating_sub(self.notification_queue_limit);
+        self.notifications.drain(..excess);
+        self.dropped_notifications += excess as u64;
+    }
+
+    fn receive_frame(&mut self) -> Result<Vec<u8>, ClientError<T::Error>> {
+        let response = match self.transport.receive() {
+            Ok(response) => response,
+            Err(err) => {
+                self.set_link_state(LinkState::Down);
+                return Err(ClientError::TransportError(err));
+            }
+        };
+        self.last_link_activity = Some(Instant::now());
+        self.set_link_state(LinkState::Up);
+        diagnostics::debug!("frame received, {} octets", response.len());
+        match &self.key {
+            Some(key) => Ok(hls_decrypt(&response, key)?),
+            None => Ok(response),
+        }
+    }
+
+    // A confirmed service error in the user-information tells why the xDLMS
+    // context was refused; otherwise the ACSE result and diagnostic are all.
+    fn association_rejected(aare: &AareApdu) -> ClientError<T::Error> {
+        match ConfirmedServiceError::from_user_information(&aare.user_information) {
+            Ok(ConfirmedServiceError::InitiateError(error)) => ClientError::InitiateRejected(error),
+            Err(_) => ClientError::AssociationRejected {
+                result: aare.result,
+                diagnostic: aare.result_source_diagnostic,
+            },
+        }
+    }
+
+    // Fails a request needing services outside the negotiated conformance
+    // before anything is sent, unless forced.
+    fn require_service(&self, service: &Conformance) -> Result<(), ClientError<T::Error>> {
+        let Some(negotiated) = &self.negotiated_parameters else {
+            return Err(ClientError::AssociationNotEstablished);
+        };
+        let missing = Conformance {
+            value: service.value & !negotiated.negotiated_conformance.value,
+        };
+        if missing.is_empty() || self.force_services {
+            return Ok(());
+        }
+        Err(ClientError::ServiceNotNegotiated(missing))
+    }
+
+    fn verify_initiate_response(
+        &self,
+        response: &InitiateResponse,
+    ) -> Result<NegotiatedAssociationParameters, ClientError<T::Error>> {
+        if response.negotiated_dlms_version_number != self.association_parameters.dlms_version {
+            return Err(ClientError::NegotiationFailed("DLMS version mismatch"));
+        }
 
         if response.negotiated_conformance.is_empty() {
             return Err(ClientError::NegotiationFailed("no negotiated conformance"));
@@ -325,7 +1438,1020 @@ impl<T: Transport> Client<T> {
             negotiated_quality_of_service: response.negotiated_quality_of_service,
             negotiated_dlms_version_number: response.negotiated_dlms_version_number,
             negotiated_conformance: response.negotiated_conformance.clone(),
-            server_max_receive_pdu_size: response.server_max_receive_pdu_size,
+            server_max_receive_pdu_size: response
+                .server_max_receive_pdu_size
+                .min(MAX_PDU_SIZE as u16),
         })
     }
 }
+
+// Receives the notifications a server pushes on a transport of their own, e.g.
+// a `UdpTransport` bound to the destination of a push setup. Every message is
+// a bare notification APDU.
+pub struct NotificationListener<T: Transport> {
+    transport: T,
+    #[cfg(feature = "compress")]
+    compression: Option<Box<dyn Compression>>,
+}
+
+impl<T: Transport> NotificationListener<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            #[cfg(feature = "compress")]
+            compression: None,
+        }
+    }
+
+    // Same as `Client::set_notification_compression`.
+    #[cfg(feature = "compress")]
+    pub fn set_compression(&mut self, compression: Option<Box<dyn Compression>>) {
+        self.compression = compression;
+    }
+
+    pub fn poll(&mut self) -> Result<Notification, ClientError<T::Error>> {
+        let apdu = self
+            .transport
+            .receive()
+            .map_err(ClientError::TransportError)?;
+        let notification = Notification::from_bytes(&apdu)?;
+        #[cfg(feature = "compress")]
+        if let Some(compression) = self.compression.as_deref() {
+            return Ok(decompress_notification(notification, compression)?);
+        }
+        Ok(notification)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::types::CosemData;
+    use crate::xdlms::{
+        ActionResponseNextPblock, ActionResponseNormal, ActionResponseWithOptionalData,
+        DataAccessResult, DataBlockG, DataBlockResult, DataNotification, GetResponseWithList,
+        ReleaseResponseInformation, SetResponseDatablock, SetResponseLastDatablock,
+    };
+
+    // Acknowledges every set datablock and action pblock and records the
+    // APDUs it received.
+    struct BlockAckTransport {
+        received: Vec<Vec<u8>>,
+    }
+
+    impl BlockAckTransport {
+        fn acknowledge_set(request: &[u8]) -> Result<Vec<u8>, ()> {
+            let response = match SetRequest::from_bytes(request).map_err(|_| ())? {
+                SetRequest::WithFirstDatablock(req) if !req.datablock.last_block => {
+                    SetResponse::Datablock(SetResponseDatablock {
+                        invoke_id_and_priority: req.invoke_id_and_priority,
+                        block_number: req.datablock.block_number,
+                    })
+                }
+                SetRequest::WithDatablock(req) if !req.datablock.last_block => {
+                    SetResponse::Datablock(SetResponseDatablock {
+                        invoke_id_and_priority: req.invoke_id_and_priority,
+                        block_number: req.datablock.block_number,
+                    })
+                }
+                SetRequest::WithDatablock(req) => {
+                    SetResponse::LastDatablock(SetResponseLastDatablock {
+                        invoke_id_and_priority: req.invoke_id_and_priority,
+                        result: DataAccessResult::Success,
+                        block_number: req.datablock.block_number,
+                    })
+                }
+                _ => return Err(()),
+            };
+            response.to_bytes().map_err(|_| ())
+        }
+
+        fn acknowledge_action(request: &[u8]) -> Result<Vec<u8>, ()> {
+            let response = match ActionRequest::from_bytes(request).map_err(|_| ())? {
+                ActionRequest::WithFirstPblock(req) if !req.pblock.last_block => {
+                    ActionResponse::NextPblock(ActionResponseNextPblock {
+                        invoke_id_and_priority: req.invoke_id_and_priority,
+                        block_number: req.pblock.block_number,
+                    })
+                }
+                ActionRequest::WithPblock(req) if !req.pblock.last_block => {
+                    ActionResponse::NextPblock(ActionResponseNextPblock {
+                        invoke_id_and_priority: req.invoke_id_and_priority,
+                        block_number: req.pblock.block_number,
+                    })
+                }
+                ActionRequest::WithPblock(req) => ActionResponse::Normal(ActionResponseNormal {
+                    invoke_id_and_priority: req.invoke_id_and_priority,
+                    single_response: ActionResponseWithOptionalData {
+                        result: ActionResult::Success,
+                        return_parameters: None,
+                    },
+                }),
+                _ => return Err(()),
+            };
+            response.to_bytes().map_err(|_| ())
+        }
+    }
+
+    impl Transport for BlockAckTransport {
+        type Error = ();
+
+        fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            let frame = HdlcFrame::from_bytes(bytes).map_err(|_| ())?;
+            self.received.push(frame.information);
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            let request = self.received.last().ok_or(())?;
+            let information = match request.first() {
+                Some(195) => Self::acknowledge_action(request)?,
+                _ => Self::acknowledge_set(request)?,
+            };
+            HdlcFrame {
+                address: 0x0001,
+                control: 0,
+                information,
+                ..Default::default()
+            }
+            .to_bytes()
+            .map_err(|_| ())
+        }
+    }
+
+    // Answers GET requests with the attribute id as value, or object-undefined
+    // for attribute 9, and counts the APDUs it received.
+    struct GetEchoTransport {
+        requests: Vec<GetRequest>,
+    }
+
+    impl GetEchoTransport {
+        fn result_for(descriptor: &CosemAttributeDescriptor) -> GetDataResult {
+            if descriptor.attribute_id == 9 {
+                GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined)
+            } else {
+                GetDataResult::Data(CosemData::Unsigned(descriptor.attribute_id as u8))
+            }
+        }
+    }
+
+    impl Transport for GetEchoTransport {
+        type Error = ();
+
+        fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            let frame = HdlcFrame::from_bytes(bytes).map_err(|_| ())?;
+            self.requests
+                .push(GetRequest::from_bytes(&frame.information).map_err(|_| ())?);
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            let response = match self.requests.last().ok_or(())? {
+                GetRequest::Normal(req) => GetResponse::Normal(GetResponseNormal {
+                    invoke_id_and_priority: req.invoke_id_and_priority,
+                    result: Self::result_for(&req.cosem_attribute_descriptor),
+                }),
+                GetRequest::WithList(req) => GetResponse::WithList(GetResponseWithList {
+                    invoke_id_and_priority: req.invoke_id_and_priority,
+                    result: req
+                        .attribute_descriptor_list
+                        .iter()
+                        .map(Self::result_for)
+                        .collect(),
+                }),
+                GetRequest::Next(_) => return Err(()),
+            };
+            HdlcFrame {
+                address: 0x0001,
+                control: 0,
+                information: response.to_bytes().map_err(|_| ())?,
+                ..Default::default()
+            }
+            .to_bytes()
+            .map_err(|_| ())
+        }
+    }
+
+    fn associated_client<X: Transport>(transport: X, conformance: Conformance) -> Client<X> {
+        let mut client = Client::new(0x0010, transport, None, None);
+        client.negotiated_parameters = Some(NegotiatedAssociationParameters {
+            negotiated_quality_of_service: None,
+            negotiated_dlms_version_number: 6,
+            negotiated_conformance: conformance,
+            server_max_receive_pdu_size: 64,
+        });
+        client
+    }
+
+    fn register_descriptors() -> Vec<CosemAttributeDescriptor> {
+        [2, 3, 9]
+            .iter()
+            .map(|&attribute_id| CosemAttributeDescriptor {
+                class_id: 3,
+                instance_id: [1, 0, 1, 8, 0, 255],
+                attribute_id,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn date_time_tolerance_accepts_a_running_clock() {
+        let at =
+            |second| CosemData::DateTime(vec![0x07, 0xEA, 10, 17, 6, 12, 0, second, 0, 0x80, 0, 0]);
+        let within = date_time_within(2);
+        assert!(within(&at(0), &at(2)));
+        assert!(!within(&at(0), &at(3)));
+        assert!(!within(&at(0), &CosemData::NullData));
+        assert!(within(&CosemData::Unsigned(1), &CosemData::Unsigned(1)));
+    }
+
+    #[test]
+    fn get_many_uses_with_list_when_multiple_references_negotiated() {
+        let conformance = Conformance::GET.union(&Conformance::MULTIPLE_REFERENCES);
+        let mut client = associated_client(
+            GetEchoTransport {
+                requests: Vec::new(),
+            },
+            conformance,
+        );
+
+        let results = client
+            .get_many(register_descriptors())
+            .expect("get_many failed");
+        assert_eq!(client.transport.requests.len(), 1);
+        assert!(matches!(
+            client.transport.requests[0],
+            GetRequest::WithList(_)
+        ));
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0.attribute_id, 2);
+        assert_eq!(results[0].1, GetDataResult::Data(CosemData::Unsigned(2)));
+        assert_eq!(
+            results[2].1,
+            GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined)
+        );
+    }
+
+    #[test]
+    fn get_many_falls_back_to_sequential_gets() {
+        let mut client = associated_client(
+            GetEchoTransport {
+                requests: Vec::new(),
+            },
+            Conformance::GET,
+        );
+
+        let results = client
+            .get_many(register_descriptors())
+            .expect("get_many failed");
+        assert_eq!(client.transport.requests.len(), 3);
+        assert!(client
+            .transport
+            .requests
+            .iter()
+            .all(|request| matches!(request, GetRequest::Normal(_))));
+        assert_eq!(results[1].1, GetDataResult::Data(CosemData::Unsigned(3)));
+        assert_eq!(
+            results[2].1,
+            GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined)
+        );
+    }
+
+    #[test]
+    fn oversized_set_is_split_into_datablocks() {
+        let mut client = associated_client(
+            BlockAckTransport {
+                received: Vec::new(),
+            },
+            Conformance::SET.union(&Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE),
+        );
+
+        let value = CosemData::OctetString(vec![0x5A; 120]);
+        let response = client
+            .send_set_request(SetRequest::Normal(SetRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 1,
+                    instance_id: [0, 0, 96, 1, 0, 255],
+                    attribute_id: 2,
+                },
+                access_selection: None,
+                value: value.clone(),
+            }))
+            .expect("block transfer failed");
+        assert!(matches!(
+            response,
+            SetResponse::LastDatablock(SetResponseLastDatablock {
+                result: DataAccessResult::Success,
+                ..
+            })
+        ));
+
+        let sent = &client.transport.received;
+        assert!(sent.len() > 1);
+        assert!(sent.iter().all(|apdu| apdu.len() <= 64));
+
+        let mut raw_data = Vec::new();
+        for (index, apdu) in sent.iter().enumerate() {
+            let datablock = match SetRequest::from_bytes(apdu).unwrap() {
+                SetRequest::WithFirstDatablock(req) => req.datablock,
+                SetRequest::WithDatablock(req) => req.datablock,
+                other => panic!("unexpected request {:?}", other),
+            };
+            assert_eq!(datablock.block_number, index as u32 + 1);
+            assert_eq!(datablock.last_block, index == sent.len() - 1);
+            raw_data.extend_from_slice(&datablock.raw_data);
+        }
+        let mut expected = Vec::new();
+        encode_data(&value, &mut expected).unwrap();
+        assert_eq!(raw_data, expected);
+    }
+
+    #[test]
+    fn oversized_action_is_split_into_pblocks() {
+        let mut client = associated_client(
+            BlockAckTransport {
+                received: Vec::new(),
+            },
+            Conformance::ACTION.union(&Conformance::BLOCK_TRANSFER_WITH_ACTION),
+        );
+
+        let parameters = CosemData::OctetString(vec![0xA5; 150]);
+        let response = client
+            .send_action_request(ActionRequest::Normal(ActionRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_method_descriptor: CosemMethodDescriptor {
+                    class_id: 9,
+                    instance_id: [0, 0, 10, 0, 0, 255],
+                    method_id: 1,
+                },
+                method_invocation_parameters: Some(parameters.clone()),
+            }))
+            .expect("block transfer failed");
+        assert!(matches!(
+            response,
+            ActionResponse::Normal(ActionResponseNormal {
+                single_response: ActionResponseWithOptionalData {
+                    result: ActionResult::Success,
+                    ..
+                },
+                ..
+            })
+        ));
+
+        let sent = &client.transport.received;
+        assert!(sent.len() > 1);
+        assert!(sent.iter().all(|apdu| apdu.len() <= 64));
+
+        let mut raw_data = Vec::new();
+        for (index, apdu) in sent.iter().enumerate() {
+            let pblock = match ActionRequest::from_bytes(apdu).unwrap() {
+                ActionRequest::WithFirstPblock(req) => req.pblock,
+                ActionRequest::WithPblock(req) => req.pblock,
+                other => panic!("unexpected request {:?}", other),
+            };
+            assert_eq!(pblock.block_number, index as u32 + 1);
+            assert_eq!(pblock.last_block, index == sent.len() - 1);
+            raw_data.extend_from_slice(&pblock.raw_data);
+        }
+        let mut expected = Vec::new();
+        encode_data(&parameters, &mut expected).unwrap();
+        assert_eq!(raw_data, expected);
+    }
+
+    #[test]
+    fn services_outside_negotiated_conformance_fail_before_sending() {
+        let oversized_set = || {
+            SetRequest::Normal(SetRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 1,
+                    instance_id: [0, 0, 96, 1, 0, 255],
+                    attribute_id: 2,
+                },
+                access_selection: None,
+                value: CosemData::OctetString(vec![0x5A; 120]),
+            })
+        };
+        let mut client = associated_client(
+            BlockAckTransport {
+                received: Vec::new(),
+            },
+            Conformance::GET,
+        );
+        assert!(matches!(
+            client.send_set_request(oversized_set()),
+            Err(ClientError::ServiceNotNegotiated(missing)) if missing == Conformance::SET
+        ));
+
+        client
+            .negotiated_parameters
+            .as_mut()
+            .unwrap()
+            .negotiated_conformance = Conformance::SET;
+        assert!(matches!(
+            client.send_set_request(oversized_set()),
+            Err(ClientError::ServiceNotNegotiated(missing))
+                if missing == Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE
+        ));
+        assert!(client.transport.received.is_empty());
+
+        client.set_force_services(true);
+        assert!(client.send_set_request(oversized_set()).is_ok());
+        assert!(!client.transport.received.is_empty());
+    }
+
+    #[test]
+    fn requests_larger_than_the_server_pdu_are_not_sent() {
+        let mut client = associated_client(
+            GetEchoTransport {
+                requests: Vec::new(),
+            },
+            Conformance::GET.union(&Conformance::MULTIPLE_REFERENCES),
+        );
+        // 4 octets of header and 10 per descriptor with its selection flag.
+        let descriptors = vec![register_descriptors()[0].clone(); 7];
+        let request = GetRequest::WithList(GetRequestWithList {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            attribute_descriptor_list: descriptors.clone(),
+        });
+        assert!(matches!(
+            client.send_get_request(request),
+            Err(ClientError::PduTooLarge {
+                size: 74,
+                limit: 64
+            })
+        ));
+
+        // Pipelined requests share the frame, and the limit.
+        let requests = descriptors
+            .into_iter()
+            .map(|descriptor| {
+                GetRequest::Normal(GetRequestNormal {
+                    invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+                    cosem_attribute_descriptor: descriptor,
+                    access_selection: None,
+                })
+            })
+            .collect();
+        assert!(matches!(
+            client.get_pipelined(requests),
+            Err(ClientError::PduTooLarge {
+                size: 91,
+                limit: 64
+            })
+        ));
+        assert!(client.transport.requests.is_empty());
+
+        let fitting = GetRequest::WithList(GetRequestWithList {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            attribute_descriptor_list: register_descriptors(),
+        });
+        assert!(client.send_get_request(fitting).is_ok());
+    }
+
+    #[test]
+    fn negotiated_pdu_sizes_are_clamped_to_max_pdu_size() {
+        let client = Client::new(
+            0x0010,
+            GetEchoTransport {
+                requests: Vec::new(),
+            },
+            None,
+            None,
+        );
+        let mut response = AssociationParameters::default()
+            .to_initiate_response(AssociationParameters::default().conformance);
+        for (advertised, negotiated) in [
+            (MAX_PDU_SIZE as u16 - 1, MAX_PDU_SIZE as u16 - 1),
+            (MAX_PDU_SIZE as u16, MAX_PDU_SIZE as u16),
+            (MAX_PDU_SIZE as u16 + 1, MAX_PDU_SIZE as u16),
+            (u16::MAX, MAX_PDU_SIZE as u16),
+        ] {
+            response.server_max_receive_pdu_size = advertised;
+            let parameters = client.verify_initiate_response(&response).unwrap();
+            assert_eq!(parameters.server_max_receive_pdu_size, negotiated);
+        }
+        response.server_max_receive_pdu_size = 0;
+        assert!(client.verify_initiate_response(&response).is_err());
+    }
+
+    #[test]
+    fn get_many_reassembles_a_with_list_response_sent_in_datablocks() {
+        let results = vec![
+            GetDataResult::Data(CosemData::OctetString(vec![0x11; 40])),
+            GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined),
+            GetDataResult::Data(CosemData::DoubleLongUnsigned(7)),
+        ];
+        let encoded = GetResponse::WithList(GetResponseWithList {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            result: results.clone(),
+        })
+        .to_bytes()
+        .unwrap();
+        // The raw data is the SEQUENCE OF Get-Data-Result after the header.
+        let raw_data = &encoded[3..];
+        let block = |block_number: u32, last_block, raw_data: &[u8]| {
+            frame(
+                GetResponse::WithDataBlock(GetResponseWithDatablock {
+                    invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+                    result: DataBlockG {
+                        last_block,
+                        block_number,
+                        result: DataBlockResult::RawData(raw_data.to_vec()),
+                    },
+                })
+                .to_bytes()
+                .unwrap(),
+            )
+        };
+        let (first, second) = raw_data.split_at(20);
+        let mut client = associated_client(
+            ScriptedTransport {
+                frames: VecDeque::from([block(1, false, first), block(2, true, second)]),
+            },
+            Conformance::GET
+                .union(&Conformance::MULTIPLE_REFERENCES)
+                .union(&Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ),
+        );
+
+        let read = client
+            .get_many(register_descriptors())
+            .expect("get_many failed");
+        assert_eq!(
+            read.into_iter()
+                .map(|(_, result)| result)
+                .collect::<Vec<_>>(),
+            results
+        );
+    }
+
+    #[test]
+    fn get_datablock_larger_than_receive_limit_is_rejected() {
+        let mut client = associated_client(
+            GetEchoTransport {
+                requests: Vec::new(),
+            },
+            Conformance::GET,
+        );
+        client.association_parameters.max_receive_pdu_size = u16::MAX;
+        assert_eq!(client.receive_pdu_limit(), MAX_PDU_SIZE);
+
+        client.association_parameters.max_receive_pdu_size = 16;
+        let first = GetResponseWithDatablock {
+            invoke_id_and_priority: 0xC1,
+            result: DataBlockG {
+                last_block: true,
+                block_number: 1,
+                result: DataBlockResult::RawData(vec![0x00; 17]),
+            },
+        };
+        assert!(client.receive_get_datablocks(first, false).is_err());
+        assert!(client.transport.requests.is_empty());
+    }
+
+    #[test]
+    fn get_datablocks_beyond_the_transfer_limit_are_abandoned() {
+        let raw_data = {
+            let mut bytes = Vec::new();
+            encode_data(&CosemData::OctetString(vec![0x11; 38]), &mut bytes).unwrap();
+            bytes
+        };
+        let (first, second) = raw_data.split_at(20);
+        let block = |block_number: u32, last_block, raw_data: &[u8]| GetResponseWithDatablock {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            result: DataBlockG {
+                last_block,
+                block_number,
+                result: DataBlockResult::RawData(raw_data.to_vec()),
+            },
+        };
+        let client_with_limit = |size| {
+            let second = GetResponse::WithDataBlock(block(2, true, second));
+            let mut client = associated_client(
+                ScriptedTransport {
+                    frames: VecDeque::from([frame(second.to_bytes().unwrap())]),
+                },
+                Conformance::GET.union(&Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ),
+            );
+            client.set_max_long_transfer_size(size);
+            client
+        };
+
+        let mut client = client_with_limit(raw_data.len() - 1);
+        assert!(client
+            .receive_get_datablocks(block(1, false, first), false)
+            .is_err());
+        let mut client = client_with_limit(raw_data.len());
+        assert!(matches!(
+            client.receive_get_datablocks(block(1, false, first), false),
+            Ok(GetResponse::Normal(response))
+                if response.result == GetDataResult::Data(CosemData::OctetString(vec![0x11; 38]))
+        ));
+    }
+
+    // Replays the queued frames whatever is sent.
+    struct ScriptedTransport {
+        frames: VecDeque<Vec<u8>>,
+    }
+
+    impl Transport for ScriptedTransport {
+        type Error = ();
+
+        fn send(&mut self, _bytes: &[u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.frames.pop_front().ok_or(())
+        }
+    }
+
+    fn frame(information: Vec<u8>) -> Vec<u8> {
+        HdlcFrame {
+            address: 0x0001,
+            control: 0,
+            information,
+            ..Default::default()
+        }
+        .to_bytes()
+        .unwrap()
+    }
+
+    fn data_notification(invoke_id: u32) -> Notification {
+        Notification::Data(DataNotification {
+            long_invoke_id_and_priority: invoke_id,
+            date_time: None,
+            notification_body: CosemData::DoubleLongUnsigned(invoke_id),
+        })
+    }
+
+    fn get_register_request() -> GetRequest {
+        GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            cosem_attribute_descriptor: register_descriptors().remove(0),
+            access_selection: None,
+        })
+    }
+
+    fn notification_then_response() -> ScriptedTransport {
+        let response = GetResponse::Normal(GetResponseNormal {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            result: GetDataResult::Data(CosemData::Unsigned(2)),
+        });
+        ScriptedTransport {
+            frames: VecDeque::from([
+                frame(data_notification(1).to_bytes().unwrap()),
+                frame(response.to_bytes().unwrap()),
+                frame(data_notification(2).to_bytes().unwrap()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn notifications_between_exchanges_are_queued() {
+        let mut client = associated_client(notification_then_response(), Conformance::GET);
+
+        let GetResponse::Normal(response) =
+            client.send_get_request(get_register_request()).unwrap()
+        else {
+            panic!("expected get-response-normal");
+        };
+        assert_eq!(response.result, GetDataResult::Data(CosemData::Unsigned(2)));
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(1))
+        );
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(2))
+        );
+        assert!(client.poll_notification().is_err());
+    }
+
+    #[test]
+    fn notifications_are_handed_to_callback() {
+        let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut client = associated_client(notification_then_response(), Conformance::GET);
+        let sink = std::sync::Arc::clone(&received);
+        client.on_notification(move |notification| {
+            sink.lock().unwrap().push(notification.clone());
+        });
+
+        assert!(client.send_get_request(get_register_request()).is_ok());
+        assert_eq!(client.poll_notification().unwrap(), None);
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![data_notification(1), data_notification(2)]
+        );
+    }
+
+    #[test]
+    fn release_response_must_match_the_association() {
+        let release = |user_information| {
+            let rlre = ArlreApdu {
+                reason: Some(0),
+                user_information: Some(user_information),
+            };
+            let transport = ScriptedTransport {
+                frames: VecDeque::from([frame(rlre.to_bytes().unwrap())]),
+            };
+            let mut client = associated_client(transport, Conformance::GET);
+            let result = client.release();
+            (result, client.negotiated_parameters.is_none())
+        };
+        let response = |conformance| {
+            ReleaseResponseInformation::Initiate(
+                AssociationParameters::default().to_initiate_response(conformance),
+            )
+        };
+
+        assert!(matches!(
+            release(response(Conformance::GET)),
+            (Ok(()), true)
+        ));
+        assert!(matches!(
+            release(response(Conformance::SET)),
+            (
+                Err(ClientError::ReleaseMismatch(
+                    ReleaseError::ConformanceMismatch
+                )),
+                false
+            )
+        ));
+        assert!(matches!(
+            release(ReleaseResponseInformation::Ciphered(vec![0x5A; 40])),
+            (
+                Err(ClientError::ReleaseMismatch(
+                    ReleaseError::CipheringMismatch
+                )),
+                false
+            )
+        ));
+    }
+
+    #[test]
+    fn unsolicited_frames_do_not_disturb_the_response() {
+        let unsolicited = |information: Vec<u8>| {
+            HdlcFrame {
+                address: 0x0001,
+                control: 0x13,
+                information,
+                ..Default::default()
+            }
+            .to_bytes()
+            .unwrap()
+        };
+        let response = GetResponse::Normal(GetResponseNormal {
+            invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+            result: GetDataResult::Data(CosemData::Unsigned(2)),
+        });
+        let transport = ScriptedTransport {
+            frames: VecDeque::from([
+                unsolicited(data_notification(1).to_bytes().unwrap()),
+                unsolicited(vec![0x0F, 0x00]),
+                unsolicited(data_notification(2).to_bytes().unwrap()),
+                unsolicited(data_notification(3).to_bytes().unwrap()),
+                frame(response.to_bytes().unwrap()),
+            ]),
+        };
+        let mut client = associated_client(transport, Conformance::GET);
+        client.set_notification_queue_limit(2);
+
+        let GetResponse::Normal(response) =
+            client.send_get_request(get_register_request()).unwrap()
+        else {
+            panic!("expected get-response-normal");
+        };
+        assert_eq!(response.result, GetDataResult::Data(CosemData::Unsigned(2)));
+        // The truncated notification and the oldest one are accounted for.
+        assert_eq!(client.dropped_notifications(), 2);
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(2))
+        );
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(3))
+        );
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn compressed_notifications_are_restored() {
+        use crate::compression::{compress_notification, Deflate};
+        let Notification::Data(notification) = data_notification(5) else {
+            unreachable!();
+        };
+        let compressed = compress_notification(&notification, &Deflate::new()).unwrap();
+        let transport = ScriptedTransport {
+            frames: VecDeque::from([
+                frame(compressed.to_bytes().unwrap()),
+                frame(data_notification(6).to_bytes().unwrap()),
+            ]),
+        };
+        let mut client = associated_client(transport, Conformance::GET);
+        client.set_notification_compression(Some(Box::new(Deflate::new())));
+
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(5))
+        );
+        // Bodies not sent compressed pass unchanged.
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(6))
+        );
+    }
+
+    #[test]
+    fn apdus_sharing_a_frame_are_all_delivered() {
+        let responses: Vec<GetResponse> = [2, 3]
+            .map(|value| {
+                GetResponse::Normal(GetResponseNormal {
+                    invoke_id_and_priority: DEFAULT_INVOKE_ID_AND_PRIORITY,
+                    result: GetDataResult::Data(CosemData::Unsigned(value)),
+                })
+            })
+            .into();
+        let mut information = data_notification(1).to_bytes().unwrap();
+        for response in &responses {
+            information.extend(response.to_bytes().unwrap());
+        }
+        let mut client = associated_client(
+            ScriptedTransport {
+                frames: VecDeque::from([frame(information)]),
+            },
+            Conformance::GET,
+        );
+
+        assert_eq!(
+            client
+                .get_pipelined(vec![get_register_request(), get_register_request()])
+                .unwrap(),
+            responses
+        );
+        assert_eq!(
+            client.poll_notification().unwrap(),
+            Some(data_notification(1))
+        );
+    }
+
+    #[test]
+    fn notification_listener_decodes_bare_apdus() {
+        let mut listener = NotificationListener::new(ScriptedTransport {
+            frames: VecDeque::from([data_notification(7).to_bytes().unwrap(), vec![0xC4]]),
+        });
+        assert_eq!(listener.poll().unwrap(), data_notification(7));
+        assert!(listener.poll().is_err());
+    }
+
+    // A meter authenticating with HLS. It answers f(StoC) with f(CtoS), or
+    // with garbage when `forged`.
+    struct HlsMeter {
+        mechanism: HlsMechanism,
+        secret: Vec<u8>,
+        forged: bool,
+        client: Option<(Vec<u8>, Vec<u8>)>,
+        responses: VecDeque<Vec<u8>>,
+    }
+
+    const SERVER_CHALLENGE: &[u8] = b"P6wRJ21F";
+
+    impl Transport for HlsMeter {
+        type Error = ();
+
+        fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            let request = HdlcFrame::from_bytes(bytes).map_err(|_| ())?.information;
+            let response = if let Ok((_, aarq)) = AarqApdu::from_bytes(&request) {
+                let client_system_title = aarq
+                    .calling_ap_title
+                    .as_deref()
+                    .and_then(system_title)
+                    .unwrap_or_default();
+                self.client = Some((
+                    client_system_title.to_vec(),
+                    aarq.calling_authentication_value.ok_or(())?,
+                ));
+                let initiate_response = InitiateResponse {
+                    negotiated_quality_of_service: None,
+                    negotiated_dlms_version_number: 6,
+                    negotiated_conformance: Conformance::ACTION,
+                    server_max_receive_pdu_size: 1024,
+                    vaa_name: 0x0007,
+                };
+                AareApdu {
+                    application_context_name: aarq.application_context_name,
+                    result_source_diagnostic: AssociateSourceDiagnostic::AUTHENTICATION_REQUIRED,
+                    responding_ap_title: Some(ap_title(b"SERVER01")),
+                    responding_authentication_value: Some(SERVER_CHALLENGE.to_vec()),
+                    user_information: initiate_response.to_user_information().unwrap(),
+                    ..Default::default()
+                }
+                .to_bytes()
+                .unwrap()
+            } else {
+                let ActionRequest::Normal(action) =
+                    ActionRequest::from_bytes(&request).map_err(|_| ())?
+                else {
+                    return Err(());
+                };
+                let (client_system_title, client_challenge) = self.client.as_ref().ok_or(())?;
+                let exchange = HlsExchange {
+                    secret: &self.secret,
+                    client_system_title,
+                    server_system_title: b"SERVER01",
+                    client_challenge,
+                    server_challenge: SERVER_CHALLENGE,
+                };
+                let verified = matches!(&action.method_invocation_parameters,
+                    Some(CosemData::OctetString(answer))
+                        if exchange.verify_client_answer(&self.mechanism, answer));
+                let single_response = if verified {
+                    let mut answer = exchange.server_answer(&self.mechanism, 7).unwrap();
+                    if self.forged {
+                        answer[0] ^= 0xFF;
+                    }
+                    ActionResponseWithOptionalData {
+                        result: ActionResult::Success,
+                        return_parameters: Some(GetDataResult::Data(CosemData::OctetString(
+                            answer,
+                        ))),
+                    }
+                } else {
+                    ActionResponseWithOptionalData {
+                        result: ActionResult::ReadWriteDenied,
+                        return_parameters: None,
+                    }
+                };
+                ActionResponse::Normal(ActionResponseNormal {
+                    invoke_id_and_priority: action.invoke_id_and_priority,
+                    single_response,
+                })
+                .to_bytes()
+                .unwrap()
+            };
+            self.responses.push_back(frame(response));
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.responses.pop_front().ok_or(())
+        }
+    }
+
+    fn hls_client(mechanism: HlsMechanism, secret: &[u8], forged: bool) -> Client<HlsMeter> {
+        let meter = HlsMeter {
+            mechanism,
+            secret: secret.to_vec(),
+            forged,
+            client: None,
+            responses: VecDeque::new(),
+        };
+        let mut client = Client::new(0x10, meter, None, None);
+        client.set_calling_ap_title(Some(b"CLIENT01".to_vec()));
+        client
+    }
+
+    #[test]
+    fn authenticate_hls_runs_pass_three_and_four_with_every_mechanism() {
+        let key = [0x5A; 16];
+        let mechanisms = [
+            HlsMechanism::Md5,
+            HlsMechanism::Sha1,
+            HlsMechanism::Sha256,
+            HlsMechanism::Gmac {
+                authentication_key: vec![0xD0; 16],
+            },
+        ];
+        for mechanism in mechanisms {
+            let mut client = hls_client(mechanism.clone(), &key, false);
+            let aare = client.authenticate_hls(mechanism.clone(), &key).unwrap();
+            assert_eq!(
+                aare.responding_authentication_value.as_deref(),
+                Some(SERVER_CHALLENGE)
+            );
+            assert!(client.negotiated_parameters().is_some());
+
+            // The meter refuses f(StoC) made with another secret.
+            let mut client = hls_client(mechanism.clone(), &key, false);
+            assert!(matches!(
+                client.authenticate_hls(mechanism.clone(), &[0xA5; 16]),
+                Err(ClientError::AuthenticationFailed)
+            ));
+            assert!(client.negotiated_parameters().is_none());
+
+            // The client refuses a wrong f(CtoS).
+            let mut client = hls_client(mechanism.clone(), &key, true);
+            assert!(matches!(
+                client.authenticate_hls(mechanism, &key),
+                Err(ClientError::AuthenticationFailed)
+            ));
+            assert!(matches!(
+                client.release(),
+                Err(ClientError::AssociationNotEstablished)
+            ));
+        }
+    }
+}
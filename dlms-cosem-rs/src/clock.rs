@@ -1,22 +1,44 @@
-use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
-use crate::cosem_object::{
-    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
-};
-use crate::types::CosemData;
+use crate::cosem_object::{AttributeSpec, CosemObjectCallbackHandlers};
+use crate::types::{status_flags, CosemData, DataType};
+use dlms_cosem_derive::cosem_object;
+use std::fmt;
 use std::sync::Arc;
 
+#[cosem_object(
+    class_id = 8,
+    methods(4 = adjust_to_preset_time, 5 = preset_adjusting_time),
+    specs = attribute_specs
+)]
 #[derive(Debug)]
 pub struct Clock {
+    #[attribute(2, read_write)]
     time: CosemData,
+    #[attribute(3, read_write)]
     time_zone: CosemData,
+    #[attribute(4, read)]
     status: CosemData,
+    #[attribute(5, read_write)]
     daylight_savings_begin: CosemData,
+    #[attribute(6, read_write)]
     daylight_savings_end: CosemData,
+    #[attribute(7, read_write)]
     daylight_savings_deviation: CosemData,
+    #[attribute(8, read_write)]
     enabled: CosemData,
+    // Time preset by preset_adjusting_time (method 5), waiting for
+    // adjust_to_preset_time (method 4).
+    preset: Option<PresetTime>,
+    #[callbacks]
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+struct PresetTime {
+    time: CosemData,
+    validity_start: u64,
+    validity_end: u64,
+}
+
 impl Clock {
     pub fn new() -> Self {
         Self {
@@ -27,6 +49,7 @@ impl Clock {
             daylight_savings_end: CosemData::NullData,
             daylight_savings_deviation: CosemData::NullData,
             enabled: CosemData::NullData,
+            preset: None,
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
@@ -34,6 +57,65 @@ impl Clock {
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
+
+    // `None` while the status is not set.
+    pub fn clock_status(&self) -> Option<ClockStatus> {
+        ClockStatus::from_cosem_data(&self.status)
+    }
+
+    pub fn set_clock_status(&mut self, status: ClockStatus) {
+        self.status = status.to_cosem_data();
+    }
+
+    // Method 5: structure of preset_time, validity_interval_start and
+    // validity_interval_end, date-times without wildcards. Replaces an earlier
+    // preset.
+    fn preset_adjusting_time(&mut self, data: CosemData) -> Option<CosemData> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        let [time, start, end] = <[CosemData; 3]>::try_from(fields).ok()?;
+        date_time_value_seconds(&time)?;
+        let validity_start = date_time_value_seconds(&start)?;
+        let validity_end = date_time_value_seconds(&end)?;
+        if validity_start > validity_end {
+            return None;
+        }
+        self.preset = Some(PresetTime {
+            time,
+            validity_start,
+            validity_end,
+        });
+        Some(CosemData::NullData)
+    }
+
+    // Method 4: sets the time to the preset one when the time of the clock
+    // lies within the validity interval, its bounds included. The preset is
+    // used once; outside the interval it is kept and the method fails.
+    fn adjust_to_preset_time(&mut self, _data: CosemData) -> Option<CosemData> {
+        let now = date_time_value_seconds(&self.time)?;
+        let preset = self.preset.as_ref()?;
+        if !(preset.validity_start..=preset.validity_end).contains(&now) {
+            return None;
+        }
+        self.time = self.preset.take()?.time;
+        Some(CosemData::NullData)
+    }
+
+    fn attribute_specs(&self) -> Vec<AttributeSpec> {
+        let date_time = |attribute_id| {
+            AttributeSpec::one_of(attribute_id, &[DataType::DateTime, DataType::OctetString])
+                .with_length(12, 12)
+        };
+        vec![
+            date_time(2),
+            AttributeSpec::new(3, DataType::Long).with_range(-720, 720),
+            date_time(5),
+            date_time(6),
+            AttributeSpec::new(7, DataType::Integer).with_range(-120, 120),
+            AttributeSpec::new(8, DataType::Boolean),
+        ]
+    }
 }
 
 impl Default for Clock {
@@ -42,91 +124,143 @@ impl Default for Clock {
     }
 }
 
-impl CosemObject for Clock {
-    fn class_id(&self) -> u16 {
-        8
+status_flags! {
+    // Clock status of attribute 4, an unsigned, and of the last octet of a
+    // date-time. Bit-strings some meters use are read as well.
+    pub struct ClockStatus encoded by CosemData::Unsigned {
+        const INVALID_VALUE = 0x01, invalid_value;
+        const DOUBTFUL_VALUE = 0x02, doubtful_value;
+        const DIFFERENT_CLOCK_BASE = 0x04, different_clock_base;
+        const INVALID_CLOCK_STATUS = 0x08, invalid_clock_status;
+        const DAYLIGHT_SAVING_ACTIVE = 0x80, daylight_saving_active;
     }
+}
 
-    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
-        vec![
-            AttributeAccessDescriptor::new(2, AttributeAccessMode::ReadWrite),
-            AttributeAccessDescriptor::new(3, AttributeAccessMode::ReadWrite),
-            AttributeAccessDescriptor::new(4, AttributeAccessMode::Read),
-            AttributeAccessDescriptor::new(5, AttributeAccessMode::ReadWrite),
-            AttributeAccessDescriptor::new(6, AttributeAccessMode::ReadWrite),
-            AttributeAccessDescriptor::new(7, AttributeAccessMode::ReadWrite),
-            AttributeAccessDescriptor::new(8, AttributeAccessMode::ReadWrite),
-        ]
+// Supplies the current date-time of the device to objects that stamp their
+// values, e.g. the capture_time of registers on reset.
+#[derive(Clone)]
+pub struct TimeSource(Arc<dyn Fn() -> CosemData + Send + Sync>);
+
+impl TimeSource {
+    pub fn new<F>(now: F) -> Self
+    where
+        F: Fn() -> CosemData + Send + Sync + 'static,
+    {
+        Self(Arc::new(now))
     }
 
-    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
-        match attribute_id {
-            2 => Some(self.time.clone()),
-            3 => Some(self.time_zone.clone()),
-            4 => Some(self.status.clone()),
-            5 => Some(self.daylight_savings_begin.clone()),
-            6 => Some(self.daylight_savings_end.clone()),
-            7 => Some(self.daylight_savings_deviation.clone()),
-            8 => Some(self.enabled.clone()),
-            _ => None,
-        }
+    pub fn now(&self) -> CosemData {
+        (self.0)()
     }
+}
 
-    fn set_attribute(
-        &mut self,
-        attribute_id: CosemObjectAttributeId,
-        data: CosemData,
-    ) -> Option<()> {
-        match attribute_id {
-            2 => {
-                self.time = data;
-                Some(())
-            }
-            3 => {
-                self.time_zone = data;
-                Some(())
-            }
-            4 => {
-                self.status = data;
-                Some(())
-            }
-            5 => {
-                self.daylight_savings_begin = data;
-                Some(())
-            }
-            6 => {
-                self.daylight_savings_end = data;
-                Some(())
-            }
-            7 => {
-                self.daylight_savings_deviation = data;
-                Some(())
-            }
-            8 => {
-                self.enabled = data;
-                Some(())
-            }
-            _ => None,
-        }
+impl fmt::Debug for TimeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TimeSource")
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Seconds since 1970-01-01 of a date-time octet string, taken as is without
+// applying the deviation. `None` when the date or time contains wildcards.
+pub fn date_time_seconds(date_time: &[u8]) -> Option<u64> {
+    let [year_high, year_low, month, day, _, ..] = date_time else {
+        return None;
+    };
+    let year = u16::from_be_bytes([*year_high, *year_low]);
+    if year == 0xFFFF || !(1..=12).contains(month) || !(1..=31).contains(day) {
+        return None;
     }
+    let days = days_from_civil(year as i64, *month as i64, *day as i64);
+    let days = u64::try_from(days).ok()?;
+    Some(days * SECONDS_PER_DAY + time_of_day_seconds(date_time)?)
+}
 
-    fn invoke_method(
-        &mut self,
-        _method_id: CosemObjectMethodId,
-        _data: CosemData,
-    ) -> Option<CosemData> {
-        None
+fn date_time_value_seconds(value: &CosemData) -> Option<u64> {
+    match value {
+        CosemData::DateTime(date_time) | CosemData::OctetString(date_time)
+            if date_time.len() == 12 =>
+        {
+            date_time_seconds(date_time)
+        }
+        _ => None,
     }
+}
 
-    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
-        Some(Arc::clone(&self.callbacks))
+// Seconds since midnight of the time part of a date-time octet string.
+pub fn time_of_day_seconds(date_time: &[u8]) -> Option<u64> {
+    let [_, _, _, _, _, hour, minute, second, ..] = date_time else {
+        return None;
+    };
+    if *hour > 23 || *minute > 59 || *second > 59 {
+        return None;
     }
+    Some(*hour as u64 * 3600 + *minute as u64 * 60 + *second as u64)
+}
+
+// Date-time octet string of seconds since 1970-01-01, the inverse of
+// `date_time_seconds`. Hundredths and clock status are 0, the deviation is
+// not specified.
+pub fn date_time_from_seconds(seconds: u64) -> Option<[u8; 12]> {
+    let days = i64::try_from(seconds / SECONDS_PER_DAY).ok()?;
+    let (year, month, day) = civil_from_days(days);
+    let year = u16::try_from(year).ok().filter(|year| *year != 0xFFFF)?;
+    let [year_high, year_low] = year.to_be_bytes();
+    let time = seconds % SECONDS_PER_DAY;
+    // 1970-01-01 was a Thursday, Monday is 1.
+    let day_of_week = ((days + 3) % 7 + 1) as u8;
+    Some([
+        year_high,
+        year_low,
+        month as u8,
+        day as u8,
+        day_of_week,
+        (time / 3600) as u8,
+        (time / 60 % 60) as u8,
+        (time % 60) as u8,
+        0,
+        0x80,
+        0,
+        0,
+    ])
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Proleptic Gregorian date of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
 }
 
 #[cfg(all(test, feature = "std"))]
 mod tests {
     extern crate std;
     use super::*;
+    use crate::cosem_object::{AttributeAccessMode, CosemObject};
+    use crate::types::BitString;
 
     #[test]
     fn test_clock_new() {
@@ -149,4 +283,131 @@ mod tests {
             .unwrap();
         assert_eq!(clock.get_attribute(2), Some(CosemData::DateTime(time)));
     }
+
+    #[test]
+    fn test_clock_status_flags() {
+        let mut clock = Clock::new();
+        assert_eq!(clock.clock_status(), None);
+        let status = ClockStatus::default()
+            .with(ClockStatus::INVALID_VALUE, true)
+            .with(ClockStatus::DAYLIGHT_SAVING_ACTIVE, true);
+        clock.set_clock_status(status);
+        assert_eq!(clock.get_attribute(4), Some(CosemData::Unsigned(0x81)));
+        let status = clock.clock_status().unwrap();
+        assert!(status.invalid_value() && status.daylight_saving_active());
+        assert!(!status.doubtful_value() && !status.different_clock_base());
+        assert!(!status
+            .with(ClockStatus::INVALID_VALUE, false)
+            .invalid_value());
+        assert_eq!(
+            ClockStatus::from_cosem_data(&CosemData::BitString(BitString::from_bytes(vec![0x08]))),
+            Some(ClockStatus::from_bits(0x08))
+        );
+        assert!(ClockStatus::from_bits(0x08).invalid_clock_status());
+    }
+
+    #[test]
+    fn test_date_time_seconds() {
+        let epoch = [0x07, 0xB2, 1, 1, 4, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(date_time_seconds(&epoch), Some(0));
+        let date_time = [0x07, 0xEA, 10, 17, 6, 12, 30, 15, 0, 0x80, 0, 0];
+        assert_eq!(date_time_seconds(&date_time), Some(1_792_240_215));
+        assert_eq!(time_of_day_seconds(&date_time), Some(45_015));
+        assert_eq!(date_time_from_seconds(1_792_240_215), Some(date_time));
+        assert_eq!(date_time_from_seconds(0).unwrap()[..9], epoch[..9]);
+        let leap_day = date_time_from_seconds(1_709_164_800).unwrap();
+        assert_eq!(leap_day[..5], [0x07, 0xE8, 2, 29, 4]);
+
+        let daily = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 12, 30, 15, 0xFF, 0x80, 0, 0];
+        assert_eq!(date_time_seconds(&daily), None);
+        assert_eq!(time_of_day_seconds(&daily), Some(45_015));
+    }
+
+    #[test]
+    fn test_generated_access_rights() {
+        let mut clock = Clock::new();
+        assert_eq!(clock.class_id(), 8);
+        let rights = clock.attribute_access_rights();
+        assert_eq!(rights.len(), 7);
+        assert_eq!(rights[2].attribute_id, 4);
+        assert_eq!(rights[2].access_mode, AttributeAccessMode::Read);
+        let methods = clock.method_access_rights();
+        assert_eq!(
+            methods
+                .iter()
+                .map(|method| method.method_id)
+                .collect::<Vec<_>>(),
+            [4, 5]
+        );
+        assert!(clock.callbacks().is_some());
+        assert_eq!(clock.set_attribute(9, CosemData::NullData), None);
+        assert_eq!(clock.invoke_method(1, CosemData::NullData), None);
+    }
+
+    #[test]
+    fn test_preset_adjusting_time_window() {
+        let at =
+            |seconds| CosemData::OctetString(date_time_from_seconds(seconds).unwrap().to_vec());
+        let preset = |time, start, end| CosemData::Structure(vec![at(time), at(start), at(end)]);
+        let mut clock = Clock::new();
+        // No preset and no time yet.
+        assert_eq!(clock.invoke_method(4, CosemData::Integer(0)), None);
+
+        clock.set_attribute(2, at(999)).unwrap();
+        assert_eq!(
+            clock.invoke_method(5, preset(5_000, 1_000, 2_000)),
+            Some(CosemData::NullData)
+        );
+        // Before the window the preset is kept.
+        assert_eq!(clock.invoke_method(4, CosemData::Integer(0)), None);
+        assert_eq!(clock.get_attribute(2), Some(at(999)));
+        clock.set_attribute(2, at(1_000)).unwrap();
+        assert_eq!(
+            clock.invoke_method(4, CosemData::Integer(0)),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(clock.get_attribute(2), Some(at(5_000)));
+        // The preset is used once.
+        clock.set_attribute(2, at(1_500)).unwrap();
+        assert_eq!(clock.invoke_method(4, CosemData::Integer(0)), None);
+
+        clock.invoke_method(5, preset(6_000, 1_000, 2_000)).unwrap();
+        clock.set_attribute(2, at(2_001)).unwrap();
+        assert_eq!(clock.invoke_method(4, CosemData::Integer(0)), None);
+        clock.set_attribute(2, at(2_000)).unwrap();
+        assert_eq!(
+            clock.invoke_method(4, CosemData::Integer(0)),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(clock.get_attribute(2), Some(at(6_000)));
+
+        // A single instant is a valid window, an inverted one is not.
+        assert!(clock
+            .invoke_method(5, preset(7_000, 6_000, 6_000))
+            .is_some());
+        assert_eq!(clock.invoke_method(5, preset(7_000, 6_001, 6_000)), None);
+        let mut wildcard = date_time_from_seconds(7_000).unwrap();
+        wildcard[0..2].copy_from_slice(&[0xFF, 0xFF]);
+        assert_eq!(
+            clock.invoke_method(
+                5,
+                CosemData::Structure(vec![
+                    CosemData::OctetString(wildcard.to_vec()),
+                    at(6_000),
+                    at(6_000),
+                ])
+            ),
+            None
+        );
+        assert_eq!(
+            clock.invoke_method(5, CosemData::Structure(vec![at(7_000), at(6_000)])),
+            None
+        );
+        // The rejected presets left the last valid one in place.
+        assert_eq!(
+            clock.invoke_method(4, CosemData::Integer(0)),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(clock.get_attribute(2), Some(at(7_000)));
+    }
 }
@@ -0,0 +1,814 @@
+use crate::axdr::{decode_data, encode_data};
+use crate::buffer_storage::BufferStorage;
+use crate::clock::{date_time_from_seconds, date_time_seconds};
+use crate::types::CosemData;
+use std::boxed::Box;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::vec::Vec;
+
+// Append only byte medium behind a `CompactBufferStorage`: RAM, a file or
+// flash pages. Offsets grow with every append and stay valid until the bytes
+// before them are discarded.
+pub trait ByteLog: Send + fmt::Debug {
+    fn append(&mut self, bytes: &[u8]) -> Option<()>;
+    // Offset of the oldest byte held.
+    fn start(&self) -> u64;
+    // Offset following the newest byte.
+    fn end(&self) -> u64;
+    // Fills `buffer` from `offset`; `None` when the bytes are not held.
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Option<()>;
+    fn discard_before(&mut self, offset: u64);
+    fn clear(&mut self);
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryByteLog {
+    start: u64,
+    bytes: Vec<u8>,
+}
+
+impl MemoryByteLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ByteLog for MemoryByteLog {
+    fn append(&mut self, bytes: &[u8]) -> Option<()> {
+        self.bytes.extend_from_slice(bytes);
+        Some(())
+    }
+
+    fn start(&self) -> u64 {
+        self.start
+    }
+
+    fn end(&self) -> u64 {
+        self.start + self.bytes.len() as u64
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Option<()> {
+        let from = usize::try_from(offset.checked_sub(self.start)?).ok()?;
+        buffer.copy_from_slice(self.bytes.get(from..from.checked_add(buffer.len())?)?);
+        Some(())
+    }
+
+    fn discard_before(&mut self, offset: u64) {
+        let count = offset
+            .saturating_sub(self.start)
+            .min(self.bytes.len() as u64);
+        self.bytes.drain(..count as usize);
+        self.start += count;
+    }
+
+    fn clear(&mut self) {
+        self.start = 0;
+        self.bytes.clear();
+    }
+}
+
+// Header of a `FileByteLog`: the offset of the first byte stored after it and
+// the offset of the oldest byte held.
+const FILE_HEADER_LENGTH: u64 = 16;
+const COPY_CHUNK_LENGTH: usize = 4096;
+
+// A log in a file. Discarded bytes are only reclaimed once they outnumber the
+// bytes held, by moving the held ones to the front of the file.
+#[derive(Debug)]
+pub struct FileByteLog {
+    file: File,
+    base: u64,
+    start: u64,
+    end: u64,
+}
+
+impl FileByteLog {
+    // Opens the log at `path`, creating an empty one if there is none.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let length = file.metadata()?.len();
+        if length < FILE_HEADER_LENGTH {
+            file.set_len(0)?;
+            file.write_all(&[0; FILE_HEADER_LENGTH as usize])?;
+            return Ok(Self {
+                file,
+                base: 0,
+                start: 0,
+                end: 0,
+            });
+        }
+        let mut header = [0; FILE_HEADER_LENGTH as usize];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut header)?;
+        let base = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let end = base + length - FILE_HEADER_LENGTH;
+        let start = u64::from_le_bytes(header[8..].try_into().unwrap()).clamp(base, end);
+        Ok(Self {
+            file,
+            base,
+            start,
+            end,
+        })
+    }
+
+    fn position(&self, offset: u64) -> u64 {
+        FILE_HEADER_LENGTH + offset - self.base
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut header = [0; FILE_HEADER_LENGTH as usize];
+        header[..8].copy_from_slice(&self.base.to_le_bytes());
+        header[8..].copy_from_slice(&self.start.to_le_bytes());
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header)
+    }
+
+    fn compact(&mut self) -> io::Result<()> {
+        let mut chunk = [0; COPY_CHUNK_LENGTH];
+        let mut offset = self.start;
+        while offset < self.end {
+            let length = ((self.end - offset) as usize).min(COPY_CHUNK_LENGTH);
+            self.file.seek(SeekFrom::Start(self.position(offset)))?;
+            self.file.read_exact(&mut chunk[..length])?;
+            self.file
+                .seek(SeekFrom::Start(FILE_HEADER_LENGTH + offset - self.start))?;
+            self.file.write_all(&chunk[..length])?;
+            offset += length as u64;
+        }
+        self.base = self.start;
+        self.write_header()?;
+        self.file.set_len(self.position(self.end))
+    }
+}
+
+impl ByteLog for FileByteLog {
+    fn append(&mut self, bytes: &[u8]) -> Option<()> {
+        self.file
+            .seek(SeekFrom::Start(self.position(self.end)))
+            .ok()?;
+        self.file.write_all(bytes).ok()?;
+        self.end += bytes.len() as u64;
+        Some(())
+    }
+
+    fn start(&self) -> u64 {
+        self.start
+    }
+
+    fn end(&self) -> u64 {
+        self.end
+    }
+
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Option<()> {
+        if offset < self.start || offset.checked_add(buffer.len() as u64)? > self.end {
+            return None;
+        }
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(self.position(offset))).ok()?;
+        file.read_exact(buffer).ok()
+    }
+
+    fn discard_before(&mut self, offset: u64) {
+        self.start = offset.clamp(self.start, self.end);
+        // A failed write leaves the discarded bytes held until the next one.
+        let _ = if self.start - self.base > self.end - self.start {
+            self.compact()
+        } else {
+            self.write_header()
+        };
+    }
+
+    fn clear(&mut self) {
+        self.base = 0;
+        self.start = 0;
+        self.end = 0;
+        let _ = self
+            .write_header()
+            .and_then(|_| self.file.set_len(FILE_HEADER_LENGTH));
+    }
+}
+
+// Entries are encoded per block of `block_entries`: the first entry of a block
+// stands alone, the date-times of the following ones are deltas to the one
+// before. Only the offset and capture time of each block are kept in RAM.
+pub const DEFAULT_BLOCK_ENTRIES: usize = 64;
+
+// Codes of a packed value; anything else is stored as A-XDR, and values A-XDR
+// does not encode are refused.
+const TAG_NULL: u8 = 0;
+const TAG_ARRAY: u8 = 1;
+const TAG_STRUCTURE: u8 = 2;
+const TAG_BOOLEAN: u8 = 3;
+const TAG_DOUBLE_LONG: u8 = 5;
+const TAG_DOUBLE_LONG_UNSIGNED: u8 = 6;
+const TAG_OCTET_STRING: u8 = 9;
+const TAG_BCD: u8 = 13;
+const TAG_INTEGER: u8 = 15;
+const TAG_LONG: u8 = 16;
+const TAG_UNSIGNED: u8 = 17;
+const TAG_LONG_UNSIGNED: u8 = 18;
+const TAG_LONG64: u8 = 20;
+const TAG_LONG64_UNSIGNED: u8 = 21;
+const TAG_ENUM: u8 = 22;
+const TAG_DATE_TIME: u8 = 25;
+// A date-time as seconds since the previous one plus the octets that are not
+// derived from the seconds and changed.
+const TAG_DELTA: u8 = 0x80;
+// Day of week, hundredths, deviation and clock status.
+const DATE_TIME_TAIL: [usize; 5] = [4, 8, 9, 10, 11];
+// Values nested deeper are not packed.
+const MAX_PACKED_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Codec {
+    seconds: u64,
+    tail: [u8; 5],
+}
+
+impl Codec {
+    fn encode(&mut self, data: &CosemData, out: &mut Vec<u8>, depth: usize) -> Option<()> {
+        if depth > MAX_PACKED_DEPTH {
+            return encode_data(data, out).ok();
+        }
+        match data {
+            CosemData::NullData => out.push(TAG_NULL),
+            CosemData::Array(elements) | CosemData::Structure(elements) => {
+                out.push(if matches!(data, CosemData::Array(_)) {
+                    TAG_ARRAY
+                } else {
+                    TAG_STRUCTURE
+                });
+                write_varint(elements.len() as u64, out);
+                for element in elements {
+                    self.encode(element, out, depth + 1)?;
+                }
+            }
+            CosemData::Boolean(value) => out.extend_from_slice(&[TAG_BOOLEAN, *value as u8]),
+            CosemData::DoubleLongUnsigned(value) => {
+                write_unsigned(TAG_DOUBLE_LONG_UNSIGNED, *value as u64, out)
+            }
+            CosemData::Unsigned(value) => write_unsigned(TAG_UNSIGNED, *value as u64, out),
+            CosemData::LongUnsigned(value) => write_unsigned(TAG_LONG_UNSIGNED, *value as u64, out),
+            CosemData::Long64Unsigned(value) => write_unsigned(TAG_LONG64_UNSIGNED, *value, out),
+            CosemData::Enum(value) => write_unsigned(TAG_ENUM, *value as u64, out),
+            CosemData::DoubleLong(value) => write_signed(TAG_DOUBLE_LONG, *value as i64, out),
+            CosemData::Bcd(value) => write_signed(TAG_BCD, *value as i64, out),
+            CosemData::Integer(value) => write_signed(TAG_INTEGER, *value as i64, out),
+            CosemData::Long(value) => write_signed(TAG_LONG, *value as i64, out),
+            CosemData::Long64(value) => write_signed(TAG_LONG64, *value, out),
+            CosemData::DateTime(bytes) if self.encode_date_time(TAG_DATE_TIME, bytes, out) => {}
+            CosemData::OctetString(bytes)
+                if self.encode_date_time(TAG_OCTET_STRING, bytes, out) => {}
+            data => encode_data(data, out).ok()?,
+        }
+        Some(())
+    }
+
+    // Whether `bytes` are a date-time the seconds restore and so were written
+    // as a delta.
+    fn encode_date_time(&mut self, tag: u8, bytes: &[u8], out: &mut Vec<u8>) -> bool {
+        let Some(seconds) = date_time_seconds(bytes) else {
+            return false;
+        };
+        let Some(restored) = date_time_from_seconds(seconds) else {
+            return false;
+        };
+        if bytes.len() != restored.len() || bytes[..8] != restored[..8] {
+            return false;
+        }
+        out.push(TAG_DELTA | tag);
+        write_varint(zigzag(seconds.wrapping_sub(self.seconds) as i64), out);
+        let mut mask = 0;
+        let mask_position = out.len();
+        out.push(0);
+        for (bit, index) in DATE_TIME_TAIL.iter().enumerate() {
+            if bytes[*index] != self.tail[bit] {
+                mask |= 1 << bit;
+                out.push(bytes[*index]);
+                self.tail[bit] = bytes[*index];
+            }
+        }
+        out[mask_position] = mask;
+        self.seconds = seconds;
+        true
+    }
+
+    fn decode(&mut self, input: &mut &[u8], depth: usize) -> Option<CosemData> {
+        let (&tag, rest) = input.split_first()?;
+        if depth > MAX_PACKED_DEPTH {
+            return decode_axdr(input);
+        }
+        let mut rest = rest;
+        let data = match tag {
+            TAG_NULL => CosemData::NullData,
+            TAG_ARRAY | TAG_STRUCTURE => {
+                let count = usize::try_from(read_varint(&mut rest)?).ok()?;
+                // Every element takes at least one octet.
+                if count > rest.len() {
+                    return None;
+                }
+                let elements = (0..count)
+                    .map(|_| self.decode(&mut rest, depth + 1))
+                    .collect::<Option<Vec<_>>>()?;
+                if tag == TAG_ARRAY {
+                    CosemData::Array(elements)
+                } else {
+                    CosemData::Structure(elements)
+                }
+            }
+            TAG_BOOLEAN => {
+                let (&value, after) = rest.split_first()?;
+                rest = after;
+                CosemData::Boolean(value != 0)
+            }
+            TAG_DOUBLE_LONG_UNSIGNED => {
+                CosemData::DoubleLongUnsigned(read_varint(&mut rest)?.try_into().ok()?)
+            }
+            TAG_UNSIGNED => CosemData::Unsigned(read_varint(&mut rest)?.try_into().ok()?),
+            TAG_LONG_UNSIGNED => CosemData::LongUnsigned(read_varint(&mut rest)?.try_into().ok()?),
+            TAG_LONG64_UNSIGNED => CosemData::Long64Unsigned(read_varint(&mut rest)?),
+            TAG_ENUM => CosemData::Enum(read_varint(&mut rest)?.try_into().ok()?),
+            TAG_DOUBLE_LONG => CosemData::DoubleLong(read_signed(&mut rest)?.try_into().ok()?),
+            TAG_BCD => CosemData::Bcd(read_signed(&mut rest)?.try_into().ok()?),
+            TAG_INTEGER => CosemData::Integer(read_signed(&mut rest)?.try_into().ok()?),
+            TAG_LONG => CosemData::Long(read_signed(&mut rest)?.try_into().ok()?),
+            TAG_LONG64 => CosemData::Long64(read_signed(&mut rest)?),
+            tag if tag == TAG_DELTA | TAG_DATE_TIME => {
+                CosemData::DateTime(self.decode_date_time(&mut rest)?)
+            }
+            tag if tag == TAG_DELTA | TAG_OCTET_STRING => {
+                CosemData::OctetString(self.decode_date_time(&mut rest)?)
+            }
+            _ => return decode_axdr(input),
+        };
+        *input = rest;
+        Some(data)
+    }
+
+    fn decode_date_time(&mut self, input: &mut &[u8]) -> Option<Vec<u8>> {
+        let delta = unzigzag(read_varint(input)?);
+        let seconds = self.seconds.wrapping_add(delta as u64);
+        let mask = *input.first()?;
+        *input = &input[1..];
+        for bit in 0..DATE_TIME_TAIL.len() {
+            if mask & 1 << bit != 0 {
+                self.tail[bit] = *input.first()?;
+                *input = &input[1..];
+            }
+        }
+        let mut bytes = date_time_from_seconds(seconds)?;
+        for (bit, index) in DATE_TIME_TAIL.iter().enumerate() {
+            bytes[*index] = self.tail[bit];
+        }
+        self.seconds = seconds;
+        Some(bytes.to_vec())
+    }
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for (index, octet) in input.iter().enumerate().take(10) {
+        value |= u64::from(octet & 0x7F) << (7 * index);
+        if octet & 0x80 == 0 {
+            *input = &input[index + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn read_signed(input: &mut &[u8]) -> Option<i64> {
+    read_varint(input).map(unzigzag)
+}
+
+fn decode_axdr(input: &mut &[u8]) -> Option<CosemData> {
+    let (data, rest) = decode_data(input).ok()?;
+    *input = rest;
+    Some(data)
+}
+
+fn write_unsigned(tag: u8, value: u64, out: &mut Vec<u8>) {
+    out.push(tag);
+    write_varint(value, out);
+}
+
+fn write_signed(tag: u8, value: i64, out: &mut Vec<u8>) {
+    out.push(tag);
+    write_varint(zigzag(value), out);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+// Seconds of the capture time of an entry: its first date-time column.
+fn capture_seconds(entry: &CosemData) -> Option<u64> {
+    let CosemData::Structure(columns) = entry else {
+        return None;
+    };
+    columns.iter().find_map(|column| match column {
+        CosemData::DateTime(bytes) | CosemData::OctetString(bytes) => date_time_seconds(bytes),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    offset: u64,
+    capture_seconds: Option<u64>,
+}
+
+// Buffer storage writing entries packed to a `ByteLog`, a few octets per
+// entry of a load profile instead of their A-XDR encoding. Appends only write
+// the new entry; reads decode from the start of the block holding the first
+// entry asked for.
+//
+// Entries are discarded from the log a block at a time, so a reopened storage
+// may again hold up to `block_entries - 1` entries discarded before.
+#[derive(Debug)]
+pub struct CompactBufferStorage<L: ByteLog> {
+    log: L,
+    block_entries: usize,
+    blocks: Vec<Block>,
+    // Entries of the oldest block already discarded.
+    skipped: usize,
+    len: usize,
+    codec: Codec,
+}
+
+impl<L: ByteLog> CompactBufferStorage<L> {
+    pub fn new(log: L) -> Self {
+        Self::with_block_entries(log, DEFAULT_BLOCK_ENTRIES)
+    }
+
+    // Larger blocks pack date-times of more entries as deltas, smaller ones
+    // keep reads of recent entries short.
+    pub fn with_block_entries(mut log: L, block_entries: usize) -> Self {
+        log.clear();
+        Self {
+            log,
+            block_entries: block_entries.max(1),
+            blocks: Vec::new(),
+            skipped: 0,
+            len: 0,
+            codec: Codec::default(),
+        }
+    }
+
+    // Takes over the entries of a log written before, e.g. after a restart.
+    // `None` when a record of the log cannot be decoded.
+    pub fn open(log: L, block_entries: usize) -> Option<Self> {
+        let mut storage = Self {
+            log,
+            block_entries: block_entries.max(1),
+            blocks: Vec::new(),
+            skipped: 0,
+            len: 0,
+            codec: Codec::default(),
+        };
+        let mut offset = storage.log.start();
+        while offset < storage.log.end() {
+            let new_block = storage.len.is_multiple_of(storage.block_entries);
+            if new_block {
+                storage.codec = Codec::default();
+            }
+            let (payload, next) = read_record(&storage.log, offset)?;
+            let entry = storage.codec.decode(&mut payload.as_slice(), 0)?;
+            if new_block {
+                storage.blocks.push(Block {
+                    offset,
+                    capture_seconds: capture_seconds(&entry),
+                });
+            }
+            storage.len += 1;
+            offset = next;
+        }
+        Some(storage)
+    }
+
+    pub fn log(&self) -> &L {
+        &self.log
+    }
+
+    pub fn into_log(self) -> L {
+        self.log
+    }
+
+    // Positions of the entries captured from `from` to `to`, seconds since
+    // 1970-01-01, for a range descriptor on the capture time. Entries are
+    // expected oldest first.
+    pub fn range_between(&self, from: u64, to: u64) -> Range<usize> {
+        let block = self
+            .blocks
+            .partition_point(|block| block.capture_seconds.is_some_and(|seconds| seconds <= from))
+            .saturating_sub(1);
+        let first = (block * self.block_entries).saturating_sub(self.skipped);
+        let mut range = first..first;
+        for (position, entry) in self.entries(first..self.len).enumerate() {
+            let position = first + position;
+            match capture_seconds(&entry) {
+                Some(seconds) if seconds < from => range = position + 1..position + 1,
+                Some(seconds) if seconds > to => break,
+                Some(_) => range.end = position + 1,
+                None => {}
+            }
+        }
+        range
+    }
+
+    fn reset(&mut self) {
+        self.log.clear();
+        self.blocks.clear();
+        self.skipped = 0;
+        self.len = 0;
+        self.codec = Codec::default();
+    }
+}
+
+impl<L: ByteLog> BufferStorage for CompactBufferStorage<L> {
+    fn append(&mut self, entry: CosemData) -> Option<()> {
+        let new_block = (self.skipped + self.len).is_multiple_of(self.block_entries);
+        let mut codec = if new_block {
+            Codec::default()
+        } else {
+            self.codec
+        };
+        let mut payload = Vec::new();
+        codec.encode(&entry, &mut payload, 0)?;
+        let mut record = Vec::with_capacity(payload.len() + 2);
+        write_varint(payload.len() as u64, &mut record);
+        record.extend_from_slice(&payload);
+
+        let offset = self.log.end();
+        self.log.append(&record)?;
+        if new_block {
+            self.blocks.push(Block {
+                offset,
+                capture_seconds: capture_seconds(&entry),
+            });
+        }
+        self.codec = codec;
+        self.len += 1;
+        Some(())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn entries(&self, range: Range<usize>) -> Box<dyn Iterator<Item = CosemData> + '_> {
+        let end = range.end.min(self.len);
+        let start = range.start.min(end);
+        if start == end {
+            return Box::new(core::iter::empty());
+        }
+        let position = self.skipped + start;
+        Box::new(Entries {
+            log: &self.log,
+            offset: self.blocks[position / self.block_entries].offset,
+            codec: Codec::default(),
+            block_entries: self.block_entries,
+            index: 0,
+            skip: position % self.block_entries,
+            remaining: end - start,
+        })
+    }
+
+    fn discard_oldest(&mut self, count: usize) {
+        let count = count.min(self.len);
+        if count == self.len {
+            self.reset();
+            return;
+        }
+        self.len -= count;
+        self.skipped += count;
+        let whole_blocks = self.skipped / self.block_entries;
+        if whole_blocks > 0 {
+            self.blocks.drain(..whole_blocks);
+            self.skipped %= self.block_entries;
+            self.log.discard_before(self.blocks[0].offset);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.reset();
+    }
+}
+
+// Payload of the record at `offset` and the offset of the next one.
+fn read_record<L: ByteLog>(log: &L, offset: u64) -> Option<(Vec<u8>, u64)> {
+    let mut header = Vec::new();
+    let length = loop {
+        let mut octet = [0];
+        log.read_at(offset + header.len() as u64, &mut octet)?;
+        header.push(octet[0]);
+        if octet[0] & 0x80 == 0 {
+            break read_varint(&mut header.as_slice())?;
+        }
+        if header.len() == 10 {
+            return None;
+        }
+    };
+    let start = offset + header.len() as u64;
+    // A corrupted length must not size the allocation.
+    if start.checked_add(length)? > log.end() {
+        return None;
+    }
+    let mut payload = vec![0; usize::try_from(length).ok()?];
+    log.read_at(start, &mut payload)?;
+    Some((payload, start + length))
+}
+
+struct Entries<'a, L: ByteLog> {
+    log: &'a L,
+    offset: u64,
+    codec: Codec,
+    block_entries: usize,
+    // Position of the next record in its block.
+    index: usize,
+    skip: usize,
+    remaining: usize,
+}
+
+impl<L: ByteLog> Iterator for Entries<'_, L> {
+    type Item = CosemData;
+
+    fn next(&mut self) -> Option<CosemData> {
+        while self.remaining > 0 {
+            if self.index == self.block_entries {
+                self.index = 0;
+                self.codec = Codec::default();
+            }
+            let (payload, next) = read_record(self.log, self.offset)?;
+            self.offset = next;
+            self.index += 1;
+            let entry = self.codec.decode(&mut payload.as_slice(), 0)?;
+            if self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+            self.remaining -= 1;
+            return Some(entry);
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::cosem_object::CosemObject;
+    use crate::profile_generic::{append_buffer_entry, ProfileGeneric};
+
+    const START: u64 = 1_792_240_200;
+
+    // A quarter-hourly load profile entry: clock, status and two energies.
+    fn entry(index: u64) -> CosemData {
+        let mut clock = date_time_from_seconds(START + index * 900).unwrap();
+        clock[8] = 0xFF;
+        clock[11] = if index == 5 { 0x80 } else { 0 };
+        CosemData::Structure(vec![
+            CosemData::OctetString(clock.to_vec()),
+            CosemData::Unsigned(0),
+            CosemData::DoubleLongUnsigned(1_000_000 + index as u32 * 250),
+            CosemData::Long(-(index as i16)),
+        ])
+    }
+
+    #[test]
+    fn test_entries_round_trip_packed() {
+        let mut storage = CompactBufferStorage::with_block_entries(MemoryByteLog::new(), 4);
+        let mut axdr = Vec::new();
+        for index in 0..10 {
+            encode_data(&entry(index), &mut axdr).unwrap();
+            storage.append(entry(index)).unwrap();
+        }
+        // 17 octets an entry instead of 26, 15 within a block.
+        assert_eq!(axdr.len(), 260);
+        assert_eq!(storage.log().end(), 170);
+        let odd = CosemData::Structure(vec![
+            CosemData::OctetString(b"no clock".to_vec()),
+            CosemData::OctetString(vec![0xFF; 12]),
+            CosemData::Structure(vec![CosemData::Long64(i64::MIN), CosemData::Boolean(true)]),
+        ]);
+        storage.append(odd.clone()).unwrap();
+
+        assert_eq!(storage.len(), 11);
+        let mut expected: Vec<_> = (0..10).map(entry).collect();
+        expected.push(odd);
+        assert_eq!(storage.entries(0..11).collect::<Vec<_>>(), expected);
+        assert_eq!(storage.entries(3..6).collect::<Vec<_>>(), expected[3..6]);
+        assert_eq!(storage.entries(9..20).count(), 2);
+    }
+
+    #[test]
+    fn test_discard_drops_whole_blocks_from_the_log() {
+        let mut storage = CompactBufferStorage::with_block_entries(MemoryByteLog::new(), 4);
+        for index in 0..10 {
+            storage.append(entry(index)).unwrap();
+        }
+        storage.discard_oldest(3);
+        assert_eq!(storage.log().start(), 0);
+        assert_eq!(
+            storage.entries(0..2).collect::<Vec<_>>(),
+            [entry(3), entry(4)]
+        );
+        storage.discard_oldest(2);
+        assert!(storage.log().start() > 0);
+        assert_eq!(storage.len(), 5);
+        assert_eq!(
+            storage.entries(0..5).collect::<Vec<_>>(),
+            (5..10).map(entry).collect::<Vec<_>>()
+        );
+        storage.append(entry(10)).unwrap();
+        assert_eq!(storage.entries(5..6).next(), Some(entry(10)));
+
+        storage.discard_oldest(100);
+        assert!(storage.is_empty());
+        assert_eq!(storage.log().end(), 0);
+    }
+
+    #[test]
+    fn test_open_refuses_a_record_with_a_corrupted_length() {
+        let mut storage = CompactBufferStorage::with_block_entries(MemoryByteLog::new(), 4);
+        for index in 0..3 {
+            storage.append(entry(index)).unwrap();
+        }
+        let mut log = storage.log().clone();
+        // A length header claiming far more than the log holds.
+        log.append(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F])
+            .unwrap();
+        assert!(CompactBufferStorage::open(log, 4).is_none());
+    }
+
+    #[test]
+    fn test_range_between_capture_times() {
+        let mut storage = CompactBufferStorage::with_block_entries(MemoryByteLog::new(), 4);
+        for index in 0..10 {
+            storage.append(entry(index)).unwrap();
+        }
+        storage.discard_oldest(1);
+        assert_eq!(
+            storage.range_between(START + 2 * 900, START + 6 * 900),
+            1..6
+        );
+        assert_eq!(
+            storage.range_between(START + 2 * 900 + 1, START + 2 * 900 + 2),
+            2..2
+        );
+        assert_eq!(storage.range_between(0, u64::MAX), 0..9);
+        assert_eq!(storage.range_between(START + 20 * 900, u64::MAX), 9..9);
+    }
+
+    #[test]
+    fn test_file_log_reopens_with_profile_generic() {
+        let path = std::env::temp_dir().join(format!("dlms-compact-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let storage =
+            CompactBufferStorage::with_block_entries(FileByteLog::open(&path).unwrap(), 4);
+        let mut profile = ProfileGeneric::with_storage(Box::new(storage));
+        profile
+            .set_attribute(8, CosemData::DoubleLongUnsigned(6))
+            .unwrap();
+        for index in 0..14 {
+            append_buffer_entry(&mut profile, entry(index)).unwrap();
+        }
+        drop(profile);
+
+        let storage = CompactBufferStorage::open(FileByteLog::open(&path).unwrap(), 4).unwrap();
+        // Entries 8 and 9 share the oldest block held with 10 and 11.
+        assert_eq!(
+            storage.entries(0..storage.len()).collect::<Vec<_>>(),
+            (8..14).map(entry).collect::<Vec<_>>()
+        );
+        let profile = ProfileGeneric::with_storage(Box::new(storage));
+        assert_eq!(
+            profile.get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(6))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "compress")]
+
+use crate::axdr::{decode_data_with, encode_data, ParseMode};
+use crate::error::DlmsError;
+use crate::types::CosemData;
+use crate::xdlms::{DataNotification, Notification};
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use std::vec::Vec;
+
+// Compression of pushed data-notification bodies, as some national profiles
+// require. A push setup with the message type PUSH_MESSAGE_COMPRESSED_AXDR
+// sends the A-XDR encoding of its body compressed, as an octet-string; a
+// client set up with the same compression restores the body.
+pub trait Compression: Send {
+    fn compress(&self, data: &[u8]) -> Vec<u8>;
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, DlmsError>;
+}
+
+// Raw deflate (RFC 1951) without zlib header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deflate {
+    level: u8,
+    // Inflated sizes beyond this are refused rather than allocated.
+    max_size: usize,
+}
+
+pub const DEFAULT_DEFLATE_LEVEL: u8 = 6;
+pub const DEFAULT_MAX_INFLATED_SIZE: usize = 64 * 1024;
+
+impl Deflate {
+    pub fn new() -> Self {
+        Self {
+            level: DEFAULT_DEFLATE_LEVEL,
+            max_size: DEFAULT_MAX_INFLATED_SIZE,
+        }
+    }
+
+    // 0 stores the data, 10 compresses best.
+    pub fn with_level(mut self, level: u8) -> Self {
+        self.level = level.min(10);
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+}
+
+impl Default for Deflate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compression for Deflate {
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        compress_to_vec(data, self.level)
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, DlmsError> {
+        decompress_to_vec_with_limit(data, self.max_size).map_err(|error| match error.status {
+            miniz_oxide::inflate::TINFLStatus::HasMoreOutput => DlmsError::DataLimitExceeded,
+            _ => DlmsError::ParseError,
+        })
+    }
+}
+
+pub fn compress_notification(
+    notification: &DataNotification,
+    compression: &dyn Compression,
+) -> Result<DataNotification, DlmsError> {
+    let mut body = Vec::new();
+    encode_data(&notification.notification_body, &mut body)?;
+    Ok(DataNotification {
+        notification_body: CosemData::OctetString(compression.compress(&body)),
+        ..notification.clone()
+    })
+}
+
+// Restores the body of a data-notification sent compressed. Notifications
+// with a body other than an octet-string were not compressed and are left as
+// they are.
+pub fn decompress_notification(
+    notification: Notification,
+    compression: &dyn Compression,
+) -> Result<Notification, DlmsError> {
+    let Notification::Data(DataNotification {
+        long_invoke_id_and_priority,
+        date_time,
+        notification_body: CosemData::OctetString(compressed),
+    }) = notification
+    else {
+        return Ok(notification);
+    };
+    let body = compression.decompress(&compressed)?;
+    let (notification_body, rest) = decode_data_with(&body, ParseMode::Strict)?;
+    if !rest.is_empty() {
+        return Err(DlmsError::ParseError);
+    }
+    Ok(Notification::Data(DataNotification {
+        long_invoke_id_and_priority,
+        date_time,
+        notification_body,
+    }))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_notification_body_round_trip() {
+        let notification = DataNotification {
+            long_invoke_id_and_priority: 7,
+            date_time: None,
+            notification_body: CosemData::Structure(vec![CosemData::DoubleLongUnsigned(1234); 32]),
+        };
+        let deflate = Deflate::new();
+        let compressed = compress_notification(&notification, &deflate).unwrap();
+        let CosemData::OctetString(body) = &compressed.notification_body else {
+            panic!("body is not an octet-string");
+        };
+        assert!(body.len() < 32 * 5);
+        assert_eq!(
+            decompress_notification(Notification::Data(compressed.clone()), &deflate).unwrap(),
+            Notification::Data(notification.clone())
+        );
+
+        let small = Deflate::new().with_max_size(16);
+        assert!(matches!(
+            decompress_notification(Notification::Data(compressed), &small),
+            Err(DlmsError::DataLimitExceeded)
+        ));
+        let garbage = DataNotification {
+            notification_body: CosemData::OctetString(vec![0xFF; 8]),
+            ..notification.clone()
+        };
+        assert!(decompress_notification(Notification::Data(garbage), &deflate).is_err());
+        assert_eq!(
+            decompress_notification(Notification::Data(notification.clone()), &deflate).unwrap(),
+            Notification::Data(notification)
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use crate::buffer_storage::BufferStorage;
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
-use crate::types::CosemData;
-use crate::xdlms::{ActionResult, DataAccessResult};
+use crate::data_stream::{BufferStream, CosemDataStream};
+use crate::register_monitor::{MonitoredValue, ScriptReference};
+use crate::types::{CosemData, DataType};
+use crate::xdlms::{ActionResult, DataAccessResult, SelectiveAccessDescriptor};
+use std::borrow::Cow;
 use std::boxed::Box;
 use std::fmt;
 use std::sync::{Arc, Mutex};
@@ -188,6 +192,24 @@ impl CosemObjectCallbackHandlers {
         }
     }
 
+    // `call_post_read` for a value that may be borrowed from the object; it
+    // is copied only when a callback is there to change it.
+    pub(crate) fn call_post_read_borrowed<'a>(
+        &self,
+        object: &dyn CosemObject,
+        attribute_id: CosemObjectAttributeId,
+        result: &mut Option<Cow<'a, CosemData>>,
+    ) -> Result<(), DataAccessResult> {
+        let mut post_read = self.post_read.lock().unwrap();
+        let Some(callback) = post_read.as_mut() else {
+            return Ok(());
+        };
+        let mut owned = result.take().map(Cow::into_owned);
+        let outcome = callback(object, attribute_id, &mut owned);
+        *result = owned.map(Cow::Owned);
+        outcome
+    }
+
     pub fn call_pre_write(
         &self,
         object: &mut dyn CosemObject,
@@ -289,6 +311,19 @@ impl AttributeAccessDescriptor {
             selective_access_descriptor,
         }
     }
+
+    // Whether `selector` is among the access selectors declared for the
+    // attribute, an array of integers.
+    pub fn supports_selector(&self, selector: u8) -> bool {
+        let Some(CosemData::Array(selectors)) = &self.selective_access_descriptor else {
+            return false;
+        };
+        selectors.iter().any(|declared| match declared {
+            CosemData::Integer(declared) => *declared as u8 == selector,
+            CosemData::Unsigned(declared) => *declared == selector,
+            _ => false,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -312,6 +347,82 @@ impl MethodAccessDescriptor {
     }
 }
 
+// other-reason of the data-access-result for values outside the declared
+// length or range.
+pub const VALUE_OUT_OF_RANGE: DataAccessResult = DataAccessResult::OtherReason(250);
+
+// Declared type of an attribute. The server checks SET values against it
+// before they reach the pre-write callback and `set_attribute`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSpec {
+    pub attribute_id: CosemObjectAttributeId,
+    pub data_types: Vec<DataType>,
+    pub length: Option<(usize, usize)>,
+    pub range: Option<(i128, i128)>,
+}
+
+impl AttributeSpec {
+    pub fn new(attribute_id: CosemObjectAttributeId, data_type: DataType) -> Self {
+        Self::one_of(attribute_id, &[data_type])
+    }
+
+    pub fn one_of(attribute_id: CosemObjectAttributeId, data_types: &[DataType]) -> Self {
+        Self {
+            attribute_id,
+            data_types: data_types.to_vec(),
+            length: None,
+            range: None,
+        }
+    }
+
+    // Bounds on the number of elements or octets, inclusive.
+    pub fn with_length(mut self, min: usize, max: usize) -> Self {
+        self.length = Some((min, max));
+        self
+    }
+
+    // Bounds on integer values, inclusive.
+    pub fn with_range(mut self, min: i128, max: i128) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn validate(&self, value: &CosemData) -> Result<(), DataAccessResult> {
+        if !self.data_types.contains(&value.data_type()) {
+            return Err(DataAccessResult::TypeUnmatched);
+        }
+        if let (Some((min, max)), Some(length)) = (self.length, value.length()) {
+            if length < min || length > max {
+                return Err(VALUE_OUT_OF_RANGE);
+            }
+        }
+        let number = value
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| value.as_u64().map(i128::from));
+        if let (Some((min, max)), Some(number)) = (self.range, number) {
+            if number < min || number > max {
+                return Err(VALUE_OUT_OF_RANGE);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Checks a value written to an attribute against the spec the object declares
+// for it; attributes without a spec accept any value.
+pub fn validate_attribute_value(
+    object: &dyn CosemObject,
+    attribute_id: CosemObjectAttributeId,
+    value: &CosemData,
+) -> Result<(), DataAccessResult> {
+    object
+        .attribute_specs()
+        .iter()
+        .find(|spec| spec.attribute_id == attribute_id)
+        .map_or(Ok(()), |spec| spec.validate(value))
+}
+
 pub trait CosemObject: Send {
     fn class_id(&self) -> u16;
     fn version(&self) -> u8 {
@@ -320,15 +431,81 @@ pub trait CosemObject: Send {
     fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
         Vec::new()
     }
+    // The access rights of one attribute. Objects with fixed rights override
+    // it to answer without building the whole list on every request.
+    fn attribute_access(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Option<AttributeAccessDescriptor> {
+        self.attribute_access_rights()
+            .into_iter()
+            .find(|descriptor| descriptor.attribute_id == attribute_id)
+    }
     fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
         Vec::new()
     }
+    fn attribute_specs(&self) -> Vec<AttributeSpec> {
+        Vec::new()
+    }
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData>;
     fn set_attribute(
         &mut self,
         attribute_id: CosemObjectAttributeId,
         data: CosemData,
     ) -> Option<()>;
+    // What the server answers a get or set with. Objects override these to
+    // report why an access failed; by default a value of another type than
+    // the current one is type-unmatched and any other failure
+    // object-unavailable.
+    fn read_attribute(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Result<CosemData, DataAccessResult> {
+        self.get_attribute(attribute_id)
+            .ok_or(DataAccessResult::ObjectUnavailable)
+    }
+    // `read_attribute` lending the value instead of copying it, for objects
+    // that hold it as it is served.
+    fn read_attribute_ref(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Result<Cow<'_, CosemData>, DataAccessResult> {
+        self.read_attribute(attribute_id).map(Cow::Owned)
+    }
+    // Reads the part of an attribute a get with selective access asks for.
+    // Objects without access selectors refuse any selection.
+    fn read_attribute_selective(
+        &self,
+        _attribute_id: CosemObjectAttributeId,
+        _selection: &SelectiveAccessDescriptor,
+    ) -> Result<CosemData, DataAccessResult> {
+        Err(DataAccessResult::ScopeOfAccessViolated)
+    }
+    // Writes the part of an attribute a set with selective access names.
+    fn write_attribute_selective(
+        &mut self,
+        _attribute_id: CosemObjectAttributeId,
+        _selection: &SelectiveAccessDescriptor,
+        _data: CosemData,
+    ) -> Result<(), DataAccessResult> {
+        Err(DataAccessResult::ScopeOfAccessViolated)
+    }
+    fn write_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Result<(), DataAccessResult> {
+        let data_type = data.data_type();
+        if self.set_attribute(attribute_id, data).is_some() {
+            return Ok(());
+        }
+        match self.get_attribute(attribute_id) {
+            Some(current) if current.data_type() != data_type => {
+                Err(DataAccessResult::TypeUnmatched)
+            }
+            _ => Err(DataAccessResult::ObjectUnavailable),
+        }
+    }
     fn invoke_method(
         &mut self,
         method_id: CosemObjectMethodId,
@@ -337,4 +514,36 @@ pub trait CosemObject: Send {
     fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
         None
     }
+    // Storage behind attribute 2 (buffer) for objects that keep it outside of
+    // `get_attribute`; the server streams it entry by entry in block transfers.
+    fn buffer(&self) -> Option<&dyn BufferStorage> {
+        None
+    }
+    fn buffer_mut(&mut self) -> Option<&mut dyn BufferStorage> {
+        None
+    }
+    // Attributes too large to encode at once are read through a stream, which
+    // the server drains block by block; the post-read callback is not run for
+    // them. Attribute 2 of objects with a `buffer` is streamed by default.
+    fn attribute_stream(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Option<Box<dyn CosemDataStream>> {
+        let buffer = self.buffer().filter(|_| attribute_id == 2)?;
+        Some(Box::new(BufferStream::new(buffer.len())))
+    }
+    // Objects watching an attribute of another object of the device (register
+    // monitor, limiter). The server resolves the reference at registration,
+    // feeds every change of the value and runs the returned scripts.
+    fn monitored_value(&self) -> Option<MonitoredValue> {
+        None
+    }
+    fn evaluate_monitored_value(
+        &mut self,
+        _previous: &CosemData,
+        _current: &CosemData,
+        _timestamp: u64,
+    ) -> Vec<ScriptReference> {
+        Vec::new()
+    }
 }
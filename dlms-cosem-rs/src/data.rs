@@ -1,11 +1,15 @@
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
-use crate::cosem_object::{CosemObject, CosemObjectCallbackHandlers};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
 use crate::types::CosemData;
 use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Data {
     value: CosemData,
+    // Access to the value granted to clients; none unless given.
+    access_mode: Option<AttributeAccessMode>,
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
@@ -13,10 +17,18 @@ impl Data {
     pub fn new(value: CosemData) -> Self {
         Self {
             value,
+            access_mode: None,
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
 
+    pub fn with_access(value: CosemData, access_mode: AttributeAccessMode) -> Self {
+        Self {
+            access_mode: Some(access_mode),
+            ..Self::new(value)
+        }
+    }
+
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
@@ -27,6 +39,13 @@ impl CosemObject for Data {
         1
     }
 
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        self.access_mode
+            .iter()
+            .map(|mode| AttributeAccessDescriptor::new(2, *mode))
+            .collect()
+    }
+
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
         match attribute_id {
             2 => Some(self.value.clone()),
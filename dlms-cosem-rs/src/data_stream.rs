@@ -0,0 +1,101 @@
+use crate::axdr::{encode_data, encode_length};
+use crate::cosem_object::CosemObject;
+use crate::error::DlmsError;
+use std::fmt;
+use std::vec::Vec;
+
+// Produces the A-XDR encoding of a large attribute value piece by piece, so
+// that the server only holds about one block of it at a time. The stream
+// keeps its position only; the object is handed in on every call, as it may
+// change between the blocks of a transfer.
+pub trait CosemDataStream: Send + fmt::Debug {
+    // Appends encoded bytes to `out` until it holds at least `target` bytes or
+    // the value is complete. `Ok(true)` once the whole value has been written.
+    fn encode_next(
+        &mut self,
+        object: &dyn CosemObject,
+        out: &mut Vec<u8>,
+        target: usize,
+    ) -> Result<bool, DlmsError>;
+}
+
+// Streams the buffer storage of an object (profile generic attribute 2) as an
+// array, one entry at a time. The number of entries is fixed when the stream
+// starts; entries captured meanwhile are not part of the value.
+#[derive(Debug, Clone)]
+pub struct BufferStream {
+    header_written: bool,
+    next_entry: usize,
+    total: usize,
+}
+
+impl BufferStream {
+    pub fn new(total: usize) -> Self {
+        Self {
+            header_written: false,
+            next_entry: 0,
+            total,
+        }
+    }
+}
+
+impl CosemDataStream for BufferStream {
+    fn encode_next(
+        &mut self,
+        object: &dyn CosemObject,
+        out: &mut Vec<u8>,
+        target: usize,
+    ) -> Result<bool, DlmsError> {
+        if !self.header_written {
+            out.push(1);
+            encode_length(self.total, out);
+            self.header_written = true;
+        }
+        if out.len() < target && self.next_entry < self.total {
+            let buffer = object.buffer().ok_or(DlmsError::Cosem)?;
+            let mut entries = buffer.entries(self.next_entry..self.total);
+            while out.len() < target && self.next_entry < self.total {
+                let entry = entries.next().ok_or(DlmsError::Cosem)?;
+                encode_data(&entry, out)?;
+                self.next_entry += 1;
+            }
+        }
+        Ok(self.next_entry >= self.total)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::axdr::decode_data;
+    use crate::profile_generic::{append_buffer_entry, ProfileGeneric};
+    use crate::types::CosemData;
+
+    #[test]
+    fn test_buffer_stream_encodes_in_pieces() {
+        let mut profile = ProfileGeneric::new();
+        for value in 0..10 {
+            append_buffer_entry(&mut profile, CosemData::DoubleLongUnsigned(value)).unwrap();
+        }
+
+        let mut stream = BufferStream::new(10);
+        let mut encoded = Vec::new();
+        let mut pieces = 0;
+        loop {
+            let mut piece = Vec::new();
+            let finished = stream.encode_next(&profile, &mut piece, 8).unwrap();
+            assert!(piece.len() < 8 + 5);
+            encoded.extend(piece);
+            pieces += 1;
+            if finished {
+                break;
+            }
+        }
+        assert!(pieces > 1);
+        assert_eq!(
+            decode_data(&encoded).unwrap().0,
+            CosemData::Array((0..10).map(CosemData::DoubleLongUnsigned).collect())
+        );
+    }
+}
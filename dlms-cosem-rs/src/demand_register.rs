@@ -1,8 +1,12 @@
+use crate::clock::TimeSource;
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
 use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
 };
+use crate::scaled_value::ScaledValue;
 use crate::types::CosemData;
+use std::collections::VecDeque;
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -15,6 +19,13 @@ pub struct DemandRegister {
     start_time_current: CosemData,
     period: CosemData,
     number_of_periods: CosemData,
+    time_source: Option<TimeSource>,
+    // Sliding demand state fed by `feed`: the value-seconds of the last
+    // completed sub-periods, oldest first, and of the current one.
+    sub_periods: VecDeque<i128>,
+    accumulation: i128,
+    last_sample: Option<(i64, u64)>,
+    sub_period_end: u64,
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
@@ -29,6 +40,11 @@ impl DemandRegister {
             start_time_current: CosemData::NullData,
             period: CosemData::NullData,
             number_of_periods: CosemData::NullData,
+            time_source: None,
+            sub_periods: VecDeque::new(),
+            accumulation: 0,
+            last_sample: None,
+            sub_period_end: 0,
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
@@ -36,6 +52,115 @@ impl DemandRegister {
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
+
+    // Stamps capture_time and start_time_current on reset.
+    pub fn set_time_source(&mut self, time_source: TimeSource) {
+        self.time_source = Some(time_source);
+    }
+
+    pub fn current_average_value_i64(&self) -> Option<i64> {
+        self.current_average_value.as_i64()
+    }
+
+    pub fn current_average_value_u64(&self) -> Option<u64> {
+        self.current_average_value.as_u64()
+    }
+
+    pub fn last_average_value_i64(&self) -> Option<i64> {
+        self.last_average_value.as_i64()
+    }
+
+    pub fn last_average_value_u64(&self) -> Option<u64> {
+        self.last_average_value.as_u64()
+    }
+
+    // There is no `accumulate` as on `Register`: both averages are computed
+    // by `feed` from the samples of the window, so a quantity added to them
+    // would be overwritten at the next sample. Meters feed the demand instead.
+
+    // Sample of the metrology loop: `value` holds from `timestamp` (seconds)
+    // until the next sample. Sub-periods of `period` seconds are aligned to
+    // multiples of it; the window spans the last `number_of_periods` of them.
+    // current_average_value is what the window has accumulated so far,
+    // including the running sub-period, and last_average_value the whole
+    // window at the end of each sub-period, both divided by the window length.
+    // `None` without a period or for a timestamp older than the last one.
+    pub fn feed(&mut self, value: i64, timestamp: u64) -> Option<()> {
+        let period = self.period.as_u64().filter(|period| *period > 0)?;
+        let periods = self
+            .number_of_periods
+            .as_u64()
+            .filter(|periods| *periods > 0)
+            .unwrap_or(1);
+        match self.last_sample {
+            None => {
+                self.sub_period_end = (timestamp / period + 1) * period;
+                self.stamp_start();
+            }
+            Some((previous, since)) => {
+                if timestamp < since {
+                    return None;
+                }
+                let mut from = since;
+                // After a gap longer than the window only its last sub-periods
+                // matter.
+                let boundaries = (timestamp.saturating_sub(self.sub_period_end)) / period + 1;
+                if timestamp >= self.sub_period_end && boundaries > periods {
+                    self.sub_period_end += (boundaries - periods) * period;
+                    from = self.sub_period_end - period;
+                    self.accumulation = 0;
+                }
+                while timestamp >= self.sub_period_end {
+                    self.accumulation += previous as i128 * (self.sub_period_end - from) as i128;
+                    from = self.sub_period_end;
+                    self.close_sub_period(periods, period);
+                    self.sub_period_end += period;
+                }
+                self.accumulation += previous as i128 * (timestamp - from) as i128;
+            }
+        }
+        self.last_sample = Some((value, timestamp));
+
+        let window = (periods * period) as i128;
+        let running = self
+            .sub_periods
+            .iter()
+            .rev()
+            .take(periods as usize - 1)
+            .sum::<i128>()
+            + self.accumulation;
+        self.current_average_value = typed_like(&self.current_average_value, running / window);
+        Some(())
+    }
+
+    fn close_sub_period(&mut self, periods: u64, period: u64) {
+        self.sub_periods.push_back(self.accumulation);
+        while self.sub_periods.len() > periods as usize {
+            self.sub_periods.pop_front();
+        }
+        self.accumulation = 0;
+        let total = self.sub_periods.iter().sum::<i128>();
+        self.last_average_value =
+            typed_like(&self.last_average_value, total / (periods * period) as i128);
+        if let Some(time_source) = &self.time_source {
+            self.capture_time = time_source.now();
+        }
+        self.stamp_start();
+    }
+
+    fn stamp_start(&mut self) {
+        if let Some(time_source) = &self.time_source {
+            self.start_time_current = time_source.now();
+        }
+    }
+
+    pub fn current_average_scaled(&self) -> Option<ScaledValue> {
+        ScaledValue::from_cosem_data(&self.current_average_value, &self.scaler_unit)
+    }
+
+    pub fn last_average_scaled(&self) -> Option<ScaledValue> {
+        ScaledValue::from_cosem_data(&self.last_average_value, &self.scaler_unit)
+    }
 }
 
 impl Default for DemandRegister {
@@ -62,6 +187,10 @@ impl CosemObject for DemandRegister {
         ]
     }
 
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+    }
+
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
         match attribute_id {
             2 => Some(self.current_average_value.clone()),
@@ -120,10 +249,13 @@ impl CosemObject for DemandRegister {
 
     fn invoke_method(
         &mut self,
-        _method_id: CosemObjectMethodId,
+        method_id: CosemObjectMethodId,
         _data: CosemData,
     ) -> Option<CosemData> {
-        None
+        match method_id {
+            1 => self.reset(),
+            _ => None,
+        }
     }
 
     fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
@@ -131,6 +263,33 @@ impl CosemObject for DemandRegister {
     }
 }
 
+impl DemandRegister {
+    // Clears both average values, stamps capture_time and start_time_current
+    // and returns the previous current_average_value.
+    fn reset(&mut self) -> Option<CosemData> {
+        let previous = self.current_average_value.zero_like();
+        self.last_average_value = self.last_average_value.zero_like();
+        self.sub_periods.clear();
+        self.accumulation = 0;
+        if let Some(time_source) = &self.time_source {
+            self.capture_time = time_source.now();
+            self.start_time_current = self.capture_time.clone();
+        }
+        Some(std::mem::replace(&mut self.current_average_value, previous))
+    }
+}
+
+// An average in the integer type of the attribute it replaces; double-long
+// while the attribute is still unset, long64 if it does not fit.
+fn typed_like(template: &CosemData, value: i128) -> CosemData {
+    let value = value.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+    let zero = match template {
+        CosemData::NullData => CosemData::DoubleLong(0),
+        other => other.zero_like(),
+    };
+    zero.checked_add(value).unwrap_or(CosemData::Long64(value))
+}
+
 #[cfg(all(test, feature = "std"))]
 mod tests {
     extern crate std;
@@ -148,4 +307,74 @@ mod tests {
         assert_eq!(register.get_attribute(8), Some(CosemData::NullData));
         assert_eq!(register.get_attribute(9), Some(CosemData::NullData));
     }
+
+    #[test]
+    fn test_demand_register_reset() {
+        let mut register = DemandRegister::new();
+        let now = CosemData::DateTime(vec![0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0]);
+        let stamp = now.clone();
+        register.set_time_source(TimeSource::new(move || stamp.clone()));
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(120))
+            .unwrap();
+        register
+            .set_attribute(3, CosemData::DoubleLongUnsigned(80))
+            .unwrap();
+
+        assert_eq!(
+            register.invoke_method(1, CosemData::Integer(0)),
+            Some(CosemData::DoubleLongUnsigned(120))
+        );
+        assert_eq!(
+            register.get_attribute(2),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        assert_eq!(
+            register.get_attribute(3),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        assert_eq!(register.get_attribute(6), Some(now.clone()));
+        assert_eq!(register.get_attribute(7), Some(now));
+    }
+
+    #[test]
+    fn test_sliding_demand_over_sub_periods() {
+        let mut register = DemandRegister::new();
+        assert_eq!(register.feed(100, 0), None);
+        // Three sub-periods of 60 s form a window of 180 s.
+        register
+            .set_attribute(8, CosemData::DoubleLongUnsigned(60))
+            .unwrap();
+        register
+            .set_attribute(9, CosemData::LongUnsigned(3))
+            .unwrap();
+
+        register.feed(300, 0).unwrap();
+        register.feed(300, 30).unwrap();
+        assert_eq!(register.current_average_value_i64(), Some(50));
+        assert_eq!(register.get_attribute(3), Some(CosemData::NullData));
+
+        register.feed(600, 60).unwrap();
+        assert_eq!(register.last_average_value_i64(), Some(100));
+        register.feed(0, 120).unwrap();
+        // 300 and 600 over the window of three sub-periods.
+        assert_eq!(register.last_average_value_i64(), Some(300));
+        assert_eq!(register.current_average_value_i64(), Some(300));
+        register.feed(0, 180).unwrap();
+        assert_eq!(register.last_average_value_i64(), Some(300));
+        // The first sub-period drops out of the window.
+        register.feed(0, 240).unwrap();
+        assert_eq!(register.last_average_value_i64(), Some(200));
+        assert_eq!(register.current_average_value_i64(), Some(0));
+        assert_eq!(register.feed(0, 200), None);
+
+        // A long outage holding 90 fills the whole window.
+        register.feed(90, 250).unwrap();
+        register.feed(90, 100_000).unwrap();
+        assert_eq!(register.last_average_value_i64(), Some(90));
+
+        register.invoke_method(1, CosemData::Integer(0)).unwrap();
+        register.feed(0, 100_020).unwrap();
+        assert_eq!(register.current_average_value_i64(), Some(10));
+    }
 }
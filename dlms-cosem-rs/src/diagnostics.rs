@@ -0,0 +1,81 @@
+// Diagnostics of protocol events: frames received, association state
+// changes, access denials and parse errors. They go to `log` when that
+// feature is enabled and compile to nothing otherwise.
+//
+// There is no defmt backend: the crate links std, so it does not build for
+// the bare-metal targets defmt logs on. One can be added here, next to log,
+// once the crate builds as no_std.
+
+macro_rules! diagnostic {
+    ($level:ident, $format:literal $(, $argument:expr)* $(,)?) => {{
+        #[cfg(feature = "log")]
+        log::$level!($format $(, $argument)*);
+        #[cfg(not(feature = "log"))]
+        {
+            $(let _ = &$argument;)*
+        }
+    }};
+}
+
+macro_rules! debug {
+    ($($tokens:tt)*) => {
+        $crate::diagnostics::diagnostic!(debug, $($tokens)*)
+    };
+}
+
+macro_rules! info {
+    ($($tokens:tt)*) => {
+        $crate::diagnostics::diagnostic!(info, $($tokens)*)
+    };
+}
+
+macro_rules! warning {
+    ($($tokens:tt)*) => {
+        $crate::diagnostics::diagnostic!(warn, $($tokens)*)
+    };
+}
+
+pub(crate) use {debug, diagnostic, info, warning};
+
+#[cfg(all(test, feature = "std", feature = "log"))]
+mod tests {
+    extern crate std;
+    use crate::server::Server;
+    use crate::testing::DetachedTransport;
+    use std::string::{String, ToString};
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            RECORDS
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_protocol_events_are_logged() {
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        let mut server = Server::new(0x0001, DetachedTransport, None, None);
+        let _ = server.process_frame(&[0x7E, 0x00, 0x7E]);
+
+        // Other tests may log meanwhile.
+        assert!(RECORDS
+            .lock()
+            .unwrap()
+            .contains(&(log::Level::Warn, "frame not parsed, 3 octets".to_string())));
+    }
+}
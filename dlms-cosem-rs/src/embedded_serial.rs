@@ -0,0 +1,215 @@
+#![cfg(feature = "embedded-io")]
+
+use crate::hdlc::HDLC_FLAG;
+use crate::transport::Transport;
+use embedded_io::{Read, Write};
+use std::vec::Vec;
+
+// Bytes asked of the UART per read.
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 64;
+
+// Longest frame accepted, flags included: an information field of 2048 bytes,
+// the largest the HDLC parameter negotiation allows, with its header and FCS.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 2048 + 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EmbeddedSerialError<E> {
+    Io(E),
+    // The UART reported the end of its input.
+    EndOfStream,
+    // A frame longer than the maximum frame size; it is dropped up to the
+    // next flag.
+    FrameTooLong,
+}
+
+// HDLC over a UART of embedded-io, for bare-metal gateways reading meters
+// over RS-485. Frames are delimited by their flags, which frames sent back to
+// back may share; bytes between two flags that do not start like a frame are
+// skipped as line noise.
+pub struct EmbeddedSerialTransport<U> {
+    uart: U,
+    read_buffer: Vec<u8>,
+    // Bytes of `read_buffer` not handed to the framer yet.
+    pending: core::ops::Range<usize>,
+    frame: Vec<u8>,
+    max_frame_size: usize,
+    in_frame: bool,
+    overflowed: bool,
+}
+
+impl<U: Read + Write> EmbeddedSerialTransport<U> {
+    pub fn new(uart: U) -> Self {
+        Self {
+            uart,
+            read_buffer: vec![0; DEFAULT_READ_BUFFER_SIZE],
+            pending: 0..0,
+            frame: Vec::new(),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            in_frame: false,
+            overflowed: false,
+        }
+    }
+
+    // At least one byte. Bytes read but not yet framed are kept.
+    pub fn set_read_buffer_size(&mut self, size: usize) {
+        let pending = self.read_buffer[self.pending.clone()].to_vec();
+        self.read_buffer = vec![0; size.max(1).max(pending.len())];
+        self.read_buffer[..pending.len()].copy_from_slice(&pending);
+        self.pending = 0..pending.len();
+    }
+
+    pub fn set_max_frame_size(&mut self, size: usize) {
+        self.max_frame_size = size;
+    }
+
+    pub fn uart(&mut self) -> &mut U {
+        &mut self.uart
+    }
+
+    pub fn into_uart(self) -> U {
+        self.uart
+    }
+
+    // Feeds one byte to the framer; a frame when `byte` closes one.
+    fn frame_byte(&mut self, byte: u8) -> Option<Result<Vec<u8>, EmbeddedSerialError<U::Error>>> {
+        if byte != HDLC_FLAG {
+            if !self.in_frame || self.overflowed {
+                return None;
+            }
+            if self.frame.len() + 2 < self.max_frame_size {
+                self.frame.push(byte);
+            } else {
+                self.overflowed = true;
+            }
+            return None;
+        }
+        // A closing flag may also open the next frame.
+        self.in_frame = true;
+        let content = core::mem::take(&mut self.frame);
+        if core::mem::take(&mut self.overflowed) {
+            return Some(Err(EmbeddedSerialError::FrameTooLong));
+        }
+        // Frame format type 3, as anything else between two flags is noise.
+        if content.len() < 2 || content[0] & 0xF0 != 0xA0 {
+            return None;
+        }
+        let mut frame = Vec::with_capacity(content.len() + 2);
+        frame.push(HDLC_FLAG);
+        frame.extend_from_slice(&content);
+        frame.push(HDLC_FLAG);
+        Some(Ok(frame))
+    }
+}
+
+impl<U: Read + Write> Transport for EmbeddedSerialTransport<U> {
+    type Error = EmbeddedSerialError<U::Error>;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.uart
+            .write_all(bytes)
+            .map_err(EmbeddedSerialError::Io)?;
+        self.uart.flush().map_err(EmbeddedSerialError::Io)
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        loop {
+            while let Some(index) = self.pending.next() {
+                if let Some(frame) = self.frame_byte(self.read_buffer[index]) {
+                    return frame;
+                }
+            }
+            let read = self
+                .uart
+                .read(&mut self.read_buffer)
+                .map_err(EmbeddedSerialError::Io)?;
+            if read == 0 {
+                return Err(EmbeddedSerialError::EndOfStream);
+            }
+            self.pending = 0..read;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_io::ErrorType;
+
+    // A UART that hands out its input a few bytes per read.
+    struct FakeUart {
+        input: Vec<u8>,
+        chunk: usize,
+        output: Vec<u8>,
+        flushed: bool,
+    }
+
+    impl ErrorType for FakeUart {
+        type Error = Infallible;
+    }
+
+    impl Read for FakeUart {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let count = self.input.len().min(self.chunk).min(buf.len());
+            buf[..count].copy_from_slice(&self.input[..count]);
+            self.input.drain(..count);
+            Ok(count)
+        }
+    }
+
+    impl Write for FakeUart {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.output.extend_from_slice(buf);
+            self.flushed = false;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flushed = true;
+            Ok(())
+        }
+    }
+
+    fn uart(input: Vec<u8>, chunk: usize) -> FakeUart {
+        FakeUart {
+            input,
+            chunk,
+            output: Vec::new(),
+            flushed: false,
+        }
+    }
+
+    #[test]
+    fn test_frames_split_across_reads() {
+        let first = vec![HDLC_FLAG, 0xA0, 0x07, 0x03, 0x21, 0x93, HDLC_FLAG];
+        let second = vec![HDLC_FLAG, 0xA0, 0x05, 0x21, HDLC_FLAG];
+        // Noise before the first frame, and the second sharing its flag.
+        let mut input = vec![0x00, 0x55];
+        input.extend_from_slice(&first);
+        input.extend_from_slice(&second[1..]);
+        let mut transport = EmbeddedSerialTransport::new(uart(input, 3));
+        transport.set_read_buffer_size(2);
+
+        assert_eq!(transport.receive(), Ok(first.clone()));
+        assert_eq!(transport.receive(), Ok(second));
+        assert_eq!(transport.receive(), Err(EmbeddedSerialError::EndOfStream));
+
+        transport.send(&first).unwrap();
+        let uart = transport.into_uart();
+        assert_eq!(uart.output, first);
+        assert!(uart.flushed);
+    }
+
+    #[test]
+    fn test_oversized_frame_is_dropped() {
+        let frame = vec![HDLC_FLAG, 0xA0, 0x05, 0x21, HDLC_FLAG];
+        let mut input = vec![HDLC_FLAG, 1, 2, 3, 4, 5, 6, HDLC_FLAG];
+        input.extend_from_slice(&frame);
+        let mut transport = EmbeddedSerialTransport::new(uart(input, 64));
+        transport.set_max_frame_size(5);
+
+        assert_eq!(transport.receive(), Err(EmbeddedSerialError::FrameTooLong));
+        assert_eq!(transport.receive(), Ok(frame));
+    }
+}
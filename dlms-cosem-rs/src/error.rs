@@ -15,6 +15,8 @@ pub enum DlmsError {
     VecIsFull,
     // Parsing error
     ParseError,
+    // Data nested deeper or holding more elements than the limits allow
+    DataLimitExceeded,
 }
 
 impl<'a> From<nom::Err<nom::error::Error<&'a [u8]>>> for DlmsError {
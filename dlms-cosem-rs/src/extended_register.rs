@@ -1,8 +1,10 @@
+use crate::clock::TimeSource;
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
 use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
     MethodAccessDescriptor, MethodAccessMode,
 };
+use crate::profile_generic::ProfileStatus;
 use crate::types::CosemData;
 use std::sync::Arc;
 
@@ -12,6 +14,7 @@ pub struct ExtendedRegister {
     scaler_unit: CosemData,
     status: CosemData,
     capture_time: CosemData,
+    time_source: Option<TimeSource>,
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
@@ -22,6 +25,7 @@ impl ExtendedRegister {
             scaler_unit: CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(255)]),
             status: CosemData::NullData,
             capture_time: CosemData::NullData,
+            time_source: None,
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
@@ -29,6 +33,38 @@ impl ExtendedRegister {
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
+
+    // Stamps capture_time on reset.
+    pub fn set_time_source(&mut self, time_source: TimeSource) {
+        self.time_source = Some(time_source);
+    }
+
+    // `None` while the status is not set or is not a status byte.
+    pub fn profile_status(&self) -> Option<ProfileStatus> {
+        ProfileStatus::from_cosem_data(&self.status)
+    }
+
+    pub fn set_profile_status(&mut self, status: ProfileStatus) {
+        self.status = status.to_cosem_data();
+    }
+
+    pub fn value_i64(&self) -> Option<i64> {
+        self.value.as_i64()
+    }
+
+    pub fn value_u64(&self) -> Option<u64> {
+        self.value.as_u64()
+    }
+
+    pub fn accumulate(&mut self, delta: i64) -> Option<()> {
+        self.value = self.value.checked_add(delta)?;
+        Some(())
+    }
+
+    pub fn accumulate_u64(&mut self, delta: u64) -> Option<()> {
+        self.value = self.value.checked_add_u64(delta)?;
+        Some(())
+    }
 }
 
 impl Default for ExtendedRegister {
@@ -108,9 +144,13 @@ impl CosemObject for ExtendedRegister {
 }
 
 impl ExtendedRegister {
+    // Clears the value, stamps capture_time and returns the previous value.
     fn reset(&mut self) -> Option<CosemData> {
-        self.value = CosemData::Unsigned(0);
-        Some(CosemData::NullData)
+        let previous = self.value.zero_like();
+        if let Some(time_source) = &self.time_source {
+            self.capture_time = time_source.now();
+        }
+        Some(std::mem::replace(&mut self.value, previous))
     }
 }
 
@@ -141,12 +181,31 @@ mod tests {
         assert_eq!(register.get_attribute(2), Some(CosemData::Unsigned(10)));
     }
 
+    #[test]
+    fn test_extended_register_profile_status() {
+        let mut register = ExtendedRegister::new();
+        assert_eq!(register.profile_status(), None);
+        let status = ProfileStatus::default()
+            .with(ProfileStatus::POWER_DOWN, true)
+            .with(ProfileStatus::CLOCK_ADJUSTED, true);
+        register.set_profile_status(status);
+        assert_eq!(register.get_attribute(4), Some(status.to_cosem_data()));
+        let status = register.profile_status().unwrap();
+        assert_eq!(status.bits(), 0xA0);
+        assert!(status.power_down() && status.clock_adjusted());
+        assert!(!status.clock_invalid() && !status.critical_error());
+    }
+
     #[test]
     fn test_extended_register_reset() {
         let mut register = ExtendedRegister::new();
+        let now = CosemData::DateTime(vec![0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0]);
+        let stamp = now.clone();
+        register.set_time_source(TimeSource::new(move || stamp.clone()));
         register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
         assert_eq!(register.get_attribute(2), Some(CosemData::Unsigned(10)));
-        register.reset();
+        assert_eq!(register.reset(), Some(CosemData::Unsigned(10)));
         assert_eq!(register.get_attribute(2), Some(CosemData::Unsigned(0)));
+        assert_eq!(register.get_attribute(5), Some(now));
     }
 }
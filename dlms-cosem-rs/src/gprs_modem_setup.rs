@@ -0,0 +1,206 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+pub const GPRS_MODEM_SETUP_LN: [u8; 6] = [0, 0, 25, 4, 0, 255];
+
+// qos_element ::= structure { precedence, delay, reliability, peak_throughput, mean_throughput }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QosElement {
+    pub precedence: u8,
+    pub delay: u8,
+    pub reliability: u8,
+    pub peak_throughput: u8,
+    pub mean_throughput: u8,
+}
+
+impl QosElement {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::Unsigned(self.precedence),
+            CosemData::Unsigned(self.delay),
+            CosemData::Unsigned(self.reliability),
+            CosemData::Unsigned(self.peak_throughput),
+            CosemData::Unsigned(self.mean_throughput),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::Unsigned(precedence), CosemData::Unsigned(delay), CosemData::Unsigned(reliability), CosemData::Unsigned(peak_throughput), CosemData::Unsigned(mean_throughput)] => {
+                Some(QosElement {
+                    precedence: *precedence,
+                    delay: *delay,
+                    reliability: *reliability,
+                    peak_throughput: *peak_throughput,
+                    mean_throughput: *mean_throughput,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// quality_of_service ::= structure { default, requested }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QualityOfService {
+    pub default: QosElement,
+    pub requested: QosElement,
+}
+
+impl QualityOfService {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            self.default.to_cosem_data(),
+            self.requested.to_cosem_data(),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [default, requested] => Some(QualityOfService {
+                default: QosElement::from_cosem_data(default)?,
+                requested: QosElement::from_cosem_data(requested)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GprsModemSetup {
+    apn: CosemData,
+    pin_code: CosemData,
+    quality_of_service: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl GprsModemSetup {
+    pub fn new() -> Self {
+        Self {
+            apn: CosemData::OctetString(Vec::new()),
+            pin_code: CosemData::LongUnsigned(0),
+            quality_of_service: QualityOfService::default().to_cosem_data(),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn apn(&self) -> Option<&[u8]> {
+        match &self.apn {
+            CosemData::OctetString(apn) => Some(apn),
+            _ => None,
+        }
+    }
+
+    pub fn quality_of_service(&self) -> Option<QualityOfService> {
+        QualityOfService::from_cosem_data(&self.quality_of_service)
+    }
+}
+
+impl Default for GprsModemSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for GprsModemSetup {
+    fn class_id(&self) -> u16 {
+        45
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        vec![
+            AttributeAccessDescriptor::new(2, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(3, AttributeAccessMode::Write),
+            AttributeAccessDescriptor::new(4, AttributeAccessMode::ReadWrite),
+        ]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.apn.clone()),
+            3 => Some(self.pin_code.clone()),
+            4 => Some(self.quality_of_service.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match (attribute_id, &data) {
+            (2, CosemData::OctetString(_)) => {
+                self.apn = data;
+                Some(())
+            }
+            (3, CosemData::LongUnsigned(_)) => {
+                self.pin_code = data;
+                Some(())
+            }
+            (4, _) => {
+                QualityOfService::from_cosem_data(&data)?;
+                self.quality_of_service = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        None
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_gprs_modem_setup_attributes() {
+        let mut setup = GprsModemSetup::new();
+        setup
+            .set_attribute(2, CosemData::OctetString(b"internet.example".to_vec()))
+            .unwrap();
+        assert_eq!(setup.apn(), Some(b"internet.example".as_slice()));
+        assert!(setup.set_attribute(3, CosemData::Unsigned(1)).is_none());
+
+        let qos = QualityOfService {
+            default: QosElement::default(),
+            requested: QosElement {
+                precedence: 1,
+                delay: 4,
+                reliability: 3,
+                peak_throughput: 9,
+                mean_throughput: 31,
+            },
+        };
+        setup.set_attribute(4, qos.to_cosem_data()).unwrap();
+        assert_eq!(setup.quality_of_service(), Some(qos));
+        assert!(setup.set_attribute(4, CosemData::NullData).is_none());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+pub const GSM_DIAGNOSTIC_LN: [u8; 6] = [0, 0, 25, 6, 0, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationStatus {
+    NotRegistered = 0,
+    RegisteredHome = 1,
+    Searching = 2,
+    RegistrationDenied = 3,
+    Unknown = 4,
+    RegisteredRoaming = 5,
+}
+
+impl From<u8> for RegistrationStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => RegistrationStatus::NotRegistered,
+            1 => RegistrationStatus::RegisteredHome,
+            2 => RegistrationStatus::Searching,
+            3 => RegistrationStatus::RegistrationDenied,
+            5 => RegistrationStatus::RegisteredRoaming,
+            _ => RegistrationStatus::Unknown,
+        }
+    }
+}
+
+// cell_info_type ::= structure { cell_ID, location_ID, signal_quality, ber, mcc, mnc, channel_number }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CellInfo {
+    pub cell_id: u32,
+    pub location_id: u16,
+    pub signal_quality: u8,
+    pub ber: u8,
+    pub mcc: u16,
+    pub mnc: u16,
+    pub channel_number: u32,
+}
+
+impl CellInfo {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::DoubleLongUnsigned(self.cell_id),
+            CosemData::LongUnsigned(self.location_id),
+            CosemData::Unsigned(self.signal_quality),
+            CosemData::Unsigned(self.ber),
+            CosemData::LongUnsigned(self.mcc),
+            CosemData::LongUnsigned(self.mnc),
+            CosemData::DoubleLongUnsigned(self.channel_number),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::DoubleLongUnsigned(cell_id), CosemData::LongUnsigned(location_id), CosemData::Unsigned(signal_quality), CosemData::Unsigned(ber), CosemData::LongUnsigned(mcc), CosemData::LongUnsigned(mnc), CosemData::DoubleLongUnsigned(channel_number)] => {
+                Some(CellInfo {
+                    cell_id: *cell_id,
+                    location_id: *location_id,
+                    signal_quality: *signal_quality,
+                    ber: *ber,
+                    mcc: *mcc,
+                    mnc: *mnc,
+                    channel_number: *channel_number,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// GSM diagnostic (class 47, version 1). The modem driver of the host reports
+// its state with the update methods; clients only read the attributes.
+#[derive(Debug)]
+pub struct GsmDiagnostic {
+    operator: CosemData,
+    status: CosemData,
+    cs_attachment: CosemData,
+    ps_status: CosemData,
+    cell_info: CosemData,
+    adjacent_cells: CosemData,
+    capture_time: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl GsmDiagnostic {
+    pub fn new() -> Self {
+        Self {
+            operator: CosemData::VisibleString(String::new()),
+            status: CosemData::Enum(RegistrationStatus::NotRegistered as u8),
+            cs_attachment: CosemData::Enum(0),
+            ps_status: CosemData::Enum(0),
+            cell_info: CellInfo::default().to_cosem_data(),
+            adjacent_cells: CosemData::Array(Vec::new()),
+            capture_time: CosemData::NullData,
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn registration_status(&self) -> RegistrationStatus {
+        match self.status {
+            CosemData::Enum(status) => status.into(),
+            _ => RegistrationStatus::Unknown,
+        }
+    }
+
+    pub fn cell_info(&self) -> Option<CellInfo> {
+        CellInfo::from_cosem_data(&self.cell_info)
+    }
+
+    pub fn update_registration(&mut self, operator: &str, status: RegistrationStatus) {
+        self.operator = CosemData::VisibleString(operator.into());
+        self.status = CosemData::Enum(status as u8);
+    }
+
+    // Serving cell and neighbours as (cell_id, signal_quality), stamped with
+    // the date-time of the capture.
+    pub fn update_cells(
+        &mut self,
+        cell_info: &CellInfo,
+        adjacent_cells: &[(u32, u8)],
+        capture_time: CosemData,
+    ) {
+        self.cell_info = cell_info.to_cosem_data();
+        self.adjacent_cells = CosemData::Array(
+            adjacent_cells
+                .iter()
+                .map(|(cell_id, signal_quality)| {
+                    CosemData::Structure(vec![
+                        CosemData::DoubleLongUnsigned(*cell_id),
+                        CosemData::Unsigned(*signal_quality),
+                    ])
+                })
+                .collect(),
+        );
+        self.capture_time = capture_time;
+    }
+}
+
+impl Default for GsmDiagnostic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for GsmDiagnostic {
+    fn class_id(&self) -> u16 {
+        47
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=8)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::Read)
+            })
+            .collect()
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.operator.clone()),
+            3 => Some(self.status.clone()),
+            4 => Some(self.cs_attachment.clone()),
+            5 => Some(self.ps_status.clone()),
+            6 => Some(self.cell_info.clone()),
+            7 => Some(self.adjacent_cells.clone()),
+            8 => Some(self.capture_time.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.operator = data;
+                Some(())
+            }
+            3 => {
+                self.status = data;
+                Some(())
+            }
+            4 => {
+                self.cs_attachment = data;
+                Some(())
+            }
+            5 => {
+                self.ps_status = data;
+                Some(())
+            }
+            6 => {
+                self.cell_info = data;
+                Some(())
+            }
+            7 => {
+                self.adjacent_cells = data;
+                Some(())
+            }
+            8 => {
+                self.capture_time = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        None
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_gsm_diagnostic_updates() {
+        let mut diagnostic = GsmDiagnostic::new();
+        assert_eq!(
+            diagnostic.registration_status(),
+            RegistrationStatus::NotRegistered
+        );
+
+        diagnostic.update_registration("Operator", RegistrationStatus::RegisteredRoaming);
+        let cell = CellInfo {
+            cell_id: 0x1234,
+            location_id: 42,
+            signal_quality: 20,
+            ber: 0,
+            mcc: 250,
+            mnc: 1,
+            channel_number: 75,
+        };
+        diagnostic.update_cells(&cell, &[(0x1235, 12)], CosemData::NullData);
+
+        assert_eq!(
+            diagnostic.get_attribute(2),
+            Some(CosemData::VisibleString("Operator".into()))
+        );
+        assert_eq!(
+            diagnostic.registration_status(),
+            RegistrationStatus::RegisteredRoaming
+        );
+        assert_eq!(diagnostic.cell_info(), Some(cell));
+        assert_eq!(
+            diagnostic.get_attribute(7),
+            Some(CosemData::Array(vec![CosemData::Structure(vec![
+                CosemData::DoubleLongUnsigned(0x1235),
+                CosemData::Unsigned(12),
+            ])]))
+        );
+    }
+}
@@ -15,8 +15,175 @@ pub const CRC_CCITT_FALSE: crc::Algorithm<u16> = crc::Algorithm {
 };
 pub const CRC_ALGORITHM: Crc<u16> = Crc::<u16>::new(&CRC_CCITT_FALSE);
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+// Reserved values of the upper (logical device) and lower (physical device)
+// halves of a server address.
+pub const HDLC_NO_STATION: u16 = 0x0000;
+pub const HDLC_ALL_STATION: u16 = 0x3FFF;
+
+// Number of bytes of a server address. The one byte form carries the upper
+// (logical device) address only, the two byte form seven bits of each half
+// and the four byte form fourteen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HdlcAddressSize {
+    One,
+    Two,
+    #[default]
+    Four,
+}
+
+impl HdlcAddressSize {
+    fn len(self) -> usize {
+        match self {
+            HdlcAddressSize::One => 1,
+            HdlcAddressSize::Two => 2,
+            HdlcAddressSize::Four => 4,
+        }
+    }
+}
+
+// Server side HDLC address: the upper half addresses the logical device, the
+// lower half the physical device. Seven address bits go in each byte, the
+// least significant bit marking the last byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HdlcAddress {
+    pub upper: u16,
+    pub lower: u16,
+    pub size: HdlcAddressSize,
+}
+
+impl HdlcAddress {
+    pub const ALL_STATION: HdlcAddress = HdlcAddress {
+        upper: HDLC_ALL_STATION,
+        lower: HDLC_ALL_STATION,
+        size: HdlcAddressSize::Four,
+    };
+
+    // Four byte address, which can carry any logical and physical address.
+    pub fn new(upper: u16, lower: u16) -> Self {
+        HdlcAddress {
+            upper,
+            lower,
+            size: HdlcAddressSize::Four,
+        }
+    }
+
+    // Address of a logical device in the shortest form able to carry it, e.g.
+    // logical 1 / physical 17 fits the two byte form, physical 0x1000 does not.
+    pub fn for_device(logical_device: u16, physical_device: u16) -> Self {
+        let address = HdlcAddress::new(logical_device, physical_device);
+        [HdlcAddressSize::One, HdlcAddressSize::Two]
+            .into_iter()
+            .map(|size| address.with_size(size))
+            .find(|address| address.to_bytes().is_ok())
+            .unwrap_or(address)
+    }
+
+    pub fn with_size(mut self, size: HdlcAddressSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    // The address bytes as sent in the frame; fails when a half does not fit
+    // the configured size.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, HdlcFrameError> {
+        let mut bytes = Vec::with_capacity(self.size.len());
+        self.encode(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        self.upper == HDLC_ALL_STATION || self.lower == HDLC_ALL_STATION
+    }
+
+    // Whether a server with the given logical and physical address must process
+    // a frame sent to this address.
+    pub fn addresses(&self, logical_address: u16, physical_address: u16) -> bool {
+        let matches = |part: u16, own: u16| part == own || part == HDLC_ALL_STATION;
+        self.upper != HDLC_NO_STATION
+            && self.lower != HDLC_NO_STATION
+            && matches(self.upper, logical_address)
+            && matches(self.lower, physical_address)
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) -> Result<(), HdlcFrameError> {
+        let (bytes, length) = self.encode_array()?;
+        buf.extend_from_slice(&bytes[..length]);
+        Ok(())
+    }
+
+    // The address bytes in the first `length` bytes of an array.
+    fn encode_array(&self) -> Result<([u8; 4], usize), HdlcFrameError> {
+        // The short forms have 0x7F for the all-station address.
+        let short = |part: u16| match part {
+            HDLC_ALL_STATION => Ok(0x7F),
+            0x7F.. => Err(HdlcFrameError::InvalidAddress),
+            part => Ok(part as u8),
+        };
+        let mut bytes = [0u8; 4];
+        match self.size {
+            HdlcAddressSize::One => {
+                if self.lower != HDLC_ALL_STATION {
+                    return Err(HdlcFrameError::InvalidAddress);
+                }
+                bytes[0] = short(self.upper)? << 1;
+            }
+            HdlcAddressSize::Two => {
+                bytes[0] = short(self.upper)? << 1;
+                bytes[1] = short(self.lower)? << 1;
+            }
+            HdlcAddressSize::Four => {
+                for (index, part) in [self.upper, self.lower].into_iter().enumerate() {
+                    if part > HDLC_ALL_STATION {
+                        return Err(HdlcFrameError::InvalidAddress);
+                    }
+                    bytes[index * 2] = ((part >> 7) & 0x7F) as u8 * 2;
+                    bytes[index * 2 + 1] = (part & 0x7F) as u8 * 2;
+                }
+            }
+        }
+        let length = self.size.len();
+        bytes[length - 1] |= 1;
+        Ok((bytes, length))
+    }
+
+    fn parse(bytes: &[u8]) -> Option<(Self, usize)> {
+        let length = bytes.iter().take(4).position(|byte| byte & 1 == 1)? + 1;
+        let value = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .fold(0u16, |value, byte| (value << 7) | (byte >> 1) as u16)
+        };
+        // The one byte form carries the upper address only; a lower address of
+        // 0x7F is the all-station address of the short forms.
+        let widen = |part: u16, bits: u32| {
+            if part == (1 << bits) - 1 {
+                HDLC_ALL_STATION
+            } else {
+                part
+            }
+        };
+        let address = match length {
+            1 => HdlcAddress::new(widen(value(&bytes[..1]), 7), HDLC_ALL_STATION)
+                .with_size(HdlcAddressSize::One),
+            2 => HdlcAddress::new(widen(value(&bytes[..1]), 7), widen(value(&bytes[1..2]), 7))
+                .with_size(HdlcAddressSize::Two),
+            4 => HdlcAddress::new(value(&bytes[..2]), value(&bytes[2..4])),
+            _ => return None,
+        };
+        Some((address, length))
+    }
+}
+
+impl Default for HdlcAddress {
+    fn default() -> Self {
+        HdlcAddress::ALL_STATION
+    }
+}
+
+// `address` is the source of the frame, `destination` the server it is sent to.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct HdlcFrame {
+    pub destination: HdlcAddress,
     pub address: u16,
     pub control: u8,
     pub information: Vec<u8>,
@@ -26,6 +193,8 @@ pub struct HdlcFrame {
 pub enum HdlcFrameError {
     InvalidFrame,
     InvalidFcs,
+    // A server address half does not fit the configured address size.
+    InvalidAddress,
 }
 
 impl From<HdlcFrameError> for DlmsError {
@@ -33,48 +202,52 @@ impl From<HdlcFrameError> for DlmsError {
         match e {
             HdlcFrameError::InvalidFrame => DlmsError::Hdlc,
             HdlcFrameError::InvalidFcs => DlmsError::Hdlc,
+            HdlcFrameError::InvalidAddress => DlmsError::Hdlc,
         }
     }
 }
 
 impl HdlcFrame {
     pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
-        let mut frame = Vec::new();
-        frame.push(HDLC_FLAG);
-
-        let mut data_to_checksum = Vec::new();
-        data_to_checksum.extend_from_slice(&self.address.to_be_bytes());
-        data_to_checksum.push(self.control);
-        data_to_checksum.extend_from_slice(&self.information);
-
-        let checksum = CRC_ALGORITHM.checksum(&data_to_checksum);
-
-        let mut frame_body = Vec::new();
-        frame_body.extend_from_slice(&self.address.to_be_bytes());
-        frame_body.push(self.control);
-        frame_body.extend_from_slice(&self.information);
-        frame_body.extend_from_slice(&checksum.to_le_bytes());
-
-        for byte in frame_body {
-            if byte == HDLC_FLAG || byte == 0x7D {
-                frame.push(0x7D);
-                frame.push(byte ^ 0x20);
-            } else {
-                frame.push(byte);
-            }
-        }
+        // Flags, address, FCS and a few escapes around the information.
+        let mut frame = Vec::with_capacity(self.information.len() + 16);
+        self.encode_into(&mut frame)?;
+        Ok(frame)
+    }
 
+    // Appends the frame to `frame`, so that a buffer can be reused for the
+    // frames of a session.
+    pub fn encode_into(&self, frame: &mut Vec<u8>) -> Result<(), DlmsError> {
+        let (address, address_length) = self.destination.encode_array()?;
+        let mut digest = CRC_ALGORITHM.digest();
+        let mut push = |frame: &mut Vec<u8>, bytes: &[u8]| {
+            digest.update(bytes);
+            push_escaped(frame, bytes);
+        };
         frame.push(HDLC_FLAG);
-
-        Ok(frame)
+        push(frame, &address[..address_length]);
+        push(frame, &self.address.to_be_bytes());
+        push(frame, &[self.control]);
+        push(frame, &self.information);
+        push_escaped(frame, &digest.finalize().to_le_bytes());
+        frame.push(HDLC_FLAG);
+        Ok(())
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_in(bytes, Vec::with_capacity(bytes.len().saturating_sub(2)))
+    }
+
+    // `from_bytes` unescaping into `buffer`, e.g. the information of the
+    // previous frame, so that a session does not allocate for each frame.
+    pub fn from_bytes_in(bytes: &[u8], buffer: Vec<u8>) -> Result<Self, DlmsError> {
         if bytes.len() < 6 || bytes[0] != HDLC_FLAG || bytes[bytes.len() - 1] != HDLC_FLAG {
             return Err(HdlcFrameError::InvalidFrame.into());
         }
 
-        let mut frame_body = Vec::new();
+        // The body is unescaped in place of the information it ends up as.
+        let mut frame_body = buffer;
+        frame_body.clear();
         let mut i = 1;
         while i < bytes.len() - 1 {
             if bytes[i] == 0x7D {
@@ -95,25 +268,147 @@ impl HdlcFrame {
             frame_body[frame_body.len() - 1],
         ];
         let received_checksum = u16::from_le_bytes(received_checksum_bytes);
-        let data_to_checksum = &frame_body[..frame_body.len() - 2];
-        let calculated_checksum = CRC_ALGORITHM.checksum(data_to_checksum);
+        frame_body.truncate(frame_body.len() - 2);
+        let calculated_checksum = CRC_ALGORITHM.checksum(&frame_body);
 
         if received_checksum != calculated_checksum {
             return Err(HdlcFrameError::InvalidFcs.into());
         }
 
-        let address = u16::from_be_bytes([data_to_checksum[0], data_to_checksum[1]]);
-        let control = data_to_checksum[2];
-        let information = data_to_checksum[3..].to_vec();
+        let (destination, header) =
+            HdlcAddress::parse(&frame_body).ok_or(HdlcFrameError::InvalidFrame)?;
+        if frame_body.len() < header + 3 {
+            return Err(HdlcFrameError::InvalidFrame.into());
+        }
+        let address = u16::from_be_bytes([frame_body[header], frame_body[header + 1]]);
+        let control = frame_body[header + 2];
+        frame_body.drain(..header + 3);
 
         Ok(HdlcFrame {
+            destination,
             address,
             control,
-            information,
+            information: frame_body,
         })
     }
 }
 
+fn push_escaped(frame: &mut Vec<u8>, bytes: &[u8]) {
+    for &byte in bytes {
+        if byte == HDLC_FLAG || byte == 0x7D {
+            frame.push(0x7D);
+            frame.push(byte ^ 0x20);
+        } else {
+            frame.push(byte);
+        }
+    }
+}
+
+// Control field of an I-frame: N(R) in bits 7-5, the poll/final bit in bit 4
+// and N(S) in bits 3-1. Supervisory RR frames carry only N(R).
+pub const HDLC_POLL_FINAL: u8 = 0x10;
+const HDLC_RR: u8 = 0x01;
+const HDLC_UI: u8 = 0x03;
+const HDLC_MAX_WINDOW: u8 = 7;
+
+pub fn is_information_frame(control: u8) -> bool {
+    control & 0x01 == 0
+}
+
+// Supervisory RR frame, with or without the poll/final bit.
+pub fn is_receive_ready(control: u8) -> bool {
+    control & 0x0F == HDLC_RR
+}
+
+// Unnumbered information frame, with or without the poll/final bit: data
+// outside the I-frame sequence, such as event notifications.
+pub fn is_unnumbered_information(control: u8) -> bool {
+    control & !HDLC_POLL_FINAL == HDLC_UI
+}
+
+// Control field of an RR frame with the poll/final bit: a poll checking the
+// link, or the answer to one.
+pub fn receive_ready(receive_sequence: u8) -> u8 {
+    (receive_sequence << 5) | HDLC_POLL_FINAL | HDLC_RR
+}
+
+pub fn send_sequence(control: u8) -> u8 {
+    (control >> 1) & 0x07
+}
+
+pub fn receive_sequence(control: u8) -> u8 {
+    control >> 5
+}
+
+// Receiving side of an HDLC connection with a window of up to seven I-frames.
+// The peer may send that many frames before it has to wait for an
+// acknowledgement, and it asks for one early by setting the poll bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HdlcWindow {
+    size: u8,
+    // V(R): N(S) expected in the next I-frame.
+    receive_sequence: u8,
+    // V(S): N(S) of the next I-frame sent.
+    send_sequence: u8,
+    // I-frames accepted since the last acknowledgement.
+    unacknowledged: u8,
+}
+
+impl HdlcWindow {
+    pub fn new(size: u8) -> Self {
+        Self {
+            size: size.clamp(1, HDLC_MAX_WINDOW),
+            receive_sequence: 0,
+            send_sequence: 0,
+            unacknowledged: 0,
+        }
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.size);
+    }
+
+    // Accepts an I-frame if it carries the expected N(S). Frames out of
+    // sequence are to be discarded; the peer resends them after the next
+    // acknowledgement.
+    pub fn receive(&mut self, control: u8) -> bool {
+        if !is_information_frame(control) || send_sequence(control) != self.receive_sequence {
+            return false;
+        }
+        self.receive_sequence = (self.receive_sequence + 1) % 8;
+        self.unacknowledged += 1;
+        true
+    }
+
+    // The peer waits for an answer once it has polled or filled the window.
+    pub fn must_acknowledge(&self, control: u8) -> bool {
+        control & HDLC_POLL_FINAL != 0 || self.unacknowledged >= self.size
+    }
+
+    // Control field for the next I-frame sent, acknowledging everything
+    // received so far.
+    pub fn next_information_control(&mut self, is_final: bool) -> u8 {
+        let control = (self.receive_sequence << 5) | (self.send_sequence << 1);
+        self.send_sequence = (self.send_sequence + 1) % 8;
+        self.unacknowledged = 0;
+        if is_final {
+            control | HDLC_POLL_FINAL
+        } else {
+            control
+        }
+    }
+
+    // Control field of an RR frame acknowledging everything received so far.
+    pub fn receive_ready_control(&mut self) -> u8 {
+        self.unacknowledged = 0;
+        receive_ready(self.receive_sequence)
+    }
+}
+
 #[cfg(all(test, feature = "std"))]
 mod tests {
     extern crate std;
@@ -123,6 +418,7 @@ mod tests {
     fn test_hdlc_frame_serialization_deserialization() {
         let info = b"hello world".to_vec();
         let frame = HdlcFrame {
+            destination: HdlcAddress::new(0x0001, 0x1234),
             address: 0x1234,
             control: 0xAB,
             information: info,
@@ -132,5 +428,98 @@ mod tests {
         let deserialized_frame = HdlcFrame::from_bytes(&bytes).unwrap();
 
         assert_eq!(frame, deserialized_frame);
+
+        // A reused buffer keeps nothing of the frame it held before.
+        let reused = HdlcFrame::from_bytes_in(&bytes, vec![0x55; 64]).unwrap();
+        assert_eq!(reused, frame);
+    }
+
+    #[test]
+    fn test_hdlc_address_forms() {
+        let address = HdlcAddress::new(0x0001, 0x0123);
+        assert!(address.addresses(0x0001, 0x0123));
+        assert!(!address.addresses(0x0001, 0x0124));
+        assert!(HdlcAddress::new(0x0001, HDLC_ALL_STATION).addresses(0x0001, 0x0124));
+        assert!(!HdlcAddress::new(HDLC_NO_STATION, 0x0123).addresses(0x0001, 0x0123));
+
+        let bytes = address.to_bytes().unwrap();
+        assert_eq!(bytes, vec![0x00, 0x02, 0x04, 0x47]);
+        assert_eq!(HdlcAddress::parse(&bytes), Some((address, 4)));
+        assert_eq!(
+            HdlcAddress::parse(&[0x02, 0x21]),
+            Some((
+                HdlcAddress::new(0x0001, 0x0010).with_size(HdlcAddressSize::Two),
+                2
+            ))
+        );
+        assert_eq!(
+            HdlcAddress::parse(&[0xFF]),
+            Some((HdlcAddress::ALL_STATION.with_size(HdlcAddressSize::One), 1))
+        );
+        assert_eq!(HdlcAddress::parse(&[0x02, 0x04, 0x06]), None);
+    }
+
+    #[test]
+    fn test_hdlc_address_sizes() {
+        let address = HdlcAddress::for_device(0x0001, 0x0011);
+        assert_eq!(address.size, HdlcAddressSize::Two);
+        assert_eq!(address.to_bytes().unwrap(), vec![0x02, 0x23]);
+        assert_eq!(
+            address.with_size(HdlcAddressSize::Four).to_bytes().unwrap(),
+            vec![0x00, 0x02, 0x00, 0x23]
+        );
+
+        let address = HdlcAddress::for_device(0x0001, HDLC_ALL_STATION);
+        assert_eq!(address.size, HdlcAddressSize::One);
+        assert_eq!(address.to_bytes().unwrap(), vec![0x03]);
+
+        let address = HdlcAddress::for_device(0x0001, 0x1000);
+        assert_eq!(address.size, HdlcAddressSize::Four);
+        assert_eq!(
+            address.with_size(HdlcAddressSize::Two).to_bytes(),
+            Err(HdlcFrameError::InvalidAddress)
+        );
+
+        for address in [
+            HdlcAddress::for_device(0x0001, 0x0011),
+            HdlcAddress::for_device(0x0010, HDLC_ALL_STATION),
+            HdlcAddress::new(0x0001, 0x0011),
+        ] {
+            let frame = HdlcFrame {
+                destination: address,
+                address: 0x0010,
+                control: 0x10,
+                information: vec![0xC0],
+            };
+            let decoded = HdlcFrame::from_bytes(&frame.to_bytes().unwrap()).unwrap();
+            assert_eq!(decoded, frame);
+        }
+    }
+
+    #[test]
+    fn test_hdlc_window_sequencing() {
+        let mut window = HdlcWindow::new(3);
+        // N(S) = 0 and 1 without poll, then N(S) = 2 with poll.
+        assert!(window.receive(0x00));
+        assert!(!window.must_acknowledge(0x00));
+        assert!(!window.receive(0x00));
+        assert!(window.receive(0x02));
+        assert!(window.receive(0x14));
+        assert!(window.must_acknowledge(0x14));
+
+        let first = window.next_information_control(false);
+        assert_eq!(receive_sequence(first), 3);
+        assert_eq!(send_sequence(first), 0);
+        let last = window.next_information_control(true);
+        assert_eq!(last, 0x72);
+        assert!(is_information_frame(last));
+
+        assert!(window.receive(0x36));
+        assert_eq!(window.receive_ready_control(), 0x91);
+        assert_eq!(HdlcWindow::new(9).size(), 7);
+        assert!(is_receive_ready(0x91));
+        assert!(is_receive_ready(0x01));
+        assert!(!is_receive_ready(0x72));
+        assert_eq!(receive_ready(0), 0x11);
     }
 }
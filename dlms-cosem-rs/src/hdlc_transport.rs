@@ -1,8 +1,12 @@
 #![cfg(feature = "std")]
 
+use crate::cosem_object::CosemObject;
 use crate::hdlc::HDLC_FLAG;
+use crate::iec_hdlc_setup::IecHdlcSetup;
 use crate::transport::Transport;
 use std::io::{Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
 use std::vec::Vec;
 
 #[derive(Debug)]
@@ -16,21 +20,165 @@ impl From<std::io::Error> for HdlcTransportError {
     }
 }
 
+// Time slice paced writes are counted over.
+const PACING_SLICE: Duration = Duration::from_millis(10);
+
+// Bits a byte takes on an asynchronous line: start, 8 data and stop bit.
+const BITS_PER_BYTE: u32 = 10;
+
+// Limits how fast bytes are written, for receivers with small buffers such as
+// optical heads and PLC modems: at most `bytes_per_slice` per `slice`, and a
+// gap after each frame of a batch so that the peer can drain its buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pacing {
+    pub bytes_per_slice: usize,
+    pub slice: Duration,
+    pub frame_gap: Duration,
+}
+
+impl Pacing {
+    pub fn new(bytes_per_slice: usize, slice: Duration) -> Self {
+        Self {
+            bytes_per_slice: bytes_per_slice.max(1),
+            slice,
+            frame_gap: Duration::ZERO,
+        }
+    }
+
+    // No faster than a line of `baud_rate` carries the bytes.
+    pub fn for_baud_rate(baud_rate: u32) -> Self {
+        let bytes_per_second = (baud_rate / BITS_PER_BYTE) as u128;
+        let bytes_per_slice = bytes_per_second * PACING_SLICE.as_millis() / 1000;
+        Self::new(bytes_per_slice as usize, PACING_SLICE)
+    }
+
+    pub fn with_frame_gap(mut self, frame_gap: Duration) -> Self {
+        self.frame_gap = frame_gap;
+        self
+    }
+}
+
+// On half-duplex media (RS-485) the side that just received a frame must not
+// transmit before the line has turned around, and a client must leave a gap
+// between its requests. Both delays are zero unless configured.
 pub struct HdlcTransport<T: Read + Write> {
     stream: T,
+    response_delay: Duration,
+    min_request_gap: Duration,
+    last_received: Option<Instant>,
+    last_sent: Option<Instant>,
+    pacing: Option<Pacing>,
 }
 
 impl<T: Read + Write> HdlcTransport<T> {
     pub fn new(stream: T) -> Self {
-        Self { stream }
+        Self {
+            stream,
+            response_delay: Duration::ZERO,
+            min_request_gap: Duration::ZERO,
+            last_received: None,
+            last_sent: None,
+            pacing: None,
+        }
+    }
+
+    // Minimum time between the end of a received frame and the next transmission.
+    pub fn set_response_delay(&mut self, delay: Duration) {
+        self.response_delay = delay;
+    }
+
+    // Minimum time between two transmissions.
+    pub fn set_min_request_gap(&mut self, gap: Duration) {
+        self.min_request_gap = gap;
+    }
+
+    // Writes at full speed when `None`, the default.
+    pub fn set_pacing(&mut self, pacing: Option<Pacing>) {
+        self.pacing = pacing;
+    }
+
+    // Takes the turnaround time from the inter_octet_time_out of an IEC HDLC
+    // setup object (class 23): the peer only detects the end of a frame after
+    // that much silence on the line.
+    pub fn apply_hdlc_setup(&mut self, setup: &dyn CosemObject) -> Option<()> {
+        if setup.class_id() != 23 {
+            return None;
+        }
+        let mut hdlc_setup = IecHdlcSetup::new();
+        hdlc_setup.set_attribute(7, setup.get_attribute(7)?)?;
+        self.response_delay = hdlc_setup.inter_octet_time_out()?;
+        Some(())
+    }
+
+    fn wait_for_turnaround(&self) {
+        let ready_at = [
+            self.last_received.map(|at| at + self.response_delay),
+            self.last_sent.map(|at| at + self.min_request_gap),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        if let Some(ready_at) = ready_at {
+            sleep_until(ready_at);
+        }
+    }
+
+    // Writes the frames of `bytes` one after the other, each in slices, and
+    // yields to other threads between frames.
+    fn write_paced(&mut self, bytes: &[u8], pacing: Pacing) -> std::io::Result<()> {
+        let mut frames = split_frames(bytes).peekable();
+        while let Some(frame) = frames.next() {
+            let started = Instant::now();
+            for (index, chunk) in frame.chunks(pacing.bytes_per_slice).enumerate() {
+                sleep_until(started + pacing.slice * index as u32);
+                self.stream.write_all(chunk)?;
+                self.stream.flush()?;
+            }
+            if frames.peek().is_some() {
+                if pacing.frame_gap.is_zero() {
+                    thread::yield_now();
+                } else {
+                    thread::sleep(pacing.frame_gap);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn sleep_until(at: Instant) {
+    if let Some(remaining) = at.checked_duration_since(Instant::now()) {
+        thread::sleep(remaining);
     }
 }
 
+// Frames sent back to back end and start with their own flags.
+fn split_frames(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut rest = bytes;
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = rest
+            .windows(2)
+            .position(|pair| pair == [HDLC_FLAG, HDLC_FLAG])
+            .map_or(rest.len(), |index| index + 1);
+        let (frame, tail) = rest.split_at(end);
+        rest = tail;
+        Some(frame)
+    })
+}
+
 impl<T: Read + Write> Transport for HdlcTransport<T> {
     type Error = HdlcTransportError;
 
     fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
-        self.stream.write_all(bytes)?;
+        self.wait_for_turnaround();
+        match self.pacing {
+            Some(pacing) => self.write_paced(bytes, pacing)?,
+            None => self.stream.write_all(bytes)?,
+        }
+        self.last_sent = Some(Instant::now());
         Ok(())
     }
 
@@ -47,6 +195,7 @@ impl<T: Read + Write> Transport for HdlcTransport<T> {
                 if in_frame {
                     if buffer.len() >= 2 {
                         buffer.push(HDLC_FLAG);
+                        self.last_received = Some(Instant::now());
                         return Ok(buffer);
                     } else {
                         buffer.clear();
@@ -62,3 +211,86 @@ impl<T: Read + Write> Transport for HdlcTransport<T> {
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::io::Cursor;
+
+    struct LoopbackStream {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for LoopbackStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for LoopbackStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_send_waits_for_turnaround() {
+        let frame = vec![HDLC_FLAG, 0xA0, 0x07, HDLC_FLAG];
+        let mut transport = HdlcTransport::new(LoopbackStream {
+            input: Cursor::new(frame.clone()),
+            output: Vec::new(),
+        });
+        let mut setup = IecHdlcSetup::new();
+        setup
+            .set_attribute(7, crate::types::CosemData::LongUnsigned(40))
+            .unwrap();
+        transport.apply_hdlc_setup(&setup).unwrap();
+        transport.set_min_request_gap(Duration::from_millis(30));
+
+        assert_eq!(transport.receive().unwrap(), frame);
+        transport.send(&frame).unwrap();
+        let received = transport.last_received.unwrap();
+        let first_sent = transport.last_sent.unwrap();
+        assert!(first_sent - received >= Duration::from_millis(40));
+
+        transport.send(&frame).unwrap();
+        assert!(transport.last_sent.unwrap() - first_sent >= Duration::from_millis(30));
+        assert_eq!(transport.stream.output.len(), 2 * frame.len());
+    }
+
+    #[test]
+    fn test_paced_send_spreads_frames_over_slices() {
+        let pacing = Pacing::for_baud_rate(9600);
+        assert_eq!(pacing.bytes_per_slice, 9);
+        assert_eq!(Pacing::for_baud_rate(300).bytes_per_slice, 1);
+
+        let mut transport = HdlcTransport::new(LoopbackStream {
+            input: Cursor::new(Vec::new()),
+            output: Vec::new(),
+        });
+        transport.set_pacing(Some(
+            Pacing::new(4, Duration::from_millis(5)).with_frame_gap(Duration::from_millis(10)),
+        ));
+        let first = [
+            HDLC_FLAG, 0xA0, 0x0A, 0x21, 0x03, 0x10, 0x11, 0x12, 0x13, HDLC_FLAG,
+        ];
+        let second = [HDLC_FLAG, 0xA0, 0x07, 0x21, 0x03, HDLC_FLAG];
+        let batch = [&first[..], &second[..]].concat();
+        assert_eq!(
+            split_frames(&batch).collect::<Vec<_>>(),
+            [&first[..], &second[..]]
+        );
+
+        let started = Instant::now();
+        transport.send(&batch).unwrap();
+        // Three slices of the first frame, the gap, then two slices.
+        assert!(started.elapsed() >= Duration::from_millis(10 + 10 + 5));
+        assert_eq!(transport.stream.output, batch);
+    }
+}
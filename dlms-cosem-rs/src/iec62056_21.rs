@@ -0,0 +1,513 @@
+#![cfg(feature = "std")]
+
+// IEC 62056-21 mode C: the ASCII readout protocol spoken by meters that do not
+// support DLMS/COSEM. It runs over the same serial line as HDLC, so the client
+// works on any byte stream; switching the line to the baud rate proposed by the
+// meter is left to a handler installed by the host.
+
+use std::boxed::Box;
+use std::io::{Read, Write};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const ETX: u8 = 0x03;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+
+#[derive(Debug)]
+pub enum Iec62056Error {
+    Io(std::io::Error),
+    // Malformed identification, data set or programming mode message
+    Parse,
+    // Block check character of a received block does not match
+    Bcc,
+    // The meter answered a command with NAK
+    Nak,
+    UnexpectedResponse,
+}
+
+impl From<std::io::Error> for Iec62056Error {
+    fn from(e: std::io::Error) -> Self {
+        Iec62056Error::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadoutMode {
+    DataReadout,
+    Programming,
+}
+
+// Identification message: /XXXZ<identification>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identification {
+    pub manufacturer: String,
+    pub baud_rate_id: u8,
+    pub identification: String,
+}
+
+impl Identification {
+    pub fn parse(line: &[u8]) -> Result<Self, Iec62056Error> {
+        let line = std::str::from_utf8(line).map_err(|_| Iec62056Error::Parse)?;
+        let line = line.trim_end_matches(['\r', '\n']);
+        let body = line.strip_prefix('/').ok_or(Iec62056Error::Parse)?;
+        if body.len() < 4 || !body.is_char_boundary(4) {
+            return Err(Iec62056Error::Parse);
+        }
+        Ok(Identification {
+            manufacturer: body[..3].to_string(),
+            baud_rate_id: body.as_bytes()[3],
+            identification: body[4..].to_string(),
+        })
+    }
+
+    // Mode C baud rate identifiers '0'..'6'.
+    pub fn baud_rate(&self) -> Option<u32> {
+        baud_rate(self.baud_rate_id)
+    }
+}
+
+pub fn baud_rate(baud_rate_id: u8) -> Option<u32> {
+    match baud_rate_id {
+        b'0' => Some(300),
+        b'1' => Some(600),
+        b'2' => Some(1200),
+        b'3' => Some(2400),
+        b'4' => Some(4800),
+        b'5' => Some(9600),
+        b'6' => Some(19200),
+        _ => None,
+    }
+}
+
+// One data set of a readout: address(value*unit)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSet {
+    pub address: String,
+    pub value: String,
+    pub unit: Option<String>,
+}
+
+impl DataSet {
+    // OBIS code of the address, either in full form (A-B:C.D.E*F) or in the
+    // reduced C.D.E form of electricity meters. Letter codes of value group C
+    // follow IEC 62056-61 (C = 96, F = 97, L = 98, P = 99).
+    pub fn obis_code(&self) -> Option<[u8; 6]> {
+        let (ab, cdef) = match self.address.split_once(':') {
+            Some((ab, cdef)) => (Some(ab), cdef),
+            None => (None, self.address.as_str()),
+        };
+        let (a, b) = match ab {
+            Some(ab) => {
+                let (a, b) = ab.split_once('-')?;
+                (a.parse().ok()?, b.parse().ok()?)
+            }
+            None => (1, 0),
+        };
+        let (cde, f) = match cdef.split_once(['*', '&']) {
+            Some((cde, f)) => (cde, f.parse().ok()?),
+            None => (cdef, 255),
+        };
+        let mut groups = cde.split('.');
+        let c = match groups.next()? {
+            "C" => 96,
+            "F" => 97,
+            "L" => 98,
+            "P" => 99,
+            c => c.parse().ok()?,
+        };
+        let d = groups.next()?.parse().ok()?;
+        let e = groups.next().map_or(Some(0), |e| e.parse().ok())?;
+        if groups.next().is_some() {
+            return None;
+        }
+        Some([a, b, c, d, e, f])
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.address.as_bytes());
+        out.push(b'(');
+        out.extend_from_slice(self.value.as_bytes());
+        if let Some(unit) = &self.unit {
+            out.push(b'*');
+            out.extend_from_slice(unit.as_bytes());
+        }
+        out.push(b')');
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataReadout {
+    pub identification: Identification,
+    pub data_sets: Vec<DataSet>,
+}
+
+// Parses the data block of a readout or of a programming mode reply. Data sets
+// without an address (e.g. further values of a profile line) take the address
+// of the preceding data set. Parsing stops at the end mark '!'.
+pub fn parse_data_block(block: &[u8]) -> Result<Vec<DataSet>, Iec62056Error> {
+    let text = std::str::from_utf8(block).map_err(|_| Iec62056Error::Parse)?;
+    let mut data_sets: Vec<DataSet> = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start_matches(['\r', '\n', ' ']);
+        if rest.is_empty() || rest.starts_with('!') {
+            break;
+        }
+        let open = rest.find('(').ok_or(Iec62056Error::Parse)?;
+        let close = rest[open..].find(')').ok_or(Iec62056Error::Parse)? + open;
+        let address = match rest[..open].trim() {
+            "" => data_sets
+                .last()
+                .map(|previous| previous.address.clone())
+                .ok_or(Iec62056Error::Parse)?,
+            address => address.to_string(),
+        };
+        let content = &rest[open + 1..close];
+        let (value, unit) = match content.split_once('*') {
+            Some((value, unit)) => (value.to_string(), Some(unit.to_string())),
+            None => (content.to_string(), None),
+        };
+        data_sets.push(DataSet {
+            address,
+            value,
+            unit,
+        });
+        rest = &rest[close + 1..];
+    }
+    Ok(data_sets)
+}
+
+// Block check character: XOR of every byte after the leading SOH/STX up to and
+// including the trailing ETX/EOT.
+pub fn bcc(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |bcc, byte| bcc ^ byte)
+}
+
+pub fn request_message(device_address: &str) -> Vec<u8> {
+    let mut message = b"/?".to_vec();
+    message.extend_from_slice(device_address.as_bytes());
+    message.extend_from_slice(b"!\r\n");
+    message
+}
+
+pub fn acknowledgement_message(baud_rate_id: u8, mode: ReadoutMode) -> Vec<u8> {
+    let mode = match mode {
+        ReadoutMode::DataReadout => b'0',
+        ReadoutMode::Programming => b'1',
+    };
+    vec![ACK, b'0', baud_rate_id, mode, b'\r', b'\n']
+}
+
+// Programming mode command: SOH C D STX data ETX BCC, or SOH C D ETX BCC when
+// the command carries no data (break).
+pub fn command_message(command: &[u8; 2], data: &[u8]) -> Vec<u8> {
+    let mut message = vec![SOH];
+    message.extend_from_slice(command);
+    if !data.is_empty() {
+        message.push(STX);
+        message.extend_from_slice(data);
+    }
+    message.push(ETX);
+    message.push(bcc(&message[1..]));
+    message
+}
+
+type BaudRateHandler = Box<dyn FnMut(u32) -> Result<(), std::io::Error> + Send>;
+
+pub struct Iec62056Client<T: Read + Write> {
+    stream: T,
+    baud_rate_handler: Option<BaudRateHandler>,
+}
+
+impl<T: Read + Write> Iec62056Client<T> {
+    pub fn new(stream: T) -> Self {
+        Self {
+            stream,
+            baud_rate_handler: None,
+        }
+    }
+
+    // Called with the new baud rate after the acknowledgement message was sent.
+    pub fn set_baud_rate_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(u32) -> Result<(), std::io::Error> + Send + 'static,
+    {
+        self.baud_rate_handler = Some(Box::new(handler));
+    }
+
+    pub fn request_identification(
+        &mut self,
+        device_address: &str,
+    ) -> Result<Identification, Iec62056Error> {
+        self.stream.write_all(&request_message(device_address))?;
+        let line = self.read_line()?;
+        Identification::parse(&line)
+    }
+
+    pub fn read_out(&mut self, device_address: &str) -> Result<DataReadout, Iec62056Error> {
+        let identification = self.request_identification(device_address)?;
+        self.acknowledge(&identification, ReadoutMode::DataReadout)?;
+        match self.read_block()? {
+            Block::Data { start: STX, data } => Ok(DataReadout {
+                identification,
+                data_sets: parse_data_block(&data)?,
+            }),
+            _ => Err(Iec62056Error::UnexpectedResponse),
+        }
+    }
+
+    // Switches the meter to programming mode and returns the operand of its P0
+    // message, typically the seed for the password command.
+    pub fn enter_programming_mode(
+        &mut self,
+        device_address: &str,
+    ) -> Result<(Identification, Vec<u8>), Iec62056Error> {
+        let identification = self.request_identification(device_address)?;
+        self.acknowledge(&identification, ReadoutMode::Programming)?;
+        match self.read_block()? {
+            Block::Data { start: SOH, data } if data.starts_with(b"P0\x02") => {
+                let operand = strip_parentheses(&data[3..])?;
+                Ok((identification, operand.to_vec()))
+            }
+            _ => Err(Iec62056Error::UnexpectedResponse),
+        }
+    }
+
+    pub fn send_password(&mut self, password: &str) -> Result<(), Iec62056Error> {
+        let mut data = vec![b'('];
+        data.extend_from_slice(password.as_bytes());
+        data.push(b')');
+        self.command(b"P1", &data)
+    }
+
+    pub fn read_value(&mut self, address: &str) -> Result<DataSet, Iec62056Error> {
+        let mut data = address.as_bytes().to_vec();
+        data.extend_from_slice(b"()");
+        self.stream.write_all(&command_message(b"R1", &data))?;
+        match self.read_block()? {
+            Block::Data { start: STX, data } => parse_data_block(&data)?
+                .into_iter()
+                .next()
+                .ok_or(Iec62056Error::Parse),
+            Block::Nak => Err(Iec62056Error::Nak),
+            _ => Err(Iec62056Error::UnexpectedResponse),
+        }
+    }
+
+    pub fn write_value(&mut self, data_set: &DataSet) -> Result<(), Iec62056Error> {
+        let mut data = Vec::new();
+        data_set.encode(&mut data);
+        self.command(b"W1", &data)
+    }
+
+    pub fn end_session(&mut self) -> Result<(), Iec62056Error> {
+        self.stream.write_all(&command_message(b"B0", &[]))?;
+        Ok(())
+    }
+
+    fn acknowledge(
+        &mut self,
+        identification: &Identification,
+        mode: ReadoutMode,
+    ) -> Result<(), Iec62056Error> {
+        self.stream
+            .write_all(&acknowledgement_message(identification.baud_rate_id, mode))?;
+        if let (Some(handler), Some(baud_rate)) =
+            (self.baud_rate_handler.as_mut(), identification.baud_rate())
+        {
+            handler(baud_rate)?;
+        }
+        Ok(())
+    }
+
+    fn command(&mut self, command: &[u8; 2], data: &[u8]) -> Result<(), Iec62056Error> {
+        self.stream.write_all(&command_message(command, data))?;
+        match self.read_block()? {
+            Block::Ack => Ok(()),
+            Block::Nak => Err(Iec62056Error::Nak),
+            _ => Err(Iec62056Error::UnexpectedResponse),
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<u8, Iec62056Error> {
+        let mut byte = [0u8; 1];
+        self.stream.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_line(&mut self) -> Result<Vec<u8>, Iec62056Error> {
+        let mut line = Vec::new();
+        loop {
+            let byte = self.read_byte()?;
+            line.push(byte);
+            if byte == b'\n' {
+                return Ok(line);
+            }
+        }
+    }
+
+    fn read_block(&mut self) -> Result<Block, Iec62056Error> {
+        let start = self.read_byte()?;
+        match start {
+            ACK => return Ok(Block::Ack),
+            NAK => return Ok(Block::Nak),
+            SOH | STX => {}
+            _ => return Err(Iec62056Error::UnexpectedResponse),
+        }
+        let mut data = Vec::new();
+        loop {
+            let byte = self.read_byte()?;
+            if byte == ETX || byte == EOT {
+                let mut checked = data.clone();
+                checked.push(byte);
+                if self.read_byte()? != bcc(&checked) {
+                    return Err(Iec62056Error::Bcc);
+                }
+                return Ok(Block::Data { start, data });
+            }
+            data.push(byte);
+        }
+    }
+}
+
+enum Block {
+    Ack,
+    Nak,
+    Data { start: u8, data: Vec<u8> },
+}
+
+fn strip_parentheses(data: &[u8]) -> Result<&[u8], Iec62056Error> {
+    data.strip_prefix(b"(")
+        .and_then(|data| data.strip_suffix(b")"))
+        .ok_or(Iec62056Error::Parse)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::io::Cursor;
+
+    struct ScriptedStream {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for ScriptedStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for ScriptedStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn stx_block(data: &[u8]) -> Vec<u8> {
+        let mut block = vec![STX];
+        block.extend_from_slice(data);
+        block.push(ETX);
+        block.push(bcc(&block[1..]));
+        block
+    }
+
+    #[test]
+    fn test_data_readout() {
+        let mut input = b"/LGZ5ZMD3104107.B32\r\n".to_vec();
+        input.extend(stx_block(
+            b"0.0.0(12345678)\r\n1.8.0(001234.567*kWh)\r\n1-0:2.8.1*255(000010.0*kWh)\r\nC.1.0(1)(2)\r\n!\r\n",
+        ));
+        let mut client = Iec62056Client::new(ScriptedStream {
+            input: Cursor::new(input),
+            output: Vec::new(),
+        });
+        let baud_rates = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&baud_rates);
+        client.set_baud_rate_handler(move |baud_rate| {
+            seen.lock().unwrap().push(baud_rate);
+            Ok(())
+        });
+
+        let readout = client.read_out("").unwrap();
+        assert_eq!(readout.identification.manufacturer, "LGZ");
+        assert_eq!(readout.identification.baud_rate(), Some(9600));
+        assert_eq!(*baud_rates.lock().unwrap(), vec![9600]);
+        assert_eq!(client.stream.output, b"/?!\r\n\x06050\r\n".to_vec());
+
+        let data_sets = readout.data_sets;
+        assert_eq!(data_sets.len(), 5);
+        assert_eq!(
+            data_sets[1],
+            DataSet {
+                address: "1.8.0".to_string(),
+                value: "001234.567".to_string(),
+                unit: Some("kWh".to_string()),
+            }
+        );
+        assert_eq!(data_sets[1].obis_code(), Some([1, 0, 1, 8, 0, 255]));
+        assert_eq!(data_sets[2].obis_code(), Some([1, 0, 2, 8, 1, 255]));
+        assert_eq!(data_sets[3].obis_code(), Some([1, 0, 96, 1, 0, 255]));
+        assert_eq!(data_sets[4].address, "C.1.0");
+        assert_eq!(data_sets[4].value, "2");
+    }
+
+    #[test]
+    fn test_readout_rejects_bad_bcc() {
+        let mut input = b"/ABC5meter\r\n".to_vec();
+        let mut block = stx_block(b"1.8.0(1)\r\n!\r\n");
+        *block.last_mut().unwrap() ^= 0xFF;
+        input.extend(block);
+        let mut client = Iec62056Client::new(ScriptedStream {
+            input: Cursor::new(input),
+            output: Vec::new(),
+        });
+        assert!(matches!(client.read_out(""), Err(Iec62056Error::Bcc)));
+    }
+
+    #[test]
+    fn test_programming_mode_password_and_read() {
+        let mut input = b"/ABC5meter\r\n".to_vec();
+        let mut p0 = vec![SOH];
+        p0.extend_from_slice(b"P0\x02(1234567)\x03");
+        p0.push(bcc(&p0[1..]));
+        input.extend(p0);
+        input.push(ACK);
+        input.extend(stx_block(b"1.8.0(000042.000*kWh)"));
+        input.push(NAK);
+
+        let mut client = Iec62056Client::new(ScriptedStream {
+            input: Cursor::new(input),
+            output: Vec::new(),
+        });
+
+        let (_, seed) = client.enter_programming_mode("12345").unwrap();
+        assert_eq!(seed, b"1234567".to_vec());
+        client.send_password("00000000").unwrap();
+        let value = client.read_value("1.8.0").unwrap();
+        assert_eq!(value.value, "000042.000");
+        let result = client.write_value(&DataSet {
+            address: "0.9.1".to_string(),
+            value: "12:00:00".to_string(),
+            unit: None,
+        });
+        assert!(matches!(result, Err(Iec62056Error::Nak)));
+
+        let output = &client.stream.output;
+        assert!(output.starts_with(b"/?12345!\r\n\x06051\r\n"));
+        let password = command_message(b"P1", b"(00000000)");
+        assert!(output
+            .windows(password.len())
+            .any(|window| window == password.as_slice()));
+    }
+}
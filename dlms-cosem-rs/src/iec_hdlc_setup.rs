@@ -0,0 +1,182 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
+use crate::types::CosemData;
+use core::time::Duration;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// comm_speed enum of the IEC HDLC setup: 0 = 300 baud .. 9 = 115200 baud.
+const COMM_SPEED_9600: u8 = 5;
+
+// IEC HDLC setup (class 23, version 1) of a serial port. The transport reads
+// the timing and addressing attributes from it, see
+// `HdlcTransport::apply_hdlc_setup`.
+#[derive(Debug)]
+pub struct IecHdlcSetup {
+    comm_speed: CosemData,
+    window_size_transmit: CosemData,
+    window_size_receive: CosemData,
+    max_info_field_length_transmit: CosemData,
+    max_info_field_length_receive: CosemData,
+    inter_octet_time_out: CosemData,
+    inactivity_time_out: CosemData,
+    device_address: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl IecHdlcSetup {
+    pub fn new() -> Self {
+        Self {
+            comm_speed: CosemData::Enum(COMM_SPEED_9600),
+            window_size_transmit: CosemData::Unsigned(1),
+            window_size_receive: CosemData::Unsigned(1),
+            max_info_field_length_transmit: CosemData::LongUnsigned(128),
+            max_info_field_length_receive: CosemData::LongUnsigned(128),
+            inter_octet_time_out: CosemData::LongUnsigned(25),
+            inactivity_time_out: CosemData::LongUnsigned(120),
+            device_address: CosemData::LongUnsigned(0x0010),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn baud_rate(&self) -> Option<u32> {
+        match self.comm_speed {
+            CosemData::Enum(speed) if speed <= 9 => Some(match speed {
+                0 => 300,
+                1 => 600,
+                2 => 1200,
+                3 => 2400,
+                4 => 4800,
+                5 => 9600,
+                6 => 19200,
+                7 => 38400,
+                8 => 57600,
+                _ => 115200,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn inter_octet_time_out(&self) -> Option<Duration> {
+        match self.inter_octet_time_out {
+            CosemData::LongUnsigned(millis) => Some(Duration::from_millis(millis as u64)),
+            _ => None,
+        }
+    }
+
+    pub fn device_address(&self) -> Option<u16> {
+        match self.device_address {
+            CosemData::LongUnsigned(address) => Some(address),
+            _ => None,
+        }
+    }
+}
+
+impl Default for IecHdlcSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for IecHdlcSetup {
+    fn class_id(&self) -> u16 {
+        23
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=9)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::ReadWrite)
+            })
+            .collect()
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.comm_speed.clone()),
+            3 => Some(self.window_size_transmit.clone()),
+            4 => Some(self.window_size_receive.clone()),
+            5 => Some(self.max_info_field_length_transmit.clone()),
+            6 => Some(self.max_info_field_length_receive.clone()),
+            7 => Some(self.inter_octet_time_out.clone()),
+            8 => Some(self.inactivity_time_out.clone()),
+            9 => Some(self.device_address.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match (attribute_id, &data) {
+            (2, CosemData::Enum(speed)) if *speed <= 9 => self.comm_speed = data,
+            (3, CosemData::Unsigned(1..=7)) => self.window_size_transmit = data,
+            (4, CosemData::Unsigned(1..=7)) => self.window_size_receive = data,
+            (5, CosemData::LongUnsigned(32..=2030)) => self.max_info_field_length_transmit = data,
+            (6, CosemData::LongUnsigned(32..=2030)) => self.max_info_field_length_receive = data,
+            (7, CosemData::LongUnsigned(20..=6000)) => self.inter_octet_time_out = data,
+            (8, CosemData::LongUnsigned(_)) => self.inactivity_time_out = data,
+            (9, CosemData::LongUnsigned(_)) => self.device_address = data,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        None
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_iec_hdlc_setup_attributes() {
+        let mut setup = IecHdlcSetup::new();
+        assert_eq!(setup.baud_rate(), Some(9600));
+        assert_eq!(
+            setup.inter_octet_time_out(),
+            Some(Duration::from_millis(25))
+        );
+
+        setup.set_attribute(2, CosemData::Enum(9)).unwrap();
+        setup
+            .set_attribute(7, CosemData::LongUnsigned(200))
+            .unwrap();
+        setup
+            .set_attribute(9, CosemData::LongUnsigned(0x0123))
+            .unwrap();
+        assert_eq!(setup.baud_rate(), Some(115200));
+        assert_eq!(
+            setup.inter_octet_time_out(),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(setup.device_address(), Some(0x0123));
+
+        assert!(setup.set_attribute(2, CosemData::Enum(10)).is_none());
+        assert!(setup.set_attribute(3, CosemData::Unsigned(8)).is_none());
+        assert!(setup.set_attribute(7, CosemData::LongUnsigned(5)).is_none());
+    }
+}
@@ -0,0 +1,234 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::types::CosemData;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// IPv4 addresses are carried as double-long-unsigned in network byte order.
+pub fn ipv4_to_cosem_data(address: Ipv4Addr) -> CosemData {
+    CosemData::DoubleLongUnsigned(u32::from(address))
+}
+
+pub fn ipv4_from_cosem_data(data: &CosemData) -> Option<Ipv4Addr> {
+    match data {
+        CosemData::DoubleLongUnsigned(address) => Some(Ipv4Addr::from(*address)),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub struct Ipv4Setup {
+    dl_reference: CosemData,
+    ip_address: CosemData,
+    multicast_ip_address: Vec<CosemData>,
+    ip_options: CosemData,
+    subnet_mask: CosemData,
+    gateway_ip_address: CosemData,
+    use_dhcp_flag: CosemData,
+    primary_dns_address: CosemData,
+    secondary_dns_address: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl Ipv4Setup {
+    pub fn new() -> Self {
+        Self {
+            dl_reference: CosemData::OctetString(vec![0, 0, 25, 2, 0, 255]),
+            ip_address: ipv4_to_cosem_data(Ipv4Addr::UNSPECIFIED),
+            multicast_ip_address: Vec::new(),
+            ip_options: CosemData::Array(Vec::new()),
+            subnet_mask: ipv4_to_cosem_data(Ipv4Addr::UNSPECIFIED),
+            gateway_ip_address: ipv4_to_cosem_data(Ipv4Addr::UNSPECIFIED),
+            use_dhcp_flag: CosemData::Boolean(false),
+            primary_dns_address: ipv4_to_cosem_data(Ipv4Addr::UNSPECIFIED),
+            secondary_dns_address: ipv4_to_cosem_data(Ipv4Addr::UNSPECIFIED),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn ip_address(&self) -> Option<Ipv4Addr> {
+        ipv4_from_cosem_data(&self.ip_address)
+    }
+
+    pub fn dns_servers(&self) -> Vec<Ipv4Addr> {
+        [&self.primary_dns_address, &self.secondary_dns_address]
+            .into_iter()
+            .filter_map(ipv4_from_cosem_data)
+            .filter(|address| !address.is_unspecified())
+            .collect()
+    }
+}
+
+impl Default for Ipv4Setup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for Ipv4Setup {
+    fn class_id(&self) -> u16 {
+        42
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=10)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::ReadWrite)
+            })
+            .collect()
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![
+            MethodAccessDescriptor::new(1, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(2, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(3, MethodAccessMode::Access),
+        ]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.dl_reference.clone()),
+            3 => Some(self.ip_address.clone()),
+            4 => Some(CosemData::Array(self.multicast_ip_address.clone())),
+            5 => Some(self.ip_options.clone()),
+            6 => Some(self.subnet_mask.clone()),
+            7 => Some(self.gateway_ip_address.clone()),
+            8 => Some(self.use_dhcp_flag.clone()),
+            9 => Some(self.primary_dns_address.clone()),
+            10 => Some(self.secondary_dns_address.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.dl_reference = data;
+                Some(())
+            }
+            3 => {
+                ipv4_from_cosem_data(&data)?;
+                self.ip_address = data;
+                Some(())
+            }
+            4 => {
+                let CosemData::Array(addresses) = data else {
+                    return None;
+                };
+                if !addresses
+                    .iter()
+                    .all(|address| ipv4_from_cosem_data(address).is_some())
+                {
+                    return None;
+                }
+                self.multicast_ip_address = addresses;
+                Some(())
+            }
+            5 => {
+                self.ip_options = data;
+                Some(())
+            }
+            6 => {
+                ipv4_from_cosem_data(&data)?;
+                self.subnet_mask = data;
+                Some(())
+            }
+            7 => {
+                ipv4_from_cosem_data(&data)?;
+                self.gateway_ip_address = data;
+                Some(())
+            }
+            8 => {
+                self.use_dhcp_flag = data;
+                Some(())
+            }
+            9 => {
+                ipv4_from_cosem_data(&data)?;
+                self.primary_dns_address = data;
+                Some(())
+            }
+            10 => {
+                ipv4_from_cosem_data(&data)?;
+                self.secondary_dns_address = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
+    ) -> Option<CosemData> {
+        match method_id {
+            1 => {
+                ipv4_from_cosem_data(&data)?;
+                if !self.multicast_ip_address.contains(&data) {
+                    self.multicast_ip_address.push(data);
+                }
+                Some(CosemData::NullData)
+            }
+            2 => {
+                let index = self
+                    .multicast_ip_address
+                    .iter()
+                    .position(|address| *address == data)?;
+                self.multicast_ip_address.remove(index);
+                Some(CosemData::NullData)
+            }
+            3 => Some(CosemData::LongUnsigned(
+                self.multicast_ip_address.len() as u16
+            )),
+            _ => None,
+        }
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_ipv4_setup_addresses() {
+        let mut setup = Ipv4Setup::new();
+        setup
+            .set_attribute(3, ipv4_to_cosem_data(Ipv4Addr::new(192, 168, 0, 10)))
+            .unwrap();
+        setup
+            .set_attribute(9, ipv4_to_cosem_data(Ipv4Addr::new(8, 8, 8, 8)))
+            .unwrap();
+        assert!(setup.set_attribute(3, CosemData::Unsigned(1)).is_none());
+        assert_eq!(setup.ip_address(), Some(Ipv4Addr::new(192, 168, 0, 10)));
+        assert_eq!(setup.dns_servers(), vec![Ipv4Addr::new(8, 8, 8, 8)]);
+
+        let multicast = ipv4_to_cosem_data(Ipv4Addr::new(224, 0, 0, 1));
+        setup.invoke_method(1, multicast.clone()).unwrap();
+        setup.invoke_method(1, multicast.clone()).unwrap();
+        assert_eq!(
+            setup.invoke_method(3, CosemData::Integer(0)),
+            Some(CosemData::LongUnsigned(1))
+        );
+        setup.invoke_method(2, multicast.clone()).unwrap();
+        assert!(setup.invoke_method(2, multicast).is_none());
+        assert_eq!(setup.get_attribute(4), Some(CosemData::Array(Vec::new())));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::types::CosemData;
+use std::net::Ipv6Addr;
+use std::sync::Arc;
+use std::vec::Vec;
+
+pub fn ipv6_to_cosem_data(address: Ipv6Addr) -> CosemData {
+    CosemData::OctetString(address.octets().to_vec())
+}
+
+pub fn ipv6_from_cosem_data(data: &CosemData) -> Option<Ipv6Addr> {
+    match data {
+        CosemData::OctetString(octets) => {
+            let octets: [u8; 16] = octets.as_slice().try_into().ok()?;
+            Some(Ipv6Addr::from(octets))
+        }
+        _ => None,
+    }
+}
+
+// address_type of the add_IPv6_address / remove_IPv6_address methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ipv6AddressType {
+    Unicast = 0,
+    Multicast = 1,
+    Gateway = 2,
+}
+
+#[derive(Debug)]
+pub struct Ipv6Setup {
+    dl_reference: CosemData,
+    address_config_mode: CosemData,
+    unicast_addresses: Vec<CosemData>,
+    multicast_addresses: Vec<CosemData>,
+    gateway_addresses: Vec<CosemData>,
+    primary_dns_address: CosemData,
+    secondary_dns_address: CosemData,
+    traffic_class: CosemData,
+    neighbor_discovery_setup: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl Ipv6Setup {
+    pub fn new() -> Self {
+        Self {
+            dl_reference: CosemData::OctetString(vec![0, 0, 25, 2, 0, 255]),
+            address_config_mode: CosemData::Enum(0),
+            unicast_addresses: Vec::new(),
+            multicast_addresses: Vec::new(),
+            gateway_addresses: Vec::new(),
+            primary_dns_address: CosemData::OctetString(Vec::new()),
+            secondary_dns_address: CosemData::OctetString(Vec::new()),
+            traffic_class: CosemData::Unsigned(0),
+            neighbor_discovery_setup: CosemData::Array(Vec::new()),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn unicast_addresses(&self) -> Vec<Ipv6Addr> {
+        self.unicast_addresses
+            .iter()
+            .filter_map(ipv6_from_cosem_data)
+            .collect()
+    }
+
+    pub fn dns_servers(&self) -> Vec<Ipv6Addr> {
+        [&self.primary_dns_address, &self.secondary_dns_address]
+            .into_iter()
+            .filter_map(ipv6_from_cosem_data)
+            .collect()
+    }
+
+    fn addresses_mut(&mut self, address_type: u8) -> Option<&mut Vec<CosemData>> {
+        match address_type {
+            0 => Some(&mut self.unicast_addresses),
+            1 => Some(&mut self.multicast_addresses),
+            2 => Some(&mut self.gateway_addresses),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Ipv6Setup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The address lists hold 16 byte addresses; an empty octet-string marks an
+// unset DNS address.
+fn address_list(data: CosemData) -> Option<Vec<CosemData>> {
+    let CosemData::Array(addresses) = data else {
+        return None;
+    };
+    addresses
+        .iter()
+        .all(|address| ipv6_from_cosem_data(address).is_some())
+        .then_some(addresses)
+}
+
+fn optional_address(data: &CosemData) -> Option<()> {
+    match data {
+        CosemData::OctetString(octets) if octets.is_empty() => Some(()),
+        _ => ipv6_from_cosem_data(data).map(|_| ()),
+    }
+}
+
+impl CosemObject for Ipv6Setup {
+    fn class_id(&self) -> u16 {
+        48
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=10)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::ReadWrite)
+            })
+            .collect()
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![
+            MethodAccessDescriptor::new(1, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(2, MethodAccessMode::Access),
+        ]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.dl_reference.clone()),
+            3 => Some(self.address_config_mode.clone()),
+            4 => Some(CosemData::Array(self.unicast_addresses.clone())),
+            5 => Some(CosemData::Array(self.multicast_addresses.clone())),
+            6 => Some(CosemData::Array(self.gateway_addresses.clone())),
+            7 => Some(self.primary_dns_address.clone()),
+            8 => Some(self.secondary_dns_address.clone()),
+            9 => Some(self.traffic_class.clone()),
+            10 => Some(self.neighbor_discovery_setup.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.dl_reference = data;
+                Some(())
+            }
+            3 => {
+                self.address_config_mode = data;
+                Some(())
+            }
+            4 => {
+                self.unicast_addresses = address_list(data)?;
+                Some(())
+            }
+            5 => {
+                self.multicast_addresses = address_list(data)?;
+                Some(())
+            }
+            6 => {
+                self.gateway_addresses = address_list(data)?;
+                Some(())
+            }
+            7 => {
+                optional_address(&data)?;
+                self.primary_dns_address = data;
+                Some(())
+            }
+            8 => {
+                optional_address(&data)?;
+                self.secondary_dns_address = data;
+                Some(())
+            }
+            9 => {
+                self.traffic_class = data;
+                Some(())
+            }
+            10 => {
+                self.neighbor_discovery_setup = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    // add_IPv6_address / remove_IPv6_address (data ::= structure { address_type, address })
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
+    ) -> Option<CosemData> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        let [CosemData::Enum(address_type), address] = fields.as_slice() else {
+            return None;
+        };
+        ipv6_from_cosem_data(address)?;
+        let addresses = self.addresses_mut(*address_type)?;
+        match method_id {
+            1 => {
+                if !addresses.contains(address) {
+                    addresses.push(address.clone());
+                }
+                Some(CosemData::NullData)
+            }
+            2 => {
+                let index = addresses.iter().position(|existing| existing == address)?;
+                addresses.remove(index);
+                Some(CosemData::NullData)
+            }
+            _ => None,
+        }
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_ipv6_setup_add_and_remove_address() {
+        let mut setup = Ipv6Setup::new();
+        let address = "2001:db8::10".parse::<Ipv6Addr>().unwrap();
+        let parameters = |address_type: Ipv6AddressType| {
+            CosemData::Structure(vec![
+                CosemData::Enum(address_type as u8),
+                ipv6_to_cosem_data(address),
+            ])
+        };
+
+        setup
+            .invoke_method(1, parameters(Ipv6AddressType::Unicast))
+            .unwrap();
+        assert_eq!(setup.unicast_addresses(), vec![address]);
+        assert!(setup
+            .invoke_method(2, parameters(Ipv6AddressType::Gateway))
+            .is_none());
+        setup
+            .invoke_method(2, parameters(Ipv6AddressType::Unicast))
+            .unwrap();
+        assert!(setup.unicast_addresses().is_empty());
+        assert!(setup
+            .set_attribute(
+                4,
+                CosemData::Array(vec![CosemData::OctetString(vec![1; 4])])
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "serde")]
+
+use crate::error::DlmsError;
+use crate::object_model::{parse_bits, parse_hex, write_bits, write_hex};
+use crate::types::CosemData;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Number, Value};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+// JSON form of data for fixtures and tools: an object with one member named
+// after the type as in the DLMS UA XML schema, e.g. {"long-unsigned": 5} or
+// {"array": [...]}. Octet strings and date-times are hex, bit strings binary
+// digits, so that every value reads back as it was.
+impl CosemData {
+    pub fn to_json(&self) -> Value {
+        let (name, value) = match self {
+            CosemData::NullData => ("null-data", Value::Null),
+            CosemData::DontCare => ("dont-care", Value::Null),
+            CosemData::Array(elements) => ("array", elements_to_json(elements)),
+            CosemData::Structure(elements) => ("structure", elements_to_json(elements)),
+            CosemData::Boolean(value) => ("boolean", Value::Bool(*value)),
+            CosemData::BitString(bits) => {
+                let mut text = String::new();
+                write_bits(bits, &mut text);
+                ("bit-string", Value::String(text))
+            }
+            CosemData::DoubleLong(value) => ("double-long", Value::from(*value)),
+            CosemData::DoubleLongUnsigned(value) => ("double-long-unsigned", Value::from(*value)),
+            CosemData::OctetString(bytes) => ("octet-string", hex(bytes)),
+            CosemData::VisibleString(text) => ("visible-string", Value::from(text.as_str())),
+            CosemData::Utf8String(text) => ("utf8-string", Value::from(text.as_str())),
+            CosemData::Bcd(value) => ("bcd", Value::from(*value)),
+            CosemData::Integer(value) => ("integer", Value::from(*value)),
+            CosemData::Long(value) => ("long", Value::from(*value)),
+            CosemData::Unsigned(value) => ("unsigned", Value::from(*value)),
+            CosemData::LongUnsigned(value) => ("long-unsigned", Value::from(*value)),
+            CosemData::Long64(value) => ("long64", Value::from(*value)),
+            CosemData::Long64Unsigned(value) => ("long64-unsigned", Value::from(*value)),
+            CosemData::Enum(value) => ("enum", Value::from(*value)),
+            CosemData::Float32(value) => ("float32", Value::from(*value)),
+            CosemData::Float64(value) => ("float64", Value::from(*value)),
+            CosemData::DateTime(bytes) => ("date-time", hex(bytes)),
+            CosemData::Date(bytes) => ("date", hex(bytes)),
+            CosemData::Time(bytes) => ("time", hex(bytes)),
+        };
+        let mut object = Map::new();
+        object.insert(name.to_string(), value);
+        Value::Object(object)
+    }
+
+    pub fn from_json(json: &Value) -> Result<CosemData, DlmsError> {
+        let Some((name, value)) = json
+            .as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.iter().next())
+        else {
+            return Err(DlmsError::ParseError);
+        };
+        let data = match name.as_str() {
+            "null-data" => CosemData::NullData,
+            "dont-care" => CosemData::DontCare,
+            "array" => CosemData::Array(elements_from_json(value)?),
+            "structure" => CosemData::Structure(elements_from_json(value)?),
+            "boolean" => CosemData::Boolean(value.as_bool().ok_or(DlmsError::ParseError)?),
+            "bit-string" => CosemData::BitString(parse_bits(string(value)?)?),
+            "double-long" => CosemData::DoubleLong(integer(value)?),
+            "double-long-unsigned" => CosemData::DoubleLongUnsigned(integer(value)?),
+            "octet-string" => CosemData::OctetString(parse_hex(string(value)?)?),
+            "visible-string" => CosemData::VisibleString(string(value)?.to_string()),
+            "utf8-string" => CosemData::Utf8String(string(value)?.to_string()),
+            "bcd" => CosemData::Bcd(integer(value)?),
+            "integer" => CosemData::Integer(integer(value)?),
+            "long" => CosemData::Long(integer(value)?),
+            "unsigned" => CosemData::Unsigned(integer(value)?),
+            "long-unsigned" => CosemData::LongUnsigned(integer(value)?),
+            "long64" => CosemData::Long64(integer(value)?),
+            "long64-unsigned" => CosemData::Long64Unsigned(integer(value)?),
+            "enum" => CosemData::Enum(integer(value)?),
+            "float32" => CosemData::Float32(float(value)? as f32),
+            "float64" => CosemData::Float64(float(value)?),
+            "date-time" => CosemData::DateTime(parse_hex(string(value)?)?),
+            "date" => CosemData::Date(parse_hex(string(value)?)?),
+            "time" => CosemData::Time(parse_hex(string(value)?)?),
+            _ => return Err(DlmsError::ParseError),
+        };
+        Ok(data)
+    }
+}
+
+impl Serialize for CosemData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CosemData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        CosemData::from_json(&json).map_err(|_| D::Error::custom("invalid COSEM data"))
+    }
+}
+
+fn elements_to_json(elements: &[CosemData]) -> Value {
+    Value::Array(elements.iter().map(CosemData::to_json).collect())
+}
+
+fn elements_from_json(value: &Value) -> Result<Vec<CosemData>, DlmsError> {
+    value
+        .as_array()
+        .ok_or(DlmsError::ParseError)?
+        .iter()
+        .map(CosemData::from_json)
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> Value {
+    let mut text = String::new();
+    write_hex(bytes, &mut text);
+    Value::String(text)
+}
+
+fn string(value: &Value) -> Result<&str, DlmsError> {
+    value.as_str().ok_or(DlmsError::ParseError)
+}
+
+// Integers out of the range of the type are refused rather than truncated.
+fn integer<V: TryFrom<i64> + TryFrom<u64>>(value: &Value) -> Result<V, DlmsError> {
+    let converted = match value.as_number().ok_or(DlmsError::ParseError)? {
+        number if number.is_u64() => number.as_u64().and_then(|value| V::try_from(value).ok()),
+        number => number.as_i64().and_then(|value| V::try_from(value).ok()),
+    };
+    converted.ok_or(DlmsError::ParseError)
+}
+
+fn float(value: &Value) -> Result<f64, DlmsError> {
+    value
+        .as_number()
+        .and_then(Number::as_f64)
+        .ok_or(DlmsError::ParseError)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::types::BitString;
+    use std::vec;
+
+    #[test]
+    fn test_json_round_trip_keeps_types() {
+        let data = CosemData::Structure(vec![
+            CosemData::OctetString(vec![1, 0, 1, 8, 0, 255]),
+            CosemData::Array(vec![
+                CosemData::LongUnsigned(5),
+                CosemData::Long64Unsigned(u64::MAX),
+            ]),
+            CosemData::BitString(BitString::new(3, vec![0b1010_0000]).unwrap()),
+            CosemData::Integer(-3),
+            CosemData::Enum(30),
+            CosemData::Float64(1.5),
+            CosemData::VisibleString("meter".to_string()),
+            CosemData::NullData,
+        ]);
+        let json = data.to_json();
+        assert_eq!(
+            json,
+            serde_json::json!({"structure": [
+                {"octet-string": "0100010800FF"},
+                {"array": [{"long-unsigned": 5}, {"long64-unsigned": u64::MAX}]},
+                {"bit-string": "101"},
+                {"integer": -3},
+                {"enum": 30},
+                {"float64": 1.5},
+                {"visible-string": "meter"},
+                {"null-data": null},
+            ]})
+        );
+        assert_eq!(CosemData::from_json(&json).unwrap(), data);
+        let text = serde_json::to_string(&data).unwrap();
+        assert_eq!(serde_json::from_str::<CosemData>(&text).unwrap(), data);
+
+        assert!(CosemData::from_json(&serde_json::json!({"unsigned": 256})).is_err());
+        assert!(CosemData::from_json(&serde_json::json!({"unsigned": 1, "long": 1})).is_err());
+        assert!(CosemData::from_json(&serde_json::json!({"octet-string": "0"})).is_err());
+    }
+}
@@ -1,27 +1,70 @@
+// Lets the code generated by `cosem_object` refer to this crate by name from
+// within it.
+extern crate self as dlms_cosem;
+
 pub mod acse;
 pub mod activity_calendar;
 pub mod association_ln;
 pub mod axdr;
+pub mod billing;
+pub mod buffer_storage;
 pub mod client;
 pub mod clock;
+pub mod compact_buffer;
+pub mod compression;
 pub mod cosem;
 pub mod cosem_object;
 pub mod data;
+pub mod data_stream;
 pub mod demand_register;
+mod diagnostics;
 pub mod disconnect_control;
+pub mod embedded_serial;
 pub mod error;
 pub mod extended_register;
+pub mod gprs_modem_setup;
+pub mod gsm_diagnostic;
 pub mod hdlc;
 pub mod hdlc_transport;
+pub mod iec62056_21;
+pub mod iec_hdlc_setup;
+pub mod ipv4_setup;
+pub mod ipv6_setup;
+pub mod json;
+pub mod limiter;
+pub mod logical_device;
+pub mod mbus_client;
+pub mod mbus_diagnostic;
+pub mod mbus_master_port_setup;
+pub mod multiplexed_transport;
+pub mod object_model;
+pub mod object_set;
+pub mod poll_scheduler;
 pub mod profile_generic;
+pub mod push_setup;
 pub mod register;
+pub mod register_activation;
+pub mod register_monitor;
 pub mod sap_assignment;
+pub mod scaled_value;
+pub mod script_table;
 pub mod security;
+pub mod security_log;
 pub mod security_setup;
 pub mod server;
+pub mod server_listener;
+pub mod short_name;
+pub mod simulator;
+pub mod system_title;
+pub mod tariff;
+pub mod testing;
+pub mod trace;
 pub mod transport;
 pub mod types;
+pub mod udp_transport;
 pub mod wrapper_transport;
 pub mod xdlms;
 
+pub use dlms_cosem_derive::cosem_object;
+
 pub const MAX_PDU_SIZE: usize = 2048;
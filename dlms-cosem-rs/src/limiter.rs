@@ -0,0 +1,240 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
+use crate::register_monitor::{numeric_value, MonitoredValue, ScriptReference};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// Limiter (class 71). The over threshold action runs once the monitored value
+// stayed above the active threshold for min_over_threshold_duration seconds, the
+// under threshold action once it stayed at or below it for
+// min_under_threshold_duration seconds.
+#[derive(Debug)]
+pub struct Limiter {
+    monitored_value: CosemData,
+    threshold_active: CosemData,
+    threshold_normal: CosemData,
+    threshold_emergency: CosemData,
+    min_over_threshold_duration: CosemData,
+    min_under_threshold_duration: CosemData,
+    emergency_profile: CosemData,
+    emergency_profile_group_id_list: CosemData,
+    emergency_profile_active: CosemData,
+    actions: CosemData,
+    over_since: Option<u64>,
+    under_since: Option<u64>,
+    over_threshold: bool,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl Limiter {
+    pub fn new() -> Self {
+        Self {
+            monitored_value: CosemData::NullData,
+            threshold_active: CosemData::NullData,
+            threshold_normal: CosemData::NullData,
+            threshold_emergency: CosemData::NullData,
+            min_over_threshold_duration: CosemData::DoubleLongUnsigned(0),
+            min_under_threshold_duration: CosemData::DoubleLongUnsigned(0),
+            emergency_profile: CosemData::NullData,
+            emergency_profile_group_id_list: CosemData::Array(Vec::new()),
+            emergency_profile_active: CosemData::Boolean(false),
+            actions: CosemData::NullData,
+            over_since: None,
+            under_since: None,
+            over_threshold: false,
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn is_over_threshold(&self) -> bool {
+        self.over_threshold
+    }
+
+    // The emergency threshold replaces the normal one while an emergency
+    // profile is active.
+    fn effective_threshold(&self) -> &CosemData {
+        if self.emergency_profile_active == CosemData::Boolean(true) {
+            &self.threshold_emergency
+        } else if self.threshold_active != CosemData::NullData {
+            &self.threshold_active
+        } else {
+            &self.threshold_normal
+        }
+    }
+
+    // action_set ::= structure { action_over_threshold, action_under_threshold }
+    fn action(&self, over: bool) -> Option<ScriptReference> {
+        let CosemData::Structure(actions) = &self.actions else {
+            return None;
+        };
+        match actions.as_slice() {
+            [action_over, action_under] => {
+                ScriptReference::from_cosem_data(if over { action_over } else { action_under })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for Limiter {
+    fn class_id(&self) -> u16 {
+        71
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=11)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::ReadWrite)
+            })
+            .collect()
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.monitored_value.clone()),
+            3 => Some(self.threshold_active.clone()),
+            4 => Some(self.threshold_normal.clone()),
+            5 => Some(self.threshold_emergency.clone()),
+            6 => Some(self.min_over_threshold_duration.clone()),
+            7 => Some(self.min_under_threshold_duration.clone()),
+            8 => Some(self.emergency_profile.clone()),
+            9 => Some(self.emergency_profile_group_id_list.clone()),
+            10 => Some(self.emergency_profile_active.clone()),
+            11 => Some(self.actions.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        let attribute = match attribute_id {
+            2 => &mut self.monitored_value,
+            3 => &mut self.threshold_active,
+            4 => &mut self.threshold_normal,
+            5 => &mut self.threshold_emergency,
+            6 => &mut self.min_over_threshold_duration,
+            7 => &mut self.min_under_threshold_duration,
+            8 => &mut self.emergency_profile,
+            9 => &mut self.emergency_profile_group_id_list,
+            10 => &mut self.emergency_profile_active,
+            11 => &mut self.actions,
+            _ => return None,
+        };
+        *attribute = data;
+        Some(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        None
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+
+    fn monitored_value(&self) -> Option<MonitoredValue> {
+        MonitoredValue::from_cosem_data(&self.monitored_value)
+    }
+
+    fn evaluate_monitored_value(
+        &mut self,
+        _previous: &CosemData,
+        current: &CosemData,
+        timestamp: u64,
+    ) -> Vec<ScriptReference> {
+        let (Some(value), Some(threshold)) = (
+            numeric_value(current),
+            numeric_value(self.effective_threshold()),
+        ) else {
+            return Vec::new();
+        };
+
+        let over = value > threshold;
+        let (since, min_duration) = if over {
+            self.under_since = None;
+            (
+                *self.over_since.get_or_insert(timestamp),
+                &self.min_over_threshold_duration,
+            )
+        } else {
+            self.over_since = None;
+            (
+                *self.under_since.get_or_insert(timestamp),
+                &self.min_under_threshold_duration,
+            )
+        };
+        let min_duration = min_duration.as_u64().unwrap_or(0);
+
+        if over == self.over_threshold || timestamp.saturating_sub(since) < min_duration {
+            return Vec::new();
+        }
+        self.over_threshold = over;
+        self.action(over).into_iter().collect()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_limiter_waits_for_min_durations() {
+        let script = |script_selector| ScriptReference {
+            script_logical_name: [0, 0, 10, 0, 106, 255],
+            script_selector,
+        };
+        let mut limiter = Limiter::new();
+        limiter
+            .set_attribute(4, CosemData::LongUnsigned(1000))
+            .unwrap();
+        limiter
+            .set_attribute(6, CosemData::DoubleLongUnsigned(10))
+            .unwrap();
+        limiter
+            .set_attribute(7, CosemData::DoubleLongUnsigned(5))
+            .unwrap();
+        limiter
+            .set_attribute(
+                11,
+                CosemData::Structure(vec![script(1).to_cosem_data(), script(2).to_cosem_data()]),
+            )
+            .unwrap();
+
+        let mut evaluate = |value: u16, timestamp: u64| {
+            limiter.evaluate_monitored_value(
+                &CosemData::NullData,
+                &CosemData::LongUnsigned(value),
+                timestamp,
+            )
+        };
+
+        assert!(evaluate(1200, 0).is_empty());
+        assert!(evaluate(1200, 9).is_empty());
+        assert_eq!(evaluate(1200, 10), vec![script(1)]);
+        assert!(evaluate(1300, 20).is_empty());
+        assert!(evaluate(800, 21).is_empty());
+        assert_eq!(evaluate(800, 26), vec![script(2)]);
+        assert!(evaluate(800, 40).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::cosem::CosemObjectInstanceId;
+use crate::cosem_object::AttributeAccessMode;
+use crate::data::Data;
+use crate::system_title::{starts_with_flag_id, SystemTitle, SystemTitleError, FLAG_ID_LENGTH};
+use crate::types::CosemData;
+use std::string::String;
+use std::vec::Vec;
+
+// COSEM logical device name, a data object (class 1) every logical device
+// must hold.
+pub const LOGICAL_DEVICE_NAME_LN: CosemObjectInstanceId = [0, 0, 42, 0, 0, 255];
+
+const LOGICAL_DEVICE_NAME_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalDeviceNameError {
+    // The name is not 16 octets long.
+    InvalidLength(usize),
+    // The name does not open with a FLAG manufacturer ID.
+    InvalidFlagId,
+    // The manufacturer specific part is not printable ASCII.
+    InvalidCharacter,
+}
+
+// 16 octets: the three letter FLAG ID of the manufacturer followed by 13
+// manufacturer specific octets, typically the serial number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogicalDeviceName([u8; LOGICAL_DEVICE_NAME_LENGTH]);
+
+impl LogicalDeviceName {
+    // The serial number is left padded with '0' to fill the 13 octets.
+    pub fn new(flag_id: &str, serial: &str) -> Result<Self, LogicalDeviceNameError> {
+        let width = LOGICAL_DEVICE_NAME_LENGTH - FLAG_ID_LENGTH;
+        if serial.len() > width {
+            return Err(LogicalDeviceNameError::InvalidLength(
+                flag_id.len() + serial.len(),
+            ));
+        }
+        let mut name = Vec::with_capacity(LOGICAL_DEVICE_NAME_LENGTH);
+        name.extend_from_slice(flag_id.as_bytes());
+        name.resize(flag_id.len() + width - serial.len(), b'0');
+        name.extend_from_slice(serial.as_bytes());
+        Self::from_bytes(&name)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LogicalDeviceNameError> {
+        let name: [u8; LOGICAL_DEVICE_NAME_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| LogicalDeviceNameError::InvalidLength(bytes.len()))?;
+        if !starts_with_flag_id(&name) {
+            return Err(LogicalDeviceNameError::InvalidFlagId);
+        }
+        if !name[FLAG_ID_LENGTH..].iter().all(u8::is_ascii_graphic) {
+            return Err(LogicalDeviceNameError::InvalidCharacter);
+        }
+        Ok(Self(name))
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Result<Self, LogicalDeviceNameError> {
+        match data {
+            CosemData::OctetString(bytes) => Self::from_bytes(bytes),
+            CosemData::VisibleString(name) => Self::from_bytes(name.as_bytes()),
+            _ => Err(LogicalDeviceNameError::InvalidLength(0)),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn flag_id(&self) -> String {
+        String::from_utf8_lossy(&self.0[..FLAG_ID_LENGTH]).into_owned()
+    }
+
+    // The manufacturer specific part, as given to `new` including the padding.
+    pub fn serial(&self) -> String {
+        String::from_utf8_lossy(&self.0[FLAG_ID_LENGTH..]).into_owned()
+    }
+
+    // The system title of the same manufacturer and serial number, for a
+    // serial number in decimal digits.
+    pub fn system_title(&self) -> Result<SystemTitle, SystemTitleError> {
+        let serial = self
+            .serial()
+            .parse()
+            .map_err(|_| SystemTitleError::InvalidSerial)?;
+        SystemTitle::new(&self.flag_id(), serial)
+    }
+
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::OctetString(self.0.to_vec())
+    }
+
+    // The read-only data object to register at `LOGICAL_DEVICE_NAME_LN`.
+    pub fn to_object(&self) -> Data {
+        Data::with_access(self.to_cosem_data(), AttributeAccessMode::Read)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_logical_device_name_from_flag_id_and_serial() {
+        let name = LogicalDeviceName::new("ABC", "12345678").unwrap();
+        assert_eq!(name.as_bytes(), b"ABC0000012345678");
+        assert_eq!(name.flag_id(), "ABC");
+        assert_eq!(name.serial(), "0000012345678");
+        assert_eq!(
+            LogicalDeviceName::from_cosem_data(&name.to_cosem_data()),
+            Ok(name)
+        );
+
+        assert_eq!(name.system_title(), SystemTitle::new("ABC", 12_345_678));
+        assert_eq!(
+            LogicalDeviceName::new("ABC", "9999999999999")
+                .unwrap()
+                .system_title(),
+            Err(SystemTitleError::SerialOutOfRange(9_999_999_999_999))
+        );
+        assert_eq!(
+            LogicalDeviceName::new("ABC", "SN-1")
+                .unwrap()
+                .system_title(),
+            Err(SystemTitleError::InvalidSerial)
+        );
+
+        assert_eq!(
+            LogicalDeviceName::new("ABC", "12345678901234"),
+            Err(LogicalDeviceNameError::InvalidLength(17))
+        );
+        assert_eq!(
+            LogicalDeviceName::new("Abc", "1"),
+            Err(LogicalDeviceNameError::InvalidFlagId)
+        );
+        assert_eq!(
+            LogicalDeviceName::from_bytes(b"ABC"),
+            Err(LogicalDeviceNameError::InvalidLength(3))
+        );
+        assert_eq!(
+            LogicalDeviceName::from_bytes(b"ABC 000012345678"),
+            Err(LogicalDeviceNameError::InvalidCharacter)
+        );
+    }
+}
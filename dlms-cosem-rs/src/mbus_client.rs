@@ -0,0 +1,341 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// encryption_key_status values of the M-Bus client (class 72, version 1).
+const NO_ENCRYPTION_KEY: u8 = 0;
+const ENCRYPTION_KEY_SET: u8 = 1;
+const ENCRYPTION_KEY_TRANSFERRED: u8 = 2;
+const ENCRYPTION_KEY_SET_AND_TRANSFERRED: u8 = 3;
+
+// Models an M-Bus slave (gas, water, heat meter) attached to the meter. The
+// object does not drive the bus itself: the host performs the exchange with the
+// slave in the post action callback of capture, synchronize_clock and data_send
+// and stores the results with `update_slave_identification`.
+#[derive(Debug)]
+pub struct MBusClient {
+    mbus_port_reference: CosemData,
+    capture_definition: CosemData,
+    capture_period: CosemData,
+    primary_address: CosemData,
+    identification_number: CosemData,
+    manufacturer_id: CosemData,
+    version: CosemData,
+    device_type: CosemData,
+    access_number: CosemData,
+    status: CosemData,
+    alarm: CosemData,
+    configuration: CosemData,
+    encryption_key_status: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl MBusClient {
+    pub fn new() -> Self {
+        Self {
+            mbus_port_reference: CosemData::OctetString(vec![0, 0, 24, 6, 0, 255]),
+            capture_definition: CosemData::Array(Vec::new()),
+            capture_period: CosemData::DoubleLongUnsigned(0),
+            primary_address: CosemData::Unsigned(0),
+            identification_number: CosemData::DoubleLongUnsigned(0),
+            manufacturer_id: CosemData::LongUnsigned(0),
+            version: CosemData::Unsigned(0),
+            device_type: CosemData::Unsigned(0),
+            access_number: CosemData::Unsigned(0),
+            status: CosemData::Unsigned(0),
+            alarm: CosemData::Unsigned(0),
+            configuration: CosemData::LongUnsigned(0),
+            encryption_key_status: CosemData::Enum(NO_ENCRYPTION_KEY),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.primary_address != CosemData::Unsigned(0)
+    }
+
+    // Values read from the slave header during installation or capture.
+    pub fn update_slave_identification(
+        &mut self,
+        identification_number: u32,
+        manufacturer_id: u16,
+        version: u8,
+        device_type: u8,
+    ) {
+        self.identification_number = CosemData::DoubleLongUnsigned(identification_number);
+        self.manufacturer_id = CosemData::LongUnsigned(manufacturer_id);
+        self.version = CosemData::Unsigned(version);
+        self.device_type = CosemData::Unsigned(device_type);
+    }
+}
+
+impl Default for MBusClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for MBusClient {
+    fn class_id(&self) -> u16 {
+        72
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        vec![
+            AttributeAccessDescriptor::new(2, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(3, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(4, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(5, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(6, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(7, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(8, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(9, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(10, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(11, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(12, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(13, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(14, AttributeAccessMode::Read),
+        ]
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        (1..=8)
+            .map(|method_id| MethodAccessDescriptor::new(method_id, MethodAccessMode::Access))
+            .collect()
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.mbus_port_reference.clone()),
+            3 => Some(self.capture_definition.clone()),
+            4 => Some(self.capture_period.clone()),
+            5 => Some(self.primary_address.clone()),
+            6 => Some(self.identification_number.clone()),
+            7 => Some(self.manufacturer_id.clone()),
+            8 => Some(self.version.clone()),
+            9 => Some(self.device_type.clone()),
+            10 => Some(self.access_number.clone()),
+            11 => Some(self.status.clone()),
+            12 => Some(self.alarm.clone()),
+            13 => Some(self.configuration.clone()),
+            14 => Some(self.encryption_key_status.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.mbus_port_reference = data;
+                Some(())
+            }
+            3 => {
+                self.capture_definition = data;
+                Some(())
+            }
+            4 => {
+                self.capture_period = data;
+                Some(())
+            }
+            5 => {
+                self.primary_address = data;
+                Some(())
+            }
+            6 => {
+                self.identification_number = data;
+                Some(())
+            }
+            7 => {
+                self.manufacturer_id = data;
+                Some(())
+            }
+            8 => {
+                self.version = data;
+                Some(())
+            }
+            9 => {
+                self.device_type = data;
+                Some(())
+            }
+            10 => {
+                self.access_number = data;
+                Some(())
+            }
+            11 => {
+                self.status = data;
+                Some(())
+            }
+            12 => {
+                self.alarm = data;
+                Some(())
+            }
+            13 => {
+                self.configuration = data;
+                Some(())
+            }
+            14 => {
+                self.encryption_key_status = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
+    ) -> Option<CosemData> {
+        match method_id {
+            1 => self.slave_install(data),
+            2 => self.slave_deinstall(),
+            3 | 5 => Some(CosemData::NullData),
+            4 => {
+                self.alarm = CosemData::Unsigned(0);
+                Some(CosemData::NullData)
+            }
+            6 => self.data_send(data),
+            7 => self.set_encryption_key(data),
+            8 => self.transfer_key(data),
+            _ => None,
+        }
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+impl MBusClient {
+    // slave_install (data ::= unsigned): primary address 0 keeps the address
+    // already configured in attribute 5.
+    fn slave_install(&mut self, data: CosemData) -> Option<CosemData> {
+        let CosemData::Unsigned(primary_address) = data else {
+            return None;
+        };
+        if primary_address != 0 {
+            self.primary_address = CosemData::Unsigned(primary_address);
+        }
+        if !self.is_installed() {
+            return None;
+        }
+        Some(CosemData::NullData)
+    }
+
+    fn slave_deinstall(&mut self) -> Option<CosemData> {
+        self.primary_address = CosemData::Unsigned(0);
+        self.update_slave_identification(0, 0, 0, 0);
+        self.encryption_key_status = CosemData::Enum(NO_ENCRYPTION_KEY);
+        Some(CosemData::NullData)
+    }
+
+    // data_send (data ::= array data_definition_element { dib, vib, data })
+    fn data_send(&mut self, data: CosemData) -> Option<CosemData> {
+        if !self.is_installed() {
+            return None;
+        }
+        let CosemData::Array(elements) = data else {
+            return None;
+        };
+        let well_formed = elements.iter().all(|element| {
+            matches!(
+                element,
+                CosemData::Structure(fields)
+                    if matches!(
+                        fields.as_slice(),
+                        [CosemData::OctetString(_), CosemData::OctetString(_), _]
+                    )
+            )
+        });
+        well_formed.then_some(CosemData::NullData)
+    }
+
+    fn set_encryption_key(&mut self, data: CosemData) -> Option<CosemData> {
+        let CosemData::OctetString(key) = data else {
+            return None;
+        };
+        let status = match (key.is_empty(), &self.encryption_key_status) {
+            (true, _) => NO_ENCRYPTION_KEY,
+            (false, CosemData::Enum(ENCRYPTION_KEY_TRANSFERRED)) => {
+                ENCRYPTION_KEY_SET_AND_TRANSFERRED
+            }
+            (false, _) => ENCRYPTION_KEY_SET,
+        };
+        self.encryption_key_status = CosemData::Enum(status);
+        Some(CosemData::NullData)
+    }
+
+    fn transfer_key(&mut self, data: CosemData) -> Option<CosemData> {
+        let CosemData::OctetString(_) = data else {
+            return None;
+        };
+        if !self.is_installed() {
+            return None;
+        }
+        let status = match self.encryption_key_status {
+            CosemData::Enum(ENCRYPTION_KEY_SET) => ENCRYPTION_KEY_SET_AND_TRANSFERRED,
+            _ => ENCRYPTION_KEY_TRANSFERRED,
+        };
+        self.encryption_key_status = CosemData::Enum(status);
+        Some(CosemData::NullData)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_mbus_client_install_and_deinstall() {
+        let mut client = MBusClient::new();
+        assert!(client.invoke_method(1, CosemData::Unsigned(0)).is_none());
+        assert!(client
+            .invoke_method(6, CosemData::Array(Vec::new()))
+            .is_none());
+
+        client.invoke_method(1, CosemData::Unsigned(5)).unwrap();
+        client.update_slave_identification(12345678, 0x2C2D, 1, 3);
+        assert_eq!(client.get_attribute(5), Some(CosemData::Unsigned(5)));
+        assert_eq!(
+            client.get_attribute(6),
+            Some(CosemData::DoubleLongUnsigned(12345678))
+        );
+
+        client
+            .invoke_method(7, CosemData::OctetString(vec![0x11; 16]))
+            .unwrap();
+        client
+            .invoke_method(8, CosemData::OctetString(vec![0x22; 16]))
+            .unwrap();
+        assert_eq!(
+            client.get_attribute(14),
+            Some(CosemData::Enum(ENCRYPTION_KEY_SET_AND_TRANSFERRED))
+        );
+
+        client.invoke_method(2, CosemData::Integer(0)).unwrap();
+        assert!(!client.is_installed());
+        assert_eq!(client.get_attribute(7), Some(CosemData::LongUnsigned(0)));
+        assert_eq!(
+            client.get_attribute(14),
+            Some(CosemData::Enum(NO_ENCRYPTION_KEY))
+        );
+    }
+}
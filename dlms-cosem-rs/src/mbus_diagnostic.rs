@@ -0,0 +1,196 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+#[derive(Debug)]
+pub struct MBusDiagnostic {
+    received_signal_strength: CosemData,
+    channel_id: CosemData,
+    link_status: CosemData,
+    broadcast_frames_counter: CosemData,
+    transmissions_counter: CosemData,
+    fcs_ok_frames_counter: CosemData,
+    fcs_nok_frames_counter: CosemData,
+    capture_time: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl MBusDiagnostic {
+    pub fn new() -> Self {
+        Self {
+            received_signal_strength: CosemData::Unsigned(0),
+            channel_id: CosemData::Unsigned(0),
+            link_status: CosemData::Enum(0),
+            broadcast_frames_counter: CosemData::Array(Vec::new()),
+            transmissions_counter: CosemData::DoubleLongUnsigned(0),
+            fcs_ok_frames_counter: CosemData::DoubleLongUnsigned(0),
+            fcs_nok_frames_counter: CosemData::DoubleLongUnsigned(0),
+            capture_time: CosemData::NullData,
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    // Counts a frame received from the slave, with a good or a bad frame check
+    // sequence.
+    pub fn record_frame(&mut self, fcs_ok: bool) -> Option<()> {
+        let counter = if fcs_ok {
+            &mut self.fcs_ok_frames_counter
+        } else {
+            &mut self.fcs_nok_frames_counter
+        };
+        *counter = counter.checked_add(1)?;
+        Some(())
+    }
+
+    pub fn record_transmission(&mut self) -> Option<()> {
+        self.transmissions_counter = self.transmissions_counter.checked_add(1)?;
+        Some(())
+    }
+}
+
+impl Default for MBusDiagnostic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for MBusDiagnostic {
+    fn class_id(&self) -> u16 {
+        77
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=9)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::Read)
+            })
+            .collect()
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.received_signal_strength.clone()),
+            3 => Some(self.channel_id.clone()),
+            4 => Some(self.link_status.clone()),
+            5 => Some(self.broadcast_frames_counter.clone()),
+            6 => Some(self.transmissions_counter.clone()),
+            7 => Some(self.fcs_ok_frames_counter.clone()),
+            8 => Some(self.fcs_nok_frames_counter.clone()),
+            9 => Some(self.capture_time.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.received_signal_strength = data;
+                Some(())
+            }
+            3 => {
+                self.channel_id = data;
+                Some(())
+            }
+            4 => {
+                self.link_status = data;
+                Some(())
+            }
+            5 => {
+                self.broadcast_frames_counter = data;
+                Some(())
+            }
+            6 => {
+                self.transmissions_counter = data;
+                Some(())
+            }
+            7 => {
+                self.fcs_ok_frames_counter = data;
+                Some(())
+            }
+            8 => {
+                self.fcs_nok_frames_counter = data;
+                Some(())
+            }
+            9 => {
+                self.capture_time = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        match method_id {
+            1 => self.reset(),
+            _ => None,
+        }
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+impl MBusDiagnostic {
+    fn reset(&mut self) -> Option<CosemData> {
+        self.broadcast_frames_counter = CosemData::Array(Vec::new());
+        self.transmissions_counter = CosemData::DoubleLongUnsigned(0);
+        self.fcs_ok_frames_counter = CosemData::DoubleLongUnsigned(0);
+        self.fcs_nok_frames_counter = CosemData::DoubleLongUnsigned(0);
+        Some(CosemData::NullData)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_mbus_diagnostic_counters_and_reset() {
+        let mut diagnostic = MBusDiagnostic::new();
+        diagnostic.record_frame(true).unwrap();
+        diagnostic.record_frame(true).unwrap();
+        diagnostic.record_frame(false).unwrap();
+        diagnostic.record_transmission().unwrap();
+        assert_eq!(
+            diagnostic.get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(2))
+        );
+        assert_eq!(
+            diagnostic.get_attribute(8),
+            Some(CosemData::DoubleLongUnsigned(1))
+        );
+
+        diagnostic.invoke_method(1, CosemData::Integer(0)).unwrap();
+        assert_eq!(
+            diagnostic.get_attribute(6),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        assert_eq!(
+            diagnostic.get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+    }
+}
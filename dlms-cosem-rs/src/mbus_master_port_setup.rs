@@ -0,0 +1,114 @@
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+
+// comm_speed enum of the M-Bus master port setup: 0 = 300 baud .. 9 = 115200 baud.
+const COMM_SPEED_2400: u8 = 3;
+
+#[derive(Debug)]
+pub struct MBusMasterPortSetup {
+    comm_speed: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl MBusMasterPortSetup {
+    pub fn new() -> Self {
+        Self {
+            comm_speed: CosemData::Enum(COMM_SPEED_2400),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn baud_rate(&self) -> Option<u32> {
+        match self.comm_speed {
+            CosemData::Enum(0) => Some(300),
+            CosemData::Enum(1) => Some(600),
+            CosemData::Enum(2) => Some(1200),
+            CosemData::Enum(3) => Some(2400),
+            CosemData::Enum(4) => Some(4800),
+            CosemData::Enum(5) => Some(9600),
+            CosemData::Enum(6) => Some(19200),
+            CosemData::Enum(7) => Some(38400),
+            CosemData::Enum(8) => Some(57600),
+            CosemData::Enum(9) => Some(115200),
+            _ => None,
+        }
+    }
+}
+
+impl Default for MBusMasterPortSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for MBusMasterPortSetup {
+    fn class_id(&self) -> u16 {
+        74
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        vec![AttributeAccessDescriptor::new(
+            2,
+            AttributeAccessMode::ReadWrite,
+        )]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.comm_speed.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => match data {
+                CosemData::Enum(speed) if speed <= 9 => {
+                    self.comm_speed = data;
+                    Some(())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        None
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_mbus_master_port_setup_comm_speed() {
+        let mut setup = MBusMasterPortSetup::new();
+        assert_eq!(setup.baud_rate(), Some(2400));
+        setup.set_attribute(2, CosemData::Enum(5)).unwrap();
+        assert_eq!(setup.baud_rate(), Some(9600));
+        assert!(setup.set_attribute(2, CosemData::Enum(10)).is_none());
+    }
+}
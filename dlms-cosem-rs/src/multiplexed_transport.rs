@@ -0,0 +1,185 @@
+use crate::client::Client;
+use crate::hdlc::{HdlcAddress, HdlcFrame};
+use crate::transport::Transport;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::vec::Vec;
+
+struct Shared<T: Transport> {
+    transport: T,
+    // Frames received for a session other than the one receiving, by the
+    // logical address of the server that sent them.
+    inboxes: BTreeMap<u16, VecDeque<Vec<u8>>>,
+}
+
+// One link, e.g. a TCP connection to a data concentrator, carrying the
+// associations of several clients to different servers at the same time.
+// Each association is a `Client` of its own over a `SessionTransport`, which
+// keeps its state apart from the others. Responses are told apart by the
+// address of the server that sent them, so the frames must not be ciphered
+// as a whole: a frame that does not parse goes to the session receiving it.
+pub struct MultiplexedTransport<T: Transport> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T: Transport> MultiplexedTransport<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                transport,
+                inboxes: BTreeMap::new(),
+            })),
+        }
+    }
+
+    // The transport of the session with the server at logical address
+    // `server_sap`. Sessions may be used from different threads.
+    pub fn session(&self, server_sap: u16) -> SessionTransport<T> {
+        SessionTransport {
+            shared: Arc::clone(&self.shared),
+            server_sap,
+        }
+    }
+
+    // A client of its own for the association between `client_address` and
+    // the server at `server_address`.
+    pub fn client(
+        &self,
+        client_address: u16,
+        server_address: HdlcAddress,
+        password: Option<Vec<u8>>,
+    ) -> Client<SessionTransport<T>> {
+        let mut client = Client::new(
+            client_address,
+            self.session(server_address.upper),
+            password,
+            None,
+        );
+        client.set_server_address(server_address);
+        client
+    }
+
+    // Runs `f` on the underlying transport, e.g. to reconnect it.
+    pub fn with_transport<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut shared.transport)
+    }
+}
+
+impl<T: Transport> Clone for MultiplexedTransport<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+pub struct SessionTransport<T: Transport> {
+    shared: Arc<Mutex<Shared<T>>>,
+    server_sap: u16,
+}
+
+impl<T: Transport> SessionTransport<T> {
+    pub fn server_sap(&self) -> u16 {
+        self.server_sap
+    }
+}
+
+impl<T: Transport> Transport for SessionTransport<T> {
+    type Error = T::Error;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        shared.transport.send(bytes)
+    }
+
+    // Receives until a frame of this session's server arrives, setting
+    // aside those of the other servers for their sessions.
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(frame) = shared
+                .inboxes
+                .get_mut(&self.server_sap)
+                .and_then(VecDeque::pop_front)
+            {
+                return Ok(frame);
+            }
+            let frame = shared.transport.receive()?;
+            match HdlcFrame::from_bytes(&frame) {
+                Ok(parsed) if parsed.address != self.server_sap => {
+                    shared
+                        .inboxes
+                        .entry(parsed.address)
+                        .or_default()
+                        .push_back(frame);
+                }
+                _ => return Ok(frame),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    // Answers with frames queued in advance, recording what is sent.
+    struct ScriptedTransport {
+        responses: VecDeque<Vec<u8>>,
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl Transport for ScriptedTransport {
+        type Error = ();
+
+        fn send(&mut self, bytes: &[u8]) -> Result<(), ()> {
+            self.sent.push(bytes.to_vec());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, ()> {
+            self.responses.pop_front().ok_or(())
+        }
+    }
+
+    fn frame_from(server_sap: u16, information: u8) -> Vec<u8> {
+        HdlcFrame {
+            address: server_sap,
+            information: vec![information],
+            ..Default::default()
+        }
+        .to_bytes()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_frames_are_routed_to_the_session_of_their_server() {
+        let multiplexer = MultiplexedTransport::new(ScriptedTransport {
+            responses: VecDeque::from([frame_from(2, 0xB2), frame_from(1, 0xA1)]),
+            sent: Vec::new(),
+        });
+        let mut first = multiplexer.session(1);
+        let mut second = multiplexer.session(2);
+        first.send(&[1]).unwrap();
+        second.send(&[2]).unwrap();
+
+        // The response to the second session arrives first and waits.
+        assert_eq!(first.receive(), Ok(frame_from(1, 0xA1)));
+        assert_eq!(second.receive(), Ok(frame_from(2, 0xB2)));
+        assert_eq!(second.receive(), Err(()));
+        multiplexer.with_transport(|transport| {
+            assert_eq!(transport.sent, vec![vec![1], vec![2]]);
+        });
+    }
+
+    #[test]
+    fn test_unparsed_frames_go_to_the_receiving_session() {
+        let multiplexer = MultiplexedTransport::new(ScriptedTransport {
+            responses: VecDeque::from([vec![0x7E, 0x00, 0x7E]]),
+            sent: Vec::new(),
+        });
+        assert_eq!(multiplexer.session(2).receive(), Ok(vec![0x7E, 0x00, 0x7E]));
+    }
+}
@@ -0,0 +1,728 @@
+use crate::billing::{BillingPeriod, MAX_PREVIOUS_BILLING_PERIODS};
+use crate::cosem::CosemObjectAttributeId;
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::error::DlmsError;
+use crate::types::{BitString, CosemData};
+use std::fmt::Write;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+// Interchange description of a meter object model. Values are rendered with the
+// element names of the DLMS UA XML schema (Green Book, COSEMpdu.xsd "Data" CHOICE)
+// so the documents can be compared with those produced by other DLMS tools.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectDescription {
+    pub class_id: u16,
+    pub version: u8,
+    pub logical_name: [u8; 6],
+    pub attribute_access: Vec<AttributeAccessDescriptor>,
+    pub method_access: Vec<MethodAccessDescriptor>,
+    pub attribute_values: Vec<(CosemObjectAttributeId, CosemData)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ObjectModel {
+    pub objects: Vec<ObjectDescription>,
+}
+
+impl ObjectModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn find(&self, logical_name: &[u8; 6]) -> Option<&ObjectDescription> {
+        self.objects
+            .iter()
+            .find(|object| &object.logical_name == logical_name)
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<Objects>\n");
+        for object in &self.objects {
+            let _ = writeln!(
+                out,
+                "  <Object ClassId=\"{}\" Version=\"{}\" LogicalName=\"{}\">",
+                object.class_id,
+                object.version,
+                format_logical_name(&object.logical_name)
+            );
+
+            out.push_str("    <AccessRights>\n");
+            for descriptor in &object.attribute_access {
+                match &descriptor.selective_access_descriptor {
+                    Some(selective_access) => {
+                        let _ = writeln!(
+                            out,
+                            "      <Attribute Id=\"{}\" Access=\"{}\">",
+                            descriptor.attribute_id,
+                            attribute_access_name(descriptor.access_mode)
+                        );
+                        write_data(selective_access, 8, &mut out);
+                        out.push_str("      </Attribute>\n");
+                    }
+                    None => {
+                        let _ = writeln!(
+                            out,
+                            "      <Attribute Id=\"{}\" Access=\"{}\"/>",
+                            descriptor.attribute_id,
+                            attribute_access_name(descriptor.access_mode)
+                        );
+                    }
+                }
+            }
+            for descriptor in &object.method_access {
+                let _ = writeln!(
+                    out,
+                    "      <Method Id=\"{}\" Access=\"{}\"/>",
+                    descriptor.method_id,
+                    method_access_name(descriptor.access_mode)
+                );
+            }
+            out.push_str("    </AccessRights>\n");
+
+            out.push_str("    <Values>\n");
+            for (attribute_id, value) in &object.attribute_values {
+                let _ = writeln!(out, "      <Attribute Id=\"{}\">", attribute_id);
+                write_data(value, 8, &mut out);
+                out.push_str("      </Attribute>\n");
+            }
+            out.push_str("    </Values>\n");
+            out.push_str("  </Object>\n");
+        }
+        out.push_str("</Objects>\n");
+        out
+    }
+
+    pub fn from_xml(xml: &str) -> Result<Self, DlmsError> {
+        let root = XmlParser::new(xml).parse_document()?;
+        if root.name != "Objects" {
+            return Err(DlmsError::ParseError);
+        }
+
+        let mut objects = Vec::new();
+        for element in root.children_named("Object") {
+            let class_id = element.parse_attribute("ClassId")?;
+            let version = element.parse_attribute("Version")?;
+            let logical_name = parse_logical_name(element.attribute("LogicalName")?)?;
+
+            let mut attribute_access = Vec::new();
+            let mut method_access = Vec::new();
+            if let Some(rights) = element.children_named("AccessRights").next() {
+                for child in &rights.children {
+                    match child.name.as_str() {
+                        "Attribute" => {
+                            let selective_access = match child.children.first() {
+                                Some(data) => Some(parse_data(data)?),
+                                None => None,
+                            };
+                            attribute_access.push(
+                                AttributeAccessDescriptor::with_selective_access(
+                                    child.parse_attribute("Id")?,
+                                    parse_attribute_access(child.attribute("Access")?)?,
+                                    selective_access,
+                                ),
+                            );
+                        }
+                        "Method" => method_access.push(MethodAccessDescriptor::new(
+                            child.parse_attribute("Id")?,
+                            parse_method_access(child.attribute("Access")?)?,
+                        )),
+                        _ => return Err(DlmsError::ParseError),
+                    }
+                }
+            }
+
+            let mut attribute_values = Vec::new();
+            if let Some(values) = element.children_named("Values").next() {
+                for child in values.children_named("Attribute") {
+                    let data = child.children.first().ok_or(DlmsError::ParseError)?;
+                    attribute_values.push((child.parse_attribute("Id")?, parse_data(data)?));
+                }
+            }
+
+            objects.push(ObjectDescription {
+                class_id,
+                version,
+                logical_name,
+                attribute_access,
+                method_access,
+                attribute_values,
+            });
+        }
+
+        Ok(ObjectModel { objects })
+    }
+}
+
+pub fn format_logical_name(logical_name: &[u8; 6]) -> String {
+    let mut out = String::new();
+    for (index, byte) in logical_name.iter().enumerate() {
+        if index > 0 {
+            out.push('.');
+        }
+        let _ = write!(out, "{}", byte);
+    }
+    out
+}
+
+pub fn parse_logical_name(text: &str) -> Result<[u8; 6], DlmsError> {
+    let mut logical_name = [0u8; 6];
+    let mut parts = text.split('.');
+    for (index, byte) in logical_name.iter_mut().enumerate() {
+        let part = parts.next().ok_or(DlmsError::ParseError)?.trim();
+        *byte = match part.parse() {
+            Ok(value) => value,
+            // Value group F may name a billing period back from the last one
+            // ended: VZ, VZ-1 and so on.
+            Err(_) if index == 5 => parse_billing_period(part)?,
+            Err(_) => return Err(DlmsError::ParseError),
+        };
+    }
+    if parts.next().is_some() {
+        return Err(DlmsError::ParseError);
+    }
+    Ok(logical_name)
+}
+
+fn parse_billing_period(part: &str) -> Result<u8, DlmsError> {
+    let back = match part.strip_prefix("VZ") {
+        Some("") => 0,
+        Some(back) => back
+            .strip_prefix('-')
+            .and_then(|back| back.parse().ok())
+            .ok_or(DlmsError::ParseError)?,
+        None => return Err(DlmsError::ParseError),
+    };
+    if back >= MAX_PREVIOUS_BILLING_PERIODS {
+        return Err(DlmsError::ParseError);
+    }
+    Ok(BillingPeriod::Previous(back).value_group_f())
+}
+
+fn attribute_access_name(mode: AttributeAccessMode) -> &'static str {
+    match mode {
+        AttributeAccessMode::NoAccess => "NoAccess",
+        AttributeAccessMode::Read => "Read",
+        AttributeAccessMode::Write => "Write",
+        AttributeAccessMode::ReadWrite => "ReadWrite",
+    }
+}
+
+fn parse_attribute_access(name: &str) -> Result<AttributeAccessMode, DlmsError> {
+    match name {
+        "NoAccess" => Ok(AttributeAccessMode::NoAccess),
+        "Read" => Ok(AttributeAccessMode::Read),
+        "Write" => Ok(AttributeAccessMode::Write),
+        "ReadWrite" => Ok(AttributeAccessMode::ReadWrite),
+        _ => Err(DlmsError::ParseError),
+    }
+}
+
+fn method_access_name(mode: MethodAccessMode) -> &'static str {
+    match mode {
+        MethodAccessMode::NoAccess => "NoAccess",
+        MethodAccessMode::Access => "Access",
+    }
+}
+
+fn parse_method_access(name: &str) -> Result<MethodAccessMode, DlmsError> {
+    match name {
+        "NoAccess" => Ok(MethodAccessMode::NoAccess),
+        "Access" => Ok(MethodAccessMode::Access),
+        _ => Err(DlmsError::ParseError),
+    }
+}
+
+pub(crate) fn write_hex(bytes: &[u8], out: &mut String) {
+    for byte in bytes {
+        let _ = write!(out, "{:02X}", byte);
+    }
+}
+
+pub(crate) fn parse_hex(text: &str) -> Result<Vec<u8>, DlmsError> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return Err(DlmsError::ParseError);
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| {
+            text.get(index..index + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(DlmsError::ParseError)
+        })
+        .collect()
+}
+
+pub(crate) fn write_bits(bits: &BitString, out: &mut String) {
+    out.extend(bits.bits().map(|bit| if bit { '1' } else { '0' }));
+}
+
+// One character per bit, so any number of bits.
+pub(crate) fn parse_bits(text: &str) -> Result<BitString, DlmsError> {
+    let text = text.trim();
+    let mut bits =
+        BitString::new(text.len(), vec![0; text.len().div_ceil(8)]).ok_or(DlmsError::ParseError)?;
+    for (index, bit) in text.chars().enumerate() {
+        match bit {
+            '0' => {}
+            '1' => bits.set_bit(index, true).ok_or(DlmsError::ParseError)?,
+            _ => return Err(DlmsError::ParseError),
+        }
+    }
+    Ok(bits)
+}
+
+fn write_data(data: &CosemData, indent: usize, out: &mut String) {
+    for _ in 0..indent {
+        out.push(' ');
+    }
+
+    let (name, text) = match data {
+        CosemData::NullData => {
+            out.push_str("<null-data/>\n");
+            return;
+        }
+        CosemData::DontCare => {
+            out.push_str("<dont-care/>\n");
+            return;
+        }
+        CosemData::Array(elements) | CosemData::Structure(elements) => {
+            let name = if matches!(data, CosemData::Array(_)) {
+                "array"
+            } else {
+                "structure"
+            };
+            if elements.is_empty() {
+                let _ = writeln!(out, "<{}/>", name);
+                return;
+            }
+            let _ = writeln!(out, "<{}>", name);
+            for element in elements {
+                write_data(element, indent + 2, out);
+            }
+            for _ in 0..indent {
+                out.push(' ');
+            }
+            let _ = writeln!(out, "</{}>", name);
+            return;
+        }
+        CosemData::Boolean(value) => ("boolean", value.to_string()),
+        CosemData::BitString(bits) => {
+            let mut text = String::new();
+            write_bits(bits, &mut text);
+            ("bit-string", text)
+        }
+        CosemData::DoubleLong(value) => ("double-long", value.to_string()),
+        CosemData::DoubleLongUnsigned(value) => ("double-long-unsigned", value.to_string()),
+        CosemData::OctetString(bytes) => {
+            let mut hex = String::new();
+            write_hex(bytes, &mut hex);
+            ("octet-string", hex)
+        }
+        CosemData::VisibleString(value) => ("visible-string", escape(value)),
+        CosemData::Utf8String(value) => ("utf8-string", escape(value)),
+        CosemData::Bcd(value) => ("bcd", value.to_string()),
+        CosemData::Integer(value) => ("integer", value.to_string()),
+        CosemData::Long(value) => ("long", value.to_string()),
+        CosemData::Unsigned(value) => ("unsigned", value.to_string()),
+        CosemData::LongUnsigned(value) => ("long-unsigned", value.to_string()),
+        CosemData::Long64(value) => ("long64", value.to_string()),
+        CosemData::Long64Unsigned(value) => ("long64-unsigned", value.to_string()),
+        CosemData::Enum(value) => ("enum", value.to_string()),
+        CosemData::Float32(value) => ("float32", value.to_string()),
+        CosemData::Float64(value) => ("float64", value.to_string()),
+        CosemData::DateTime(bytes) | CosemData::Date(bytes) | CosemData::Time(bytes) => {
+            let name = match data {
+                CosemData::DateTime(_) => "date-time",
+                CosemData::Date(_) => "date",
+                _ => "time",
+            };
+            let mut hex = String::new();
+            write_hex(bytes, &mut hex);
+            (name, hex)
+        }
+    };
+
+    let _ = writeln!(out, "<{0}>{1}</{0}>", name, text);
+}
+
+fn parse_data(element: &XmlElement) -> Result<CosemData, DlmsError> {
+    fn number<V: core::str::FromStr>(element: &XmlElement) -> Result<V, DlmsError> {
+        element
+            .text
+            .trim()
+            .parse()
+            .map_err(|_| DlmsError::ParseError)
+    }
+
+    let data = match element.name.as_str() {
+        "null-data" => CosemData::NullData,
+        "dont-care" => CosemData::DontCare,
+        "array" | "structure" => {
+            let elements = element
+                .children
+                .iter()
+                .map(parse_data)
+                .collect::<Result<Vec<_>, _>>()?;
+            if element.name == "array" {
+                CosemData::Array(elements)
+            } else {
+                CosemData::Structure(elements)
+            }
+        }
+        "boolean" => CosemData::Boolean(number(element)?),
+        "bit-string" => CosemData::BitString(parse_bits(&element.text)?),
+        "double-long" => CosemData::DoubleLong(number(element)?),
+        "double-long-unsigned" => CosemData::DoubleLongUnsigned(number(element)?),
+        "octet-string" => CosemData::OctetString(parse_hex(&element.text)?),
+        "visible-string" => CosemData::VisibleString(element.text.clone()),
+        "utf8-string" => CosemData::Utf8String(element.text.clone()),
+        "bcd" => CosemData::Bcd(number(element)?),
+        "integer" => CosemData::Integer(number(element)?),
+        "long" => CosemData::Long(number(element)?),
+        "unsigned" => CosemData::Unsigned(number(element)?),
+        "long-unsigned" => CosemData::LongUnsigned(number(element)?),
+        "long64" => CosemData::Long64(number(element)?),
+        "long64-unsigned" => CosemData::Long64Unsigned(number(element)?),
+        "enum" => CosemData::Enum(number(element)?),
+        "float32" => CosemData::Float32(number(element)?),
+        "float64" => CosemData::Float64(number(element)?),
+        "date-time" => CosemData::DateTime(parse_hex(&element.text)?),
+        "date" => CosemData::Date(parse_hex(&element.text)?),
+        "time" => CosemData::Time(parse_hex(&element.text)?),
+        _ => return Err(DlmsError::ParseError),
+    };
+    Ok(data)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> Result<String, DlmsError> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or(DlmsError::ParseError)? + start;
+        out.push(match &rest[start + 1..end] {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => return Err(DlmsError::ParseError),
+        });
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// Minimal XML reader covering the subset emitted by `ObjectModel::to_xml`:
+// elements, attributes, text content, comments and the XML declaration.
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn attribute(&self, name: &str) -> Result<&str, DlmsError> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .ok_or(DlmsError::ParseError)
+    }
+
+    fn parse_attribute<V: core::str::FromStr>(&self, name: &str) -> Result<V, DlmsError> {
+        self.attribute(name)?
+            .trim()
+            .parse()
+            .map_err(|_| DlmsError::ParseError)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+// Elements nested deeper are refused rather than recursed into; a model is
+// a handful of levels deep even with nested structures as values.
+const MAX_XML_DEPTH: usize = 64;
+
+struct XmlParser<'a> {
+    input: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> XmlParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn skip_misc(&mut self) -> Result<(), DlmsError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_past(&mut self, terminator: &str) -> Result<(), DlmsError> {
+        let end = self.rest().find(terminator).ok_or(DlmsError::ParseError)?;
+        self.position += end + terminator.len();
+        Ok(())
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), DlmsError> {
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            Ok(())
+        } else {
+            Err(DlmsError::ParseError)
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<String, DlmsError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == ':'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(DlmsError::ParseError);
+        }
+        self.position += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn parse_document(&mut self) -> Result<XmlElement, DlmsError> {
+        self.skip_misc()?;
+        let root = self.parse_element()?;
+        self.skip_misc()?;
+        if !self.rest().is_empty() {
+            return Err(DlmsError::ParseError);
+        }
+        Ok(root)
+    }
+
+    fn parse_element(&mut self) -> Result<XmlElement, DlmsError> {
+        if self.depth == MAX_XML_DEPTH {
+            return Err(DlmsError::ParseError);
+        }
+        self.depth += 1;
+        let element = self.parse_element_body();
+        self.depth -= 1;
+        element
+    }
+
+    fn parse_element_body(&mut self) -> Result<XmlElement, DlmsError> {
+        self.expect("<")?;
+        let mut element = XmlElement {
+            name: self.parse_name()?,
+            ..XmlElement::default()
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let key = self.parse_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.rest().starts_with('"') {
+                "\""
+            } else {
+                "'"
+            };
+            self.expect(quote)?;
+            let end = self.rest().find(quote).ok_or(DlmsError::ParseError)?;
+            let value = unescape(&self.rest()[..end])?;
+            self.position += end + 1;
+            element.attributes.push((key, value));
+        }
+
+        let mut text = String::new();
+        loop {
+            let rest = self.rest();
+            let next_tag = rest.find('<').ok_or(DlmsError::ParseError)?;
+            text.push_str(&rest[..next_tag]);
+            self.position += next_tag;
+
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("</") {
+                self.position += 2;
+                let name = self.parse_name()?;
+                self.skip_whitespace();
+                self.expect(">")?;
+                if name != element.name {
+                    return Err(DlmsError::ParseError);
+                }
+                break;
+            } else {
+                element.children.push(self.parse_element()?);
+            }
+        }
+
+        element.text = if element.children.is_empty() {
+            unescape(&text)?
+        } else {
+            String::new()
+        };
+        Ok(element)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    fn sample_model() -> ObjectModel {
+        ObjectModel {
+            objects: vec![ObjectDescription {
+                class_id: 3,
+                version: 0,
+                logical_name: [1, 0, 1, 8, 0, 255],
+                attribute_access: vec![
+                    AttributeAccessDescriptor::new(2, AttributeAccessMode::ReadWrite),
+                    AttributeAccessDescriptor::with_selective_access(
+                        3,
+                        AttributeAccessMode::Read,
+                        Some(CosemData::Array(vec![CosemData::Integer(1)])),
+                    ),
+                ],
+                method_access: vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)],
+                attribute_values: vec![
+                    (2, CosemData::DoubleLongUnsigned(123_456)),
+                    (
+                        3,
+                        CosemData::Structure(vec![CosemData::Integer(-3), CosemData::Enum(30)]),
+                    ),
+                    (
+                        4,
+                        CosemData::Structure(vec![
+                            CosemData::NullData,
+                            CosemData::Boolean(true),
+                            CosemData::BitString(BitString::new(5, vec![0b1010_0000]).unwrap()),
+                            CosemData::OctetString(vec![0x00, 0xAB, 0xFF]),
+                            CosemData::VisibleString("<meter & co>".to_string()),
+                            CosemData::Long64(-5),
+                            CosemData::Float32(1.5),
+                            CosemData::DateTime(vec![0x07, 0xE8, 1, 1, 1, 0, 0, 0, 0, 0x80, 0, 0]),
+                            CosemData::Array(Vec::new()),
+                        ]),
+                    ),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn object_model_round_trips_through_xml() {
+        let model = sample_model();
+        let xml = model.to_xml();
+        assert!(xml.contains("LogicalName=\"1.0.1.8.0.255\""));
+        assert!(xml.contains("<double-long-unsigned>123456</double-long-unsigned>"));
+        assert!(xml.contains("&lt;meter &amp; co&gt;"));
+
+        let parsed = ObjectModel::from_xml(&xml).expect("failed to parse exported model");
+        assert_eq!(parsed, model);
+    }
+
+    #[test]
+    fn malformed_documents_are_rejected() {
+        assert!(ObjectModel::from_xml("<Objects><Object></Objects>").is_err());
+        assert!(ObjectModel::from_xml("<Meters/>").is_err());
+        assert!(parse_logical_name("1.0.1.8.0").is_err());
+        assert!(parse_logical_name("1.0.1.8.0.256").is_err());
+    }
+
+    #[test]
+    fn multibyte_text_and_deep_nesting_are_rejected() {
+        assert!(parse_bits("1010€101").is_err());
+        assert!(parse_bits("€€€€€€€€").is_err());
+        assert!(parse_hex("0€").is_err());
+        assert!(parse_hex("€0").is_err());
+
+        let nested = |depth: usize| {
+            let mut xml = String::new();
+            for _ in 0..depth {
+                xml.push_str("<structure>");
+            }
+            for _ in 0..depth {
+                xml.push_str("</structure>");
+            }
+            xml
+        };
+        assert!(XmlParser::new(&nested(MAX_XML_DEPTH))
+            .parse_document()
+            .is_ok());
+        assert!(XmlParser::new(&nested(MAX_XML_DEPTH + 1))
+            .parse_document()
+            .is_err());
+        assert!(ObjectModel::from_xml(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn billing_periods_are_named_back_from_the_last() {
+        assert_eq!(
+            parse_logical_name("1.0.1.8.0.VZ").unwrap(),
+            [1, 0, 1, 8, 0, 101]
+        );
+        assert_eq!(
+            parse_logical_name("1.0.1.8.0.VZ-24").unwrap(),
+            [1, 0, 1, 8, 0, 125]
+        );
+        assert!(parse_logical_name("1.0.1.8.0.VZ-25").is_err());
+        assert!(parse_logical_name("1.0.1.8.0.VZ1").is_err());
+        assert!(parse_logical_name("1.0.1.VZ.0.255").is_err());
+    }
+}
@@ -0,0 +1,577 @@
+use crate::cosem::CosemObjectInstanceId;
+use crate::cosem_object::CosemObject;
+use crate::server::ObjectVisibility;
+use std::boxed::Box;
+use std::vec::Vec;
+
+type ObjectSetEntry = (
+    CosemObjectInstanceId,
+    Box<dyn CosemObject>,
+    ObjectVisibility,
+);
+
+// Objects registered together, each with the associations it is listed in.
+// The sets mandated by companion profiles are built behind the profile-*
+// features; the logical device name and the association objects are left to
+// `ServerBuilder`, as every server has them.
+#[derive(Default)]
+pub struct ObjectSet {
+    objects: Vec<ObjectSetEntry>,
+}
+
+impl ObjectSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces an object of the same logical name, so that a profile set can
+    // be adjusted before it is registered.
+    pub fn object(
+        mut self,
+        logical_name: CosemObjectInstanceId,
+        object: Box<dyn CosemObject>,
+        visibility: ObjectVisibility,
+    ) -> Self {
+        self.objects
+            .retain(|(existing, _, _)| *existing != logical_name);
+        self.objects.push((logical_name, object, visibility));
+        self
+    }
+
+    pub fn get(&self, logical_name: CosemObjectInstanceId) -> Option<&dyn CosemObject> {
+        self.objects
+            .iter()
+            .find(|(existing, _, _)| *existing == logical_name)
+            .map(|(_, object, _)| object.as_ref())
+    }
+
+    pub fn logical_names(&self) -> impl Iterator<Item = CosemObjectInstanceId> + '_ {
+        self.objects
+            .iter()
+            .map(|(logical_name, _, _)| *logical_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub(crate) fn into_objects(self) -> Vec<ObjectSetEntry> {
+        self.objects
+    }
+}
+
+#[cfg(any(
+    feature = "profile-basic",
+    feature = "profile-idis",
+    feature = "profile-sto"
+))]
+mod objects {
+    use super::ObjectSet;
+    use crate::cosem::CosemObjectInstanceId;
+    use crate::cosem_object::{AttributeAccessMode, CosemObject};
+    use crate::data::Data;
+    use crate::register::Register;
+    use crate::scaled_value::{ScaledValue, Unit};
+    use crate::server::ObjectVisibility;
+    use crate::types::CosemData;
+    use std::boxed::Box;
+
+    pub const CLOCK: CosemObjectInstanceId = [0, 0, 1, 0, 0, 255];
+
+    pub fn authenticated(
+        set: ObjectSet,
+        logical_name: CosemObjectInstanceId,
+        object: Box<dyn CosemObject>,
+    ) -> ObjectSet {
+        set.object(logical_name, object, ObjectVisibility::Authenticated)
+    }
+
+    // Identification data, set by the manufacturer and read-only for clients.
+    pub fn identifier(value: CosemData) -> Box<dyn CosemObject> {
+        Box::new(Data::with_access(value, AttributeAccessMode::Read))
+    }
+
+    // Energies count up from zero in double-long-unsigned.
+    pub fn energy(unit: Unit) -> Box<dyn CosemObject> {
+        register(CosemData::DoubleLongUnsigned(0), 0, unit)
+    }
+
+    #[cfg(any(feature = "profile-idis", feature = "profile-sto"))]
+    pub fn instantaneous(scaler: i8, unit: Unit) -> Box<dyn CosemObject> {
+        register(CosemData::LongUnsigned(0), scaler, unit)
+    }
+
+    fn register(value: CosemData, scaler: i8, unit: Unit) -> Box<dyn CosemObject> {
+        let mut register = Register::new();
+        register.set_attribute(2, value);
+        register.set_attribute(3, ScaledValue::new(0, scaler, unit).scaler_unit());
+        Box::new(register)
+    }
+
+    // The event code of an event log.
+    #[cfg(any(feature = "profile-idis", feature = "profile-sto"))]
+    pub fn event_code() -> Box<dyn CosemObject> {
+        Box::new(Data::new(CosemData::LongUnsigned(0)))
+    }
+
+    // The status column of a load profile.
+    #[cfg(feature = "profile-idis")]
+    pub fn profile_status() -> Box<dyn CosemObject> {
+        use crate::profile_generic::ProfileStatus;
+        Box::new(Data::new(ProfileStatus::default().to_cosem_data()))
+    }
+
+    // Captures attribute 2 of each object, the clock first. A capture period
+    // of 0 leaves capturing to the host or a script.
+    #[cfg(any(feature = "profile-idis", feature = "profile-sto"))]
+    pub fn profile(
+        captured: &[(crate::cosem::CosemClassId, CosemObjectInstanceId)],
+        capture_period: u32,
+        profile_entries: u32,
+    ) -> Box<dyn CosemObject> {
+        use crate::profile_generic::{CaptureObjectDefinition, ProfileGeneric};
+        use std::vec::Vec;
+
+        let capture_objects = std::iter::once((8, CLOCK))
+            .chain(captured.iter().copied())
+            .map(|(class_id, logical_name)| {
+                CaptureObjectDefinition {
+                    class_id,
+                    logical_name,
+                    attribute_index: 2,
+                    data_index: 0,
+                }
+                .to_cosem_data()
+            })
+            .collect::<Vec<_>>();
+        let mut profile = ProfileGeneric::new();
+        profile.set_attribute(3, CosemData::Array(capture_objects));
+        profile.set_attribute(4, CosemData::DoubleLongUnsigned(capture_period));
+        // FIFO
+        profile.set_attribute(5, CosemData::Enum(1));
+        profile.set_attribute(7, CosemData::DoubleLongUnsigned(0));
+        profile.set_attribute(8, CosemData::DoubleLongUnsigned(profile_entries));
+        Box::new(profile)
+    }
+}
+
+// DLMS UA basic metering: clock, serial number and the active energy totals,
+// reached over HDLC.
+#[cfg(feature = "profile-basic")]
+pub fn basic_metering() -> ObjectSet {
+    use crate::clock::Clock;
+    use crate::iec_hdlc_setup::IecHdlcSetup;
+    use crate::scaled_value::Unit;
+    use crate::security_setup::SecuritySetup;
+    use crate::types::CosemData;
+    use objects::*;
+
+    let set = ObjectSet::new()
+        .object(CLOCK, Box::new(Clock::new()), ObjectVisibility::Public)
+        .object(
+            [0, 0, 96, 1, 0, 255],
+            identifier(CosemData::OctetString(Vec::new())),
+            ObjectVisibility::Public,
+        );
+    let set = authenticated(set, [1, 0, 1, 8, 0, 255], energy(Unit::WATT_HOUR));
+    let set = authenticated(set, [1, 0, 2, 8, 0, 255], energy(Unit::WATT_HOUR));
+    let set = authenticated(set, [0, 0, 22, 0, 0, 255], Box::new(IecHdlcSetup::new()));
+    authenticated(set, [0, 0, 43, 0, 0, 255], Box::new(SecuritySetup::new()))
+}
+
+// IDIS package 2 (IP profile): energies by tariff, instantaneous values of
+// three phases, load, billing and event profiles, disconnector, limiter,
+// tariffication and push over GPRS.
+#[cfg(feature = "profile-idis")]
+pub fn idis_package_2() -> ObjectSet {
+    use crate::activity_calendar::ActivityCalendar;
+    use crate::clock::Clock;
+    use crate::data::Data;
+    use crate::disconnect_control::DisconnectControl;
+    use crate::gprs_modem_setup::GprsModemSetup;
+    use crate::ipv4_setup::Ipv4Setup;
+    use crate::limiter::Limiter;
+    use crate::push_setup::PushSetup;
+    use crate::scaled_value::Unit;
+    use crate::script_table::ScriptTable;
+    use crate::security_setup::SecuritySetup;
+    use crate::types::CosemData;
+    use objects::*;
+
+    let mut set = ObjectSet::new()
+        .object(CLOCK, Box::new(Clock::new()), ObjectVisibility::Public)
+        .object(
+            [0, 0, 96, 1, 0, 255],
+            identifier(CosemData::OctetString(Vec::new())),
+            ObjectVisibility::Public,
+        );
+    for (logical_name, value) in [
+        ([0, 0, 96, 1, 1, 255], CosemData::OctetString(Vec::new())),
+        ([1, 0, 0, 2, 0, 255], CosemData::OctetString(Vec::new())),
+        ([1, 0, 0, 2, 8, 255], CosemData::OctetString(Vec::new())),
+    ] {
+        set = authenticated(set, logical_name, identifier(value));
+    }
+    set = authenticated(
+        set,
+        [0, 0, 96, 14, 0, 255],
+        Box::new(Data::new(CosemData::OctetString(Vec::new()))),
+    );
+
+    // Active import and export by tariff, reactive totals.
+    for (quantity, unit) in [
+        (1, Unit::WATT_HOUR),
+        (2, Unit::WATT_HOUR),
+        (3, Unit::VAR_HOUR),
+        (4, Unit::VAR_HOUR),
+    ] {
+        let tariffs = if quantity <= 2 { 0..=4 } else { 0..=0 };
+        for tariff in tariffs {
+            set = authenticated(set, [1, 0, quantity, 8, tariff, 255], energy(unit));
+        }
+    }
+    for phase in [32, 52, 72] {
+        set = authenticated(set, [1, 0, phase, 7, 0, 255], instantaneous(-1, Unit::VOLT));
+    }
+    for phase in [31, 51, 71] {
+        set = authenticated(
+            set,
+            [1, 0, phase, 7, 0, 255],
+            instantaneous(-2, Unit::AMPERE),
+        );
+    }
+    for direction in [1, 2] {
+        set = authenticated(
+            set,
+            [1, 0, direction, 7, 0, 255],
+            instantaneous(0, Unit::WATT),
+        );
+    }
+
+    // Profile status of the load profiles and the event codes of the logs.
+    set = authenticated(set, [0, 0, 96, 10, 1, 255], profile_status());
+    set = authenticated(set, [0, 0, 96, 10, 2, 255], profile_status());
+    let energies = [
+        (3, [1, 0, 1, 8, 0, 255]),
+        (3, [1, 0, 2, 8, 0, 255]),
+        (3, [1, 0, 3, 8, 0, 255]),
+        (3, [1, 0, 4, 8, 0, 255]),
+    ];
+    let load_profile = |status_ln| {
+        let mut captured = vec![(1, status_ln)];
+        captured.extend(energies);
+        captured
+    };
+    set = authenticated(
+        set,
+        [1, 0, 99, 1, 0, 255],
+        profile(&load_profile([0, 0, 96, 10, 1, 255]), 900, 960),
+    );
+    set = authenticated(
+        set,
+        [1, 0, 99, 2, 0, 255],
+        profile(&load_profile([0, 0, 96, 10, 2, 255]), 86_400, 40),
+    );
+    let billing: Vec<_> = (0..=4)
+        .flat_map(|tariff| {
+            [
+                (3, [1, 0, 1, 8, tariff, 255]),
+                (3, [1, 0, 2, 8, tariff, 255]),
+            ]
+        })
+        .collect();
+    set = authenticated(set, [0, 0, 98, 1, 0, 255], profile(&billing, 0, 12));
+    // Standard, fraud, disconnector control and power quality events.
+    for log in [0, 1, 2, 4] {
+        set = authenticated(set, [0, 0, 96, 11, log, 255], event_code());
+        set = authenticated(
+            set,
+            [0, 0, 99, 98, log, 255],
+            profile(&[(1, [0, 0, 96, 11, log, 255])], 0, 100),
+        );
+    }
+
+    set = authenticated(
+        set,
+        [0, 0, 96, 3, 10, 255],
+        Box::new(DisconnectControl::new()),
+    );
+    set = authenticated(set, [0, 0, 17, 0, 0, 255], Box::new(Limiter::new()));
+    set = authenticated(
+        set,
+        [0, 0, 13, 0, 0, 255],
+        Box::new(ActivityCalendar::new()),
+    );
+    // Tariffication, end of billing and disconnector scripts.
+    for table in [100, 1, 106] {
+        set = authenticated(set, [0, 0, 10, 0, table, 255], Box::new(ScriptTable::new()));
+    }
+    set = authenticated(set, [0, 0, 25, 9, 0, 255], Box::new(PushSetup::new()));
+    set = authenticated(set, [0, 0, 25, 1, 0, 255], Box::new(Ipv4Setup::new()));
+    set = authenticated(set, [0, 0, 25, 4, 0, 255], Box::new(GprsModemSetup::new()));
+    authenticated(set, [0, 0, 43, 0, 0, 255], Box::new(SecuritySetup::new()))
+}
+
+// СТО 34.01-5.1-013-2023 (SPODES): identification, energies by tariff,
+// instantaneous values of three phases, the instantaneous, load, daily and
+// monthly profiles and the event logs.
+#[cfg(feature = "profile-sto")]
+pub fn sto_34_01_5_1_013() -> ObjectSet {
+    use crate::activity_calendar::ActivityCalendar;
+    use crate::clock::Clock;
+    use crate::disconnect_control::DisconnectControl;
+    use crate::iec_hdlc_setup::IecHdlcSetup;
+    use crate::limiter::Limiter;
+    use crate::scaled_value::Unit;
+    use crate::script_table::ScriptTable;
+    use crate::security_setup::SecuritySetup;
+    use crate::types::CosemData;
+    use objects::*;
+    use std::string::String;
+
+    // Serial number, meter type, metrology software version and manufacturer
+    // are readable by the public client.
+    let mut set = ObjectSet::new().object(CLOCK, Box::new(Clock::new()), ObjectVisibility::Public);
+    for identification in 0..=3 {
+        set = set.object(
+            [0, 0, 96, 1, identification, 255],
+            identifier(CosemData::VisibleString(String::new())),
+            ObjectVisibility::Public,
+        );
+    }
+
+    for (quantity, unit) in [
+        (1, Unit::WATT_HOUR),
+        (2, Unit::WATT_HOUR),
+        (3, Unit::VAR_HOUR),
+        (4, Unit::VAR_HOUR),
+    ] {
+        let tariffs = if quantity == 1 { 0..=4 } else { 0..=0 };
+        for tariff in tariffs {
+            set = authenticated(set, [1, 0, quantity, 8, tariff, 255], energy(unit));
+        }
+        // Energy of the last load profile period.
+        set = authenticated(set, [1, 0, quantity, 29, 0, 255], energy(unit));
+    }
+    let instantaneous_values = [
+        ([1, 0, 32, 7, 0, 255], instantaneous(-2, Unit::VOLT)),
+        ([1, 0, 52, 7, 0, 255], instantaneous(-2, Unit::VOLT)),
+        ([1, 0, 72, 7, 0, 255], instantaneous(-2, Unit::VOLT)),
+        ([1, 0, 31, 7, 0, 255], instantaneous(-3, Unit::AMPERE)),
+        ([1, 0, 51, 7, 0, 255], instantaneous(-3, Unit::AMPERE)),
+        ([1, 0, 71, 7, 0, 255], instantaneous(-3, Unit::AMPERE)),
+        ([1, 0, 1, 7, 0, 255], instantaneous(0, Unit::WATT)),
+        ([1, 0, 3, 7, 0, 255], instantaneous(0, Unit::VAR)),
+        ([1, 0, 14, 7, 0, 255], instantaneous(-2, Unit::HERTZ)),
+    ];
+    let instantaneous_captured: Vec<_> = instantaneous_values
+        .iter()
+        .map(|(logical_name, _)| (3, *logical_name))
+        .collect();
+    for (logical_name, object) in instantaneous_values {
+        set = authenticated(set, logical_name, object);
+    }
+    set = authenticated(
+        set,
+        [1, 0, 94, 7, 0, 255],
+        profile(&instantaneous_captured, 0, 1),
+    );
+
+    let increments: Vec<_> = (1..=4)
+        .map(|quantity| (3, [1, 0, quantity, 29, 0, 255]))
+        .collect();
+    set = authenticated(set, [1, 0, 99, 1, 0, 255], profile(&increments, 1800, 4464));
+    let mut totals: Vec<_> = (0..=4)
+        .map(|tariff| (3, [1, 0, 1, 8, tariff, 255]))
+        .collect();
+    totals.extend((2..=4).map(|quantity| (3, [1, 0, quantity, 8, 0, 255])));
+    set = authenticated(set, [1, 0, 98, 1, 0, 255], profile(&totals, 0, 36));
+    set = authenticated(set, [1, 0, 98, 2, 0, 255], profile(&totals, 0, 124));
+
+    // Voltage, current, switching, correction, external impact,
+    // communication, access and self-diagnostics events.
+    for log in 0..=7 {
+        set = authenticated(set, [0, 0, 96, 11, log, 255], event_code());
+        set = authenticated(
+            set,
+            [0, 0, 99, 98, log, 255],
+            profile(&[(1, [0, 0, 96, 11, log, 255])], 0, 500),
+        );
+    }
+
+    set = authenticated(
+        set,
+        [0, 0, 96, 3, 10, 255],
+        Box::new(DisconnectControl::new()),
+    );
+    set = authenticated(set, [0, 0, 17, 0, 0, 255], Box::new(Limiter::new()));
+    set = authenticated(
+        set,
+        [0, 0, 13, 0, 0, 255],
+        Box::new(ActivityCalendar::new()),
+    );
+    set = authenticated(set, [0, 0, 10, 0, 100, 255], Box::new(ScriptTable::new()));
+    set = authenticated(set, [0, 0, 22, 0, 0, 255], Box::new(IecHdlcSetup::new()));
+    authenticated(set, [0, 0, 43, 0, 0, 255], Box::new(SecuritySetup::new()))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::data::Data;
+    use crate::register::Register;
+    use crate::server::ServerBuilder;
+    use crate::testing::DetachedTransport;
+    use crate::types::CosemData;
+
+    // Logical names in the object list of an association of the server.
+    fn listed(
+        server: &crate::server::Server<DetachedTransport>,
+        association: CosemObjectInstanceId,
+    ) -> Vec<CosemObjectInstanceId> {
+        let (_, object) = server
+            .objects()
+            .find(|(logical_name, _)| *logical_name == association)
+            .unwrap();
+        let Some(CosemData::Array(entries)) = object.get_attribute(2) else {
+            panic!("no object list");
+        };
+        entries
+            .iter()
+            .filter_map(|entry| match entry {
+                CosemData::Structure(fields) => match &fields[2] {
+                    CosemData::OctetString(logical_name) => logical_name.as_slice().try_into().ok(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn object_set_is_registered_with_the_visibility_of_each_object() {
+        let serial = [0, 0, 96, 1, 0, 255];
+        let energy = [1, 0, 1, 8, 0, 255];
+        let set = ObjectSet::new()
+            .object(serial, Box::new(Register::new()), ObjectVisibility::Public)
+            .object(
+                serial,
+                Box::new(Data::new(CosemData::OctetString(b"12345678".to_vec()))),
+                ObjectVisibility::Public,
+            )
+            .object(
+                energy,
+                Box::new(Register::new()),
+                ObjectVisibility::Authenticated,
+            );
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.get(serial).map(|object| object.class_id()), Some(1));
+        assert_eq!(set.logical_names().collect::<Vec<_>>(), [serial, energy]);
+
+        let server = ServerBuilder::new(1, DetachedTransport)
+            .object_set(set)
+            .build();
+        let public = listed(&server, [0, 0, 40, 0, 1, 255]);
+        assert!(public.contains(&serial));
+        assert!(!public.contains(&energy));
+        assert!(listed(&server, [0, 0, 40, 0, 2, 255]).contains(&energy));
+    }
+
+    // Every object a profile of the set captures is in the set, with the
+    // class given in the capture definition.
+    #[cfg(any(feature = "profile-idis", feature = "profile-sto"))]
+    fn assert_captured_objects_present(set: &ObjectSet) {
+        use crate::profile_generic::capture_object_definitions;
+
+        for logical_name in set.logical_names() {
+            let profile = set.get(logical_name).unwrap();
+            if profile.class_id() != 7 {
+                continue;
+            }
+            let definitions =
+                capture_object_definitions(&profile.get_attribute(3).unwrap()).unwrap();
+            assert!(!definitions.is_empty());
+            for definition in definitions {
+                let captured = set
+                    .get(definition.logical_name)
+                    .unwrap_or_else(|| panic!("{logical_name:?} captures missing {definition:?}"));
+                assert_eq!(captured.class_id(), definition.class_id);
+            }
+        }
+    }
+
+    #[cfg(feature = "profile-basic")]
+    #[test]
+    fn basic_metering_holds_clock_serial_and_energies() {
+        let set = basic_metering();
+        for (logical_name, class_id) in [
+            ([0, 0, 1, 0, 0, 255], 8),
+            ([0, 0, 96, 1, 0, 255], 1),
+            ([1, 0, 1, 8, 0, 255], 3),
+            ([1, 0, 2, 8, 0, 255], 3),
+            ([0, 0, 22, 0, 0, 255], 23),
+            ([0, 0, 43, 0, 0, 255], 64),
+        ] {
+            assert_eq!(set.get(logical_name).unwrap().class_id(), class_id);
+        }
+        let energy = set.get([1, 0, 1, 8, 0, 255]).unwrap();
+        assert_eq!(
+            energy.get_attribute(3),
+            Some(CosemData::Structure(vec![
+                CosemData::Integer(0),
+                CosemData::Enum(30)
+            ]))
+        );
+    }
+
+    #[cfg(feature = "profile-idis")]
+    #[test]
+    fn idis_package_2_profiles_capture_objects_of_the_set() {
+        let set = idis_package_2();
+        assert_captured_objects_present(&set);
+        for (logical_name, class_id) in [
+            ([1, 0, 99, 1, 0, 255], 7),
+            ([0, 0, 98, 1, 0, 255], 7),
+            ([0, 0, 99, 98, 0, 255], 7),
+            ([1, 0, 1, 8, 4, 255], 3),
+            ([0, 0, 96, 3, 10, 255], 70),
+            ([0, 0, 17, 0, 0, 255], 71),
+            ([0, 0, 25, 9, 0, 255], 40),
+        ] {
+            assert_eq!(set.get(logical_name).unwrap().class_id(), class_id);
+        }
+        let load_profile = set.get([1, 0, 99, 1, 0, 255]).unwrap();
+        assert_eq!(
+            load_profile.get_attribute(4),
+            Some(CosemData::DoubleLongUnsigned(900))
+        );
+    }
+
+    #[cfg(feature = "profile-sto")]
+    #[test]
+    fn sto_profiles_capture_objects_of_the_set() {
+        let set = sto_34_01_5_1_013();
+        assert_captured_objects_present(&set);
+        for (logical_name, class_id) in [
+            ([0, 0, 96, 1, 3, 255], 1),
+            ([1, 0, 94, 7, 0, 255], 7),
+            ([1, 0, 98, 2, 0, 255], 7),
+            ([0, 0, 99, 98, 7, 255], 7),
+            ([1, 0, 14, 7, 0, 255], 3),
+        ] {
+            assert_eq!(set.get(logical_name).unwrap().class_id(), class_id);
+        }
+
+        let server = ServerBuilder::new(1, DetachedTransport)
+            .object_set(set)
+            .build();
+        let public = listed(&server, [0, 0, 40, 0, 1, 255]);
+        assert!(public.contains(&[0, 0, 96, 1, 2, 255]));
+        assert!(!public.contains(&[1, 0, 99, 1, 0, 255]));
+    }
+}
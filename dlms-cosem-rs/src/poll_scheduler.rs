@@ -0,0 +1,165 @@
+#![cfg(feature = "std")]
+
+use crate::client::{Client, ClientError};
+use crate::cosem::CosemAttributeDescriptor;
+use crate::transport::Transport;
+use crate::xdlms::GetDataResult;
+use std::boxed::Box;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+// Attributes read in one get-request-with-list unless configured otherwise.
+const DEFAULT_MAX_BATCH_SIZE: usize = 10;
+
+// Last result read for a point and when it was read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedValue {
+    pub result: GetDataResult,
+    pub read_at: Instant,
+}
+
+// Handed to the change callback when a read returns something other than the
+// cached result; `previous` is `None` on the first read of a point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointChange {
+    pub descriptor: CosemAttributeDescriptor,
+    pub previous: Option<GetDataResult>,
+    pub current: CachedValue,
+}
+
+type ChangeCallback = Box<dyn FnMut(&PointChange) + Send>;
+
+struct PollPoint {
+    descriptor: CosemAttributeDescriptor,
+    interval: Duration,
+    due_at: Option<Instant>,
+    cached: Option<CachedValue>,
+}
+
+// Reads a set of attributes at their own intervals. The points due at a poll
+// are read together, `max_batch_size` at a time, through `Client::get_many`,
+// which uses get-request-with-list when the server negotiated
+// multiple-references.
+pub struct PollScheduler {
+    points: Vec<PollPoint>,
+    max_batch_size: usize,
+    on_change: Option<ChangeCallback>,
+}
+
+impl PollScheduler {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            on_change: None,
+        }
+    }
+
+    pub fn set_max_batch_size(&mut self, size: usize) {
+        self.max_batch_size = size.max(1);
+    }
+
+    // Registering a point again only changes its interval; a new point is due
+    // at the next poll.
+    pub fn register(&mut self, descriptor: CosemAttributeDescriptor, interval: Duration) {
+        match self
+            .points
+            .iter_mut()
+            .find(|point| point.descriptor == descriptor)
+        {
+            Some(point) => point.interval = interval,
+            None => self.points.push(PollPoint {
+                descriptor,
+                interval,
+                due_at: None,
+                cached: None,
+            }),
+        }
+    }
+
+    pub fn unregister(&mut self, descriptor: &CosemAttributeDescriptor) -> bool {
+        let before = self.points.len();
+        self.points.retain(|point| &point.descriptor != descriptor);
+        self.points.len() != before
+    }
+
+    pub fn on_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(&PointChange) + Send + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+    }
+
+    pub fn cached(&self, descriptor: &CosemAttributeDescriptor) -> Option<&CachedValue> {
+        self.points
+            .iter()
+            .find(|point| &point.descriptor == descriptor)
+            .and_then(|point| point.cached.as_ref())
+    }
+
+    // When the next point falls due; `None` without points, and a point never
+    // read is due right away.
+    pub fn next_due(&self, now: Instant) -> Option<Instant> {
+        self.points
+            .iter()
+            .map(|point| point.due_at.unwrap_or(now))
+            .min()
+    }
+
+    pub fn poll<T: Transport>(
+        &mut self,
+        client: &mut Client<T>,
+    ) -> Result<usize, ClientError<T::Error>> {
+        self.poll_at(client, Instant::now())
+    }
+
+    // Reads every point due at `now` and returns how many were read. A failed
+    // batch leaves its points due, so they are retried at the next poll.
+    pub fn poll_at<T: Transport>(
+        &mut self,
+        client: &mut Client<T>,
+        now: Instant,
+    ) -> Result<usize, ClientError<T::Error>> {
+        let due: Vec<usize> = (0..self.points.len())
+            .filter(|&index| self.points[index].due_at.is_none_or(|at| at <= now))
+            .collect();
+
+        for batch in due.chunks(self.max_batch_size) {
+            let descriptors = batch
+                .iter()
+                .map(|&index| self.points[index].descriptor.clone())
+                .collect();
+            let results = client.get_many(descriptors)?;
+            for (&index, (_, result)) in batch.iter().zip(results) {
+                self.store(index, result, now);
+            }
+        }
+        Ok(due.len())
+    }
+
+    fn store(&mut self, index: usize, result: GetDataResult, now: Instant) {
+        let point = &mut self.points[index];
+        point.due_at = Some(now + point.interval);
+        let current = CachedValue {
+            result,
+            read_at: now,
+        };
+        let previous = point.cached.replace(current.clone());
+        if previous.as_ref().map(|cached| &cached.result) == Some(&current.result) {
+            return;
+        }
+        if let Some(callback) = self.on_change.as_mut() {
+            callback(&PointChange {
+                descriptor: point.descriptor.clone(),
+                previous: previous.map(|cached| cached.result),
+                current,
+            });
+        }
+    }
+}
+
+impl Default for PollScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
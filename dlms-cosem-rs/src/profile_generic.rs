@@ -1,13 +1,153 @@
-use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::buffer_storage::{BufferStorage, MemoryBufferStorage};
+use crate::cosem::{
+    CosemClassId, CosemObjectAttributeId, CosemObjectInstanceId, CosemObjectMethodId,
+};
 use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
 };
-use crate::types::CosemData;
+use crate::types::{status_bit_string, status_flags, CosemData};
+use std::boxed::Box;
 use std::sync::Arc;
+use std::vec::Vec;
+
+// capture_object_definition ::= structure { class_id, logical_name, attribute_index, data_index }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureObjectDefinition {
+    pub class_id: CosemClassId,
+    pub logical_name: CosemObjectInstanceId,
+    pub attribute_index: CosemObjectAttributeId,
+    pub data_index: u16,
+}
+
+impl CaptureObjectDefinition {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::LongUnsigned(self.class_id),
+            CosemData::OctetString(self.logical_name.to_vec()),
+            CosemData::Integer(self.attribute_index),
+            CosemData::LongUnsigned(self.data_index),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::LongUnsigned(class_id), CosemData::OctetString(logical_name), CosemData::Integer(attribute_index), CosemData::LongUnsigned(data_index)] => {
+                Some(CaptureObjectDefinition {
+                    class_id: *class_id,
+                    logical_name: logical_name.as_slice().try_into().ok()?,
+                    attribute_index: *attribute_index,
+                    data_index: *data_index,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // Value stored in a buffer entry for this definition. Attribute index 0
+    // stands for the whole object: its logical name followed by every attribute.
+    pub fn capture(&self, object: &dyn CosemObject) -> Option<CosemData> {
+        if object.class_id() != self.class_id {
+            return None;
+        }
+        let value = if self.attribute_index == 0 {
+            let mut attributes = vec![CosemData::OctetString(self.logical_name.to_vec())];
+            attributes.extend(
+                (2..=CosemObjectAttributeId::MAX)
+                    .map_while(|attribute_id| object.get_attribute(attribute_id)),
+            );
+            CosemData::Structure(attributes)
+        } else if self.attribute_index == 1 {
+            CosemData::OctetString(self.logical_name.to_vec())
+        } else {
+            object.get_attribute(self.attribute_index)?
+        };
+
+        if self.data_index == 0 {
+            return Some(value);
+        }
+        match value {
+            CosemData::Array(elements) | CosemData::Structure(elements) => {
+                elements.into_iter().nth(self.data_index as usize - 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+status_flags! {
+    // Status column of load profiles, captured from a status object such as
+    // 0.0.96.10.1.255 and kept in extended registers (the AMR profile status).
+    // It is an eight bit bit-string.
+    pub struct ProfileStatus encoded by status_bit_string {
+        const CRITICAL_ERROR = 0x01, critical_error;
+        const CLOCK_INVALID = 0x02, clock_invalid;
+        const DATA_NOT_VALID = 0x04, data_not_valid;
+        const DAYLIGHT_SAVING = 0x08, daylight_saving;
+        const CLOCK_ADJUSTED = 0x20, clock_adjusted;
+        const POWER_DOWN = 0x80, power_down;
+    }
+}
+
+pub fn capture_object_definitions(
+    capture_objects: &CosemData,
+) -> Option<Vec<CaptureObjectDefinition>> {
+    match capture_objects {
+        CosemData::NullData => Some(Vec::new()),
+        CosemData::Array(elements) => elements
+            .iter()
+            .map(CaptureObjectDefinition::from_cosem_data)
+            .collect(),
+        _ => None,
+    }
+}
+
+// Appends an entry to the buffer of a profile generic object (class 7), dropping
+// the oldest entries once profile_entries is reached, and updates entries_in_use.
+pub fn append_buffer_entry(profile: &mut dyn CosemObject, entry: CosemData) -> Option<()> {
+    if profile.class_id() != 7 {
+        return None;
+    }
+    let limit = profile
+        .get_attribute(8)
+        .and_then(|profile_entries| profile_entries.as_u64())
+        .filter(|limit| *limit > 0)
+        .map(|limit| limit as usize);
+
+    let entries_in_use = if let Some(buffer) = profile.buffer_mut() {
+        buffer.append(entry)?;
+        if let Some(limit) = limit {
+            let excess = buffer.len().saturating_sub(limit);
+            if excess > 0 {
+                buffer.discard_oldest(excess);
+            }
+        }
+        buffer.len()
+    } else {
+        let mut entries = match profile.get_attribute(2)? {
+            CosemData::NullData => Vec::new(),
+            CosemData::Array(entries) => entries,
+            _ => return None,
+        };
+        entries.push(entry);
+        if let Some(limit) = limit {
+            let excess = entries.len().saturating_sub(limit);
+            entries.drain(..excess);
+        }
+        let entries_in_use = entries.len();
+        profile.set_attribute(2, CosemData::Array(entries))?;
+        entries_in_use
+    };
+
+    profile.set_attribute(7, CosemData::DoubleLongUnsigned(entries_in_use as u32))
+}
 
 #[derive(Debug)]
 pub struct ProfileGeneric {
-    buffer: CosemData,
+    buffer: Box<dyn BufferStorage>,
     capture_objects: CosemData,
     capture_period: CosemData,
     sort_method: CosemData,
@@ -19,13 +159,22 @@ pub struct ProfileGeneric {
 
 impl ProfileGeneric {
     pub fn new() -> Self {
+        Self::with_storage(Box::new(MemoryBufferStorage::new()))
+    }
+
+    // A storage reopened with entries sets entries_in_use.
+    pub fn with_storage(buffer: Box<dyn BufferStorage>) -> Self {
+        let entries_in_use = match buffer.len() {
+            0 => CosemData::NullData,
+            len => CosemData::DoubleLongUnsigned(len as u32),
+        };
         Self {
-            buffer: CosemData::NullData,
+            buffer,
             capture_objects: CosemData::NullData,
             capture_period: CosemData::NullData,
             sort_method: CosemData::NullData,
             sort_object: CosemData::NullData,
-            entries_in_use: CosemData::NullData,
+            entries_in_use,
             profile_entries: CosemData::NullData,
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
@@ -61,7 +210,9 @@ impl CosemObject for ProfileGeneric {
 
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
         match attribute_id {
-            2 => Some(self.buffer.clone()),
+            2 => Some(CosemData::Array(
+                self.buffer.entries(0..self.buffer.len()).collect(),
+            )),
             3 => Some(self.capture_objects.clone()),
             4 => Some(self.capture_period.clone()),
             5 => Some(self.sort_method.clone()),
@@ -79,7 +230,15 @@ impl CosemObject for ProfileGeneric {
     ) -> Option<()> {
         match attribute_id {
             2 => {
-                self.buffer = data;
+                let entries = match data {
+                    CosemData::NullData => Vec::new(),
+                    CosemData::Array(entries) => entries,
+                    _ => return None,
+                };
+                self.buffer.clear();
+                for entry in entries {
+                    self.buffer.append(entry)?;
+                }
                 Some(())
             }
             3 => {
@@ -110,17 +269,42 @@ impl CosemObject for ProfileGeneric {
         }
     }
 
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![
+            MethodAccessDescriptor::new(1, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(2, MethodAccessMode::Access),
+        ]
+    }
+
+    // Capture (method 2) reads the capture objects, which only the server
+    // holds: the object accepts it and the server appends the entry.
     fn invoke_method(
         &mut self,
-        _method_id: CosemObjectMethodId,
+        method_id: CosemObjectMethodId,
         _data: CosemData,
     ) -> Option<CosemData> {
-        None
+        match method_id {
+            1 => {
+                self.buffer.clear();
+                self.entries_in_use = CosemData::DoubleLongUnsigned(0);
+                Some(CosemData::NullData)
+            }
+            2 => Some(CosemData::NullData),
+            _ => None,
+        }
     }
 
     fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
         Some(Arc::clone(&self.callbacks))
     }
+
+    fn buffer(&self) -> Option<&dyn BufferStorage> {
+        Some(self.buffer.as_ref())
+    }
+
+    fn buffer_mut(&mut self) -> Option<&mut dyn BufferStorage> {
+        Some(self.buffer.as_mut())
+    }
 }
 
 #[cfg(all(test, feature = "std"))]
@@ -128,10 +312,91 @@ mod tests {
     extern crate std;
     use super::*;
 
+    #[test]
+    fn test_capture_attribute_zero_expands_whole_object() {
+        let mut register = crate::register::Register::new();
+        register
+            .set_attribute(2, CosemData::Long64Unsigned(42))
+            .unwrap();
+        let logical_name = [1, 0, 1, 8, 0, 255];
+
+        let whole = CaptureObjectDefinition {
+            class_id: 3,
+            logical_name,
+            attribute_index: 0,
+            data_index: 0,
+        };
+        assert_eq!(
+            CaptureObjectDefinition::from_cosem_data(&whole.to_cosem_data()),
+            Some(whole.clone())
+        );
+        assert_eq!(
+            whole.capture(&register),
+            Some(CosemData::Structure(vec![
+                CosemData::OctetString(logical_name.to_vec()),
+                CosemData::Long64Unsigned(42),
+                CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(255)]),
+            ]))
+        );
+
+        let scaler = CaptureObjectDefinition {
+            attribute_index: 3,
+            data_index: 1,
+            ..whole
+        };
+        assert_eq!(scaler.capture(&register), Some(CosemData::Integer(0)));
+    }
+
+    #[test]
+    fn test_append_buffer_entry_limits_entries() {
+        let mut profile = ProfileGeneric::new();
+        profile
+            .set_attribute(8, CosemData::DoubleLongUnsigned(2))
+            .unwrap();
+        for value in 0..3 {
+            append_buffer_entry(
+                &mut profile,
+                CosemData::Structure(vec![CosemData::Unsigned(value)]),
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            profile.get_attribute(2),
+            Some(CosemData::Array(vec![
+                CosemData::Structure(vec![CosemData::Unsigned(1)]),
+                CosemData::Structure(vec![CosemData::Unsigned(2)]),
+            ]))
+        );
+        assert_eq!(
+            profile.get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(2))
+        );
+    }
+
+    #[test]
+    fn test_reset_clears_buffer() {
+        let mut profile = ProfileGeneric::new();
+        append_buffer_entry(
+            &mut profile,
+            CosemData::Structure(vec![CosemData::Unsigned(1)]),
+        )
+        .unwrap();
+        assert_eq!(
+            profile.invoke_method(1, CosemData::Integer(0)),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(profile.get_attribute(2), Some(CosemData::Array(Vec::new())));
+        assert_eq!(
+            profile.get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        assert_eq!(profile.invoke_method(3, CosemData::Integer(0)), None);
+    }
+
     #[test]
     fn test_profile_generic_new() {
         let profile = ProfileGeneric::new();
-        assert_eq!(profile.get_attribute(2), Some(CosemData::NullData));
+        assert_eq!(profile.get_attribute(2), Some(CosemData::Array(Vec::new())));
         assert_eq!(profile.get_attribute(3), Some(CosemData::NullData));
         assert_eq!(profile.get_attribute(4), Some(CosemData::NullData));
         assert_eq!(profile.get_attribute(5), Some(CosemData::NullData));
@@ -0,0 +1,344 @@
+use crate::clock::{date_time_seconds, time_of_day_seconds};
+use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, AttributeSpec, CosemObject,
+    CosemObjectCallbackHandlers, MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::profile_generic::{capture_object_definitions, CaptureObjectDefinition};
+use crate::types::{CosemData, DataType};
+use std::sync::Arc;
+use std::vec::Vec;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Message types of send_destination_and_method: A-XDR encoded xDLMS APDUs,
+// and the manufacturer-specific type of profiles sending them with a deflated
+// data-notification body, see `compression`.
+pub const PUSH_MESSAGE_AXDR: u8 = 0;
+pub const PUSH_MESSAGE_COMPRESSED_AXDR: u8 = 128;
+
+// Push setup (class 40). The server sends the values of push_object_list as a
+// data-notification once a push is triggered; see `Server::trigger_push`.
+#[derive(Debug)]
+pub struct PushSetup {
+    push_object_list: CosemData,
+    send_destination_and_method: CosemData,
+    communication_window: CosemData,
+    randomisation_start_interval: CosemData,
+    number_of_retries: CosemData,
+    repetition_delay: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl PushSetup {
+    pub fn new() -> Self {
+        Self {
+            push_object_list: CosemData::Array(Vec::new()),
+            send_destination_and_method: CosemData::NullData,
+            communication_window: CosemData::Array(Vec::new()),
+            randomisation_start_interval: CosemData::LongUnsigned(0),
+            number_of_retries: CosemData::Unsigned(0),
+            repetition_delay: CosemData::LongUnsigned(0),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+}
+
+impl Default for PushSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for PushSetup {
+    fn class_id(&self) -> u16 {
+        40
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        (2..=7)
+            .map(|attribute_id| {
+                AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::ReadWrite)
+            })
+            .collect()
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+    }
+
+    fn attribute_specs(&self) -> Vec<AttributeSpec> {
+        vec![
+            AttributeSpec::new(2, DataType::Array),
+            AttributeSpec::new(3, DataType::Structure).with_length(3, 3),
+            AttributeSpec::new(4, DataType::Array),
+            AttributeSpec::new(5, DataType::LongUnsigned),
+            AttributeSpec::new(6, DataType::Unsigned),
+            AttributeSpec::new(7, DataType::LongUnsigned),
+        ]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.push_object_list.clone()),
+            3 => Some(self.send_destination_and_method.clone()),
+            4 => Some(self.communication_window.clone()),
+            5 => Some(self.randomisation_start_interval.clone()),
+            6 => Some(self.number_of_retries.clone()),
+            7 => Some(self.repetition_delay.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                capture_object_definitions(&data)?;
+                self.push_object_list = data;
+            }
+            3 => self.send_destination_and_method = data,
+            4 => {
+                communication_windows(&data)?;
+                self.communication_window = data;
+            }
+            5 => self.randomisation_start_interval = data,
+            6 => self.number_of_retries = data,
+            7 => self.repetition_delay = data,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        match method_id {
+            1 => Some(CosemData::NullData),
+            _ => None,
+        }
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+// window_element ::= structure { start_time: date-time, end_time: date-time }
+// Windows whose date is left unspecified repeat every day at the same times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommunicationWindow {
+    Once { start: u64, end: u64 },
+    Daily { start: u64, end: u64 },
+}
+
+impl CommunicationWindow {
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        let [CosemData::DateTime(start) | CosemData::OctetString(start), CosemData::DateTime(end) | CosemData::OctetString(end)] =
+            fields.as_slice()
+        else {
+            return None;
+        };
+        match (date_time_seconds(start), date_time_seconds(end)) {
+            (Some(start), Some(end)) => Some(CommunicationWindow::Once { start, end }),
+            _ => Some(CommunicationWindow::Daily {
+                start: time_of_day_seconds(start)?,
+                end: time_of_day_seconds(end)?,
+            }),
+        }
+    }
+
+    // Earliest time at or after `now` inside the window.
+    fn next_opening(&self, now: u64) -> Option<u64> {
+        match *self {
+            CommunicationWindow::Once { start, end } => (now < end).then_some(start.max(now)),
+            CommunicationWindow::Daily { start, end } => {
+                let midnight = now - now % SECONDS_PER_DAY;
+                // Windows spanning midnight end on the following day.
+                let length = (end + SECONDS_PER_DAY - start) % SECONDS_PER_DAY;
+                [midnight.checked_sub(SECONDS_PER_DAY), Some(midnight)]
+                    .into_iter()
+                    .flatten()
+                    .chain([midnight + SECONDS_PER_DAY])
+                    .map(|day| (day + start, day + start + length))
+                    .find(|(_, end)| now < *end)
+                    .map(|(start, _)| start.max(now))
+            }
+        }
+    }
+}
+
+pub fn communication_windows(data: &CosemData) -> Option<Vec<CommunicationWindow>> {
+    match data {
+        CosemData::NullData => Some(Vec::new()),
+        CosemData::Array(elements) => elements
+            .iter()
+            .map(CommunicationWindow::from_cosem_data)
+            .collect(),
+        _ => None,
+    }
+}
+
+// Earliest time at or after `now` a push may be sent; pushes are allowed at
+// any time when no communication window is configured.
+pub fn next_push_opportunity(windows: &[CommunicationWindow], now: u64) -> Option<u64> {
+    if windows.is_empty() {
+        return Some(now);
+    }
+    windows
+        .iter()
+        .filter_map(|window| window.next_opening(now))
+        .min()
+}
+
+pub fn in_communication_window(windows: &[CommunicationWindow], now: u64) -> bool {
+    next_push_opportunity(windows, now) == Some(now)
+}
+
+// send_destination_and_method ::= structure { transport_service: enum,
+// destination: octet-string, message: enum }; A-XDR while it is not set.
+pub fn push_message_type(data: &CosemData) -> Option<u8> {
+    match data {
+        CosemData::NullData => Some(PUSH_MESSAGE_AXDR),
+        CosemData::Structure(fields) => match fields.as_slice() {
+            [_, _, CosemData::Enum(message)] => Some(*message),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// The push setup attributes the scheduler works with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushSchedule {
+    pub push_objects: Vec<CaptureObjectDefinition>,
+    pub message: u8,
+    pub windows: Vec<CommunicationWindow>,
+    pub randomisation_start_interval: u64,
+    pub number_of_retries: u8,
+    pub repetition_delay: u64,
+}
+
+impl PushSchedule {
+    pub fn from_push_setup(setup: &dyn CosemObject) -> Option<Self> {
+        if setup.class_id() != 40 {
+            return None;
+        }
+        Some(PushSchedule {
+            push_objects: capture_object_definitions(&setup.get_attribute(2)?)?,
+            message: push_message_type(&setup.get_attribute(3)?)?,
+            windows: communication_windows(&setup.get_attribute(4)?)?,
+            randomisation_start_interval: setup.get_attribute(5)?.as_u64()?,
+            number_of_retries: setup.get_attribute(6)?.as_u64()?.try_into().ok()?,
+            repetition_delay: setup.get_attribute(7)?.as_u64()?,
+        })
+    }
+
+    // First attempt of a push triggered at `now`: the next window opening
+    // delayed by a random share of randomisation_start_interval, so that many
+    // meters do not answer the same trigger at once.
+    pub fn first_attempt(&self, now: u64, random: u64) -> Option<u64> {
+        let opening = next_push_opportunity(&self.windows, now)?;
+        Some(opening + random % (self.randomisation_start_interval + 1))
+    }
+}
+
+// A push waiting for its next attempt; `attempts` counts the failed ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingPush {
+    pub due: u64,
+    pub attempts: u8,
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    fn date_time(hour: u8, minute: u8) -> CosemData {
+        CosemData::DateTime(vec![0x07, 0xEA, 10, 17, 6, hour, minute, 0, 0, 0x80, 0, 0])
+    }
+
+    fn daily(hour: u8, minute: u8) -> CosemData {
+        CosemData::DateTime(vec![
+            0xFF, 0xFF, 0xFF, 0xFF, 0xFF, hour, minute, 0, 0xFF, 0x80, 0, 0,
+        ])
+    }
+
+    #[test]
+    fn test_push_setup_rejects_malformed_lists() {
+        let mut setup = PushSetup::new();
+        assert_eq!(setup.set_attribute(2, CosemData::Unsigned(1)), None);
+        assert_eq!(
+            setup.set_attribute(4, CosemData::Array(vec![CosemData::NullData])),
+            None
+        );
+        let window = CosemData::Structure(vec![date_time(1, 0), date_time(2, 0)]);
+        assert_eq!(
+            setup.set_attribute(4, CosemData::Array(vec![window.clone()])),
+            Some(())
+        );
+        assert_eq!(setup.get_attribute(4), Some(CosemData::Array(vec![window])));
+        assert_eq!(
+            setup.invoke_method(1, CosemData::NullData),
+            Some(CosemData::NullData)
+        );
+    }
+
+    #[test]
+    fn test_next_push_opportunity_honours_windows() {
+        assert_eq!(next_push_opportunity(&[], 100), Some(100));
+
+        let once = CommunicationWindow::from_cosem_data(&CosemData::Structure(vec![
+            date_time(1, 0),
+            date_time(2, 0),
+        ]))
+        .unwrap();
+        let CommunicationWindow::Once { start, end } = once else {
+            panic!("expected a dated window");
+        };
+        assert_eq!(end - start, 3600);
+        assert_eq!(next_push_opportunity(&[once], start - 10), Some(start));
+        assert_eq!(next_push_opportunity(&[once], start + 10), Some(start + 10));
+        assert_eq!(next_push_opportunity(&[once], end), None);
+
+        let nightly = CommunicationWindow::from_cosem_data(&CosemData::Structure(vec![
+            daily(23, 0),
+            daily(1, 0),
+        ]))
+        .unwrap();
+        let midnight = 20 * SECONDS_PER_DAY;
+        assert_eq!(
+            next_push_opportunity(&[nightly], midnight + 600),
+            Some(midnight + 600)
+        );
+        assert!(in_communication_window(&[nightly], midnight + 600));
+        assert!(!in_communication_window(&[nightly], midnight + 7200));
+        assert_eq!(
+            next_push_opportunity(&[nightly], midnight + 7200),
+            Some(midnight + 23 * 3600)
+        );
+    }
+
+    #[test]
+    fn test_first_attempt_is_randomised_within_interval() {
+        let mut setup = PushSetup::new();
+        setup.set_attribute(5, CosemData::LongUnsigned(30)).unwrap();
+        let schedule = PushSchedule::from_push_setup(&setup).unwrap();
+        assert_eq!(schedule.first_attempt(1000, 0), Some(1000));
+        assert_eq!(schedule.first_attempt(1000, 30), Some(1030));
+        assert_eq!(schedule.first_attempt(1000, 31), Some(1000));
+    }
+}
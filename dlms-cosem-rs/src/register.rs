@@ -3,7 +3,10 @@ use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
     MethodAccessDescriptor, MethodAccessMode,
 };
+use crate::scaled_value::ScaledValue;
 use crate::types::CosemData;
+use crate::xdlms::DataAccessResult;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -25,6 +28,36 @@ impl Register {
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
+
+    pub fn value_i64(&self) -> Option<i64> {
+        self.value.as_i64()
+    }
+
+    pub fn value_u64(&self) -> Option<u64> {
+        self.value.as_u64()
+    }
+
+    pub fn accumulate(&mut self, delta: i64) -> Option<()> {
+        self.value = self.value.checked_add(delta)?;
+        Some(())
+    }
+
+    pub fn accumulate_u64(&mut self, delta: u64) -> Option<()> {
+        self.value = self.value.checked_add_u64(delta)?;
+        Some(())
+    }
+
+    pub fn scaled_value(&self) -> Option<ScaledValue> {
+        ScaledValue::from_cosem_data(&self.value, &self.scaler_unit)
+    }
+
+    // Adds a quantity measured in any scaler or convertible unit; `None`, with
+    // the value unchanged, if it cannot be expressed exactly in the register's.
+    pub fn accumulate_scaled(&mut self, delta: &ScaledValue) -> Option<()> {
+        let current = self.scaled_value()?;
+        let delta = delta.convert_to(current.unit)?.rescale(current.scaler)?;
+        self.accumulate(delta.raw)
+    }
 }
 
 impl Default for Register {
@@ -45,6 +78,14 @@ impl CosemObject for Register {
         ]
     }
 
+    fn attribute_access(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Option<AttributeAccessDescriptor> {
+        matches!(attribute_id, 2 | 3)
+            .then(|| AttributeAccessDescriptor::new(attribute_id, AttributeAccessMode::ReadWrite))
+    }
+
     fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
         vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
     }
@@ -57,6 +98,17 @@ impl CosemObject for Register {
         }
     }
 
+    fn read_attribute_ref(
+        &self,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Result<Cow<'_, CosemData>, DataAccessResult> {
+        match attribute_id {
+            2 => Ok(Cow::Borrowed(&self.value)),
+            3 => Ok(Cow::Borrowed(&self.scaler_unit)),
+            _ => Err(DataAccessResult::ObjectUnavailable),
+        }
+    }
+
     fn set_attribute(
         &mut self,
         attribute_id: CosemObjectAttributeId,
@@ -92,9 +144,10 @@ impl CosemObject for Register {
 }
 
 impl Register {
+    // Clears the value and returns the previous one.
     fn reset(&mut self) -> Option<CosemData> {
-        self.value = CosemData::Unsigned(0);
-        Some(CosemData::NullData)
+        let previous = self.value.zero_like();
+        Some(std::mem::replace(&mut self.value, previous))
     }
 }
 
@@ -128,7 +181,69 @@ mod tests {
         let mut register = Register::new();
         register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
         assert_eq!(register.get_attribute(2), Some(CosemData::Unsigned(10)));
-        register.reset();
+        assert_eq!(
+            register.invoke_method(1, CosemData::Integer(0)),
+            Some(CosemData::Unsigned(10))
+        );
         assert_eq!(register.get_attribute(2), Some(CosemData::Unsigned(0)));
     }
+
+    #[test]
+    fn test_register_long64_accumulation() {
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::Long64Unsigned(u64::MAX - 10))
+            .unwrap();
+        register.accumulate(10).unwrap();
+        assert_eq!(register.value_u64(), Some(u64::MAX));
+        assert_eq!(register.value_i64(), None);
+        assert!(register.accumulate(1).is_none());
+        assert_eq!(register.value_u64(), Some(u64::MAX));
+
+        register.reset();
+        assert_eq!(
+            register.get_attribute(2),
+            Some(CosemData::Long64Unsigned(0))
+        );
+
+        // Increments beyond i64::MAX take the unsigned helper.
+        register.accumulate_u64(u64::MAX).unwrap();
+        assert_eq!(register.value_u64(), Some(u64::MAX));
+        assert!(register.accumulate_u64(1).is_none());
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(0))
+            .unwrap();
+        assert!(register.accumulate_u64(u64::from(u32::MAX) + 1).is_none());
+    }
+
+    #[test]
+    fn test_register_scaled_accumulation() {
+        use crate::scaled_value::Unit;
+
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(1000))
+            .unwrap();
+        register
+            .set_attribute(
+                3,
+                CosemData::Structure(vec![CosemData::Integer(-1), CosemData::Enum(30)]),
+            )
+            .unwrap();
+        assert_eq!(
+            register.scaled_value(),
+            Some(ScaledValue::new(1000, -1, Unit::WATT_HOUR))
+        );
+
+        register
+            .accumulate_scaled(&ScaledValue::new(18, 3, Unit::JOULE))
+            .unwrap();
+        assert_eq!(
+            register.get_attribute(2),
+            Some(CosemData::DoubleLongUnsigned(1050))
+        );
+        assert!(register
+            .accumulate_scaled(&ScaledValue::new(1, 0, Unit::VOLT))
+            .is_none());
+    }
 }
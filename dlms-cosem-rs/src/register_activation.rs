@@ -0,0 +1,314 @@
+use crate::cosem::{
+    CosemClassId, CosemObjectAttributeId, CosemObjectInstanceId, CosemObjectMethodId,
+};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// object_definition ::= structure { class_id: long-unsigned, logical_name: octet-string }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterAssignment {
+    pub class_id: CosemClassId,
+    pub logical_name: CosemObjectInstanceId,
+}
+
+impl RegisterAssignment {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::LongUnsigned(self.class_id),
+            CosemData::OctetString(self.logical_name.to_vec()),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::LongUnsigned(class_id), CosemData::OctetString(logical_name)] => {
+                Some(Self {
+                    class_id: *class_id,
+                    logical_name: logical_name.as_slice().try_into().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// register_act_mask ::= structure { mask_name: octet-string, index_list: array of unsigned }
+// The indices refer to register_assignment, starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterActivationMask {
+    pub name: Vec<u8>,
+    pub index_list: Vec<u8>,
+}
+
+impl RegisterActivationMask {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::OctetString(self.name.clone()),
+            CosemData::Array(
+                self.index_list
+                    .iter()
+                    .map(|index| CosemData::Unsigned(*index))
+                    .collect(),
+            ),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        let [CosemData::OctetString(name), CosemData::Array(indices)] = fields.as_slice() else {
+            return None;
+        };
+        let index_list = indices
+            .iter()
+            .map(|index| match index {
+                CosemData::Unsigned(index) => Some(*index),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            name: name.clone(),
+            index_list,
+        })
+    }
+}
+
+/// Register Activation (Class ID 6)
+#[derive(Debug)]
+pub struct RegisterActivation {
+    // Attribute 2: The registers the masks select from.
+    register_assignment: Vec<RegisterAssignment>,
+    // Attribute 3: Named sets of registers, e.g. one per tariff rate.
+    mask_list: Vec<RegisterActivationMask>,
+    // Attribute 4: Name of the mask whose registers are currently enabled.
+    active_mask: Vec<u8>,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl RegisterActivation {
+    pub fn new() -> Self {
+        Self {
+            register_assignment: Vec::new(),
+            mask_list: Vec::new(),
+            active_mask: Vec::new(),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn add_register(&mut self, register: RegisterAssignment) {
+        self.register_assignment.push(register);
+    }
+
+    // Replaces a mask of the same name. `None` if an index does not refer to
+    // an assigned register.
+    pub fn add_mask(&mut self, mask: RegisterActivationMask) -> Option<()> {
+        let assigned = self.register_assignment.len();
+        if mask
+            .index_list
+            .iter()
+            .any(|&index| index == 0 || index as usize > assigned)
+        {
+            return None;
+        }
+        match self
+            .mask_list
+            .iter_mut()
+            .find(|existing| existing.name == mask.name)
+        {
+            Some(existing) => *existing = mask,
+            None => self.mask_list.push(mask),
+        }
+        Some(())
+    }
+
+    pub fn delete_mask(&mut self, name: &[u8]) -> Option<()> {
+        let position = self.mask_list.iter().position(|mask| mask.name == name)?;
+        self.mask_list.remove(position);
+        if self.active_mask == name {
+            self.active_mask.clear();
+        }
+        Some(())
+    }
+
+    pub fn active_mask(&self) -> &[u8] {
+        &self.active_mask
+    }
+
+    // Only masks of the mask list can be activated.
+    pub fn set_active_mask(&mut self, name: Vec<u8>) -> Option<()> {
+        if !self.mask_list.iter().any(|mask| mask.name == name) {
+            return None;
+        }
+        self.active_mask = name;
+        Some(())
+    }
+
+    // Logical names of the registers enabled by the active mask.
+    pub fn active_registers(&self) -> Vec<CosemObjectInstanceId> {
+        self.mask_list
+            .iter()
+            .find(|mask| mask.name == self.active_mask)
+            .map(|mask| {
+                mask.index_list
+                    .iter()
+                    .filter_map(|&index| self.register_assignment.get(index as usize - 1))
+                    .map(|register| register.logical_name)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Default for RegisterActivation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for RegisterActivation {
+    fn class_id(&self) -> u16 {
+        6
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        vec![
+            AttributeAccessDescriptor::new(2, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(3, AttributeAccessMode::Read),
+            AttributeAccessDescriptor::new(4, AttributeAccessMode::ReadWrite),
+        ]
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![
+            MethodAccessDescriptor::new(1, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(2, MethodAccessMode::Access),
+            MethodAccessDescriptor::new(3, MethodAccessMode::Access),
+        ]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(CosemData::Array(
+                self.register_assignment
+                    .iter()
+                    .map(RegisterAssignment::to_cosem_data)
+                    .collect(),
+            )),
+            3 => Some(CosemData::Array(
+                self.mask_list
+                    .iter()
+                    .map(RegisterActivationMask::to_cosem_data)
+                    .collect(),
+            )),
+            4 => Some(CosemData::OctetString(self.active_mask.clone())),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match (attribute_id, data) {
+            (4, CosemData::OctetString(name)) => self.set_active_mask(name),
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
+    ) -> Option<CosemData> {
+        match (method_id, data) {
+            (1, data) => self.add_register(RegisterAssignment::from_cosem_data(&data)?),
+            (2, data) => self.add_mask(RegisterActivationMask::from_cosem_data(&data)?)?,
+            (3, CosemData::OctetString(name)) => self.delete_mask(&name)?,
+            _ => return None,
+        }
+        Some(CosemData::NullData)
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_active_mask_selects_registers() {
+        let mut activation = RegisterActivation::new();
+        for logical_name in [[1, 0, 1, 8, 1, 255], [1, 0, 1, 8, 2, 255]] {
+            let register = RegisterAssignment {
+                class_id: 3,
+                logical_name,
+            };
+            assert_eq!(
+                activation.invoke_method(1, register.to_cosem_data()),
+                Some(CosemData::NullData)
+            );
+        }
+        let day = RegisterActivationMask {
+            name: b"T1".to_vec(),
+            index_list: vec![1],
+        };
+        activation.add_mask(day.clone()).unwrap();
+        activation
+            .add_mask(RegisterActivationMask {
+                name: b"T2".to_vec(),
+                index_list: vec![2],
+            })
+            .unwrap();
+        assert!(activation
+            .add_mask(RegisterActivationMask {
+                name: b"T3".to_vec(),
+                index_list: vec![3],
+            })
+            .is_none());
+
+        assert_eq!(
+            activation.set_attribute(4, CosemData::OctetString(b"T3".to_vec())),
+            None
+        );
+        activation
+            .set_attribute(4, CosemData::OctetString(b"T1".to_vec()))
+            .unwrap();
+        assert_eq!(activation.active_registers(), vec![[1, 0, 1, 8, 1, 255]]);
+        assert_eq!(
+            activation.get_attribute(3),
+            Some(CosemData::Array(vec![
+                day.to_cosem_data(),
+                RegisterActivationMask {
+                    name: b"T2".to_vec(),
+                    index_list: vec![2],
+                }
+                .to_cosem_data(),
+            ]))
+        );
+
+        assert_eq!(
+            activation.invoke_method(3, CosemData::OctetString(b"T1".to_vec())),
+            Some(CosemData::NullData)
+        );
+        assert!(activation.active_mask().is_empty());
+        assert!(activation.active_registers().is_empty());
+    }
+}
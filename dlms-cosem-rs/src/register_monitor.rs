@@ -0,0 +1,300 @@
+use crate::cosem::{
+    CosemClassId, CosemObjectAttributeId, CosemObjectInstanceId, CosemObjectMethodId,
+};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// value_definition ::= structure { class_id, logical_name, attribute_index }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitoredValue {
+    pub class_id: CosemClassId,
+    pub logical_name: CosemObjectInstanceId,
+    pub attribute_index: CosemObjectAttributeId,
+}
+
+impl MonitoredValue {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::LongUnsigned(self.class_id),
+            CosemData::OctetString(self.logical_name.to_vec()),
+            CosemData::Integer(self.attribute_index),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::LongUnsigned(class_id), CosemData::OctetString(logical_name), CosemData::Integer(attribute_index)] => {
+                Some(MonitoredValue {
+                    class_id: *class_id,
+                    logical_name: logical_name.as_slice().try_into().ok()?,
+                    attribute_index: *attribute_index,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// action_item ::= structure { script_logical_name, script_selector }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptReference {
+    pub script_logical_name: CosemObjectInstanceId,
+    pub script_selector: u16,
+}
+
+impl ScriptReference {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::OctetString(self.script_logical_name.to_vec()),
+            CosemData::LongUnsigned(self.script_selector),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::OctetString(logical_name), CosemData::LongUnsigned(script_selector)] => {
+                Some(ScriptReference {
+                    script_logical_name: logical_name.as_slice().try_into().ok()?,
+                    script_selector: *script_selector,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// action_set ::= structure { action_up, action_down }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorActionSet {
+    pub action_up: ScriptReference,
+    pub action_down: ScriptReference,
+}
+
+impl MonitorActionSet {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            self.action_up.to_cosem_data(),
+            self.action_down.to_cosem_data(),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [action_up, action_down] => Some(MonitorActionSet {
+                action_up: ScriptReference::from_cosem_data(action_up)?,
+                action_down: ScriptReference::from_cosem_data(action_down)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn numeric_value(data: &CosemData) -> Option<i128> {
+    data.as_i64()
+        .map(i128::from)
+        .or_else(|| data.as_u64().map(i128::from))
+}
+
+// Scripts to run for a change of the monitored value of a register monitor
+// (class 21) from `previous` to `current`. A threshold is crossed upwards when
+// the value moves from below it to at or above it, and downwards the other way.
+pub fn triggered_scripts(
+    monitor: &dyn CosemObject,
+    previous: &CosemData,
+    current: &CosemData,
+) -> Option<Vec<ScriptReference>> {
+    if monitor.class_id() != 21 {
+        return None;
+    }
+    let (CosemData::Array(thresholds), CosemData::Array(actions)) =
+        (monitor.get_attribute(2)?, monitor.get_attribute(4)?)
+    else {
+        return None;
+    };
+    let previous = numeric_value(previous)?;
+    let current = numeric_value(current)?;
+
+    let mut scripts = Vec::new();
+    for (threshold, action_set) in thresholds.iter().zip(actions.iter()) {
+        let threshold = numeric_value(threshold)?;
+        let action_set = MonitorActionSet::from_cosem_data(action_set)?;
+        if previous < threshold && current >= threshold {
+            scripts.push(action_set.action_up);
+        } else if previous >= threshold && current < threshold {
+            scripts.push(action_set.action_down);
+        }
+    }
+    Some(scripts)
+}
+
+#[derive(Debug)]
+pub struct RegisterMonitor {
+    thresholds: CosemData,
+    monitored_value: CosemData,
+    actions: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl RegisterMonitor {
+    pub fn new() -> Self {
+        Self {
+            thresholds: CosemData::Array(Vec::new()),
+            monitored_value: CosemData::NullData,
+            actions: CosemData::Array(Vec::new()),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+}
+
+impl Default for RegisterMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for RegisterMonitor {
+    fn class_id(&self) -> u16 {
+        21
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        vec![
+            AttributeAccessDescriptor::new(2, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(3, AttributeAccessMode::ReadWrite),
+            AttributeAccessDescriptor::new(4, AttributeAccessMode::ReadWrite),
+        ]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.thresholds.clone()),
+            3 => Some(self.monitored_value.clone()),
+            4 => Some(self.actions.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.thresholds = data;
+                Some(())
+            }
+            3 => {
+                self.monitored_value = data;
+                Some(())
+            }
+            4 => {
+                self.actions = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: CosemObjectMethodId,
+        _data: CosemData,
+    ) -> Option<CosemData> {
+        None
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+
+    fn monitored_value(&self) -> Option<MonitoredValue> {
+        MonitoredValue::from_cosem_data(&self.monitored_value)
+    }
+
+    fn evaluate_monitored_value(
+        &mut self,
+        previous: &CosemData,
+        current: &CosemData,
+        _timestamp: u64,
+    ) -> Vec<ScriptReference> {
+        triggered_scripts(self, previous, current).unwrap_or_default()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    const SCRIPT_TABLE_LN: [u8; 6] = [0, 0, 10, 0, 106, 255];
+
+    fn action_set(up: u16, down: u16) -> CosemData {
+        MonitorActionSet {
+            action_up: ScriptReference {
+                script_logical_name: SCRIPT_TABLE_LN,
+                script_selector: up,
+            },
+            action_down: ScriptReference {
+                script_logical_name: SCRIPT_TABLE_LN,
+                script_selector: down,
+            },
+        }
+        .to_cosem_data()
+    }
+
+    #[test]
+    fn test_register_monitor_threshold_crossings() {
+        let mut monitor = RegisterMonitor::new();
+        monitor
+            .set_attribute(
+                2,
+                CosemData::Array(vec![
+                    CosemData::DoubleLongUnsigned(100),
+                    CosemData::DoubleLongUnsigned(200),
+                ]),
+            )
+            .unwrap();
+        monitor
+            .set_attribute(
+                4,
+                CosemData::Array(vec![action_set(1, 2), action_set(3, 4)]),
+            )
+            .unwrap();
+
+        let selectors = |previous: u32, current: u32| {
+            triggered_scripts(
+                &monitor,
+                &CosemData::DoubleLongUnsigned(previous),
+                &CosemData::DoubleLongUnsigned(current),
+            )
+            .unwrap()
+            .iter()
+            .map(|script| script.script_selector)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(selectors(50, 150), vec![1]);
+        assert_eq!(selectors(150, 250), vec![3]);
+        assert_eq!(selectors(250, 50), vec![2, 4]);
+        assert_eq!(selectors(120, 180), Vec::<u16>::new());
+        assert_eq!(selectors(99, 100), vec![1]);
+    }
+}
@@ -1,28 +1,68 @@
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
 use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
 };
 use crate::types::CosemData;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::vec::Vec;
 
-#[derive(Debug)]
+// asslist_element ::= structure { SAP: long-unsigned, logical_device_name: octet-string }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SapAssignmentEntry {
+    pub sap: u16,
+    pub logical_device_name: Vec<u8>,
+}
+
+impl SapAssignmentEntry {
+    pub fn new(sap: u16, logical_device_name: Vec<u8>) -> Self {
+        Self {
+            sap,
+            logical_device_name,
+        }
+    }
+
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::LongUnsigned(self.sap),
+            CosemData::OctetString(self.logical_device_name.clone()),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::LongUnsigned(sap), CosemData::OctetString(name)] => {
+                Some(Self::new(*sap, name.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// SAP Assignment (Class ID 17)
+#[derive(Debug, Clone)]
 pub struct SapAssignment {
-    pub logical_device_name_list: Vec<u8>,
+    // Attribute 2: The logical devices of the physical device and their SAPs.
+    // Shared with whatever hosts the logical devices, so both stay in sync.
+    sap_assignment_list: Arc<Mutex<Vec<SapAssignmentEntry>>>,
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
 impl SapAssignment {
     pub fn new() -> Self {
-        Self {
-            logical_device_name_list: Vec::new(),
-            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
-        }
+        Self::shared(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    pub fn with_entries(entries: Vec<SapAssignmentEntry>) -> Self {
+        Self::shared(Arc::new(Mutex::new(entries)))
     }
 
-    pub fn with_logical_device_names(names: Vec<u8>) -> Self {
+    pub fn shared(sap_assignment_list: Arc<Mutex<Vec<SapAssignmentEntry>>>) -> Self {
         Self {
-            logical_device_name_list: names,
+            sap_assignment_list,
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
@@ -30,6 +70,37 @@ impl SapAssignment {
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
+
+    pub fn list_handle(&self) -> Arc<Mutex<Vec<SapAssignmentEntry>>> {
+        Arc::clone(&self.sap_assignment_list)
+    }
+
+    pub fn entries(&self) -> Vec<SapAssignmentEntry> {
+        self.sap_assignment_list
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+
+    // Assigns the logical device to the SAP, taking over the SAP and any SAP
+    // the device had before. SAP 0 disconnects the device.
+    pub fn connect_logical_device(&mut self, entry: SapAssignmentEntry) -> Option<()> {
+        let mut entries = self.sap_assignment_list.lock().ok()?;
+        entries.retain(|existing| {
+            existing.sap != entry.sap && existing.logical_device_name != entry.logical_device_name
+        });
+        if entry.sap != 0 {
+            entries.push(entry);
+            entries.sort_by_key(|existing| existing.sap);
+        }
+        Some(())
+    }
+
+    fn invoke_connect_logical_device(&mut self, data: CosemData) -> Option<CosemData> {
+        let entry = SapAssignmentEntry::from_cosem_data(&data)?;
+        self.connect_logical_device(entry)?;
+        Some(CosemData::NullData)
+    }
 }
 
 impl Default for SapAssignment {
@@ -40,18 +111,28 @@ impl Default for SapAssignment {
 
 impl CosemObject for SapAssignment {
     fn class_id(&self) -> u16 {
-        21
+        17
     }
 
     fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
         vec![AttributeAccessDescriptor::new(2, AttributeAccessMode::Read)]
     }
 
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+    }
+
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
         match attribute_id {
-            2 => Some(CosemData::OctetString(
-                self.logical_device_name_list.clone(),
-            )),
+            2 => {
+                let entries = self.sap_assignment_list.lock().ok()?;
+                Some(CosemData::Array(
+                    entries
+                        .iter()
+                        .map(SapAssignmentEntry::to_cosem_data)
+                        .collect(),
+                ))
+            }
             _ => None,
         }
     }
@@ -66,13 +147,65 @@ impl CosemObject for SapAssignment {
 
     fn invoke_method(
         &mut self,
-        _method_id: CosemObjectMethodId,
-        _data: CosemData,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
     ) -> Option<CosemData> {
-        None
+        match method_id {
+            1 => self.invoke_connect_logical_device(data),
+            _ => None,
+        }
     }
 
     fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
         Some(Arc::clone(&self.callbacks))
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn connect_logical_device_updates_shared_list() {
+        let mut sap_assignment =
+            SapAssignment::with_entries(vec![SapAssignmentEntry::new(1, b"MGMT".to_vec())]);
+        let handle = sap_assignment.list_handle();
+
+        let meter = SapAssignmentEntry::new(16, b"METER".to_vec());
+        assert_eq!(
+            sap_assignment.invoke_method(1, meter.to_cosem_data()),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(
+            sap_assignment.get_attribute(2),
+            Some(CosemData::Array(vec![
+                SapAssignmentEntry::new(1, b"MGMT".to_vec()).to_cosem_data(),
+                meter.to_cosem_data(),
+            ]))
+        );
+
+        // Moving a device to another SAP replaces its old assignment.
+        sap_assignment
+            .connect_logical_device(SapAssignmentEntry::new(17, b"METER".to_vec()))
+            .unwrap();
+        handle
+            .lock()
+            .unwrap()
+            .push(SapAssignmentEntry::new(32, b"GAS".to_vec()));
+        assert_eq!(
+            sap_assignment
+                .entries()
+                .iter()
+                .map(|entry| entry.sap)
+                .collect::<Vec<_>>(),
+            vec![1, 17, 32]
+        );
+
+        sap_assignment
+            .connect_logical_device(SapAssignmentEntry::new(0, b"METER".to_vec()))
+            .unwrap();
+        assert_eq!(sap_assignment.entries().len(), 2);
+        assert_eq!(sap_assignment.invoke_method(1, CosemData::NullData), None);
+    }
+}
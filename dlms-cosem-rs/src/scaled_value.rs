@@ -0,0 +1,685 @@
+use crate::types::CosemData;
+use core::fmt;
+use core::ops::Neg;
+use std::string::ToString;
+use std::vec;
+
+// Physical unit of a scaler_unit structure, by its code in the Blue Book unit
+// table. Codes the table does not list are kept as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Unit(pub u8);
+
+impl Unit {
+    pub const YEAR: Unit = Unit(1);
+    pub const MONTH: Unit = Unit(2);
+    pub const WEEK: Unit = Unit(3);
+    pub const DAY: Unit = Unit(4);
+    pub const HOUR: Unit = Unit(5);
+    pub const MINUTE: Unit = Unit(6);
+    pub const SECOND: Unit = Unit(7);
+    pub const PHASE_ANGLE_DEGREE: Unit = Unit(8);
+    pub const DEGREE_CELSIUS: Unit = Unit(9);
+    pub const CURRENCY: Unit = Unit(10);
+    pub const METRE: Unit = Unit(11);
+    pub const METRE_PER_SECOND: Unit = Unit(12);
+    pub const CUBIC_METRE: Unit = Unit(13);
+    pub const CORRECTED_CUBIC_METRE: Unit = Unit(14);
+    pub const CUBIC_METRE_PER_HOUR: Unit = Unit(15);
+    pub const CORRECTED_CUBIC_METRE_PER_HOUR: Unit = Unit(16);
+    pub const CUBIC_METRE_PER_DAY: Unit = Unit(17);
+    pub const CORRECTED_CUBIC_METRE_PER_DAY: Unit = Unit(18);
+    pub const LITRE: Unit = Unit(19);
+    pub const KILOGRAM: Unit = Unit(20);
+    pub const NEWTON: Unit = Unit(21);
+    pub const NEWTON_METRE: Unit = Unit(22);
+    pub const PASCAL: Unit = Unit(23);
+    pub const BAR: Unit = Unit(24);
+    pub const JOULE: Unit = Unit(25);
+    pub const JOULE_PER_HOUR: Unit = Unit(26);
+    pub const WATT: Unit = Unit(27);
+    pub const VOLT_AMPERE: Unit = Unit(28);
+    pub const VAR: Unit = Unit(29);
+    pub const WATT_HOUR: Unit = Unit(30);
+    pub const VOLT_AMPERE_HOUR: Unit = Unit(31);
+    pub const VAR_HOUR: Unit = Unit(32);
+    pub const AMPERE: Unit = Unit(33);
+    pub const COULOMB: Unit = Unit(34);
+    pub const VOLT: Unit = Unit(35);
+    pub const VOLT_PER_METRE: Unit = Unit(36);
+    pub const FARAD: Unit = Unit(37);
+    pub const OHM: Unit = Unit(38);
+    pub const OHM_SQUARE_METRE_PER_METRE: Unit = Unit(39);
+    pub const WEBER: Unit = Unit(40);
+    pub const TESLA: Unit = Unit(41);
+    pub const AMPERE_PER_METRE: Unit = Unit(42);
+    pub const HENRY: Unit = Unit(43);
+    pub const HERTZ: Unit = Unit(44);
+    pub const ACTIVE_ENERGY_METER_CONSTANT: Unit = Unit(45);
+    pub const REACTIVE_ENERGY_METER_CONSTANT: Unit = Unit(46);
+    pub const APPARENT_ENERGY_METER_CONSTANT: Unit = Unit(47);
+    pub const VOLT_SQUARED_HOUR: Unit = Unit(48);
+    pub const AMPERE_SQUARED_HOUR: Unit = Unit(49);
+    pub const KILOGRAM_PER_SECOND: Unit = Unit(50);
+    pub const SIEMENS: Unit = Unit(51);
+    pub const KELVIN: Unit = Unit(52);
+    pub const VOLT_SQUARED_HOUR_METER_CONSTANT: Unit = Unit(53);
+    pub const AMPERE_SQUARED_HOUR_METER_CONSTANT: Unit = Unit(54);
+    pub const VOLUME_METER_CONSTANT: Unit = Unit(55);
+    pub const PERCENTAGE: Unit = Unit(56);
+    pub const AMPERE_HOUR: Unit = Unit(57);
+    pub const WATT_HOUR_PER_CUBIC_METRE: Unit = Unit(60);
+    pub const JOULE_PER_CUBIC_METRE: Unit = Unit(61);
+    pub const MOLE_PERCENT: Unit = Unit(62);
+    pub const GRAM_PER_CUBIC_METRE: Unit = Unit(63);
+    pub const PASCAL_SECOND: Unit = Unit(64);
+    pub const JOULE_PER_KILOGRAM: Unit = Unit(65);
+    pub const GRAM_PER_SQUARE_CENTIMETRE: Unit = Unit(66);
+    pub const ATMOSPHERE: Unit = Unit(67);
+    pub const DECIBEL_MILLIWATT: Unit = Unit(70);
+    pub const DECIBEL_MICROVOLT: Unit = Unit(71);
+    pub const DECIBEL: Unit = Unit(72);
+    pub const OTHER: Unit = Unit(254);
+    pub const COUNT: Unit = Unit(255);
+
+    pub fn symbol(&self) -> &'static str {
+        match self.0 {
+            1 => "a",
+            2 => "mo",
+            3 => "wk",
+            4 => "d",
+            5 => "h",
+            6 => "min",
+            7 => "s",
+            8 => "°",
+            9 => "°C",
+            10 => "currency",
+            11 => "m",
+            12 => "m/s",
+            13 | 14 => "m³",
+            15 | 16 => "m³/h",
+            17 | 18 => "m³/d",
+            19 => "l",
+            20 => "kg",
+            21 => "N",
+            22 => "Nm",
+            23 => "Pa",
+            24 => "bar",
+            25 => "J",
+            26 => "J/h",
+            27 => "W",
+            28 => "VA",
+            29 => "var",
+            30 => "Wh",
+            31 => "VAh",
+            32 => "varh",
+            33 => "A",
+            34 => "C",
+            35 => "V",
+            36 => "V/m",
+            37 => "F",
+            38 => "Ω",
+            39 => "Ωm²/m",
+            40 => "Wb",
+            41 => "T",
+            42 => "A/m",
+            43 => "H",
+            44 => "Hz",
+            45 => "1/(Wh)",
+            46 => "1/(varh)",
+            47 => "1/(VAh)",
+            48 => "V²h",
+            49 => "A²h",
+            50 => "kg/s",
+            51 => "S",
+            52 => "K",
+            53 => "1/(V²h)",
+            54 => "1/(A²h)",
+            55 => "1/m³",
+            56 => "%",
+            57 => "Ah",
+            60 => "Wh/m³",
+            61 => "J/m³",
+            62 => "Mol %",
+            63 => "g/m³",
+            64 => "Pa s",
+            65 => "J/kg",
+            66 => "g/cm²",
+            67 => "atm",
+            70 => "dBm",
+            71 => "dBμV",
+            72 => "dB",
+            _ => "",
+        }
+    }
+
+    // Power and energy are shown with a k, M or G prefix once they reach it.
+    fn takes_prefix(&self) -> bool {
+        matches!(*self, Unit::JOULE | Unit(27..=32))
+    }
+}
+
+// Factors between units the Blue Book relates: `to = from * mantissa * 10^exponent`.
+const CONVERSIONS: &[(Unit, Unit, i128, i8)] = &[
+    (Unit::WATT_HOUR, Unit::JOULE, 36, 2),
+    (Unit::WATT, Unit::JOULE_PER_HOUR, 36, 2),
+    (
+        Unit::WATT_HOUR_PER_CUBIC_METRE,
+        Unit::JOULE_PER_CUBIC_METRE,
+        36,
+        2,
+    ),
+    (Unit::WEEK, Unit::DAY, 7, 0),
+    (Unit::DAY, Unit::HOUR, 24, 0),
+    (Unit::DAY, Unit::MINUTE, 144, 1),
+    (Unit::DAY, Unit::SECOND, 864, 2),
+    (Unit::HOUR, Unit::MINUTE, 6, 1),
+    (Unit::HOUR, Unit::SECOND, 36, 2),
+    (Unit::MINUTE, Unit::SECOND, 6, 1),
+    (Unit::CUBIC_METRE_PER_HOUR, Unit::CUBIC_METRE_PER_DAY, 24, 0),
+    (
+        Unit::CORRECTED_CUBIC_METRE_PER_HOUR,
+        Unit::CORRECTED_CUBIC_METRE_PER_DAY,
+        24,
+        0,
+    ),
+    (Unit::LITRE, Unit::CUBIC_METRE, 1, -3),
+    (Unit::BAR, Unit::PASCAL, 1, 5),
+    (Unit::ATMOSPHERE, Unit::PASCAL, 101325, 0),
+];
+
+// Extra decimal places a conversion may add to stay exact.
+const MAX_EXTRA_DIGITS: i8 = 6;
+
+// How a value with more digits than a scaler keeps is brought to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    // Half away from zero.
+    #[default]
+    Nearest,
+    // Half to even.
+    NearestEven,
+    TowardZero,
+    Floor,
+    Ceiling,
+}
+
+impl Rounding {
+    fn round(self, value: f64) -> f64 {
+        match self {
+            Rounding::Nearest => value.round(),
+            Rounding::NearestEven => value.round_ties_even(),
+            Rounding::TowardZero => value.trunc(),
+            Rounding::Floor => value.floor(),
+            Rounding::Ceiling => value.ceil(),
+        }
+    }
+
+    // `value / divisor` for a positive divisor.
+    fn divide(self, value: i128, divisor: i128) -> i128 {
+        let (quotient, remainder) = (value / divisor, value % divisor);
+        if remainder == 0 {
+            return quotient;
+        }
+        let away = quotient + value.signum();
+        let half = (2 * remainder.abs()).cmp(&divisor);
+        match self {
+            Rounding::Nearest if half.is_ge() => away,
+            Rounding::NearestEven if half.is_gt() || (half.is_eq() && quotient % 2 != 0) => away,
+            Rounding::Floor if value < 0 => away,
+            Rounding::Ceiling if value > 0 => away,
+            _ => quotient,
+        }
+    }
+}
+
+// A register reading: `raw * 10^scaler` in `unit`, as carried by the value and
+// scaler_unit attributes of registers. Values are kept exact; arithmetic fails
+// rather than rounding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaledValue {
+    pub raw: i64,
+    pub scaler: i8,
+    pub unit: Unit,
+}
+
+impl ScaledValue {
+    pub fn new(raw: i64, scaler: i8, unit: Unit) -> Self {
+        Self { raw, scaler, unit }
+    }
+
+    // From a value attribute and its scaler_unit structure { scaler, unit }.
+    pub fn from_cosem_data(value: &CosemData, scaler_unit: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = scaler_unit else {
+            return None;
+        };
+        let [CosemData::Integer(scaler), CosemData::Enum(unit)] = fields.as_slice() else {
+            return None;
+        };
+        Some(Self::new(value.as_i64()?, *scaler, Unit(*unit)))
+    }
+
+    // From a value attribute of any integer or float type, brought to
+    // `scaler` with `rounding`. Float32 values are taken with the digits they
+    // show, so 230.1 stays 230.1 rather than 230.100006.
+    pub fn from_cosem_data_rounded(
+        value: &CosemData,
+        scaler_unit: &CosemData,
+        scaler: i8,
+        rounding: Rounding,
+    ) -> Option<Self> {
+        let float = match value {
+            CosemData::Float32(value) => value.to_string().parse().ok()?,
+            CosemData::Float64(value) => *value,
+            _ => {
+                return Self::from_cosem_data(value, scaler_unit)?
+                    .rescale_rounded(scaler, rounding);
+            }
+        };
+        let register = Self::from_cosem_data(&CosemData::Integer(0), scaler_unit)?;
+        let raw = scale_float(float, register.scaler as i32 - scaler as i32, rounding)?;
+        Some(Self::new(raw, scaler, register.unit))
+    }
+
+    // A float quantity in `unit` with `scaler`. `None` for NaN, infinities and
+    // values out of range.
+    pub fn from_f64(value: f64, scaler: i8, unit: Unit, rounding: Rounding) -> Option<Self> {
+        Some(Self::new(
+            scale_float(value, -(scaler as i32), rounding)?,
+            scaler,
+            unit,
+        ))
+    }
+
+    pub fn scaler_unit(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::Integer(self.scaler),
+            CosemData::Enum(self.unit.0),
+        ])
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.raw as f64 * 10f64.powi(self.scaler as i32)
+    }
+
+    // The same value with another scaler; `None` if digits would be lost or
+    // the raw value overflows.
+    pub fn rescale(&self, scaler: i8) -> Option<Self> {
+        let raw = shift(self.raw as i128, self.scaler as i32 - scaler as i32)?;
+        Some(Self::new(raw.try_into().ok()?, scaler, self.unit))
+    }
+
+    // The same value with another scaler, rounding the digits a coarser one
+    // drops; `None` if the raw value overflows.
+    pub fn rescale_rounded(&self, scaler: i8, rounding: Rounding) -> Option<Self> {
+        let places = self.scaler as i32 - scaler as i32;
+        let raw = if places >= 0 {
+            shift(self.raw as i128, places)?
+        } else {
+            let divisor = 10i128.checked_pow(places.unsigned_abs())?;
+            rounding.divide(self.raw as i128, divisor)
+        };
+        Some(Self::new(raw.try_into().ok()?, scaler, self.unit))
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let (left, right, scaler) = self.aligned(other)?;
+        Some(Self::new(
+            left.checked_add(right)?.try_into().ok()?,
+            scaler,
+            self.unit,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self::new(self.raw.checked_neg()?, self.scaler, self.unit))
+    }
+
+    pub fn checked_mul(&self, factor: i64) -> Option<Self> {
+        Some(Self::new(
+            self.raw.checked_mul(factor)?,
+            self.scaler,
+            self.unit,
+        ))
+    }
+
+    // Converts between units related by a fixed factor (energy and joules,
+    // time spans, volumes, pressures) and between kelvin and degrees Celsius.
+    // Division adds at most six decimal places and fails if still inexact.
+    pub fn convert_to(&self, unit: Unit) -> Option<Self> {
+        if self.unit == unit {
+            return Some(*self);
+        }
+        match (self.unit, unit) {
+            (Unit::DEGREE_CELSIUS, Unit::KELVIN) => {
+                let offset = Self::new(27315, -2, self.unit);
+                return Some(Self {
+                    unit,
+                    ..self.checked_add(&offset)?
+                });
+            }
+            (Unit::KELVIN, Unit::DEGREE_CELSIUS) => {
+                let offset = Self::new(27315, -2, self.unit);
+                return Some(Self {
+                    unit,
+                    ..self.checked_sub(&offset)?
+                });
+            }
+            _ => {}
+        }
+        if let Some(&(_, _, mantissa, exponent)) = CONVERSIONS
+            .iter()
+            .find(|(from, to, _, _)| *from == self.unit && *to == unit)
+        {
+            let raw = (self.raw as i128).checked_mul(mantissa)?;
+            let scaler = self.scaler.checked_add(exponent)?;
+            return Some(Self::new(raw.try_into().ok()?, scaler, unit));
+        }
+        let &(_, _, mantissa, exponent) = CONVERSIONS
+            .iter()
+            .find(|(from, to, _, _)| *from == unit && *to == self.unit)?;
+        let mut raw = self.raw as i128;
+        let mut scaler = self.scaler.checked_sub(exponent)?;
+        for _ in 0..=MAX_EXTRA_DIGITS {
+            if raw % mantissa == 0 {
+                return Some(Self::new((raw / mantissa).try_into().ok()?, scaler, unit));
+            }
+            raw = raw.checked_mul(10)?;
+            scaler = scaler.checked_sub(1)?;
+        }
+        None
+    }
+
+    // Both raw values at the finer of the two scalers; `None` for different units.
+    fn aligned(&self, other: &Self) -> Option<(i128, i128, i8)> {
+        if self.unit != other.unit {
+            return None;
+        }
+        let scaler = self.scaler.min(other.scaler);
+        Some((
+            shift(self.raw as i128, (self.scaler - scaler) as i32)?,
+            shift(other.raw as i128, (other.scaler - scaler) as i32)?,
+            scaler,
+        ))
+    }
+}
+
+// `value * 10^places`; `None` if a negative shift drops non-zero digits.
+fn shift(value: i128, places: i32) -> Option<i128> {
+    let factor = 10i128.checked_pow(places.unsigned_abs())?;
+    if places >= 0 {
+        value.checked_mul(factor)
+    } else if value % factor == 0 {
+        Some(value / factor)
+    } else {
+        None
+    }
+}
+
+// `value * 10^places` rounded to an integer. Results within float error of
+// an integer are that integer whatever the rounding, so 230.1 at two more
+// places is 23010 even when rounding down.
+fn scale_float(value: f64, places: i32, rounding: Rounding) -> Option<i64> {
+    if !value.is_finite() {
+        return None;
+    }
+    let factor = 10f64.powi(places.abs());
+    let scaled = if places >= 0 {
+        value * factor
+    } else {
+        value / factor
+    };
+    let nearest = scaled.round();
+    let raw = if (scaled - nearest).abs() <= scaled.abs() * 4.0 * f64::EPSILON {
+        nearest
+    } else {
+        rounding.round(scaled)
+    };
+    // i64::MAX as f64 rounds up to 2^63, which is already out of range.
+    if !(i64::MIN as f64..i64::MAX as f64).contains(&raw) {
+        return None;
+    }
+    Some(raw as i64)
+}
+
+impl Neg for ScaledValue {
+    type Output = ScaledValue;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("scaled value overflow")
+    }
+}
+
+// Writes the exact decimal value, e.g. "12.345 kWh" for 12345 Wh. Decimal
+// places given by the scaler are kept; those only added by a prefix are not.
+impl fmt::Display for ScaledValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.raw.unsigned_abs().to_string();
+        let magnitude = digits.len() as i32 - 1 + self.scaler as i32;
+        let (prefix, prefix_exponent) = match magnitude {
+            _ if !self.unit.takes_prefix() || self.raw == 0 => ("", 0),
+            9.. => ("G", 9),
+            6.. => ("M", 6),
+            3.. => ("k", 3),
+            _ => ("", 0),
+        };
+        let exponent = self.scaler as i32 - prefix_exponent;
+
+        if self.raw < 0 {
+            f.write_str("-")?;
+        }
+        if exponent >= 0 {
+            f.write_str(&digits)?;
+            for _ in 0..exponent {
+                f.write_str("0")?;
+            }
+        } else {
+            let places = exponent.unsigned_abs() as usize;
+            let padded = format!("{digits:0>width$}", width = places + 1);
+            let (integer, fraction) = padded.split_at(padded.len() - places);
+            let kept = (-(self.scaler as i32)).max(0) as usize;
+            let significant = fraction.trim_end_matches('0').len().max(kept);
+            let fraction = &fraction[..significant];
+            f.write_str(integer)?;
+            if !fraction.is_empty() {
+                write!(f, ".{fraction}")?;
+            }
+        }
+
+        let symbol = self.unit.symbol();
+        if !symbol.is_empty() {
+            write!(f, " {prefix}{symbol}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_display_uses_scaler_and_prefix() {
+        assert_eq!(
+            ScaledValue::new(12345, 0, Unit::WATT_HOUR).to_string(),
+            "12.345 kWh"
+        );
+        assert_eq!(
+            ScaledValue::new(12300, 0, Unit::WATT_HOUR).to_string(),
+            "12.3 kWh"
+        );
+        assert_eq!(
+            ScaledValue::new(23012, -2, Unit::VOLT).to_string(),
+            "230.12 V"
+        );
+        assert_eq!(
+            ScaledValue::new(100, -2, Unit::AMPERE).to_string(),
+            "1.00 A"
+        );
+        assert_eq!(ScaledValue::new(-5, 3, Unit::WATT).to_string(), "-5 kW");
+        assert_eq!(ScaledValue::new(42, 1, Unit::COUNT).to_string(), "420");
+        assert_eq!(
+            ScaledValue::new(5, -3, Unit::WATT_HOUR).to_string(),
+            "0.005 Wh"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_aligns_scalers() {
+        let a = ScaledValue::new(15, -1, Unit::WATT_HOUR);
+        let b = ScaledValue::new(2, 0, Unit::WATT_HOUR);
+        assert_eq!(
+            a.checked_add(&b),
+            Some(ScaledValue::new(35, -1, Unit::WATT_HOUR))
+        );
+        assert_eq!(
+            a.checked_sub(&b),
+            Some(ScaledValue::new(-5, -1, Unit::WATT_HOUR))
+        );
+        assert_eq!(a.checked_add(&ScaledValue::new(1, 0, Unit::VOLT)), None);
+        assert_eq!(
+            b.rescale(-2),
+            Some(ScaledValue::new(200, -2, Unit::WATT_HOUR))
+        );
+        assert_eq!(a.rescale(0), None);
+        assert_eq!(-b, ScaledValue::new(-2, 0, Unit::WATT_HOUR));
+    }
+
+    #[test]
+    fn test_unit_conversions() {
+        let energy = ScaledValue::new(2, 0, Unit::WATT_HOUR);
+        let joules = energy.convert_to(Unit::JOULE).unwrap();
+        assert_eq!(joules, ScaledValue::new(72, 2, Unit::JOULE));
+        assert_eq!(joules.convert_to(Unit::WATT_HOUR), Some(energy));
+        assert_eq!(
+            ScaledValue::new(1, 0, Unit::JOULE).convert_to(Unit::WATT_HOUR),
+            None
+        );
+        assert_eq!(
+            ScaledValue::new(90, 0, Unit::MINUTE).convert_to(Unit::HOUR),
+            Some(ScaledValue::new(15, -1, Unit::HOUR))
+        );
+        assert_eq!(
+            ScaledValue::new(2150, -2, Unit::DEGREE_CELSIUS).convert_to(Unit::KELVIN),
+            Some(ScaledValue::new(29465, -2, Unit::KELVIN))
+        );
+        assert_eq!(
+            ScaledValue::new(1, 0, Unit::VOLT).convert_to(Unit::AMPERE),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_cosem_data() {
+        let value = ScaledValue::from_cosem_data(
+            &CosemData::DoubleLongUnsigned(12345),
+            &CosemData::Structure(vec![CosemData::Integer(-3), CosemData::Enum(30)]),
+        )
+        .unwrap();
+        assert_eq!(value, ScaledValue::new(12345, -3, Unit::WATT_HOUR));
+        assert_eq!(
+            value.scaler_unit(),
+            CosemData::Structure(vec![CosemData::Integer(-3), CosemData::Enum(30)])
+        );
+        assert_eq!(
+            ScaledValue::from_cosem_data(&CosemData::NullData, &value.scaler_unit()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rounding_to_scaled_integers() {
+        let ties = [
+            (Rounding::Nearest, 3, -3),
+            (Rounding::NearestEven, 2, -2),
+            (Rounding::TowardZero, 2, -2),
+            (Rounding::Floor, 2, -3),
+            (Rounding::Ceiling, 3, -2),
+        ];
+        for (rounding, up, down) in ties {
+            let half = ScaledValue::new(25, -1, Unit::VOLT);
+            assert_eq!(half.rescale_rounded(0, rounding).unwrap().raw, up);
+            assert_eq!((-half).rescale_rounded(0, rounding).unwrap().raw, down);
+            assert_eq!(
+                ScaledValue::from_f64(2.5, 0, Unit::VOLT, rounding)
+                    .unwrap()
+                    .raw,
+                up
+            );
+            assert_eq!(
+                ScaledValue::from_f64(-2.5, 0, Unit::VOLT, rounding)
+                    .unwrap()
+                    .raw,
+                down
+            );
+        }
+        assert_eq!(
+            ScaledValue::new(35, -1, Unit::VOLT).rescale_rounded(0, Rounding::NearestEven),
+            Some(ScaledValue::new(4, 0, Unit::VOLT))
+        );
+        assert_eq!(
+            ScaledValue::new(7, 0, Unit::VOLT).rescale_rounded(-2, Rounding::Floor),
+            Some(ScaledValue::new(700, -2, Unit::VOLT))
+        );
+        assert_eq!(
+            ScaledValue::from_f64(f64::NAN, 0, Unit::VOLT, Rounding::Nearest),
+            None
+        );
+        assert_eq!(
+            ScaledValue::from_f64(1e19, 0, Unit::VOLT, Rounding::Nearest),
+            None
+        );
+        assert_eq!(
+            ScaledValue::from_f64(1234.0, 2, Unit::WATT, Rounding::Floor),
+            Some(ScaledValue::new(12, 2, Unit::WATT))
+        );
+    }
+
+    #[test]
+    fn test_float_values_are_scaled() {
+        let volts = CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(35)]);
+        for rounding in [Rounding::Floor, Rounding::Ceiling] {
+            assert_eq!(
+                ScaledValue::from_cosem_data_rounded(
+                    &CosemData::Float32(230.1),
+                    &volts,
+                    -2,
+                    rounding
+                ),
+                Some(ScaledValue::new(23010, -2, Unit::VOLT))
+            );
+        }
+        let kilowatts = CosemData::Structure(vec![CosemData::Integer(3), CosemData::Enum(27)]);
+        assert_eq!(
+            ScaledValue::from_cosem_data_rounded(
+                &CosemData::Float64(1.2345),
+                &kilowatts,
+                0,
+                Rounding::Nearest
+            ),
+            Some(ScaledValue::new(1235, 0, Unit::WATT))
+        );
+        assert_eq!(
+            ScaledValue::from_cosem_data_rounded(
+                &CosemData::DoubleLong(-12345),
+                &kilowatts,
+                4,
+                Rounding::TowardZero
+            ),
+            Some(ScaledValue::new(-1234, 4, Unit::WATT))
+        );
+        assert_eq!(
+            ScaledValue::from_cosem_data_rounded(
+                &CosemData::Float32(f32::INFINITY),
+                &volts,
+                0,
+                Rounding::Nearest
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use crate::cosem::{
+    CosemClassId, CosemObjectAttributeId, CosemObjectInstanceId, CosemObjectMethodId,
+};
+use crate::cosem_object::{
+    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
+};
+use crate::types::CosemData;
+use std::sync::Arc;
+use std::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptService {
+    WriteAttribute = 1,
+    ExecuteMethod = 2,
+}
+
+// action_specification ::= structure { service_id, class_id, logical_name, index, parameter }
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptAction {
+    pub service: ScriptService,
+    pub class_id: CosemClassId,
+    pub logical_name: CosemObjectInstanceId,
+    pub index: i8,
+    pub parameter: CosemData,
+}
+
+impl ScriptAction {
+    pub fn to_cosem_data(&self) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::Enum(self.service as u8),
+            CosemData::LongUnsigned(self.class_id),
+            CosemData::OctetString(self.logical_name.to_vec()),
+            CosemData::Integer(self.index),
+            self.parameter.clone(),
+        ])
+    }
+
+    pub fn from_cosem_data(data: &CosemData) -> Option<Self> {
+        let CosemData::Structure(fields) = data else {
+            return None;
+        };
+        match fields.as_slice() {
+            [CosemData::Enum(service), CosemData::LongUnsigned(class_id), CosemData::OctetString(logical_name), CosemData::Integer(index), parameter] =>
+            {
+                let service = match service {
+                    1 => ScriptService::WriteAttribute,
+                    2 => ScriptService::ExecuteMethod,
+                    _ => return None,
+                };
+                Some(ScriptAction {
+                    service,
+                    class_id: *class_id,
+                    logical_name: logical_name.as_slice().try_into().ok()?,
+                    index: *index,
+                    parameter: parameter.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// Actions of the script with the given identifier in the scripts attribute of
+// a script table (class 9).
+pub fn script_actions(table: &dyn CosemObject, script_id: u16) -> Option<Vec<ScriptAction>> {
+    if table.class_id() != 9 {
+        return None;
+    }
+    let CosemData::Array(scripts) = table.get_attribute(2)? else {
+        return None;
+    };
+    scripts.iter().find_map(|script| match script {
+        CosemData::Structure(fields) => match fields.as_slice() {
+            [CosemData::LongUnsigned(id), CosemData::Array(actions)] if *id == script_id => {
+                actions.iter().map(ScriptAction::from_cosem_data).collect()
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+#[derive(Debug)]
+pub struct ScriptTable {
+    scripts: CosemData,
+    callbacks: Arc<CosemObjectCallbackHandlers>,
+}
+
+impl ScriptTable {
+    pub fn new() -> Self {
+        Self {
+            scripts: CosemData::Array(Vec::new()),
+            callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
+        }
+    }
+
+    pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
+        Arc::clone(&self.callbacks)
+    }
+
+    pub fn add_script(&mut self, script_id: u16, actions: &[ScriptAction]) -> Option<()> {
+        let CosemData::Array(scripts) = &mut self.scripts else {
+            return None;
+        };
+        scripts.push(CosemData::Structure(vec![
+            CosemData::LongUnsigned(script_id),
+            CosemData::Array(actions.iter().map(ScriptAction::to_cosem_data).collect()),
+        ]));
+        Some(())
+    }
+}
+
+impl Default for ScriptTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CosemObject for ScriptTable {
+    fn class_id(&self) -> u16 {
+        9
+    }
+
+    fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+        vec![AttributeAccessDescriptor::new(
+            2,
+            AttributeAccessMode::ReadWrite,
+        )]
+    }
+
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+    }
+
+    fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        match attribute_id {
+            2 => Some(self.scripts.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_attribute(
+        &mut self,
+        attribute_id: CosemObjectAttributeId,
+        data: CosemData,
+    ) -> Option<()> {
+        match attribute_id {
+            2 => {
+                self.scripts = data;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    // The script table cannot reach the objects its actions refer to; the server
+    // runs the actions once execute has been accepted here.
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
+    ) -> Option<CosemData> {
+        match method_id {
+            1 => {
+                let CosemData::LongUnsigned(script_id) = data else {
+                    return None;
+                };
+                script_actions(self, script_id)?;
+                Some(CosemData::NullData)
+            }
+            _ => None,
+        }
+    }
+
+    fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
+        Some(Arc::clone(&self.callbacks))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_script_table_actions_round_trip() {
+        let action = ScriptAction {
+            service: ScriptService::ExecuteMethod,
+            class_id: 70,
+            logical_name: [0, 0, 96, 3, 10, 255],
+            index: 1,
+            parameter: CosemData::Integer(0),
+        };
+        let mut table = ScriptTable::new();
+        table.add_script(1, std::slice::from_ref(&action)).unwrap();
+
+        assert_eq!(script_actions(&table, 1), Some(vec![action]));
+        assert_eq!(script_actions(&table, 2), None);
+        assert_eq!(
+            table.invoke_method(1, CosemData::LongUnsigned(1)),
+            Some(CosemData::NullData)
+        );
+        assert_eq!(table.invoke_method(1, CosemData::LongUnsigned(2)), None);
+    }
+}
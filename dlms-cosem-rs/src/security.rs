@@ -1,8 +1,18 @@
-use aead::{Aead, AeadCore, KeyInit, OsRng};
+use aead::rand_core::RngCore;
+use aead::{Aead, AeadCore, AeadInPlace, KeyInit, OsRng};
+use aes::cipher::{BlockDecrypt, BlockEncrypt};
+use aes::{Aes128, Block};
 use aes_gcm::{Aes128Gcm, Error, Nonce};
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 use std::vec::Vec;
+use subtle::ConstantTimeEq;
+
+use crate::security_setup::KeyId;
 
 #[derive(Debug)]
 pub enum SecurityError {
@@ -40,6 +50,29 @@ pub fn hls_encrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>, SecurityError> {
     Ok(encrypted_data)
 }
 
+// Like `hls_encrypt`, with the nonce made of the system title of the sender
+// and the invocation counter of the frame, so that no nonce is used twice.
+pub fn hls_encrypt_with_counter(
+    data: &[u8],
+    key: &[u8],
+    system_title: &[u8],
+    invocation_counter: u32,
+) -> Result<Vec<u8>, SecurityError> {
+    if system_title.len() != 8 {
+        return Err(SecurityError::EncryptionError);
+    }
+    let cipher = Aes128Gcm::new_from_slice(key).map_err(|_| SecurityError::InvalidKeyLength)?;
+    let mut nonce = Nonce::default();
+    nonce[..8].copy_from_slice(system_title);
+    nonce[8..].copy_from_slice(&invocation_counter.to_be_bytes());
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| SecurityError::EncryptionError)?;
+    let mut encrypted_data = nonce.to_vec();
+    encrypted_data.extend_from_slice(&ciphertext);
+    Ok(encrypted_data)
+}
+
 pub fn hls_decrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>, SecurityError> {
     let cipher = Aes128Gcm::new_from_slice(key).map_err(|_| SecurityError::InvalidKeyLength)?;
     let (nonce_slice, ciphertext) = data.split_at(12);
@@ -48,3 +81,502 @@ pub fn hls_decrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>, SecurityError> {
     let plaintext = cipher.decrypt(&nonce, ciphertext)?;
     Ok(plaintext)
 }
+
+// Security control byte of a GMAC answer: authentication only, suite 0.
+const GMAC_SECURITY_CONTROL: u8 = 0x10;
+const GMAC_TAG_LENGTH: usize = 12;
+
+// Mechanisms of the HLS pass 3 and 4, in which each side answers the challenge
+// of the other. For GMAC the secret is the encryption key, and the
+// authentication key goes with the mechanism.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HlsMechanism {
+    Md5,
+    Sha1,
+    Gmac { authentication_key: Vec<u8> },
+    Sha256,
+}
+
+impl HlsMechanism {
+    pub fn mechanism_id(&self) -> u8 {
+        match self {
+            HlsMechanism::Md5 => 3,
+            HlsMechanism::Sha1 => 4,
+            HlsMechanism::Gmac { .. } => 5,
+            HlsMechanism::Sha256 => 6,
+        }
+    }
+
+    // Object identifier of the mechanism, sent as mechanism name in the AARQ.
+    pub fn mechanism_name(&self) -> Vec<u8> {
+        vec![0x60, 0x85, 0x74, 0x05, 0x08, 0x02, self.mechanism_id()]
+    }
+}
+
+// The challenges and system titles of one HLS association. The client answers
+// StoC with f(StoC), the server answers CtoS with f(CtoS).
+pub struct HlsExchange<'a> {
+    pub secret: &'a [u8],
+    pub client_system_title: &'a [u8],
+    pub server_system_title: &'a [u8],
+    pub client_challenge: &'a [u8],
+    pub server_challenge: &'a [u8],
+}
+
+impl HlsExchange<'_> {
+    // f(StoC). Only GMAC uses the invocation counter.
+    pub fn client_answer(
+        &self,
+        mechanism: &HlsMechanism,
+        invocation_counter: u32,
+    ) -> Result<Vec<u8>, SecurityError> {
+        hls_answer(
+            mechanism,
+            self.secret,
+            self.client_system_title,
+            self.server_system_title,
+            self.server_challenge,
+            self.client_challenge,
+            invocation_counter,
+        )
+    }
+
+    // f(CtoS).
+    pub fn server_answer(
+        &self,
+        mechanism: &HlsMechanism,
+        invocation_counter: u32,
+    ) -> Result<Vec<u8>, SecurityError> {
+        hls_answer(
+            mechanism,
+            self.secret,
+            self.server_system_title,
+            self.client_system_title,
+            self.client_challenge,
+            self.server_challenge,
+            invocation_counter,
+        )
+    }
+
+    pub fn verify_client_answer(&self, mechanism: &HlsMechanism, answer: &[u8]) -> bool {
+        self.client_answer(mechanism, gmac_invocation_counter(answer))
+            .is_ok_and(|expected| expected.ct_eq(answer).into())
+    }
+
+    pub fn verify_server_answer(&self, mechanism: &HlsMechanism, answer: &[u8]) -> bool {
+        self.server_answer(mechanism, gmac_invocation_counter(answer))
+            .is_ok_and(|expected| expected.ct_eq(answer).into())
+    }
+}
+
+// Counter carried after the security control byte of a GMAC answer.
+fn gmac_invocation_counter(answer: &[u8]) -> u32 {
+    answer
+        .get(1..5)
+        .and_then(|counter| counter.try_into().ok())
+        .map_or(0, u32::from_be_bytes)
+}
+
+// The answer of `sender` to `challenge`, the challenge of the receiver.
+fn hls_answer(
+    mechanism: &HlsMechanism,
+    secret: &[u8],
+    sender_system_title: &[u8],
+    receiver_system_title: &[u8],
+    challenge: &[u8],
+    own_challenge: &[u8],
+    invocation_counter: u32,
+) -> Result<Vec<u8>, SecurityError> {
+    match mechanism {
+        HlsMechanism::Md5 => Ok(Md5::new()
+            .chain_update(challenge)
+            .chain_update(secret)
+            .finalize()
+            .to_vec()),
+        HlsMechanism::Sha1 => Ok(Sha1::new()
+            .chain_update(challenge)
+            .chain_update(secret)
+            .finalize()
+            .to_vec()),
+        HlsMechanism::Sha256 => {
+            if sender_system_title.len() != 8 || receiver_system_title.len() != 8 {
+                return Err(SecurityError::EncryptionError);
+            }
+            Ok(Sha256::new()
+                .chain_update(secret)
+                .chain_update(sender_system_title)
+                .chain_update(receiver_system_title)
+                .chain_update(challenge)
+                .chain_update(own_challenge)
+                .finalize()
+                .to_vec())
+        }
+        HlsMechanism::Gmac { authentication_key } => {
+            if sender_system_title.len() != 8 {
+                return Err(SecurityError::EncryptionError);
+            }
+            let cipher =
+                Aes128Gcm::new_from_slice(secret).map_err(|_| SecurityError::InvalidKeyLength)?;
+            let mut nonce = Nonce::default();
+            nonce[..8].copy_from_slice(sender_system_title);
+            nonce[8..].copy_from_slice(&invocation_counter.to_be_bytes());
+            let mut associated_data = vec![GMAC_SECURITY_CONTROL];
+            associated_data.extend_from_slice(authentication_key);
+            associated_data.extend_from_slice(challenge);
+            let tag = cipher
+                .encrypt_in_place_detached(&nonce, &associated_data, &mut [])
+                .map_err(|_| SecurityError::EncryptionError)?;
+            let mut answer = vec![GMAC_SECURITY_CONTROL];
+            answer.extend_from_slice(&invocation_counter.to_be_bytes());
+            answer.extend_from_slice(&tag[..GMAC_TAG_LENGTH]);
+            Ok(answer)
+        }
+    }
+}
+
+// Random challenge of an authenticating side, CtoS or StoC.
+pub fn generate_challenge() -> Vec<u8> {
+    let mut challenge = vec![0u8; 16];
+    OsRng.fill_bytes(&mut challenge);
+    challenge
+}
+
+// Keeps the last invocation counter used with a key, by the system title of
+// the sender and the key id, across restarts of the device. A counter used
+// twice with the same key breaks GCM, and meters reject such frames.
+pub trait FrameCounterStore: Send {
+    fn load(&mut self, system_title: &[u8], key_id: KeyId) -> Option<u32>;
+    fn save(&mut self, system_title: &[u8], key_id: KeyId, invocation_counter: u32);
+}
+
+// Last invocation counter by system title and key id.
+type FrameCounters = BTreeMap<(Vec<u8>, KeyId), u32>;
+
+// Counters held in memory. Clones share the counters, so a device built again
+// with a clone continues where the previous one stopped.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFrameCounterStore {
+    counters: Arc<Mutex<FrameCounters>>,
+}
+
+impl MemoryFrameCounterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FrameCounterStore for MemoryFrameCounterStore {
+    fn load(&mut self, system_title: &[u8], key_id: KeyId) -> Option<u32> {
+        let counters = self.counters.lock().ok()?;
+        counters.get(&(system_title.to_vec(), key_id)).copied()
+    }
+
+    fn save(&mut self, system_title: &[u8], key_id: KeyId, invocation_counter: u32) {
+        if let Ok(mut counters) = self.counters.lock() {
+            counters.insert((system_title.to_vec(), key_id), invocation_counter);
+        }
+    }
+}
+
+// Invocation counter of the frames a device ciphers with one key. Each counter
+// is saved before the frame using it is sent.
+pub struct FrameCounter {
+    system_title: Vec<u8>,
+    key_id: KeyId,
+    store: Box<dyn FrameCounterStore>,
+    last: Option<u32>,
+}
+
+impl FrameCounter {
+    pub fn new(system_title: Vec<u8>, key_id: KeyId, store: Box<dyn FrameCounterStore>) -> Self {
+        Self {
+            system_title,
+            key_id,
+            store,
+            last: None,
+        }
+    }
+
+    pub fn system_title(&self) -> &[u8] {
+        &self.system_title
+    }
+
+    // Takes the next counter. Once the counters are exhausted the key has to be
+    // changed, and nothing is ciphered until then.
+    pub fn next_invocation_counter(&mut self) -> Result<u32, SecurityError> {
+        let last = match self.last {
+            Some(last) => Some(last),
+            None => self.store.load(&self.system_title, self.key_id),
+        };
+        let next = match last {
+            Some(last) => last.checked_add(1).ok_or(SecurityError::EncryptionError)?,
+            None => 0,
+        };
+        self.store.save(&self.system_title, self.key_id, next);
+        self.last = Some(next);
+        Ok(next)
+    }
+
+    pub fn encrypt(&mut self, data: &[u8], key: &[u8]) -> Result<Vec<u8>, SecurityError> {
+        let invocation_counter = self.next_invocation_counter()?;
+        hls_encrypt_with_counter(data, key, &self.system_title, invocation_counter)
+    }
+}
+
+// Initial value of RFC 3394, checked on unwrapping.
+const KEY_WRAP_IV: [u8; 8] = [0xA6; 8];
+
+// Wraps `key` under the key encrypting key `kek` (AES key wrap, RFC 3394), as
+// key_transfer of the security setup expects keys. `key` is a whole number of
+// 64 bit blocks, at least two.
+pub fn aes_key_wrap(key: &[u8], kek: &[u8]) -> Result<Vec<u8>, SecurityError> {
+    if key.len() < 16 || !key.len().is_multiple_of(8) {
+        return Err(SecurityError::EncryptionError);
+    }
+    let cipher = Aes128::new_from_slice(kek).map_err(|_| SecurityError::InvalidKeyLength)?;
+    let n = key.len() / 8;
+    let mut a = KEY_WRAP_IV;
+    let mut r = key.to_vec();
+    for j in 0..6 {
+        for i in 0..n {
+            let mut block = Block::default();
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(&r[i * 8..i * 8 + 8]);
+            cipher.encrypt_block(&mut block);
+            let t = (n * j + i + 1) as u64;
+            for (byte, (msb, counter)) in a.iter_mut().zip(block[..8].iter().zip(t.to_be_bytes())) {
+                *byte = msb ^ counter;
+            }
+            r[i * 8..i * 8 + 8].copy_from_slice(&block[8..]);
+        }
+    }
+    let mut wrapped = a.to_vec();
+    wrapped.extend_from_slice(&r);
+    Ok(wrapped)
+}
+
+// Unwraps a key wrapped with `aes_key_wrap`. A wrong `kek` or a modified
+// `wrapped` fails the integrity check and yields a decryption error.
+pub fn aes_key_unwrap(wrapped: &[u8], kek: &[u8]) -> Result<Vec<u8>, SecurityError> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(SecurityError::DecryptionError);
+    }
+    let cipher = Aes128::new_from_slice(kek).map_err(|_| SecurityError::InvalidKeyLength)?;
+    let n = wrapped.len() / 8 - 1;
+    let mut a = [0u8; 8];
+    a.copy_from_slice(&wrapped[..8]);
+    let mut r = wrapped[8..].to_vec();
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = (n * j + i + 1) as u64;
+            let mut block = Block::default();
+            for (byte, (value, counter)) in block.iter_mut().zip(a.iter().zip(t.to_be_bytes())) {
+                *byte = value ^ counter;
+            }
+            block[8..].copy_from_slice(&r[i * 8..i * 8 + 8]);
+            cipher.decrypt_block(&mut block);
+            a.copy_from_slice(&block[..8]);
+            r[i * 8..i * 8 + 8].copy_from_slice(&block[8..]);
+        }
+    }
+    if a != KEY_WRAP_IV {
+        return Err(SecurityError::DecryptionError);
+    }
+    Ok(r)
+}
+
+// Source of the secrets behind the authentication of associations. The
+// server consults it with the address of the client associating, so that a
+// provider can keep one secret per association, or leave them in an HSM or a
+// secure element.
+pub trait AuthenticationProvider: Send {
+    // Challenge (StoC) for a client asking for an authenticated association.
+    fn generate_challenge(&mut self, client_address: u16) -> Result<Vec<u8>, SecurityError>;
+    // Whether `response` answers `challenge` with the LLS secret.
+    fn verify_lls(&self, client_address: u16, challenge: &[u8], response: &[u8]) -> bool;
+    // The HLS answer of this side to a challenge of the peer.
+    fn compute_hls_response(
+        &self,
+        client_address: u16,
+        challenge: &[u8],
+    ) -> Result<Vec<u8>, SecurityError>;
+    // Whether `response` is the HLS answer of the peer to `challenge`.
+    fn verify_hls(&self, client_address: u16, challenge: &[u8], response: &[u8]) -> bool;
+}
+
+// One secret held in memory for every association. HLS answers are the
+// challenge ciphered with it, so the secret must be an AES-128 key for them.
+pub struct PasswordAuthentication {
+    password: Vec<u8>,
+}
+
+impl PasswordAuthentication {
+    pub fn new(password: Vec<u8>) -> Self {
+        Self { password }
+    }
+}
+
+impl AuthenticationProvider for PasswordAuthentication {
+    fn generate_challenge(&mut self, _client_address: u16) -> Result<Vec<u8>, SecurityError> {
+        Ok(generate_challenge())
+    }
+
+    fn verify_lls(&self, _client_address: u16, challenge: &[u8], response: &[u8]) -> bool {
+        lls_authenticate(&self.password, challenge)
+            .is_ok_and(|expected| expected.ct_eq(response).into())
+    }
+
+    fn compute_hls_response(
+        &self,
+        _client_address: u16,
+        challenge: &[u8],
+    ) -> Result<Vec<u8>, SecurityError> {
+        hls_encrypt(challenge, &self.password)
+    }
+
+    fn verify_hls(&self, _client_address: u16, challenge: &[u8], response: &[u8]) -> bool {
+        // Nonce and tag around the ciphered challenge.
+        response.len() >= 12 + 16
+            && hls_decrypt(response, &self.password)
+                .is_ok_and(|plain| plain.ct_eq(challenge).into())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_frame_counter_continues_after_restart() {
+        let store = MemoryFrameCounterStore::new();
+        let key = [0x0F; 16];
+        let mut counter = FrameCounter::new(
+            b"METER001".to_vec(),
+            KeyId::GlobalUnicastEncryption,
+            Box::new(store.clone()),
+        );
+        assert_eq!(counter.next_invocation_counter().unwrap(), 0);
+        let frame = counter.encrypt(b"apdu", &key).unwrap();
+        assert_eq!(&frame[..12], b"METER001\x00\x00\x00\x01");
+        assert_eq!(hls_decrypt(&frame, &key).unwrap(), b"apdu");
+
+        // Another key and another sender count on their own.
+        let mut broadcast = FrameCounter::new(
+            b"METER001".to_vec(),
+            KeyId::GlobalBroadcastEncryption,
+            Box::new(store.clone()),
+        );
+        assert_eq!(broadcast.next_invocation_counter().unwrap(), 0);
+
+        let mut restarted = FrameCounter::new(
+            b"METER001".to_vec(),
+            KeyId::GlobalUnicastEncryption,
+            Box::new(store.clone()),
+        );
+        assert_eq!(restarted.next_invocation_counter().unwrap(), 2);
+
+        let mut exhausted = store.clone();
+        exhausted.save(b"METER001", KeyId::Authentication, u32::MAX);
+        let mut counter =
+            FrameCounter::new(b"METER001".to_vec(), KeyId::Authentication, Box::new(store));
+        assert!(matches!(
+            counter.next_invocation_counter(),
+            Err(SecurityError::EncryptionError)
+        ));
+    }
+
+    #[test]
+    fn test_hls_answers_follow_the_mechanism() {
+        let exchange = HlsExchange {
+            secret: b"Gurux",
+            client_system_title: b"CLIENT01",
+            server_system_title: b"SERVER01",
+            client_challenge: b"CtoS-challenge",
+            server_challenge: b"P6wRJ21F",
+        };
+        // MD5 and SHA-1 digest the challenge followed by the secret.
+        let md5 = [
+            0xB4, 0x81, 0x20, 0x9B, 0x43, 0x9A, 0xD7, 0xB3, 0x1C, 0x68, 0x52, 0xFB, 0x03, 0x65,
+            0x5D, 0xD9,
+        ];
+        assert_eq!(exchange.client_answer(&HlsMechanism::Md5, 0).unwrap(), md5);
+        let sha1 = [
+            0xEE, 0x1A, 0xE0, 0x61, 0xD6, 0xC8, 0xDB, 0x8A, 0xF1, 0xF9, 0x3B, 0xB7, 0xF7, 0x94,
+            0x5D, 0x85, 0xA2, 0xD7, 0xB5, 0x22,
+        ];
+        assert_eq!(
+            exchange.client_answer(&HlsMechanism::Sha1, 0).unwrap(),
+            sha1
+        );
+        // SHA-256 mixes both system titles and challenges, from the view of
+        // the answering side.
+        let sha256 = [
+            0xF7, 0x04, 0x41, 0x3A, 0xD2, 0x55, 0x09, 0x84, 0xAB, 0x57, 0xED, 0x10, 0xA0, 0xDE,
+            0xD1, 0x37, 0xA8, 0x25, 0x70, 0x3A, 0x88, 0x25, 0x6D, 0x46, 0x40, 0x45, 0xFD, 0x52,
+            0xBC, 0x4D, 0xAA, 0x17,
+        ];
+        assert_eq!(
+            exchange.server_answer(&HlsMechanism::Sha256, 0).unwrap(),
+            sha256
+        );
+        assert!(exchange.verify_server_answer(&HlsMechanism::Sha256, &sha256));
+        assert!(!exchange.verify_client_answer(&HlsMechanism::Sha256, &sha256));
+        assert!(!exchange.verify_server_answer(&HlsMechanism::Sha256, &sha256[..16]));
+
+        // GMAC carries its invocation counter, which verification takes over.
+        let gmac = HlsMechanism::Gmac {
+            authentication_key: vec![0xD0; 16],
+        };
+        let exchange = HlsExchange {
+            secret: &[0x5A; 16],
+            ..exchange
+        };
+        let answer = exchange.client_answer(&gmac, 0x0102_0304).unwrap();
+        assert_eq!(answer[..5], [0x10, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(answer.len(), 17);
+        assert!(exchange.verify_client_answer(&gmac, &answer));
+        assert!(!exchange.verify_server_answer(&gmac, &answer));
+        assert_eq!(
+            gmac.mechanism_name(),
+            [0x60, 0x85, 0x74, 0x05, 0x08, 0x02, 0x05]
+        );
+
+        let untitled = HlsExchange {
+            client_system_title: b"",
+            ..exchange
+        };
+        assert!(matches!(
+            untitled.client_answer(&gmac, 0),
+            Err(SecurityError::EncryptionError)
+        ));
+    }
+
+    #[test]
+    fn test_aes_key_wrap_rfc_3394_vector() {
+        let kek: Vec<u8> = (0x00..=0x0F).collect();
+        let key: Vec<u8> = (0x00..=0xFF).step_by(0x11).collect();
+        let wrapped = [
+            0x1F, 0xA6, 0x8B, 0x0A, 0x81, 0x12, 0xB4, 0x47, 0xAE, 0xF3, 0x4B, 0xD8, 0xFB, 0x5A,
+            0x7B, 0x82, 0x9D, 0x3E, 0x86, 0x23, 0x71, 0xD2, 0xCF, 0xE5,
+        ];
+
+        assert_eq!(aes_key_wrap(&key, &kek).unwrap(), wrapped);
+        assert_eq!(aes_key_unwrap(&wrapped, &kek).unwrap(), key);
+
+        let mut tampered = wrapped;
+        tampered[10] ^= 1;
+        assert!(matches!(
+            aes_key_unwrap(&tampered, &kek),
+            Err(SecurityError::DecryptionError)
+        ));
+        assert!(matches!(
+            aes_key_unwrap(&wrapped[..16], &kek),
+            Err(SecurityError::DecryptionError)
+        ));
+        assert!(matches!(
+            aes_key_wrap(&key, &kek[..8]),
+            Err(SecurityError::InvalidKeyLength)
+        ));
+    }
+}
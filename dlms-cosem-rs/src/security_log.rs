@@ -0,0 +1,64 @@
+use crate::cosem::CosemObjectInstanceId;
+use crate::cosem_object::CosemObject;
+use crate::data::Data;
+use crate::profile_generic::{CaptureObjectDefinition, ProfileGeneric};
+use crate::types::CosemData;
+use std::vec::Vec;
+
+pub const SECURITY_LOG_LN: CosemObjectInstanceId = [0, 0, 99, 98, 4, 255];
+pub const SECURITY_EVENT_CODE_LN: CosemObjectInstanceId = [0, 0, 96, 11, 4, 255];
+pub const CLOCK_LN: CosemObjectInstanceId = [0, 0, 1, 0, 0, 255];
+
+// Events the server logs to the security log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityEvent {
+    // An AARQ whose authentication failed.
+    AuthenticationFailure,
+    // A GET, SET or ACTION the access rights do not allow.
+    AccessDenied,
+    // A key transferred to a security setup object.
+    KeyChanged,
+}
+
+impl SecurityEvent {
+    pub fn code(self) -> u16 {
+        match self {
+            SecurityEvent::AuthenticationFailure => 46,
+            SecurityEvent::AccessDenied => 49,
+            SecurityEvent::KeyChanged => 50,
+        }
+    }
+}
+
+// The event code object the log captures.
+pub fn security_event_code() -> Data {
+    Data::new(CosemData::LongUnsigned(0))
+}
+
+// A FIFO log of `entries` events capturing the event code, preceded by the
+// time of the clock when `with_clock`. Entries are captured by the server,
+// never periodically.
+pub fn security_log(entries: u32, with_clock: bool) -> ProfileGeneric {
+    let clock = with_clock.then_some((8, CLOCK_LN));
+    let capture_objects = clock
+        .into_iter()
+        .chain([(1, SECURITY_EVENT_CODE_LN)])
+        .map(|(class_id, logical_name)| {
+            CaptureObjectDefinition {
+                class_id,
+                logical_name,
+                attribute_index: 2,
+                data_index: 0,
+            }
+            .to_cosem_data()
+        })
+        .collect::<Vec<_>>();
+    let mut log = ProfileGeneric::new();
+    log.set_attribute(3, CosemData::Array(capture_objects));
+    log.set_attribute(4, CosemData::DoubleLongUnsigned(0));
+    // FIFO
+    log.set_attribute(5, CosemData::Enum(1));
+    log.set_attribute(7, CosemData::DoubleLongUnsigned(0));
+    log.set_attribute(8, CosemData::DoubleLongUnsigned(entries));
+    log
+}
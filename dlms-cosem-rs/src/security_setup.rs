@@ -1,17 +1,56 @@
 use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
 use crate::cosem_object::{
     AttributeAccessDescriptor, AttributeAccessMode, CosemObject, CosemObjectCallbackHandlers,
+    MethodAccessDescriptor, MethodAccessMode,
 };
+use crate::security::{aes_key_unwrap, SecurityError};
+use crate::system_title::SystemTitle;
 use crate::types::CosemData;
 use std::sync::Arc;
 use std::vec::Vec;
 
+// Length of the AES-128 keys of security suites 0 and 1.
+const KEY_LENGTH: usize = 16;
+
+// The keys key_transfer replaces, by their key_id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyId {
+    GlobalUnicastEncryption = 0,
+    GlobalBroadcastEncryption = 1,
+    Authentication = 2,
+    Master = 3,
+}
+
+impl KeyId {
+    pub fn from_u8(key_id: u8) -> Option<Self> {
+        match key_id {
+            0 => Some(KeyId::GlobalUnicastEncryption),
+            1 => Some(KeyId::GlobalBroadcastEncryption),
+            2 => Some(KeyId::Authentication),
+            3 => Some(KeyId::Master),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyTransferError {
+    // No master key (KEK) to unwrap the keys with.
+    NoMasterKey,
+    // The parameters are not an array of key_id and wrapped key structures.
+    InvalidParameters,
+    UnknownKeyId(u8),
+    // A wrapped key failed the integrity check or was not an AES-128 key.
+    Unwrap(SecurityError),
+}
+
 #[derive(Debug)]
 pub struct SecuritySetup {
     security_policy: u8,
     security_suite: u8,
     client_system_title: Vec<u8>,
     server_system_title: Vec<u8>,
+    keys: [Option<Vec<u8>>; 4],
     callbacks: Arc<CosemObjectCallbackHandlers>,
 }
 
@@ -22,10 +61,60 @@ impl SecuritySetup {
             security_suite: 0,
             client_system_title: Vec::new(),
             server_system_title: Vec::new(),
+            keys: Default::default(),
             callbacks: Arc::new(CosemObjectCallbackHandlers::new()),
         }
     }
 
+    // Attributes 4 and 5, `None` until set.
+    pub fn client_system_title(&self) -> Option<SystemTitle> {
+        SystemTitle::from_bytes(&self.client_system_title).ok()
+    }
+
+    pub fn server_system_title(&self) -> Option<SystemTitle> {
+        SystemTitle::from_bytes(&self.server_system_title).ok()
+    }
+
+    pub fn key(&self, key_id: KeyId) -> Option<&[u8]> {
+        self.keys[key_id as usize].as_deref()
+    }
+
+    pub fn set_key(&mut self, key_id: KeyId, key: Vec<u8>) {
+        self.keys[key_id as usize] = Some(key);
+    }
+
+    // Method 2: replaces the keys given as an array of structures of key_id
+    // and the key wrapped under the master key (RFC 3394). The keys are only
+    // replaced when all of them unwrap.
+    pub fn key_transfer(&mut self, data: &CosemData) -> Result<(), KeyTransferError> {
+        let master_key = self
+            .key(KeyId::Master)
+            .ok_or(KeyTransferError::NoMasterKey)?;
+        let CosemData::Array(entries) = data else {
+            return Err(KeyTransferError::InvalidParameters);
+        };
+        let mut transferred = Vec::new();
+        for entry in entries {
+            let CosemData::Structure(fields) = entry else {
+                return Err(KeyTransferError::InvalidParameters);
+            };
+            let [CosemData::Enum(key_id), CosemData::OctetString(wrapped)] = fields.as_slice()
+            else {
+                return Err(KeyTransferError::InvalidParameters);
+            };
+            let key_id = KeyId::from_u8(*key_id).ok_or(KeyTransferError::UnknownKeyId(*key_id))?;
+            let key = aes_key_unwrap(wrapped, master_key).map_err(KeyTransferError::Unwrap)?;
+            if key.len() != KEY_LENGTH {
+                return Err(KeyTransferError::Unwrap(SecurityError::InvalidKeyLength));
+            }
+            transferred.push((key_id, key));
+        }
+        for (key_id, key) in transferred {
+            self.set_key(key_id, key);
+        }
+        Ok(())
+    }
+
     pub fn callback_handlers(&self) -> Arc<CosemObjectCallbackHandlers> {
         Arc::clone(&self.callbacks)
     }
@@ -51,6 +140,10 @@ impl CosemObject for SecuritySetup {
         ]
     }
 
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(2, MethodAccessMode::Access)]
+    }
+
     fn get_attribute(&self, attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
         match attribute_id {
             2 => Some(CosemData::Unsigned(self.security_policy)),
@@ -83,32 +176,33 @@ impl CosemObject for SecuritySetup {
                     None
                 }
             }
-            4 => {
-                if let CosemData::OctetString(title) = data {
+            4 => match data {
+                CosemData::OctetString(title) if SystemTitle::from_bytes(&title).is_ok() => {
                     self.client_system_title = title;
                     Some(())
-                } else {
-                    None
                 }
-            }
-            5 => {
-                if let CosemData::OctetString(title) = data {
+                _ => None,
+            },
+            5 => match data {
+                CosemData::OctetString(title) if SystemTitle::from_bytes(&title).is_ok() => {
                     self.server_system_title = title;
                     Some(())
-                } else {
-                    None
                 }
-            }
+                _ => None,
+            },
             _ => None,
         }
     }
 
     fn invoke_method(
         &mut self,
-        _method_id: CosemObjectMethodId,
-        _data: CosemData,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
     ) -> Option<CosemData> {
-        None
+        match method_id {
+            2 => self.key_transfer(&data).ok().map(|_| CosemData::NullData),
+            _ => None,
+        }
     }
 
     fn callbacks(&self) -> Option<Arc<CosemObjectCallbackHandlers>> {
@@ -120,6 +214,7 @@ impl CosemObject for SecuritySetup {
 mod tests {
     extern crate std;
     use super::*;
+    use crate::security::aes_key_wrap;
 
     #[test]
     fn test_security_setup_new() {
@@ -134,6 +229,89 @@ mod tests {
             setup.get_attribute(5),
             Some(CosemData::OctetString(Vec::new()))
         );
+        assert_eq!(setup.server_system_title(), None);
+    }
+
+    #[test]
+    fn test_security_setup_accepts_valid_system_titles_only() {
+        let mut setup = SecuritySetup::new();
+        let title = SystemTitle::new("ABC", 42).unwrap();
+        assert!(setup
+            .set_attribute(5, CosemData::OctetString(title.as_bytes().to_vec()))
+            .is_some());
+        assert_eq!(setup.server_system_title(), Some(title));
+
+        for invalid in [b"abc00042".to_vec(), b"ABC42".to_vec()] {
+            assert!(setup
+                .set_attribute(4, CosemData::OctetString(invalid))
+                .is_none());
+        }
+        assert_eq!(setup.client_system_title(), None);
+    }
+
+    #[test]
+    fn test_security_setup_key_transfer() {
+        let master_key = [0x4D; 16].to_vec();
+        let unicast_key = [0x11; 16].to_vec();
+        let authentication_key = [0x22; 16].to_vec();
+        let key_data = |key_id, key: &[u8]| {
+            CosemData::Structure(vec![
+                CosemData::Enum(key_id),
+                CosemData::OctetString(aes_key_wrap(key, &master_key).unwrap()),
+            ])
+        };
+
+        let mut setup = SecuritySetup::new();
+        let transfer = CosemData::Array(vec![
+            key_data(0, &unicast_key),
+            key_data(2, &authentication_key),
+        ]);
+        assert!(matches!(
+            setup.key_transfer(&transfer),
+            Err(KeyTransferError::NoMasterKey)
+        ));
+
+        setup.set_key(KeyId::Master, master_key.clone());
+        assert_eq!(setup.invoke_method(2, transfer), Some(CosemData::NullData));
+        assert_eq!(
+            setup.key(KeyId::GlobalUnicastEncryption),
+            Some(unicast_key.as_slice())
+        );
+        assert_eq!(
+            setup.key(KeyId::Authentication),
+            Some(authentication_key.as_slice())
+        );
+        assert_eq!(setup.key(KeyId::GlobalBroadcastEncryption), None);
+
+        // One key that does not unwrap leaves all keys as they were.
+        let mut tampered = aes_key_wrap(&[0x33; 16], &master_key).unwrap();
+        tampered[4] ^= 1;
+        let transfer = CosemData::Array(vec![
+            key_data(1, &[0x44; 16]),
+            CosemData::Structure(vec![CosemData::Enum(0), CosemData::OctetString(tampered)]),
+        ]);
+        assert!(matches!(
+            setup.key_transfer(&transfer),
+            Err(KeyTransferError::Unwrap(SecurityError::DecryptionError))
+        ));
+        assert_eq!(setup.key(KeyId::GlobalBroadcastEncryption), None);
+        assert_eq!(
+            setup.key(KeyId::GlobalUnicastEncryption),
+            Some(unicast_key.as_slice())
+        );
+
+        assert!(matches!(
+            setup.key_transfer(&CosemData::Array(vec![key_data(7, &unicast_key)])),
+            Err(KeyTransferError::UnknownKeyId(7))
+        ));
+        assert!(matches!(
+            setup.key_transfer(&CosemData::Array(vec![key_data(0, &[0x55; 24])])),
+            Err(KeyTransferError::Unwrap(SecurityError::InvalidKeyLength))
+        ));
+        assert!(matches!(
+            setup.key_transfer(&CosemData::OctetString(unicast_key)),
+            Err(KeyTransferError::InvalidParameters)
+        ));
     }
 
     #[test]
@@ -146,7 +324,7 @@ mod tests {
         setup.set_attribute(3, CosemData::Unsigned(2)).unwrap();
         assert_eq!(setup.get_attribute(3), Some(CosemData::Unsigned(2)));
 
-        let client_title = b"client".to_vec();
+        let client_title = b"CLIENT01".to_vec();
         setup
             .set_attribute(4, CosemData::OctetString(client_title.clone()))
             .unwrap();
@@ -155,7 +333,7 @@ mod tests {
             Some(CosemData::OctetString(client_title))
         );
 
-        let server_title = b"server".to_vec();
+        let server_title = b"SERVER01".to_vec();
         setup
             .set_attribute(5, CosemData::OctetString(server_title.clone()))
             .unwrap();
@@ -1,23 +1,71 @@
-use crate::acse::{AareApdu, AarqApdu, ArlreApdu, ArlrqApdu};
-use crate::association_ln::{AssociationLN, ObjectListEntry};
-use crate::cosem::{CosemObjectAttributeId, CosemObjectMethodId};
+use crate::acse::{
+    system_title, AareApdu, AarqApdu, ArlreApdu, ArlrqApdu, AssociateSourceDiagnostic,
+};
+use crate::association_ln::{AssociationLN, AssociationStatus, ObjectListEntry};
+use crate::axdr::{decode_data, encode_data, ParseMode};
+use crate::billing::{increment_billing_counter, BillingConfiguration, BillingPeriod};
+#[cfg(feature = "compress")]
+use crate::compression::{compress_notification, Compression};
+use crate::cosem::{
+    CosemAttributeDescriptor, CosemMethodDescriptor, CosemObjectAttributeId, CosemObjectMethodId,
+};
 use crate::cosem_object::{
-    AttributeAccessDescriptor, AttributeAccessMode, CosemObject, MethodAccessDescriptor,
-    MethodAccessMode,
+    validate_attribute_value, AttributeAccessDescriptor, AttributeAccessMode, CosemObject,
+    MethodAccessDescriptor, MethodAccessMode,
 };
+use crate::data_stream::CosemDataStream;
+use crate::diagnostics;
 use crate::error::DlmsError;
-use crate::hdlc::{HdlcFrame, HdlcFrameError};
-use crate::security::lls_authenticate;
-use crate::security::{hls_decrypt, hls_encrypt, SecurityError};
+use crate::hdlc::{
+    is_receive_ready, receive_ready, HdlcAddress, HdlcFrame, HdlcFrameError, HdlcWindow,
+    HDLC_POLL_FINAL,
+};
+use crate::logical_device::{LogicalDeviceName, LOGICAL_DEVICE_NAME_LN};
+use crate::object_model::{ObjectDescription, ObjectModel};
+use crate::object_set::ObjectSet;
+use crate::profile_generic::{append_buffer_entry, capture_object_definitions};
+#[cfg(feature = "compress")]
+use crate::push_setup::PUSH_MESSAGE_COMPRESSED_AXDR;
+use crate::push_setup::{
+    in_communication_window, next_push_opportunity, PendingPush, PushSchedule, PUSH_MESSAGE_AXDR,
+};
+use crate::register_monitor::{MonitoredValue, ScriptReference};
+use crate::scaled_value::ScaledValue;
+use crate::script_table::{script_actions, ScriptService};
+use crate::security::{
+    hls_decrypt, hls_encrypt, AuthenticationProvider, FrameCounter, PasswordAuthentication,
+    SecurityError,
+};
+use crate::security_log::{self, SecurityEvent, SECURITY_EVENT_CODE_LN, SECURITY_LOG_LN};
+use crate::short_name::{ShortNameEntry, ShortNameMap};
+use crate::system_title::SystemTitle;
+use crate::tariff::{DayProfileAction, TariffConfiguration, TariffSchedule};
+use crate::trace::{
+    apdu_name, CorrelationId, PhaseTimings, TraceLayer, TracePhase, TraceRecord, Tracer,
+};
 use crate::transport::Transport;
 use crate::types::CosemData;
 use crate::xdlms::{
-    ActionRequest, ActionResponse, ActionResponseNormal, ActionResult, AssociationParameters,
-    DataAccessResult, GetDataResult, GetRequest, GetResponse, GetResponseNormal, InitiateRequest,
-    InitiateResponse, SetRequest, SetResponse, SetResponseNormal,
+    ActionRequest, ActionRequestNormal, ActionRequestWithFirstPblock, ActionRequestWithPblock,
+    ActionResponse, ActionResponseNextPblock, ActionResponseNormal, ActionResult, Apdus,
+    AssociationParameters, ConfirmedServiceError, DataAccessResult, DataBlockG, DataBlockResult,
+    DataNotification, ExceptionResponse, GetDataResult, GetRequest, GetRequestNext, GetResponse,
+    GetResponseNormal, GetResponseWithDatablock, InitiateError, InitiateRequest, InitiateResponse,
+    InvokeIdAndPriority, ReleaseResponseInformation, SelectiveAccessDescriptor, ServiceError,
+    SetRequest, SetRequestWithDatablock, SetRequestWithFirstDatablock, SetResponse,
+    SetResponseDatablock, SetResponseLastDatablock, SetResponseNormal, StateError,
 };
+use crate::MAX_PDU_SIZE;
+use core::sync::atomic::{AtomicBool, Ordering};
 use rand_core::{OsRng, RngCore};
+use std::borrow::Cow;
+use std::boxed::Box;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
 
 // Clause 6.3 of СТО 34.01-5.1-013-2023 prescribes the standard HDLC client SAPs
 // for public (16), meter reader (32), and configurator (48) associations.
@@ -28,9 +76,212 @@ const CONFIGURATOR_CLIENT_SAP: u16 = 0x0030;
 const PUBLIC_ASSOCIATION_LN: [u8; 6] = [0x00, 0x00, 0x28, 0x00, 0x01, 0xFF];
 const METER_READER_ASSOCIATION_LN: [u8; 6] = [0x00, 0x00, 0x28, 0x00, 0x02, 0xFF];
 const CONFIGURATOR_ASSOCIATION_LN: [u8; 6] = [0x00, 0x00, 0x28, 0x00, 0x03, 0xFF];
-use std::boxed::Box;
-use std::collections::BTreeMap;
-use std::vec::Vec;
+// Reserved name of the association of the requesting client, whichever
+// logical name its association object is registered under.
+const CURRENT_ASSOCIATION_LN: [u8; 6] = [0x00, 0x00, 0x28, 0x00, 0x00, 0xFF];
+
+// Header bytes of get-response-normal (tag, choice, invoke-id, result choice) and
+// get-response-with-datablock (tag, choice, invoke-id, last-block, block-number,
+// result choice and the length of the raw data, at most three octets).
+const GET_RESPONSE_NORMAL_OVERHEAD: usize = 4;
+const GET_RESPONSE_BLOCK_OVERHEAD: usize = 12;
+
+// Octets a long SET or ACTION may accumulate unless configured otherwise.
+const DEFAULT_MAX_LONG_TRANSFER_SIZE: usize = 64 * 1024;
+
+// Release-response-reason not-finished.
+const RELEASE_NOT_FINISHED: u8 = 1;
+
+const AARQ_TAG: u8 = 0x60;
+const RLRQ_TAG: u8 = 0x62;
+const GET_REQUEST_TAG: u8 = 192;
+const SET_REQUEST_TAG: u8 = 193;
+const ACTION_REQUEST_TAG: u8 = 195;
+
+// Metadata of an association handed to the server event hooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssociationInfo {
+    pub client_address: u16,
+    pub logical_name: [u8; 6],
+    pub mechanism_name: Option<Vec<u8>>,
+    pub calling_ap_title: Option<Vec<u8>>,
+}
+
+impl AssociationInfo {
+    // The system title of the client, `None` when it sent none or an invalid
+    // one. Some clients send the title bare instead of as an octet-string.
+    pub fn system_title(&self) -> Option<SystemTitle> {
+        let ap_title = self.calling_ap_title.as_deref()?;
+        SystemTitle::from_bytes(system_title(ap_title).unwrap_or(ap_title)).ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessService {
+    Get,
+    Set,
+    Action,
+}
+
+// A GET, SET or ACTION rejected because the association is missing or the
+// access rights of the object do not allow it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDenied {
+    pub client_address: u16,
+    pub service: AccessService,
+    pub class_id: u16,
+    pub instance_id: [u8; 6],
+    pub index: i8,
+}
+
+// Who may associate: client SAPs, or the peers connections come from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum AccessList<A: Ord> {
+    #[default]
+    AllowAll,
+    Allow(BTreeSet<A>),
+    Deny(BTreeSet<A>),
+}
+
+impl<A: Ord> AccessList<A> {
+    pub fn allows(&self, value: &A) -> bool {
+        match self {
+            AccessList::AllowAll => true,
+            AccessList::Allow(allowed) => allowed.contains(value),
+            AccessList::Deny(denied) => !denied.contains(value),
+        }
+    }
+}
+
+// An attempt turned away by an access list: an AARQ of a client SAP that is
+// not allowed, or a connection from a peer that is not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectedAttempt {
+    ClientSap(u16),
+    Peer(IpAddr),
+}
+
+// An entry added to or removed from the object list of an association, by
+// registering, unregistering or replacing objects. A replaced object whose
+// entry differs is reported as removed, then added.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectListChange {
+    Added {
+        association: [u8; 6],
+        entry: ObjectListEntry,
+    },
+    Removed {
+        association: [u8; 6],
+        entry: ObjectListEntry,
+    },
+}
+
+// Stops `Server::run` from another thread or an interrupt. The server checks
+// it before waiting for each frame, so a run blocked in `receive` stops once
+// the transport returns.
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Frames handled by `Server::step` since the server was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServerStatistics {
+    pub frames_received: u64,
+    pub bytes_received: u64,
+    pub responses_sent: u64,
+    pub bytes_sent: u64,
+}
+
+// One of the communication interfaces a server is reachable over, e.g. the
+// optical port or a TCP wrapper, with the client SAPs it serves and the
+// association object each of them gets there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInterface {
+    id: u8,
+    clients: BTreeMap<u16, [u8; 6]>,
+}
+
+impl ServerInterface {
+    pub fn new(id: u8) -> Self {
+        Self {
+            id,
+            clients: BTreeMap::new(),
+        }
+    }
+
+    pub fn client(mut self, client_sap: u16, association_ln: [u8; 6]) -> Self {
+        self.clients.insert(client_sap, association_ln);
+        self
+    }
+
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    pub fn association_for(&self, client_sap: u16) -> Option<[u8; 6]> {
+        self.clients.get(&client_sap).copied()
+    }
+}
+
+// A watched attribute whose value changed. The values are as `get_attribute`
+// returns them, None for attributes it does not return.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    pub logical_name: [u8; 6],
+    pub attribute_id: CosemObjectAttributeId,
+    pub old_value: Option<CosemData>,
+    pub new_value: Option<CosemData>,
+}
+
+// Associations whose object list (attribute 2) shows an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectVisibility {
+    // Every association, the one of the public client included.
+    Public,
+    // Every association but the one of the public client.
+    Authenticated,
+    // Only the associations with these logical names.
+    Associations(Vec<[u8; 6]>),
+}
+
+impl ObjectVisibility {
+    // What `register_object` assumes: association objects, the logical device
+    // name and the clock are public, everything else needs authentication.
+    pub fn default_for(instance_id: [u8; 6], object: &dyn CosemObject) -> Self {
+        if matches!(object.class_id(), 8 | 15) || instance_id == LOGICAL_DEVICE_NAME_LN {
+            ObjectVisibility::Public
+        } else {
+            ObjectVisibility::Authenticated
+        }
+    }
+
+    fn includes(&self, association: [u8; 6], public: bool) -> bool {
+        match self {
+            ObjectVisibility::Public => true,
+            ObjectVisibility::Authenticated => !public,
+            ObjectVisibility::Associations(associations) => associations.contains(&association),
+        }
+    }
+}
+
+type AssociationCallback = Box<dyn FnMut(&AssociationInfo) + Send>;
+type AccessDeniedCallback = Box<dyn FnMut(&AccessDenied) + Send>;
+type RejectedAttemptCallback = Box<dyn FnMut(&RejectedAttempt) + Send>;
+type AttributeWatchCallback = Box<dyn FnMut(&AttributeChange) + Send>;
+type ObjectListCallback = Box<dyn FnMut(&ObjectListChange) + Send>;
+// Serves one request APDU, chosen by its leading tag, and returns the frame
+// to answer with.
+type ApduHandler<T> =
+    fn(&mut Server<T>, &HdlcFrame) -> Result<Vec<u8>, ServerError<<T as Transport>::Error>>;
 
 #[derive(Debug)]
 pub enum ServerError<E> {
@@ -55,17 +306,195 @@ impl<E> From<DlmsError> for ServerError<E> {
 
 pub struct Server<T: Transport> {
     address: u16,
+    physical_address: Option<u16>,
+    parse_mode: ParseMode,
+    // Set once a window of more than one I-frame is configured; responses to
+    // the frames of an open window wait until the client polls.
+    hdlc_window: Option<HdlcWindow>,
+    windowed_responses: Vec<HdlcFrame>,
     transport: T,
-    password: Option<Vec<u8>>,
+    authentication: Option<Box<dyn AuthenticationProvider>>,
     key: Option<Vec<u8>>,
+    frame_counter: Option<FrameCounter>,
+    // Capacity and time to live of the GET response cache of each
+    // association; no cache without it.
+    response_cache: Option<(usize, Duration)>,
+    duplicate_detection: bool,
+    // Octets the blocks of one long SET or ACTION may add up to.
+    max_long_transfer_size: usize,
     objects: BTreeMap<[u8; 6], Box<dyn CosemObject>>,
+    // Short names assigned as objects are registered, so that removing or
+    // replacing an object leaves those of the others as they were.
+    short_names: ShortNameMap,
     association_logical_names: BTreeMap<u16, [u8; 6]>,
     association_templates: BTreeMap<[u8; 6], AssociationLN>,
     client_association_instances: BTreeMap<u16, Box<dyn CosemObject>>,
-    lls_challenges: BTreeMap<u16, Vec<u8>>,
     association_parameters: AssociationParameters,
-    active_associations: BTreeMap<u16, AssociationContext>,
-    association_object_list: Arc<Mutex<Vec<ObjectListEntry>>>,
+    // Clients missing from the map are idle.
+    associations: BTreeMap<u16, AssociationState>,
+    // Interface the last frame of each client came over; only meaningful
+    // while the client is not idle.
+    client_interfaces: BTreeMap<u16, u8>,
+    // Associations over each open connection, by connection id. Those of the
+    // connection being served are swapped into `associations` meanwhile.
+    connections: BTreeMap<u64, ConnectionAssociations>,
+    // Object list of each association object by logical name, shared with the
+    // instances of the clients.
+    association_object_lists: BTreeMap<[u8; 6], Arc<Mutex<Vec<ObjectListEntry>>>>,
+    object_visibility: BTreeMap<[u8; 6], ObjectVisibility>,
+    // Monitoring objects (register monitors, limiters) by logical name, with the
+    // attribute they watch and the last value they have seen.
+    monitors: BTreeMap<[u8; 6], MonitoredValue>,
+    monitor_values: BTreeMap<[u8; 6], CosemData>,
+    monitor_timestamp: u64,
+    billing: Option<BillingConfiguration>,
+    // Billing profiles capturing a register before it is reset, by register.
+    billing_profiles: BTreeMap<[u8; 6], [u8; 6]>,
+    update_captures: BTreeMap<[u8; 6], Vec<[u8; 6]>>,
+    tariff: Option<TariffConfiguration>,
+    // Day profile action last executed by the tariff engine.
+    tariff_action: Option<DayProfileAction>,
+    // Triggered pushes of push setup objects by logical name.
+    pending_pushes: BTreeMap<[u8; 6], PendingPush>,
+    push_invoke_id: u32,
+    // Compresses the bodies of pushes sent as PUSH_MESSAGE_COMPRESSED_AXDR.
+    #[cfg(feature = "compress")]
+    push_compression: Option<Box<dyn Compression>>,
+    on_association_established: Option<AssociationCallback>,
+    on_association_released: Option<AssociationCallback>,
+    on_authentication_failed: Option<AssociationCallback>,
+    on_access_denied: Option<AccessDeniedCallback>,
+    client_access: AccessList<u16>,
+    peer_access: AccessList<IpAddr>,
+    on_rejected_attempt: Option<RejectedAttemptCallback>,
+    watches: BTreeMap<([u8; 6], CosemObjectAttributeId), Vec<AttributeWatchCallback>>,
+    on_object_list_changed: Option<ObjectListCallback>,
+    // Whether security events are captured into `SECURITY_LOG_LN`.
+    security_log: bool,
+    apdu_handlers: BTreeMap<u8, ApduHandler<T>>,
+    stop: StopHandle,
+    statistics: ServerStatistics,
+    // Encoding buffer kept across requests, so that reading a value does not
+    // allocate once it has grown to the usual size.
+    scratch: Vec<u8>,
+    // Information of the last request and response and the last response
+    // frame, reused the same way; `process_frame_into` hands the frame
+    // buffer back.
+    request_information: Vec<u8>,
+    response_information: Vec<u8>,
+    response_frame: Vec<u8>,
+    tracer: Tracer,
+}
+
+// Assembles a server with its mandatory objects. The logical device name
+// (0.0.42.0.0.255) is registered from the configured name, as conformance
+// tests read it before anything else.
+pub struct ServerBuilder<T: Transport> {
+    address: u16,
+    transport: T,
+    authentication: Option<Box<dyn AuthenticationProvider>>,
+    key: Option<Vec<u8>>,
+    frame_counter: Option<FrameCounter>,
+    logical_device_name: Option<LogicalDeviceName>,
+    object_sets: Vec<ObjectSet>,
+    objects: Vec<([u8; 6], Box<dyn CosemObject>)>,
+    security_log_entries: Option<u32>,
+}
+
+impl<T: Transport> ServerBuilder<T> {
+    pub fn new(address: u16, transport: T) -> Self {
+        Self {
+            address,
+            transport,
+            authentication: None,
+            key: None,
+            frame_counter: None,
+            logical_device_name: None,
+            object_sets: Vec::new(),
+            objects: Vec::new(),
+            security_log_entries: None,
+        }
+    }
+
+    pub fn password(self, password: Vec<u8>) -> Self {
+        self.authentication(Box::new(PasswordAuthentication::new(password)))
+    }
+
+    pub fn authentication(mut self, authentication: Box<dyn AuthenticationProvider>) -> Self {
+        self.authentication = Some(authentication);
+        self
+    }
+
+    pub fn key(mut self, key: Vec<u8>) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn frame_counter(mut self, frame_counter: FrameCounter) -> Self {
+        self.frame_counter = Some(frame_counter);
+        self
+    }
+
+    pub fn logical_device_name(mut self, name: LogicalDeviceName) -> Self {
+        self.logical_device_name = Some(name);
+        self
+    }
+
+    pub fn object(mut self, instance_id: [u8; 6], object: Box<dyn CosemObject>) -> Self {
+        self.objects.push((instance_id, object));
+        self
+    }
+
+    // Registered before the objects added one by one, which replace objects
+    // of the set with the same logical name.
+    pub fn object_set(mut self, set: ObjectSet) -> Self {
+        self.object_sets.push(set);
+        self
+    }
+
+    // Keeps the last `entries` access denials, authentication failures and
+    // key changes in the security log (0.0.99.98.4.255) with their event
+    // code (0.0.96.11.4.255), which replace any objects with those logical
+    // names. Each entry holds the time of the clock, when there is one.
+    pub fn security_log(mut self, entries: u32) -> Self {
+        self.security_log_entries = Some(entries);
+        self
+    }
+
+    pub fn build(self) -> Server<T> {
+        let mut server = Server::with_authentication(
+            self.address,
+            self.transport,
+            self.authentication,
+            self.key,
+        );
+        server.frame_counter = self.frame_counter;
+        if let Some(name) = self.logical_device_name {
+            server.register_object(LOGICAL_DEVICE_NAME_LN, Box::new(name.to_object()));
+        }
+        for set in self.object_sets {
+            server.register_object_set(set);
+        }
+        for (instance_id, object) in self.objects {
+            server.register_object(instance_id, object);
+        }
+        if let Some(entries) = self.security_log_entries {
+            let with_clock = server
+                .objects
+                .get(&security_log::CLOCK_LN)
+                .is_some_and(|clock| clock.class_id() == 8);
+            server.register_object(
+                SECURITY_EVENT_CODE_LN,
+                Box::new(security_log::security_event_code()),
+            );
+            server.register_object(
+                SECURITY_LOG_LN,
+                Box::new(security_log::security_log(entries, with_clock)),
+            );
+            server.security_log = true;
+        }
+        server
+    }
 }
 
 impl<T: Transport> Server<T> {
@@ -75,8 +504,21 @@ impl<T: Transport> Server<T> {
         password: Option<Vec<u8>>,
         key: Option<Vec<u8>>,
     ) -> Self {
-        let association_object_list = Arc::new(Mutex::new(Vec::new()));
-        let auth_mechanism_name = if password.is_some() {
+        let authentication = password.map(|password| {
+            Box::new(PasswordAuthentication::new(password)) as Box<dyn AuthenticationProvider>
+        });
+        Self::with_authentication(address, transport, authentication, key)
+    }
+
+    // Like `new`, with the secrets of authenticated associations kept by
+    // `authentication` rather than as one password.
+    pub fn with_authentication(
+        address: u16,
+        transport: T,
+        authentication: Option<Box<dyn AuthenticationProvider>>,
+        key: Option<Vec<u8>>,
+    ) -> Self {
+        let auth_mechanism_name = if authentication.is_some() {
             b"LLS".to_vec()
         } else {
             b"NO_AUTH".to_vec()
@@ -84,22 +526,69 @@ impl<T: Transport> Server<T> {
 
         let mut server = Server {
             address,
+            physical_address: None,
+            parse_mode: ParseMode::default(),
+            hdlc_window: None,
+            windowed_responses: Vec::new(),
             transport,
-            password,
+            authentication,
             key,
+            frame_counter: None,
+            response_cache: None,
+            duplicate_detection: false,
+            max_long_transfer_size: DEFAULT_MAX_LONG_TRANSFER_SIZE,
             objects: BTreeMap::new(),
+            short_names: ShortNameMap::new(),
             association_logical_names: BTreeMap::new(),
             association_templates: BTreeMap::new(),
             client_association_instances: BTreeMap::new(),
-            lls_challenges: BTreeMap::new(),
             association_parameters: AssociationParameters::default(),
-            active_associations: BTreeMap::new(),
-            association_object_list,
+            associations: BTreeMap::new(),
+            client_interfaces: BTreeMap::new(),
+            connections: BTreeMap::new(),
+            association_object_lists: BTreeMap::new(),
+            object_visibility: BTreeMap::new(),
+            monitors: BTreeMap::new(),
+            monitor_values: BTreeMap::new(),
+            monitor_timestamp: 0,
+            billing: None,
+            billing_profiles: BTreeMap::new(),
+            update_captures: BTreeMap::new(),
+            tariff: None,
+            tariff_action: None,
+            pending_pushes: BTreeMap::new(),
+            push_invoke_id: 0,
+            #[cfg(feature = "compress")]
+            push_compression: None,
+            on_association_established: None,
+            on_association_released: None,
+            on_authentication_failed: None,
+            on_access_denied: None,
+            client_access: AccessList::AllowAll,
+            peer_access: AccessList::AllowAll,
+            on_rejected_attempt: None,
+            watches: BTreeMap::new(),
+            on_object_list_changed: None,
+            security_log: false,
+            apdu_handlers: BTreeMap::from([
+                (AARQ_TAG, Self::handle_aarq as ApduHandler<T>),
+                (RLRQ_TAG, Self::handle_release_request),
+                (GET_REQUEST_TAG, Self::handle_get_request),
+                (SET_REQUEST_TAG, Self::handle_set_request),
+                (ACTION_REQUEST_TAG, Self::handle_action_request),
+            ]),
+            stop: StopHandle::default(),
+            tracer: Tracer::default(),
+            statistics: ServerStatistics::default(),
+            scratch: Vec::new(),
+            request_information: Vec::new(),
+            response_information: Vec::new(),
+            response_frame: Vec::new(),
         };
 
         let mut register_predefined_association = |client_sap: u16, logical_name: [u8; 6]| {
             let association = AssociationLN::new(
-                Arc::clone(&server.association_object_list),
+                Arc::new(Mutex::new(Vec::new())),
                 ((client_sap as u32) << 16) | address as u32,
                 b"LN_WITH_NO_CIPHERING".to_vec(),
                 Vec::new(),
@@ -119,10 +608,201 @@ impl<T: Transport> Server<T> {
         self.association_parameters = params;
     }
 
+    // Physical device address on a multi-drop bus; frames sent to other
+    // physical addresses are ignored. Any physical address is accepted until
+    // one is set.
+    pub fn set_physical_address(&mut self, physical_address: u16) {
+        self.physical_address = Some(physical_address);
+    }
+
+    // Keeps the encoded values of the last `capacity` attributes each
+    // association read for `ttl`, and answers repeated GETs of them without
+    // reading the objects again. A SET or ACTION empties the caches; other
+    // changes of a value show up once its entry has expired. The post-read
+    // callback does not run for cached values.
+    pub fn set_response_cache(&mut self, capacity: usize, ttl: Duration) {
+        self.response_cache = Some((capacity, ttl));
+    }
+
+    // Answers a SET or ACTION repeating the previous request of the
+    // association byte for byte, invoke-id included, with the response sent
+    // for it instead of executing it again, as a client does after losing a
+    // response on a serial line. Only for clients varying the invoke-id of
+    // consecutive requests: identical requests are taken as a retransmission.
+    pub fn set_duplicate_detection(&mut self, enabled: bool) {
+        self.duplicate_detection = enabled;
+    }
+
+    // Size the blocks of a long SET or ACTION may add up to; a transfer
+    // growing beyond it is aborted.
+    pub fn set_max_long_transfer_size(&mut self, size: usize) {
+        self.max_long_transfer_size = size;
+    }
+
+    // How strictly incoming APDUs are decoded; lenient by default.
+    pub fn set_parse_mode(&mut self, mode: ParseMode) {
+        self.parse_mode = mode;
+    }
+
+    // Number of I-frames the client may send before it waits for an answer.
+    // With a window of one every frame is answered on its own and the control
+    // field is left unsequenced.
+    pub fn set_hdlc_window_size(&mut self, size: u8) {
+        self.hdlc_window = (size > 1).then(|| HdlcWindow::new(size));
+        self.windowed_responses.clear();
+    }
+
+    // Takes the physical address from the device_address and the receive
+    // window from window_size_receive of an IEC HDLC setup object (class 23).
+    pub fn apply_hdlc_setup(&mut self, setup: &dyn CosemObject) -> Option<()> {
+        if setup.class_id() != 23 {
+            return None;
+        }
+        let CosemData::LongUnsigned(device_address) = setup.get_attribute(9)? else {
+            return None;
+        };
+        self.physical_address = Some(device_address);
+        if let Some(CosemData::Unsigned(window_size)) = setup.get_attribute(4) {
+            self.set_hdlc_window_size(window_size);
+        }
+        Some(())
+    }
+
+    fn is_addressed_to(&self, destination: &HdlcAddress) -> bool {
+        destination.addresses(
+            self.address,
+            self.physical_address.unwrap_or(destination.lower),
+        )
+    }
+
+    pub fn set_on_association_established<F>(&mut self, callback: F)
+    where
+        F: FnMut(&AssociationInfo) + Send + 'static,
+    {
+        self.on_association_established = Some(Box::new(callback));
+    }
+
+    pub fn set_on_association_released<F>(&mut self, callback: F)
+    where
+        F: FnMut(&AssociationInfo) + Send + 'static,
+    {
+        self.on_association_released = Some(Box::new(callback));
+    }
+
+    pub fn set_on_authentication_failed<F>(&mut self, callback: F)
+    where
+        F: FnMut(&AssociationInfo) + Send + 'static,
+    {
+        self.on_authentication_failed = Some(Box::new(callback));
+    }
+
+    pub fn set_on_access_denied<F>(&mut self, callback: F)
+    where
+        F: FnMut(&AccessDenied) + Send + 'static,
+    {
+        self.on_access_denied = Some(Box::new(callback));
+    }
+
+    // Client SAPs whose AARQs are accepted; the others are rejected with the
+    // no-reason-given diagnostic.
+    pub fn set_client_access(&mut self, access: AccessList<u16>) {
+        self.client_access = access;
+    }
+
+    // Peers a listener serves connections of, e.g. by IP address for the
+    // wrapper over TCP.
+    pub fn set_peer_access(&mut self, access: AccessList<IpAddr>) {
+        self.peer_access = access;
+    }
+
+    pub fn set_on_rejected_attempt<F>(&mut self, callback: F)
+    where
+        F: FnMut(&RejectedAttempt) + Send + 'static,
+    {
+        self.on_rejected_attempt = Some(Box::new(callback));
+    }
+
+    // Client SAPs an association object is registered for, to allow only
+    // those with `set_client_access`.
+    pub fn configured_clients(&self) -> BTreeSet<u16> {
+        self.association_logical_names.keys().copied().collect()
+    }
+
+    // Whether a connection from `peer` is to be served, reporting it to the
+    // hook when it is not.
+    pub fn admits_peer(&mut self, peer: IpAddr) -> bool {
+        if self.peer_access.allows(&peer) {
+            return true;
+        }
+        self.reject_attempt(RejectedAttempt::Peer(peer));
+        false
+    }
+
+    fn reject_attempt(&mut self, attempt: RejectedAttempt) {
+        if let Some(callback) = self.on_rejected_attempt.as_mut() {
+            callback(&attempt);
+        }
+    }
+
+    // Called with a record for each frame and APDU handled, tagged with the
+    // correlation id of the frame received.
+    pub fn set_on_trace<F>(&mut self, callback: F)
+    where
+        F: FnMut(&TraceRecord) + Send + 'static,
+    {
+        self.tracer.set_callback(Some(Box::new(callback)));
+    }
+
+    // Correlation id of the last frame received.
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.tracer.correlation_id()
+    }
+
+    // Time the last frame took per phase; sending the response counts as
+    // transport when the server drives its transport.
+    pub fn timings(&self) -> PhaseTimings {
+        self.tracer.timings()
+    }
+
+    // Calls `callback` whenever the value of the attribute changes, be it by a
+    // SET or ACTION of a client, a script or one of the methods of the server
+    // updating objects. Writes leaving the value as it was are not reported.
+    pub fn watch<F>(
+        &mut self,
+        logical_name: [u8; 6],
+        attribute_id: CosemObjectAttributeId,
+        callback: F,
+    ) where
+        F: FnMut(&AttributeChange) + Send + 'static,
+    {
+        self.watches
+            .entry((logical_name, attribute_id))
+            .or_default()
+            .push(Box::new(callback));
+    }
+
+    // Listed as `ObjectVisibility::default_for` the object.
     pub fn register_object(&mut self, instance_id: [u8; 6], object: Box<dyn CosemObject>) {
+        let visibility = ObjectVisibility::default_for(instance_id, object.as_ref());
+        self.register_object_with_visibility(instance_id, object, visibility);
+    }
+
+    pub fn register_object_with_visibility(
+        &mut self,
+        instance_id: [u8; 6],
+        object: Box<dyn CosemObject>,
+        visibility: ObjectVisibility,
+    ) {
+        self.object_visibility.insert(instance_id, visibility);
         self.register_object_internal(instance_id, object);
     }
 
+    pub fn register_object_set(&mut self, set: ObjectSet) {
+        for (instance_id, object, visibility) in set.into_objects() {
+            self.register_object_with_visibility(instance_id, object, visibility);
+        }
+    }
+
     pub fn register_association_for_client(
         &mut self,
         client_sap: u16,
@@ -133,677 +813,4687 @@ impl<T: Transport> Server<T> {
             .insert(client_sap, logical_name);
         self.association_templates
             .insert(logical_name, association.clone());
+        self.association_object_lists
+            .insert(logical_name, association.object_list());
+        self.object_visibility
+            .insert(logical_name, ObjectVisibility::Public);
         self.register_object_internal(logical_name, Box::new(association));
     }
 
-    pub fn handle_frame(&mut self, request_bytes: &[u8]) -> Result<Vec<u8>, ServerError<T::Error>> {
-        self.handle_request(request_bytes)
-    }
-
-    fn register_object_internal(&mut self, instance_id: [u8; 6], object: Box<dyn CosemObject>) {
-        self.objects.insert(instance_id, object);
+    // Removes an object, dropping it from the object lists and freeing its
+    // short names. Removing an association object also drops the association
+    // of the clients using it.
+    pub fn unregister_object(&mut self, instance_id: [u8; 6]) -> Option<Box<dyn CosemObject>> {
+        let object = self.objects.remove(&instance_id)?;
+        self.clear_response_caches();
+        self.object_visibility.remove(&instance_id);
+        self.short_names.remove(&instance_id);
+        if self.association_templates.remove(&instance_id).is_some() {
+            self.association_object_lists.remove(&instance_id);
+            let clients: Vec<u16> = self
+                .association_logical_names
+                .iter()
+                .filter(|(_, logical_name)| **logical_name == instance_id)
+                .map(|(&client_sap, _)| client_sap)
+                .collect();
+            for client_sap in clients {
+                self.release_association(client_sap);
+                self.association_logical_names.remove(&client_sap);
+            }
+        }
         self.rebuild_association_object_list();
+        self.resolve_monitors();
+        Some(object)
     }
 
-    fn rebuild_association_object_list(&self) {
-        let mut list = self
-            .association_object_list
-            .lock()
-            .expect("association object list poisoned");
-        list.clear();
-        for (logical_name, object) in &self.objects {
-            list.push(ObjectListEntry {
-                class_id: object.class_id(),
-                version: object.version(),
-                logical_name: *logical_name,
-                attribute_access: object.attribute_access_rights(),
-                method_access: object.method_access_rights(),
-            });
+    // Swaps the object registered under `instance_id`, keeping its visibility
+    // and, when the new object fits in them, its short names. The object is
+    // handed back if nothing was registered under that logical name.
+    pub fn replace_object(
+        &mut self,
+        instance_id: [u8; 6],
+        object: Box<dyn CosemObject>,
+    ) -> Result<Box<dyn CosemObject>, Box<dyn CosemObject>> {
+        if !self.objects.contains_key(&instance_id) {
+            return Err(object);
         }
+        self.clear_response_caches();
+        let previous = self
+            .objects
+            .remove(&instance_id)
+            .expect("object checked above");
+        self.register_object_internal(instance_id, object);
+        Ok(previous)
     }
 
-    pub fn run(&mut self) -> Result<(), ServerError<T::Error>> {
-        loop {
-            let request_bytes = self
-                .transport
-                .receive()
-                .map_err(ServerError::TransportError)?;
-            let decrypted_request = if let Some(key) = &self.key {
-                hls_decrypt(&request_bytes, key).map_err(ServerError::SecurityError)?
-            } else {
-                request_bytes
-            };
-            let response_bytes = self.handle_request(&decrypted_request)?;
-            let encrypted_response = if let Some(key) = &self.key {
-                hls_encrypt(&response_bytes, key).map_err(ServerError::SecurityError)?
-            } else {
-                response_bytes
-            };
-            self.transport
-                .send(&encrypted_response)
-                .map_err(ServerError::TransportError)?;
-        }
+    // The registered objects ordered by class, then logical name, as the
+    // Blue Book recommends for the object list.
+    pub fn objects(&self) -> impl Iterator<Item = ([u8; 6], &dyn CosemObject)> {
+        let mut objects: Vec<_> = self
+            .objects
+            .iter()
+            .map(|(logical_name, object)| (*logical_name, object.as_ref()))
+            .collect();
+        objects.sort_by_key(|(logical_name, object)| (object.class_id(), *logical_name));
+        objects.into_iter()
     }
 
-    fn handle_request(&mut self, request_bytes: &[u8]) -> Result<Vec<u8>, ServerError<T::Error>> {
-        let request_frame = HdlcFrame::from_bytes(request_bytes)?;
+    // Client addresses with an established association, not counting those
+    // established over connections.
+    pub fn associated_clients(&self) -> Vec<u16> {
+        established_clients(&self.associations)
+    }
 
-        if request_frame.information.len()
-            > self.association_parameters.max_receive_pdu_size as usize
-        {
-            return Err(ServerError::DlmsError(DlmsError::Xdlms));
+    // Client addresses with an association established over a connection.
+    pub fn connection_clients(&self, connection: u64) -> Vec<u16> {
+        self.connections
+            .get(&connection)
+            .map(|state| established_clients(&state.associations))
+            .unwrap_or_default()
+    }
+
+    // Interface a client that is not idle is served over, `None` if it was
+    // reached through `process_frame` alone.
+    pub fn interface_of(&self, client_address: u16) -> Option<u8> {
+        if !self.associations.contains_key(&client_address) {
+            return None;
         }
+        self.client_interfaces.get(&client_address).copied()
+    }
 
-        let mut pending_client_limit = None;
-        let response_bytes = if let Ok((_, aarq_apdu)) =
-            AarqApdu::from_bytes(&request_frame.information)
+    // Drops the association of a client as a release request would, e.g. when
+    // the connection it was established on closed. `false` if there was none.
+    pub fn release_association(&mut self, client_address: u16) -> bool {
+        self.client_association_instances.remove(&client_address);
+        let Some(AssociationState::Associated { negotiated }) =
+            self.transition(client_address, AssociationState::Releasing)
+        else {
+            // A challenge still waiting for its answer is dropped as well.
+            self.transition(client_address, AssociationState::Idle);
+            return false;
+        };
+        if let (Some(info), Some(callback)) =
+            (negotiated.info, self.on_association_released.as_mut())
         {
-            let initiate_request =
-                InitiateRequest::from_user_information(&aarq_apdu.user_information)?;
-            pending_client_limit = Some(initiate_request.client_max_receive_pdu_size);
-            let negotiation = self.negotiate_initiate_response(&initiate_request);
-            let mut aare = AareApdu {
-                application_context_name: aarq_apdu.application_context_name.clone(),
-                result: 0,
-                result_source_diagnostic: 0,
-                responding_authentication_value: None,
-                user_information: Vec::new(),
-            };
-            let mut negotiation_succeeded = false;
+            callback(&info);
+        }
+        self.transition(client_address, AssociationState::Idle);
+        true
+    }
 
-            match negotiation {
-                Ok(initiate_response) => {
-                    aare.user_information = initiate_response.to_user_information()?;
-                    negotiation_succeeded = true;
-                }
-                Err(err) => {
-                    aare.result = 1;
-                    aare.result_source_diagnostic = err.diagnostic();
-                    aare.user_information = self
-                        .association_parameters
-                        .to_initiate_response(self.association_parameters.conformance.clone())
-                        .to_user_information()?;
-                }
-            }
+    pub fn handle_frame(&mut self, request_bytes: &[u8]) -> Result<Vec<u8>, ServerError<T::Error>> {
+        self.handle_request(request_bytes)
+    }
 
-            let association_address = request_frame.address;
-            if aare.result != 0 {
-                self.active_associations.remove(&association_address);
-                self.client_association_instances
-                    .remove(&association_address);
-                return Ok(HdlcFrame {
-                    address: self.address,
-                    control: 0,
-                    information: aare.to_bytes()?,
+    pub fn export_object_model(&self) -> ObjectModel {
+        let objects = self
+            .objects()
+            .map(|(logical_name, object)| {
+                let attribute_access = object.attribute_access_rights();
+                let attribute_values = attribute_access
+                    .iter()
+                    .filter(|descriptor| descriptor.attribute_id != 1)
+                    .filter_map(|descriptor| {
+                        object
+                            .get_attribute(descriptor.attribute_id)
+                            .map(|value| (descriptor.attribute_id, value))
+                    })
+                    .collect();
+                ObjectDescription {
+                    class_id: object.class_id(),
+                    version: object.version(),
+                    logical_name,
+                    attribute_access,
+                    method_access: object.method_access_rights(),
+                    attribute_values,
                 }
-                .to_bytes()?);
-            }
-            if let (Some(password), Some(mechanism_name)) =
-                (&self.password, aarq_apdu.mechanism_name.as_ref())
-            {
-                let association_address = request_frame.address;
-                if mechanism_name == b"LLS" {
-                    if let Some(auth_value) = aarq_apdu.calling_authentication_value.clone() {
-                        if let Some(challenge) = self.lls_challenges.get(&association_address) {
-                            match lls_authenticate(password, challenge) {
-                                Ok(expected_response) => {
-                                    if auth_value == expected_response {
-                                        aare.result = 0; // success
-                                        self.lls_challenges.remove(&association_address);
-                                    } else {
-                                        aare.result = 1; // failure
-                                    }
-                                }
-                                Err(_) => aare.result = 1, // failure
-                            }
-                        } else {
-                            aare.result = 1; // failure due to missing challenge
-                        }
-                    } else {
-                        let mut challenge = vec![0u8; 16];
-                        OsRng.fill_bytes(&mut challenge);
-                        self.lls_challenges
-                            .insert(association_address, challenge.clone());
-                        aare.responding_authentication_value = Some(challenge);
-                        self.active_associations.remove(&association_address);
-                        self.client_association_instances
-                            .remove(&association_address);
-                    }
+            })
+            .collect();
+        ObjectModel { objects }
+    }
+
+    pub fn import_object_model(&mut self, model: &ObjectModel) -> usize {
+        let mut applied = 0;
+        for description in &model.objects {
+            let object = match self.objects.get_mut(&description.logical_name) {
+                Some(object) if object.class_id() == description.class_id => object,
+                _ => continue,
+            };
+            for (attribute_id, value) in &description.attribute_values {
+                if object.set_attribute(*attribute_id, value.clone()).is_some() {
+                    applied += 1;
                 }
             }
-            if aare.responding_authentication_value.is_none() && negotiation_succeeded {
-                self.active_associations.insert(
-                    association_address,
-                    AssociationContext {
-                        client_max_receive_pdu_size: initiate_request.client_max_receive_pdu_size,
-                    },
-                );
-
-                let logical_name = if let Some(&logical_name) =
-                    self.association_logical_names.get(&association_address)
-                {
-                    logical_name
-                } else {
-                    self.association_logical_names
-                        .insert(association_address, PUBLIC_ASSOCIATION_LN);
-                    PUBLIC_ASSOCIATION_LN
-                };
+        }
+        applied
+    }
 
-                let template = self
-                    .association_templates
-                    .get(&logical_name)
-                    .cloned()
-                    .or_else(|| {
-                        self.association_templates
-                            .get(&PUBLIC_ASSOCIATION_LN)
-                            .cloned()
-                    });
+    // Fails with the error of the first object short names could not be
+    // allocated for when it was registered.
+    pub fn short_name_map(&self) -> Result<ShortNameMap, DlmsError> {
+        let mut map = self.short_names.clone();
+        for (logical_name, object) in self.objects() {
+            if map.entry_for(&logical_name).is_none() {
+                let (attribute_count, method_count) = short_name_counts(object);
+                map.allocate(
+                    object.class_id(),
+                    logical_name,
+                    attribute_count,
+                    method_count,
+                )?;
+            }
+        }
+        Ok(map)
+    }
 
-                let Some(template) = template else {
-                    self.client_association_instances
-                        .remove(&association_address);
-                    self.active_associations.remove(&association_address);
-                    return Err(ServerError::DlmsError(DlmsError::Xdlms));
-                };
+    // Captures the current values of the capture objects of a profile generic
+    // object into a new buffer entry.
+    pub fn capture_profile(&mut self, logical_name: [u8; 6]) -> Option<()> {
+        let watched = self.watched_values(None, logical_name);
+        let mut profile = self.objects.remove(&logical_name)?;
+        let entry = profile
+            .get_attribute(3)
+            .as_ref()
+            .and_then(capture_object_definitions)
+            .and_then(|definitions| {
+                definitions
+                    .iter()
+                    .map(|definition| {
+                        let object = self.objects.get(&definition.logical_name)?;
+                        definition.capture(object.as_ref())
+                    })
+                    .collect::<Option<Vec<_>>>()
+            });
+        let result = entry
+            .and_then(|entry| append_buffer_entry(profile.as_mut(), CosemData::Structure(entry)));
+        self.objects.insert(logical_name, profile);
+        self.notify_watchers(None, logical_name, watched);
+        result
+    }
 
-                let partners_id = ((association_address as u32) << 16) | self.address as u32;
+    // Archives the value of a register, extended register or demand register
+    // into a profile generic object before each reset: the profile captures
+    // its capture objects, which should include the register.
+    pub fn link_billing_profile(&mut self, register_ln: [u8; 6], profile_ln: [u8; 6]) {
+        self.billing_profiles.insert(register_ln, profile_ln);
+    }
 
-                let entry = self
-                    .client_association_instances
-                    .entry(association_address)
-                    .or_insert_with(|| Box::new(template.clone()) as Box<dyn CosemObject>);
+    // Scripts executing capture (method 2) on the billing profile end the
+    // billing period instead of only capturing it.
+    pub fn configure_billing(&mut self, configuration: BillingConfiguration) {
+        self.billing = Some(configuration);
+    }
 
-                let _ = entry
-                    .as_mut()
-                    .set_attribute(3, CosemData::DoubleLongUnsigned(partners_id));
+    // End of billing period (MD reset): captures the billing registers into
+    // the billing profile, increments the billing period counter and resets
+    // the demand registers, stopping at the first step that fails.
+    pub fn end_of_billing_period(&mut self) -> Option<()> {
+        let billing = self.billing.clone()?;
+        self.capture_profile(billing.profile)?;
+        if let Some(counter_ln) = billing.counter {
+            let watched = self.watched_values(None, counter_ln);
+            let incremented =
+                increment_billing_counter(self.objects.get_mut(&counter_ln)?.as_mut());
+            self.notify_watchers(None, counter_ln, watched);
+            incremented?;
+        }
+        for register_ln in billing.demand_registers {
+            if self.objects.get(&register_ln)?.class_id() != 5 {
+                return None;
             }
-            aare.to_bytes()?
-        } else if let Ok((_, release_req)) = ArlrqApdu::from_bytes(&request_frame.information) {
-            self.active_associations.remove(&request_frame.address);
-            self.lls_challenges.remove(&request_frame.address);
-            self.client_association_instances
-                .remove(&request_frame.address);
-
-            let reason = release_req.reason.unwrap_or(0);
-            let rlre = ArlreApdu {
-                reason: Some(reason),
-                user_information: release_req.user_information,
-            };
+            self.invoke_object_method(register_ln, 1, CosemData::Integer(0))?;
+        }
+        Some(())
+    }
 
-            rlre.to_bytes()?
-        } else if let Ok(get_req) = GetRequest::from_bytes(&request_frame.information) {
-            let GetRequest::Normal(get_req) = get_req else {
-                return Err(ServerError::DlmsError(DlmsError::Xdlms));
-            };
+    // Capture (method 2) of a profile generic object invoked by a script or
+    // a client: the billing profile ends the billing period.
+    fn capture_requested_profile(&mut self, logical_name: [u8; 6]) -> Option<()> {
+        if self
+            .billing
+            .as_ref()
+            .is_some_and(|billing| billing.profile == logical_name)
+        {
+            self.end_of_billing_period()
+        } else {
+            self.capture_profile(logical_name)
+        }
+    }
 
-            if !self
-                .active_associations
-                .contains_key(&request_frame.address)
-            {
-                let denial = GetResponse::Normal(GetResponseNormal {
-                    invoke_id_and_priority: get_req.invoke_id_and_priority,
-                    result: GetDataResult::DataAccessResult(DataAccessResult::ReadWriteDenied),
-                });
-                denial.to_bytes()?
-            } else {
-                let instance_id = get_req.cosem_attribute_descriptor.instance_id;
-                let Some(object) = self.resolve_object(request_frame.address, instance_id) else {
-                    return Err(ServerError::DlmsError(DlmsError::Xdlms));
-                };
-
-                let attribute_access = object.attribute_access_rights();
-                let attribute_id = get_req.cosem_attribute_descriptor.attribute_id;
-                if !Self::attribute_operation_allowed(
-                    &attribute_access,
-                    attribute_id,
-                    AttributeOperation::Read,
-                ) {
-                    let denial = GetResponse::Normal(GetResponseNormal {
-                        invoke_id_and_priority: get_req.invoke_id_and_priority,
-                        result: GetDataResult::DataAccessResult(DataAccessResult::ReadWriteDenied),
-                    });
-                    denial.to_bytes()?
-                } else {
-                    if let Some(callbacks) = object.callbacks() {
-                        if let Err(result_code) = callbacks.call_pre_read(&*object, attribute_id) {
-                            let denial = GetResponse::Normal(GetResponseNormal {
-                                invoke_id_and_priority: get_req.invoke_id_and_priority,
-                                result: GetDataResult::DataAccessResult(result_code),
-                            });
-                            return self.build_response_frame(denial.to_bytes()?);
-                        }
-                    }
+    // Reads an attribute of a past billing period instance of a register, one
+    // with value group F of 0..=99 or 101..=125. The access rights are those
+    // of the current instance. `None` for instances of no billing period.
+    fn read_billing_period(
+        &mut self,
+        client_address: u16,
+        descriptor: &CosemAttributeDescriptor,
+    ) -> Option<GetDataResult> {
+        let period = BillingPeriod::of(descriptor.instance_id)
+            .filter(|period| *period != BillingPeriod::Current)?;
+        let current_ln = BillingPeriod::Current.instance(descriptor.instance_id);
+        let undefined = GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined);
+        let Some(current) = self
+            .objects
+            .get(&current_ln)
+            .filter(|object| object.class_id() == descriptor.class_id)
+        else {
+            return Some(undefined);
+        };
+        if !Self::attribute_operation_allowed(
+            &current.attribute_access_rights(),
+            descriptor.attribute_id,
+            AttributeOperation::Read,
+        ) {
+            self.access_denied(client_address, AccessService::Get, descriptor);
+            return Some(GetDataResult::DataAccessResult(
+                DataAccessResult::ReadWriteDenied,
+            ));
+        }
+        let value = match descriptor.attribute_id {
+            1 => Some(CosemData::OctetString(descriptor.instance_id.to_vec())),
+            // The scaler and unit hold for every period.
+            3 if matches!(descriptor.class_id, 3..=5) => current.get_attribute(3),
+            _ => self.billing_period_value(
+                period,
+                descriptor.class_id,
+                current_ln,
+                descriptor.attribute_id,
+            ),
+        };
+        Some(value.map_or(undefined, GetDataResult::Data))
+    }
 
-                    let mut result = object.get_attribute(attribute_id);
+    // The value of an attribute of a register in the entry of a billing
+    // period: from the profile linked to the register, or else the configured
+    // billing profile. `None` when the period has no entry or the profile does
+    // not capture the attribute.
+    fn billing_period_value(
+        &self,
+        period: BillingPeriod,
+        class_id: u16,
+        logical_name: [u8; 6],
+        attribute_id: CosemObjectAttributeId,
+    ) -> Option<CosemData> {
+        let profile_ln = self
+            .billing_profiles
+            .get(&logical_name)
+            .copied()
+            .or(self.billing.as_ref().map(|billing| billing.profile))?;
+        let profile = self.objects.get(&profile_ln)?;
+        let column = capture_object_definitions(&profile.get_attribute(3)?)?
+            .iter()
+            .position(|definition| {
+                definition.class_id == class_id
+                    && definition.logical_name == logical_name
+                    && definition.attribute_index == attribute_id
+                    && definition.data_index == 0
+            })?;
+        let counter = self
+            .billing
+            .as_ref()
+            .and_then(|billing| billing.counter)
+            .and_then(|counter_ln| self.objects.get(&counter_ln))
+            .and_then(|counter| counter.get_attribute(2))
+            .and_then(|value| value.as_u64());
+        let entry = match profile.buffer() {
+            Some(buffer) => {
+                let index = period.entry_index(buffer.len(), counter)?;
+                buffer.entries(index..index + 1).next()?
+            }
+            None => {
+                let CosemData::Array(entries) = profile.get_attribute(2)? else {
+                    return None;
+                };
+                let index = period.entry_index(entries.len(), counter)?;
+                entries.into_iter().nth(index)?
+            }
+        };
+        match entry {
+            CosemData::Structure(values) => values.into_iter().nth(column),
+            _ => None,
+        }
+    }
 
-                    if let Some(callbacks) = object.callbacks() {
-                        if let Err(result_code) =
-                            callbacks.call_post_read(&*object, attribute_id, &mut result)
-                        {
-                            let denial = GetResponse::Normal(GetResponseNormal {
-                                invoke_id_and_priority: get_req.invoke_id_and_priority,
-                                result: GetDataResult::DataAccessResult(result_code),
-                            });
-                            return self.build_response_frame(denial.to_bytes()?);
-                        }
-                    }
+    fn archive_before_reset(&mut self, logical_name: [u8; 6], method_id: CosemObjectMethodId) {
+        let is_reset = method_id == 1
+            && self
+                .objects
+                .get(&logical_name)
+                .is_some_and(|object| matches!(object.class_id(), 3..=5));
+        if let Some(profile_ln) = self
+            .billing_profiles
+            .get(&logical_name)
+            .filter(|_| is_reset)
+        {
+            self.capture_profile(*profile_ln);
+        }
+    }
 
-                    let get_res = GetResponse::Normal(GetResponseNormal {
-                        invoke_id_and_priority: get_req.invoke_id_and_priority,
-                        result: result.map_or(
-                            GetDataResult::DataAccessResult(DataAccessResult::ObjectUnavailable),
-                            GetDataResult::Data,
-                        ),
-                    });
-                    get_res.to_bytes()?
-                }
+    // Runs the actions of a script of a script table (class 9) against the
+    // registered objects, stopping at the first action that fails.
+    pub fn execute_script(&mut self, logical_name: [u8; 6], script_id: u16) -> Option<()> {
+        let actions = script_actions(self.objects.get(&logical_name)?.as_ref(), script_id)?;
+        for action in actions {
+            if matches!(action.service, ScriptService::ExecuteMethod) {
+                self.archive_before_reset(action.logical_name, action.index as CosemObjectMethodId);
             }
-        } else if let Ok(set_req) = SetRequest::from_bytes(&request_frame.information) {
-            let SetRequest::Normal(set_req) = set_req else {
-                return Err(ServerError::DlmsError(DlmsError::Xdlms));
+            let object = self.objects.get_mut(&action.logical_name)?;
+            if object.class_id() != action.class_id || action.index < 1 {
+                return None;
+            }
+            // Profile generic capture needs the other objects of the device.
+            if matches!(action.service, ScriptService::ExecuteMethod)
+                && action.class_id == 7
+                && action.index == 2
+            {
+                self.capture_requested_profile(action.logical_name)?;
+                continue;
+            }
+            let watched = self.watched_values(None, action.logical_name);
+            let object = self.objects.get_mut(&action.logical_name)?;
+            let executed = match action.service {
+                ScriptService::WriteAttribute => {
+                    object.set_attribute(action.index, action.parameter)
+                }
+                ScriptService::ExecuteMethod => object
+                    .invoke_method(action.index as CosemObjectMethodId, action.parameter)
+                    .map(|_| ()),
             };
+            self.notify_watchers(None, action.logical_name, watched);
+            executed?;
+        }
+        Some(())
+    }
 
-            if !self
-                .active_associations
-                .contains_key(&request_frame.address)
-            {
-                let denial = SetResponse::Normal(SetResponseNormal {
-                    invoke_id_and_priority: set_req.invoke_id_and_priority,
-                    result: DataAccessResult::ReadWriteDenied,
-                });
-                denial.to_bytes()?
-            } else {
-                let instance_id = set_req.cosem_attribute_descriptor.instance_id;
-                let Some(object) = self.resolve_object(request_frame.address, instance_id) else {
-                    return Err(ServerError::DlmsError(DlmsError::Xdlms));
-                };
+    pub fn configure_tariff(&mut self, configuration: TariffConfiguration) {
+        self.tariff = Some(configuration);
+        self.tariff_action = None;
+    }
 
-                let attribute_access = object.attribute_access_rights();
-                let attribute_id = set_req.cosem_attribute_descriptor.attribute_id;
-                if !Self::attribute_operation_allowed(
-                    &attribute_access,
-                    attribute_id,
-                    AttributeOperation::Write,
-                ) {
-                    let denial = SetResponse::Normal(SetResponseNormal {
-                        invoke_id_and_priority: set_req.invoke_id_and_priority,
-                        result: DataAccessResult::ReadWriteDenied,
-                    });
-                    denial.to_bytes()?
-                } else {
-                    let mut value = set_req.value;
-                    if let Some(callbacks) = object.callbacks() {
-                        if let Err(result_code) =
-                            callbacks.call_pre_write(object, attribute_id, &mut value)
-                        {
-                            let denial = SetResponse::Normal(SetResponseNormal {
-                                invoke_id_and_priority: set_req.invoke_id_and_priority,
-                                result: result_code,
-                            });
-                            return self.build_response_frame(denial.to_bytes()?);
-                        }
-                    }
+    // Tariff rate (script selector) in force at the date-time according to the
+    // activity calendar. When the day profile action changed since the last
+    // update its script is executed, e.g. switching the active mask of the
+    // register activation, and the rate is written to the current tariff.
+    pub fn update_tariff_at(&mut self, date_time: &[u8]) -> Option<u16> {
+        let tariff = self.tariff.clone()?;
+        let schedule = TariffSchedule::from_calendar(self.objects.get(&tariff.calendar)?.as_ref())?;
+        let action = schedule.action_at(date_time)?;
+        if self.tariff_action != Some(action) {
+            self.execute_script(action.script_logical_name, action.script_selector)?;
+            if let Some(current_ln) = tariff.current_tariff {
+                self.set_object_attribute(
+                    current_ln,
+                    2,
+                    CosemData::LongUnsigned(action.script_selector),
+                )?;
+            }
+            self.tariff_action = Some(action);
+        }
+        Some(action.script_selector)
+    }
 
-                    let result = object.set_attribute(attribute_id, value.clone());
-                    let response_code = result.map_or(DataAccessResult::ObjectUnavailable, |_| {
-                        if let Some(callbacks) = object.callbacks() {
-                            if let Err(result_code) =
-                                callbacks.call_post_write(object, attribute_id, &value)
-                            {
-                                return result_code;
-                            }
-                        }
-                        DataAccessResult::Success
-                    });
-                    let set_res = SetResponse::Normal(SetResponseNormal {
-                        invoke_id_and_priority: set_req.invoke_id_and_priority,
-                        result: response_code,
-                    });
-                    set_res.to_bytes()?
-                }
+    // Same as `update_tariff_at` with the time of the configured clock.
+    pub fn update_tariff(&mut self) -> Option<u16> {
+        let clock_ln = self.tariff.as_ref()?.clock;
+        let date_time = match self.objects.get(&clock_ln)?.get_attribute(2)? {
+            CosemData::DateTime(bytes) | CosemData::OctetString(bytes) => bytes,
+            _ => return None,
+        };
+        self.update_tariff_at(&date_time)
+    }
+
+    // To be called by the host after an attribute value changed. Monitoring
+    // objects watching the attribute compare `previous` against the current
+    // value and the scripts they trigger are run. Returns the number of scripts
+    // executed.
+    pub fn monitored_value_changed(
+        &mut self,
+        logical_name: [u8; 6],
+        attribute_id: CosemObjectAttributeId,
+        previous: &CosemData,
+    ) -> usize {
+        let Some(current) = self
+            .objects
+            .get(&logical_name)
+            .and_then(|object| object.get_attribute(attribute_id))
+        else {
+            return 0;
+        };
+        let monitors: Vec<[u8; 6]> = self
+            .monitors
+            .iter()
+            .filter(|(_, monitored)| {
+                monitored.logical_name == logical_name && monitored.attribute_index == attribute_id
+            })
+            .map(|(monitor_ln, _)| *monitor_ln)
+            .collect();
+
+        let mut scripts = Vec::new();
+        for monitor_ln in monitors {
+            self.monitor_values.insert(monitor_ln, current.clone());
+            if let Some(monitor) = self.objects.get_mut(&monitor_ln) {
+                scripts.extend(monitor.evaluate_monitored_value(
+                    previous,
+                    &current,
+                    self.monitor_timestamp,
+                ));
             }
-        } else if let Ok(action_req) = ActionRequest::from_bytes(&request_frame.information) {
-            let ActionRequest::Normal(action_req) = action_req else {
-                return Err(ServerError::DlmsError(DlmsError::Xdlms));
+        }
+        self.run_scripts(scripts)
+    }
+
+    // Feeds every monitoring object the current value of the attribute it
+    // watches. Limiters only act once a value stayed beyond their threshold for
+    // the configured duration, so the host calls this periodically with a
+    // monotonic time in seconds. Returns the number of scripts executed.
+    pub fn evaluate_monitors(&mut self, timestamp: u64) -> usize {
+        self.monitor_timestamp = timestamp;
+        let monitors: Vec<([u8; 6], MonitoredValue)> = self
+            .monitors
+            .iter()
+            .map(|(monitor_ln, monitored)| (*monitor_ln, monitored.clone()))
+            .collect();
+
+        let mut scripts = Vec::new();
+        for (monitor_ln, monitored) in monitors {
+            let Some(current) = self
+                .objects
+                .get(&monitored.logical_name)
+                .and_then(|object| object.get_attribute(monitored.attribute_index))
+            else {
+                continue;
             };
+            let previous = self
+                .monitor_values
+                .insert(monitor_ln, current.clone())
+                .unwrap_or_else(|| current.clone());
+            if let Some(monitor) = self.objects.get_mut(&monitor_ln) {
+                scripts.extend(monitor.evaluate_monitored_value(&previous, &current, timestamp));
+            }
+        }
+        self.run_scripts(scripts)
+    }
 
-            if !self
-                .active_associations
-                .contains_key(&request_frame.address)
-            {
-                let denial = ActionResponse::Normal(ActionResponseNormal {
-                    invoke_id_and_priority: action_req.invoke_id_and_priority,
-                    single_response: crate::xdlms::ActionResponseWithOptionalData {
-                        result: ActionResult::ReadWriteDenied,
-                        return_parameters: None,
-                    },
-                });
-                denial.to_bytes()?
-            } else {
-                let instance_id = action_req.cosem_method_descriptor.instance_id;
-                let Some(object) = self.resolve_object(request_frame.address, instance_id) else {
-                    return Err(ServerError::DlmsError(DlmsError::Xdlms));
-                };
+    // Schedules a push of a push setup object (class 40) triggered at `now`:
+    // the first attempt is made at the next opening of its communication
+    // window, delayed by a random share of randomisation_start_interval.
+    pub fn trigger_push(&mut self, logical_name: [u8; 6], now: u64) -> Option<()> {
+        let schedule = PushSchedule::from_push_setup(self.objects.get(&logical_name)?.as_ref())?;
+        let due = schedule.first_attempt(now, OsRng.next_u64())?;
+        self.pending_pushes
+            .insert(logical_name, PendingPush { due, attempts: 0 });
+        Some(())
+    }
 
-                let method_access = object.method_access_rights();
-                let method_id = action_req.cosem_method_descriptor.method_id;
-                if !Self::method_operation_allowed(&method_access, method_id) {
-                    let denial = ActionResponse::Normal(ActionResponseNormal {
-                        invoke_id_and_priority: action_req.invoke_id_and_priority,
-                        single_response: crate::xdlms::ActionResponseWithOptionalData {
-                            result: ActionResult::ReadWriteDenied,
-                            return_parameters: None,
-                        },
-                    });
-                    denial.to_bytes()?
-                } else {
-                    let mut parameters = action_req
-                        .method_invocation_parameters
-                        .unwrap_or(crate::types::CosemData::NullData);
-                    if let Some(callbacks) = object.callbacks() {
-                        if let Err(result_code) =
-                            callbacks.call_pre_action(object, method_id, &mut parameters)
-                        {
-                            let denial = ActionResponse::Normal(ActionResponseNormal {
-                                invoke_id_and_priority: action_req.invoke_id_and_priority,
-                                single_response: crate::xdlms::ActionResponseWithOptionalData {
-                                    result: result_code,
-                                    return_parameters: None,
-                                },
-                            });
-                            return self.build_response_frame(denial.to_bytes()?);
-                        }
-                    }
+    // Push setups with the message type PUSH_MESSAGE_COMPRESSED_AXDR are not
+    // sent without one.
+    #[cfg(feature = "compress")]
+    pub fn set_push_compression(&mut self, compression: Option<Box<dyn Compression>>) {
+        self.push_compression = compression;
+    }
 
-                    let mut result = object.invoke_method(method_id, parameters);
+    pub fn pending_push(&self, logical_name: [u8; 6]) -> Option<PendingPush> {
+        self.pending_pushes.get(&logical_name).copied()
+    }
 
-                    if let Some(callbacks) = object.callbacks() {
-                        if let Err(result_code) =
-                            callbacks.call_post_action(object, method_id, &mut result)
-                        {
-                            let denial = ActionResponse::Normal(ActionResponseNormal {
-                                invoke_id_and_priority: action_req.invoke_id_and_priority,
-                                single_response: crate::xdlms::ActionResponseWithOptionalData {
-                                    result: result_code,
-                                    return_parameters: None,
-                                },
-                            });
-                            return self.build_response_frame(denial.to_bytes()?);
-                        }
+    // Sends the data-notifications of the pushes due at `now` over `transport`,
+    // e.g. a `UdpTransport` to the push destination. A failed attempt is
+    // repeated after repetition_delay until number_of_retries is exhausted, and
+    // attempts falling outside the communication window wait for its next
+    // opening. Returns the number of notifications sent.
+    pub fn poll_pushes<P: Transport>(&mut self, now: u64, transport: &mut P) -> usize {
+        let due: Vec<([u8; 6], PendingPush)> = self
+            .pending_pushes
+            .iter()
+            .filter(|(_, pending)| pending.due <= now)
+            .map(|(logical_name, pending)| (*logical_name, *pending))
+            .collect();
+
+        let mut sent = 0;
+        for (logical_name, mut pending) in due {
+            let Some(schedule) = self
+                .objects
+                .get(&logical_name)
+                .and_then(|setup| PushSchedule::from_push_setup(setup.as_ref()))
+            else {
+                self.pending_pushes.remove(&logical_name);
+                continue;
+            };
+            if !in_communication_window(&schedule.windows, now) {
+                match next_push_opportunity(&schedule.windows, now) {
+                    Some(opening) => {
+                        pending.due = opening;
+                        self.pending_pushes.insert(logical_name, pending);
+                    }
+                    None => {
+                        self.pending_pushes.remove(&logical_name);
                     }
-                    let action_res = ActionResponse::Normal(ActionResponseNormal {
-                        invoke_id_and_priority: action_req.invoke_id_and_priority,
-                        single_response: crate::xdlms::ActionResponseWithOptionalData {
-                            result: result
-                                .as_ref()
-                                .map_or(ActionResult::ObjectUnavailable, |_| ActionResult::Success),
-                            return_parameters: result.map(GetDataResult::Data),
-                        },
-                    });
-                    action_res.to_bytes()?
                 }
+                continue;
             }
-        } else {
-            return Err(ServerError::DlmsError(DlmsError::Xdlms));
-        };
 
-        let response_hdlc_frame = HdlcFrame {
-            address: self.address,
-            control: 0,
-            information: response_bytes,
-        };
+            let delivered = self
+                .push_notification(&schedule)
+                .is_some_and(|bytes| transport.send(&bytes).is_ok());
+            if delivered {
+                sent += 1;
+                self.pending_pushes.remove(&logical_name);
+            } else if pending.attempts < schedule.number_of_retries {
+                pending.attempts += 1;
+                pending.due = now + schedule.repetition_delay;
+                self.pending_pushes.insert(logical_name, pending);
+            } else {
+                self.pending_pushes.remove(&logical_name);
+            }
+        }
+        sent
+    }
 
-        let client_limit = pending_client_limit
-            .or_else(|| {
-                self.active_associations
-                    .get(&request_frame.address)
-                    .map(|ctx| ctx.client_max_receive_pdu_size)
+    // Data-notification carrying the values of the push object list.
+    fn push_notification(&mut self, schedule: &PushSchedule) -> Option<Vec<u8>> {
+        let values = schedule
+            .push_objects
+            .iter()
+            .map(|definition| {
+                let object = self.objects.get(&definition.logical_name)?;
+                definition.capture(object.as_ref())
             })
-            .unwrap_or(self.association_parameters.max_receive_pdu_size)
-            as usize;
-
-        if response_hdlc_frame.information.len() > client_limit {
-            return Err(ServerError::DlmsError(DlmsError::Xdlms));
+            .collect::<Option<Vec<_>>>()?;
+        self.push_invoke_id = (self.push_invoke_id + 1) & 0x00FF_FFFF;
+        let notification = DataNotification {
+            long_invoke_id_and_priority: self.push_invoke_id,
+            date_time: None,
+            notification_body: CosemData::Structure(values),
+        };
+        match schedule.message {
+            PUSH_MESSAGE_AXDR => notification.to_bytes().ok(),
+            #[cfg(feature = "compress")]
+            PUSH_MESSAGE_COMPRESSED_AXDR => {
+                compress_notification(&notification, self.push_compression.as_deref()?)
+                    .and_then(|notification| notification.to_bytes())
+                    .ok()
+            }
+            _ => None,
         }
-
-        Ok(response_hdlc_frame.to_bytes()?)
     }
 
-    fn build_response_frame(&self, information: Vec<u8>) -> Result<Vec<u8>, ServerError<T::Error>> {
-        Ok(HdlcFrame {
-            address: self.address,
-            control: 0,
-            information,
+    // Intra-device access to registered objects. Monitoring objects see the
+    // outcome right away, so the host needs no callbacks to glue a register to
+    // a register monitor or limiter and the disconnect control behind them.
+    pub fn set_object_attribute(
+        &mut self,
+        logical_name: [u8; 6],
+        attribute_id: CosemObjectAttributeId,
+        value: CosemData,
+    ) -> Option<()> {
+        self.clear_response_caches();
+        let watched = self.watched_values(None, logical_name);
+        let object = self.objects.get_mut(&logical_name)?;
+        let reconfigured = object.monitored_value().is_some();
+        object.set_attribute(attribute_id, value)?;
+        self.notify_watchers(None, logical_name, watched);
+        if reconfigured {
+            self.resolve_monitors();
         }
-        .to_bytes()?)
+        self.evaluate_monitors(self.monitor_timestamp);
+        Some(())
     }
 
-    fn resolve_object(
+    // Value injection for the metrology: writes the attribute with the checks
+    // of a SET (attribute specs, pre- and post-write callbacks) but without
+    // access rights, then captures the profiles linked by `capture_on_update`.
+    pub fn update_attribute(
         &mut self,
-        client_address: u16,
         logical_name: [u8; 6],
-    ) -> Option<&mut dyn CosemObject> {
-        if self
-            .association_logical_names
-            .get(&client_address)
-            .is_some_and(|ln| *ln == logical_name)
-        {
-            if let Some(association) = self.client_association_instances.get_mut(&client_address) {
-                return Some(association.as_mut());
-            }
+        attribute_id: CosemObjectAttributeId,
+        value: CosemData,
+    ) -> Result<(), DataAccessResult> {
+        self.clear_response_caches();
+        let watched = self.watched_values(None, logical_name);
+        let object = self
+            .objects
+            .get_mut(&logical_name)
+            .ok_or(DataAccessResult::ObjectUndefined)?;
+        let reconfigured = object.monitored_value().is_some();
+        let result = Self::write_validated(object.as_mut(), attribute_id, value);
+        self.notify_watchers(None, logical_name, watched);
+        if result != DataAccessResult::Success {
+            return Err(result);
         }
-
-        if let Some(object) = self.objects.get_mut(&logical_name) {
-            return Some(object.as_mut());
+        if reconfigured {
+            self.resolve_monitors();
         }
+        self.evaluate_monitors(self.monitor_timestamp);
+        for profile_ln in self
+            .update_captures
+            .get(&logical_name)
+            .cloned()
+            .unwrap_or_default()
+        {
+            self.capture_profile(profile_ln);
+        }
+        Ok(())
+    }
 
-        None
+    // Sets the value (attribute 2) of a register, extended register or
+    // demand register from a quantity in any scaler or convertible unit. The
+    // value keeps its integer type; a quantity that cannot be expressed
+    // exactly in it is refused as type-unmatched. Float values take the
+    // quantity at the precision of their type.
+    pub fn update_register_value(
+        &mut self,
+        logical_name: [u8; 6],
+        value: &ScaledValue,
+    ) -> Result<(), DataAccessResult> {
+        let object = self
+            .objects
+            .get(&logical_name)
+            .ok_or(DataAccessResult::ObjectUndefined)?;
+        let (value_id, scaler_unit_id) = match object.class_id() {
+            3 | 4 => (2, 3),
+            5 => (2, 4),
+            _ => return Err(DataAccessResult::ObjectClassInconsistent),
+        };
+        let current = object
+            .get_attribute(value_id)
+            .unwrap_or(CosemData::NullData);
+        let target = object
+            .get_attribute(scaler_unit_id)
+            .and_then(|scaler_unit| {
+                ScaledValue::from_cosem_data(&CosemData::Integer(0), &scaler_unit)
+            })
+            .ok_or(DataAccessResult::ObjectUnavailable)?;
+        let value = value
+            .convert_to(target.unit)
+            .ok_or(DataAccessResult::TypeUnmatched)?;
+        let float = || value.raw as f64 * 10f64.powi(value.scaler as i32 - target.scaler as i32);
+        let data = match current {
+            CosemData::Float32(_) => CosemData::Float32(float() as f32),
+            CosemData::Float64(_) => CosemData::Float64(float()),
+            _ => value
+                .rescale(target.scaler)
+                .and_then(|value| current.zero_like().checked_add(value.raw))
+                .ok_or(DataAccessResult::TypeUnmatched)?,
+        };
+        self.update_attribute(logical_name, value_id, data)
     }
 
-    fn negotiate_initiate_response(
-        &self,
-        request: &InitiateRequest,
-    ) -> Result<InitiateResponse, InitiateValidationError> {
-        if !request.response_allowed {
-            return Err(InitiateValidationError::ResponseNotAllowed);
+    // Sets the time (attribute 2) of a clock to a date-time octet string.
+    pub fn update_clock_time(
+        &mut self,
+        logical_name: [u8; 6],
+        date_time: [u8; 12],
+    ) -> Result<(), DataAccessResult> {
+        let object = self
+            .objects
+            .get(&logical_name)
+            .ok_or(DataAccessResult::ObjectUndefined)?;
+        if object.class_id() != 8 {
+            return Err(DataAccessResult::ObjectClassInconsistent);
         }
+        self.update_attribute(logical_name, 2, CosemData::OctetString(date_time.to_vec()))
+    }
 
-        if request.proposed_dlms_version_number != self.association_parameters.dlms_version {
-            return Err(InitiateValidationError::DlmsVersionMismatch);
+    // Captures the profile generic object each time `update_attribute` changes
+    // an attribute of the object, e.g. a load profile of a register.
+    pub fn capture_on_update(&mut self, logical_name: [u8; 6], profile_ln: [u8; 6]) {
+        let profiles = self.update_captures.entry(logical_name).or_default();
+        if !profiles.contains(&profile_ln) {
+            profiles.push(profile_ln);
         }
+    }
 
-        if request.client_max_receive_pdu_size == 0 {
-            return Err(InitiateValidationError::InvalidClientPduSize);
+    pub fn invoke_object_method(
+        &mut self,
+        logical_name: [u8; 6],
+        method_id: CosemObjectMethodId,
+        parameters: CosemData,
+    ) -> Option<CosemData> {
+        self.clear_response_caches();
+        self.archive_before_reset(logical_name, method_id);
+        let watched = self.watched_values(None, logical_name);
+        let object = self.objects.get_mut(&logical_name)?;
+        let class_id = object.class_id();
+        let result = object.invoke_method(method_id, parameters);
+        self.notify_watchers(None, logical_name, watched);
+        let result = result?;
+        if class_id == 7 && method_id == 2 {
+            self.capture_requested_profile(logical_name)?;
         }
+        self.evaluate_monitors(self.monitor_timestamp);
+        Some(result)
+    }
 
-        let negotiated_conformance = self
-            .association_parameters
-            .conformance
-            .intersection(&request.proposed_conformance);
-
-        if negotiated_conformance.is_empty() {
-            return Err(InitiateValidationError::NoCommonConformance);
-        }
+    fn run_scripts(&mut self, scripts: Vec<ScriptReference>) -> usize {
+        scripts
+            .into_iter()
+            .filter(|script| {
+                self.execute_script(script.script_logical_name, script.script_selector)
+                    .is_some()
+            })
+            .count()
+    }
 
-        let mut response = self
-            .association_parameters
-            .to_initiate_response(negotiated_conformance);
+    // Resolves the monitored value references of the monitoring objects against
+    // the registered objects; references to unknown objects are left out.
+    fn resolve_monitors(&mut self) {
+        self.monitors = self
+            .objects
+            .iter()
+            .filter_map(|(monitor_ln, monitor)| {
+                let monitored = monitor.monitored_value()?;
+                let target = self.objects.get(&monitored.logical_name)?;
+                (target.class_id() == monitored.class_id).then_some((*monitor_ln, monitored))
+            })
+            .collect();
+        let monitors = &self.monitors;
+        self.monitor_values
+            .retain(|monitor_ln, _| monitors.contains_key(monitor_ln));
+    }
 
-        if response.negotiated_quality_of_service.is_none() {
-            response.negotiated_quality_of_service = request.proposed_quality_of_service;
+    fn register_object_internal(&mut self, instance_id: [u8; 6], object: Box<dyn CosemObject>) {
+        // An object registered again keeps its base name if it still fits.
+        let previous = self.short_names.remove(&instance_id);
+        let (attribute_count, method_count) = short_name_counts(object.as_ref());
+        let class_id = object.class_id();
+        let kept = previous.is_some_and(|previous| {
+            self.short_names
+                .assign(ShortNameEntry {
+                    base_name: previous.base_name,
+                    class_id,
+                    logical_name: instance_id,
+                    attribute_count,
+                    method_count,
+                })
+                .is_ok()
+        });
+        if !kept {
+            // A full map is reported by `short_name_map`.
+            self.short_names
+                .allocate(class_id, instance_id, attribute_count, method_count)
+                .ok();
         }
+        self.objects.insert(instance_id, object);
+        self.rebuild_association_object_list();
+        self.resolve_monitors();
+    }
 
-        Ok(response)
+    fn rebuild_association_object_list(&mut self) {
+        let public_association = self.association_logical_names.get(&PUBLIC_CLIENT_SAP);
+        let mut changes = Vec::new();
+        for (association, object_list) in &self.association_object_lists {
+            let public = public_association == Some(association);
+            let mut list = object_list
+                .lock()
+                .expect("association object list poisoned");
+            let previous = core::mem::take(&mut *list);
+            for (logical_name, object) in self.objects() {
+                let visible = self
+                    .object_visibility
+                    .get(&logical_name)
+                    .is_none_or(|visibility| visibility.includes(*association, public));
+                if !visible {
+                    continue;
+                }
+                list.push(ObjectListEntry {
+                    class_id: object.class_id(),
+                    version: object.version(),
+                    logical_name,
+                    attribute_access: object.attribute_access_rights(),
+                    method_access: object.method_access_rights(),
+                });
+            }
+            if self.on_object_list_changed.is_none() {
+                continue;
+            }
+            changes.extend(
+                previous
+                    .iter()
+                    .filter(|entry| !list.contains(entry))
+                    .map(|entry| ObjectListChange::Removed {
+                        association: *association,
+                        entry: entry.clone(),
+                    }),
+            );
+            changes.extend(
+                list.iter()
+                    .filter(|entry| !previous.contains(entry))
+                    .map(|entry| ObjectListChange::Added {
+                        association: *association,
+                        entry: entry.clone(),
+                    }),
+            );
+        }
+        if let Some(callback) = self.on_object_list_changed.as_mut() {
+            for change in &changes {
+                callback(change);
+            }
+        }
     }
 
-    fn attribute_operation_allowed(
-        descriptors: &[AttributeAccessDescriptor],
-        attribute_id: CosemObjectAttributeId,
-        operation: AttributeOperation,
-    ) -> bool {
-        descriptors
-            .iter()
-            .find(|descriptor| descriptor.attribute_id == attribute_id)
-            .is_some_and(|descriptor| match operation {
-                AttributeOperation::Read => matches!(
-                    descriptor.access_mode,
-                    AttributeAccessMode::Read | AttributeAccessMode::ReadWrite
-                ),
-                AttributeOperation::Write => matches!(
-                    descriptor.access_mode,
-                    AttributeAccessMode::Write | AttributeAccessMode::ReadWrite
-                ),
+    // The object list of the association object at `association`, as the
+    // clients using it read attribute 2.
+    pub fn object_list(&self, association: [u8; 6]) -> Option<Vec<ObjectListEntry>> {
+        self.association_object_lists
+            .get(&association)
+            .map(|object_list| {
+                object_list
+                    .lock()
+                    .expect("association object list poisoned")
+                    .clone()
             })
     }
 
-    fn method_operation_allowed(
-        descriptors: &[MethodAccessDescriptor],
-        method_id: CosemObjectMethodId,
-    ) -> bool {
-        descriptors.iter().any(|descriptor| {
-            descriptor.method_id == method_id
-                && matches!(descriptor.access_mode, MethodAccessMode::Access)
-        })
+    // Called with each entry added to or removed from the object list of an
+    // association, e.g. to update a host UI without reading attribute 2.
+    pub fn set_on_object_list_changed<F>(&mut self, callback: F)
+    where
+        F: FnMut(&ObjectListChange) + Send + 'static,
+    {
+        self.on_object_list_changed = Some(Box::new(callback));
     }
-}
 
-#[derive(Debug, Clone)]
-struct AssociationContext {
-    client_max_receive_pdu_size: u16,
-}
+    // Serves frames until stopped through a `StopHandle`, then returns the
+    // statistics. The stop request is consumed, so the server can run again.
+    pub fn run(&mut self) -> Result<ServerStatistics, ServerError<T::Error>> {
+        while !self.stop.0.swap(false, Ordering::SeqCst) {
+            self.step()?;
+        }
+        Ok(self.statistics)
+    }
 
-#[derive(Debug, Clone, Copy)]
-enum AttributeOperation {
-    Read,
-    Write,
-}
+    // Receives one frame from the transport and sends the response, if any,
+    // for hosts driving the server from their own loop. `true` if a response
+    // was sent.
+    pub fn step(&mut self) -> Result<bool, ServerError<T::Error>> {
+        let request_bytes = self
+            .transport
+            .receive()
+            .map_err(ServerError::TransportError)?;
+        self.statistics.frames_received += 1;
+        self.statistics.bytes_received += request_bytes.len() as u64;
+        let Some(response_bytes) = self.process_frame(&request_bytes)? else {
+            return Ok(false);
+        };
+        self.transport
+            .send(&response_bytes)
+            .map_err(ServerError::TransportError)?;
+        self.tracer.mark(TracePhase::Transport);
+        self.statistics.responses_sent += 1;
+        self.statistics.bytes_sent += response_bytes.len() as u64;
+        Ok(true)
+    }
 
-#[derive(Debug, Clone, Copy)]
-enum InitiateValidationError {
-    ResponseNotAllowed,
-    DlmsVersionMismatch,
-    InvalidClientPduSize,
-    NoCommonConformance,
-}
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
 
-impl InitiateValidationError {
-    fn diagnostic(self) -> u8 {
-        match self {
-            InitiateValidationError::ResponseNotAllowed => 1,
-            InitiateValidationError::DlmsVersionMismatch => 2,
-            InitiateValidationError::InvalidClientPduSize => 3,
-            InitiateValidationError::NoCommonConformance => 4,
+    pub fn statistics(&self) -> ServerStatistics {
+        self.statistics
+    }
+
+    // One step of `run` for a frame received from the transport: decrypts the
+    // request and returns the encrypted response, if one is to be sent.
+    pub fn process_frame(
+        &mut self,
+        request_bytes: &[u8],
+    ) -> Result<Option<Vec<u8>>, ServerError<T::Error>> {
+        self.process_frame_from(None, request_bytes)
+    }
+
+    // `process_frame` writing the response frame into `response`, whose
+    // buffer the server takes over for the next one, so that a host serving
+    // frames in a loop does not allocate for them. `true` if there is a
+    // response to send.
+    pub fn process_frame_into(
+        &mut self,
+        request_bytes: &[u8],
+        response: &mut Vec<u8>,
+    ) -> Result<bool, ServerError<T::Error>> {
+        response.clear();
+        let Some(mut frame) = self.process_frame(request_bytes)? else {
+            return Ok(false);
+        };
+        core::mem::swap(response, &mut frame);
+        self.response_frame = frame;
+        Ok(true)
+    }
+
+    // `process_frame` for a frame received over one of several interfaces.
+    // Clients the interface does not serve are not answered, nor are those
+    // associated over another interface until they release there.
+    pub fn process_interface_frame(
+        &mut self,
+        interface: &ServerInterface,
+        request_bytes: &[u8],
+    ) -> Result<Option<Vec<u8>>, ServerError<T::Error>> {
+        self.process_frame_from(Some(interface), request_bytes)
+    }
+
+    // `process_frame` for a frame received over one of several connections,
+    // e.g. those accepted by a TCP listener. Each connection has associations
+    // of its own: a client address associated over one of them is idle over
+    // the others.
+    pub fn process_connection_frame(
+        &mut self,
+        connection: u64,
+        request_bytes: &[u8],
+    ) -> Result<Option<Vec<u8>>, ServerError<T::Error>> {
+        self.with_connection(connection, |server| server.process_frame(request_bytes))
+    }
+
+    // Releases the associations established over a connection that closed.
+    pub fn close_connection(&mut self, connection: u64) {
+        self.with_connection(connection, |server| {
+            let clients: Vec<u16> = server.associations.keys().copied().collect();
+            for client_address in clients {
+                server.release_association(client_address);
+            }
+        });
+        self.connections.remove(&connection);
+    }
+
+    fn with_connection<R>(&mut self, connection: u64, serve: impl FnOnce(&mut Self) -> R) -> R {
+        let mut state = self.connections.remove(&connection).unwrap_or_default();
+        self.swap_associations(&mut state);
+        let result = serve(self);
+        self.swap_associations(&mut state);
+        self.connections.insert(connection, state);
+        result
+    }
+
+    fn swap_associations(&mut self, state: &mut ConnectionAssociations) {
+        core::mem::swap(&mut self.associations, &mut state.associations);
+        core::mem::swap(
+            &mut self.client_association_instances,
+            &mut state.client_association_instances,
+        );
+        core::mem::swap(&mut self.client_interfaces, &mut state.client_interfaces);
+    }
+
+    fn process_frame_from(
+        &mut self,
+        interface: Option<&ServerInterface>,
+        request_bytes: &[u8],
+    ) -> Result<Option<Vec<u8>>, ServerError<T::Error>> {
+        self.tracer.begin();
+        let decrypted_request = if let Some(key) = &self.key {
+            Cow::Owned(hls_decrypt(request_bytes, key).map_err(ServerError::SecurityError)?)
+        } else {
+            Cow::Borrowed(request_bytes)
+        };
+        // Frames for other drops on the bus are dropped silently. Once a
+        // physical address is set the server shares a bus, so broadcasts are
+        // processed but not answered to avoid collisions.
+        let request_information = core::mem::take(&mut self.request_information);
+        if let Ok(frame) = HdlcFrame::from_bytes_in(&decrypted_request, request_information) {
+            self.tracer.mark(TracePhase::Decode);
+            self.tracer.record(
+                TraceLayer::Hdlc,
+                format_args!(
+                    "frame from client {} to {:?}, control {:#04X}, {} octets",
+                    frame.address,
+                    frame.destination,
+                    frame.control,
+                    request_bytes.len()
+                ),
+            );
+            diagnostics::debug!(
+                "frame from client {}, control {}, {} octets",
+                frame.address,
+                frame.control,
+                request_bytes.len()
+            );
+            if !self.is_addressed_to(&frame.destination) {
+                return Ok(None);
+            }
+            if let Some(interface) = interface {
+                if !self.admit_over(interface, frame.address) {
+                    return Ok(None);
+                }
+            }
+            if self.physical_address.is_some() && frame.destination.is_broadcast() {
+                let _ = self.handle_request_frame(&frame);
+                return Ok(None);
+            }
+            if self.hdlc_window.is_some() {
+                return self.process_windowed_frame(&decrypted_request, frame.control);
+            }
+            // Link supervision: an RR poll is answered with an RR carrying the
+            // final bit.
+            if is_receive_ready(frame.control) {
+                if frame.control & HDLC_POLL_FINAL == 0 {
+                    return Ok(None);
+                }
+                let response = HdlcFrame {
+                    address: self.address,
+                    control: receive_ready(0),
+                    ..Default::default()
+                };
+                return self.encrypt_response(response.to_bytes()?).map(Some);
+            }
+            let response_bytes = self.handle_request_frame(&frame).inspect_err(|error| {
+                if let ServerError::DlmsError(error) = error {
+                    diagnostics::warning!("request not parsed: {:?}", error);
+                }
+            });
+            self.request_information = frame.information;
+            return self.encrypt_response(response_bytes?).map(Some);
         }
+        diagnostics::warning!("frame not parsed, {} octets", decrypted_request.len());
+        let response_bytes = self.handle_request(&decrypted_request)?;
+        self.encrypt_response(response_bytes).map(Some)
     }
-}
 
-#[cfg(all(test, feature = "std"))]
-mod tests {
-    extern crate std;
-    use super::*;
-    use crate::activity_calendar::ActivityCalendar;
-    use crate::clock::Clock;
-    use crate::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
-    use crate::demand_register::DemandRegister;
-    use crate::disconnect_control::DisconnectControl;
-    use crate::extended_register::ExtendedRegister;
-    use crate::profile_generic::ProfileGeneric;
-    use crate::register::Register;
-    use crate::sap_assignment::SapAssignment;
-    use crate::security_setup::SecuritySetup;
-    use crate::types::CosemData;
-    use crate::xdlms::{
-        ActionRequest, ActionRequestNormal, ActionResponse, ActionResult, AssociationParameters,
-        Conformance, DataAccessResult, GetDataResult, GetRequest, GetRequestNormal, GetResponse,
-        InitiateRequest, InitiateResponse, SetRequest, SetRequestNormal, SetResponse,
-    };
+    // Whether a client may be served over an interface. An idle client takes
+    // the association object the interface maps it to.
+    fn admit_over(&mut self, interface: &ServerInterface, client_address: u16) -> bool {
+        let Some(logical_name) = interface.association_for(client_address) else {
+            return false;
+        };
+        if self.associations.contains_key(&client_address) {
+            if self.client_interfaces.get(&client_address) != Some(&interface.id) {
+                return false;
+            }
+        } else {
+            self.association_logical_names
+                .insert(client_address, logical_name);
+            self.client_interfaces.insert(client_address, interface.id);
+        }
+        true
+    }
 
-    struct DummyTransport;
+    fn encrypt_response(
+        &mut self,
+        response_bytes: Vec<u8>,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        self.tracer.mark(TracePhase::Process);
+        let response_bytes = if let Some(key) = &self.key {
+            match self.frame_counter.as_mut() {
+                Some(frame_counter) => frame_counter.encrypt(&response_bytes, key),
+                None => hls_encrypt(&response_bytes, key),
+            }
+            .map_err(ServerError::SecurityError)?
+        } else {
+            response_bytes
+        };
+        self.tracer.mark(TracePhase::Encode);
+        self.tracer.record(
+            TraceLayer::Hdlc,
+            format_args!("response frame, {} octets", response_bytes.len()),
+        );
+        Ok(response_bytes)
+    }
 
-    impl Transport for DummyTransport {
-        type Error = ();
+    // I-frames of an open window are served in order as they arrive. Once the
+    // client polls or the window is full, the held responses are sent back to
+    // back, each acknowledging all frames received; without responses an RR
+    // frame carries the acknowledgement. Frames out of sequence are dropped.
+    fn process_windowed_frame(
+        &mut self,
+        request_bytes: &[u8],
+        control: u8,
+    ) -> Result<Option<Vec<u8>>, ServerError<T::Error>> {
+        let Some(window) = self.hdlc_window.as_mut() else {
+            return Ok(None);
+        };
+        if window.receive(control) {
+            let response = self.handle_request(request_bytes)?;
+            self.windowed_responses
+                .push(HdlcFrame::from_bytes(&response)?);
+        }
 
-        fn send(&mut self, _bytes: &[u8]) -> Result<(), Self::Error> {
-            Ok(())
+        let Some(window) = self.hdlc_window.as_mut() else {
+            return Ok(None);
+        };
+        if !window.must_acknowledge(control) {
+            return Ok(None);
+        }
+        let mut frames = core::mem::take(&mut self.windowed_responses);
+        if frames.is_empty() {
+            frames.push(HdlcFrame {
+                address: self.address,
+                control: window.receive_ready_control(),
+                ..Default::default()
+            });
+        } else {
+            let last = frames.len() - 1;
+            for (index, frame) in frames.iter_mut().enumerate() {
+                frame.control = window.next_information_control(index == last);
+            }
         }
 
-        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
-            Ok(Vec::new())
+        let mut response_bytes = Vec::new();
+        for frame in frames {
+            response_bytes.extend(self.encrypt_response(frame.to_bytes()?)?);
         }
+        Ok(Some(response_bytes))
     }
 
-    fn build_hdlc_request(address: u16, aarq: AarqApdu) -> Vec<u8> {
-        let frame = HdlcFrame {
-            address,
+    fn handle_request(&mut self, request_bytes: &[u8]) -> Result<Vec<u8>, ServerError<T::Error>> {
+        self.handle_request_frame(&HdlcFrame::from_bytes(request_bytes)?)
+    }
+
+    fn handle_request_frame(
+        &mut self,
+        request_frame: &HdlcFrame,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        if request_frame.information.len() > self.max_receive_pdu_size() as usize {
+            return Err(ServerError::DlmsError(DlmsError::Xdlms));
+        }
+
+        // Pipelined requests are served one after the other and their
+        // responses returned together in one frame.
+        if Apdus::new(&request_frame.information).nth(1).is_some() {
+            let mut information = Vec::new();
+            for apdu in Apdus::new(&request_frame.information) {
+                let frame = HdlcFrame {
+                    information: apdu.to_vec(),
+                    ..request_frame.clone()
+                };
+                let response = self.handle_request_frame(&frame)?;
+                information.extend(HdlcFrame::from_bytes(&response)?.information);
+            }
+            return self.build_response_frame(information);
+        }
+
+        let Some(&tag) = request_frame.information.first() else {
+            return Err(ServerError::DlmsError(DlmsError::Xdlms));
+        };
+        self.tracer.record(
+            TraceLayer::of_apdu(&request_frame.information),
+            format_args!(
+                "{} from client {}, {} octets",
+                apdu_name(&request_frame.information),
+                request_frame.address,
+                request_frame.information.len()
+            ),
+        );
+        let Some(handler) = self.apdu_handlers.get(&tag).copied() else {
+            let exception = ExceptionResponse {
+                state_error: StateError::ServiceUnknown,
+                service_error: ServiceError::ServiceNotSupported,
+            };
+            return self.build_response_frame(exception.to_bytes()?);
+        };
+        if !self.duplicate_detection || !self.is_associated(request_frame.address) {
+            return handler(self, request_frame);
+        }
+
+        let client_address = request_frame.address;
+        let request = matches!(tag, SET_REQUEST_TAG | ACTION_REQUEST_TAG)
+            .then(|| request_frame.information.get(2).copied())
+            .flatten()
+            .map(|invoke_id_and_priority| LastRequest {
+                invoke_id_and_priority,
+                hash: request_hash(&request_frame.information),
+                response: Vec::new(),
+            });
+        if let Some(request) = &request {
+            let repeated = self
+                .association_context(client_address)
+                .and_then(|context| context.last_request.as_ref())
+                .filter(|last| last.repeats(request))
+                .map(|last| last.response.clone());
+            if let Some(response) = repeated {
+                return Ok(response);
+            }
+        }
+        let response = handler(self, request_frame)?;
+        if let Some(context) = self.association_context_mut(client_address) {
+            context.last_request = request.map(|request| {
+                Box::new(LastRequest {
+                    response: response.clone(),
+                    ..request
+                })
+            });
+        }
+        Ok(response)
+    }
+
+    fn handle_aarq(&mut self, request_frame: &HdlcFrame) -> Result<Vec<u8>, ServerError<T::Error>> {
+        // An AARQ that does not decode is rejected at the ACSE level, never
+        // tried as another service.
+        let Ok((_, aarq_apdu)) =
+            AarqApdu::from_bytes_with(&request_frame.information, self.parse_mode)
+        else {
+            diagnostics::warning!("client {}: undecodable AARQ", request_frame.address);
+            let aare = AareApdu {
+                application_context_name: b"LN_WITH_NO_CIPHERING".to_vec(),
+                result: 1,
+                result_source_diagnostic: AssociateSourceDiagnostic::NO_REASON_GIVEN,
+                ..Default::default()
+            };
+            return self.reject_aarq(request_frame.address, &aare);
+        };
+        let Ok(initiate_request) = InitiateRequest::from_user_information_with(
+            &aarq_apdu.user_information,
+            self.parse_mode,
+        ) else {
+            diagnostics::warning!(
+                "client {}: undecodable InitiateRequest",
+                request_frame.address
+            );
+            let aare = AareApdu {
+                application_context_name: aarq_apdu.application_context_name,
+                result: 1,
+                result_source_diagnostic: AssociateSourceDiagnostic::NO_REASON_GIVEN,
+                user_information: ConfirmedServiceError::InitiateError(InitiateError::Other)
+                    .to_user_information()?,
+                ..Default::default()
+            };
+            return self.reject_aarq(request_frame.address, &aare);
+        };
+        let client_limit = initiate_request
+            .client_max_receive_pdu_size
+            .min(MAX_PDU_SIZE as u16);
+        let negotiation = self.negotiate_initiate_response(&initiate_request);
+        let mut aare = AareApdu {
+            application_context_name: aarq_apdu.application_context_name.clone(),
+            result: 0,
+            result_source_diagnostic: AssociateSourceDiagnostic::NULL,
+            responding_authentication_value: None,
+            user_information: Vec::new(),
+            ..Default::default()
+        };
+        let mut negotiation_succeeded = false;
+
+        let association_address = request_frame.address;
+        if !self.client_access.allows(&association_address) {
+            self.reject_attempt(RejectedAttempt::ClientSap(association_address));
+            aare.result = 1;
+            aare.result_source_diagnostic = AssociateSourceDiagnostic::NO_REASON_GIVEN;
+        } else {
+            match negotiation {
+                Ok(initiate_response) => {
+                    aare.user_information = initiate_response.to_user_information()?;
+                    negotiation_succeeded = true;
+                }
+                Err(err) => {
+                    // The confirmed service error tells the reason.
+                    aare.result = 1;
+                    aare.result_source_diagnostic = AssociateSourceDiagnostic::NO_REASON_GIVEN;
+                    aare.user_information =
+                        ConfirmedServiceError::InitiateError(err.initiate_error())
+                            .to_user_information()?;
+                }
+            }
+        }
+
+        if aare.result != 0 {
+            return self.reject_aarq(association_address, &aare);
+        }
+        let mut issued_challenge = None;
+        if let (Some(authentication), Some(b"LLS")) = (
+            self.authentication.as_mut(),
+            aarq_apdu.mechanism_name.as_deref(),
+        ) {
+            if let Some(auth_value) = aarq_apdu.calling_authentication_value.as_deref() {
+                // No challenge issued means nothing to answer.
+                let verified = self
+                    .associations
+                    .get(&association_address)
+                    .and_then(AssociationState::challenge)
+                    .is_some_and(|challenge| {
+                        authentication.verify_lls(association_address, challenge, auth_value)
+                    });
+                if !verified {
+                    aare.result = 1;
+                    aare.result_source_diagnostic =
+                        AssociateSourceDiagnostic::AUTHENTICATION_FAILURE;
+                }
+            } else {
+                aare.result_source_diagnostic = AssociateSourceDiagnostic::AUTHENTICATION_REQUIRED;
+                let challenge = authentication
+                    .generate_challenge(association_address)
+                    .map_err(ServerError::SecurityError)?;
+                aare.responding_authentication_value = Some(challenge.clone());
+                issued_challenge = Some(challenge);
+            }
+        }
+        if let Some(challenge) = issued_challenge {
+            self.transition(
+                association_address,
+                AssociationState::PendingAuth { challenge },
+            );
+            self.client_association_instances
+                .remove(&association_address);
+        }
+        let info = AssociationInfo {
+            client_address: association_address,
+            logical_name: self
+                .association_logical_names
+                .get(&association_address)
+                .copied()
+                .unwrap_or(PUBLIC_ASSOCIATION_LN),
+            mechanism_name: aarq_apdu.mechanism_name.clone(),
+            calling_ap_title: aarq_apdu.calling_ap_title.clone(),
+        };
+        if aare.result != 0 {
+            self.refuse_association(association_address);
+            self.client_association_instances
+                .remove(&association_address);
+            self.log_security_event(SecurityEvent::AuthenticationFailure);
+            if let Some(callback) = self.on_authentication_failed.as_mut() {
+                callback(&info);
+            }
+        } else if aare.responding_authentication_value.is_none() && negotiation_succeeded {
+            let mut context = AssociationContext::new(client_limit);
+            context.info = Some(info.clone());
+            context.response_cache = self
+                .response_cache
+                .map(|(capacity, ttl)| ResponseCache::new(capacity, ttl));
+            self.transition(
+                association_address,
+                AssociationState::Associated {
+                    negotiated: context,
+                },
+            );
+
+            let logical_name = if let Some(&logical_name) =
+                self.association_logical_names.get(&association_address)
+            {
+                logical_name
+            } else {
+                self.association_logical_names
+                    .insert(association_address, PUBLIC_ASSOCIATION_LN);
+                PUBLIC_ASSOCIATION_LN
+            };
+
+            let template = self
+                .association_templates
+                .get(&logical_name)
+                .cloned()
+                .or_else(|| {
+                    self.association_templates
+                        .get(&PUBLIC_ASSOCIATION_LN)
+                        .cloned()
+                });
+
+            let Some(template) = template else {
+                self.client_association_instances
+                    .remove(&association_address);
+                self.transition(association_address, AssociationState::Idle);
+                return Err(ServerError::DlmsError(DlmsError::Xdlms));
+            };
+
+            let partners_id = ((association_address as u32) << 16) | self.address as u32;
+
+            let entry = self
+                .client_association_instances
+                .entry(association_address)
+                .or_insert_with(|| {
+                    let mut association = template.clone();
+                    association.set_association_status(AssociationStatus::Associated);
+                    Box::new(association) as Box<dyn CosemObject>
+                });
+
+            let _ = entry
+                .as_mut()
+                .set_attribute(3, CosemData::DoubleLongUnsigned(partners_id));
+            if let Some(callback) = self.on_association_established.as_mut() {
+                callback(&info);
+            }
+        }
+        self.finish_response(request_frame.address, Some(client_limit), aare.to_bytes()?)
+    }
+
+    // Answers an AARQ with the rejecting `aare`, leaving the client without
+    // an association.
+    fn reject_aarq(
+        &mut self,
+        association_address: u16,
+        aare: &AareApdu,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        self.refuse_association(association_address);
+        self.client_association_instances
+            .remove(&association_address);
+        Ok(HdlcFrame {
+            address: self.address,
             control: 0,
-            information: aarq.to_bytes().expect("failed to serialize aarq"),
+            information: aare.to_bytes()?,
+            ..Default::default()
+        }
+        .to_bytes()?)
+    }
+
+    fn handle_release_request(
+        &mut self,
+        request_frame: &HdlcFrame,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let (_, release_req) =
+            ArlrqApdu::from_bytes_with(&request_frame.information, self.parse_mode)
+                .map_err(|_| DlmsError::Acse)?;
+        let mut rlre = ArlreApdu {
+            reason: Some(release_req.reason.unwrap_or(0)),
+            user_information: None,
         };
+        // The InitiateRequest of the release is answered with an
+        // InitiateResponse, both ciphered in a ciphered association. One that
+        // does not fit the association leaves the association in place.
+        if let Some(user_information) = &release_req.user_information {
+            let response = user_information
+                .initiate_request(self.key.as_deref(), self.parse_mode)
+                .map_err(|err| {
+                    diagnostics::warning!(
+                        "client {}: release refused: {:?}",
+                        request_frame.address,
+                        err
+                    );
+                })
+                .ok()
+                .and_then(|request| self.negotiate_initiate_response(&request).ok());
+            let Some(response) = response else {
+                let refusal = ArlreApdu {
+                    reason: Some(RELEASE_NOT_FINISHED),
+                    user_information: None,
+                };
+                return self.finish_response(request_frame.address, None, refusal.to_bytes()?);
+            };
+            rlre.user_information = Some(match &self.key {
+                Some(key) => ReleaseResponseInformation::ciphered(&response, key)?,
+                None => ReleaseResponseInformation::Initiate(response),
+            });
+        }
+        self.release_association(request_frame.address);
 
-        frame.to_bytes().expect("failed to encode frame")
+        self.finish_response(request_frame.address, None, rlre.to_bytes()?)
     }
 
-    fn parse_aare(bytes: &[u8]) -> AareApdu {
-        let frame = HdlcFrame::from_bytes(bytes).expect("failed to decode frame");
-        AareApdu::from_bytes(&frame.information)
-            .expect("failed to decode aare")
-            .1
+    fn handle_get_request(
+        &mut self,
+        request_frame: &HdlcFrame,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        // The value read may be lent by the object, so it is encoded into a
+        // buffer held apart from the server.
+        let mut scratch = core::mem::take(&mut self.scratch);
+        let response = self.serve_get_request(request_frame, &mut scratch);
+        self.scratch = scratch;
+        response
+    }
+
+    fn serve_get_request(
+        &mut self,
+        request_frame: &HdlcFrame,
+        scratch: &mut Vec<u8>,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let get_req = GetRequest::from_bytes_with(&request_frame.information, self.parse_mode)?;
+        if let Some(exception) = self.state_exception(request_frame.address) {
+            return self.build_response_frame(exception.to_bytes()?);
+        }
+        let get_req = match get_req {
+            GetRequest::Normal(get_req) => get_req,
+            GetRequest::Next(next_req) => {
+                let response = self.handle_get_next(request_frame.address, next_req)?;
+                return self.build_response_frame(response);
+            }
+            GetRequest::WithList(_) => return Err(ServerError::DlmsError(DlmsError::Xdlms)),
+        };
+        if let Some(context) = self.association_context_mut(request_frame.address) {
+            context.long_get = None;
+        }
+
+        let response_bytes = if !self.is_associated(request_frame.address) {
+            self.access_denied(
+                request_frame.address,
+                AccessService::Get,
+                &get_req.cosem_attribute_descriptor,
+            );
+            let denial = GetResponse::Normal(GetResponseNormal {
+                invoke_id_and_priority: get_req.invoke_id_and_priority,
+                result: GetDataResult::DataAccessResult(DataAccessResult::ReadWriteDenied),
+            });
+            denial.to_bytes()?
+        } else {
+            let now = Instant::now();
+            let cached = self
+                .association_context_mut(request_frame.address)
+                .and_then(|context| context.response_cache.as_mut())
+                .and_then(|cache| {
+                    cache.get(
+                        &get_req.cosem_attribute_descriptor,
+                        get_req.access_selection.as_ref(),
+                        now,
+                    )
+                });
+            let instance_id = get_req.cosem_attribute_descriptor.instance_id;
+            if self
+                .resolve_object(request_frame.address, instance_id)
+                .is_none()
+            {
+                let Some(result) = self.read_billing_period(
+                    request_frame.address,
+                    &get_req.cosem_attribute_descriptor,
+                ) else {
+                    return Err(ServerError::DlmsError(DlmsError::Xdlms));
+                };
+                let response = GetResponse::Normal(GetResponseNormal {
+                    invoke_id_and_priority: get_req.invoke_id_and_priority,
+                    result,
+                });
+                return self.build_response_frame(response.to_bytes()?);
+            }
+            let limit = self.client_pdu_limit(request_frame.address);
+            let Some(object) = self.resolve_object(request_frame.address, instance_id) else {
+                return Err(ServerError::DlmsError(DlmsError::Xdlms));
+            };
+
+            let attribute_id = get_req.cosem_attribute_descriptor.attribute_id;
+            let attribute_access = object.attribute_access(attribute_id);
+            let attribute_access = attribute_access.as_slice();
+            if !Self::attribute_operation_allowed(
+                attribute_access,
+                attribute_id,
+                AttributeOperation::Read,
+            ) {
+                self.access_denied(
+                    request_frame.address,
+                    AccessService::Get,
+                    &get_req.cosem_attribute_descriptor,
+                );
+                let denial = GetResponse::Normal(GetResponseNormal {
+                    invoke_id_and_priority: get_req.invoke_id_and_priority,
+                    result: GetDataResult::DataAccessResult(DataAccessResult::ReadWriteDenied),
+                });
+                denial.to_bytes()?
+            } else if !Self::selection_declared(
+                attribute_access,
+                attribute_id,
+                get_req.access_selection.as_ref(),
+            ) {
+                GetResponse::Normal(GetResponseNormal {
+                    invoke_id_and_priority: get_req.invoke_id_and_priority,
+                    result: GetDataResult::DataAccessResult(
+                        DataAccessResult::ScopeOfAccessViolated,
+                    ),
+                })
+                .to_bytes()?
+            } else {
+                if let Some(callbacks) = object.callbacks() {
+                    if let Err(result_code) = callbacks.call_pre_read(&*object, attribute_id) {
+                        let denial = GetResponse::Normal(GetResponseNormal {
+                            invoke_id_and_priority: get_req.invoke_id_and_priority,
+                            result: GetDataResult::DataAccessResult(result_code),
+                        });
+                        return self.build_response_frame(denial.to_bytes()?);
+                    }
+                }
+                if let Some(encoded) = cached {
+                    let response = get_response_normal_data(
+                        core::mem::take(&mut self.response_information),
+                        get_req.invoke_id_and_priority,
+                        &encoded,
+                    );
+                    return self.finish_response(request_frame.address, None, response);
+                }
+
+                // Streamed attributes are encoded block by block, so the
+                // post-read callback never sees the materialised value. A
+                // selection reads only part of the value and is not streamed.
+                let stream = match get_req.access_selection {
+                    None => object.attribute_stream(attribute_id),
+                    Some(_) => None,
+                };
+                if let Some(stream) = stream {
+                    let transfer = LongGetTransfer {
+                        invoke_id_and_priority: get_req.invoke_id_and_priority,
+                        block_number: 0,
+                        source: LongGetSource::Stream {
+                            logical_name: instance_id,
+                            stream,
+                            finished: false,
+                        },
+                        pending: Vec::new(),
+                    };
+                    let response = self.start_long_get(request_frame.address, transfer)?;
+                    return self.build_response_frame(response);
+                }
+
+                let read = match &get_req.access_selection {
+                    Some(selection) => object
+                        .read_attribute_selective(attribute_id, selection)
+                        .map(Cow::Owned),
+                    None => object.read_attribute_ref(attribute_id),
+                };
+                let (mut result, read_failure) = match read {
+                    Ok(data) => (Some(data), DataAccessResult::ObjectUnavailable),
+                    Err(result_code) => (None, result_code),
+                };
+
+                if let Some(callbacks) = object.callbacks() {
+                    if let Err(result_code) =
+                        callbacks.call_post_read_borrowed(&*object, attribute_id, &mut result)
+                    {
+                        let denial = GetResponse::Normal(GetResponseNormal {
+                            invoke_id_and_priority: get_req.invoke_id_and_priority,
+                            result: GetDataResult::DataAccessResult(result_code),
+                        });
+                        return self.build_response_frame(denial.to_bytes()?);
+                    }
+                }
+
+                match result {
+                    Some(data) => {
+                        scratch.clear();
+                        encode_data(&data, scratch)?;
+                        if scratch.len() + GET_RESPONSE_NORMAL_OVERHEAD > limit {
+                            let transfer = LongGetTransfer {
+                                invoke_id_and_priority: get_req.invoke_id_and_priority,
+                                block_number: 0,
+                                source: LongGetSource::Encoded,
+                                pending: core::mem::take(scratch),
+                            };
+                            let response = self.start_long_get(request_frame.address, transfer)?;
+                            return self.build_response_frame(response);
+                        }
+                        let response = get_response_normal_data(
+                            core::mem::take(&mut self.response_information),
+                            get_req.invoke_id_and_priority,
+                            scratch,
+                        );
+                        if let Some(cache) = self
+                            .association_context_mut(request_frame.address)
+                            .and_then(|context| context.response_cache.as_mut())
+                        {
+                            cache.insert(
+                                get_req.cosem_attribute_descriptor.clone(),
+                                get_req.access_selection.clone(),
+                                response[GET_RESPONSE_NORMAL_OVERHEAD..].to_vec(),
+                                now,
+                            );
+                        }
+                        response
+                    }
+                    None => GetResponse::Normal(GetResponseNormal {
+                        invoke_id_and_priority: get_req.invoke_id_and_priority,
+                        result: GetDataResult::DataAccessResult(read_failure),
+                    })
+                    .to_bytes()?,
+                }
+            }
+        };
+        self.finish_response(request_frame.address, None, response_bytes)
+    }
+
+    fn handle_set_request(
+        &mut self,
+        request_frame: &HdlcFrame,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let set_req = SetRequest::from_bytes_with(&request_frame.information, self.parse_mode)?;
+        if let Some(exception) = self.state_exception(request_frame.address) {
+            return self.build_response_frame(exception.to_bytes()?);
+        }
+        self.clear_response_caches();
+        let set_req = match set_req {
+            SetRequest::Normal(set_req) => set_req,
+            SetRequest::WithFirstDatablock(first) => {
+                let response = self.handle_set_first_datablock(request_frame.address, first)?;
+                return self.build_response_frame(response);
+            }
+            SetRequest::WithDatablock(next) => {
+                let response = self.handle_set_datablock(request_frame.address, next)?;
+                return self.build_response_frame(response);
+            }
+            SetRequest::WithList(_) => return Err(ServerError::DlmsError(DlmsError::Xdlms)),
+        };
+        if let Some(context) = self.association_context_mut(request_frame.address) {
+            context.long_set = None;
+        }
+        let result = self.write_requested_attribute(
+            request_frame.address,
+            &set_req.cosem_attribute_descriptor,
+            set_req.access_selection.as_ref(),
+            set_req.value,
+        )?;
+        let response = SetResponse::Normal(SetResponseNormal {
+            invoke_id_and_priority: set_req.invoke_id_and_priority,
+            result,
+        });
+        self.finish_response(request_frame.address, None, response.to_bytes()?)
+    }
+
+    fn handle_action_request(
+        &mut self,
+        request_frame: &HdlcFrame,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let action_req =
+            ActionRequest::from_bytes_with(&request_frame.information, self.parse_mode)?;
+        if let Some(exception) = self.state_exception(request_frame.address) {
+            return self.build_response_frame(exception.to_bytes()?);
+        }
+        self.clear_response_caches();
+        let action_req = match action_req {
+            ActionRequest::Normal(action_req) => action_req,
+            ActionRequest::WithFirstPblock(first) => {
+                let response = self.handle_action_first_pblock(request_frame.address, first)?;
+                return self.finish_response(request_frame.address, None, response);
+            }
+            ActionRequest::WithPblock(next) => {
+                let response = self.handle_action_pblock(request_frame.address, next)?;
+                return self.finish_response(request_frame.address, None, response);
+            }
+            ActionRequest::WithList(_) => return Err(ServerError::DlmsError(DlmsError::Xdlms)),
+        };
+        if let Some(context) = self.association_context_mut(request_frame.address) {
+            context.long_action = None;
+        }
+        let response_bytes = self.invoke_requested_method(request_frame.address, action_req)?;
+        self.finish_response(request_frame.address, None, response_bytes)
+    }
+
+    // Checks the association and the access rights, runs the action callbacks
+    // and invokes the method of an ACTION request, returning the response APDU.
+    fn invoke_requested_method(
+        &mut self,
+        client_address: u16,
+        action_req: ActionRequestNormal,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let response_bytes = if !self.is_associated(client_address) {
+            self.method_access_denied(client_address, &action_req.cosem_method_descriptor);
+            let denial = ActionResponse::Normal(ActionResponseNormal {
+                invoke_id_and_priority: action_req.invoke_id_and_priority,
+                single_response: crate::xdlms::ActionResponseWithOptionalData {
+                    result: ActionResult::ReadWriteDenied,
+                    return_parameters: None,
+                },
+            });
+            denial.to_bytes()?
+        } else {
+            let instance_id = action_req.cosem_method_descriptor.instance_id;
+            let Some(object) = self.resolve_object(client_address, instance_id) else {
+                return Err(ServerError::DlmsError(DlmsError::Xdlms));
+            };
+
+            let method_access = object.method_access_rights();
+            let method_id = action_req.cosem_method_descriptor.method_id;
+            if !Self::method_operation_allowed(&method_access, method_id) {
+                self.method_access_denied(client_address, &action_req.cosem_method_descriptor);
+                let denial = ActionResponse::Normal(ActionResponseNormal {
+                    invoke_id_and_priority: action_req.invoke_id_and_priority,
+                    single_response: crate::xdlms::ActionResponseWithOptionalData {
+                        result: ActionResult::ReadWriteDenied,
+                        return_parameters: None,
+                    },
+                });
+                denial.to_bytes()?
+            } else {
+                let mut parameters = action_req
+                    .method_invocation_parameters
+                    .unwrap_or(crate::types::CosemData::NullData);
+                if let Some(callbacks) = object.callbacks() {
+                    if let Err(result_code) =
+                        callbacks.call_pre_action(object, method_id, &mut parameters)
+                    {
+                        let denial = ActionResponse::Normal(ActionResponseNormal {
+                            invoke_id_and_priority: action_req.invoke_id_and_priority,
+                            single_response: crate::xdlms::ActionResponseWithOptionalData {
+                                result: result_code,
+                                return_parameters: None,
+                            },
+                        });
+                        return Ok(denial.to_bytes()?);
+                    }
+                }
+
+                self.archive_before_reset(instance_id, method_id);
+                let watched = self.watched_values(Some(client_address), instance_id);
+                let Some(object) = self.resolve_object(client_address, instance_id) else {
+                    return Err(ServerError::DlmsError(DlmsError::Xdlms));
+                };
+                let class_id = object.class_id();
+                let script_id = match &parameters {
+                    crate::types::CosemData::LongUnsigned(script_id) => Some(*script_id),
+                    _ => None,
+                };
+                let mut result = object.invoke_method(method_id, parameters);
+
+                if let Some(callbacks) = object.callbacks() {
+                    if let Err(result_code) =
+                        callbacks.call_post_action(object, method_id, &mut result)
+                    {
+                        self.notify_watchers(Some(client_address), instance_id, watched);
+                        let denial = ActionResponse::Normal(ActionResponseNormal {
+                            invoke_id_and_priority: action_req.invoke_id_and_priority,
+                            single_response: crate::xdlms::ActionResponseWithOptionalData {
+                                result: result_code,
+                                return_parameters: None,
+                            },
+                        });
+                        return Ok(denial.to_bytes()?);
+                    }
+                }
+                self.notify_watchers(Some(client_address), instance_id, watched);
+
+                // Script table execute: run the accepted script on the registered objects.
+                if class_id == 9 && method_id == 1 && result.is_some() {
+                    let executed =
+                        script_id.and_then(|script_id| self.execute_script(instance_id, script_id));
+                    if executed.is_none() {
+                        result = None;
+                    }
+                }
+                // Profile generic capture: append an entry from the capture objects.
+                if class_id == 7
+                    && method_id == 2
+                    && result.is_some()
+                    && self.capture_requested_profile(instance_id).is_none()
+                {
+                    result = None;
+                }
+                if class_id == 64 && method_id == 2 && result.is_some() {
+                    self.log_security_event(SecurityEvent::KeyChanged);
+                }
+                // Push setup push: schedule the notification for `poll_pushes`.
+                if class_id == 40 && method_id == 1 && result.is_some() {
+                    self.trigger_push(instance_id, self.monitor_timestamp);
+                }
+                if result.is_some() {
+                    self.evaluate_monitors(self.monitor_timestamp);
+                }
+                let action_res = ActionResponse::Normal(ActionResponseNormal {
+                    invoke_id_and_priority: action_req.invoke_id_and_priority,
+                    single_response: crate::xdlms::ActionResponseWithOptionalData {
+                        result: result
+                            .as_ref()
+                            .map_or(ActionResult::ObjectUnavailable, |_| ActionResult::Success),
+                        return_parameters: result.map(GetDataResult::Data),
+                    },
+                });
+                action_res.to_bytes()?
+            }
+        };
+        Ok(response_bytes)
+    }
+
+    // Clears every cache, including those of the connections not being
+    // served right now, so no client is answered with a stale value.
+    fn clear_response_caches(&mut self) {
+        let connection_states = self
+            .connections
+            .values_mut()
+            .flat_map(|connection| connection.associations.values_mut());
+        for state in self.associations.values_mut().chain(connection_states) {
+            if let AssociationState::Associated { negotiated } = state {
+                if let Some(cache) = negotiated.response_cache.as_mut() {
+                    cache.clear();
+                }
+            }
+        }
+    }
+
+    // Frames the response of a handler, refusing one the client could not
+    // receive. `pending_limit` is the client limit an AARQ is negotiating.
+    fn finish_response(
+        &mut self,
+        client_address: u16,
+        pending_limit: Option<u16>,
+        response_bytes: Vec<u8>,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let client_limit = pending_limit
+            .map(usize::from)
+            .unwrap_or_else(|| self.client_pdu_limit(client_address));
+        if response_bytes.len() > client_limit {
+            return Err(ServerError::DlmsError(DlmsError::Xdlms));
+        }
+        self.build_response_frame(response_bytes)
+    }
+
+    fn access_denied(
+        &mut self,
+        client_address: u16,
+        service: AccessService,
+        descriptor: &CosemAttributeDescriptor,
+    ) {
+        diagnostics::warning!(
+            "client {}: {:?} of attribute {} of {:?} denied",
+            client_address,
+            service,
+            descriptor.attribute_id,
+            descriptor.instance_id
+        );
+        self.log_security_event(SecurityEvent::AccessDenied);
+        if let Some(callback) = self.on_access_denied.as_mut() {
+            callback(&AccessDenied {
+                client_address,
+                service,
+                class_id: descriptor.class_id,
+                instance_id: descriptor.instance_id,
+                index: descriptor.attribute_id,
+            });
+        }
+    }
+
+    // Captures `event` into the security log, if the server keeps one.
+    fn log_security_event(&mut self, event: SecurityEvent) {
+        if !self.security_log {
+            return;
+        }
+        let logged = self
+            .objects
+            .get_mut(&SECURITY_EVENT_CODE_LN)
+            .and_then(|event_code| {
+                event_code.set_attribute(2, CosemData::LongUnsigned(event.code()))
+            });
+        if logged.is_some() {
+            self.capture_profile(SECURITY_LOG_LN);
+        }
+    }
+
+    fn method_access_denied(&mut self, client_address: u16, descriptor: &CosemMethodDescriptor) {
+        diagnostics::warning!(
+            "client {}: action on method {} of {:?} denied",
+            client_address,
+            descriptor.method_id,
+            descriptor.instance_id
+        );
+        self.log_security_event(SecurityEvent::AccessDenied);
+        if let Some(callback) = self.on_access_denied.as_mut() {
+            callback(&AccessDenied {
+                client_address,
+                service: AccessService::Action,
+                class_id: descriptor.class_id,
+                instance_id: descriptor.instance_id,
+                index: descriptor.method_id,
+            });
+        }
+    }
+
+    fn client_pdu_limit(&self, client_address: u16) -> usize {
+        self.association_context(client_address)
+            .map(|context| context.client_max_receive_pdu_size)
+            .unwrap_or(self.max_receive_pdu_size()) as usize
+    }
+
+    // Checks the association and the access rights, runs the write callbacks
+    // and writes the value of a SET request.
+    fn write_requested_attribute(
+        &mut self,
+        client_address: u16,
+        descriptor: &CosemAttributeDescriptor,
+        selection: Option<&SelectiveAccessDescriptor>,
+        value: CosemData,
+    ) -> Result<DataAccessResult, ServerError<T::Error>> {
+        if !self.is_associated(client_address) {
+            self.access_denied(client_address, AccessService::Set, descriptor);
+            return Ok(DataAccessResult::ReadWriteDenied);
+        }
+        let watched = self.watched_values(Some(client_address), descriptor.instance_id);
+        let Some(object) = self.resolve_object(client_address, descriptor.instance_id) else {
+            return Err(ServerError::DlmsError(DlmsError::Xdlms));
+        };
+
+        let attribute_id = descriptor.attribute_id;
+        if !Self::attribute_operation_allowed(
+            &object.attribute_access_rights(),
+            attribute_id,
+            AttributeOperation::Write,
+        ) {
+            self.access_denied(client_address, AccessService::Set, descriptor);
+            return Ok(DataAccessResult::ReadWriteDenied);
+        }
+        let response_code = match selection {
+            None => Self::write_validated(object, attribute_id, value),
+            Some(selection) => {
+                if !Self::selection_declared(
+                    &object.attribute_access_rights(),
+                    attribute_id,
+                    Some(selection),
+                ) {
+                    return Ok(DataAccessResult::ScopeOfAccessViolated);
+                }
+                match object.write_attribute_selective(attribute_id, selection, value) {
+                    Ok(()) => DataAccessResult::Success,
+                    Err(result_code) => result_code,
+                }
+            }
+        };
+        let reconfigured = object.monitored_value().is_some();
+        self.notify_watchers(Some(client_address), descriptor.instance_id, watched);
+        if response_code == DataAccessResult::Success {
+            if reconfigured {
+                self.resolve_monitors();
+            }
+            self.evaluate_monitors(self.monitor_timestamp);
+        }
+        Ok(response_code)
+    }
+
+    // Writes an attribute once its value passes the attribute specs and the
+    // pre-write callback; the post-write callback can still refuse it.
+    fn write_validated(
+        object: &mut dyn CosemObject,
+        attribute_id: CosemObjectAttributeId,
+        mut value: CosemData,
+    ) -> DataAccessResult {
+        if let Err(result_code) = validate_attribute_value(&*object, attribute_id, &value) {
+            return result_code;
+        }
+        if let Some(callbacks) = object.callbacks() {
+            if let Err(result_code) = callbacks.call_pre_write(object, attribute_id, &mut value) {
+                return result_code;
+            }
+        }
+
+        let result = object.write_attribute(attribute_id, value.clone());
+        result.map_or_else(
+            |result_code| result_code,
+            |_| {
+                if let Some(callbacks) = object.callbacks() {
+                    if let Err(result_code) =
+                        callbacks.call_post_write(object, attribute_id, &value)
+                    {
+                        return result_code;
+                    }
+                }
+                DataAccessResult::Success
+            },
+        )
+    }
+
+    // Starts buffering a value sent in blocks. The access rights are checked
+    // up front so a denied write is refused before the value is transferred.
+    fn handle_set_first_datablock(
+        &mut self,
+        client_address: u16,
+        request: SetRequestWithFirstDatablock,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let descriptor = request.cosem_attribute_descriptor;
+        let block_number = request.datablock.block_number;
+        let refuse = |result| {
+            SetResponse::LastDatablock(SetResponseLastDatablock {
+                invoke_id_and_priority: request.invoke_id_and_priority,
+                result,
+                block_number,
+            })
+            .to_bytes()
+        };
+        if !self.is_associated(client_address) {
+            self.access_denied(client_address, AccessService::Set, &descriptor);
+            return Ok(refuse(DataAccessResult::ReadWriteDenied)?);
+        }
+        let access = self
+            .resolve_object(client_address, descriptor.instance_id)
+            .map(|object| object.attribute_access_rights());
+        let Some(access) = access else {
+            return Ok(refuse(DataAccessResult::ObjectUndefined)?);
+        };
+        if !Self::attribute_operation_allowed(
+            &access,
+            descriptor.attribute_id,
+            AttributeOperation::Write,
+        ) {
+            self.access_denied(client_address, AccessService::Set, &descriptor);
+            return Ok(refuse(DataAccessResult::ReadWriteDenied)?);
+        }
+        if !Self::selection_declared(
+            &access,
+            descriptor.attribute_id,
+            request.access_selection.as_ref(),
+        ) {
+            return Ok(refuse(DataAccessResult::ScopeOfAccessViolated)?);
+        }
+        if block_number != 1 {
+            return Ok(refuse(DataAccessResult::DataBlockNumberInvalid)?);
+        }
+
+        let transfer = Box::new(LongSetTransfer {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            descriptor,
+            access_selection: request.access_selection,
+            block_number,
+            raw_data: request.datablock.raw_data,
+        });
+        self.continue_long_set(client_address, transfer, request.datablock.last_block)
+    }
+
+    fn handle_set_datablock(
+        &mut self,
+        client_address: u16,
+        request: SetRequestWithDatablock,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let block_number = request.datablock.block_number;
+        let transfer = self
+            .association_context_mut(client_address)
+            .and_then(|context| context.long_set.take());
+        let result = match transfer {
+            None => DataAccessResult::NoLongSetInProgress,
+            Some(mut transfer) if block_number == transfer.block_number + 1 => {
+                transfer.block_number = block_number;
+                transfer
+                    .raw_data
+                    .extend_from_slice(&request.datablock.raw_data);
+                return self.continue_long_set(
+                    client_address,
+                    transfer,
+                    request.datablock.last_block,
+                );
+            }
+            // A block out of sequence aborts the transfer.
+            Some(_) => DataAccessResult::DataBlockNumberInvalid,
+        };
+        Ok(SetResponse::LastDatablock(SetResponseLastDatablock {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            result,
+            block_number,
+        })
+        .to_bytes()?)
+    }
+
+    // Acknowledges a block, or decodes and writes the value after the last.
+    fn continue_long_set(
+        &mut self,
+        client_address: u16,
+        transfer: Box<LongSetTransfer>,
+        last_block: bool,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        if transfer.raw_data.len() > self.max_long_transfer_size {
+            return Ok(SetResponse::LastDatablock(SetResponseLastDatablock {
+                invoke_id_and_priority: transfer.invoke_id_and_priority,
+                result: DataAccessResult::LongSetAborted,
+                block_number: transfer.block_number,
+            })
+            .to_bytes()?);
+        }
+        if !last_block {
+            let response = SetResponse::Datablock(SetResponseDatablock {
+                invoke_id_and_priority: transfer.invoke_id_and_priority,
+                block_number: transfer.block_number,
+            });
+            if let Some(context) = self.association_context_mut(client_address) {
+                context.long_set = Some(transfer);
+            }
+            return Ok(response.to_bytes()?);
+        }
+
+        let result = match decode_data(&transfer.raw_data) {
+            Ok((value, [])) => self.write_requested_attribute(
+                client_address,
+                &transfer.descriptor,
+                transfer.access_selection.as_ref(),
+                value,
+            )?,
+            _ => DataAccessResult::TypeUnmatched,
+        };
+        Ok(SetResponse::LastDatablock(SetResponseLastDatablock {
+            invoke_id_and_priority: transfer.invoke_id_and_priority,
+            result,
+            block_number: transfer.block_number,
+        })
+        .to_bytes()?)
+    }
+
+    // Starts buffering method invocation parameters sent in pblocks. The
+    // access rights are checked up front, as for a long SET.
+    fn handle_action_first_pblock(
+        &mut self,
+        client_address: u16,
+        request: ActionRequestWithFirstPblock,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let descriptor = request.cosem_method_descriptor;
+        let refuse = |result| {
+            ActionResponse::Normal(ActionResponseNormal {
+                invoke_id_and_priority: request.invoke_id_and_priority,
+                single_response: crate::xdlms::ActionResponseWithOptionalData {
+                    result,
+                    return_parameters: None,
+                },
+            })
+            .to_bytes()
+        };
+        if !self.is_associated(client_address) {
+            self.method_access_denied(client_address, &descriptor);
+            return Ok(refuse(ActionResult::ReadWriteDenied)?);
+        }
+        let access = self
+            .resolve_object(client_address, descriptor.instance_id)
+            .map(|object| object.method_access_rights());
+        let Some(access) = access else {
+            return Ok(refuse(ActionResult::ObjectUndefined)?);
+        };
+        if !Self::method_operation_allowed(&access, descriptor.method_id) {
+            self.method_access_denied(client_address, &descriptor);
+            return Ok(refuse(ActionResult::ReadWriteDenied)?);
+        }
+        if request.pblock.block_number != 1 {
+            return Ok(refuse(ActionResult::LongActionAborted)?);
+        }
+
+        let transfer = Box::new(LongActionTransfer {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            descriptor,
+            block_number: request.pblock.block_number,
+            raw_data: request.pblock.raw_data,
+        });
+        self.continue_long_action(client_address, transfer, request.pblock.last_block)
+    }
+
+    fn handle_action_pblock(
+        &mut self,
+        client_address: u16,
+        request: ActionRequestWithPblock,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let block_number = request.pblock.block_number;
+        let transfer = self
+            .association_context_mut(client_address)
+            .and_then(|context| context.long_action.take());
+        let result = match transfer {
+            None => ActionResult::NoLongActionInProgress,
+            Some(mut transfer) if block_number == transfer.block_number + 1 => {
+                transfer.block_number = block_number;
+                transfer
+                    .raw_data
+                    .extend_from_slice(&request.pblock.raw_data);
+                return self.continue_long_action(
+                    client_address,
+                    transfer,
+                    request.pblock.last_block,
+                );
+            }
+            // A block out of sequence aborts the transfer.
+            Some(_) => ActionResult::LongActionAborted,
+        };
+        Ok(ActionResponse::Normal(ActionResponseNormal {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            single_response: crate::xdlms::ActionResponseWithOptionalData {
+                result,
+                return_parameters: None,
+            },
+        })
+        .to_bytes()?)
+    }
+
+    // Acknowledges a pblock, or decodes the parameters and invokes the method
+    // after the last.
+    fn continue_long_action(
+        &mut self,
+        client_address: u16,
+        transfer: Box<LongActionTransfer>,
+        last_block: bool,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        if transfer.raw_data.len() > self.max_long_transfer_size {
+            return Ok(ActionResponse::Normal(ActionResponseNormal {
+                invoke_id_and_priority: transfer.invoke_id_and_priority,
+                single_response: crate::xdlms::ActionResponseWithOptionalData {
+                    result: ActionResult::LongActionAborted,
+                    return_parameters: None,
+                },
+            })
+            .to_bytes()?);
+        }
+        if !last_block {
+            let response = ActionResponse::NextPblock(ActionResponseNextPblock {
+                invoke_id_and_priority: transfer.invoke_id_and_priority,
+                block_number: transfer.block_number,
+            });
+            if let Some(context) = self.association_context_mut(client_address) {
+                context.long_action = Some(transfer);
+            }
+            return Ok(response.to_bytes()?);
+        }
+
+        let LongActionTransfer {
+            invoke_id_and_priority,
+            descriptor,
+            raw_data,
+            ..
+        } = *transfer;
+        let method_invocation_parameters = match decode_data(&raw_data) {
+            Ok((parameters, [])) => Some(parameters),
+            _ if raw_data.is_empty() => None,
+            _ => {
+                return Ok(ActionResponse::Normal(ActionResponseNormal {
+                    invoke_id_and_priority,
+                    single_response: crate::xdlms::ActionResponseWithOptionalData {
+                        result: ActionResult::TypeUnmatched,
+                        return_parameters: None,
+                    },
+                })
+                .to_bytes()?)
+            }
+        };
+        self.invoke_requested_method(
+            client_address,
+            ActionRequestNormal {
+                invoke_id_and_priority,
+                cosem_method_descriptor: descriptor,
+                method_invocation_parameters,
+            },
+        )
+    }
+
+    // The configured receive size, bounded by the buffers of the crate.
+    fn max_receive_pdu_size(&self) -> u16 {
+        self.association_parameters
+            .max_receive_pdu_size
+            .min(MAX_PDU_SIZE as u16)
+    }
+
+    // Replies with get-response-normal when the whole value fits into the client
+    // PDU, otherwise with the first block of a get-response-with-datablock transfer.
+    fn start_long_get(
+        &mut self,
+        client_address: u16,
+        mut transfer: LongGetTransfer,
+    ) -> Result<Vec<u8>, DlmsError> {
+        let normal_capacity = self
+            .client_pdu_limit(client_address)
+            .saturating_sub(GET_RESPONSE_NORMAL_OVERHEAD);
+        self.fill_long_get(client_address, &mut transfer, normal_capacity + 1)?;
+        if transfer.is_exhausted() && transfer.pending.len() <= normal_capacity {
+            let (data, _) = decode_data(&transfer.pending)?;
+            return GetResponse::Normal(GetResponseNormal {
+                invoke_id_and_priority: transfer.invoke_id_and_priority,
+                result: GetDataResult::Data(data),
+            })
+            .to_bytes();
+        }
+        self.next_get_block(client_address, transfer)
+    }
+
+    fn handle_get_next(
+        &mut self,
+        client_address: u16,
+        request: GetRequestNext,
+    ) -> Result<Vec<u8>, DlmsError> {
+        let transfer = match self.association_context_mut(client_address) {
+            Some(context) => context.long_get.take(),
+            None => {
+                return GetResponse::Normal(GetResponseNormal {
+                    invoke_id_and_priority: request.invoke_id_and_priority,
+                    result: GetDataResult::DataAccessResult(DataAccessResult::ReadWriteDenied),
+                })
+                .to_bytes()
+            }
+        };
+        let result = match transfer {
+            None => DataAccessResult::NoLongGetInProgress,
+            Some(transfer) if transfer.block_number == request.block_number => {
+                return self.next_get_block(client_address, transfer);
+            }
+            Some(_) => DataAccessResult::DataBlockNumberInvalid,
+        };
+        GetResponse::Normal(GetResponseNormal {
+            invoke_id_and_priority: request.invoke_id_and_priority,
+            result: GetDataResult::DataAccessResult(result),
+        })
+        .to_bytes()
+    }
+
+    fn next_get_block(
+        &mut self,
+        client_address: u16,
+        mut transfer: LongGetTransfer,
+    ) -> Result<Vec<u8>, DlmsError> {
+        let capacity = self
+            .client_pdu_limit(client_address)
+            .saturating_sub(GET_RESPONSE_BLOCK_OVERHEAD);
+        if capacity == 0 {
+            return Err(DlmsError::Xdlms);
+        }
+        self.fill_long_get(client_address, &mut transfer, capacity)?;
+
+        let take = capacity.min(transfer.pending.len());
+        let raw_data = transfer.pending.drain(..take).collect();
+        transfer.block_number += 1;
+        let last_block = transfer.is_exhausted() && transfer.pending.is_empty();
+        let response = GetResponse::WithDataBlock(GetResponseWithDatablock {
+            invoke_id_and_priority: transfer.invoke_id_and_priority,
+            result: DataBlockG {
+                last_block,
+                block_number: transfer.block_number,
+                result: DataBlockResult::RawData(raw_data),
+            },
+        });
+
+        if let Some(context) = self.association_context_mut(client_address) {
+            context.long_get = if last_block { None } else { Some(transfer) };
+        }
+        response.to_bytes()
+    }
+
+    // Pulls further bytes from a streamed source until `target` bytes are
+    // pending or the value is complete.
+    fn fill_long_get(
+        &self,
+        client_address: u16,
+        transfer: &mut LongGetTransfer,
+        target: usize,
+    ) -> Result<(), DlmsError> {
+        let LongGetSource::Stream {
+            logical_name,
+            stream,
+            finished,
+        } = &mut transfer.source
+        else {
+            return Ok(());
+        };
+        if transfer.pending.len() >= target || *finished {
+            return Ok(());
+        }
+        let object = self
+            .find_object(client_address, *logical_name)
+            .ok_or(DlmsError::Cosem)?;
+        *finished = stream.encode_next(object, &mut transfer.pending, target)?;
+        Ok(())
+    }
+
+    fn association_context(&self, client_address: u16) -> Option<&AssociationContext> {
+        match self.associations.get(&client_address) {
+            Some(AssociationState::Associated { negotiated }) => Some(negotiated),
+            _ => None,
+        }
+    }
+
+    fn association_context_mut(&mut self, client_address: u16) -> Option<&mut AssociationContext> {
+        match self.associations.get_mut(&client_address) {
+            Some(AssociationState::Associated { negotiated }) => Some(negotiated),
+            _ => None,
+        }
+    }
+
+    fn is_associated(&self, client_address: u16) -> bool {
+        self.association_context(client_address).is_some()
+    }
+
+    // A refused AARQ ends an established association, while a challenge stays
+    // open for another answer.
+    fn refuse_association(&mut self, client_address: u16) {
+        if !matches!(
+            self.associations.get(&client_address),
+            Some(AssociationState::PendingAuth { .. })
+        ) {
+            self.transition(client_address, AssociationState::Idle);
+        }
+    }
+
+    // Moves a client to `next` and returns the state it left, or `None` when
+    // the state machine does not allow the step.
+    fn transition(
+        &mut self,
+        client_address: u16,
+        next: AssociationState,
+    ) -> Option<AssociationState> {
+        let current = self.associations.get(&client_address);
+        if !current.unwrap_or(&AssociationState::Idle).can_become(&next) {
+            return None;
+        }
+        diagnostics::info!(
+            "client {}: association {} -> {}",
+            client_address,
+            current.map_or("idle", AssociationState::name),
+            next.name()
+        );
+        let previous = match next {
+            AssociationState::Idle => self.associations.remove(&client_address),
+            next => self.associations.insert(client_address, next),
+        };
+        Some(previous.unwrap_or(AssociationState::Idle))
+    }
+
+    // A client that is authenticating or releasing cannot be served at all,
+    // unlike one that never associated, whose requests are denied one by one.
+    fn state_exception(&self, client_address: u16) -> Option<ExceptionResponse> {
+        match self.associations.get(&client_address)? {
+            AssociationState::PendingAuth { .. } | AssociationState::Releasing => {
+                Some(ExceptionResponse {
+                    state_error: StateError::ServiceNotAllowed,
+                    service_error: ServiceError::OperationNotPossible,
+                })
+            }
+            AssociationState::Idle | AssociationState::Associated { .. } => None,
+        }
+    }
+
+    // Encodes into the frame buffer and keeps `information` to build the
+    // next response in.
+    fn build_response_frame(
+        &mut self,
+        information: Vec<u8>,
+    ) -> Result<Vec<u8>, ServerError<T::Error>> {
+        let frame = HdlcFrame {
+            address: self.address,
+            control: 0,
+            information,
+            ..Default::default()
+        };
+        let mut bytes = core::mem::take(&mut self.response_frame);
+        bytes.clear();
+        frame.encode_into(&mut bytes)?;
+        self.response_information = frame.information;
+        Ok(bytes)
+    }
+
+    // Values of the watched attributes of an object, taken before it may
+    // change. `client_address` picks the association object of a client.
+    fn watched_values(
+        &self,
+        client_address: Option<u16>,
+        logical_name: [u8; 6],
+    ) -> Vec<(CosemObjectAttributeId, Option<CosemData>)> {
+        let object = match client_address {
+            Some(client_address) => self.find_object(client_address, logical_name),
+            None => self
+                .objects
+                .get(&logical_name)
+                .map(|object| object.as_ref()),
+        };
+        self.watches
+            .range(
+                (logical_name, CosemObjectAttributeId::MIN)
+                    ..=(logical_name, CosemObjectAttributeId::MAX),
+            )
+            .map(|((_, attribute_id), _)| {
+                let value = object.and_then(|object| object.get_attribute(*attribute_id));
+                (*attribute_id, value)
+            })
+            .collect()
+    }
+
+    // Reports the watched attributes whose value differs from `watched`.
+    fn notify_watchers(
+        &mut self,
+        client_address: Option<u16>,
+        logical_name: [u8; 6],
+        watched: Vec<(CosemObjectAttributeId, Option<CosemData>)>,
+    ) {
+        for (attribute_id, old_value) in watched {
+            let object = match client_address {
+                Some(client_address) => self.find_object(client_address, logical_name),
+                None => self
+                    .objects
+                    .get(&logical_name)
+                    .map(|object| object.as_ref()),
+            };
+            let new_value = object.and_then(|object| object.get_attribute(attribute_id));
+            if new_value == old_value {
+                continue;
+            }
+            let change = AttributeChange {
+                logical_name,
+                attribute_id,
+                old_value,
+                new_value,
+            };
+            for callback in self
+                .watches
+                .get_mut(&(logical_name, attribute_id))
+                .into_iter()
+                .flatten()
+            {
+                callback(&change);
+            }
+        }
+    }
+
+    fn is_client_association(&self, client_address: u16, logical_name: [u8; 6]) -> bool {
+        logical_name == CURRENT_ASSOCIATION_LN
+            || self.association_logical_names.get(&client_address) == Some(&logical_name)
+    }
+
+    // Shared counterpart of `resolve_object`.
+    fn find_object(&self, client_address: u16, logical_name: [u8; 6]) -> Option<&dyn CosemObject> {
+        if self.is_client_association(client_address, logical_name) {
+            if let Some(association) = self.client_association_instances.get(&client_address) {
+                return Some(association.as_ref());
+            }
+        }
+        self.objects
+            .get(&logical_name)
+            .map(|object| object.as_ref())
+    }
+
+    fn resolve_object(
+        &mut self,
+        client_address: u16,
+        logical_name: [u8; 6],
+    ) -> Option<&mut dyn CosemObject> {
+        if self.is_client_association(client_address, logical_name) {
+            if let Some(association) = self.client_association_instances.get_mut(&client_address) {
+                return Some(association.as_mut());
+            }
+        }
+
+        if let Some(object) = self.objects.get_mut(&logical_name) {
+            return Some(object.as_mut());
+        }
+
+        None
+    }
+
+    fn negotiate_initiate_response(
+        &self,
+        request: &InitiateRequest,
+    ) -> Result<InitiateResponse, InitiateValidationError> {
+        if !request.response_allowed {
+            return Err(InitiateValidationError::ResponseNotAllowed);
+        }
+
+        if request.proposed_dlms_version_number != self.association_parameters.dlms_version {
+            return Err(InitiateValidationError::DlmsVersionMismatch);
+        }
+
+        if request.client_max_receive_pdu_size == 0 {
+            return Err(InitiateValidationError::InvalidClientPduSize);
+        }
+
+        let negotiated_conformance = self
+            .association_parameters
+            .conformance
+            .intersection(&request.proposed_conformance);
+
+        if negotiated_conformance.is_empty() {
+            return Err(InitiateValidationError::NoCommonConformance);
+        }
+
+        let mut response = self
+            .association_parameters
+            .to_initiate_response(negotiated_conformance);
+        response.server_max_receive_pdu_size = self.max_receive_pdu_size();
+
+        if response.negotiated_quality_of_service.is_none() {
+            response.negotiated_quality_of_service = request.proposed_quality_of_service;
+        }
+
+        Ok(response)
+    }
+
+    fn attribute_operation_allowed(
+        descriptors: &[AttributeAccessDescriptor],
+        attribute_id: CosemObjectAttributeId,
+        operation: AttributeOperation,
+    ) -> bool {
+        descriptors
+            .iter()
+            .find(|descriptor| descriptor.attribute_id == attribute_id)
+            .is_some_and(|descriptor| match operation {
+                AttributeOperation::Read => matches!(
+                    descriptor.access_mode,
+                    AttributeAccessMode::Read | AttributeAccessMode::ReadWrite
+                ),
+                AttributeOperation::Write => matches!(
+                    descriptor.access_mode,
+                    AttributeAccessMode::Write | AttributeAccessMode::ReadWrite
+                ),
+            })
+    }
+
+    // Whether the access selector of a request, if any, is declared for the
+    // attribute in the access rights of the object.
+    fn selection_declared(
+        descriptors: &[AttributeAccessDescriptor],
+        attribute_id: CosemObjectAttributeId,
+        selection: Option<&SelectiveAccessDescriptor>,
+    ) -> bool {
+        let Some(selection) = selection else {
+            return true;
+        };
+        descriptors
+            .iter()
+            .find(|descriptor| descriptor.attribute_id == attribute_id)
+            .is_some_and(|descriptor| descriptor.supports_selector(selection.access_selector))
+    }
+
+    fn method_operation_allowed(
+        descriptors: &[MethodAccessDescriptor],
+        method_id: CosemObjectMethodId,
+    ) -> bool {
+        descriptors.iter().any(|descriptor| {
+            descriptor.method_id == method_id
+                && matches!(descriptor.access_mode, MethodAccessMode::Access)
+        })
+    }
+}
+
+fn established_clients(associations: &BTreeMap<u16, AssociationState>) -> Vec<u16> {
+    associations
+        .iter()
+        .filter(|(_, state)| matches!(state, AssociationState::Associated { .. }))
+        .map(|(&client_address, _)| client_address)
+        .collect()
+}
+
+// The association state a server keeps apart for each connection.
+#[derive(Default)]
+struct ConnectionAssociations {
+    associations: BTreeMap<u16, AssociationState>,
+    client_association_instances: BTreeMap<u16, Box<dyn CosemObject>>,
+    client_interfaces: BTreeMap<u16, u8>,
+}
+
+// Where the application association of a client stands.
+#[derive(Debug)]
+enum AssociationState {
+    Idle,
+    // An LLS challenge was sent in the AARE; the next AARQ has to answer it.
+    PendingAuth { challenge: Vec<u8> },
+    Associated { negotiated: AssociationContext },
+    Releasing,
+}
+
+impl AssociationState {
+    // Only an established association can be released, and a release can
+    // only end in Idle. A new AARQ may replace any other state.
+    fn can_become(&self, next: &AssociationState) -> bool {
+        match (self, next) {
+            (AssociationState::Releasing, next) => matches!(next, AssociationState::Idle),
+            (current, AssociationState::Releasing) => {
+                matches!(current, AssociationState::Associated { .. })
+            }
+            _ => true,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            AssociationState::Idle => "idle",
+            AssociationState::PendingAuth { .. } => "pending authentication",
+            AssociationState::Associated { .. } => "associated",
+            AssociationState::Releasing => "releasing",
+        }
+    }
+
+    fn challenge(&self) -> Option<&[u8]> {
+        match self {
+            AssociationState::PendingAuth { challenge } => Some(challenge),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AssociationContext {
+    client_max_receive_pdu_size: u16,
+    long_get: Option<LongGetTransfer>,
+    long_set: Option<Box<LongSetTransfer>>,
+    long_action: Option<Box<LongActionTransfer>>,
+    info: Option<AssociationInfo>,
+    response_cache: Option<ResponseCache>,
+    // SET or ACTION served last, for duplicate detection.
+    last_request: Option<Box<LastRequest>>,
+}
+
+impl AssociationContext {
+    fn new(client_max_receive_pdu_size: u16) -> Self {
+        Self {
+            client_max_receive_pdu_size,
+            long_get: None,
+            long_set: None,
+            long_action: None,
+            info: None,
+            response_cache: None,
+            last_request: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LastRequest {
+    invoke_id_and_priority: InvokeIdAndPriority,
+    hash: u64,
+    // The response frame, before any encryption.
+    response: Vec<u8>,
+}
+
+impl LastRequest {
+    fn repeats(&self, request: &LastRequest) -> bool {
+        self.invoke_id_and_priority == request.invoke_id_and_priority && self.hash == request.hash
+    }
+}
+
+fn request_hash(information: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    information.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Encoded values of the attributes an association read last, by attribute
+// descriptor and selection; the least recently used entry goes first.
+#[derive(Debug)]
+struct ResponseCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: VecDeque<CachedValue>,
+}
+
+#[derive(Debug)]
+struct CachedValue {
+    descriptor: CosemAttributeDescriptor,
+    selection: Option<SelectiveAccessDescriptor>,
+    encoded: Vec<u8>,
+    stored_at: Instant,
+}
+
+impl CachedValue {
+    fn is_for(
+        &self,
+        descriptor: &CosemAttributeDescriptor,
+        selection: Option<&SelectiveAccessDescriptor>,
+    ) -> bool {
+        self.descriptor == *descriptor && self.selection.as_ref() == selection
+    }
+}
+
+impl ResponseCache {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(
+        &mut self,
+        descriptor: &CosemAttributeDescriptor,
+        selection: Option<&SelectiveAccessDescriptor>,
+        now: Instant,
+    ) -> Option<Vec<u8>> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.is_for(descriptor, selection))?;
+        let entry = self.entries.remove(index)?;
+        if now.duration_since(entry.stored_at) >= self.ttl {
+            return None;
+        }
+        let encoded = entry.encoded.clone();
+        self.entries.push_back(entry);
+        Some(encoded)
+    }
+
+    fn insert(
+        &mut self,
+        descriptor: CosemAttributeDescriptor,
+        selection: Option<SelectiveAccessDescriptor>,
+        encoded: Vec<u8>,
+        now: Instant,
+    ) {
+        self.entries
+            .retain(|entry| !entry.is_for(&descriptor, selection.as_ref()));
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(CachedValue {
+            descriptor,
+            selection,
+            encoded,
+            stored_at: now,
+        });
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// get-response-normal carrying a value encoded already, built in `response`.
+fn get_response_normal_data(
+    mut response: Vec<u8>,
+    invoke_id_and_priority: InvokeIdAndPriority,
+    encoded: &[u8],
+) -> Vec<u8> {
+    response.clear();
+    response.reserve(encoded.len() + GET_RESPONSE_NORMAL_OVERHEAD);
+    response.extend_from_slice(&[196, 1, invoke_id_and_priority, 0]);
+    response.extend_from_slice(encoded);
+    response
+}
+
+// Highest attribute and method of an object, which its short names span.
+fn short_name_counts(object: &dyn CosemObject) -> (CosemObjectAttributeId, CosemObjectMethodId) {
+    let attribute_count = object
+        .attribute_access_rights()
+        .iter()
+        .map(|descriptor| descriptor.attribute_id)
+        .max()
+        .unwrap_or(1);
+    let method_count = object
+        .method_access_rights()
+        .iter()
+        .map(|descriptor| descriptor.method_id)
+        .max()
+        .unwrap_or(0);
+    (attribute_count, method_count)
+}
+
+// State of a get-response-with-datablock transfer in progress.
+#[derive(Debug)]
+struct LongGetTransfer {
+    invoke_id_and_priority: InvokeIdAndPriority,
+    block_number: u32,
+    source: LongGetSource,
+    pending: Vec<u8>,
+}
+
+impl LongGetTransfer {
+    fn is_exhausted(&self) -> bool {
+        match &self.source {
+            LongGetSource::Encoded => true,
+            LongGetSource::Stream { finished, .. } => *finished,
+        }
+    }
+}
+
+// A value received in set-request-with-datablock blocks, up to `block_number`.
+#[derive(Debug)]
+struct LongSetTransfer {
+    invoke_id_and_priority: InvokeIdAndPriority,
+    descriptor: CosemAttributeDescriptor,
+    access_selection: Option<SelectiveAccessDescriptor>,
+    block_number: u32,
+    raw_data: Vec<u8>,
+}
+
+// Method invocation parameters received in action-request-with-pblock blocks,
+// up to `block_number`.
+#[derive(Debug)]
+struct LongActionTransfer {
+    invoke_id_and_priority: InvokeIdAndPriority,
+    descriptor: CosemMethodDescriptor,
+    block_number: u32,
+    raw_data: Vec<u8>,
+}
+
+#[derive(Debug)]
+enum LongGetSource {
+    Encoded,
+    Stream {
+        logical_name: [u8; 6],
+        stream: Box<dyn CosemDataStream>,
+        finished: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AttributeOperation {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum InitiateValidationError {
+    ResponseNotAllowed,
+    DlmsVersionMismatch,
+    InvalidClientPduSize,
+    NoCommonConformance,
+}
+
+impl InitiateValidationError {
+    fn initiate_error(self) -> InitiateError {
+        match self {
+            InitiateValidationError::ResponseNotAllowed => InitiateError::Other,
+            InitiateValidationError::DlmsVersionMismatch => InitiateError::DlmsVersionTooLow,
+            InitiateValidationError::InvalidClientPduSize => InitiateError::PduSizeTooShort,
+            InitiateValidationError::NoCommonConformance => InitiateError::IncompatibleConformance,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::activity_calendar::ActivityCalendar;
+    use crate::clock::Clock;
+    use crate::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
+    use crate::demand_register::DemandRegister;
+    use crate::disconnect_control::DisconnectControl;
+    use crate::extended_register::ExtendedRegister;
+    use crate::iec_hdlc_setup::IecHdlcSetup;
+    use crate::limiter::Limiter;
+    use crate::profile_generic::{CaptureObjectDefinition, ProfileGeneric};
+    use crate::push_setup::PushSetup;
+    use crate::register::Register;
+    use crate::register_monitor::{MonitorActionSet, RegisterMonitor, ScriptReference};
+    use crate::sap_assignment::{SapAssignment, SapAssignmentEntry};
+    use crate::scaled_value::Unit;
+    use crate::script_table::{ScriptAction, ScriptTable};
+    use crate::security::lls_authenticate;
+    use crate::security_setup::SecuritySetup;
+    use crate::types::CosemData;
+    use crate::xdlms::{
+        ActionRequest, ActionRequestNormal, ActionResponse, ActionResult, AssociationParameters,
+        Conformance, DataAccessResult, GetDataResult, GetRequest, GetRequestNormal, GetResponse,
+        InitiateRequest, InitiateResponse, ReleaseRequestInformation, SetRequest, SetRequestNormal,
+        SetResponse,
+    };
+
+    struct DummyTransport;
+
+    impl Transport for DummyTransport {
+        type Error = ();
+
+        fn send(&mut self, _bytes: &[u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn build_hdlc_request(address: u16, aarq: AarqApdu) -> Vec<u8> {
+        let frame = HdlcFrame {
+            address,
+            control: 0,
+            information: aarq.to_bytes().expect("failed to serialize aarq"),
+            ..Default::default()
+        };
+
+        frame.to_bytes().expect("failed to encode frame")
+    }
+
+    fn parse_aare(bytes: &[u8]) -> AareApdu {
+        let frame = HdlcFrame::from_bytes(bytes).expect("failed to decode frame");
+        AareApdu::from_bytes(&frame.information)
+            .expect("failed to decode aare")
+            .1
+    }
+
+    fn parse_rlre(bytes: &[u8]) -> ArlreApdu {
+        let frame = HdlcFrame::from_bytes(bytes).expect("failed to decode frame");
+        ArlreApdu::from_bytes(&frame.information)
+            .expect("failed to decode rlre")
+            .1
+    }
+
+    fn default_initiate_request() -> InitiateRequest {
+        AssociationParameters::default().to_initiate_request()
+    }
+
+    fn associated_context(client_max_receive_pdu_size: u16) -> AssociationState {
+        AssociationState::Associated {
+            negotiated: AssociationContext::new(client_max_receive_pdu_size),
+        }
+    }
+
+    fn activate_association(server: &mut Server<DummyTransport>, address: u16) {
+        let limit = server.association_parameters.max_receive_pdu_size;
+        server
+            .associations
+            .insert(address, associated_context(limit));
+    }
+
+    #[test]
+    fn response_cache_evicts_the_least_recently_used_and_expired_values() {
+        let descriptor = |attribute_id| CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: [1, 0, 1, 8, 0, 255],
+            attribute_id,
+        };
+        let start = Instant::now();
+        let mut cache = ResponseCache::new(2, Duration::from_secs(10));
+        cache.insert(descriptor(2), None, vec![2], start);
+        cache.insert(descriptor(3), None, vec![3], start);
+        assert_eq!(cache.get(&descriptor(2), None, start), Some(vec![2]));
+
+        cache.insert(descriptor(4), None, vec![4], start);
+        assert_eq!(cache.get(&descriptor(3), None, start), None);
+        assert_eq!(cache.get(&descriptor(2), None, start), Some(vec![2]));
+        let selection = SelectiveAccessDescriptor {
+            access_selector: 1,
+            access_parameters: CosemData::NullData,
+        };
+        assert_eq!(cache.get(&descriptor(2), Some(&selection), start), None);
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(cache.get(&descriptor(4), None, later), None);
+        assert!(cache
+            .entries
+            .iter()
+            .all(|entry| entry.descriptor != descriptor(4)));
+    }
+
+    #[test]
+    fn association_object_list_tracks_registered_objects() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        {
+            let list = server.association_object_lists[&METER_READER_ASSOCIATION_LN]
+                .lock()
+                .expect("association list poisoned");
+            let logical_names: Vec<[u8; 6]> = list.iter().map(|entry| entry.logical_name).collect();
+            assert_eq!(logical_names.len(), 3);
+            assert!(logical_names.contains(&PUBLIC_ASSOCIATION_LN));
+            assert!(logical_names.contains(&METER_READER_ASSOCIATION_LN));
+            assert!(logical_names.contains(&CONFIGURATOR_ASSOCIATION_LN));
+            for entry in list.iter().filter(|entry| entry.class_id == 15) {
+                assert!(!entry.attribute_access.is_empty());
+            }
+        }
+
+        let logical_name = [0, 0, 1, 0, 0, 255];
+        server.register_object(logical_name, Box::new(Register::new()));
+
+        let list = server.association_object_lists[&METER_READER_ASSOCIATION_LN]
+            .lock()
+            .expect("association list poisoned");
+        assert_eq!(list.len(), 4);
+        let register_entry = list
+            .iter()
+            .find(|entry| entry.logical_name == logical_name)
+            .expect("register not present in association list");
+        assert_eq!(register_entry.class_id, 3);
+        assert_eq!(register_entry.version, 0);
+        assert_eq!(register_entry.attribute_access.len(), 2);
+        assert_eq!(register_entry.method_access.len(), 1);
+    }
+
+    #[test]
+    fn object_list_changes_are_reported() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&changes);
+        server.set_on_object_list_changed(move |change| log.lock().unwrap().push(change.clone()));
+        let before = server.object_list(METER_READER_ASSOCIATION_LN).unwrap();
+
+        let logical_name = [0, 0, 1, 0, 0, 255];
+        server.register_object(logical_name, Box::new(Register::new()));
+        let after = server.object_list(METER_READER_ASSOCIATION_LN).unwrap();
+        assert_eq!(after.len(), before.len() + 1);
+        let entry = after
+            .iter()
+            .find(|entry| entry.logical_name == logical_name)
+            .unwrap()
+            .clone();
+        assert!(changes.lock().unwrap().contains(&ObjectListChange::Added {
+            association: METER_READER_ASSOCIATION_LN,
+            entry: entry.clone(),
+        }));
+        assert!(changes.lock().unwrap().iter().all(|change| matches!(
+            change,
+            ObjectListChange::Added { entry, .. } if entry.logical_name == logical_name
+        )));
+
+        // Replacing an object by an identical one changes no list.
+        changes.lock().unwrap().clear();
+        assert!(server
+            .replace_object(logical_name, Box::new(Register::new()))
+            .is_ok());
+        assert!(changes.lock().unwrap().is_empty());
+
+        server.unregister_object(logical_name);
+        assert!(changes
+            .lock()
+            .unwrap()
+            .contains(&ObjectListChange::Removed {
+                association: METER_READER_ASSOCIATION_LN,
+                entry,
+            }));
+        assert_eq!(
+            server.object_list(METER_READER_ASSOCIATION_LN).unwrap(),
+            before
+        );
+        assert_eq!(server.object_list([0, 0, 40, 0, 9, 255]), None);
+    }
+
+    #[test]
+    fn public_association_lists_only_public_objects() {
+        use crate::tariff::CLOCK_LN;
+
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let register = [1, 0, 1, 8, 0, 255];
+        let configuration = [0, 0, 96, 1, 0, 255];
+        server.register_object(register, Box::new(Register::new()));
+        server.register_object(CLOCK_LN, Box::new(Clock::new()));
+        server.register_object(
+            LOGICAL_DEVICE_NAME_LN,
+            Box::new(LogicalDeviceName::new("ABC", "1").unwrap().to_object()),
+        );
+        server.register_object_with_visibility(
+            configuration,
+            Box::new(Register::new()),
+            ObjectVisibility::Associations(vec![CONFIGURATOR_ASSOCIATION_LN]),
+        );
+
+        let listed = |association| {
+            let list = server.association_object_lists[&association]
+                .lock()
+                .expect("association list poisoned");
+            list.iter()
+                .map(|entry| entry.logical_name)
+                .collect::<Vec<_>>()
+        };
+        let public = listed(PUBLIC_ASSOCIATION_LN);
+        assert_eq!(public.len(), 5);
+        assert!(public.contains(&CLOCK_LN));
+        assert!(public.contains(&LOGICAL_DEVICE_NAME_LN));
+        assert!(public.contains(&METER_READER_ASSOCIATION_LN));
+        assert!(!public.contains(&register));
+
+        let meter_reader = listed(METER_READER_ASSOCIATION_LN);
+        assert!(meter_reader.contains(&register));
+        assert!(!meter_reader.contains(&configuration));
+        assert!(listed(CONFIGURATOR_ASSOCIATION_LN).contains(&configuration));
+    }
+
+    #[test]
+    fn object_model_export_and_import_round_trip() {
+        let logical_name = [1, 0, 1, 8, 0, 255];
+        let mut source = Server::new(0x0001, DummyTransport, None, None);
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(4242))
+            .expect("failed to seed register value");
+        source.register_object(logical_name, Box::new(register));
+
+        let xml = source.export_object_model().to_xml();
+        let model = ObjectModel::from_xml(&xml).expect("failed to parse exported model");
+        let description = model
+            .find(&logical_name)
+            .expect("register missing from exported model");
+        assert_eq!(description.class_id, 3);
+        assert_eq!(description.method_access.len(), 1);
+
+        let mut target = Server::new(0x0001, DummyTransport, None, None);
+        target.register_object(logical_name, Box::new(Register::new()));
+        assert!(target.import_object_model(&model) >= 2);
+        assert_eq!(
+            target
+                .objects
+                .get(&logical_name)
+                .and_then(|object| object.get_attribute(2)),
+            Some(CosemData::DoubleLongUnsigned(4242))
+        );
+    }
+
+    #[test]
+    fn short_name_map_covers_registered_objects() {
+        let logical_name = [1, 0, 1, 8, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        server.register_object(logical_name, Box::new(Register::new()));
+
+        let map = server
+            .short_name_map()
+            .expect("failed to build short name map");
+        assert_eq!(map.entries().len(), 4);
+        let value = map
+            .attribute_short_name(&logical_name, 2)
+            .expect("register value has no short name");
+        assert_eq!(
+            map.resolve(value),
+            Some(crate::short_name::ShortNameReference::Attribute {
+                logical_name,
+                attribute_id: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn unregister_and_replace_keep_object_lists_and_short_names_consistent() {
+        use crate::tariff::CLOCK_LN;
+
+        let energy = [1, 0, 1, 8, 0, 255];
+        let voltage = [1, 0, 32, 7, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        server.register_object(voltage, Box::new(Register::new()));
+        server.register_object(CLOCK_LN, Box::new(Clock::new()));
+        server.register_object(energy, Box::new(Register::new()));
+
+        let ordered: Vec<(u16, [u8; 6])> = server
+            .objects()
+            .map(|(logical_name, object)| (object.class_id(), logical_name))
+            .collect();
+        assert_eq!(
+            ordered,
+            vec![
+                (3, energy),
+                (3, voltage),
+                (8, CLOCK_LN),
+                (15, PUBLIC_ASSOCIATION_LN),
+                (15, METER_READER_ASSOCIATION_LN),
+                (15, CONFIGURATOR_ASSOCIATION_LN),
+            ]
+        );
+        let listed = |server: &Server<DummyTransport>| {
+            server.association_object_lists[&METER_READER_ASSOCIATION_LN]
+                .lock()
+                .expect("association list poisoned")
+                .iter()
+                .map(|entry| entry.logical_name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            listed(&server),
+            ordered
+                .iter()
+                .map(|(_, logical_name)| *logical_name)
+                .collect::<Vec<_>>()
+        );
+
+        let before = server
+            .short_name_map()
+            .expect("failed to build short name map");
+        let removed = server
+            .unregister_object(voltage)
+            .expect("voltage not registered");
+        assert_eq!(removed.class_id(), 3);
+        assert!(server.unregister_object(voltage).is_none());
+        assert!(!listed(&server).contains(&voltage));
+        let after = server
+            .short_name_map()
+            .expect("failed to build short name map");
+        assert_eq!(after.entry_for(&voltage), None);
+        for logical_name in [energy, CLOCK_LN, METER_READER_ASSOCIATION_LN] {
+            assert_eq!(
+                after.base_name(&logical_name),
+                before.base_name(&logical_name)
+            );
+        }
+
+        let configuration = [0, 0, 96, 1, 0, 255];
+        server.register_object_with_visibility(
+            configuration,
+            Box::new(Register::new()),
+            ObjectVisibility::Associations(vec![CONFIGURATOR_ASSOCIATION_LN]),
+        );
+        let base_name = server.short_name_map().unwrap().base_name(&configuration);
+        let mut replacement = Register::new();
+        replacement
+            .set_attribute(2, CosemData::Unsigned(7))
+            .expect("failed to seed register");
+        assert!(server
+            .replace_object(configuration, Box::new(replacement))
+            .is_ok());
+        assert_eq!(
+            server.objects[&configuration].get_attribute(2),
+            Some(CosemData::Unsigned(7))
+        );
+        assert_eq!(
+            server.short_name_map().unwrap().base_name(&configuration),
+            base_name
+        );
+        assert!(!listed(&server).contains(&configuration));
+        assert!(server
+            .replace_object(voltage, Box::new(Register::new()))
+            .is_err());
+        assert!(!server.objects.contains_key(&voltage));
+
+        // Clients of a removed association object are refused.
+        server.unregister_object(CONFIGURATOR_ASSOCIATION_LN);
+        assert!(!server
+            .association_logical_names
+            .values()
+            .any(|logical_name| *logical_name == CONFIGURATOR_ASSOCIATION_LN));
+    }
+
+    fn disconnect_scripts() -> ScriptTable {
+        let mut scripts = ScriptTable::new();
+        for (script_id, method_id) in [(1, 1), (2, 2)] {
+            scripts
+                .add_script(
+                    script_id,
+                    &[ScriptAction {
+                        service: ScriptService::ExecuteMethod,
+                        class_id: 70,
+                        logical_name: [0, 0, 96, 3, 10, 255],
+                        index: method_id,
+                        parameter: CosemData::Integer(0),
+                    }],
+                )
+                .expect("failed to add script");
+        }
+        scripts
+    }
+
+    #[test]
+    fn register_monitor_runs_scripts_on_threshold_crossing() {
+        let register_ln = [1, 0, 1, 7, 0, 255];
+        let disconnect_ln = [0, 0, 96, 3, 10, 255];
+        let scripts_ln = [0, 0, 10, 0, 106, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(500))
+            .expect("failed to seed register");
+        server.register_object(register_ln, Box::new(register));
+        server.register_object(disconnect_ln, Box::new(DisconnectControl::new()));
+        server.register_object(scripts_ln, Box::new(disconnect_scripts()));
+
+        let mut monitor = RegisterMonitor::new();
+        monitor
+            .set_attribute(
+                2,
+                CosemData::Array(vec![CosemData::DoubleLongUnsigned(1000)]),
+            )
+            .expect("failed to set thresholds");
+        monitor
+            .set_attribute(
+                3,
+                MonitoredValue {
+                    class_id: 3,
+                    logical_name: register_ln,
+                    attribute_index: 2,
+                }
+                .to_cosem_data(),
+            )
+            .expect("failed to set monitored value");
+        let script = |script_selector| ScriptReference {
+            script_logical_name: scripts_ln,
+            script_selector,
+        };
+        monitor
+            .set_attribute(
+                4,
+                CosemData::Array(vec![MonitorActionSet {
+                    action_up: script(1),
+                    action_down: script(2),
+                }
+                .to_cosem_data()]),
+            )
+            .expect("failed to set actions");
+        server.register_object([0, 0, 16, 1, 0, 255], Box::new(monitor));
+
+        let mut update = |value: u32| {
+            let object = server
+                .objects
+                .get_mut(&register_ln)
+                .expect("missing register");
+            let previous = object.get_attribute(2).expect("missing value");
+            object
+                .set_attribute(2, CosemData::DoubleLongUnsigned(value))
+                .expect("failed to update register");
+            let executed = server.monitored_value_changed(register_ln, 2, &previous);
+            let state = server.objects[&disconnect_ln].get_attribute(2);
+            (executed, state)
+        };
+
+        assert_eq!(update(800), (0, Some(CosemData::NullData)));
+        assert_eq!(update(1200), (1, Some(CosemData::Boolean(false))));
+        assert_eq!(update(1500), (0, Some(CosemData::Boolean(false))));
+        assert_eq!(update(900), (1, Some(CosemData::Boolean(true))));
+    }
+
+    #[test]
+    fn limiter_disconnects_after_min_over_threshold_duration() {
+        let register_ln = [1, 0, 1, 7, 0, 255];
+        let disconnect_ln = [0, 0, 96, 3, 10, 255];
+        let scripts_ln = [0, 0, 10, 0, 106, 255];
+        let limiter_ln = [0, 0, 17, 0, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        // Registered before the register it monitors: the reference is
+        // resolved once the register shows up.
+        let mut limiter = Limiter::new();
+        let script = |script_selector| ScriptReference {
+            script_logical_name: scripts_ln,
+            script_selector,
+        };
+        for (attribute_id, value) in [
+            (
+                2,
+                MonitoredValue {
+                    class_id: 3,
+                    logical_name: register_ln,
+                    attribute_index: 2,
+                }
+                .to_cosem_data(),
+            ),
+            (4, CosemData::DoubleLongUnsigned(1000)),
+            (6, CosemData::DoubleLongUnsigned(30)),
+            (7, CosemData::DoubleLongUnsigned(60)),
+            (
+                11,
+                CosemData::Structure(vec![script(1).to_cosem_data(), script(2).to_cosem_data()]),
+            ),
+        ] {
+            limiter
+                .set_attribute(attribute_id, value)
+                .expect("failed to configure limiter");
+        }
+        server.register_object(limiter_ln, Box::new(limiter));
+        assert!(server.monitors.is_empty());
+
+        server.register_object(register_ln, Box::new(Register::new()));
+        server.register_object(disconnect_ln, Box::new(DisconnectControl::new()));
+        server.register_object(scripts_ln, Box::new(disconnect_scripts()));
+        assert!(server.monitors.contains_key(&limiter_ln));
+
+        let state =
+            |server: &Server<DummyTransport>| server.objects[&disconnect_ln].get_attribute(2);
+
+        server.evaluate_monitors(0);
+        server
+            .set_object_attribute(register_ln, 2, CosemData::DoubleLongUnsigned(1500))
+            .expect("failed to update register");
+        assert_eq!(server.evaluate_monitors(20), 0);
+        assert_eq!(state(&server), Some(CosemData::NullData));
+        assert_eq!(server.evaluate_monitors(30), 1);
+        assert_eq!(state(&server), Some(CosemData::Boolean(false)));
+
+        server
+            .set_object_attribute(register_ln, 2, CosemData::DoubleLongUnsigned(200))
+            .expect("failed to update register");
+        assert_eq!(server.evaluate_monitors(60), 0);
+        assert_eq!(server.evaluate_monitors(90), 1);
+        assert_eq!(state(&server), Some(CosemData::Boolean(true)));
+    }
+
+    #[test]
+    fn set_request_on_monitored_register_runs_monitor_scripts() {
+        let register_ln = [1, 0, 1, 7, 0, 255];
+        let disconnect_ln = [0, 0, 96, 3, 10, 255];
+        let scripts_ln = [0, 0, 10, 0, 106, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        server.register_object(register_ln, Box::new(Register::new()));
+        server.register_object(disconnect_ln, Box::new(DisconnectControl::new()));
+        server.register_object(scripts_ln, Box::new(disconnect_scripts()));
+
+        let mut monitor = RegisterMonitor::new();
+        monitor
+            .set_attribute(2, CosemData::Array(vec![CosemData::Unsigned(100)]))
+            .expect("failed to set thresholds");
+        monitor
+            .set_attribute(
+                3,
+                MonitoredValue {
+                    class_id: 3,
+                    logical_name: register_ln,
+                    attribute_index: 2,
+                }
+                .to_cosem_data(),
+            )
+            .expect("failed to set monitored value");
+        let script = |script_selector| ScriptReference {
+            script_logical_name: scripts_ln,
+            script_selector,
+        };
+        monitor
+            .set_attribute(
+                4,
+                CosemData::Array(vec![MonitorActionSet {
+                    action_up: script(1),
+                    action_down: script(2),
+                }
+                .to_cosem_data()]),
+            )
+            .expect("failed to set actions");
+        server.register_object([0, 0, 16, 1, 0, 255], Box::new(monitor));
+        server.evaluate_monitors(0);
+        activate_association(&mut server, 0x0010);
+
+        let request = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 3,
+                instance_id: register_ln,
+                attribute_id: 2,
+            },
+            access_selection: None,
+            value: CosemData::Unsigned(150),
+        });
+        let frame = HdlcFrame {
+            address: 0x0010,
+            control: 0,
+            information: request.to_bytes().expect("failed to encode set request"),
+            ..Default::default()
+        };
+        server
+            .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+            .expect("server failed to handle set request");
+
+        assert_eq!(
+            server.objects[&disconnect_ln].get_attribute(2),
+            Some(CosemData::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn script_table_execute_action_runs_script() {
+        let disconnect_ln = [0, 0, 96, 3, 10, 255];
+        let scripts_ln = [0, 0, 10, 0, 106, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        server.register_object(disconnect_ln, Box::new(DisconnectControl::new()));
+        server.register_object(scripts_ln, Box::new(disconnect_scripts()));
+        activate_association(&mut server, 0x0010);
+
+        let mut execute = |script_id: u16| {
+            let request = ActionRequest::Normal(ActionRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_method_descriptor: CosemMethodDescriptor {
+                    class_id: 9,
+                    instance_id: scripts_ln,
+                    method_id: 1,
+                },
+                method_invocation_parameters: Some(CosemData::LongUnsigned(script_id)),
+            });
+            let frame = HdlcFrame {
+                address: 0x0010,
+                control: 0,
+                information: request.to_bytes().expect("failed to encode action request"),
+                ..Default::default()
+            };
+            let response_bytes = server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle action request");
+            let response_frame =
+                HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+            match ActionResponse::from_bytes(&response_frame.information)
+                .expect("failed to decode action response")
+            {
+                ActionResponse::Normal(response) => response.single_response.result,
+                other => panic!("unexpected action response: {other:?}"),
+            }
+        };
+
+        assert_eq!(execute(1), ActionResult::Success);
+        assert_eq!(execute(7), ActionResult::ObjectUnavailable);
+        assert_eq!(
+            server.objects[&disconnect_ln].get_attribute(2),
+            Some(CosemData::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn capture_profile_appends_entry_from_capture_objects() {
+        let profile_ln = [1, 0, 99, 98, 0, 255];
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(7))
+            .expect("failed to seed register");
+        server.register_object(register_ln, Box::new(register));
+
+        let mut profile = ProfileGeneric::new();
+        let capture_objects = [0, 2]
+            .iter()
+            .map(|&attribute_index| {
+                crate::profile_generic::CaptureObjectDefinition {
+                    class_id: 3,
+                    logical_name: register_ln,
+                    attribute_index,
+                    data_index: 0,
+                }
+                .to_cosem_data()
+            })
+            .collect();
+        profile
+            .set_attribute(3, CosemData::Array(capture_objects))
+            .expect("failed to seed capture objects");
+        server.register_object(profile_ln, Box::new(profile));
+
+        server.capture_profile(profile_ln).expect("capture failed");
+
+        let profile = server.objects.get(&profile_ln).expect("missing profile");
+        let Some(CosemData::Array(entries)) = profile.get_attribute(2) else {
+            panic!("buffer is not an array");
+        };
+        assert_eq!(entries.len(), 1);
+        let CosemData::Structure(values) = &entries[0] else {
+            panic!("entry is not a structure");
+        };
+        assert!(matches!(&values[0], CosemData::Structure(whole) if whole.len() == 3));
+        assert_eq!(values[1], CosemData::DoubleLongUnsigned(7));
+        assert_eq!(
+            profile.get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(1))
+        );
+    }
+
+    #[test]
+    fn profile_capture_and_reset_actions_maintain_the_buffer() {
+        let profile_ln = [1, 0, 99, 1, 0, 255];
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(7))
+            .expect("failed to seed register");
+        server.register_object(register_ln, Box::new(register));
+        let mut profile = ProfileGeneric::new();
+        let capture_object = crate::profile_generic::CaptureObjectDefinition {
+            class_id: 3,
+            logical_name: register_ln,
+            attribute_index: 2,
+            data_index: 0,
+        };
+        profile
+            .set_attribute(3, CosemData::Array(vec![capture_object.to_cosem_data()]))
+            .expect("failed to seed capture objects");
+        server.register_object(profile_ln, Box::new(profile));
+        activate_association(&mut server, 0x0010);
+
+        let invoke = |server: &mut Server<DummyTransport>, method_id| {
+            let request = ActionRequest::Normal(ActionRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_method_descriptor: CosemMethodDescriptor {
+                    class_id: 7,
+                    instance_id: profile_ln,
+                    method_id,
+                },
+                method_invocation_parameters: Some(CosemData::Integer(0)),
+            });
+            let frame = HdlcFrame {
+                address: 0x0010,
+                control: 0,
+                information: request.to_bytes().expect("failed to encode action request"),
+                ..Default::default()
+            };
+            let response_bytes = server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle action request");
+            let response_frame =
+                HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+            match ActionResponse::from_bytes(&response_frame.information)
+                .expect("failed to decode action response")
+            {
+                ActionResponse::Normal(response) => response.single_response.result,
+                other => panic!("unexpected action response: {other:?}"),
+            }
+        };
+
+        assert_eq!(invoke(&mut server, 2), ActionResult::Success);
+        assert_eq!(invoke(&mut server, 2), ActionResult::Success);
+        assert_eq!(
+            server.objects[&profile_ln].get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(2))
+        );
+
+        assert_eq!(invoke(&mut server, 1), ActionResult::Success);
+        assert_eq!(
+            server.objects[&profile_ln].get_attribute(2),
+            Some(CosemData::Array(Vec::new()))
+        );
+        assert_eq!(
+            server.objects[&profile_ln].get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        assert_eq!(invoke(&mut server, 3), ActionResult::ReadWriteDenied);
+
+        // A capture object the device does not hold fails the capture.
+        server.objects.remove(&register_ln);
+        assert_eq!(invoke(&mut server, 2), ActionResult::ObjectUnavailable);
+        assert_eq!(
+            server.objects[&profile_ln].get_attribute(2),
+            Some(CosemData::Array(Vec::new()))
+        );
+    }
+
+    #[test]
+    fn update_attribute_validates_without_access_rights_and_captures() {
+        let clock_ln = [0, 0, 1, 0, 0, 255];
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let profile_ln = [1, 0, 99, 1, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        server.register_object(clock_ln, Box::new(Clock::new()));
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(0))
+            .expect("failed to seed register");
+        register
+            .set_attribute(
+                3,
+                CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(30)]),
+            )
+            .expect("failed to seed scaler_unit");
+        server.register_object(register_ln, Box::new(register));
+        let mut profile = ProfileGeneric::new();
+        let capture_object = CaptureObjectDefinition {
+            class_id: 3,
+            logical_name: register_ln,
+            attribute_index: 2,
+            data_index: 0,
+        };
+        profile
+            .set_attribute(3, CosemData::Array(vec![capture_object.to_cosem_data()]))
+            .expect("failed to seed capture objects");
+        server.register_object(profile_ln, Box::new(profile));
+        server.capture_on_update(register_ln, profile_ln);
+
+        // The status of the clock is read-only for clients.
+        server
+            .update_attribute(clock_ln, 4, CosemData::Unsigned(0x80))
+            .expect("status update failed");
+        assert_eq!(
+            server.objects[&clock_ln].get_attribute(4),
+            Some(CosemData::Unsigned(0x80))
+        );
+        assert_eq!(
+            server.update_attribute(clock_ln, 3, CosemData::Long(900)),
+            Err(crate::cosem_object::VALUE_OUT_OF_RANGE)
+        );
+        assert_eq!(
+            server.update_clock_time([0, 0, 1, 0, 0, 1], [0; 12]),
+            Err(DataAccessResult::ObjectUndefined)
+        );
+        let time = [0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0];
+        server
+            .update_clock_time(clock_ln, time)
+            .expect("time update failed");
+        assert_eq!(
+            server.objects[&clock_ln].get_attribute(2),
+            Some(CosemData::OctetString(time.to_vec()))
+        );
+
+        // Rescaled to the scaler of the register.
+        server
+            .update_register_value(register_ln, &ScaledValue::new(15, 2, Unit::WATT_HOUR))
+            .expect("register update failed");
+        assert_eq!(
+            server.objects[&register_ln].get_attribute(2),
+            Some(CosemData::DoubleLongUnsigned(1500))
+        );
+        assert_eq!(
+            server.update_register_value(register_ln, &ScaledValue::new(1, 0, Unit::VOLT)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            server.update_register_value(clock_ln, &ScaledValue::new(1, 0, Unit::VOLT)),
+            Err(DataAccessResult::ObjectClassInconsistent)
+        );
+        let voltage_ln = [1, 0, 32, 7, 0, 255];
+        let mut voltage = Register::new();
+        voltage
+            .set_attribute(2, CosemData::Float32(0.0))
+            .expect("failed to seed register");
+        voltage
+            .set_attribute(
+                3,
+                CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(35)]),
+            )
+            .expect("failed to seed scaler_unit");
+        server.register_object(voltage_ln, Box::new(voltage));
+        server
+            .update_register_value(voltage_ln, &ScaledValue::new(23012, -2, Unit::VOLT))
+            .expect("float register update failed");
+        assert_eq!(
+            server.objects[&voltage_ln].get_attribute(2),
+            Some(CosemData::Float32(230.12))
+        );
+        assert_eq!(
+            server.objects[&profile_ln].get_attribute(7),
+            Some(CosemData::DoubleLongUnsigned(1))
+        );
+    }
+
+    #[test]
+    fn register_reset_archives_into_linked_billing_profile() {
+        let profile_ln = [1, 0, 98, 1, 0, 255];
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(1500))
+            .expect("failed to seed register");
+        server.register_object(register_ln, Box::new(register));
+
+        let mut profile = ProfileGeneric::new();
+        let capture_object = crate::profile_generic::CaptureObjectDefinition {
+            class_id: 3,
+            logical_name: register_ln,
+            attribute_index: 2,
+            data_index: 0,
+        };
+        profile
+            .set_attribute(3, CosemData::Array(vec![capture_object.to_cosem_data()]))
+            .expect("failed to seed capture objects");
+        server.register_object(profile_ln, Box::new(profile));
+        server.link_billing_profile(register_ln, profile_ln);
+
+        assert_eq!(
+            server.invoke_object_method(register_ln, 1, CosemData::Integer(0)),
+            Some(CosemData::DoubleLongUnsigned(1500))
+        );
+        let register = server.objects.get(&register_ln).expect("missing register");
+        assert_eq!(
+            register.get_attribute(2),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        let profile = server.objects.get(&profile_ln).expect("missing profile");
+        assert_eq!(
+            profile.get_attribute(2),
+            Some(CosemData::Array(vec![CosemData::Structure(vec![
+                CosemData::DoubleLongUnsigned(1500)
+            ])]))
+        );
+    }
+
+    #[test]
+    fn billing_period_instances_read_from_the_billing_profile() {
+        use crate::billing::{BILLING_PERIOD_COUNTER_LN, BILLING_PROFILE_LN};
+        use crate::object_model::parse_logical_name;
+        let energy_ln = [1, 0, 1, 8, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x0100;
+        server.register_object(energy_ln, Box::new(Register::new()));
+        server.register_object(
+            BILLING_PERIOD_COUNTER_LN,
+            Box::new(crate::data::Data::new(CosemData::Unsigned(98))),
+        );
+        let mut profile = ProfileGeneric::new();
+        let capture_object = crate::profile_generic::CaptureObjectDefinition {
+            class_id: 3,
+            logical_name: energy_ln,
+            attribute_index: 2,
+            data_index: 0,
+        };
+        profile
+            .set_attribute(3, CosemData::Array(vec![capture_object.to_cosem_data()]))
+            .expect("failed to seed capture objects");
+        server.register_object(BILLING_PROFILE_LN, Box::new(profile));
+        server.configure_billing(BillingConfiguration::default());
+        activate_association(&mut server, association_address);
+
+        // Periods 99, 0 and 1 end with 100, 200 and 300 counted.
+        for energy in [100, 200, 300] {
+            server
+                .objects
+                .get_mut(&energy_ln)
+                .expect("missing register")
+                .set_attribute(2, CosemData::DoubleLongUnsigned(energy))
+                .expect("failed to set energy");
+            server
+                .end_of_billing_period()
+                .expect("end of billing failed");
+        }
+
+        let mut get = |instance_id, attribute_id| {
+            let request = GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 3,
+                    instance_id,
+                    attribute_id,
+                },
+                access_selection: None,
+            });
+            let frame = HdlcFrame {
+                address: association_address,
+                control: 0,
+                information: request.to_bytes().expect("failed to encode get request"),
+                ..Default::default()
+            };
+            let response_bytes = server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle get request");
+            let response_frame =
+                HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+            match GetResponse::from_bytes(&response_frame.information) {
+                Ok(GetResponse::Normal(response)) => response.result,
+                other => panic!("unexpected response: {other:?}"),
+            }
+        };
+
+        let last = parse_logical_name("1.0.1.8.0.VZ").expect("invalid logical name");
+        assert_eq!(
+            get(last, 2),
+            GetDataResult::Data(CosemData::DoubleLongUnsigned(300))
+        );
+        assert_eq!(
+            get([1, 0, 1, 8, 0, 103], 2),
+            GetDataResult::Data(CosemData::DoubleLongUnsigned(100))
+        );
+        assert_eq!(
+            get([1, 0, 1, 8, 0, 0], 2),
+            GetDataResult::Data(CosemData::DoubleLongUnsigned(200))
+        );
+        assert_eq!(
+            get([1, 0, 1, 8, 0, 99], 2),
+            GetDataResult::Data(CosemData::DoubleLongUnsigned(100))
+        );
+        assert_eq!(
+            get(last, 3),
+            GetDataResult::Data(
+                Register::new()
+                    .get_attribute(3)
+                    .expect("missing scaler unit")
+            )
+        );
+        for instance_id in [
+            [1, 0, 1, 8, 0, 104],
+            [1, 0, 1, 8, 0, 98],
+            [1, 0, 2, 8, 0, 101],
+        ] {
+            assert_eq!(
+                get(instance_id, 2),
+                GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined)
+            );
+        }
+    }
+
+    #[test]
+    fn end_of_billing_script_captures_counts_and_resets_demand() {
+        use crate::billing::{
+            BILLING_PERIOD_COUNTER_LN, BILLING_PROFILE_LN, END_OF_BILLING_SCRIPT_TABLE_LN,
+        };
+        let energy_ln = [1, 0, 1, 8, 0, 255];
+        let demand_ln = [1, 0, 1, 4, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        let mut energy = Register::new();
+        energy
+            .set_attribute(2, CosemData::DoubleLongUnsigned(4200))
+            .expect("failed to seed register");
+        server.register_object(energy_ln, Box::new(energy));
+        let mut demand = DemandRegister::new();
+        demand
+            .set_attribute(2, CosemData::DoubleLongUnsigned(17))
+            .expect("failed to seed demand register");
+        server.register_object(demand_ln, Box::new(demand));
+        server.register_object(
+            BILLING_PERIOD_COUNTER_LN,
+            Box::new(crate::data::Data::new(CosemData::Unsigned(3))),
+        );
+
+        let mut profile = ProfileGeneric::new();
+        let capture_objects = [(3, energy_ln), (5, demand_ln)]
+            .iter()
+            .map(|&(class_id, logical_name)| {
+                crate::profile_generic::CaptureObjectDefinition {
+                    class_id,
+                    logical_name,
+                    attribute_index: 2,
+                    data_index: 0,
+                }
+                .to_cosem_data()
+            })
+            .collect();
+        profile
+            .set_attribute(3, CosemData::Array(capture_objects))
+            .expect("failed to seed capture objects");
+        server.register_object(BILLING_PROFILE_LN, Box::new(profile));
+
+        let mut scripts = ScriptTable::new();
+        scripts
+            .add_script(
+                1,
+                &[ScriptAction {
+                    service: ScriptService::ExecuteMethod,
+                    class_id: 7,
+                    logical_name: BILLING_PROFILE_LN,
+                    index: 2,
+                    parameter: CosemData::Integer(0),
+                }],
+            )
+            .expect("failed to add script");
+        server.register_object(END_OF_BILLING_SCRIPT_TABLE_LN, Box::new(scripts));
+        server.configure_billing(BillingConfiguration::default().with_demand_register(demand_ln));
+
+        server
+            .execute_script(END_OF_BILLING_SCRIPT_TABLE_LN, 1)
+            .expect("end of billing failed");
+
+        let profile = server
+            .objects
+            .get(&BILLING_PROFILE_LN)
+            .expect("missing profile");
+        assert_eq!(
+            profile.get_attribute(2),
+            Some(CosemData::Array(vec![CosemData::Structure(vec![
+                CosemData::DoubleLongUnsigned(4200),
+                CosemData::DoubleLongUnsigned(17),
+            ])]))
+        );
+        let counter = server
+            .objects
+            .get(&BILLING_PERIOD_COUNTER_LN)
+            .expect("missing counter");
+        assert_eq!(counter.get_attribute(2), Some(CosemData::Unsigned(4)));
+        let demand = server
+            .objects
+            .get(&demand_ln)
+            .expect("missing demand register");
+        assert_eq!(
+            demand.get_attribute(2),
+            Some(CosemData::DoubleLongUnsigned(0))
+        );
+        let energy = server.objects.get(&energy_ln).expect("missing register");
+        assert_eq!(
+            energy.get_attribute(2),
+            Some(CosemData::DoubleLongUnsigned(4200))
+        );
+    }
+
+    #[test]
+    fn tariff_engine_switches_register_activation_at_day_profile_actions() {
+        use crate::register_activation::{
+            RegisterActivation, RegisterActivationMask, RegisterAssignment,
+        };
+        use crate::tariff::{ACTIVITY_CALENDAR_LN, CLOCK_LN, CURRENT_TARIFF_LN};
+        let activation_ln = [0, 0, 14, 0, 0, 255];
+        let scripts_ln = [0, 0, 10, 0, 100, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        let mut activation = RegisterActivation::new();
+        for (index, logical_name) in [[1, 0, 1, 8, 1, 255], [1, 0, 1, 8, 2, 255]]
+            .into_iter()
+            .enumerate()
+        {
+            activation.add_register(RegisterAssignment {
+                class_id: 3,
+                logical_name,
+            });
+            activation
+                .add_mask(RegisterActivationMask {
+                    name: vec![b'T', b'1' + index as u8],
+                    index_list: vec![index as u8 + 1],
+                })
+                .expect("failed to add mask");
+        }
+        server.register_object(activation_ln, Box::new(activation));
+
+        // Script n activates the mask of tariff n.
+        let mut scripts = ScriptTable::new();
+        for (script_id, mask) in [(1, b"T1"), (2, b"T2")] {
+            scripts
+                .add_script(
+                    script_id,
+                    &[ScriptAction {
+                        service: ScriptService::WriteAttribute,
+                        class_id: 6,
+                        logical_name: activation_ln,
+                        index: 4,
+                        parameter: CosemData::OctetString(mask.to_vec()),
+                    }],
+                )
+                .expect("failed to add script");
+        }
+        server.register_object(scripts_ln, Box::new(scripts));
+
+        // Tariff 1 from 07:00, tariff 2 from 22:00, every day of the year.
+        let action = |hour: u8, selector| {
+            CosemData::Structure(vec![
+                CosemData::OctetString(vec![hour, 0, 0, 0]),
+                CosemData::OctetString(scripts_ln.to_vec()),
+                CosemData::LongUnsigned(selector),
+            ])
+        };
+        let mut calendar = ActivityCalendar::new();
+        calendar
+            .set_attribute(
+                3,
+                CosemData::Array(vec![CosemData::Structure(vec![
+                    CosemData::OctetString(b"YEAR".to_vec()),
+                    CosemData::OctetString(vec![0xFF, 0xFF, 1, 1, 0xFF, 0, 0, 0, 0xFF, 0x80, 0, 0]),
+                    CosemData::OctetString(b"WEEK".to_vec()),
+                ])]),
+            )
+            .expect("failed to seed season profile");
+        let mut week = vec![CosemData::OctetString(b"WEEK".to_vec())];
+        week.extend((0..7).map(|_| CosemData::Unsigned(1)));
+        calendar
+            .set_attribute(4, CosemData::Array(vec![CosemData::Structure(week)]))
+            .expect("failed to seed week profile");
+        calendar
+            .set_attribute(
+                5,
+                CosemData::Array(vec![CosemData::Structure(vec![
+                    CosemData::Unsigned(1),
+                    CosemData::Array(vec![action(7, 1), action(22, 2)]),
+                ])]),
+            )
+            .expect("failed to seed day profile");
+        server.register_object(ACTIVITY_CALENDAR_LN, Box::new(calendar));
+        server.register_object(
+            CURRENT_TARIFF_LN,
+            Box::new(crate::data::Data::new(CosemData::LongUnsigned(0))),
+        );
+        let mut clock = Clock::new();
+        clock
+            .set_attribute(
+                2,
+                CosemData::DateTime(vec![0x07, 0xEA, 10, 17, 6, 8, 0, 0, 0, 0x80, 0, 0]),
+            )
+            .expect("failed to set clock");
+        server.register_object(CLOCK_LN, Box::new(clock));
+        server.configure_tariff(TariffConfiguration::default());
+
+        let active_mask = |server: &Server<DummyTransport>| {
+            server
+                .objects
+                .get(&activation_ln)
+                .and_then(|activation| activation.get_attribute(4))
+        };
+        let current_tariff = |server: &Server<DummyTransport>| {
+            server
+                .objects
+                .get(&CURRENT_TARIFF_LN)
+                .and_then(|data| data.get_attribute(2))
+        };
+
+        assert_eq!(server.update_tariff(), Some(1));
+        assert_eq!(
+            active_mask(&server),
+            Some(CosemData::OctetString(b"T1".to_vec()))
+        );
+        assert_eq!(current_tariff(&server), Some(CosemData::LongUnsigned(1)));
+
+        assert_eq!(
+            server.update_tariff_at(&[0x07, 0xEA, 10, 17, 6, 23, 30, 0, 0, 0x80, 0, 0]),
+            Some(2)
+        );
+        assert_eq!(
+            active_mask(&server),
+            Some(CosemData::OctetString(b"T2".to_vec()))
+        );
+        assert_eq!(current_tariff(&server), Some(CosemData::LongUnsigned(2)));
+
+        // Past midnight the last action of the day stays in force.
+        assert_eq!(
+            server.update_tariff_at(&[0x07, 0xEA, 10, 18, 7, 3, 0, 0, 0, 0x80, 0, 0]),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn get_request_next_validates_transfer_state() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x0110;
+        let logical_name = [1, 0, 99, 1, 0, 255];
+        let mut profile = ProfileGeneric::new();
+        for index in 0..50u32 {
+            crate::profile_generic::append_buffer_entry(
+                &mut profile,
+                CosemData::DoubleLongUnsigned(index),
+            )
+            .expect("failed to append entry");
+        }
+        server.register_object(logical_name, Box::new(profile));
+        server
+            .associations
+            .insert(association_address, associated_context(64));
+
+        let mut exchange = |request: GetRequest| {
+            let frame = HdlcFrame {
+                address: association_address,
+                control: 0,
+                information: request.to_bytes().expect("failed to encode get request"),
+                ..Default::default()
+            };
+            let response_bytes = server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle get request");
+            let response_frame =
+                HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+            assert!(response_frame.information.len() <= 64);
+            GetResponse::from_bytes(&response_frame.information).expect("failed to decode get")
+        };
+        let next = |block_number| {
+            GetRequest::Next(GetRequestNext {
+                invoke_id_and_priority: 1,
+                block_number,
+            })
+        };
+        let data_access_result = |response: GetResponse| match response {
+            GetResponse::Normal(GetResponseNormal {
+                result: GetDataResult::DataAccessResult(result),
+                ..
+            }) => result,
+            other => panic!("unexpected response: {other:?}"),
+        };
+
+        assert_eq!(
+            data_access_result(exchange(next(1))),
+            DataAccessResult::NoLongGetInProgress
+        );
+
+        let first = exchange(GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 7,
+                instance_id: logical_name,
+                attribute_id: 2,
+            },
+            access_selection: None,
+        }));
+        let GetResponse::WithDataBlock(first) = first else {
+            panic!("expected datablock response");
+        };
+        assert_eq!(first.result.block_number, 1);
+        assert!(!first.result.last_block);
+
+        let GetResponse::WithDataBlock(second) = exchange(next(1)) else {
+            panic!("expected second datablock");
+        };
+        assert_eq!(second.result.block_number, 2);
+
+        assert_eq!(
+            data_access_result(exchange(next(5))),
+            DataAccessResult::DataBlockNumberInvalid
+        );
+        assert_eq!(
+            data_access_result(exchange(next(2))),
+            DataAccessResult::NoLongGetInProgress
+        );
+    }
+
+    #[test]
+    fn set_datablocks_are_validated_and_written_after_the_last() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x0110;
+        let logical_name = [0, 0, 96, 1, 0, 255];
+        server.register_object(
+            logical_name,
+            Box::new(crate::data::Data::with_access(
+                CosemData::OctetString(Vec::new()),
+                AttributeAccessMode::ReadWrite,
+            )),
+        );
+        server
+            .associations
+            .insert(association_address, associated_context(64));
+
+        let mut exchange = |request: SetRequest| {
+            let frame = HdlcFrame {
+                address: association_address,
+                control: 0,
+                information: request.to_bytes().unwrap(),
+                ..Default::default()
+            };
+            let response = server.handle_request(&frame.to_bytes().unwrap()).unwrap();
+            SetResponse::from_bytes(&HdlcFrame::from_bytes(&response).unwrap().information).unwrap()
+        };
+        let mut encoded = Vec::new();
+        encode_data(&CosemData::OctetString(vec![0xAB; 40]), &mut encoded).unwrap();
+        let block = |block_number: u32, last_block| crate::xdlms::DataBlockSA {
+            last_block,
+            block_number,
+            raw_data: encoded[(block_number as usize - 1) * 20..]
+                .iter()
+                .take(20)
+                .copied()
+                .collect(),
+        };
+        let first = |datablock| {
+            SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 1,
+                    instance_id: logical_name,
+                    attribute_id: 2,
+                },
+                access_selection: None,
+                datablock,
+            })
+        };
+        let next = |datablock| {
+            SetRequest::WithDatablock(SetRequestWithDatablock {
+                invoke_id_and_priority: 1,
+                datablock,
+            })
+        };
+        let last_result = |response: SetResponse| match response {
+            SetResponse::LastDatablock(response) => response.result,
+            other => panic!("unexpected response: {other:?}"),
+        };
+
+        assert_eq!(
+            last_result(exchange(next(block(2, false)))),
+            DataAccessResult::NoLongSetInProgress
+        );
+        assert_eq!(
+            exchange(first(block(1, false))),
+            SetResponse::Datablock(SetResponseDatablock {
+                invoke_id_and_priority: 1,
+                block_number: 1,
+            })
+        );
+        assert_eq!(
+            last_result(exchange(next(block(3, true)))),
+            DataAccessResult::DataBlockNumberInvalid
+        );
+
+        exchange(first(block(1, false)));
+        assert!(matches!(
+            exchange(next(block(2, false))),
+            SetResponse::Datablock(SetResponseDatablock {
+                block_number: 2,
+                ..
+            })
+        ));
+        assert_eq!(
+            last_result(exchange(next(block(3, true)))),
+            DataAccessResult::Success
+        );
+        assert_eq!(
+            server.objects[&logical_name].get_attribute(2),
+            Some(CosemData::OctetString(vec![0xAB; 40]))
+        );
+    }
+
+    // An octet-string attribute generated on demand, never held as a whole.
+    #[derive(Debug)]
+    struct GeneratedStream {
+        length: usize,
+        offset: usize,
+    }
+
+    impl CosemDataStream for GeneratedStream {
+        fn encode_next(
+            &mut self,
+            _object: &dyn CosemObject,
+            out: &mut Vec<u8>,
+            target: usize,
+        ) -> Result<bool, DlmsError> {
+            if self.offset == 0 && out.is_empty() {
+                out.push(9);
+                crate::axdr::encode_length(self.length, out);
+            }
+            while out.len() < target && self.offset < self.length {
+                out.push(self.offset as u8);
+                self.offset += 1;
+            }
+            Ok(self.offset >= self.length)
+        }
+    }
+
+    struct GeneratedCapture;
+
+    impl CosemObject for GeneratedCapture {
+        fn class_id(&self) -> u16 {
+            1
+        }
+        fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+            vec![AttributeAccessDescriptor::new(2, AttributeAccessMode::Read)]
+        }
+        fn get_attribute(&self, _attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+            None
+        }
+        fn set_attribute(
+            &mut self,
+            _attribute_id: CosemObjectAttributeId,
+            _data: CosemData,
+        ) -> Option<()> {
+            None
+        }
+        fn invoke_method(
+            &mut self,
+            _method_id: CosemObjectMethodId,
+            _data: CosemData,
+        ) -> Option<CosemData> {
+            None
+        }
+        fn attribute_stream(
+            &self,
+            attribute_id: CosemObjectAttributeId,
+        ) -> Option<Box<dyn CosemDataStream>> {
+            (attribute_id == 2).then(|| {
+                Box::new(GeneratedStream {
+                    length: 300,
+                    offset: 0,
+                }) as Box<dyn CosemDataStream>
+            })
+        }
+    }
+
+    #[test]
+    fn attribute_stream_fills_blocks_on_demand() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x0110;
+        let logical_name = [0, 0, 96, 99, 0, 255];
+        server.register_object(logical_name, Box::new(GeneratedCapture));
+        server
+            .associations
+            .insert(association_address, associated_context(64));
+
+        let mut request = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: logical_name,
+                attribute_id: 2,
+            },
+            access_selection: None,
+        });
+        let mut raw_data = Vec::new();
+        loop {
+            let frame = HdlcFrame {
+                address: association_address,
+                control: 0,
+                information: request.to_bytes().unwrap(),
+                ..Default::default()
+            };
+            let response = server.handle_request(&frame.to_bytes().unwrap()).unwrap();
+            let information = HdlcFrame::from_bytes(&response).unwrap().information;
+            assert!(information.len() <= 64);
+            let GetResponse::WithDataBlock(block) = GetResponse::from_bytes(&information).unwrap()
+            else {
+                panic!("expected datablock response");
+            };
+            // Only the part of the value not yet sent is held.
+            let pending = server
+                .association_context(association_address)
+                .unwrap()
+                .long_get
+                .as_ref()
+                .map_or(0, |transfer| transfer.pending.len());
+            assert!(pending < 64);
+            let DataBlockResult::RawData(data) = block.result.result else {
+                panic!("expected raw data");
+            };
+            raw_data.extend(data);
+            if block.result.last_block {
+                break;
+            }
+            request = GetRequest::Next(GetRequestNext {
+                invoke_id_and_priority: 1,
+                block_number: block.result.block_number,
+            });
+        }
+        assert_eq!(
+            decode_data(&raw_data).unwrap().0,
+            CosemData::OctetString((0..300).map(|offset| offset as u8).collect())
+        );
     }
 
-    fn parse_rlre(bytes: &[u8]) -> ArlreApdu {
-        let frame = HdlcFrame::from_bytes(bytes).expect("failed to decode frame");
-        ArlreApdu::from_bytes(&frame.information)
-            .expect("failed to decode rlre")
-            .1
-    }
+    #[test]
+    fn interfaces_answer_only_their_clients() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let optical =
+            ServerInterface::new(0).client(CONFIGURATOR_CLIENT_SAP, PUBLIC_ASSOCIATION_LN);
+        let tcp = ServerInterface::new(1)
+            .client(CONFIGURATOR_CLIENT_SAP, CONFIGURATOR_ASSOCIATION_LN)
+            .client(METER_READER_CLIENT_SAP, METER_READER_ASSOCIATION_LN);
+        let aarq = |client_sap| {
+            build_hdlc_request(
+                client_sap,
+                AarqApdu {
+                    application_context_name: b"CTX".to_vec(),
+                    user_information: default_initiate_request()
+                        .to_user_information()
+                        .expect("failed to encode initiate request"),
+                    ..Default::default()
+                },
+            )
+        };
 
-    fn default_initiate_request() -> InitiateRequest {
-        AssociationParameters::default().to_initiate_request()
-    }
+        let refused = server
+            .process_interface_frame(&optical, &aarq(METER_READER_CLIENT_SAP))
+            .unwrap();
+        assert_eq!(refused, None);
+        let response = server
+            .process_interface_frame(&optical, &aarq(CONFIGURATOR_CLIENT_SAP))
+            .unwrap()
+            .expect("configurator not answered");
+        assert_eq!(parse_aare(&response).result, 0);
+        assert_eq!(server.interface_of(CONFIGURATOR_CLIENT_SAP), Some(0));
+        assert_eq!(
+            server.association_logical_names[&CONFIGURATOR_CLIENT_SAP],
+            PUBLIC_ASSOCIATION_LN
+        );
 
-    fn activate_association(server: &mut Server<DummyTransport>, address: u16) {
-        server.active_associations.insert(
-            address,
-            AssociationContext {
-                client_max_receive_pdu_size: server.association_parameters.max_receive_pdu_size,
-            },
+        // Busy on the optical port until released there.
+        let busy = server
+            .process_interface_frame(&tcp, &aarq(CONFIGURATOR_CLIENT_SAP))
+            .unwrap();
+        assert_eq!(busy, None);
+        assert!(server.release_association(CONFIGURATOR_CLIENT_SAP));
+        assert!(server
+            .process_interface_frame(&tcp, &aarq(CONFIGURATOR_CLIENT_SAP))
+            .unwrap()
+            .is_some());
+        assert_eq!(server.interface_of(CONFIGURATOR_CLIENT_SAP), Some(1));
+        assert_eq!(
+            server.association_logical_names[&CONFIGURATOR_CLIENT_SAP],
+            CONFIGURATOR_ASSOCIATION_LN
         );
     }
 
     #[test]
-    fn association_object_list_tracks_registered_objects() {
+    fn aarqs_of_clients_not_allowed_are_rejected_and_reported() {
         let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&rejected);
+        server.set_on_rejected_attempt(move |attempt| log.lock().unwrap().push(attempt.clone()));
+        assert_eq!(
+            server.configured_clients(),
+            BTreeSet::from([
+                PUBLIC_CLIENT_SAP,
+                METER_READER_CLIENT_SAP,
+                CONFIGURATOR_CLIENT_SAP
+            ])
+        );
+        let mut allowed = server.configured_clients();
+        allowed.remove(&CONFIGURATOR_CLIENT_SAP);
+        server.set_client_access(AccessList::Allow(allowed));
+        let aarq = AarqApdu {
+            application_context_name: b"CTX".to_vec(),
+            user_information: default_initiate_request()
+                .to_user_information()
+                .expect("failed to encode initiate request"),
+            ..Default::default()
+        };
 
-        {
-            let list = server
-                .association_object_list
-                .lock()
-                .expect("association list poisoned");
-            let logical_names: Vec<[u8; 6]> = list.iter().map(|entry| entry.logical_name).collect();
-            assert_eq!(logical_names.len(), 3);
-            assert!(logical_names.contains(&PUBLIC_ASSOCIATION_LN));
-            assert!(logical_names.contains(&METER_READER_ASSOCIATION_LN));
-            assert!(logical_names.contains(&CONFIGURATOR_ASSOCIATION_LN));
-            for entry in list.iter().filter(|entry| entry.class_id == 15) {
-                assert!(!entry.attribute_access.is_empty());
-            }
+        for client_sap in [CONFIGURATOR_CLIENT_SAP, 0x0040] {
+            let aare = parse_aare(
+                &server
+                    .handle_request(&build_hdlc_request(client_sap, aarq.clone()))
+                    .unwrap(),
+            );
+            assert_eq!(aare.result, 1);
+            assert_eq!(
+                aare.result_source_diagnostic,
+                AssociateSourceDiagnostic::NO_REASON_GIVEN
+            );
         }
+        let aare = parse_aare(
+            &server
+                .handle_request(&build_hdlc_request(METER_READER_CLIENT_SAP, aarq))
+                .unwrap(),
+        );
+        assert_eq!(aare.result, 0);
+        assert_eq!(server.associated_clients(), vec![METER_READER_CLIENT_SAP]);
 
-        let logical_name = [0, 0, 1, 0, 0, 255];
-        server.register_object(logical_name, Box::new(Register::new()));
-
-        let list = server
-            .association_object_list
-            .lock()
-            .expect("association list poisoned");
-        assert_eq!(list.len(), 4);
-        let register_entry = list
-            .iter()
-            .find(|entry| entry.logical_name == logical_name)
-            .expect("register not present in association list");
-        assert_eq!(register_entry.class_id, 3);
-        assert_eq!(register_entry.version, 0);
-        assert_eq!(register_entry.attribute_access.len(), 2);
-        assert_eq!(register_entry.method_access.len(), 1);
+        server.set_peer_access(AccessList::Deny(BTreeSet::from([IpAddr::from([
+            10, 0, 0, 7,
+        ])])));
+        assert!(server.admits_peer(IpAddr::from([10, 0, 0, 8])));
+        assert!(!server.admits_peer(IpAddr::from([10, 0, 0, 7])));
+        assert_eq!(
+            *rejected.lock().unwrap(),
+            vec![
+                RejectedAttempt::ClientSap(CONFIGURATOR_CLIENT_SAP),
+                RejectedAttempt::ClientSap(0x0040),
+                RejectedAttempt::Peer(IpAddr::from([10, 0, 0, 7])),
+            ]
+        );
     }
 
     #[test]
@@ -821,6 +5511,7 @@ mod tests {
             user_information: default_initiate_request()
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
 
         let default_response = server
@@ -849,6 +5540,7 @@ mod tests {
             information: default_get
                 .to_bytes()
                 .expect("failed to encode default get request"),
+            ..Default::default()
         };
 
         let default_get_response = server
@@ -882,6 +5574,7 @@ mod tests {
             information: secondary_get
                 .to_bytes()
                 .expect("failed to encode secondary get request"),
+            ..Default::default()
         };
 
         let secondary_get_response = server
@@ -933,6 +5626,7 @@ mod tests {
             mechanism_name: Some(b"LLS".to_vec()),
             calling_authentication_value: None,
             user_information: user_information.clone(),
+            ..Default::default()
         };
         let aarq_bytes = aarq.to_bytes().expect("failed to encode aarq");
         assert!(AarqApdu::from_bytes(&aarq_bytes).is_ok());
@@ -946,6 +5640,10 @@ mod tests {
             .handle_request(&request)
             .expect("server failed to handle aarq");
         let aare = parse_aare(&response);
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AssociateSourceDiagnostic::AUTHENTICATION_REQUIRED
+        );
         let challenge = aare
             .responding_authentication_value
             .expect("expected challenge in response");
@@ -955,15 +5653,84 @@ mod tests {
         assert_eq!(initiate_response.negotiated_dlms_version_number, 6);
         assert_eq!(initiate_response.server_max_receive_pdu_size, 0x0400);
         assert_eq!(initiate_response.vaa_name, 0x0007);
-        assert_eq!(initiate_response.negotiated_conformance.value, 0x0010_0000);
+        assert_eq!(initiate_response.negotiated_conformance.value, 0x0000_1C1D);
 
         assert_eq!(challenge.len(), 16);
         let stored = server
-            .lls_challenges
+            .associations
             .get(&0x0002)
+            .and_then(AssociationState::challenge)
             .expect("challenge should be stored");
-        assert_eq!(stored.as_slice(), challenge.as_slice());
-        assert!(!server.active_associations.contains_key(&0x0002));
+        assert_eq!(stored, challenge.as_slice());
+        assert!(!server.is_associated(0x0002));
+    }
+
+    // Keeps one secret per client and hands out a fixed challenge.
+    struct PerClientAuthentication {
+        secrets: BTreeMap<u16, Vec<u8>>,
+    }
+
+    impl AuthenticationProvider for PerClientAuthentication {
+        fn generate_challenge(&mut self, _client_address: u16) -> Result<Vec<u8>, SecurityError> {
+            Ok(vec![0x5A; 8])
+        }
+
+        fn verify_lls(&self, client_address: u16, challenge: &[u8], response: &[u8]) -> bool {
+            self.secrets
+                .get(&client_address)
+                .and_then(|secret| lls_authenticate(secret, challenge).ok())
+                .is_some_and(|expected| expected == response)
+        }
+
+        fn compute_hls_response(
+            &self,
+            _client_address: u16,
+            _challenge: &[u8],
+        ) -> Result<Vec<u8>, SecurityError> {
+            Err(SecurityError::EncryptionError)
+        }
+
+        fn verify_hls(&self, _client_address: u16, _challenge: &[u8], _response: &[u8]) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn lls_is_verified_by_the_authentication_provider() {
+        let authentication = PerClientAuthentication {
+            secrets: BTreeMap::from([(0x0010, b"first".to_vec()), (0x0011, b"second".to_vec())]),
+        };
+        let mut server = ServerBuilder::new(0x0001, DummyTransport)
+            .authentication(Box::new(authentication))
+            .build();
+        let user_information = default_initiate_request()
+            .to_user_information()
+            .expect("failed to encode initiate request");
+        let aarq = |calling_authentication_value| AarqApdu {
+            application_context_name: b"CTX".to_vec(),
+            mechanism_name: Some(b"LLS".to_vec()),
+            calling_authentication_value,
+            user_information: user_information.clone(),
+            ..Default::default()
+        };
+
+        for (client, secret, result) in [(0x0010, b"first", 0), (0x0011, b"first", 1)] {
+            let response = server
+                .handle_request(&build_hdlc_request(client, aarq(None)))
+                .expect("server failed to issue challenge");
+            let challenge = parse_aare(&response)
+                .responding_authentication_value
+                .expect("expected challenge");
+            assert_eq!(challenge, vec![0x5A; 8]);
+
+            let answer = lls_authenticate(secret, &challenge).expect("failed to compute mac");
+            let response = server
+                .handle_request(&build_hdlc_request(client, aarq(Some(answer))))
+                .expect("server failed to validate response");
+            assert_eq!(parse_aare(&response).result, result);
+        }
+        assert!(server.is_associated(0x0010));
+        assert!(!server.is_associated(0x0011));
     }
 
     #[test]
@@ -980,6 +5747,7 @@ mod tests {
             mechanism_name: Some(b"LLS".to_vec()),
             calling_authentication_value: None,
             user_information: user_information.clone(),
+            ..Default::default()
         };
         let aarq_bytes = aarq.to_bytes().expect("failed to encode aarq");
         assert!(AarqApdu::from_bytes(&aarq_bytes).is_ok());
@@ -1008,6 +5776,7 @@ mod tests {
                 mechanism_name: Some(b"LLS".to_vec()),
                 calling_authentication_value: Some(expected_response.clone()),
                 user_information: user_information.clone(),
+                ..Default::default()
             },
         );
 
@@ -1022,11 +5791,14 @@ mod tests {
             .expect("expected initiate response");
         assert_eq!(initiate_response.negotiated_dlms_version_number, 6);
         assert_eq!(initiate_response.server_max_receive_pdu_size, 0x0400);
-        assert_eq!(initiate_response.negotiated_conformance.value, 0x0010_0000);
-        assert!(!server.lls_challenges.contains_key(&association_address));
-        let context = server
-            .active_associations
+        assert_eq!(initiate_response.negotiated_conformance.value, 0x0000_1C1D);
+        assert!(!server
+            .associations
             .get(&association_address)
+            .and_then(AssociationState::challenge)
+            .is_some());
+        let context = server
+            .association_context(association_address)
             .expect("expected active association");
         assert_eq!(
             context.client_max_receive_pdu_size,
@@ -1049,6 +5821,7 @@ mod tests {
                 user_information: default_initiate_request()
                     .to_user_information()
                     .expect("failed to encode initiate request"),
+                ..Default::default()
             },
         );
 
@@ -1058,8 +5831,7 @@ mod tests {
         let aare = parse_aare(&response);
         assert_eq!(aare.result, 0);
         let context = server
-            .active_associations
-            .get(&association_address)
+            .association_context(association_address)
             .expect("expected active association");
         assert_eq!(
             context.client_max_receive_pdu_size,
@@ -1067,6 +5839,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn negotiated_pdu_sizes_are_clamped_to_max_pdu_size() {
+        for (configured, proposed, server_limit, client_limit) in [
+            (0x0400, 0x0400, 0x0400, 0x0400),
+            (MAX_PDU_SIZE as u16, MAX_PDU_SIZE as u16 + 1, 0x0800, 0x0800),
+            (u16::MAX, u16::MAX, 0x0800, 0x0800),
+        ] {
+            let mut server = Server::new(0x0001, DummyTransport, None, None);
+            server.set_association_parameters(AssociationParameters {
+                max_receive_pdu_size: configured,
+                ..Default::default()
+            });
+            let mut request = default_initiate_request();
+            request.client_max_receive_pdu_size = proposed;
+            let aarq = AarqApdu {
+                application_context_name: b"CTX".to_vec(),
+                user_information: request
+                    .to_user_information()
+                    .expect("failed to encode initiate request"),
+                ..Default::default()
+            };
+
+            let response_bytes = server
+                .handle_request(&build_hdlc_request(0x0002, aarq))
+                .expect("server failed to handle aarq");
+            let aare = parse_aare(&response_bytes);
+            let initiate_response = InitiateResponse::from_user_information(&aare.user_information)
+                .expect("expected initiate response");
+            assert_eq!(initiate_response.server_max_receive_pdu_size, server_limit);
+            assert_eq!(
+                server
+                    .association_context(0x0002)
+                    .unwrap()
+                    .client_max_receive_pdu_size,
+                client_limit
+            );
+        }
+    }
+
     #[test]
     fn initiate_request_with_incompatible_version_is_rejected() {
         let mut server = Server::new(0x0001, DummyTransport, None, None);
@@ -1082,6 +5893,7 @@ mod tests {
             user_information: request
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1089,7 +5901,16 @@ mod tests {
             .expect("server failed to handle aarq");
         let aare = parse_aare(&response_bytes);
         assert_eq!(aare.result, 1);
-        assert_eq!(aare.result_source_diagnostic, 2);
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AssociateSourceDiagnostic::NO_REASON_GIVEN
+        );
+        assert_eq!(
+            ConfirmedServiceError::from_user_information(&aare.user_information).ok(),
+            Some(ConfirmedServiceError::InitiateError(
+                InitiateError::DlmsVersionTooLow
+            ))
+        );
     }
 
     #[test]
@@ -1107,6 +5928,7 @@ mod tests {
                 user_information: default_initiate_request()
                     .to_user_information()
                     .expect("failed to encode initiate request"),
+                ..Default::default()
             },
         );
 
@@ -1114,9 +5936,7 @@ mod tests {
             .handle_request(&successful_request)
             .expect("server failed to handle aarq");
         assert_eq!(parse_aare(&response).result, 0);
-        assert!(server
-            .active_associations
-            .contains_key(&association_address));
+        assert!(server.is_associated(association_address));
 
         let mut failing_request = default_initiate_request();
         failing_request.response_allowed = false;
@@ -1131,14 +5951,13 @@ mod tests {
                     user_information: failing_request
                         .to_user_information()
                         .expect("failed to encode initiate request"),
+                    ..Default::default()
                 },
             ))
             .expect("server failed to handle aarq");
         let aare = parse_aare(&response_bytes);
         assert_eq!(aare.result, 1);
-        assert!(!server
-            .active_associations
-            .contains_key(&association_address));
+        assert!(!server.is_associated(association_address));
     }
 
     #[test]
@@ -1156,6 +5975,7 @@ mod tests {
             user_information: request
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1163,7 +5983,16 @@ mod tests {
             .expect("server failed to handle aarq");
         let aare = parse_aare(&response_bytes);
         assert_eq!(aare.result, 1);
-        assert_eq!(aare.result_source_diagnostic, 4);
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AssociateSourceDiagnostic::NO_REASON_GIVEN
+        );
+        assert_eq!(
+            ConfirmedServiceError::from_user_information(&aare.user_information).ok(),
+            Some(ConfirmedServiceError::InitiateError(
+                InitiateError::IncompatibleConformance
+            ))
+        );
     }
 
     #[test]
@@ -1181,6 +6010,7 @@ mod tests {
             user_information: request
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1188,7 +6018,14 @@ mod tests {
             .expect("server failed to handle aarq");
         let aare = parse_aare(&response_bytes);
         assert_eq!(aare.result, 1);
-        assert_eq!(aare.result_source_diagnostic, 1);
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AssociateSourceDiagnostic::NO_REASON_GIVEN
+        );
+        assert_eq!(
+            ConfirmedServiceError::from_user_information(&aare.user_information).ok(),
+            Some(ConfirmedServiceError::InitiateError(InitiateError::Other))
+        );
     }
 
     #[test]
@@ -1206,6 +6043,7 @@ mod tests {
             user_information: request
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1213,8 +6051,17 @@ mod tests {
             .expect("server failed to handle aarq");
         let aare = parse_aare(&response_bytes);
         assert_eq!(aare.result, 1);
-        assert_eq!(aare.result_source_diagnostic, 3);
-        assert!(!server.active_associations.contains_key(&0x0002));
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AssociateSourceDiagnostic::NO_REASON_GIVEN
+        );
+        assert_eq!(
+            ConfirmedServiceError::from_user_information(&aare.user_information).ok(),
+            Some(ConfirmedServiceError::InitiateError(
+                InitiateError::PduSizeTooShort
+            ))
+        );
+        assert!(!server.is_associated(0x0002));
     }
 
     #[test]
@@ -1235,6 +6082,7 @@ mod tests {
             address: 0x0002,
             control: 0,
             information: request.to_bytes().expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1275,6 +6123,7 @@ mod tests {
             address: 0x0002,
             control: 0,
             information: request.to_bytes().expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1293,6 +6142,53 @@ mod tests {
         assert_eq!(response.result, DataAccessResult::ReadWriteDenied);
     }
 
+    #[test]
+    fn retransmitted_action_is_answered_without_executing_it_again() {
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let resets = Arc::new(Mutex::new(0));
+        let register = Register::new();
+        register.callback_handlers().set_post_action({
+            let resets = Arc::clone(&resets);
+            move |_, _, _| {
+                *resets.lock().unwrap() += 1;
+                Ok(())
+            }
+        });
+        server.register_object(register_ln, Box::new(register));
+        server.set_duplicate_detection(true);
+        activate_association(&mut server, 0x0010);
+
+        let mut reset = |invoke_id_and_priority| {
+            let request = ActionRequest::Normal(ActionRequestNormal {
+                invoke_id_and_priority,
+                cosem_method_descriptor: CosemMethodDescriptor {
+                    class_id: 3,
+                    instance_id: register_ln,
+                    method_id: 1,
+                },
+                method_invocation_parameters: Some(CosemData::Integer(0)),
+            });
+            let frame = HdlcFrame {
+                address: 0x0010,
+                control: 0,
+                information: request.to_bytes().expect("failed to encode action request"),
+                ..Default::default()
+            };
+            server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle action request")
+        };
+        let response = reset(1);
+        assert_eq!(reset(1), response);
+        assert_eq!(*resets.lock().unwrap(), 1);
+        // Another invoke-id is a new request.
+        reset(2);
+        assert_eq!(*resets.lock().unwrap(), 2);
+        reset(1);
+        assert_eq!(*resets.lock().unwrap(), 3);
+    }
+
     #[test]
     fn action_request_without_active_association_is_denied() {
         let mut server = Server::new(0x0001, DummyTransport, None, None);
@@ -1311,6 +6207,7 @@ mod tests {
             address: 0x0002,
             control: 0,
             information: request.to_bytes().expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1355,6 +6252,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1376,6 +6274,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn receive_ready_poll_is_answered_with_final_bit() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let poll = |control| {
+            HdlcFrame {
+                address: 0x0010,
+                control,
+                ..Default::default()
+            }
+            .to_bytes()
+            .expect("failed to encode frame")
+        };
+
+        let response = server
+            .process_frame(&poll(0x11))
+            .expect("server failed to handle RR")
+            .expect("RR poll was not answered");
+        let response = HdlcFrame::from_bytes(&response).expect("invalid RR response");
+        assert_eq!(response.control, 0x11);
+        assert!(response.information.is_empty());
+        assert_eq!(server.process_frame(&poll(0x01)).unwrap(), None);
+    }
+
+    #[test]
+    fn hdlc_window_serves_frames_in_order_and_acknowledges_on_poll() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x0100;
+        let logical_name = [0, 0, 1, 0, 0, 255];
+        server.register_object(logical_name, Box::new(Register::new()));
+        activate_association(&mut server, association_address);
+        server.set_hdlc_window_size(3);
+
+        let request_frame = |invoke_id: u8, control: u8| {
+            let request = GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: invoke_id,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 3,
+                    instance_id: logical_name,
+                    attribute_id: 2,
+                },
+                access_selection: None,
+            });
+            HdlcFrame {
+                address: association_address,
+                control,
+                information: request.to_bytes().expect("failed to encode get request"),
+                ..Default::default()
+            }
+            .to_bytes()
+            .expect("failed to encode frame")
+        };
+
+        // N(S) = 0, a repeated N(S) = 0 which is dropped, then N(S) = 1 with poll.
+        assert_eq!(server.process_frame(&request_frame(1, 0x00)).unwrap(), None);
+        assert_eq!(server.process_frame(&request_frame(2, 0x00)).unwrap(), None);
+        let response_bytes = server
+            .process_frame(&request_frame(3, 0x12))
+            .unwrap()
+            .expect("poll must be answered");
+
+        let frames: Vec<HdlcFrame> = response_bytes
+            .split_inclusive(|&byte| byte == crate::hdlc::HDLC_FLAG)
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|halves| HdlcFrame::from_bytes(&halves.concat()).unwrap())
+            .collect();
+        assert_eq!(frames.len(), 2);
+        // N(R) = 2 on both, N(S) = 0 and 1, final bit on the last one.
+        assert_eq!(frames[0].control, 0x40);
+        assert_eq!(frames[1].control, 0x52);
+        let invoke_ids: Vec<u8> = frames
+            .iter()
+            .map(
+                |frame| match GetResponse::from_bytes(&frame.information).unwrap() {
+                    GetResponse::Normal(response) => response.invoke_id_and_priority,
+                    other => panic!("unexpected response: {other:?}"),
+                },
+            )
+            .collect();
+        assert_eq!(invoke_ids, vec![1, 3]);
+
+        // A poll without new frames is acknowledged with RR, N(R) = 2.
+        let receive_ready = server
+            .process_frame(&request_frame(4, 0x10))
+            .unwrap()
+            .expect("poll must be answered");
+        assert_eq!(HdlcFrame::from_bytes(&receive_ready).unwrap().control, 0x51);
+    }
+
     #[test]
     fn get_request_denied_without_read_access() {
         let mut server = Server::new(0x0001, DummyTransport, None, None);
@@ -1398,6 +6385,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1442,6 +6430,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1489,6 +6478,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1529,6 +6519,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1547,7 +6538,7 @@ mod tests {
         assert_eq!(response.single_response.result, ActionResult::Success);
         assert_eq!(
             response.single_response.return_parameters,
-            Some(GetDataResult::Data(CosemData::NullData))
+            Some(GetDataResult::Data(CosemData::Unsigned(0)))
         );
     }
 
@@ -1573,6 +6564,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1629,6 +6621,7 @@ mod tests {
             information: get_request
                 .to_bytes()
                 .expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1666,6 +6659,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1716,6 +6710,7 @@ mod tests {
             address: association_address,
             control: 0,
             information: request.to_bytes().expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1734,7 +6729,7 @@ mod tests {
         assert_eq!(response.single_response.result, ActionResult::Success);
         assert_eq!(
             response.single_response.return_parameters,
-            Some(GetDataResult::Data(CosemData::NullData))
+            Some(GetDataResult::Data(CosemData::Unsigned(15)))
         );
         let register = server
             .objects
@@ -1758,6 +6753,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1804,6 +6800,7 @@ mod tests {
             information: writable_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1838,6 +6835,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1890,6 +6888,7 @@ mod tests {
             information: get_request
                 .to_bytes()
                 .expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1927,6 +6926,7 @@ mod tests {
             information: writable_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -1969,6 +6969,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2012,6 +7013,42 @@ mod tests {
             information: writable_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
+        };
+
+        let response_bytes = server
+            .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+            .expect("server failed to handle set request");
+
+        let response_frame =
+            HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+        let response =
+            SetResponse::from_bytes(&response_frame.information).expect("failed to decode set");
+
+        let SetResponse::Normal(response) = response else {
+            panic!("expected normal set response");
+        };
+
+        assert_eq!(response.result, DataAccessResult::Success);
+
+        let denied_request = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 2,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 8,
+                instance_id: logical_name,
+                attribute_id: 4,
+            },
+            access_selection: None,
+            value: CosemData::Enum(0),
+        });
+
+        let frame = HdlcFrame {
+            address: association_address,
+            control: 0,
+            information: denied_request
+                .to_bytes()
+                .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2023,45 +7060,186 @@ mod tests {
         let response =
             SetResponse::from_bytes(&response_frame.information).expect("failed to decode set");
 
-        let SetResponse::Normal(response) = response else {
-            panic!("expected normal set response");
+        let SetResponse::Normal(response) = response else {
+            panic!("expected normal set response");
+        };
+
+        assert_eq!(response.result, DataAccessResult::ReadWriteDenied);
+    }
+
+    #[test]
+    fn set_request_values_are_checked_against_attribute_specs() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x010A;
+        let logical_name = [0, 0, 1, 0, 0, 255];
+        let clock = Clock::new();
+        let pre_write_calls = Arc::new(Mutex::new(0));
+        let calls = Arc::clone(&pre_write_calls);
+        clock.callback_handlers().set_pre_write(move |_, _, _| {
+            *calls.lock().unwrap() += 1;
+            Ok(())
+        });
+        server.register_object(logical_name, Box::new(clock));
+        activate_association(&mut server, association_address);
+
+        let mut set = |attribute_id, value| {
+            let request = SetRequest::Normal(SetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 8,
+                    instance_id: logical_name,
+                    attribute_id,
+                },
+                access_selection: None,
+                value,
+            });
+            let frame = HdlcFrame {
+                address: association_address,
+                control: 0,
+                information: request.to_bytes().expect("failed to encode set request"),
+                ..Default::default()
+            };
+            let response_bytes = server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle set request");
+            let response_frame =
+                HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+            let SetResponse::Normal(response) =
+                SetResponse::from_bytes(&response_frame.information).expect("failed to decode set")
+            else {
+                panic!("expected normal set response");
+            };
+            response.result
+        };
+
+        assert_eq!(
+            set(2, CosemData::Unsigned(0)),
+            DataAccessResult::TypeUnmatched
+        );
+        assert_eq!(
+            set(2, CosemData::OctetString(vec![0; 5])),
+            DataAccessResult::OtherReason(250)
+        );
+        assert_eq!(
+            set(3, CosemData::Long(1000)),
+            DataAccessResult::OtherReason(250)
+        );
+        assert_eq!(*pre_write_calls.lock().unwrap(), 0);
+
+        assert_eq!(set(3, CosemData::Long(-60)), DataAccessResult::Success);
+        assert_eq!(*pre_write_calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn objects_report_their_own_data_access_results() {
+        struct Sensor;
+
+        impl CosemObject for Sensor {
+            fn class_id(&self) -> u16 {
+                1
+            }
+
+            fn attribute_access_rights(&self) -> Vec<AttributeAccessDescriptor> {
+                vec![AttributeAccessDescriptor::new(
+                    2,
+                    AttributeAccessMode::ReadWrite,
+                )]
+            }
+
+            fn get_attribute(&self, _attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+                None
+            }
+
+            fn read_attribute(
+                &self,
+                _attribute_id: CosemObjectAttributeId,
+            ) -> Result<CosemData, DataAccessResult> {
+                Err(DataAccessResult::TemporaryFailure)
+            }
+
+            fn set_attribute(
+                &mut self,
+                _attribute_id: CosemObjectAttributeId,
+                _data: CosemData,
+            ) -> Option<()> {
+                None
+            }
+
+            fn invoke_method(
+                &mut self,
+                _method_id: CosemObjectMethodId,
+                _data: CosemData,
+            ) -> Option<CosemData> {
+                None
+            }
+        }
+
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let association_address = 0x010A;
+        let sensor = [0, 0, 96, 9, 0, 255];
+        let hdlc_setup = [0, 0, 22, 0, 0, 255];
+        server.register_object(sensor, Box::new(Sensor));
+        server.register_object(hdlc_setup, Box::new(IecHdlcSetup::new()));
+        activate_association(&mut server, association_address);
+
+        let mut exchange = |information: Vec<u8>| {
+            let frame = HdlcFrame {
+                address: association_address,
+                control: 0,
+                information,
+                ..Default::default()
+            };
+            let response_bytes = server
+                .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+                .expect("server failed to handle request");
+            HdlcFrame::from_bytes(&response_bytes)
+                .expect("failed to decode response frame")
+                .information
+        };
+        let descriptor = |class_id, instance_id, attribute_id| CosemAttributeDescriptor {
+            class_id,
+            instance_id,
+            attribute_id,
         };
 
-        assert_eq!(response.result, DataAccessResult::Success);
-
-        let denied_request = SetRequest::Normal(SetRequestNormal {
-            invoke_id_and_priority: 2,
-            cosem_attribute_descriptor: CosemAttributeDescriptor {
-                class_id: 8,
-                instance_id: logical_name,
-                attribute_id: 4,
-            },
+        let get = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: descriptor(1, sensor, 2),
             access_selection: None,
-            value: CosemData::Enum(0),
         });
-
-        let frame = HdlcFrame {
-            address: association_address,
-            control: 0,
-            information: denied_request
-                .to_bytes()
-                .expect("failed to encode set request"),
+        let GetResponse::Normal(response) =
+            GetResponse::from_bytes(&exchange(get.to_bytes().unwrap())).unwrap()
+        else {
+            panic!("expected normal get response");
         };
+        assert_eq!(
+            response.result,
+            GetDataResult::DataAccessResult(DataAccessResult::TemporaryFailure)
+        );
 
-        let response_bytes = server
-            .handle_request(&frame.to_bytes().expect("failed to encode frame"))
-            .expect("server failed to handle set request");
-
-        let response_frame =
-            HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
-        let response =
-            SetResponse::from_bytes(&response_frame.information).expect("failed to decode set");
-
-        let SetResponse::Normal(response) = response else {
-            panic!("expected normal set response");
+        let mut set = |value| {
+            let request = SetRequest::Normal(SetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: descriptor(23, hdlc_setup, 2),
+                access_selection: None,
+                value,
+            });
+            let SetResponse::Normal(response) =
+                SetResponse::from_bytes(&exchange(request.to_bytes().unwrap())).unwrap()
+            else {
+                panic!("expected normal set response");
+            };
+            response.result
         };
-
-        assert_eq!(response.result, DataAccessResult::ReadWriteDenied);
+        assert_eq!(
+            set(CosemData::LongUnsigned(5)),
+            DataAccessResult::TypeUnmatched
+        );
+        assert_eq!(
+            set(CosemData::Enum(12)),
+            DataAccessResult::ObjectUnavailable
+        );
+        assert_eq!(set(CosemData::Enum(6)), DataAccessResult::Success);
     }
 
     #[test]
@@ -2098,6 +7276,7 @@ mod tests {
             information: get_request
                 .to_bytes()
                 .expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2137,6 +7316,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2180,6 +7360,7 @@ mod tests {
             information: writable_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2214,6 +7395,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2247,6 +7429,7 @@ mod tests {
             information: disconnect_request
                 .to_bytes()
                 .expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2289,6 +7472,7 @@ mod tests {
             information: reconnect_request
                 .to_bytes()
                 .expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2331,6 +7515,7 @@ mod tests {
             information: denied_method_request
                 .to_bytes()
                 .expect("failed to encode action request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2386,6 +7571,7 @@ mod tests {
             information: get_request
                 .to_bytes()
                 .expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2423,6 +7609,7 @@ mod tests {
             information: denied_request
                 .to_bytes()
                 .expect("failed to encode set request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2448,14 +7635,17 @@ mod tests {
         let logical_name = [0, 0, 1, 0, 0, 241];
         server.register_object(
             logical_name,
-            Box::new(SapAssignment::with_logical_device_names(b"LN".to_vec())),
+            Box::new(SapAssignment::with_entries(vec![SapAssignmentEntry::new(
+                1,
+                b"LN".to_vec(),
+            )])),
         );
         activate_association(&mut server, association_address);
 
         let get_request = GetRequest::Normal(GetRequestNormal {
             invoke_id_and_priority: 1,
             cosem_attribute_descriptor: CosemAttributeDescriptor {
-                class_id: 21,
+                class_id: 17,
                 instance_id: logical_name,
                 attribute_id: 2,
             },
@@ -2468,6 +7658,7 @@ mod tests {
             information: get_request
                 .to_bytes()
                 .expect("failed to encode get request"),
+            ..Default::default()
         };
 
         let response_bytes = server
@@ -2484,125 +7675,592 @@ mod tests {
         };
 
         match response.result {
-            GetDataResult::Data(CosemData::OctetString(value)) => assert_eq!(value, b"LN".to_vec()),
+            GetDataResult::Data(value) => assert_eq!(
+                value,
+                CosemData::Array(vec![
+                    SapAssignmentEntry::new(1, b"LN".to_vec()).to_cosem_data()
+                ])
+            ),
             other => panic!("unexpected get response: {other:?}"),
         };
 
-        let denied_request = SetRequest::Normal(SetRequestNormal {
-            invoke_id_and_priority: 2,
+        let denied_request = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 2,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 17,
+                instance_id: logical_name,
+                attribute_id: 2,
+            },
+            access_selection: None,
+            value: CosemData::Array(Vec::new()),
+        });
+
+        let frame = HdlcFrame {
+            address: association_address,
+            control: 0,
+            information: denied_request
+                .to_bytes()
+                .expect("failed to encode set request"),
+            ..Default::default()
+        };
+
+        let response_bytes = server
+            .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+            .expect("server failed to handle set request");
+
+        let response_frame =
+            HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
+        let response =
+            SetResponse::from_bytes(&response_frame.information).expect("failed to decode set");
+
+        let SetResponse::Normal(response) = response else {
+            panic!("expected normal set response");
+        };
+
+        assert_eq!(response.result, DataAccessResult::ReadWriteDenied);
+    }
+
+    #[test]
+    fn lls_challenge_response_with_wrong_mac_fails() {
+        let mut server = Server::new(0x0001, DummyTransport, Some(b"password".to_vec()), None);
+
+        let association_address = 0x0004;
+        let user_information = default_initiate_request()
+            .to_user_information()
+            .expect("failed to encode initiate request");
+        let initial_request = build_hdlc_request(
+            association_address,
+            AarqApdu {
+                application_context_name: b"CTX".to_vec(),
+                sender_acse_requirements: 0,
+                mechanism_name: Some(b"LLS".to_vec()),
+                calling_authentication_value: None,
+                user_information: user_information.clone(),
+                ..Default::default()
+            },
+        );
+
+        let initial_response = server
+            .handle_request(&initial_request)
+            .expect("server failed to issue challenge");
+        let issued_challenge = parse_aare(&initial_response)
+            .responding_authentication_value
+            .expect("expected challenge");
+
+        let mut wrong_response =
+            lls_authenticate(b"password", &issued_challenge).expect("failed to compute mac");
+        wrong_response[0] ^= 0xFF;
+
+        let follow_up_response = server
+            .handle_request(&build_hdlc_request(
+                association_address,
+                AarqApdu {
+                    application_context_name: b"CTX".to_vec(),
+                    sender_acse_requirements: 0,
+                    mechanism_name: Some(b"LLS".to_vec()),
+                    calling_authentication_value: Some(wrong_response),
+                    user_information,
+                    ..Default::default()
+                },
+            ))
+            .expect("server failed to process response");
+
+        let aare = parse_aare(&follow_up_response);
+
+        assert_eq!(aare.result, 1);
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AssociateSourceDiagnostic::AUTHENTICATION_FAILURE
+        );
+        assert!(aare.responding_authentication_value.is_none());
+        let initiate_response = InitiateResponse::from_user_information(&aare.user_information)
+            .expect("expected initiate response");
+        assert_eq!(initiate_response.vaa_name, 0x0007);
+        assert!(!server
+            .associations
+            .get(&association_address)
+            .and_then(AssociationState::challenge)
+            .expect("challenge should remain for retry")
+            .is_empty());
+    }
+
+    #[test]
+    fn release_request_clears_active_association() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+
+        let aarq = AarqApdu {
+            application_context_name: b"CTX".to_vec(),
+            sender_acse_requirements: 0,
+            mechanism_name: None,
+            calling_authentication_value: None,
+            user_information: default_initiate_request()
+                .to_user_information()
+                .expect("failed to encode initiate request"),
+            ..Default::default()
+        };
+
+        let response_bytes = server
+            .handle_request(&build_hdlc_request(0x0001, aarq))
+            .expect("failed to handle aarq");
+        let aare = parse_aare(&response_bytes);
+        assert_eq!(aare.result, 0);
+        assert!(server.is_associated(0x0001));
+
+        let release_req = ArlrqApdu {
+            reason: Some(0),
+            user_information: None,
+        };
+
+        let frame = HdlcFrame {
+            address: 0x0001,
+            control: 0,
+            information: release_req
+                .to_bytes()
+                .expect("failed to encode release request"),
+            ..Default::default()
+        };
+
+        let release_frame = frame.to_bytes().expect("failed to encode frame");
+        let response_bytes = server
+            .handle_request(&release_frame)
+            .expect("failed to handle release");
+        let rlre = parse_rlre(&response_bytes);
+        assert_eq!(rlre.reason, Some(0));
+        assert!(server.associations.is_empty());
+    }
+
+    #[test]
+    fn release_initiate_request_is_answered_with_an_initiate_response() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        activate_association(&mut server, 0x0010);
+        let frame = HdlcFrame {
+            address: 0x0010,
+            control: 0,
+            information: ArlrqApdu {
+                reason: Some(0),
+                user_information: Some(ReleaseRequestInformation::Initiate(
+                    default_initiate_request(),
+                )),
+            }
+            .to_bytes()
+            .unwrap(),
+            ..Default::default()
+        };
+        let rlre = parse_rlre(&server.handle_request(&frame.to_bytes().unwrap()).unwrap());
+        assert_eq!(rlre.reason, Some(0));
+        let response = rlre
+            .user_information
+            .unwrap()
+            .initiate_response(None, ParseMode::Strict)
+            .unwrap();
+        assert_eq!(
+            response.negotiated_conformance,
+            server.association_parameters.conformance
+        );
+        assert!(server.associations.is_empty());
+    }
+
+    #[test]
+    fn ciphered_release_needs_a_decipherable_initiate_request() {
+        let key = vec![0x11; 16];
+        let mut server = Server::new(0x0001, DummyTransport, None, Some(key.clone()));
+        activate_association(&mut server, 0x0010);
+        let mut release = |user_information: ReleaseRequestInformation| {
+            let frame = HdlcFrame {
+                address: 0x0010,
+                control: 0,
+                information: ArlrqApdu {
+                    reason: Some(0),
+                    user_information: Some(user_information),
+                }
+                .to_bytes()
+                .unwrap(),
+                ..Default::default()
+            };
+            parse_rlre(&server.handle_request(&frame.to_bytes().unwrap()).unwrap())
+        };
+
+        let forged =
+            ReleaseRequestInformation::ciphered(&default_initiate_request(), &[0x22; 16]).unwrap();
+        let rlre = release(forged);
+        assert_eq!(rlre.reason, Some(RELEASE_NOT_FINISHED));
+        assert!(rlre.user_information.is_none());
+        let rlre = release(ReleaseRequestInformation::Initiate(
+            default_initiate_request(),
+        ));
+        assert_eq!(rlre.reason, Some(RELEASE_NOT_FINISHED));
+
+        let rlre = release(
+            ReleaseRequestInformation::ciphered(&default_initiate_request(), &key).unwrap(),
+        );
+        assert_eq!(rlre.reason, Some(0));
+        let response = rlre
+            .user_information
+            .unwrap()
+            .initiate_response(Some(&key), ParseMode::Strict)
+            .unwrap();
+        assert_eq!(response.negotiated_dlms_version_number, 6);
+        assert!(server.associations.is_empty());
+    }
+
+    #[test]
+    fn association_lifecycle_hooks_report_security_events() {
+        let mut server = Server::new(0x0001, DummyTransport, Some(b"password".to_vec()), None);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let denied = Arc::new(Mutex::new(Vec::new()));
+        for (name, hook) in [
+            ("established", 0),
+            ("released", 1),
+            ("authentication failed", 2),
+        ] {
+            let events = Arc::clone(&events);
+            let callback = move |info: &AssociationInfo| {
+                events.lock().unwrap().push((name, info.clone()));
+            };
+            match hook {
+                0 => server.set_on_association_established(callback),
+                1 => server.set_on_association_released(callback),
+                _ => server.set_on_authentication_failed(callback),
+            }
+        }
+        let denied_log = Arc::clone(&denied);
+        server.set_on_access_denied(move |event| denied_log.lock().unwrap().push(event.clone()));
+
+        let client_address = 0x0010;
+        let lls_request = |calling_authentication_value| AarqApdu {
+            application_context_name: b"CTX".to_vec(),
+            calling_ap_title: Some(b"CLIENT01".to_vec()),
+            mechanism_name: Some(b"LLS".to_vec()),
+            calling_authentication_value,
+            user_information: default_initiate_request()
+                .to_user_information()
+                .expect("failed to encode initiate request"),
+            ..Default::default()
+        };
+        let challenge = parse_aare(
+            &server
+                .handle_request(&build_hdlc_request(client_address, lls_request(None)))
+                .expect("server failed to issue challenge"),
+        )
+        .responding_authentication_value
+        .expect("expected challenge");
+        let response = lls_authenticate(b"password", &challenge).expect("failed to compute mac");
+        let mut wrong_response = response.clone();
+        wrong_response[0] ^= 0xFF;
+
+        for value in [wrong_response, response] {
+            server
+                .handle_request(&build_hdlc_request(
+                    client_address,
+                    lls_request(Some(value)),
+                ))
+                .expect("server failed to handle aarq");
+        }
+        let get = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 8,
+                instance_id: [0, 0, 1, 0, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+        });
+        let frame = HdlcFrame {
+            address: 0x0002,
+            control: 0,
+            information: get.to_bytes().expect("failed to encode get request"),
+            ..Default::default()
+        };
+        server
+            .handle_request(&frame.to_bytes().expect("failed to encode frame"))
+            .expect("failed to handle get request");
+        let release = HdlcFrame {
+            address: client_address,
+            control: 0,
+            information: ArlrqApdu {
+                reason: Some(0),
+                user_information: None,
+            }
+            .to_bytes()
+            .expect("failed to encode release request"),
+            ..Default::default()
+        };
+        server
+            .handle_request(&release.to_bytes().expect("failed to encode frame"))
+            .expect("failed to handle release");
+
+        let info = AssociationInfo {
+            client_address,
+            logical_name: PUBLIC_ASSOCIATION_LN,
+            mechanism_name: Some(b"LLS".to_vec()),
+            calling_ap_title: Some(b"CLIENT01".to_vec()),
+        };
+        assert_eq!(
+            info.system_title().map(|title| title.to_string()),
+            Some(String::from("CLI-454E543031"))
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("authentication failed", info.clone()),
+                ("established", info.clone()),
+                ("released", info),
+            ]
+        );
+        assert_eq!(
+            *denied.lock().unwrap(),
+            vec![AccessDenied {
+                client_address: 0x0002,
+                service: AccessService::Get,
+                class_id: 8,
+                instance_id: [0, 0, 1, 0, 0, 255],
+                index: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn security_events_are_captured_into_the_security_log() {
+        use crate::security::aes_key_wrap;
+        use crate::security_setup::KeyId;
+
+        let setup_ln = [0, 0, 43, 0, 0, 255];
+        let master_key = [0x4D; 16].to_vec();
+        let mut setup = SecuritySetup::new();
+        setup.set_key(KeyId::Master, master_key.clone());
+        let mut server = ServerBuilder::new(0x0001, DummyTransport)
+            .password(b"password".to_vec())
+            .object(security_log::CLOCK_LN, Box::new(Clock::new()))
+            .object(setup_ln, Box::new(setup))
+            .security_log(10)
+            .build();
+        let exchange = |server: &mut Server<DummyTransport>, address, information| {
+            let frame = HdlcFrame {
+                address,
+                control: 0,
+                information,
+                ..Default::default()
+            };
+            server.handle_request(&frame.to_bytes().unwrap()).unwrap()
+        };
+
+        // An LLS answer that does not match the challenge.
+        let lls_request = |calling_authentication_value| {
+            AarqApdu {
+                application_context_name: b"CTX".to_vec(),
+                mechanism_name: Some(b"LLS".to_vec()),
+                calling_authentication_value,
+                user_information: default_initiate_request().to_user_information().unwrap(),
+                ..Default::default()
+            }
+            .to_bytes()
+            .unwrap()
+        };
+        let challenge = parse_aare(&exchange(&mut server, 0x0010, lls_request(None)))
+            .responding_authentication_value
+            .unwrap();
+        let mut answer = lls_authenticate(b"password", &challenge).unwrap();
+        answer[0] ^= 0xFF;
+        assert_eq!(
+            parse_aare(&exchange(&mut server, 0x0010, lls_request(Some(answer)))).result,
+            1
+        );
+
+        // A GET without an association.
+        let get = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 0xC1,
             cosem_attribute_descriptor: CosemAttributeDescriptor {
-                class_id: 21,
-                instance_id: logical_name,
+                class_id: 64,
+                instance_id: setup_ln,
                 attribute_id: 2,
             },
             access_selection: None,
-            value: CosemData::OctetString(b"UPDATED".to_vec()),
         });
+        exchange(&mut server, 0x0002, get.to_bytes().unwrap());
 
-        let frame = HdlcFrame {
-            address: association_address,
-            control: 0,
-            information: denied_request
-                .to_bytes()
-                .expect("failed to encode set request"),
+        // A key transfer.
+        server.associations.insert(0x0020, associated_context(1024));
+        let transfer = ActionRequest::Normal(ActionRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 64,
+                instance_id: setup_ln,
+                method_id: 2,
+            },
+            method_invocation_parameters: Some(CosemData::Array(vec![CosemData::Structure(vec![
+                CosemData::Enum(0),
+                CosemData::OctetString(aes_key_wrap(&[0x11; 16], &master_key).unwrap()),
+            ])])),
+        });
+        exchange(&mut server, 0x0020, transfer.to_bytes().unwrap());
+
+        let (_, log) = server
+            .objects()
+            .find(|(logical_name, _)| *logical_name == SECURITY_LOG_LN)
+            .unwrap();
+        let Some(CosemData::Array(entries)) = log.get_attribute(2) else {
+            panic!("no security log buffer");
         };
+        let codes: Vec<_> = entries
+            .iter()
+            .map(|entry| match entry {
+                CosemData::Structure(fields) if fields.len() == 2 => fields[1].clone(),
+                other => panic!("unexpected entry {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                SecurityEvent::AuthenticationFailure,
+                SecurityEvent::AccessDenied,
+                SecurityEvent::KeyChanged,
+            ]
+            .map(|event| CosemData::LongUnsigned(event.code()))
+        );
+    }
 
-        let response_bytes = server
-            .handle_request(&frame.to_bytes().expect("failed to encode frame"))
-            .expect("server failed to handle set request");
+    // Replays queued frames and records what the server sends back.
+    struct QueuedTransport {
+        requests: std::collections::VecDeque<Vec<u8>>,
+        sent: Vec<Vec<u8>>,
+    }
 
-        let response_frame =
-            HdlcFrame::from_bytes(&response_bytes).expect("failed to decode response frame");
-        let response =
-            SetResponse::from_bytes(&response_frame.information).expect("failed to decode set");
+    impl Transport for QueuedTransport {
+        type Error = ();
 
-        let SetResponse::Normal(response) = response else {
-            panic!("expected normal set response");
-        };
+        fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.sent.push(bytes.to_vec());
+            Ok(())
+        }
 
-        assert_eq!(response.result, DataAccessResult::ReadWriteDenied);
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.requests.pop_front().ok_or(())
+        }
     }
 
     #[test]
-    fn lls_challenge_response_with_wrong_mac_fails() {
-        let mut server = Server::new(0x0001, DummyTransport, Some(b"password".to_vec()), None);
-
-        let association_address = 0x0004;
-        let user_information = default_initiate_request()
-            .to_user_information()
-            .expect("failed to encode initiate request");
-        let initial_request = build_hdlc_request(
-            association_address,
-            AarqApdu {
-                application_context_name: b"CTX".to_vec(),
-                sender_acse_requirements: 0,
-                mechanism_name: Some(b"LLS".to_vec()),
-                calling_authentication_value: None,
-                user_information: user_information.clone(),
-            },
-        );
-
-        let initial_response = server
-            .handle_request(&initial_request)
-            .expect("server failed to issue challenge");
-        let issued_challenge = parse_aare(&initial_response)
-            .responding_authentication_value
-            .expect("expected challenge");
-
-        let mut wrong_response =
-            lls_authenticate(b"password", &issued_challenge).expect("failed to compute mac");
-        wrong_response[0] ^= 0xFF;
-
-        let follow_up_response = server
-            .handle_request(&build_hdlc_request(
-                association_address,
-                AarqApdu {
+    fn multi_drop_server_ignores_frames_for_other_drops() {
+        let aarq_frame = |client_address: u16, destination: HdlcAddress| {
+            HdlcFrame {
+                destination,
+                address: client_address,
+                control: 0,
+                information: AarqApdu {
                     application_context_name: b"CTX".to_vec(),
-                    sender_acse_requirements: 0,
-                    mechanism_name: Some(b"LLS".to_vec()),
-                    calling_authentication_value: Some(wrong_response),
-                    user_information,
-                },
-            ))
-            .expect("server failed to process response");
-
-        let aare = parse_aare(&follow_up_response);
+                    user_information: default_initiate_request()
+                        .to_user_information()
+                        .expect("failed to encode initiate request"),
+                    ..Default::default()
+                }
+                .to_bytes()
+                .expect("failed to serialize aarq"),
+            }
+            .to_bytes()
+            .expect("failed to encode frame")
+        };
+        let requests = [
+            aarq_frame(0x0010, HdlcAddress::new(0x0001, 0x0012)),
+            aarq_frame(
+                0x0010,
+                HdlcAddress::new(0x0001, crate::hdlc::HDLC_NO_STATION),
+            ),
+            aarq_frame(
+                0x0020,
+                HdlcAddress::new(0x0001, crate::hdlc::HDLC_ALL_STATION),
+            ),
+            aarq_frame(0x0030, HdlcAddress::new(0x0001, 0x0011)),
+        ];
+        let transport = QueuedTransport {
+            requests: requests.into_iter().collect(),
+            sent: Vec::new(),
+        };
+        let mut server = Server::new(0x0001, transport, None, None);
+        let mut hdlc_setup = crate::iec_hdlc_setup::IecHdlcSetup::new();
+        hdlc_setup
+            .set_attribute(9, CosemData::LongUnsigned(0x0011))
+            .unwrap();
+        server.apply_hdlc_setup(&hdlc_setup).unwrap();
+
+        assert!(server.run().is_err());
+        assert_eq!(server.transport.sent.len(), 1);
+        assert_eq!(parse_aare(&server.transport.sent[0]).result, 0);
+        assert_eq!(server.associated_clients(), vec![0x0020, 0x0030]);
+    }
 
-        assert_eq!(aare.result, 1);
-        assert!(aare.responding_authentication_value.is_none());
-        let initiate_response = InitiateResponse::from_user_information(&aare.user_information)
-            .expect("expected initiate response");
-        assert_eq!(initiate_response.vaa_name, 0x0007);
-        assert!(!server
-            .lls_challenges
-            .get(&association_address)
-            .expect("challenge should remain for retry")
-            .is_empty());
+    #[test]
+    fn step_serves_one_frame_and_run_stops_on_request() {
+        let aarq_frame = |destination: u16| {
+            HdlcFrame {
+                destination: HdlcAddress::new(destination, 0x0011),
+                address: 0x0010,
+                control: 0,
+                information: AarqApdu {
+                    application_context_name: b"CTX".to_vec(),
+                    user_information: default_initiate_request()
+                        .to_user_information()
+                        .expect("failed to encode initiate request"),
+                    ..Default::default()
+                }
+                .to_bytes()
+                .expect("failed to serialize aarq"),
+            }
+            .to_bytes()
+            .expect("failed to encode frame")
+        };
+        let requests = [aarq_frame(0x0002), aarq_frame(0x0001), aarq_frame(0x0001)];
+        let received: u64 = requests.iter().map(|frame| frame.len() as u64).sum();
+        let transport = QueuedTransport {
+            requests: requests.into_iter().collect(),
+            sent: Vec::new(),
+        };
+        let mut server = Server::new(0x0001, transport, None, None);
+
+        // A frame for another server is received but not answered.
+        assert!(matches!(server.step(), Ok(false)));
+        assert!(matches!(server.step(), Ok(true)));
+        let stop = server.stop_handle();
+        stop.stop();
+        assert!(stop.is_stopped());
+        let statistics = server.run().expect("stopped run failed");
+        assert!(!stop.is_stopped());
+        assert_eq!(server.transport.requests.len(), 1);
+        assert_eq!(statistics.frames_received, 2);
+        assert_eq!(statistics.responses_sent, 1);
+        assert_eq!(statistics.bytes_sent, server.transport.sent[0].len() as u64);
+
+        assert!(server.run().is_err());
+        assert_eq!(server.statistics().frames_received, 3);
+        assert_eq!(server.statistics().bytes_received, received);
     }
 
     #[test]
-    fn release_request_clears_active_association() {
-        let mut server = Server::new(0x0001, DummyTransport, None, None);
+    fn release_request_clears_pending_lls_challenge() {
+        let mut server = Server::new(0x0001, DummyTransport, Some(b"password".to_vec()), None);
 
         let aarq = AarqApdu {
             application_context_name: b"CTX".to_vec(),
             sender_acse_requirements: 0,
-            mechanism_name: None,
+            mechanism_name: Some(b"LLS".to_vec()),
             calling_authentication_value: None,
             user_information: default_initiate_request()
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
 
         let response_bytes = server
             .handle_request(&build_hdlc_request(0x0001, aarq))
             .expect("failed to handle aarq");
         let aare = parse_aare(&response_bytes);
-        assert_eq!(aare.result, 0);
-        assert!(server.active_associations.contains_key(&0x0001));
+        assert!(aare.responding_authentication_value.is_some());
+        assert!(server
+            .associations
+            .get(&0x0001)
+            .and_then(AssociationState::challenge)
+            .is_some());
 
         let release_req = ArlrqApdu {
-            reason: Some(0),
+            reason: None,
             user_information: None,
         };
 
@@ -2612,6 +8270,7 @@ mod tests {
             information: release_req
                 .to_bytes()
                 .expect("failed to encode release request"),
+            ..Default::default()
         };
 
         let release_frame = frame.to_bytes().expect("failed to encode frame");
@@ -2620,49 +8279,416 @@ mod tests {
             .expect("failed to handle release");
         let rlre = parse_rlre(&response_bytes);
         assert_eq!(rlre.reason, Some(0));
-        assert!(server.active_associations.is_empty());
+        assert!(server
+            .associations
+            .get(&0x0001)
+            .and_then(AssociationState::challenge)
+            .is_none());
     }
 
     #[test]
-    fn release_request_clears_pending_lls_challenge() {
+    fn requests_while_authenticating_get_an_exception_response() {
         let mut server = Server::new(0x0001, DummyTransport, Some(b"password".to_vec()), None);
-
+        server.register_object([1, 0, 1, 8, 0, 255], Box::new(Register::new()));
         let aarq = AarqApdu {
             application_context_name: b"CTX".to_vec(),
-            sender_acse_requirements: 0,
             mechanism_name: Some(b"LLS".to_vec()),
-            calling_authentication_value: None,
             user_information: default_initiate_request()
                 .to_user_information()
                 .expect("failed to encode initiate request"),
+            ..Default::default()
         };
-
-        let response_bytes = server
+        server
             .handle_request(&build_hdlc_request(0x0001, aarq))
             .expect("failed to handle aarq");
-        let aare = parse_aare(&response_bytes);
-        assert!(aare.responding_authentication_value.is_some());
-        assert!(server.lls_challenges.contains_key(&0x0001));
+        assert!(matches!(
+            server.associations.get(&0x0001),
+            Some(AssociationState::PendingAuth { .. })
+        ));
 
-        let release_req = ArlrqApdu {
-            reason: None,
-            user_information: None,
+        let get = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 3,
+                instance_id: [1, 0, 1, 8, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+        });
+        let frame = HdlcFrame {
+            address: 0x0001,
+            control: 0,
+            information: get.to_bytes().unwrap(),
+            ..Default::default()
+        };
+        let response = server.handle_request(&frame.to_bytes().unwrap()).unwrap();
+        let information = HdlcFrame::from_bytes(&response).unwrap().information;
+        assert_eq!(
+            ExceptionResponse::from_bytes(&information).unwrap(),
+            ExceptionResponse {
+                state_error: StateError::ServiceNotAllowed,
+                service_error: ServiceError::OperationNotPossible,
+            }
+        );
+    }
+
+    #[test]
+    fn requests_are_dispatched_by_their_tag() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let exchange = |server: &mut Server<DummyTransport>, information: Vec<u8>| {
+            let frame = HdlcFrame {
+                address: 0x0010,
+                control: 0,
+                information,
+                ..Default::default()
+            };
+            server.handle_request(&frame.to_bytes().unwrap())
+        };
+
+        let response = exchange(&mut server, vec![0xCB, 0x01, 0x02]).unwrap();
+        let information = HdlcFrame::from_bytes(&response).unwrap().information;
+        assert_eq!(
+            ExceptionResponse::from_bytes(&information).unwrap(),
+            ExceptionResponse {
+                state_error: StateError::ServiceUnknown,
+                service_error: ServiceError::ServiceNotSupported,
+            }
+        );
+
+        // A truncated AARQ is refused as such instead of being tried as
+        // another service.
+        let response = exchange(&mut server, vec![0x60, 0x20, 0xA1]).unwrap();
+        assert_eq!(parse_aare(&response).result, 1);
+        assert!(server.associated_clients().is_empty());
+    }
+
+    #[test]
+    fn corrupted_aarqs_are_rejected_by_acse() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let aarq = AarqApdu {
+            application_context_name: b"CTX".to_vec(),
+            user_information: default_initiate_request()
+                .to_user_information()
+                .expect("failed to encode initiate request"),
+            ..Default::default()
+        }
+        .to_bytes()
+        .unwrap();
+        let mut exchange = |information: Vec<u8>| {
+            let frame = HdlcFrame {
+                address: PUBLIC_CLIENT_SAP,
+                control: 0,
+                information,
+                ..Default::default()
+            };
+            let response = server.handle_request(&frame.to_bytes().unwrap()).unwrap();
+            let associated = server.is_associated(PUBLIC_CLIENT_SAP);
+            server.associations.clear();
+            (
+                HdlcFrame::from_bytes(&response).unwrap().information,
+                associated,
+            )
+        };
+
+        // Inputs found by fuzzing: a GET request behind the AARQ tag, an
+        // overlong length and a user-information that is no InitiateRequest.
+        let mut inputs = vec![
+            vec![
+                0x60, 0xC0, 0x01, 0xC1, 0x00, 0x01, 0x00, 0x00, 0x01, 0x08, 0x00, 0xFF, 0x02,
+            ],
+            vec![0x60, 0x84, 0xFF, 0xFF, 0xFF, 0xFF, 0xA1],
+            vec![0x60, 0x08, 0xA1, 0x03, b'C', b'T', b'X', 0xBE, 0x01, 0xC0],
+        ];
+        inputs.extend((1..aarq.len()).map(|length| aarq[..length].to_vec()));
+        for input in inputs {
+            let (information, associated) = exchange(input.clone());
+            let aare = AareApdu::from_bytes(&information)
+                .unwrap_or_else(|_| panic!("no AARE for {input:02X?}"))
+                .1;
+            assert_eq!(aare.result, 1, "{input:02X?} accepted");
+            assert!(!associated);
+        }
+
+        // Flipped octets after the tag never make the server answer with
+        // another service.
+        for index in 1..aarq.len() {
+            let mut input = aarq.clone();
+            input[index] ^= 0xFF;
+            let (information, _) = exchange(input);
+            assert_eq!(information.first(), Some(&0x61));
+        }
+    }
+
+    #[test]
+    fn truncated_service_requests_are_survived() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let energy = [1, 0, 1, 8, 0, 255];
+        server.register_object(energy, Box::new(Register::new()));
+        activate_association(&mut server, 0x0010);
+        let descriptor = CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: energy,
+            attribute_id: 2,
         };
+        let get = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: descriptor.clone(),
+            access_selection: None,
+        })
+        .to_bytes()
+        .unwrap();
+        let set = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: descriptor,
+            access_selection: None,
+            value: CosemData::DoubleLongUnsigned(5),
+        })
+        .to_bytes()
+        .unwrap();
+        let action = ActionRequest::Normal(ActionRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 3,
+                instance_id: energy,
+                method_id: 1,
+            },
+            method_invocation_parameters: Some(CosemData::Integer(0)),
+        })
+        .to_bytes()
+        .unwrap();
+
+        // Inputs found by fuzzing, then every truncation of each service.
+        let mut inputs = vec![
+            vec![0xC1, 0x01, 0xFF, 0x00, 0x03, 0x01, 0x00, 0x01],
+            vec![0xC0, 0x01, 0xC1, 0x00],
+        ];
+        for request in [&get, &set, &action] {
+            inputs.extend((1..request.len()).map(|length| request[..length].to_vec()));
+        }
+        for information in inputs {
+            let frame = HdlcFrame {
+                address: 0x0010,
+                control: 0,
+                information,
+                ..Default::default()
+            };
+            let _ = server.process_frame(&frame.to_bytes().unwrap());
+        }
 
         let frame = HdlcFrame {
-            address: 0x0001,
+            address: 0x0010,
             control: 0,
-            information: release_req
-                .to_bytes()
-                .expect("failed to encode release request"),
+            information: get,
+            ..Default::default()
         };
+        let response = server.process_frame(&frame.to_bytes().unwrap()).unwrap();
+        let information = HdlcFrame::from_bytes(&response.unwrap())
+            .unwrap()
+            .information;
+        assert!(matches!(
+            GetResponse::from_bytes(&information),
+            Ok(GetResponse::Normal(response)) if matches!(response.result, GetDataResult::Data(_))
+        ));
+    }
 
-        let release_frame = frame.to_bytes().expect("failed to encode frame");
-        let response_bytes = server
-            .handle_request(&release_frame)
-            .expect("failed to handle release");
-        let rlre = parse_rlre(&response_bytes);
-        assert_eq!(rlre.reason, Some(0));
-        assert!(!server.lls_challenges.contains_key(&0x0001));
+    #[test]
+    fn association_state_transitions_are_validated() {
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        assert!(server
+            .transition(0x0010, AssociationState::Releasing)
+            .is_none());
+        assert!(server.associations.is_empty());
+
+        assert!(matches!(
+            server.transition(0x0010, associated_context(64)),
+            Some(AssociationState::Idle)
+        ));
+        assert!(matches!(
+            server.transition(0x0010, AssociationState::Releasing),
+            Some(AssociationState::Associated { .. })
+        ));
+        assert!(server.transition(0x0010, associated_context(64)).is_none());
+        assert!(server
+            .transition(
+                0x0010,
+                AssociationState::PendingAuth {
+                    challenge: vec![1; 16]
+                }
+            )
+            .is_none());
+        assert!(matches!(
+            server.transition(0x0010, AssociationState::Idle),
+            Some(AssociationState::Releasing)
+        ));
+        assert!(server.associations.is_empty());
+        assert!(!server.release_association(0x0010));
+    }
+
+    struct FlakyTransport {
+        failures: usize,
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl Transport for FlakyTransport {
+        type Error = ();
+
+        fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(());
+            }
+            self.sent.push(bytes.to_vec());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn push_is_sent_in_window_and_retried_after_failures() {
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let push_ln = [0, 0, 25, 9, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(1234))
+            .unwrap();
+        server.register_object(register_ln, Box::new(register));
+
+        let daily = |hour: u8| {
+            CosemData::DateTime(vec![
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, hour, 0, 0, 0xFF, 0x80, 0, 0,
+            ])
+        };
+        let mut push = PushSetup::new();
+        push.set_attribute(
+            2,
+            CosemData::Array(vec![CaptureObjectDefinition {
+                class_id: 3,
+                logical_name: register_ln,
+                attribute_index: 2,
+                data_index: 0,
+            }
+            .to_cosem_data()]),
+        )
+        .unwrap();
+        push.set_attribute(
+            4,
+            CosemData::Array(vec![CosemData::Structure(vec![daily(2), daily(4)])]),
+        )
+        .unwrap();
+        push.set_attribute(6, CosemData::Unsigned(2)).unwrap();
+        push.set_attribute(7, CosemData::LongUnsigned(60)).unwrap();
+        server.register_object(push_ln, Box::new(push));
+
+        // Triggered at midnight, the push waits for the window opening at 02:00.
+        server.trigger_push(push_ln, 0).unwrap();
+        assert_eq!(
+            server.pending_push(push_ln),
+            Some(PendingPush {
+                due: 7200,
+                attempts: 0
+            })
+        );
+        let mut transport = FlakyTransport {
+            failures: 2,
+            sent: Vec::new(),
+        };
+        assert_eq!(server.poll_pushes(7199, &mut transport), 0);
+        assert_eq!(server.poll_pushes(7200, &mut transport), 0);
+        assert_eq!(server.pending_push(push_ln).map(|p| p.due), Some(7260));
+        assert_eq!(server.poll_pushes(7260, &mut transport), 0);
+        assert_eq!(server.poll_pushes(7320, &mut transport), 1);
+        assert_eq!(server.pending_push(push_ln), None);
+
+        let notification = DataNotification::from_bytes(&transport.sent[0]).unwrap();
+        assert_eq!(
+            notification.notification_body,
+            CosemData::Structure(vec![CosemData::DoubleLongUnsigned(1234)])
+        );
+
+        // Retries exhausted: the push is dropped.
+        server.trigger_push(push_ln, 7400).unwrap();
+        transport.failures = 3;
+        for now in [7400, 7460, 7520] {
+            assert_eq!(server.poll_pushes(now, &mut transport), 0);
+        }
+        assert_eq!(server.pending_push(push_ln), None);
+
+        // An attempt due after the window closed waits for the next opening.
+        server.trigger_push(push_ln, 14_000).unwrap();
+        assert_eq!(server.poll_pushes(14_500, &mut transport), 0);
+        assert_eq!(
+            server.pending_push(push_ln).map(|p| p.due),
+            Some(86_400 + 7200)
+        );
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn push_marked_compressed_is_sent_deflated() {
+        use crate::compression::{decompress_notification, Deflate};
+        use crate::push_setup::PUSH_MESSAGE_COMPRESSED_AXDR;
+        use crate::xdlms::Notification;
+        let register_ln = [1, 0, 1, 8, 0, 255];
+        let push_ln = [0, 0, 25, 9, 0, 255];
+        let mut server = Server::new(0x0001, DummyTransport, None, None);
+        let mut register = Register::new();
+        register
+            .set_attribute(2, CosemData::DoubleLongUnsigned(1234))
+            .unwrap();
+        server.register_object(register_ln, Box::new(register));
+        let mut push = PushSetup::new();
+        push.set_attribute(
+            2,
+            CosemData::Array(vec![CaptureObjectDefinition {
+                class_id: 3,
+                logical_name: register_ln,
+                attribute_index: 2,
+                data_index: 0,
+            }
+            .to_cosem_data()]),
+        )
+        .unwrap();
+        push.set_attribute(
+            3,
+            CosemData::Structure(vec![
+                CosemData::Enum(0),
+                CosemData::OctetString(b"10.0.0.1:4059".to_vec()),
+                CosemData::Enum(PUSH_MESSAGE_COMPRESSED_AXDR),
+            ]),
+        )
+        .unwrap();
+        server.register_object(push_ln, Box::new(push));
+        let mut transport = FlakyTransport {
+            failures: 0,
+            sent: Vec::new(),
+        };
+
+        // Without a compression the marked push cannot be sent.
+        server.trigger_push(push_ln, 0).unwrap();
+        assert_eq!(server.poll_pushes(0, &mut transport), 0);
+
+        server.set_push_compression(Some(Box::new(Deflate::new())));
+        server.trigger_push(push_ln, 0).unwrap();
+        assert_eq!(server.poll_pushes(0, &mut transport), 1);
+        let sent = Notification::from_bytes(&transport.sent[0]).unwrap();
+        assert!(matches!(
+            &sent,
+            Notification::Data(DataNotification {
+                notification_body: CosemData::OctetString(_),
+                ..
+            })
+        ));
+        let Notification::Data(restored) = decompress_notification(sent, &Deflate::new()).unwrap()
+        else {
+            panic!("expected a data-notification");
+        };
+        assert_eq!(
+            restored.notification_body,
+            CosemData::Structure(vec![CosemData::DoubleLongUnsigned(1234)])
+        );
     }
 }
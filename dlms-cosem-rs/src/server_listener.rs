@@ -0,0 +1,222 @@
+#![cfg(feature = "std")]
+
+use crate::server::{Server, ServerInterface};
+use crate::transport::{Connection, Listener, Transport};
+use std::collections::BTreeSet;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+
+// Connections served at the same time unless configured otherwise.
+const DEFAULT_MAX_CONNECTIONS: usize = 4;
+
+// Accepts connections and serves each on its own thread against one shared
+// server; wrapper connections over TCP unless another listener is given.
+// Every connection has associations of its own, released when it closes, so
+// a peer cannot use an association another connection established.
+// Connections beyond `max_connections` are dropped right away.
+pub struct ServerListener<T: Transport, L = TcpListener> {
+    listener: L,
+    server: Arc<Mutex<Server<T>>>,
+    // Ids of the open connections.
+    connections: Arc<Mutex<BTreeSet<u64>>>,
+    max_connections: usize,
+    next_connection_id: u64,
+}
+
+impl<T: Transport + Send + 'static> ServerListener<T> {
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+impl<T, L> ServerListener<T, L>
+where
+    T: Transport + Send + 'static,
+    L: Listener,
+    L::Connection: Send + 'static,
+{
+    pub fn new(listener: L, server: Server<T>) -> Self {
+        Self {
+            listener,
+            server: Arc::new(Mutex::new(server)),
+            connections: Arc::new(Mutex::new(BTreeSet::new())),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            next_connection_id: 0,
+        }
+    }
+
+    pub fn set_max_connections(&mut self, max_connections: usize) {
+        self.max_connections = max_connections.max(1);
+    }
+
+    // The server shared by all connections, for the host to update objects.
+    pub fn server(&self) -> Arc<Mutex<Server<T>>> {
+        Arc::clone(&self.server)
+    }
+
+    pub fn active_connections(&self) -> usize {
+        self.connections
+            .lock()
+            .map(|connections| connections.len())
+            .unwrap_or(0)
+    }
+
+    pub fn run(&mut self) -> Result<(), L::Error> {
+        loop {
+            self.accept()?;
+        }
+    }
+
+    // Waits for the next connection. `false` if it was refused because the
+    // connection limit is reached or the peer access list of the server does
+    // not allow the peer. Connections are numbered from 0 in the order they
+    // are accepted, as `Server::connection_clients` takes them.
+    pub fn accept(&mut self) -> Result<bool, L::Error> {
+        let (connection, peer) = self.listener.accept_from()?;
+        if let Some(peer) = peer {
+            let admitted = self
+                .server
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .admits_peer(peer);
+            if !admitted {
+                return Ok(false);
+            }
+        }
+        let id = self.next_connection_id;
+        {
+            // The set stays consistent even if a connection thread panicked.
+            let mut connections = self
+                .connections
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if connections.len() >= self.max_connections {
+                // Dropping the connection closes it.
+                return Ok(false);
+            }
+            connections.insert(id);
+        }
+        self.next_connection_id += 1;
+
+        let server = Arc::clone(&self.server);
+        let connections = Arc::clone(&self.connections);
+        thread::spawn(move || {
+            serve_connection(id, connection, &server);
+            server
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .close_connection(id);
+            connections
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&id);
+        });
+        Ok(true)
+    }
+}
+
+impl<T: Transport + Send + 'static> Server<T> {
+    // Serves the connections of `listener` until it fails, as a
+    // `ServerListener` with the default connection limit does.
+    pub fn serve<L>(self, listener: L) -> Result<(), L::Error>
+    where
+        L: Listener,
+        L::Connection: Send + 'static,
+    {
+        ServerListener::new(listener, self).run()
+    }
+}
+
+fn serve_connection<T: Transport, C: Connection>(
+    id: u64,
+    mut transport: C,
+    server: &Mutex<Server<T>>,
+) {
+    // A read error also covers the peer closing the connection.
+    while let Ok(request) = transport.receive() {
+        let response = {
+            let Ok(mut server) = server.lock() else {
+                return;
+            };
+            server.process_connection_frame(id, &request)
+        };
+        // A frame the server fails on is dropped; the peer may send the next.
+        if let Ok(Some(bytes)) = response {
+            if transport.send(&bytes).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// Serves one server over several interfaces at once, e.g. the optical port
+// and a TCP wrapper, each read on its own thread so that their requests
+// interleave. Each interface only answers the clients it is configured for.
+pub struct ServerInterfaces<T: Transport> {
+    server: Arc<Mutex<Server<T>>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<T: Transport + Send + 'static> ServerInterfaces<T> {
+    pub fn new(server: Server<T>) -> Self {
+        Self {
+            server: Arc::new(Mutex::new(server)),
+            threads: Vec::new(),
+        }
+    }
+
+    // The server shared by all interfaces, for the host to update objects.
+    pub fn server(&self) -> Arc<Mutex<Server<T>>> {
+        Arc::clone(&self.server)
+    }
+
+    // Starts serving an interface until its transport fails or closes. The
+    // associations established over it are released then.
+    pub fn spawn<C>(&mut self, interface: ServerInterface, transport: C)
+    where
+        C: Transport + Send + 'static,
+    {
+        let server = Arc::clone(&self.server);
+        self.threads.push(thread::spawn(move || {
+            serve_interface(&interface, transport, &server);
+            let Ok(mut server) = server.lock() else {
+                return;
+            };
+            for address in server.associated_clients() {
+                if server.interface_of(address) == Some(interface.id()) {
+                    server.release_association(address);
+                }
+            }
+        }));
+    }
+
+    // Waits until every interface has closed.
+    pub fn join(self) {
+        for thread in self.threads {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve_interface<T: Transport, C: Transport>(
+    interface: &ServerInterface,
+    mut transport: C,
+    server: &Mutex<Server<T>>,
+) {
+    while let Ok(request) = transport.receive() {
+        let response = {
+            let Ok(mut server) = server.lock() else {
+                return;
+            };
+            server.process_interface_frame(interface, &request)
+        };
+        // Unlike a connection, a serial port cannot be reopened by the client,
+        // so a frame the server fails on is only dropped.
+        if let Ok(Some(bytes)) = response {
+            if transport.send(&bytes).is_err() {
+                return;
+            }
+        }
+    }
+}
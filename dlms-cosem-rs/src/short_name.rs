@@ -0,0 +1,278 @@
+use crate::cosem::{
+    CosemClassId, CosemObjectAttributeId, CosemObjectInstanceId, CosemObjectMethodId,
+};
+use crate::error::DlmsError;
+use std::collections::BTreeMap;
+use std::vec::Vec;
+
+pub type ShortName = u16;
+
+// Blue Book 4.1.3: the base name of an object is the short name of attribute 1,
+// each further attribute follows at an offset of 8. Methods start at a class
+// specific offset and are also spaced by 8.
+const SHORT_NAME_STEP: u16 = 8;
+
+// Reserved for the Association SN object (class 12) of the current association.
+pub const ASSOCIATION_SN_BASE_NAME: ShortName = 0xFA00;
+
+const FIRST_ALLOCATED_BASE_NAME: ShortName = 0x0100;
+
+pub fn first_method_offset(class_id: CosemClassId) -> Option<u16> {
+    match class_id {
+        3 => Some(0x28),
+        4 => Some(0x38),
+        5 => Some(0x48),
+        6 => Some(0x30),
+        7 => Some(0x58),
+        8 => Some(0x60),
+        9 | 10 | 12 | 17 | 70 => Some(0x20),
+        11 => Some(0x10),
+        15 => Some(0x60),
+        18 => Some(0x40),
+        20 => Some(0x50),
+        40 => Some(0x38),
+        61 => Some(0x28),
+        64 => Some(0x30),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortNameReference {
+    Attribute {
+        logical_name: CosemObjectInstanceId,
+        attribute_id: CosemObjectAttributeId,
+    },
+    Method {
+        logical_name: CosemObjectInstanceId,
+        method_id: CosemObjectMethodId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortNameEntry {
+    pub base_name: ShortName,
+    pub class_id: CosemClassId,
+    pub logical_name: CosemObjectInstanceId,
+    pub attribute_count: CosemObjectAttributeId,
+    pub method_count: CosemObjectMethodId,
+}
+
+impl ShortNameEntry {
+    fn method_offset(&self) -> u16 {
+        first_method_offset(self.class_id)
+            .unwrap_or(self.attribute_count.max(1) as u16 * SHORT_NAME_STEP)
+    }
+
+    // Number of short names spanned by the object, rounded to the step size.
+    fn span(&self) -> u16 {
+        let attributes = self.attribute_count.max(1) as u16 * SHORT_NAME_STEP;
+        if self.method_count > 0 {
+            attributes.max(self.method_offset() + self.method_count as u16 * SHORT_NAME_STEP)
+        } else {
+            attributes
+        }
+    }
+
+    fn contains(&self, short_name: ShortName) -> bool {
+        short_name >= self.base_name && (short_name - self.base_name) < self.span()
+    }
+
+    pub fn attribute_short_name(&self, attribute_id: CosemObjectAttributeId) -> Option<ShortName> {
+        if attribute_id < 1 || attribute_id > self.attribute_count.max(1) {
+            return None;
+        }
+        Some(self.base_name + (attribute_id as u16 - 1) * SHORT_NAME_STEP)
+    }
+
+    pub fn method_short_name(&self, method_id: CosemObjectMethodId) -> Option<ShortName> {
+        if method_id < 1 || method_id > self.method_count {
+            return None;
+        }
+        Some(self.base_name + self.method_offset() + (method_id as u16 - 1) * SHORT_NAME_STEP)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ShortNameMap {
+    entries: BTreeMap<ShortName, ShortNameEntry>,
+}
+
+impl ShortNameMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn assign(&mut self, entry: ShortNameEntry) -> Result<(), DlmsError> {
+        let end = entry.base_name as u32 + entry.span() as u32;
+        if !entry.base_name.is_multiple_of(SHORT_NAME_STEP) || end > u16::MAX as u32 + 1 {
+            return Err(DlmsError::Cosem);
+        }
+        let overlaps = self.entries.values().any(|existing| {
+            existing.logical_name == entry.logical_name
+                || (entry.base_name as u32) < existing.base_name as u32 + existing.span() as u32
+                    && (existing.base_name as u32) < end
+        });
+        if overlaps {
+            return Err(DlmsError::Cosem);
+        }
+        self.entries.insert(entry.base_name, entry);
+        Ok(())
+    }
+
+    pub fn allocate(
+        &mut self,
+        class_id: CosemClassId,
+        logical_name: CosemObjectInstanceId,
+        attribute_count: CosemObjectAttributeId,
+        method_count: CosemObjectMethodId,
+    ) -> Result<ShortName, DlmsError> {
+        let base_name = self
+            .entries
+            .values()
+            .filter(|entry| entry.base_name < ASSOCIATION_SN_BASE_NAME)
+            .map(|entry| entry.base_name as u32 + entry.span() as u32)
+            .max()
+            .unwrap_or(FIRST_ALLOCATED_BASE_NAME as u32);
+        let base_name = u16::try_from(base_name).map_err(|_| DlmsError::VecIsFull)?;
+
+        let entry = ShortNameEntry {
+            base_name,
+            class_id,
+            logical_name,
+            attribute_count,
+            method_count,
+        };
+        if base_name as u32 + entry.span() as u32 > ASSOCIATION_SN_BASE_NAME as u32 {
+            return Err(DlmsError::VecIsFull);
+        }
+        self.assign(entry)?;
+        Ok(base_name)
+    }
+
+    // Frees the short names of an object; the others keep theirs.
+    pub fn remove(&mut self, logical_name: &CosemObjectInstanceId) -> Option<ShortNameEntry> {
+        let base_name = self.base_name(logical_name)?;
+        self.entries.remove(&base_name)
+    }
+
+    pub fn entries(&self) -> Vec<&ShortNameEntry> {
+        self.entries.values().collect()
+    }
+
+    pub fn entry_for(&self, logical_name: &CosemObjectInstanceId) -> Option<&ShortNameEntry> {
+        self.entries
+            .values()
+            .find(|entry| &entry.logical_name == logical_name)
+    }
+
+    pub fn base_name(&self, logical_name: &CosemObjectInstanceId) -> Option<ShortName> {
+        self.entry_for(logical_name).map(|entry| entry.base_name)
+    }
+
+    pub fn attribute_short_name(
+        &self,
+        logical_name: &CosemObjectInstanceId,
+        attribute_id: CosemObjectAttributeId,
+    ) -> Option<ShortName> {
+        self.entry_for(logical_name)?
+            .attribute_short_name(attribute_id)
+    }
+
+    pub fn method_short_name(
+        &self,
+        logical_name: &CosemObjectInstanceId,
+        method_id: CosemObjectMethodId,
+    ) -> Option<ShortName> {
+        self.entry_for(logical_name)?.method_short_name(method_id)
+    }
+
+    pub fn resolve(&self, short_name: ShortName) -> Option<ShortNameReference> {
+        let (_, entry) = self.entries.range(..=short_name).next_back()?;
+        if !entry.contains(short_name) {
+            return None;
+        }
+        let offset = short_name - entry.base_name;
+        if !offset.is_multiple_of(SHORT_NAME_STEP) {
+            return None;
+        }
+
+        let method_offset = entry.method_offset();
+        if entry.method_count > 0 && offset >= method_offset {
+            let method_id = ((offset - method_offset) / SHORT_NAME_STEP + 1) as CosemObjectMethodId;
+            if method_id <= entry.method_count {
+                return Some(ShortNameReference::Method {
+                    logical_name: entry.logical_name,
+                    method_id,
+                });
+            }
+        }
+
+        let attribute_id = (offset / SHORT_NAME_STEP + 1) as CosemObjectAttributeId;
+        if attribute_id <= entry.attribute_count.max(1) {
+            return Some(ShortNameReference::Attribute {
+                logical_name: entry.logical_name,
+                attribute_id,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    const REGISTER_LN: [u8; 6] = [1, 0, 1, 8, 0, 255];
+    const CLOCK_LN: [u8; 6] = [0, 0, 1, 0, 0, 255];
+
+    #[test]
+    fn register_short_names_follow_blue_book_offsets() {
+        let mut map = ShortNameMap::new();
+        map.assign(ShortNameEntry {
+            base_name: 0x2000,
+            class_id: 3,
+            logical_name: REGISTER_LN,
+            attribute_count: 3,
+            method_count: 1,
+        })
+        .expect("failed to assign register");
+
+        assert_eq!(map.attribute_short_name(&REGISTER_LN, 2), Some(0x2008));
+        assert_eq!(map.attribute_short_name(&REGISTER_LN, 3), Some(0x2010));
+        assert_eq!(map.method_short_name(&REGISTER_LN, 1), Some(0x2028));
+        assert_eq!(
+            map.resolve(0x2028),
+            Some(ShortNameReference::Method {
+                logical_name: REGISTER_LN,
+                method_id: 1,
+            })
+        );
+        assert_eq!(
+            map.resolve(0x2010),
+            Some(ShortNameReference::Attribute {
+                logical_name: REGISTER_LN,
+                attribute_id: 3,
+            })
+        );
+        assert_eq!(map.resolve(0x2018), None);
+        assert_eq!(map.resolve(0x2004), None);
+    }
+
+    #[test]
+    fn allocation_does_not_overlap_existing_objects() {
+        let mut map = ShortNameMap::new();
+        let register = map
+            .allocate(3, REGISTER_LN, 3, 1)
+            .expect("failed to allocate register");
+        let clock = map
+            .allocate(8, CLOCK_LN, 9, 6)
+            .expect("failed to allocate clock");
+
+        assert_eq!(register, FIRST_ALLOCATED_BASE_NAME);
+        assert_eq!(clock, register + 0x30);
+        assert_eq!(map.method_short_name(&CLOCK_LN, 6), Some(clock + 0x88));
+        assert!(map.allocate(3, REGISTER_LN, 3, 1).is_err());
+    }
+}
@@ -0,0 +1,480 @@
+use crate::clock::{date_time_from_seconds, date_time_seconds, ClockStatus};
+use crate::server::Server;
+use crate::transport::Transport;
+use crate::types::CosemData;
+use rand_core::{OsRng, RngCore};
+use std::ops::Range;
+use std::vec::Vec;
+
+pub const CLOCK_LN: [u8; 6] = [0, 0, 1, 0, 0, 255];
+pub const EVENT_CODE_LN: [u8; 6] = [0, 0, 96, 11, 0, 255];
+pub const STANDARD_EVENT_LOG_LN: [u8; 6] = [0, 0, 99, 98, 0, 255];
+
+// Bit of the clock status set while the time is not valid.
+pub const CLOCK_STATUS_INVALID: u8 = ClockStatus::INVALID_VALUE;
+
+// Simulated time passes in steps of a minute; outages start and end on them.
+const STEP_SECONDS: u64 = 60;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+// Events of the standard event log the simulator raises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterEvent {
+    PowerDown,
+    PowerUp,
+    // Logged with the time before and after the clock was set.
+    ClockAdjustedOld,
+    ClockAdjustedNew,
+    ClockInvalid,
+}
+
+impl MeterEvent {
+    pub fn code(self) -> u16 {
+        match self {
+            MeterEvent::PowerDown => 1,
+            MeterEvent::PowerUp => 2,
+            MeterEvent::ClockAdjustedOld => 4,
+            MeterEvent::ClockAdjustedNew => 5,
+            MeterEvent::ClockInvalid => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Outage {
+    elapsed: u64,
+    duration: u64,
+}
+
+// Runs the clock of a server like the one of a field meter: it drifts from the
+// reference time, stops once an outage outlasts its backup and is then
+// invalid until a client sets it. Power downs and ups, clock adjustments and
+// invalid clocks are logged to the standard event log by writing the event
+// code object and capturing the log, which should capture the clock and the
+// event code. Objects the server lacks are left out.
+//
+// Outages come at random with `with_outages`, or on demand with
+// `power_outage`. `with_seed` makes the random ones reproducible.
+#[derive(Debug, Clone)]
+pub struct MeterSimulator {
+    clock: [u8; 6],
+    event_code: [u8; 6],
+    event_log: [u8; 6],
+    reference: u64,
+    meter: f64,
+    drift_ppm: f64,
+    outages_per_day: f64,
+    outage_duration: Range<u64>,
+    backup: u64,
+    outage: Option<Outage>,
+    invalid: bool,
+    written: Option<Vec<u8>>,
+    rng: Xorshift,
+}
+
+impl MeterSimulator {
+    // Starts with the meter clock at the reference time, seconds since
+    // 1970-01-01.
+    pub fn new(start: u64) -> Self {
+        Self {
+            clock: CLOCK_LN,
+            event_code: EVENT_CODE_LN,
+            event_log: STANDARD_EVENT_LOG_LN,
+            reference: start,
+            meter: start as f64,
+            drift_ppm: 0.0,
+            outages_per_day: 0.0,
+            outage_duration: 0..0,
+            backup: 0,
+            outage: None,
+            invalid: false,
+            written: None,
+            rng: Xorshift::from_entropy(),
+        }
+    }
+
+    pub fn with_objects(mut self, clock: [u8; 6], event_code: [u8; 6], event_log: [u8; 6]) -> Self {
+        self.clock = clock;
+        self.event_code = event_code;
+        self.event_log = event_log;
+        self
+    }
+
+    // Positive values make the meter clock run fast.
+    pub fn with_drift_ppm(mut self, drift_ppm: f64) -> Self {
+        self.drift_ppm = drift_ppm;
+        self
+    }
+
+    // Outages starting on average `per_day` times a day, each lasting a
+    // number of seconds drawn from `duration`.
+    pub fn with_outages(mut self, per_day: f64, duration: Range<u64>) -> Self {
+        self.outages_per_day = per_day.max(0.0);
+        self.outage_duration = duration;
+        self
+    }
+
+    // Seconds the clock keeps running on its backup during an outage.
+    pub fn with_backup(mut self, seconds: u64) -> Self {
+        self.backup = seconds;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Xorshift::new(seed);
+        self
+    }
+
+    pub fn reference_time(&self) -> u64 {
+        self.reference
+    }
+
+    pub fn meter_time(&self) -> u64 {
+        self.meter as u64
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.outage.is_none()
+    }
+
+    pub fn is_clock_invalid(&self) -> bool {
+        self.invalid
+    }
+
+    // Lets `seconds` of reference time pass and writes the meter time to the
+    // clock. A time a client set since the last call is taken over first.
+    // Returns the events logged, with the meter time they were logged at.
+    pub fn advance<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        seconds: u64,
+    ) -> Vec<(u64, MeterEvent)> {
+        let mut events = Vec::new();
+        self.take_over_clock_setting(server, &mut events);
+        let mut left = seconds;
+        while left > 0 {
+            let step = left.min(STEP_SECONDS);
+            left -= step;
+            self.reference += step;
+            match self.outage.as_mut() {
+                Some(outage) => {
+                    let running = step.min(self.backup.saturating_sub(outage.elapsed));
+                    outage.elapsed += step;
+                    let over = outage.elapsed >= outage.duration;
+                    self.meter += self.drifted(running);
+                    if over {
+                        self.power_up(server, &mut events);
+                    }
+                }
+                None => {
+                    self.meter += self.drifted(step);
+                    let probability = self.outages_per_day * step as f64 / SECONDS_PER_DAY;
+                    if probability > 0.0 && self.rng.draw() < probability {
+                        let duration = self.draw_duration();
+                        self.power_down(server, duration, &mut events);
+                    }
+                }
+            }
+        }
+        self.write_clock(server);
+        events
+    }
+
+    // Powers the meter down now for `duration` seconds and lets them pass.
+    pub fn power_outage<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        duration: u64,
+    ) -> Vec<(u64, MeterEvent)> {
+        let mut events = Vec::new();
+        self.take_over_clock_setting(server, &mut events);
+        if self.outage.is_none() {
+            self.power_down(server, duration.max(1), &mut events);
+        }
+        events.extend(self.advance(server, duration.max(1)));
+        events
+    }
+
+    fn power_down<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        duration: u64,
+        events: &mut Vec<(u64, MeterEvent)>,
+    ) {
+        self.log(server, MeterEvent::PowerDown, events);
+        self.outage = Some(Outage {
+            elapsed: 0,
+            duration,
+        });
+    }
+
+    fn power_up<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        events: &mut Vec<(u64, MeterEvent)>,
+    ) {
+        let Some(outage) = self.outage.take() else {
+            return;
+        };
+        let stopped = outage.elapsed > self.backup && !self.invalid;
+        self.invalid |= stopped;
+        self.log(server, MeterEvent::PowerUp, events);
+        if stopped {
+            self.log(server, MeterEvent::ClockInvalid, events);
+        }
+    }
+
+    fn take_over_clock_setting<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        events: &mut Vec<(u64, MeterEvent)>,
+    ) {
+        let Some(written) = self.written.as_ref() else {
+            return;
+        };
+        let Some((_, clock)) = server
+            .objects()
+            .find(|(logical_name, _)| *logical_name == self.clock)
+        else {
+            return;
+        };
+        let time = match clock.get_attribute(2) {
+            Some(CosemData::OctetString(time)) | Some(CosemData::DateTime(time)) => time,
+            _ => return,
+        };
+        if time == *written {
+            return;
+        }
+        let Some(seconds) = date_time_seconds(&time) else {
+            return;
+        };
+        self.log(server, MeterEvent::ClockAdjustedOld, events);
+        self.meter = seconds as f64;
+        self.invalid = false;
+        self.log(server, MeterEvent::ClockAdjustedNew, events);
+    }
+
+    fn log<T: Transport>(
+        &mut self,
+        server: &mut Server<T>,
+        event: MeterEvent,
+        events: &mut Vec<(u64, MeterEvent)>,
+    ) {
+        self.write_clock(server);
+        if server
+            .set_object_attribute(self.event_code, 2, CosemData::LongUnsigned(event.code()))
+            .is_some()
+        {
+            server.capture_profile(self.event_log);
+        }
+        events.push((self.meter_time(), event));
+    }
+
+    fn write_clock<T: Transport>(&mut self, server: &mut Server<T>) {
+        let status = ClockStatus::default().with(ClockStatus::INVALID_VALUE, self.invalid);
+        let Some(mut time) = date_time_from_seconds(self.meter_time()) else {
+            return;
+        };
+        time[11] = status.bits();
+        if server.update_clock_time(self.clock, time).is_ok() {
+            server.set_object_attribute(self.clock, 4, status.to_cosem_data());
+            self.written = Some(time.to_vec());
+        }
+    }
+
+    fn drifted(&self, seconds: u64) -> f64 {
+        seconds as f64 * (1.0 + self.drift_ppm / 1_000_000.0)
+    }
+
+    fn draw_duration(&mut self) -> u64 {
+        let Range { start, end } = self.outage_duration;
+        let span = end.saturating_sub(start);
+        (start + (self.rng.draw() * span as f64) as u64).max(1)
+    }
+}
+
+// xorshift64* draws of the simulator and of `FaultyTransport`, reproducible
+// from a seed.
+#[derive(Debug, Clone)]
+pub(crate) struct Xorshift(u64);
+
+impl Xorshift {
+    pub(crate) fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero.
+        Self(seed | 1)
+    }
+
+    pub(crate) fn from_entropy() -> Self {
+        Self::new(OsRng.next_u64())
+    }
+
+    // Mapped onto [0, 1).
+    pub(crate) fn draw(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::clock::Clock;
+    use crate::cosem_object::CosemObject;
+    use crate::data::Data;
+    use crate::profile_generic::{CaptureObjectDefinition, ProfileGeneric};
+    use crate::server::ServerBuilder;
+    use crate::testing::DetachedTransport;
+
+    const START: u64 = 1_792_240_200;
+
+    fn meter() -> Server<DetachedTransport> {
+        let mut event_log = ProfileGeneric::new();
+        let capture = |class_id, logical_name| {
+            CaptureObjectDefinition {
+                class_id,
+                logical_name,
+                attribute_index: 2,
+                data_index: 0,
+            }
+            .to_cosem_data()
+        };
+        event_log
+            .set_attribute(
+                3,
+                CosemData::Array(vec![capture(8, CLOCK_LN), capture(1, EVENT_CODE_LN)]),
+            )
+            .unwrap();
+        ServerBuilder::new(1, DetachedTransport)
+            .object(CLOCK_LN, Box::new(Clock::new()))
+            .object(
+                EVENT_CODE_LN,
+                Box::new(Data::new(CosemData::LongUnsigned(0))),
+            )
+            .object(STANDARD_EVENT_LOG_LN, Box::new(event_log))
+            .build()
+    }
+
+    fn attribute(
+        server: &Server<DetachedTransport>,
+        logical_name: [u8; 6],
+        attribute_id: i8,
+    ) -> CosemData {
+        server
+            .objects()
+            .find(|(object_ln, _)| *object_ln == logical_name)
+            .and_then(|(_, object)| object.get_attribute(attribute_id))
+            .unwrap()
+    }
+
+    fn clock_seconds(server: &Server<DetachedTransport>) -> u64 {
+        let CosemData::OctetString(time) = attribute(server, CLOCK_LN, 2) else {
+            panic!("no clock time");
+        };
+        date_time_seconds(&time).unwrap()
+    }
+
+    // Event codes in the standard event log.
+    fn logged(server: &Server<DetachedTransport>) -> Vec<u16> {
+        let CosemData::Array(entries) = attribute(server, STANDARD_EVENT_LOG_LN, 2) else {
+            panic!("no buffer");
+        };
+        entries
+            .iter()
+            .map(|entry| match entry {
+                CosemData::Structure(columns) => match columns[1] {
+                    CosemData::LongUnsigned(code) => code,
+                    _ => panic!("no event code"),
+                },
+                _ => panic!("no entry"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clock_drifts_from_the_reference() {
+        let mut server = meter();
+        let mut simulator = MeterSimulator::new(START).with_drift_ppm(100.0);
+        assert!(simulator.advance(&mut server, 86_400).is_empty());
+        assert_eq!(simulator.reference_time(), START + 86_400);
+        assert_eq!(clock_seconds(&server), START + 86_400 + 8);
+        assert_eq!(attribute(&server, CLOCK_LN, 4), CosemData::Unsigned(0));
+    }
+
+    #[test]
+    fn test_outage_beyond_backup_invalidates_clock_until_set() {
+        let mut server = meter();
+        let mut simulator = MeterSimulator::new(START).with_backup(600);
+        let events = simulator.power_outage(&mut server, 300);
+        assert_eq!(
+            events,
+            [
+                (START, MeterEvent::PowerDown),
+                (START + 300, MeterEvent::PowerUp)
+            ]
+        );
+        assert!(!simulator.is_clock_invalid());
+
+        let events = simulator.power_outage(&mut server, 3600);
+        assert_eq!(
+            events,
+            [
+                (START + 300, MeterEvent::PowerDown),
+                (START + 900, MeterEvent::PowerUp),
+                (START + 900, MeterEvent::ClockInvalid)
+            ]
+        );
+        assert!(simulator.is_powered());
+        assert_eq!(
+            attribute(&server, CLOCK_LN, 4),
+            CosemData::Unsigned(CLOCK_STATUS_INVALID)
+        );
+        assert_eq!(logged(&server), [1, 2, 1, 2, 6]);
+
+        // The head-end sets the clock back to the reference time.
+        let reference = date_time_from_seconds(simulator.reference_time()).unwrap();
+        server.update_clock_time(CLOCK_LN, reference).unwrap();
+        let events = simulator.advance(&mut server, 60);
+        assert_eq!(
+            events,
+            [
+                (START + 900, MeterEvent::ClockAdjustedOld),
+                (START + 3900, MeterEvent::ClockAdjustedNew)
+            ]
+        );
+        assert!(!simulator.is_clock_invalid());
+        assert_eq!(clock_seconds(&server), START + 3960);
+        assert_eq!(attribute(&server, CLOCK_LN, 4), CosemData::Unsigned(0));
+        assert_eq!(logged(&server), [1, 2, 1, 2, 6, 4, 5]);
+    }
+
+    #[test]
+    fn test_random_outages_pair_power_down_and_up() {
+        let mut server = meter();
+        let mut simulator = MeterSimulator::new(START)
+            .with_outages(2.0, 60..7200)
+            .with_backup(3600)
+            .with_seed(7);
+        let events = simulator.advance(&mut server, 30 * 86_400);
+        let downs = events
+            .iter()
+            .filter(|(_, event)| *event == MeterEvent::PowerDown)
+            .count();
+        let ups = events
+            .iter()
+            .filter(|(_, event)| *event == MeterEvent::PowerUp)
+            .count();
+        assert!((20..=100).contains(&downs));
+        assert!(ups == downs || ups + 1 == downs);
+        assert_eq!(
+            logged(&server),
+            events
+                .iter()
+                .map(|(_, event)| event.code())
+                .collect::<Vec<_>>()
+        );
+        assert!(clock_seconds(&server) <= simulator.reference_time());
+    }
+}
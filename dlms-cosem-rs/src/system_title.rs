@@ -0,0 +1,149 @@
+use std::fmt;
+use std::string::String;
+
+const SYSTEM_TITLE_LENGTH: usize = 8;
+pub(crate) const FLAG_ID_LENGTH: usize = 3;
+// The manufacturer specific part holds a serial number of five octets.
+const MAX_SERIAL: u64 = (1 << 40) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemTitleError {
+    // The title is not 8 octets long.
+    InvalidLength(usize),
+    // The first three octets are not an upper case FLAG manufacturer ID.
+    InvalidFlagId,
+    // The serial number does not fit in five octets.
+    SerialOutOfRange(u64),
+    // The serial number is not a decimal number.
+    InvalidSerial,
+}
+
+// 8 octets: the three letter FLAG ID of the manufacturer followed by five
+// manufacturer specific octets, the serial number or the device part of the
+// EUI-64 of the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTitle([u8; SYSTEM_TITLE_LENGTH]);
+
+impl SystemTitle {
+    // The serial number is stored big endian in the five octets.
+    pub fn new(flag_id: &str, serial: u64) -> Result<Self, SystemTitleError> {
+        if serial > MAX_SERIAL {
+            return Err(SystemTitleError::SerialOutOfRange(serial));
+        }
+        Self::with_specific(flag_id, &serial.to_be_bytes()[3..])
+    }
+
+    // The five octets following the OUI of an EUI-64.
+    pub fn from_eui64(flag_id: &str, eui64: [u8; 8]) -> Result<Self, SystemTitleError> {
+        Self::with_specific(flag_id, &eui64[FLAG_ID_LENGTH..])
+    }
+
+    // Through the EUI-64 of the MAC address, FF FE inserted after the OUI.
+    pub fn from_mac_address(flag_id: &str, mac: [u8; 6]) -> Result<Self, SystemTitleError> {
+        let [a, b, c, d, e, f] = mac;
+        Self::from_eui64(flag_id, [a, b, c, 0xFF, 0xFE, d, e, f])
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SystemTitleError> {
+        let title: [u8; SYSTEM_TITLE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| SystemTitleError::InvalidLength(bytes.len()))?;
+        if !starts_with_flag_id(&title) {
+            return Err(SystemTitleError::InvalidFlagId);
+        }
+        Ok(Self(title))
+    }
+
+    fn with_specific(flag_id: &str, specific: &[u8]) -> Result<Self, SystemTitleError> {
+        if flag_id.len() != FLAG_ID_LENGTH {
+            return Err(SystemTitleError::InvalidFlagId);
+        }
+        let mut title = [0; SYSTEM_TITLE_LENGTH];
+        title[..FLAG_ID_LENGTH].copy_from_slice(flag_id.as_bytes());
+        title[FLAG_ID_LENGTH..].copy_from_slice(specific);
+        Self::from_bytes(&title)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn flag_id(&self) -> String {
+        String::from_utf8_lossy(&self.0[..FLAG_ID_LENGTH]).into_owned()
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.0[FLAG_ID_LENGTH..]
+            .iter()
+            .fold(0, |serial, &octet| serial << 8 | u64::from(octet))
+    }
+}
+
+// FLAG ID and the manufacturer specific part in hex, e.g. `ABC-00075BCD15`.
+impl fmt::Display for SystemTitle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-", self.flag_id())?;
+        self.0[FLAG_ID_LENGTH..]
+            .iter()
+            .try_for_each(|octet| write!(f, "{octet:02X}"))
+    }
+}
+
+// Whether `bytes` open with a FLAG manufacturer ID, three upper case letters,
+// as system titles and logical device names do.
+pub(crate) fn starts_with_flag_id(bytes: &[u8]) -> bool {
+    bytes
+        .get(..FLAG_ID_LENGTH)
+        .is_some_and(|flag_id| flag_id.iter().all(u8::is_ascii_uppercase))
+}
+
+// For logs of titles received from peers: valid ones as `SystemTitle`, any
+// other as plain hex.
+pub fn format_system_title(bytes: &[u8]) -> String {
+    match SystemTitle::from_bytes(bytes) {
+        Ok(title) => title.to_string(),
+        Err(_) => bytes.iter().map(|octet| format!("{octet:02X}")).collect(),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_system_title_from_serial_eui64_and_mac_address() {
+        let title = SystemTitle::new("ABC", 123_456_789).unwrap();
+        assert_eq!(title.as_bytes(), b"ABC\x00\x07\x5B\xCD\x15");
+        assert_eq!(title.flag_id(), "ABC");
+        assert_eq!(title.serial(), 123_456_789);
+        assert_eq!(title.to_string(), "ABC-00075BCD15");
+        assert_eq!(SystemTitle::from_bytes(title.as_bytes()), Ok(title));
+        assert_eq!(
+            SystemTitle::new("ABC", 1 << 40),
+            Err(SystemTitleError::SerialOutOfRange(1 << 40))
+        );
+
+        let eui64 = [0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E, 0x6F, 0x70];
+        let title = SystemTitle::from_eui64("XYZ", eui64).unwrap();
+        assert_eq!(title.as_bytes(), b"XYZ\x3C\x4D\x5E\x6F\x70");
+        let mac = [0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E];
+        let title = SystemTitle::from_mac_address("XYZ", mac).unwrap();
+        assert_eq!(title.as_bytes(), b"XYZ\xFF\xFE\x3C\x4D\x5E");
+
+        assert_eq!(
+            SystemTitle::new("Abc", 1),
+            Err(SystemTitleError::InvalidFlagId)
+        );
+        assert_eq!(
+            SystemTitle::new("ABCD", 1),
+            Err(SystemTitleError::InvalidFlagId)
+        );
+        assert_eq!(
+            SystemTitle::from_bytes(b"ABC"),
+            Err(SystemTitleError::InvalidLength(3))
+        );
+        assert_eq!(format_system_title(b"METER001"), "MET-4552303031");
+        assert_eq!(format_system_title(&[0x01, 0xFF]), "01FF");
+    }
+}
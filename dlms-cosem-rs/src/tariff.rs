@@ -0,0 +1,341 @@
+use crate::clock::{date_time_seconds, time_of_day_seconds};
+use crate::cosem::CosemObjectInstanceId;
+use crate::cosem_object::CosemObject;
+use crate::types::CosemData;
+use std::vec::Vec;
+
+// Activity calendar (class 20) switching the tariffs.
+pub const ACTIVITY_CALENDAR_LN: CosemObjectInstanceId = [0, 0, 13, 0, 0, 255];
+// Clock (class 8) of the device.
+pub const CLOCK_LN: CosemObjectInstanceId = [0, 0, 1, 0, 0, 255];
+// Currently active tariff, a data object (class 1).
+pub const CURRENT_TARIFF_LN: CosemObjectInstanceId = [0, 0, 96, 14, 0, 255];
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Objects taking part in tariffication. The day profile actions of the
+// calendar run scripts, typically writing the active mask of a register
+// activation object; the script selector is taken as the tariff rate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TariffConfiguration {
+    pub calendar: CosemObjectInstanceId,
+    pub clock: CosemObjectInstanceId,
+    pub current_tariff: Option<CosemObjectInstanceId>,
+}
+
+impl Default for TariffConfiguration {
+    fn default() -> Self {
+        Self {
+            calendar: ACTIVITY_CALENDAR_LN,
+            clock: CLOCK_LN,
+            current_tariff: Some(CURRENT_TARIFF_LN),
+        }
+    }
+}
+
+// day_profile_action ::= structure { start_time, script_logical_name, script_selector }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayProfileAction {
+    // Seconds since midnight.
+    pub start_time: u64,
+    pub script_logical_name: CosemObjectInstanceId,
+    pub script_selector: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Season {
+    // (month, day, seconds since midnight) of season_start; wildcards count as 0.
+    start: (u8, u8, u64),
+    week_name: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WeekProfile {
+    name: Vec<u8>,
+    // Day ids from Monday to Sunday.
+    day_ids: [u8; 7],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DayProfile {
+    day_id: u8,
+    // Sorted by start time.
+    actions: Vec<DayProfileAction>,
+}
+
+// The active season, week and day profiles of an activity calendar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TariffSchedule {
+    seasons: Vec<Season>,
+    weeks: Vec<WeekProfile>,
+    days: Vec<DayProfile>,
+}
+
+impl TariffSchedule {
+    pub fn from_calendar(calendar: &dyn CosemObject) -> Option<Self> {
+        if calendar.class_id() != 20 {
+            return None;
+        }
+        let entries = |attribute_id| match calendar.get_attribute(attribute_id) {
+            Some(CosemData::Array(entries)) => Some(entries),
+            _ => None,
+        };
+        Some(Self {
+            seasons: entries(3)?
+                .iter()
+                .map(parse_season)
+                .collect::<Option<_>>()?,
+            weeks: entries(4)?
+                .iter()
+                .map(parse_week_profile)
+                .collect::<Option<_>>()?,
+            days: entries(5)?
+                .iter()
+                .map(parse_day_profile)
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    // The day profile action in force at a date-time: the last one started
+    // on that day, or the last action of the day profile before the first.
+    pub fn action_at(&self, date_time: &[u8]) -> Option<DayProfileAction> {
+        let [_, _, month, day, ..] = date_time else {
+            return None;
+        };
+        let time = time_of_day_seconds(date_time)?;
+        let now = (*month, *day, time);
+        let season = self
+            .seasons
+            .iter()
+            .filter(|season| season.start <= now)
+            .max_by_key(|season| season.start)
+            .or_else(|| self.seasons.iter().max_by_key(|season| season.start))?;
+        let week = self
+            .weeks
+            .iter()
+            .find(|week| week.name == season.week_name)?;
+        let day_id = week.day_ids[day_of_week(date_time)? as usize - 1];
+        let actions = &self.days.iter().find(|day| day.day_id == day_id)?.actions;
+        actions
+            .iter()
+            .rev()
+            .find(|action| action.start_time <= time)
+            .or_else(|| actions.last())
+            .copied()
+    }
+
+    pub fn tariff_at(&self, date_time: &[u8]) -> Option<u16> {
+        self.action_at(date_time)
+            .map(|action| action.script_selector)
+    }
+}
+
+// 1 for Monday to 7 for Sunday, from the date-time or computed from the date.
+fn day_of_week(date_time: &[u8]) -> Option<u8> {
+    match date_time.get(4)? {
+        day @ 1..=7 => Some(*day),
+        _ => {
+            // 1970-01-01 was a Thursday.
+            let days = date_time_seconds(date_time)? / SECONDS_PER_DAY;
+            Some(((days + 3) % 7 + 1) as u8)
+        }
+    }
+}
+
+fn octet_string(data: &CosemData) -> Option<&[u8]> {
+    match data {
+        CosemData::OctetString(bytes)
+        | CosemData::DateTime(bytes)
+        | CosemData::Date(bytes)
+        | CosemData::Time(bytes) => Some(bytes),
+        _ => None,
+    }
+}
+
+fn wildcard_as_zero(value: u8) -> u8 {
+    if value == 0xFF {
+        0
+    } else {
+        value
+    }
+}
+
+// season ::= structure { season_profile_name, season_start, week_name }
+fn parse_season(data: &CosemData) -> Option<Season> {
+    let CosemData::Structure(fields) = data else {
+        return None;
+    };
+    let [_, start, week_name] = fields.as_slice() else {
+        return None;
+    };
+    let start = octet_string(start)?;
+    let [_, _, month, day, _, hour, minute, second, ..] = start else {
+        return None;
+    };
+    let seconds = wildcard_as_zero(*hour) as u64 * 3600
+        + wildcard_as_zero(*minute) as u64 * 60
+        + wildcard_as_zero(*second) as u64;
+    Some(Season {
+        start: (wildcard_as_zero(*month), wildcard_as_zero(*day), seconds),
+        week_name: octet_string(week_name)?.to_vec(),
+    })
+}
+
+// week_profile ::= structure { week_profile_name, monday, ..., sunday }
+fn parse_week_profile(data: &CosemData) -> Option<WeekProfile> {
+    let CosemData::Structure(fields) = data else {
+        return None;
+    };
+    let [name, days @ ..] = fields.as_slice() else {
+        return None;
+    };
+    let day_ids = days
+        .iter()
+        .map(|day| match day {
+            CosemData::Unsigned(day_id) => Some(*day_id),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(WeekProfile {
+        name: octet_string(name)?.to_vec(),
+        day_ids: day_ids.try_into().ok()?,
+    })
+}
+
+// day_profile ::= structure { day_id, day_schedule: array of day_profile_action }
+fn parse_day_profile(data: &CosemData) -> Option<DayProfile> {
+    let CosemData::Structure(fields) = data else {
+        return None;
+    };
+    let [CosemData::Unsigned(day_id), CosemData::Array(schedule)] = fields.as_slice() else {
+        return None;
+    };
+    let mut actions = schedule
+        .iter()
+        .map(|action| {
+            let CosemData::Structure(fields) = action else {
+                return None;
+            };
+            let [start_time, script, CosemData::LongUnsigned(selector)] = fields.as_slice() else {
+                return None;
+            };
+            let start_time = octet_string(start_time)?;
+            let [hour, minute, second, ..] = start_time else {
+                return None;
+            };
+            Some(DayProfileAction {
+                start_time: wildcard_as_zero(*hour) as u64 * 3600
+                    + wildcard_as_zero(*minute) as u64 * 60
+                    + wildcard_as_zero(*second) as u64,
+                script_logical_name: octet_string(script)?.try_into().ok()?,
+                script_selector: *selector,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    actions.sort_by_key(|action| action.start_time);
+    Some(DayProfile {
+        day_id: *day_id,
+        actions,
+    })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::activity_calendar::ActivityCalendar;
+
+    const SCRIPTS: CosemObjectInstanceId = [0, 0, 10, 0, 100, 255];
+
+    fn action(hour: u8, selector: u16) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::OctetString(vec![hour, 0, 0, 0]),
+            CosemData::OctetString(SCRIPTS.to_vec()),
+            CosemData::LongUnsigned(selector),
+        ])
+    }
+
+    fn season(month: u8, week: &[u8]) -> CosemData {
+        CosemData::Structure(vec![
+            CosemData::OctetString(b"S".to_vec()),
+            CosemData::OctetString(vec![0xFF, 0xFF, month, 1, 0xFF, 0, 0, 0, 0xFF, 0x80, 0, 0]),
+            CosemData::OctetString(week.to_vec()),
+        ])
+    }
+
+    fn week(name: &[u8], workday: u8, weekend: u8) -> CosemData {
+        let mut fields = vec![CosemData::OctetString(name.to_vec())];
+        fields
+            .extend((0..7).map(|day| CosemData::Unsigned(if day < 5 { workday } else { weekend })));
+        CosemData::Structure(fields)
+    }
+
+    fn calendar() -> ActivityCalendar {
+        let mut calendar = ActivityCalendar::new();
+        // Summer from April, winter from October; weekends run on tariff 1.
+        calendar
+            .set_attribute(
+                3,
+                CosemData::Array(vec![season(4, b"SUMMER"), season(10, b"WINTER")]),
+            )
+            .unwrap();
+        calendar
+            .set_attribute(
+                4,
+                CosemData::Array(vec![week(b"SUMMER", 1, 3), week(b"WINTER", 2, 3)]),
+            )
+            .unwrap();
+        calendar
+            .set_attribute(
+                5,
+                CosemData::Array(vec![
+                    CosemData::Structure(vec![
+                        CosemData::Unsigned(1),
+                        CosemData::Array(vec![action(7, 2), action(23, 1)]),
+                    ]),
+                    CosemData::Structure(vec![
+                        CosemData::Unsigned(2),
+                        CosemData::Array(vec![action(6, 2), action(22, 1)]),
+                    ]),
+                    CosemData::Structure(vec![
+                        CosemData::Unsigned(3),
+                        CosemData::Array(vec![action(0, 1)]),
+                    ]),
+                ]),
+            )
+            .unwrap();
+        calendar
+    }
+
+    fn at(month: u8, day: u8, hour: u8) -> Vec<u8> {
+        vec![0x07, 0xEA, month, day, 0xFF, hour, 30, 0, 0, 0x80, 0, 0]
+    }
+
+    #[test]
+    fn test_tariff_follows_seasons_weeks_and_days() {
+        let schedule = TariffSchedule::from_calendar(&calendar()).unwrap();
+        // 2026-10-16 is a Friday in winter.
+        assert_eq!(schedule.tariff_at(&at(10, 16, 5)), Some(1));
+        assert_eq!(schedule.tariff_at(&at(10, 16, 6)), Some(2));
+        assert_eq!(schedule.tariff_at(&at(10, 16, 22)), Some(1));
+        // 2026-07-15 is a Wednesday in summer.
+        assert_eq!(schedule.tariff_at(&at(7, 15, 6)), Some(1));
+        assert_eq!(schedule.tariff_at(&at(7, 15, 7)), Some(2));
+        // January still belongs to the winter season started last October.
+        assert_eq!(schedule.tariff_at(&at(1, 14, 12)), Some(2));
+        // 2026-10-17 is a Saturday.
+        assert_eq!(schedule.tariff_at(&at(10, 17, 12)), Some(1));
+        assert_eq!(
+            schedule
+                .action_at(&at(10, 16, 12))
+                .unwrap()
+                .script_logical_name,
+            SCRIPTS
+        );
+
+        assert_eq!(
+            TariffSchedule::from_calendar(&ActivityCalendar::new()),
+            None
+        );
+    }
+}
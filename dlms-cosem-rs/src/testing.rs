@@ -0,0 +1,848 @@
+#![cfg(feature = "std")]
+
+use crate::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
+use crate::hdlc::HdlcFrame;
+use crate::server::{Server, ServerError};
+use crate::simulator::Xorshift;
+use crate::transport::Transport;
+use crate::xdlms::{
+    ActionRequest, ActionResponse, ActionResponseNormal, ActionResponseWithOptionalData,
+    ActionResult, DataAccessResult, GetDataResult, GetRequest, GetResponse, GetResponseNormal,
+    SetRequest, SetResponse, SetResponseNormal,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::Path;
+use std::string::String;
+use std::thread;
+use std::time::Duration;
+use std::vec::Vec;
+
+// Transport of a server that is only driven through `Server::process_frame`,
+// for instance by a `LoopbackTransport`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DetachedTransport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detached;
+
+impl Transport for DetachedTransport {
+    type Error = Detached;
+
+    fn send(&mut self, _bytes: &[u8]) -> Result<(), Self::Error> {
+        Err(Detached)
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        Err(Detached)
+    }
+}
+
+#[derive(Debug)]
+pub enum LoopbackError<E> {
+    Server(ServerError<E>),
+    // The client waited for a frame the server did not answer.
+    NoResponse,
+}
+
+// Client transport handing every frame straight to an in-process server. The
+// response is queued for the next `receive`, so a whole protocol flow runs on
+// the calling thread.
+pub struct LoopbackTransport<T: Transport = DetachedTransport> {
+    server: Server<T>,
+    responses: VecDeque<Vec<u8>>,
+}
+
+impl<T: Transport> LoopbackTransport<T> {
+    pub fn new(server: Server<T>) -> Self {
+        Self {
+            server,
+            responses: VecDeque::new(),
+        }
+    }
+
+    pub fn server(&self) -> &Server<T> {
+        &self.server
+    }
+
+    pub fn server_mut(&mut self) -> &mut Server<T> {
+        &mut self.server
+    }
+
+    pub fn into_server(self) -> Server<T> {
+        self.server
+    }
+}
+
+impl<T: Transport> Transport for LoopbackTransport<T> {
+    type Error = LoopbackError<T::Error>;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        if let Some(response) = self
+            .server
+            .process_frame(bytes)
+            .map_err(LoopbackError::Server)?
+        {
+            self.responses.push_back(response);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        self.responses.pop_front().ok_or(LoopbackError::NoResponse)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+impl Direction {
+    fn reverse(self) -> Self {
+        match self {
+            Direction::ClientToServer => Direction::ServerToClient,
+            Direction::ServerToClient => Direction::ClientToServer,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFrame {
+    pub direction: Direction,
+    pub bytes: Vec<u8>,
+}
+
+// Line of a scenario file that could not be parsed, counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScenarioParseError {
+    pub line: usize,
+}
+
+// Byte exchange of a protocol flow, in the order the frames crossed the link.
+//
+// The file format is one frame per line, `>` followed by the hex bytes for a
+// frame sent by the client and `<` for a frame sent by the server. Blank lines
+// and lines starting with `#` are ignored, as is whitespace between bytes, so
+// field captures can be pasted and annotated by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scenario {
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, direction: Direction, bytes: &[u8]) {
+        self.frames.push(RecordedFrame {
+            direction,
+            bytes: bytes.to_vec(),
+        });
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ScenarioParseError> {
+        let mut scenario = Scenario::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = ScenarioParseError { line: index + 1 };
+            let (direction, hex) = match line.split_at(1) {
+                (">", hex) => (Direction::ClientToServer, hex),
+                ("<", hex) => (Direction::ServerToClient, hex),
+                _ => return Err(error),
+            };
+            let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+            if !digits.len().is_multiple_of(2) {
+                return Err(error);
+            }
+            let bytes = digits
+                .chunks(2)
+                .map(|pair| {
+                    std::str::from_utf8(pair)
+                        .ok()
+                        .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .ok_or(error)?;
+            scenario.push(direction, &bytes);
+        }
+        Ok(scenario)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Scenario::parse(&text).map_err(|error| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                std::format!("malformed scenario line {}", error.line),
+            )
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for frame in &self.frames {
+            let marker = match frame.direction {
+                Direction::ClientToServer => '>',
+                Direction::ServerToClient => '<',
+            };
+            let hex: String = frame
+                .bytes
+                .iter()
+                .map(|byte| std::format!("{:02x}", byte))
+                .collect();
+            writeln!(f, "{} {}", marker, hex)?;
+        }
+        Ok(())
+    }
+}
+
+// Records every frame passing through the wrapped transport. `client` wraps the
+// transport of a client, `server` the transport of a server.
+pub struct RecordingTransport<T: Transport> {
+    inner: T,
+    sent: Direction,
+    scenario: Scenario,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn client(inner: T) -> Self {
+        Self {
+            inner,
+            sent: Direction::ClientToServer,
+            scenario: Scenario::new(),
+        }
+    }
+
+    pub fn server(inner: T) -> Self {
+        Self {
+            inner,
+            sent: Direction::ServerToClient,
+            scenario: Scenario::new(),
+        }
+    }
+
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_parts(self) -> (T, Scenario) {
+        (self.inner, self.scenario)
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    type Error = T::Error;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(bytes)?;
+        self.scenario.push(self.sent, bytes);
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        let bytes = self.inner.receive()?;
+        self.scenario.push(self.sent.reverse(), &bytes);
+        Ok(bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    // A frame was sent that differs from the recorded one at `index`.
+    Mismatch {
+        index: usize,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+    // The side under test sent while the recording expected it to receive, or
+    // the other way round.
+    OutOfOrder {
+        index: usize,
+    },
+    // All recorded frames have been replayed.
+    Exhausted,
+}
+
+// Plays one side of a recorded scenario. `as_server` is the transport of a
+// client under test: it answers with the recorded server frames and checks
+// the frames the client sends. `as_client` does the same for a server under
+// test; `Server::run` then ends with `ReplayError::Exhausted` once the
+// scenario is through.
+//
+// Frames encrypted with HLS carry a fresh nonce and do not replay byte for
+// byte.
+pub struct ReplayTransport {
+    scenario: Scenario,
+    peer: Direction,
+    position: usize,
+}
+
+impl ReplayTransport {
+    pub fn as_server(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            peer: Direction::ServerToClient,
+            position: 0,
+        }
+    }
+
+    pub fn as_client(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            peer: Direction::ClientToServer,
+            position: 0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.scenario.frames.len()
+    }
+
+    fn next_frame(&mut self, direction: Direction) -> Result<&RecordedFrame, ReplayError> {
+        let index = self.position;
+        let frame = self
+            .scenario
+            .frames
+            .get(index)
+            .ok_or(ReplayError::Exhausted)?;
+        if frame.direction != direction {
+            return Err(ReplayError::OutOfOrder { index });
+        }
+        self.position += 1;
+        Ok(frame)
+    }
+}
+
+impl Transport for ReplayTransport {
+    type Error = ReplayError;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let index = self.position;
+        let frame = self.next_frame(self.peer.reverse())?;
+        if frame.bytes != bytes {
+            return Err(ReplayError::Mismatch {
+                index,
+                expected: frame.bytes.clone(),
+                actual: bytes.to_vec(),
+            });
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.next_frame(self.peer)?.bytes.clone())
+    }
+}
+
+// What a request asks for, ignoring invoke ids, values and parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestKey {
+    Associate,
+    Release,
+    Get {
+        attributes: Vec<CosemAttributeDescriptor>,
+        block_number: Option<u32>,
+    },
+    Set {
+        attributes: Vec<CosemAttributeDescriptor>,
+        block_number: Option<u32>,
+    },
+    Action {
+        methods: Vec<CosemMethodDescriptor>,
+        block_number: Option<u32>,
+    },
+    // Any other APDU is matched byte for byte.
+    Other(Vec<u8>),
+}
+
+impl RequestKey {
+    pub fn from_apdu(apdu: &[u8]) -> Self {
+        let key = match apdu.first() {
+            Some(0x60) => Some(RequestKey::Associate),
+            Some(0x62) => Some(RequestKey::Release),
+            Some(0xC0) => GetRequest::from_bytes(apdu)
+                .ok()
+                .map(|request| match request {
+                    GetRequest::Normal(request) => RequestKey::Get {
+                        attributes: vec![request.cosem_attribute_descriptor],
+                        block_number: None,
+                    },
+                    GetRequest::Next(request) => RequestKey::Get {
+                        attributes: Vec::new(),
+                        block_number: Some(request.block_number),
+                    },
+                    GetRequest::WithList(request) => RequestKey::Get {
+                        attributes: request.attribute_descriptor_list,
+                        block_number: None,
+                    },
+                }),
+            Some(0xC1) => SetRequest::from_bytes(apdu)
+                .ok()
+                .map(|request| match request {
+                    SetRequest::Normal(request) => RequestKey::Set {
+                        attributes: vec![request.cosem_attribute_descriptor],
+                        block_number: None,
+                    },
+                    SetRequest::WithFirstDatablock(request) => RequestKey::Set {
+                        attributes: vec![request.cosem_attribute_descriptor],
+                        block_number: Some(request.datablock.block_number),
+                    },
+                    SetRequest::WithDatablock(request) => RequestKey::Set {
+                        attributes: Vec::new(),
+                        block_number: Some(request.datablock.block_number),
+                    },
+                    SetRequest::WithList(request) => RequestKey::Set {
+                        attributes: request.attribute_descriptor_list,
+                        block_number: None,
+                    },
+                }),
+            Some(0xC3) => ActionRequest::from_bytes(apdu)
+                .ok()
+                .map(|request| match request {
+                    ActionRequest::Normal(request) => RequestKey::Action {
+                        methods: vec![request.cosem_method_descriptor],
+                        block_number: None,
+                    },
+                    ActionRequest::WithFirstPblock(request) => RequestKey::Action {
+                        methods: vec![request.cosem_method_descriptor],
+                        block_number: Some(request.pblock.block_number),
+                    },
+                    ActionRequest::WithPblock(request) => RequestKey::Action {
+                        methods: Vec::new(),
+                        block_number: Some(request.pblock.block_number),
+                    },
+                    ActionRequest::WithList(request) => RequestKey::Action {
+                        methods: request.cosem_method_descriptor_list,
+                        block_number: None,
+                    },
+                }),
+            _ => None,
+        };
+        key.unwrap_or_else(|| RequestKey::Other(apdu.to_vec()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockMeterError {
+    // The recorded session holds no answer to the request.
+    Unrecorded(RequestKey),
+    // The frame sent is not an HDLC frame.
+    InvalidFrame,
+    // The client waited for a frame the meter did not answer.
+    NoResponse,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedExchange {
+    key: RequestKey,
+    responses: Vec<HdlcFrame>,
+    served: bool,
+}
+
+// Client transport answering from a recorded session with a real meter. Each
+// request is matched on its type and descriptors against the recorded client
+// frames and answered with the server frames that followed it, carrying the
+// invoke id of the new request. Recorded exchanges are served in order; once
+// all matching ones are used the last is repeated, as a meter answers the same
+// read again. Sessions secured with HLS cannot be replayed.
+pub struct MockMeter {
+    exchanges: Vec<RecordedExchange>,
+    responses: VecDeque<Vec<u8>>,
+}
+
+impl MockMeter {
+    pub fn new(session: &Scenario) -> Self {
+        let mut exchanges: Vec<RecordedExchange> = Vec::new();
+        for frame in &session.frames {
+            let Ok(hdlc) = HdlcFrame::from_bytes(&frame.bytes) else {
+                continue;
+            };
+            match frame.direction {
+                Direction::ClientToServer => exchanges.push(RecordedExchange {
+                    key: RequestKey::from_apdu(&hdlc.information),
+                    responses: Vec::new(),
+                    served: false,
+                }),
+                Direction::ServerToClient => {
+                    if let Some(exchange) = exchanges.last_mut() {
+                        exchange.responses.push(hdlc);
+                    }
+                }
+            }
+        }
+        Self {
+            exchanges,
+            responses: VecDeque::new(),
+        }
+    }
+
+    pub fn answer(&mut self, request: &HdlcFrame) -> Result<Vec<HdlcFrame>, MockMeterError> {
+        let key = RequestKey::from_apdu(&request.information);
+        let index = self
+            .exchanges
+            .iter()
+            .position(|exchange| !exchange.served && exchange.key == key)
+            .or_else(|| {
+                self.exchanges
+                    .iter()
+                    .rposition(|exchange| exchange.key == key)
+            })
+            .ok_or(MockMeterError::Unrecorded(key))?;
+        let exchange = &mut self.exchanges[index];
+        exchange.served = true;
+        let invoke_id = match request.information.as_slice() {
+            [0xC0 | 0xC1 | 0xC3, _, invoke_id, ..] => Some(*invoke_id),
+            _ => None,
+        };
+        Ok(exchange
+            .responses
+            .iter()
+            .cloned()
+            .map(|mut response| {
+                if let (Some(invoke_id), [0xC4 | 0xC5 | 0xC7, _, recorded, ..]) =
+                    (invoke_id, response.information.as_mut_slice())
+                {
+                    *recorded = invoke_id;
+                }
+                response
+            })
+            .collect())
+    }
+}
+
+impl Transport for MockMeter {
+    type Error = MockMeterError;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let request = HdlcFrame::from_bytes(bytes).map_err(|_| MockMeterError::InvalidFrame)?;
+        for response in self.answer(&request)? {
+            let bytes = response
+                .to_bytes()
+                .map_err(|_| MockMeterError::InvalidFrame)?;
+            self.responses.push_back(bytes);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        self.responses.pop_front().ok_or(MockMeterError::NoResponse)
+    }
+}
+
+// Misbehaviour of a meter, injected into the frames it sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    // Flips a bit of the HDLC frame check sequence.
+    CorruptCrc,
+    // Delivers the frame only after the given time.
+    Delay(Duration),
+    // Loses the frame.
+    Drop,
+    // Answers a get, set or action request with temporary-failure.
+    TemporaryFailure,
+    // Answers with an invoke id other than the one of the request.
+    WrongInvokeId,
+}
+
+// Injects `fault` into the frames answering requests that match `on`, each
+// with the given probability and at most `times` times. Rules without a
+// request apply to every frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultRule {
+    fault: Fault,
+    probability: f64,
+    request: Option<RequestKey>,
+    limit: Option<usize>,
+    applied: usize,
+}
+
+impl FaultRule {
+    pub fn new(fault: Fault) -> Self {
+        Self {
+            fault,
+            probability: 1.0,
+            request: None,
+            limit: None,
+            applied: 0,
+        }
+    }
+
+    pub fn with_probability(mut self, probability: f64) -> Self {
+        self.probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn on(mut self, request: RequestKey) -> Self {
+        self.request = Some(request);
+        self
+    }
+
+    pub fn times(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn fault(&self) -> Fault {
+        self.fault
+    }
+
+    // How often the fault has been injected so far.
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+
+    fn matches(&self, request: Option<&RequestKey>) -> bool {
+        self.limit.is_none_or(|limit| self.applied < limit)
+            && self
+                .request
+                .as_ref()
+                .is_none_or(|expected| request == Some(expected))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultError<E> {
+    Inner(E),
+    // The frame was dropped; on a real link the receiver would time out.
+    Dropped,
+}
+
+// Makes the meter behind the wrapped transport misbehave, to validate the
+// retry and timeout handling of clients. `client` wraps the transport of a
+// client, e.g. a `LoopbackTransport`, and tampers with the frames received;
+// `server` wraps the transport of a server and tampers with the frames sent.
+// The first matching rule whose draw succeeds is applied, one fault per frame.
+// `set_seed` makes the draws reproducible.
+pub struct FaultyTransport<T: Transport> {
+    inner: T,
+    sent: Direction,
+    rules: Vec<FaultRule>,
+    request: Option<RequestKey>,
+    rng: Xorshift,
+}
+
+impl<T: Transport> FaultyTransport<T> {
+    pub fn client(inner: T) -> Self {
+        Self::new(inner, Direction::ClientToServer)
+    }
+
+    pub fn server(inner: T) -> Self {
+        Self::new(inner, Direction::ServerToClient)
+    }
+
+    fn new(inner: T, sent: Direction) -> Self {
+        Self {
+            inner,
+            sent,
+            rules: Vec::new(),
+            request: None,
+            rng: Xorshift::from_entropy(),
+        }
+    }
+
+    pub fn add_rule(&mut self, rule: FaultRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &[FaultRule] {
+        &self.rules
+    }
+
+    pub fn clear_rules(&mut self) {
+        self.rules.clear();
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Xorshift::new(seed);
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn note_request(&mut self, bytes: &[u8]) {
+        if let Ok(frame) = HdlcFrame::from_bytes(bytes) {
+            self.request = Some(RequestKey::from_apdu(&frame.information));
+        }
+    }
+
+    // The frame to deliver to the client in place of `bytes`, if any.
+    fn tamper(&mut self, bytes: Vec<u8>) -> Option<Vec<u8>> {
+        for index in 0..self.rules.len() {
+            if !self.rules[index].matches(self.request.as_ref()) {
+                continue;
+            }
+            if self.rng.draw() >= self.rules[index].probability {
+                continue;
+            }
+            let Some(tampered) = apply_fault(self.rules[index].fault, &bytes) else {
+                continue;
+            };
+            self.rules[index].applied += 1;
+            return tampered;
+        }
+        Some(bytes)
+    }
+}
+
+// `None` if the fault does not apply to the frame, `Some(None)` if the frame
+// is lost.
+fn apply_fault(fault: Fault, bytes: &[u8]) -> Option<Option<Vec<u8>>> {
+    match fault {
+        Fault::CorruptCrc => {
+            let mut corrupted = bytes.to_vec();
+            // The FCS precedes the closing flag.
+            let index = corrupted.len().checked_sub(2)?;
+            corrupted[index] ^= 0x01;
+            Some(Some(corrupted))
+        }
+        Fault::Delay(delay) => {
+            thread::sleep(delay);
+            Some(Some(bytes.to_vec()))
+        }
+        Fault::Drop => Some(None),
+        Fault::TemporaryFailure => {
+            let mut frame = HdlcFrame::from_bytes(bytes).ok()?;
+            frame.information = temporary_failure(&frame.information)?;
+            Some(Some(frame.to_bytes().ok()?))
+        }
+        Fault::WrongInvokeId => {
+            let mut frame = HdlcFrame::from_bytes(bytes).ok()?;
+            let [0xC4 | 0xC5 | 0xC7, _, invoke_id, ..] = frame.information.as_mut_slice() else {
+                return None;
+            };
+            *invoke_id = (*invoke_id & 0xF0) | (invoke_id.wrapping_add(1) & 0x0F);
+            Some(Some(frame.to_bytes().ok()?))
+        }
+    }
+}
+
+fn temporary_failure(apdu: &[u8]) -> Option<Vec<u8>> {
+    let invoke_id_and_priority = *apdu.get(2)?;
+    match apdu.first()? {
+        0xC4 => GetResponse::Normal(GetResponseNormal {
+            invoke_id_and_priority,
+            result: GetDataResult::DataAccessResult(DataAccessResult::TemporaryFailure),
+        })
+        .to_bytes()
+        .ok(),
+        0xC5 => SetResponse::Normal(SetResponseNormal {
+            invoke_id_and_priority,
+            result: DataAccessResult::TemporaryFailure,
+        })
+        .to_bytes()
+        .ok(),
+        0xC7 => ActionResponse::Normal(ActionResponseNormal {
+            invoke_id_and_priority,
+            single_response: ActionResponseWithOptionalData {
+                result: ActionResult::TemporaryFailure,
+                return_parameters: None,
+            },
+        })
+        .to_bytes()
+        .ok(),
+        _ => None,
+    }
+}
+
+impl<T: Transport> Transport for FaultyTransport<T> {
+    type Error = FaultError<T::Error>;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        if self.sent == Direction::ClientToServer {
+            self.note_request(bytes);
+            return self.inner.send(bytes).map_err(FaultError::Inner);
+        }
+        match self.tamper(bytes.to_vec()) {
+            Some(bytes) => self.inner.send(&bytes).map_err(FaultError::Inner),
+            None => Ok(()),
+        }
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        let bytes = self.inner.receive().map_err(FaultError::Inner)?;
+        if self.sent == Direction::ServerToClient {
+            self.note_request(&bytes);
+            return Ok(bytes);
+        }
+        self.tamper(bytes).ok_or(FaultError::Dropped)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn test_scenario_text_round_trip() {
+        let text = "# association\n> 7e a0 01\n\n< 7E0203\n";
+        let scenario = Scenario::parse(text).unwrap();
+        assert_eq!(
+            scenario.frames,
+            vec![
+                RecordedFrame {
+                    direction: Direction::ClientToServer,
+                    bytes: vec![0x7E, 0xA0, 0x01],
+                },
+                RecordedFrame {
+                    direction: Direction::ServerToClient,
+                    bytes: vec![0x7E, 0x02, 0x03],
+                },
+            ]
+        );
+        assert_eq!(scenario.to_string(), "> 7ea001\n< 7e0203\n");
+        assert_eq!(Scenario::parse(&scenario.to_string()).unwrap(), scenario);
+        assert_eq!(
+            Scenario::parse("> 7e\n? 00\n"),
+            Err(ScenarioParseError { line: 2 })
+        );
+        assert_eq!(
+            Scenario::parse("> 7e0\n"),
+            Err(ScenarioParseError { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_replay_checks_sent_frames() {
+        let scenario = Scenario::parse("> 01\n< 02\n").unwrap();
+        let mut transport = ReplayTransport::as_server(scenario.clone());
+        assert_eq!(
+            transport.receive(),
+            Err(ReplayError::OutOfOrder { index: 0 })
+        );
+        assert_eq!(
+            transport.send(&[0x03]),
+            Err(ReplayError::Mismatch {
+                index: 0,
+                expected: vec![0x01],
+                actual: vec![0x03],
+            })
+        );
+
+        let mut transport = ReplayTransport::as_server(scenario);
+        transport.send(&[0x01]).unwrap();
+        assert_eq!(transport.receive(), Ok(vec![0x02]));
+        assert!(transport.is_finished());
+        assert_eq!(transport.receive(), Err(ReplayError::Exhausted));
+    }
+}
@@ -0,0 +1,170 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::boxed::Box;
+use std::fmt;
+use std::string::{String, ToString};
+use std::time::{Duration, Instant};
+
+static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
+// Ties together the trace records and timings of one request, from the
+// frames on the link to the APDUs they carry. Unique within the process, so
+// records of many clients polling at once can be told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CorrelationId(pub u64);
+
+impl CorrelationId {
+    pub fn next() -> Self {
+        Self(NEXT_CORRELATION_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraceLayer {
+    Hdlc,
+    Acse,
+    Xdlms,
+}
+
+impl TraceLayer {
+    // The layer of an APDU by its tag.
+    pub fn of_apdu(apdu: &[u8]) -> Self {
+        match apdu.first() {
+            Some(0x60..=0x63) => TraceLayer::Acse,
+            _ => TraceLayer::Xdlms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRecord {
+    pub correlation_id: CorrelationId,
+    pub layer: TraceLayer,
+    pub message: String,
+}
+
+impl fmt::Display for TraceRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {}",
+            self.correlation_id, self.layer, self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TracePhase {
+    // Building the APDUs and frames sent.
+    Encode,
+    // Waiting for the link: sending, and on a client receiving the response.
+    Transport,
+    // Parsing the frames and APDUs received.
+    Decode,
+    // Serving the request and building the response APDU, on a server.
+    Process,
+}
+
+// Time spent per phase by one request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhaseTimings {
+    pub encode: Duration,
+    pub transport: Duration,
+    pub decode: Duration,
+    pub process: Duration,
+}
+
+impl PhaseTimings {
+    pub fn total(&self) -> Duration {
+        self.encode + self.transport + self.decode + self.process
+    }
+
+    fn phase_mut(&mut self, phase: TracePhase) -> &mut Duration {
+        match phase {
+            TracePhase::Encode => &mut self.encode,
+            TracePhase::Transport => &mut self.transport,
+            TracePhase::Decode => &mut self.decode,
+            TracePhase::Process => &mut self.process,
+        }
+    }
+}
+
+pub(crate) type TraceCallback = Box<dyn FnMut(&TraceRecord) + Send>;
+
+// Correlation id, timings and trace output of the request being handled.
+// Time between two marks counts towards the phase of the later one.
+#[derive(Default)]
+pub(crate) struct Tracer {
+    callback: Option<TraceCallback>,
+    correlation_id: CorrelationId,
+    timings: PhaseTimings,
+    marked_at: Option<Instant>,
+}
+
+impl Tracer {
+    pub(crate) fn set_callback(&mut self, callback: Option<TraceCallback>) {
+        self.callback = callback;
+    }
+
+    pub(crate) fn begin(&mut self) -> CorrelationId {
+        self.correlation_id = CorrelationId::next();
+        self.timings = PhaseTimings::default();
+        self.marked_at = Some(Instant::now());
+        self.correlation_id
+    }
+
+    pub(crate) fn mark(&mut self, phase: TracePhase) {
+        let now = Instant::now();
+        if let Some(marked_at) = self.marked_at.replace(now) {
+            *self.timings.phase_mut(phase) += now - marked_at;
+        }
+    }
+
+    // The message is only formatted while a callback is set.
+    pub(crate) fn record(&mut self, layer: TraceLayer, message: fmt::Arguments<'_>) {
+        if let Some(callback) = self.callback.as_mut() {
+            callback(&TraceRecord {
+                correlation_id: self.correlation_id,
+                layer,
+                message: message.to_string(),
+            });
+        }
+    }
+
+    pub(crate) fn correlation_id(&self) -> CorrelationId {
+        self.correlation_id
+    }
+
+    pub(crate) fn timings(&self) -> PhaseTimings {
+        self.timings
+    }
+}
+
+// Name of an APDU in trace records.
+pub fn apdu_name(apdu: &[u8]) -> &'static str {
+    match apdu.first() {
+        Some(0x01) => "initiate-request",
+        Some(0x08) => "initiate-response",
+        Some(0x0E) => "confirmed-service-error",
+        Some(0x0F) => "data-notification",
+        Some(0x60) => "aarq",
+        Some(0x61) => "aare",
+        Some(0x62) => "rlrq",
+        Some(0x63) => "rlre",
+        Some(0xC0) => "get-request",
+        Some(0xC1) => "set-request",
+        Some(0xC2) => "event-notification-request",
+        Some(0xC3) => "action-request",
+        Some(0xC4) => "get-response",
+        Some(0xC5) => "set-response",
+        Some(0xC7) => "action-response",
+        Some(0xD8) => "exception-response",
+        Some(_) => "apdu",
+        None => "empty",
+    }
+}
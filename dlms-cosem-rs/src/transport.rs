@@ -1,8 +1,48 @@
+use std::net::IpAddr;
 use std::vec::Vec;
 
+// An established link to a peer: a serial line, a socket, a loopback.
 pub trait Transport {
     type Error;
 
     fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
     fn receive(&mut self) -> Result<Vec<u8>, Self::Error>;
 }
+
+// The link a listener hands out for one peer. Any transport is one, so the
+// transports of this crate serve as connections as they are; a listener only
+// needs its connections to send and receive, whatever their medium.
+pub trait Connection {
+    type Error;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error>;
+}
+
+impl<T: Transport> Connection for T {
+    type Error = T::Error;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        Transport::send(self, bytes)
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        Transport::receive(self)
+    }
+}
+
+// Hands out a connection per peer, e.g. a TCP socket accepting clients.
+// `Server::serve` and `ServerListener` serve the connections of a listener at
+// the same time, each with associations of its own.
+pub trait Listener {
+    type Connection: Connection;
+    type Error;
+
+    // Waits for the next peer.
+    fn accept(&mut self) -> Result<Self::Connection, Self::Error>;
+
+    // `accept` with the address of the peer, when the medium has one.
+    fn accept_from(&mut self) -> Result<(Self::Connection, Option<IpAddr>), Self::Error> {
+        self.accept().map(|connection| (connection, None))
+    }
+}
@@ -1,3 +1,4 @@
+use core::fmt;
 use std::vec::Vec;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -6,7 +7,7 @@ pub enum CosemData {
     Array(Vec<CosemData>),
     Structure(Vec<CosemData>),
     Boolean(bool),
-    BitString(Vec<u8>),
+    BitString(BitString),
     DoubleLong(i32),
     DoubleLongUnsigned(u32),
     OctetString(Vec<u8>),
@@ -28,6 +29,442 @@ pub enum CosemData {
     DontCare,
 }
 
+// A bit-string: `len` bits, the first in the most significant bit of the
+// first byte. Bits past `len` in the last byte are kept zero.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitString {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+impl BitString {
+    // `None` when `bytes` does not hold exactly the octets `len` bits need.
+    pub fn new(len: usize, mut bytes: Vec<u8>) -> Option<Self> {
+        if bytes.len() != len.div_ceil(8) {
+            return None;
+        }
+        if let Some(last) = bytes.last_mut() {
+            *last &= padding_mask(len);
+        }
+        Some(Self { len, bytes })
+    }
+
+    // All the bits of `bytes`.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            len: bytes.len() * 8,
+            bytes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    // `None` past the end.
+    pub fn bit(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+    }
+
+    // `None` past the end.
+    pub fn set_bit(&mut self, index: usize, value: bool) -> Option<()> {
+        if index >= self.len {
+            return None;
+        }
+        let mask = 0x80 >> (index % 8);
+        if value {
+            self.bytes[index / 8] |= mask;
+        } else {
+            self.bytes[index / 8] &= !mask;
+        }
+        Some(())
+    }
+
+    pub fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+    }
+}
+
+// The bits of the last octet of a bit-string of `len` bits that are in use.
+pub(crate) fn padding_mask(len: usize) -> u8 {
+    match len % 8 {
+        0 => 0xFF,
+        used => 0xFF << (8 - used),
+    }
+}
+
+// A status byte as a bit-string of eight bits, bit 0 of the status being the
+// last one.
+pub(crate) fn status_bit_string(bits: u8) -> CosemData {
+    CosemData::BitString(BitString::from_bytes(vec![bits]))
+}
+
+// Status bytes travel as unsigned or as bit-strings of eight bits, whichever
+// their attribute is defined as.
+pub(crate) fn status_bits(data: &CosemData) -> Option<u8> {
+    match data {
+        CosemData::Unsigned(bits) => Some(*bits),
+        CosemData::BitString(bits) if bits.len() == 8 => Some(bits.as_bytes()[0]),
+        _ => None,
+    }
+}
+
+// Declares a status byte of named flags, each with its getter. The byte is
+// written with `$encode` and read back from either form `status_bits` takes.
+macro_rules! status_flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident encoded by $encode:path {
+            $(const $flag:ident = $bit:literal, $getter:ident;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct $name(u8);
+
+        impl $name {
+            $(pub const $flag: u8 = $bit;)*
+
+            pub fn from_bits(bits: u8) -> Self {
+                Self(bits)
+            }
+
+            pub fn bits(self) -> u8 {
+                self.0
+            }
+
+            pub fn contains(self, flag: u8) -> bool {
+                self.0 & flag == flag
+            }
+
+            pub fn with(self, flag: u8, set: bool) -> Self {
+                Self(if set { self.0 | flag } else { self.0 & !flag })
+            }
+
+            $(
+                pub fn $getter(self) -> bool {
+                    self.contains(Self::$flag)
+                }
+            )*
+
+            pub fn to_cosem_data(self) -> $crate::types::CosemData {
+                $encode(self.0)
+            }
+
+            pub fn from_cosem_data(data: &$crate::types::CosemData) -> Option<Self> {
+                $crate::types::status_bits(data).map(Self)
+            }
+        }
+    };
+}
+
+pub(crate) use status_flags;
+
+// The type of a CosemData value, without the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    NullData,
+    Array,
+    Structure,
+    Boolean,
+    BitString,
+    DoubleLong,
+    DoubleLongUnsigned,
+    OctetString,
+    VisibleString,
+    Utf8String,
+    Bcd,
+    Integer,
+    Long,
+    Unsigned,
+    LongUnsigned,
+    Long64,
+    Long64Unsigned,
+    Enum,
+    Float32,
+    Float64,
+    DateTime,
+    Date,
+    Time,
+    DontCare,
+}
+
+impl CosemData {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            CosemData::NullData => DataType::NullData,
+            CosemData::Array(_) => DataType::Array,
+            CosemData::Structure(_) => DataType::Structure,
+            CosemData::Boolean(_) => DataType::Boolean,
+            CosemData::BitString(_) => DataType::BitString,
+            CosemData::DoubleLong(_) => DataType::DoubleLong,
+            CosemData::DoubleLongUnsigned(_) => DataType::DoubleLongUnsigned,
+            CosemData::OctetString(_) => DataType::OctetString,
+            CosemData::VisibleString(_) => DataType::VisibleString,
+            CosemData::Utf8String(_) => DataType::Utf8String,
+            CosemData::Bcd(_) => DataType::Bcd,
+            CosemData::Integer(_) => DataType::Integer,
+            CosemData::Long(_) => DataType::Long,
+            CosemData::Unsigned(_) => DataType::Unsigned,
+            CosemData::LongUnsigned(_) => DataType::LongUnsigned,
+            CosemData::Long64(_) => DataType::Long64,
+            CosemData::Long64Unsigned(_) => DataType::Long64Unsigned,
+            CosemData::Enum(_) => DataType::Enum,
+            CosemData::Float32(_) => DataType::Float32,
+            CosemData::Float64(_) => DataType::Float64,
+            CosemData::DateTime(_) => DataType::DateTime,
+            CosemData::Date(_) => DataType::Date,
+            CosemData::Time(_) => DataType::Time,
+            CosemData::DontCare => DataType::DontCare,
+        }
+    }
+
+    // Number of elements of arrays and structures, of bits of bit-strings and
+    // of octets of other strings; `None` for other data.
+    pub fn length(&self) -> Option<usize> {
+        match self {
+            CosemData::Array(elements) | CosemData::Structure(elements) => Some(elements.len()),
+            CosemData::BitString(bits) => Some(bits.len()),
+            CosemData::OctetString(bytes)
+            | CosemData::DateTime(bytes)
+            | CosemData::Date(bytes)
+            | CosemData::Time(bytes) => Some(bytes.len()),
+            CosemData::VisibleString(text) | CosemData::Utf8String(text) => Some(text.len()),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            CosemData::Integer(value) => Some(*value as i64),
+            CosemData::Long(value) => Some(*value as i64),
+            CosemData::DoubleLong(value) => Some(*value as i64),
+            CosemData::Long64(value) => Some(*value),
+            CosemData::Unsigned(value) => Some(*value as i64),
+            CosemData::LongUnsigned(value) => Some(*value as i64),
+            CosemData::DoubleLongUnsigned(value) => Some(*value as i64),
+            CosemData::Long64Unsigned(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            CosemData::Unsigned(value) => Some(*value as u64),
+            CosemData::LongUnsigned(value) => Some(*value as u64),
+            CosemData::DoubleLongUnsigned(value) => Some(*value as u64),
+            CosemData::Long64Unsigned(value) => Some(*value),
+            _ => self.as_i64().and_then(|value| u64::try_from(value).ok()),
+        }
+    }
+
+    // Integers and floats as a float; 64-bit integers may lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CosemData::Float32(value) => Some(*value as f64),
+            CosemData::Float64(value) => Some(*value),
+            CosemData::Long64Unsigned(value) => Some(*value as f64),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, CosemData::Float32(_) | CosemData::Float64(_))
+    }
+
+    // Zero of the same integer or float type, falling back to unsigned(0) for
+    // other data.
+    pub fn zero_like(&self) -> CosemData {
+        match self {
+            CosemData::Float32(_) => CosemData::Float32(0.0),
+            CosemData::Float64(_) => CosemData::Float64(0.0),
+            CosemData::Integer(_) => CosemData::Integer(0),
+            CosemData::Long(_) => CosemData::Long(0),
+            CosemData::DoubleLong(_) => CosemData::DoubleLong(0),
+            CosemData::Long64(_) => CosemData::Long64(0),
+            CosemData::LongUnsigned(_) => CosemData::LongUnsigned(0),
+            CosemData::DoubleLongUnsigned(_) => CosemData::DoubleLongUnsigned(0),
+            CosemData::Long64Unsigned(_) => CosemData::Long64Unsigned(0),
+            _ => CosemData::Unsigned(0),
+        }
+    }
+
+    // Adds `delta` while keeping the integer type of the value; `None` when the
+    // value is not an integer or the result does not fit the type.
+    pub fn checked_add(&self, delta: i64) -> Option<CosemData> {
+        self.checked_add_wide(delta as i128)
+    }
+
+    // `checked_add` for increments beyond `i64::MAX`, as Long64Unsigned
+    // counters take.
+    pub fn checked_add_u64(&self, delta: u64) -> Option<CosemData> {
+        self.checked_add_wide(delta as i128)
+    }
+
+    fn checked_add_wide(&self, delta: i128) -> Option<CosemData> {
+        let sum = |value: i128| value.checked_add(delta);
+        let data = match self {
+            CosemData::Integer(value) => CosemData::Integer(sum(*value as i128)?.try_into().ok()?),
+            CosemData::Long(value) => CosemData::Long(sum(*value as i128)?.try_into().ok()?),
+            CosemData::DoubleLong(value) => {
+                CosemData::DoubleLong(sum(*value as i128)?.try_into().ok()?)
+            }
+            CosemData::Long64(value) => CosemData::Long64(sum(*value as i128)?.try_into().ok()?),
+            CosemData::Unsigned(value) => {
+                CosemData::Unsigned(sum(*value as i128)?.try_into().ok()?)
+            }
+            CosemData::LongUnsigned(value) => {
+                CosemData::LongUnsigned(sum(*value as i128)?.try_into().ok()?)
+            }
+            CosemData::DoubleLongUnsigned(value) => {
+                CosemData::DoubleLongUnsigned(sum(*value as i128)?.try_into().ok()?)
+            }
+            CosemData::Long64Unsigned(value) => {
+                CosemData::Long64Unsigned(sum(*value as i128)?.try_into().ok()?)
+            }
+            _ => return None,
+        };
+        Some(data)
+    }
+}
+
+// Readable rendering for logs and tools: arrays in brackets, structures in
+// braces, strings quoted and octet strings in hex, except that those shaped like
+// a logical name or a date-time are shown as such. Not meant to be parsed back.
+impl fmt::Display for CosemData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CosemData::NullData => f.write_str("null"),
+            CosemData::DontCare => f.write_str("dont-care"),
+            CosemData::Array(elements) => write_elements(f, "[", elements, "]"),
+            CosemData::Structure(elements) => write_elements(f, "{", elements, "}"),
+            CosemData::Boolean(value) => write!(f, "{value}"),
+            CosemData::BitString(bits) => {
+                f.write_str("0b")?;
+                bits.bits()
+                    .try_for_each(|bit| f.write_str(if bit { "1" } else { "0" }))
+            }
+            CosemData::DoubleLong(value) => write!(f, "{value}"),
+            CosemData::DoubleLongUnsigned(value) => write!(f, "{value}"),
+            CosemData::OctetString(bytes) => {
+                if let Ok(logical_name) = <[u8; 6]>::try_from(bytes.as_slice()) {
+                    let [a, b, c, d, e, g] = logical_name;
+                    return write!(f, "{a}.{b}.{c}.{d}.{e}.{g}");
+                }
+                if bytes.len() == 12 && is_date(&bytes[..5]) && is_time(&bytes[5..9]) {
+                    return write_date_time(f, bytes);
+                }
+                write_hex(f, bytes)
+            }
+            CosemData::VisibleString(text) | CosemData::Utf8String(text) => write!(f, "{text:?}"),
+            CosemData::Bcd(value) => write!(f, "{value}"),
+            CosemData::Integer(value) => write!(f, "{value}"),
+            CosemData::Long(value) => write!(f, "{value}"),
+            CosemData::Unsigned(value) => write!(f, "{value}"),
+            CosemData::LongUnsigned(value) => write!(f, "{value}"),
+            CosemData::Long64(value) => write!(f, "{value}"),
+            CosemData::Long64Unsigned(value) => write!(f, "{value}"),
+            CosemData::Enum(value) => write!(f, "enum({value})"),
+            CosemData::Float32(value) => write!(f, "{value}"),
+            CosemData::Float64(value) => write!(f, "{value}"),
+            CosemData::DateTime(bytes) if bytes.len() == 12 => write_date_time(f, bytes),
+            CosemData::Date(bytes) if bytes.len() == 5 => write_date(f, bytes),
+            CosemData::Time(bytes) if bytes.len() == 4 => write_time(f, bytes),
+            CosemData::DateTime(bytes) | CosemData::Date(bytes) | CosemData::Time(bytes) => {
+                write_hex(f, bytes)
+            }
+        }
+    }
+}
+
+fn write_elements(
+    f: &mut fmt::Formatter<'_>,
+    open: &str,
+    elements: &[CosemData],
+    close: &str,
+) -> fmt::Result {
+    f.write_str(open)?;
+    for (index, element) in elements.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{element}")?;
+    }
+    f.write_str(close)
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    f.write_str("0x")?;
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02X}"))
+}
+
+// Month and day also take the special values of the Blue Book (DST begin and
+// end, last and second last day), shown as numbers.
+fn is_date(date: &[u8]) -> bool {
+    let [_, _, month, day, _] = date else {
+        return false;
+    };
+    matches!(month, 1..=12 | 0xFD..=0xFF) && matches!(day, 1..=31 | 0xFD..=0xFF)
+}
+
+fn is_time(time: &[u8]) -> bool {
+    let [hour, minute, second, hundredths] = time else {
+        return false;
+    };
+    matches!(hour, 0..=23 | 0xFF)
+        && matches!(minute, 0..=59 | 0xFF)
+        && matches!(second, 0..=59 | 0xFF)
+        && matches!(hundredths, 0..=99 | 0xFF)
+}
+
+// A field not specified (0xFF, 0xFFFF for the year) is shown as `*`.
+fn write_field(f: &mut fmt::Formatter<'_>, value: u16, wildcard: u16, width: usize) -> fmt::Result {
+    if value == wildcard {
+        f.write_str("*")
+    } else {
+        write!(f, "{value:0width$}")
+    }
+}
+
+fn write_date(f: &mut fmt::Formatter<'_>, date: &[u8]) -> fmt::Result {
+    write_field(f, u16::from_be_bytes([date[0], date[1]]), 0xFFFF, 4)?;
+    f.write_str("-")?;
+    write_field(f, date[2] as u16, 0xFF, 2)?;
+    f.write_str("-")?;
+    write_field(f, date[3] as u16, 0xFF, 2)
+}
+
+fn write_time(f: &mut fmt::Formatter<'_>, time: &[u8]) -> fmt::Result {
+    write_field(f, time[0] as u16, 0xFF, 2)?;
+    f.write_str(":")?;
+    write_field(f, time[1] as u16, 0xFF, 2)?;
+    f.write_str(":")?;
+    write_field(f, time[2] as u16, 0xFF, 2)
+}
+
+// Date and time, then the deviation in minutes when specified.
+fn write_date_time(f: &mut fmt::Formatter<'_>, date_time: &[u8]) -> fmt::Result {
+    write_date(f, &date_time[..5])?;
+    f.write_str(" ")?;
+    write_time(f, &date_time[5..9])?;
+    let deviation = i16::from_be_bytes([date_time[9], date_time[10]]);
+    if deviation != i16::MIN {
+        write!(f, " {deviation:+}")?;
+    }
+    Ok(())
+}
+
 #[cfg(all(test, feature = "std"))]
 mod tests {
     extern crate std;
@@ -39,4 +476,88 @@ mod tests {
         let cloned_data = data.clone();
         assert_eq!(data, cloned_data);
     }
+
+    #[test]
+    fn test_integer_accessors() {
+        assert_eq!(CosemData::Long64(-5).as_i64(), Some(-5));
+        assert_eq!(CosemData::Long64(-5).as_u64(), None);
+        assert_eq!(CosemData::Long64Unsigned(u64::MAX).as_i64(), None);
+        assert_eq!(CosemData::Long64Unsigned(u64::MAX).as_u64(), Some(u64::MAX));
+        assert_eq!(CosemData::DoubleLong(-1).as_i64(), Some(-1));
+        assert_eq!(CosemData::OctetString(vec![1]).as_i64(), None);
+        assert_eq!(CosemData::Float32(1.5).as_f64(), Some(1.5));
+        assert_eq!(CosemData::Float64(-2.25).as_i64(), None);
+        assert_eq!(CosemData::Long(-3).as_f64(), Some(-3.0));
+        assert_eq!(CosemData::Float32(7.5).zero_like(), CosemData::Float32(0.0));
+    }
+
+    #[test]
+    fn test_checked_add_preserves_type() {
+        assert_eq!(
+            CosemData::Long64Unsigned(10).checked_add(5),
+            Some(CosemData::Long64Unsigned(15))
+        );
+        assert_eq!(
+            CosemData::DoubleLong(-10).checked_add(-5),
+            Some(CosemData::DoubleLong(-15))
+        );
+        assert_eq!(CosemData::Long64Unsigned(u64::MAX).checked_add(1), None);
+        assert_eq!(CosemData::DoubleLongUnsigned(0).checked_add(-1), None);
+        assert_eq!(CosemData::Long64(i64::MIN).checked_add(-1), None);
+        assert_eq!(CosemData::NullData.checked_add(1), None);
+    }
+
+    #[test]
+    fn test_data_type_and_length() {
+        assert_eq!(CosemData::Long(1).data_type(), DataType::Long);
+        assert_eq!(
+            CosemData::DateTime(vec![0; 12]).data_type(),
+            DataType::DateTime
+        );
+        assert_eq!(CosemData::OctetString(vec![0; 12]).length(), Some(12));
+        assert_eq!(
+            CosemData::Structure(vec![CosemData::NullData; 3]).length(),
+            Some(3)
+        );
+        assert_eq!(CosemData::VisibleString("abc".into()).length(), Some(3));
+        let bits = BitString::new(12, vec![0xAB, 0xCD]).unwrap();
+        assert_eq!(CosemData::BitString(bits).length(), Some(12));
+        assert_eq!(CosemData::Unsigned(3).length(), None);
+    }
+
+    #[test]
+    fn test_display_renders_nested_data_readably() {
+        let data = CosemData::Structure(vec![
+            CosemData::OctetString(vec![1, 0, 1, 8, 0, 255]),
+            CosemData::OctetString(vec![0x07, 0xEA, 10, 17, 6, 12, 5, 0, 0xFF, 0x80, 0, 0]),
+            CosemData::Array(vec![CosemData::Unsigned(3), CosemData::Enum(30)]),
+            CosemData::VisibleString("meter".to_string()),
+            CosemData::OctetString(vec![0xDE, 0xAD]),
+            CosemData::Date(vec![0xFF, 0xFF, 12, 25, 0xFF]),
+            CosemData::DateTime(vec![0x07, 0xEA, 1, 2, 5, 3, 4, 5, 0, 0xFF, 0xC4, 0]),
+            CosemData::NullData,
+        ]);
+        assert_eq!(
+            data.to_string(),
+            "{1.0.1.8.0.255, 2026-10-17 12:05:00, [3, enum(30)], \"meter\", 0xDEAD, \
+             *-12-25, 2026-01-02 03:04:05 -60, null}"
+        );
+    }
+
+    #[test]
+    fn test_bit_string_bits() {
+        let mut bits = BitString::new(10, vec![0b1010_0000, 0xFF]).unwrap();
+        // The padding of the last octet is cleared.
+        assert_eq!(bits.as_bytes(), &[0b1010_0000, 0b1100_0000]);
+        assert_eq!(bits.bit(0), Some(true));
+        assert_eq!(bits.bit(1), Some(false));
+        assert_eq!(bits.bit(9), Some(true));
+        assert_eq!(bits.bit(10), None);
+        bits.set_bit(1, true).unwrap();
+        bits.set_bit(9, false).unwrap();
+        assert_eq!(bits.set_bit(10, true), None);
+        assert_eq!(CosemData::BitString(bits).to_string(), "0b1110000010");
+        assert_eq!(BitString::new(9, vec![0]), None);
+        assert!(BitString::new(0, Vec::new()).unwrap().is_empty());
+    }
 }
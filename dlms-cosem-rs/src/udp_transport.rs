@@ -0,0 +1,120 @@
+#![cfg(feature = "std")]
+
+use crate::cosem_object::CosemObject;
+use crate::transport::Transport;
+use crate::types::CosemData;
+use crate::wrapper_transport::WrapperTransportError;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::vec::Vec;
+
+// Largest datagram accepted: a length prefix followed by an APDU of at most
+// MAX_PDU_SIZE bytes.
+const MAX_DATAGRAM_SIZE: usize = crate::MAX_PDU_SIZE + 2;
+
+// Destination of a push setup (class 40): the destination field of
+// send_destination_and_method when the transport service is UDP (1), given as
+// "host:port".
+pub fn push_destination(push_setup: &dyn CosemObject) -> Option<SocketAddr> {
+    if push_setup.class_id() != 40 {
+        return None;
+    }
+    let CosemData::Structure(fields) = push_setup.get_attribute(3)? else {
+        return None;
+    };
+    match fields.as_slice() {
+        [CosemData::Enum(1), CosemData::OctetString(destination), _] => {
+            std::str::from_utf8(destination)
+                .ok()?
+                .to_socket_addrs()
+                .ok()?
+                .next()
+        }
+        _ => None,
+    }
+}
+
+// DLMS/COSEM over UDP. Every datagram carries one APDU with the same length
+// prefix as `WrapperTransport`.
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    // The socket must be connected to the peer.
+    pub fn new(socket: UdpSocket) -> Self {
+        Self { socket }
+    }
+
+    pub fn connect(destination: SocketAddr) -> std::io::Result<Self> {
+        let local = match destination {
+            SocketAddr::V4(_) => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+            SocketAddr::V6(_) => SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(destination)?;
+        Ok(Self::new(socket))
+    }
+
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+impl Transport for UdpTransport {
+    type Error = WrapperTransportError;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let mut datagram = Vec::with_capacity(bytes.len() + 2);
+        datagram.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        datagram.extend_from_slice(bytes);
+        self.socket.send(&datagram)?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        let mut buffer = vec![0u8; MAX_DATAGRAM_SIZE];
+        let received = self.socket.recv(&mut buffer)?;
+        let [length_high, length_low, apdu @ ..] = &buffer[..received] else {
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+        };
+        let length = u16::from_be_bytes([*length_high, *length_low]) as usize;
+        if apdu.len() != length {
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData).into());
+        }
+        Ok(apdu.to_vec())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::push_setup::PushSetup;
+
+    #[test]
+    fn test_udp_transport_round_trip_to_push_destination() {
+        let head_end = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = head_end.local_addr().unwrap();
+
+        let mut setup = PushSetup::new();
+        setup
+            .set_attribute(
+                3,
+                CosemData::Structure(vec![
+                    CosemData::Enum(1),
+                    CosemData::OctetString(address.to_string().into_bytes()),
+                    CosemData::Enum(0),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(push_destination(&setup), Some(address));
+
+        let mut meter = UdpTransport::connect(address).unwrap();
+        meter.send(&[0x0F, 0, 0, 0, 1, 0, 0]).unwrap();
+        head_end
+            .connect(meter.socket().local_addr().unwrap())
+            .unwrap();
+        let mut head_end = UdpTransport::new(head_end);
+        assert_eq!(head_end.receive().unwrap(), vec![0x0F, 0, 0, 0, 1, 0, 0]);
+    }
+}
@@ -1,9 +1,46 @@
 #![cfg(feature = "std")]
 
-use crate::transport::Transport;
+use crate::cosem_object::CosemObject;
+use crate::ipv4_setup::ipv4_from_cosem_data;
+use crate::ipv6_setup::ipv6_from_cosem_data;
+use crate::transport::{Listener, Transport};
 use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
 use std::vec::Vec;
 
+// IANA registered port of the DLMS/COSEM wrapper over TCP and UDP.
+pub const DLMS_WRAPPER_PORT: u16 = 4059;
+
+// Local address configured in an IPv4 setup (class 42) or IPv6 setup (class 48)
+// object: the IP_address attribute, or the first unicast address respectively.
+pub fn configured_ip_address(ip_setup: &dyn CosemObject) -> Option<IpAddr> {
+    match ip_setup.class_id() {
+        42 => ipv4_from_cosem_data(&ip_setup.get_attribute(3)?)
+            .filter(|address| !address.is_unspecified())
+            .map(IpAddr::V4),
+        48 => match ip_setup.get_attribute(4)? {
+            crate::types::CosemData::Array(addresses) => addresses
+                .first()
+                .and_then(ipv6_from_cosem_data)
+                .map(IpAddr::V6),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Binds the wrapper listener to the address of the IP setup object when one is
+// given and configured, and to all interfaces otherwise.
+pub fn bind_listener(
+    ip_setup: Option<&dyn CosemObject>,
+    port: u16,
+) -> std::io::Result<TcpListener> {
+    let address = ip_setup
+        .and_then(configured_ip_address)
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    TcpListener::bind(SocketAddr::new(address, port))
+}
+
 #[derive(Debug)]
 pub enum WrapperTransportError {
     Io(std::io::Error),
@@ -46,3 +83,58 @@ impl<T: Read + Write> Transport for WrapperTransport<T> {
         Ok(buffer)
     }
 }
+
+// Each accepted socket carries wrapper frames.
+impl Listener for TcpListener {
+    type Connection = WrapperTransport<TcpStream>;
+    type Error = std::io::Error;
+
+    fn accept(&mut self) -> std::io::Result<Self::Connection> {
+        let (stream, _) = TcpListener::accept(self)?;
+        Ok(WrapperTransport::new(stream))
+    }
+
+    fn accept_from(&mut self) -> std::io::Result<(Self::Connection, Option<IpAddr>)> {
+        let (stream, peer) = TcpListener::accept(self)?;
+        Ok((WrapperTransport::new(stream), Some(peer.ip())))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::ipv4_setup::{ipv4_to_cosem_data, Ipv4Setup};
+    use crate::ipv6_setup::{ipv6_to_cosem_data, Ipv6Setup};
+    use crate::types::CosemData;
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn test_listener_uses_configured_ip_setup() {
+        let mut ipv4 = Ipv4Setup::new();
+        assert_eq!(configured_ip_address(&ipv4), None);
+        ipv4.set_attribute(3, ipv4_to_cosem_data(Ipv4Addr::LOCALHOST))
+            .unwrap();
+        assert_eq!(
+            configured_ip_address(&ipv4),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+
+        let mut ipv6 = Ipv6Setup::new();
+        ipv6.set_attribute(
+            4,
+            CosemData::Array(vec![ipv6_to_cosem_data(Ipv6Addr::LOCALHOST)]),
+        )
+        .unwrap();
+        assert_eq!(
+            configured_ip_address(&ipv6),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+
+        let listener = bind_listener(Some(&ipv4), 0).unwrap();
+        assert_eq!(
+            listener.local_addr().unwrap().ip(),
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use crate::axdr::{decode_data, encode_data};
+use crate::axdr::{decode_data_with, encode_data, ParseMode};
 use crate::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
 use crate::error::DlmsError;
+use crate::security::{hls_decrypt, hls_encrypt};
 use crate::types::CosemData;
 use std::vec::Vec;
 
@@ -22,7 +23,13 @@ fn encode_object_count(len: usize, buffer: &mut Vec<u8>) {
     buffer.extend_from_slice(&bytes);
 }
 
-fn decode_object_count(bytes: &[u8]) -> Result<(usize, usize), DlmsError> {
+// Splits `count` octets off the front of an APDU being decoded. A truncated
+// APDU is an error, never a panic.
+fn take_octets(bytes: &[u8], count: usize) -> Result<(&[u8], &[u8]), DlmsError> {
+    bytes.split_at_checked(count).ok_or(DlmsError::Xdlms)
+}
+
+fn decode_object_count_with(bytes: &[u8], mode: ParseMode) -> Result<(usize, usize), DlmsError> {
     if bytes.is_empty() {
         return Err(DlmsError::Xdlms);
     }
@@ -36,6 +43,11 @@ fn decode_object_count(bytes: &[u8]) -> Result<(usize, usize), DlmsError> {
     if bytes.len() < 1 + count_len {
         return Err(DlmsError::Xdlms);
     }
+    if mode == ParseMode::Strict
+        && (count_len == 0 || bytes[1] == 0 || (count_len == 1 && bytes[1] < 0x80))
+    {
+        return Err(DlmsError::Xdlms);
+    }
 
     let mut value = 0usize;
     for &byte in &bytes[1..=count_len] {
@@ -45,12 +57,12 @@ fn decode_object_count(bytes: &[u8]) -> Result<(usize, usize), DlmsError> {
     Ok((value, 1 + count_len))
 }
 
-fn decode_octet_string(bytes: &[u8]) -> Result<(&[u8], usize), DlmsError> {
+fn decode_octet_string(bytes: &[u8], mode: ParseMode) -> Result<(&[u8], usize), DlmsError> {
     if bytes.is_empty() || bytes[0] != 0x04 {
         return Err(DlmsError::Xdlms);
     }
 
-    let (len, consumed) = decode_object_count(&bytes[1..])?;
+    let (len, consumed) = decode_object_count_with(&bytes[1..], mode)?;
     let start = 1 + consumed;
     let end = start + len;
     if bytes.len() < end {
@@ -60,6 +72,148 @@ fn decode_octet_string(bytes: &[u8]) -> Result<(&[u8], usize), DlmsError> {
     Ok((&bytes[start..end], end))
 }
 
+// Presence flag of an optional field. Lenient mode reads any value but 1 as
+// absent; strict mode only accepts 0 and 1.
+fn presence_flag(flag: u8, mode: ParseMode) -> Result<bool, DlmsError> {
+    match (flag, mode) {
+        (0 | 1, _) | (_, ParseMode::Lenient) => Ok(flag == 1),
+        _ => Err(DlmsError::Xdlms),
+    }
+}
+
+// Finishes decoding a whole buffer; strict mode rejects bytes left over after
+// the APDU.
+fn complete<T>((value, rest): (T, &[u8]), mode: ParseMode) -> Result<T, DlmsError> {
+    if mode == ParseMode::Strict && !rest.is_empty() {
+        return Err(DlmsError::Xdlms);
+    }
+    Ok(value)
+}
+
+// Decodes `count` items of a SEQUENCE OF, each with `item`, refusing a count
+// the remaining bytes cannot hold before allocating for it.
+fn decode_sequence<'a, T>(
+    bytes: &'a [u8],
+    mode: ParseMode,
+    mut item: impl FnMut(&'a [u8]) -> Result<(T, &'a [u8]), DlmsError>,
+) -> Result<(Vec<T>, &'a [u8]), DlmsError> {
+    let (count, header) = decode_object_count_with(bytes, mode)?;
+    let mut rest = &bytes[header..];
+    if count > rest.len() {
+        return Err(DlmsError::Xdlms);
+    }
+    let mut items = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (value, after) = item(rest)?;
+        items.push(value);
+        rest = after;
+    }
+    Ok((items, rest))
+}
+
+fn decode_attribute_descriptor(
+    bytes: &[u8],
+) -> Result<(CosemAttributeDescriptor, &[u8]), DlmsError> {
+    let [c0, c1, i0, i1, i2, i3, i4, i5, attribute_id, rest @ ..] = bytes else {
+        return Err(DlmsError::Xdlms);
+    };
+    Ok((
+        CosemAttributeDescriptor {
+            class_id: u16::from_be_bytes([*c0, *c1]),
+            instance_id: [*i0, *i1, *i2, *i3, *i4, *i5],
+            attribute_id: *attribute_id as i8,
+        },
+        rest,
+    ))
+}
+
+// Cosem-Attribute-Descriptor-With-Selection lists of the with-list requests.
+// The list types carry no selection, so each descriptor goes out with the
+// access-selection flag cleared.
+fn encode_descriptor_list(descriptors: &[CosemAttributeDescriptor], bytes: &mut Vec<u8>) {
+    encode_object_count(descriptors.len(), bytes);
+    for desc in descriptors {
+        bytes.extend_from_slice(&desc.class_id.to_be_bytes());
+        bytes.extend_from_slice(&desc.instance_id);
+        bytes.push(desc.attribute_id as u8);
+        bytes.push(0); // no access-selection
+    }
+}
+
+// A selection on a list item is refused rather than dropped, which would
+// read or write the whole attribute instead of the part asked for.
+fn decode_descriptor_with_selection(
+    bytes: &[u8],
+    mode: ParseMode,
+) -> Result<(CosemAttributeDescriptor, &[u8]), DlmsError> {
+    let (descriptor, rest) = decode_attribute_descriptor(bytes)?;
+    let (has_access_selection, rest) = rest.split_first().ok_or(DlmsError::Xdlms)?;
+    if presence_flag(*has_access_selection, mode)? {
+        return Err(DlmsError::Xdlms);
+    }
+    Ok((descriptor, rest))
+}
+
+fn encode_get_data_result(result: &GetDataResult, bytes: &mut Vec<u8>) -> Result<(), DlmsError> {
+    match result {
+        GetDataResult::Data(data) => {
+            bytes.push(0); // data
+            encode_data(data, bytes)
+        }
+        GetDataResult::DataAccessResult(dar) => {
+            bytes.push(1); // data-access-result
+            bytes.push(dar.clone().into());
+            Ok(())
+        }
+    }
+}
+
+fn decode_get_data_result(
+    bytes: &[u8],
+    mode: ParseMode,
+) -> Result<(GetDataResult, &[u8]), DlmsError> {
+    match bytes {
+        [0, rest @ ..] => {
+            let (data, rest) = decode_data_with(rest, mode)?;
+            Ok((GetDataResult::Data(data), rest))
+        }
+        [_, dar, rest @ ..] => Ok((GetDataResult::DataAccessResult((*dar).into()), rest)),
+        _ => Err(DlmsError::Xdlms),
+    }
+}
+
+// The SEQUENCE OF Get-Data-Result of get-response-with-list, also what the
+// raw data of a with-list response sent in datablocks adds up to.
+pub(crate) fn decode_get_data_results(
+    bytes: &[u8],
+    mode: ParseMode,
+) -> Result<(Vec<GetDataResult>, &[u8]), DlmsError> {
+    decode_sequence(bytes, mode, |bytes| decode_get_data_result(bytes, mode))
+}
+
+fn decode_method_descriptor(bytes: &[u8]) -> Result<(CosemMethodDescriptor, &[u8]), DlmsError> {
+    let [c0, c1, i0, i1, i2, i3, i4, i5, method_id, rest @ ..] = bytes else {
+        return Err(DlmsError::Xdlms);
+    };
+    Ok((
+        CosemMethodDescriptor {
+            class_id: u16::from_be_bytes([*c0, *c1]),
+            instance_id: [*i0, *i1, *i2, *i3, *i4, *i5],
+            method_id: *method_id as i8,
+        },
+        rest,
+    ))
+}
+
+// Flag of an optional initiate field, read as present when nonzero; strict
+// mode only accepts 0 and 1.
+fn initiate_flag(flag: u8, mode: ParseMode) -> Result<bool, DlmsError> {
+    if mode == ParseMode::Strict && flag > 1 {
+        return Err(DlmsError::Xdlms);
+    }
+    Ok(flag != 0)
+}
+
 pub type InvokeIdAndPriority = u8;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -67,7 +221,43 @@ pub struct Conformance {
     pub value: u32,
 }
 
+// Conformance block bits (Green Book 9.5), bit 0 being the most significant
+// bit of the 24-bit string.
 impl Conformance {
+    pub const GENERAL_PROTECTION: Conformance = Conformance::bit(1);
+    pub const GENERAL_BLOCK_TRANSFER: Conformance = Conformance::bit(2);
+    pub const READ: Conformance = Conformance::bit(3);
+    pub const WRITE: Conformance = Conformance::bit(4);
+    pub const UNCONFIRMED_WRITE: Conformance = Conformance::bit(5);
+    pub const ATTRIBUTE0_SUPPORTED_WITH_SET: Conformance = Conformance::bit(8);
+    pub const PRIORITY_MGMT_SUPPORTED: Conformance = Conformance::bit(9);
+    pub const ATTRIBUTE0_SUPPORTED_WITH_GET: Conformance = Conformance::bit(10);
+    pub const BLOCK_TRANSFER_WITH_GET_OR_READ: Conformance = Conformance::bit(11);
+    pub const BLOCK_TRANSFER_WITH_SET_OR_WRITE: Conformance = Conformance::bit(12);
+    pub const BLOCK_TRANSFER_WITH_ACTION: Conformance = Conformance::bit(13);
+    pub const MULTIPLE_REFERENCES: Conformance = Conformance::bit(14);
+    pub const INFORMATION_REPORT: Conformance = Conformance::bit(15);
+    pub const DATA_NOTIFICATION: Conformance = Conformance::bit(16);
+    pub const ACCESS: Conformance = Conformance::bit(17);
+    pub const PARAMETERIZED_ACCESS: Conformance = Conformance::bit(18);
+    pub const GET: Conformance = Conformance::bit(19);
+    pub const SET: Conformance = Conformance::bit(20);
+    pub const SELECTIVE_ACCESS: Conformance = Conformance::bit(21);
+    pub const EVENT_NOTIFICATION: Conformance = Conformance::bit(22);
+    pub const ACTION: Conformance = Conformance::bit(23);
+
+    const fn bit(index: u32) -> Conformance {
+        Conformance {
+            value: 1 << (23 - index),
+        }
+    }
+
+    pub fn union(&self, other: &Conformance) -> Conformance {
+        Conformance {
+            value: self.value | other.value,
+        }
+    }
+
     pub fn to_bytes(&self) -> [u8; 3] {
         [
             ((self.value >> 16) & 0xFF) as u8,
@@ -113,7 +303,17 @@ impl Default for AssociationParameters {
     fn default() -> Self {
         AssociationParameters {
             dlms_version: 6,
-            conformance: Conformance { value: 0x0010_0000 },
+            conformance: [
+                Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ,
+                Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE,
+                Conformance::BLOCK_TRANSFER_WITH_ACTION,
+                Conformance::GET,
+                Conformance::SET,
+                Conformance::SELECTIVE_ACCESS,
+                Conformance::ACTION,
+            ]
+            .iter()
+            .fold(Conformance { value: 0 }, |all, service| all.union(service)),
             max_receive_pdu_size: 0x0400,
             quality_of_service: None,
         }
@@ -158,7 +358,8 @@ impl GetRequest {
         let mut bytes = Vec::new();
         match self {
             GetRequest::Normal(req) => {
-                bytes.push(192); // get-request-normal
+                bytes.push(192); // get-request
+                bytes.push(1); // get-request-normal
                 bytes.push(req.invoke_id_and_priority);
                 bytes.extend_from_slice(&req.cosem_attribute_descriptor.class_id.to_be_bytes());
                 bytes.extend_from_slice(&req.cosem_attribute_descriptor.instance_id);
@@ -172,46 +373,60 @@ impl GetRequest {
                 }
             }
             GetRequest::Next(req) => {
-                bytes.push(193); // get-request-next
+                bytes.push(192); // get-request
+                bytes.push(2); // get-request-next
                 bytes.push(req.invoke_id_and_priority);
                 bytes.extend_from_slice(&req.block_number.to_be_bytes());
             }
             GetRequest::WithList(req) => {
-                bytes.push(194); // get-request-with-list
+                bytes.push(192); // get-request
+                bytes.push(3); // get-request-with-list
                 bytes.push(req.invoke_id_and_priority);
-                bytes.push(req.attribute_descriptor_list.len() as u8);
-                for desc in &req.attribute_descriptor_list {
-                    bytes.extend_from_slice(&desc.class_id.to_be_bytes());
-                    bytes.extend_from_slice(&desc.instance_id);
-                    bytes.push(desc.attribute_id as u8);
-                }
+                encode_descriptor_list(&req.attribute_descriptor_list, &mut bytes);
             }
         }
         Ok(bytes)
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
-        if bytes.is_empty() {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    // Decodes one APDU from the front of `bytes` and returns it with the bytes
+    // following it.
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 2 {
             return Err(DlmsError::Xdlms);
         }
-        let (tag, rest) = bytes.split_at(1);
-        match tag[0] {
-            192 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (class_id, rest) = rest.split_at(2);
-                let (instance_id, rest) = rest.split_at(6);
-                let (attribute_id, rest) = rest.split_at(1);
-                let (has_access_selection, rest) = rest.split_at(1);
-
-                let access_selection = if has_access_selection[0] == 1 {
-                    let (access_selector, rest) = rest.split_at(1);
-                    let (access_parameters, _) = decode_data(rest)?;
-                    Some(SelectiveAccessDescriptor {
-                        access_selector: access_selector[0],
-                        access_parameters,
-                    })
+        let (tag, rest) = take_octets(bytes, 2)?;
+        match (tag[0], tag[1]) {
+            (192, 1) => {
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (class_id, rest) = take_octets(rest, 2)?;
+                let (instance_id, rest) = take_octets(rest, 6)?;
+                let (attribute_id, rest) = take_octets(rest, 1)?;
+                let (has_access_selection, rest) = take_octets(rest, 1)?;
+
+                let (access_selection, rest) = if presence_flag(has_access_selection[0], mode)? {
+                    let (access_selector, rest) = take_octets(rest, 1)?;
+                    let (access_parameters, rest) = decode_data_with(rest, mode)?;
+                    (
+                        Some(SelectiveAccessDescriptor {
+                            access_selector: access_selector[0],
+                            access_parameters,
+                        }),
+                        rest,
+                    )
                 } else {
-                    None
+                    (None, rest)
                 };
 
                 let mut class_id_bytes = [0u8; 2];
@@ -220,42 +435,48 @@ impl GetRequest {
                 let mut instance_id_bytes = [0u8; 6];
                 instance_id_bytes.copy_from_slice(instance_id);
 
-                Ok(GetRequest::Normal(GetRequestNormal {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    cosem_attribute_descriptor: CosemAttributeDescriptor {
-                        class_id: u16::from_be_bytes(class_id_bytes),
-                        instance_id: instance_id_bytes,
-                        attribute_id: attribute_id[0] as i8,
-                    },
-                    access_selection,
-                }))
-            }
-            194 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (len, mut rest) = rest.split_at(1);
-                let mut attribute_descriptor_list = Vec::new();
-                for _ in 0..len[0] {
-                    let (class_id, r) = rest.split_at(2);
-                    let (instance_id, r) = r.split_at(6);
-                    let (attribute_id, r) = r.split_at(1);
-                    rest = r;
-
-                    let mut class_id_bytes = [0u8; 2];
-                    class_id_bytes.copy_from_slice(class_id);
-
-                    let mut instance_id_bytes = [0u8; 6];
-                    instance_id_bytes.copy_from_slice(instance_id);
-
-                    attribute_descriptor_list.push(CosemAttributeDescriptor {
-                        class_id: u16::from_be_bytes(class_id_bytes),
-                        instance_id: instance_id_bytes,
-                        attribute_id: attribute_id[0] as i8,
-                    });
+                Ok((
+                    GetRequest::Normal(GetRequestNormal {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        cosem_attribute_descriptor: CosemAttributeDescriptor {
+                            class_id: u16::from_be_bytes(class_id_bytes),
+                            instance_id: instance_id_bytes,
+                            attribute_id: attribute_id[0] as i8,
+                        },
+                        access_selection,
+                    }),
+                    rest,
+                ))
+            }
+            (192, 2) => {
+                if rest.len() < 5 {
+                    return Err(DlmsError::Xdlms);
                 }
-                Ok(GetRequest::WithList(GetRequestWithList {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    attribute_descriptor_list,
-                }))
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let mut block_number_bytes = [0u8; 4];
+                block_number_bytes.copy_from_slice(&rest[..4]);
+                Ok((
+                    GetRequest::Next(GetRequestNext {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        block_number: u32::from_be_bytes(block_number_bytes),
+                    }),
+                    &rest[4..],
+                ))
+            }
+            (192, 3) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (attribute_descriptor_list, rest) = decode_sequence(rest, mode, |bytes| {
+                    decode_descriptor_with_selection(bytes, mode)
+                })?;
+                Ok((
+                    GetRequest::WithList(GetRequestWithList {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        attribute_descriptor_list,
+                    }),
+                    rest,
+                ))
             }
             _ => Err(DlmsError::Xdlms),
         }
@@ -267,6 +488,137 @@ mod tests {
     extern crate std;
     use super::*;
 
+    type Parse<A> = fn(&[u8], ParseMode) -> Result<(A, &[u8]), DlmsError>;
+
+    // Every proper prefix of an APDU is refused, whichever part it ends in.
+    fn assert_truncations_refused<A>(bytes: &[u8], parse: Parse<A>) {
+        assert!(parse(bytes, ParseMode::Lenient).is_ok());
+        for length in 0..bytes.len() {
+            let truncated = &bytes[..length];
+            assert!(
+                parse(truncated, ParseMode::Lenient).is_err(),
+                "{truncated:02X?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_apdus_are_errors() {
+        let descriptor = CosemAttributeDescriptor {
+            class_id: 7,
+            instance_id: [1, 0, 99, 1, 0, 255],
+            attribute_id: 2,
+        };
+        let method = CosemMethodDescriptor {
+            class_id: 7,
+            instance_id: [1, 0, 99, 1, 0, 255],
+            method_id: 1,
+        };
+        let selection = Some(SelectiveAccessDescriptor {
+            access_selector: 2,
+            access_parameters: CosemData::Structure(vec![CosemData::DoubleLongUnsigned(1)]),
+        });
+        let datablock = DataBlockSA {
+            last_block: false,
+            block_number: 1,
+            raw_data: vec![0x09, 0x02, 0xAB],
+        };
+
+        for request in [
+            GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_attribute_descriptor: descriptor.clone(),
+                access_selection: selection.clone(),
+            }),
+            GetRequest::Next(GetRequestNext {
+                invoke_id_and_priority: 0xC1,
+                block_number: 2,
+            }),
+            GetRequest::WithList(GetRequestWithList {
+                invoke_id_and_priority: 0xC1,
+                attribute_descriptor_list: vec![descriptor.clone(); 2],
+            }),
+        ] {
+            assert_truncations_refused(&request.to_bytes().unwrap(), GetRequest::parse_with);
+        }
+        for request in [
+            SetRequest::Normal(SetRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_attribute_descriptor: descriptor.clone(),
+                access_selection: selection.clone(),
+                value: CosemData::LongUnsigned(5),
+            }),
+            SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+                invoke_id_and_priority: 0xC1,
+                cosem_attribute_descriptor: descriptor.clone(),
+                access_selection: selection.clone(),
+                datablock: datablock.clone(),
+            }),
+            SetRequest::WithDatablock(SetRequestWithDatablock {
+                invoke_id_and_priority: 0xC1,
+                datablock: datablock.clone(),
+            }),
+            SetRequest::WithList(SetRequestWithList {
+                invoke_id_and_priority: 0xC1,
+                attribute_descriptor_list: vec![descriptor.clone(); 2],
+                value_list: vec![CosemData::Unsigned(1), CosemData::Unsigned(2)],
+            }),
+        ] {
+            assert_truncations_refused(&request.to_bytes().unwrap(), SetRequest::parse_with);
+        }
+        for request in [
+            ActionRequest::Normal(ActionRequestNormal {
+                invoke_id_and_priority: 0xC1,
+                cosem_method_descriptor: method.clone(),
+                method_invocation_parameters: Some(CosemData::Integer(0)),
+            }),
+            ActionRequest::WithFirstPblock(ActionRequestWithFirstPblock {
+                invoke_id_and_priority: 0xC1,
+                cosem_method_descriptor: method.clone(),
+                pblock: datablock.clone(),
+            }),
+            ActionRequest::WithList(ActionRequestWithList {
+                invoke_id_and_priority: 0xC1,
+                cosem_method_descriptor_list: vec![method.clone(); 2],
+                method_invocation_parameters: vec![CosemData::Integer(0), CosemData::Integer(1)],
+            }),
+        ] {
+            assert_truncations_refused(&request.to_bytes().unwrap(), ActionRequest::parse_with);
+        }
+        for response in [
+            GetResponse::Normal(GetResponseNormal {
+                invoke_id_and_priority: 0xC1,
+                result: GetDataResult::Data(CosemData::LongUnsigned(5)),
+            }),
+            GetResponse::WithList(GetResponseWithList {
+                invoke_id_and_priority: 0xC1,
+                result: vec![
+                    GetDataResult::Data(CosemData::Unsigned(1)),
+                    GetDataResult::DataAccessResult(DataAccessResult::ObjectUndefined),
+                ],
+            }),
+        ] {
+            assert_truncations_refused(&response.to_bytes().unwrap(), GetResponse::parse_with);
+        }
+        for response in [
+            SetResponse::Normal(SetResponseNormal {
+                invoke_id_and_priority: 0xC1,
+                result: DataAccessResult::Success,
+            }),
+            SetResponse::WithList(SetResponseWithList {
+                invoke_id_and_priority: 0xC1,
+                result: vec![DataAccessResult::Success, DataAccessResult::TypeUnmatched],
+            }),
+        ] {
+            assert_truncations_refused(&response.to_bytes().unwrap(), SetResponse::parse_with);
+        }
+
+        // Found by fuzzing.
+        let set = [0xC1, 0x01, 0xFF, 0x00, 0x03, 0x01, 0x00, 0x01];
+        assert!(SetRequest::parse_with(&set, ParseMode::Lenient).is_err());
+        assert!(GetRequest::parse_with(&[0xC0, 0x01, 0xC1, 0x00], ParseMode::Lenient).is_err());
+    }
+
     #[test]
     fn test_get_request_normal_serialization_deserialization() {
         let req = GetRequest::Normal(GetRequestNormal {
@@ -311,6 +663,99 @@ mod tests {
         assert_eq!(req, req2);
     }
 
+    #[test]
+    fn test_get_request_with_list_matches_reference_encoding() {
+        // Each item is a Cosem-Attribute-Descriptor-With-Selection: the
+        // descriptor, then the access-selection OPTIONAL flag.
+        let encoded = [
+            0xC0, 0x03, 0xC1, 0x02, // get-request-with-list, two items
+            0x00, 0x08, 0x00, 0x00, 0x01, 0x00, 0x00, 0xFF, 0x02, 0x00, // clock time
+            0x00, 0x03, 0x01, 0x00, 0x01, 0x08, 0x00, 0xFF, 0x02, 0x00, // energy value
+        ];
+        let request = GetRequest::WithList(GetRequestWithList {
+            invoke_id_and_priority: 0xC1,
+            attribute_descriptor_list: vec![
+                CosemAttributeDescriptor {
+                    class_id: 8,
+                    instance_id: [0, 0, 1, 0, 0, 255],
+                    attribute_id: 2,
+                },
+                CosemAttributeDescriptor {
+                    class_id: 3,
+                    instance_id: [1, 0, 1, 8, 0, 255],
+                    attribute_id: 2,
+                },
+            ],
+        });
+        assert_eq!(request.to_bytes().unwrap(), encoded);
+        assert_eq!(GetRequest::from_bytes(&encoded).unwrap(), request);
+
+        // An item with a selection cannot be represented and is refused.
+        let mut selected = encoded.to_vec();
+        selected[13] = 0x01;
+        assert!(GetRequest::from_bytes(&selected).is_err());
+
+        // Lists beyond 127 items take a long-form count.
+        let request = GetRequest::WithList(GetRequestWithList {
+            invoke_id_and_priority: 0xC1,
+            attribute_descriptor_list: vec![
+                CosemAttributeDescriptor {
+                    class_id: 1,
+                    instance_id: [0, 0, 96, 1, 0, 255],
+                    attribute_id: 2,
+                };
+                300
+            ],
+        });
+        let bytes = request.to_bytes().unwrap();
+        assert_eq!(&bytes[..6], &[0xC0, 0x03, 0xC1, 0x82, 0x01, 0x2C]);
+        assert_eq!(GetRequest::from_bytes(&bytes).unwrap(), request);
+
+        let response = GetResponse::WithList(GetResponseWithList {
+            invoke_id_and_priority: 0xC1,
+            result: vec![GetDataResult::Data(CosemData::Unsigned(1)); 300],
+        });
+        let bytes = response.to_bytes().unwrap();
+        assert_eq!(&bytes[..6], &[0xC4, 0x03, 0xC1, 0x82, 0x01, 0x2C]);
+        assert_eq!(GetResponse::from_bytes(&bytes).unwrap(), response);
+    }
+
+    #[test]
+    fn test_set_request_with_list_matches_reference_encoding() {
+        // The descriptors carry their access-selection flag, then the
+        // values follow as a second SEQUENCE OF.
+        let encoded = [
+            0xC1, 0x04, 0xC1, 0x02, // set-request-with-list, two items
+            0x00, 0x01, 0x00, 0x00, 0x60, 0x01, 0x00, 0xFF, 0x02, 0x00, // first data
+            0x00, 0x01, 0x00, 0x00, 0x60, 0x01, 0x01, 0xFF, 0x02, 0x00, // second data
+            0x02, // two values
+            0x11, 0x01, // unsigned 1
+            0x12, 0x00, 0x02, // long-unsigned 2
+        ];
+        let request = SetRequest::WithList(SetRequestWithList {
+            invoke_id_and_priority: 0xC1,
+            attribute_descriptor_list: vec![
+                CosemAttributeDescriptor {
+                    class_id: 1,
+                    instance_id: [0, 0, 96, 1, 0, 255],
+                    attribute_id: 2,
+                },
+                CosemAttributeDescriptor {
+                    class_id: 1,
+                    instance_id: [0, 0, 96, 1, 1, 255],
+                    attribute_id: 2,
+                },
+            ],
+            value_list: vec![CosemData::Unsigned(1), CosemData::LongUnsigned(2)],
+        });
+        assert_eq!(request.to_bytes().unwrap(), encoded);
+        assert_eq!(SetRequest::from_bytes(&encoded).unwrap(), request);
+
+        let mut selected = encoded.to_vec();
+        selected[23] = 0x01;
+        assert!(SetRequest::from_bytes(&selected).is_err());
+    }
+
     #[test]
     fn test_get_response_normal_serialization_deserialization() {
         let res = GetResponse::Normal(GetResponseNormal {
@@ -342,6 +787,65 @@ mod tests {
         assert_eq!(res, res2);
     }
 
+    #[test]
+    fn test_service_requests_match_reference_encoding() {
+        // Each service is one context tag, get-request [192], set-request
+        // [193] and action-request [195], followed by the index of its CHOICE,
+        // as other stacks send it. A tag per variant would let [193] read as
+        // both get-request-next and set-request.
+        let descriptor = CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: [1, 0, 1, 8, 0, 255],
+            attribute_id: 2,
+        };
+        let get = [
+            0xC0, 0x01, 0xC1, 0x00, 0x03, 0x01, 0x00, 0x01, 0x08, 0x00, 0xFF, 0x02, 0x00,
+        ];
+        let request = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: descriptor.clone(),
+            access_selection: None,
+        });
+        assert_eq!(request.to_bytes().unwrap(), get);
+        assert_eq!(GetRequest::from_bytes(&get).unwrap(), request);
+
+        let next = [0xC0, 0x02, 0xC1, 0x00, 0x00, 0x00, 0x02];
+        let request = GetRequest::Next(GetRequestNext {
+            invoke_id_and_priority: 0xC1,
+            block_number: 2,
+        });
+        assert_eq!(request.to_bytes().unwrap(), next);
+        assert_eq!(GetRequest::from_bytes(&next).unwrap(), request);
+
+        let set = [
+            0xC1, 0x01, 0xC1, 0x00, 0x03, 0x01, 0x00, 0x01, 0x08, 0x00, 0xFF, 0x02, 0x00, 0x11,
+            0x05,
+        ];
+        let request = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: descriptor,
+            access_selection: None,
+            value: CosemData::Unsigned(5),
+        });
+        assert_eq!(request.to_bytes().unwrap(), set);
+        assert_eq!(SetRequest::from_bytes(&set).unwrap(), request);
+
+        let action = [
+            0xC3, 0x01, 0xC1, 0x00, 0x09, 0x00, 0x00, 0x0A, 0x00, 0x00, 0xFF, 0x01, 0x00,
+        ];
+        let request = ActionRequest::Normal(ActionRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 9,
+                instance_id: [0, 0, 10, 0, 0, 255],
+                method_id: 1,
+            },
+            method_invocation_parameters: None,
+        });
+        assert_eq!(request.to_bytes().unwrap(), action);
+        assert_eq!(ActionRequest::from_bytes(&action).unwrap(), request);
+    }
+
     #[test]
     fn test_get_response_with_datablock_serialization_deserialization() {
         let mut data = Vec::new();
@@ -351,7 +855,7 @@ mod tests {
             result: DataBlockG {
                 last_block: true,
                 block_number: 1,
-                raw_data: data,
+                result: DataBlockResult::RawData(data),
             },
         });
 
@@ -361,6 +865,60 @@ mod tests {
         assert_eq!(res, res2);
     }
 
+    #[test]
+    fn test_get_response_with_datablock_matches_reference_encoding() {
+        // Block 1 of 3 of an array of two long-unsigned, as other stacks
+        // send it: raw-data is a length-prefixed octet string.
+        let encoded = [
+            0xC4, 0x02, 0xC1, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x04, 0x01, 0x02, 0x12, 0x00,
+        ];
+        let response = GetResponse::WithDataBlock(GetResponseWithDatablock {
+            invoke_id_and_priority: 0xC1,
+            result: DataBlockG {
+                last_block: false,
+                block_number: 1,
+                result: DataBlockResult::RawData(vec![0x01, 0x02, 0x12, 0x00]),
+            },
+        });
+        assert_eq!(response.to_bytes().unwrap(), encoded);
+        assert_eq!(GetResponse::from_bytes(&encoded).unwrap(), response);
+
+        // A long block takes a long-form length.
+        let block = GetResponse::WithDataBlock(GetResponseWithDatablock {
+            invoke_id_and_priority: 0xC1,
+            result: DataBlockG {
+                last_block: true,
+                block_number: 2,
+                result: DataBlockResult::RawData(vec![0xAB; 300]),
+            },
+        });
+        let bytes = block.to_bytes().unwrap();
+        assert_eq!(
+            &bytes[..12],
+            &[0xC4, 0x02, 0xC1, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x82, 0x01, 0x2C]
+        );
+        assert_eq!(GetResponse::from_bytes(&bytes).unwrap(), block);
+
+        // The raw data no longer runs to the end of the buffer.
+        let followed = [&encoded[..], &[0xC4]].concat();
+        let (_, rest) = GetResponse::parse(&followed).unwrap();
+        assert_eq!(rest, &[0xC4]);
+        assert!(GetResponse::from_bytes(&encoded[..12]).is_err());
+
+        // A block ending the transfer with a data-access-result.
+        let aborted = [0xC4, 0x02, 0xC1, 0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x0A];
+        let response = GetResponse::WithDataBlock(GetResponseWithDatablock {
+            invoke_id_and_priority: 0xC1,
+            result: DataBlockG {
+                last_block: true,
+                block_number: 2,
+                result: DataBlockResult::DataAccessResult(DataAccessResult::LongGetAborted),
+            },
+        });
+        assert_eq!(GetResponse::from_bytes(&aborted).unwrap(), response);
+        assert_eq!(response.to_bytes().unwrap(), aborted);
+    }
+
     #[test]
     fn test_set_request_normal_serialization_deserialization() {
         let req = SetRequest::Normal(SetRequestNormal {
@@ -380,6 +938,56 @@ mod tests {
         assert_eq!(req, req2);
     }
 
+    #[test]
+    fn test_set_request_datablocks_round_trip() {
+        let first = SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 9,
+                instance_id: [0, 0, 10, 0, 100, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+            datablock: DataBlockSA {
+                last_block: false,
+                block_number: 1,
+                raw_data: vec![0xAA; 200],
+            },
+        });
+        let bytes = first.to_bytes().unwrap();
+        assert_eq!(&bytes[..2], &[193, 2]);
+        assert_eq!(SetRequest::from_bytes(&bytes).unwrap(), first);
+
+        let next = SetRequest::WithDatablock(SetRequestWithDatablock {
+            invoke_id_and_priority: 0xC1,
+            datablock: DataBlockSA {
+                last_block: true,
+                block_number: 2,
+                raw_data: vec![0x01, 0x02],
+            },
+        });
+        let bytes = next.to_bytes().unwrap();
+        assert_eq!(bytes, vec![193, 3, 0xC1, 1, 0, 0, 0, 2, 2, 0x01, 0x02]);
+        assert_eq!(SetRequest::from_bytes(&bytes).unwrap(), next);
+    }
+
+    #[test]
+    fn test_set_response_datablocks_round_trip() {
+        let ack = SetResponse::Datablock(SetResponseDatablock {
+            invoke_id_and_priority: 1,
+            block_number: 7,
+        });
+        let last = SetResponse::LastDatablock(SetResponseLastDatablock {
+            invoke_id_and_priority: 1,
+            result: DataAccessResult::TypeUnmatched,
+            block_number: 8,
+        });
+        for res in [ack, last] {
+            let bytes = res.to_bytes().unwrap();
+            assert_eq!(SetResponse::from_bytes(&bytes).unwrap(), res);
+        }
+    }
+
     #[test]
     fn test_set_response_normal_serialization_deserialization() {
         let res = SetResponse::Normal(SetResponseNormal {
@@ -427,6 +1035,210 @@ mod tests {
         assert_eq!(res, res2);
     }
 
+    #[test]
+    fn test_action_response_return_parameters_branches() {
+        let response = |return_parameters| {
+            ActionResponse::Normal(ActionResponseNormal {
+                invoke_id_and_priority: 0xC1,
+                single_response: ActionResponseWithOptionalData {
+                    result: ActionResult::OtherReason(250),
+                    return_parameters: Some(return_parameters),
+                },
+            })
+        };
+
+        let with_data = response(GetDataResult::Data(CosemData::Unsigned(7)));
+        let bytes = with_data.to_bytes().unwrap();
+        assert_eq!(bytes, vec![199, 1, 0xC1, 250, 1, 0, 0x11, 7]);
+        assert_eq!(ActionResponse::from_bytes(&bytes).unwrap(), with_data);
+
+        let bytes = [199, 1, 0xC1, 250, 1, 1, 3];
+        let ActionResponse::Normal(parsed) = ActionResponse::from_bytes(&bytes).unwrap() else {
+            panic!("expected action-response-normal");
+        };
+        assert_eq!(
+            parsed.single_response.data_access_result(),
+            Some(&DataAccessResult::ReadWriteDenied)
+        );
+        assert_eq!(parsed.single_response.return_data(), None);
+        assert_eq!(
+            response(GetDataResult::DataAccessResult(
+                DataAccessResult::ReadWriteDenied
+            ))
+            .to_bytes()
+            .unwrap(),
+            bytes
+        );
+        assert!(ActionResponse::from_bytes(&[199, 1, 0xC1, 0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_action_pblocks_round_trip() {
+        let first = ActionRequest::WithFirstPblock(ActionRequestWithFirstPblock {
+            invoke_id_and_priority: 0xC1,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 20,
+                instance_id: [0, 0, 13, 0, 0, 255],
+                method_id: 1,
+            },
+            pblock: DataBlockSA {
+                last_block: false,
+                block_number: 1,
+                raw_data: vec![0xAA; 200],
+            },
+        });
+        let bytes = first.to_bytes().unwrap();
+        assert_eq!(&bytes[..2], &[195, 4]);
+        assert_eq!(ActionRequest::from_bytes(&bytes).unwrap(), first);
+
+        let next = ActionRequest::WithPblock(ActionRequestWithPblock {
+            invoke_id_and_priority: 0xC1,
+            pblock: DataBlockSA {
+                last_block: true,
+                block_number: 2,
+                raw_data: vec![0x01, 0x02],
+            },
+        });
+        let bytes = next.to_bytes().unwrap();
+        assert_eq!(bytes, vec![195, 6, 0xC1, 1, 0, 0, 0, 2, 2, 0x01, 0x02]);
+        assert_eq!(ActionRequest::from_bytes(&bytes).unwrap(), next);
+
+        let ack = ActionResponse::NextPblock(ActionResponseNextPblock {
+            invoke_id_and_priority: 0xC1,
+            block_number: 1,
+        });
+        let bytes = ack.to_bytes().unwrap();
+        assert_eq!(bytes, vec![199, 4, 0xC1, 0, 0, 0, 1]);
+        assert_eq!(ActionResponse::from_bytes(&bytes).unwrap(), ack);
+        assert!(ActionRequest::from_bytes(&[195, 6, 0xC1, 1, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_general_protection_apdus_round_trip() {
+        let header = GeneralProtectionHeader {
+            transaction_id: vec![0x00, 0x00, 0x00, 0x01],
+            originator_system_title: b"HEADEND1".to_vec(),
+            recipient_system_title: b"METER001".to_vec(),
+            date_time: Vec::new(),
+            other_information: Vec::new(),
+        };
+        let ciphering = GeneralCiphering {
+            header: header.clone(),
+            key_info: Some(KeyInfo::WrappedKey {
+                kek_id: 0,
+                key_ciphered_data: vec![0xAA; 24],
+            }),
+            ciphered_content: vec![0x30, 0x00, 0x00, 0x00, 0x01, 0x5A],
+        };
+        let bytes = ciphering.to_bytes().unwrap();
+        assert_eq!(&bytes[..3], &[221, 4, 0x00]);
+        assert_eq!(GeneralCiphering::from_bytes(&bytes).unwrap(), ciphering);
+
+        let signing = GeneralSigning {
+            header,
+            content: bytes,
+            signature: vec![0x55; 64],
+        };
+        let bytes = signing.to_bytes().unwrap();
+        assert!(bytes.starts_with(&signing.signed_data()));
+        assert_eq!(GeneralSigning::from_bytes(&bytes).unwrap(), signing);
+        assert!(GeneralSigning::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(GeneralCiphering::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_data_notification_round_trip() {
+        let notification = DataNotification {
+            long_invoke_id_and_priority: 0x0000_0001,
+            date_time: None,
+            notification_body: CosemData::Structure(vec![
+                CosemData::OctetString(vec![0, 0, 1, 0, 0, 255]),
+                CosemData::DoubleLongUnsigned(1234),
+            ]),
+        };
+        let bytes = notification.to_bytes().unwrap();
+        assert_eq!(&bytes[..7], &[0x0F, 0, 0, 0, 1, 0, 0x02]);
+        assert_eq!(DataNotification::from_bytes(&bytes).unwrap(), notification);
+
+        let stamped = DataNotification {
+            date_time: Some(vec![0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0]),
+            ..notification
+        };
+        let bytes = stamped.to_bytes().unwrap();
+        assert_eq!(&bytes[5..7], &[12, 0x07]);
+        assert_eq!(DataNotification::from_bytes(&bytes).unwrap(), stamped);
+        assert!(DataNotification::from_bytes(&bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn test_event_notification_round_trip() {
+        let event = Notification::Event(EventNotificationRequest {
+            time: Some(vec![0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0]),
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: [0, 0, 96, 11, 0, 255],
+                attribute_id: 2,
+            },
+            attribute_value: CosemData::LongUnsigned(47),
+        });
+        let bytes = event.to_bytes().unwrap();
+        assert_eq!(&bytes[..3], &[194, 1, 12]);
+        assert!(Notification::is_notification(&bytes));
+        assert_eq!(Notification::from_bytes(&bytes).unwrap(), event);
+        assert!(Notification::from_bytes(&bytes[..10]).is_err());
+        assert!(!Notification::is_notification(&[196, 1, 0xC1]));
+        assert!(Notification::from_bytes(&[196, 1, 0xC1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_returns_bytes_after_the_apdu() {
+        let get = GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 3,
+                instance_id: [1, 0, 1, 8, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+        });
+        let set = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 0xC2,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: [0, 0, 96, 1, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+            value: CosemData::LongUnsigned(7),
+        });
+        let action = ActionResponse::Normal(ActionResponseNormal {
+            invoke_id_and_priority: 0xC3,
+            single_response: ActionResponseWithOptionalData {
+                result: ActionResult::Success,
+                return_parameters: Some(GetDataResult::Data(CosemData::Unsigned(1))),
+            },
+        });
+        let mut bytes = get.to_bytes().unwrap();
+        bytes.extend(set.to_bytes().unwrap());
+        bytes.extend(action.to_bytes().unwrap());
+
+        let (parsed, rest) = GetRequest::parse(&bytes).unwrap();
+        assert_eq!(parsed, get);
+        let (parsed, rest) = SetRequest::parse(rest).unwrap();
+        assert_eq!(parsed, set);
+        let (parsed, rest) = ActionResponse::parse(rest).unwrap();
+        assert_eq!(parsed, action);
+        assert!(rest.is_empty());
+
+        let apdus = split_apdus(&bytes);
+        assert_eq!(apdus.len(), 3);
+        assert_eq!(apdus[1], set.to_bytes().unwrap().as_slice());
+        assert_eq!(
+            split_apdus(&[0x60, 0x01, 0xC0]),
+            vec![&[0x60, 0x01, 0xC0][..]]
+        );
+    }
+
     #[test]
     fn test_initiate_request_round_trip() {
         let req = InitiateRequest {
@@ -465,6 +1277,177 @@ mod tests {
         let decoded_from_ui = InitiateResponse::from_user_information(&user_information).unwrap();
         assert_eq!(res, decoded_from_ui);
     }
+
+    #[test]
+    fn test_confirmed_service_error_round_trip() {
+        let error = ConfirmedServiceError::InitiateError(InitiateError::PduSizeTooShort);
+        let user_information = error.to_user_information().unwrap();
+        assert_eq!(user_information, vec![0x04, 0x04, 0x0E, 0x01, 0x06, 0x03]);
+        assert_eq!(
+            ConfirmedServiceError::from_user_information(&user_information).unwrap(),
+            error
+        );
+        assert!(ConfirmedServiceError::from_bytes_with(
+            &[0x0E, 0x01, 0x06, 0x01, 0x00],
+            ParseMode::Strict
+        )
+        .is_err());
+        assert!(InitiateResponse::from_user_information(&user_information).is_err());
+    }
+
+    #[test]
+    fn test_ciphered_initiate_user_information() {
+        let key = [0x11; 16];
+        let request = AssociationParameters::default().to_initiate_request();
+        let user_information = request.to_ciphered_user_information(&key).unwrap();
+        assert_eq!(&user_information[2..3], &[0x21]);
+        assert_eq!(
+            InitiateRequest::from_ciphered_user_information_with(
+                &user_information,
+                &key,
+                ParseMode::Strict
+            )
+            .unwrap(),
+            request
+        );
+        assert!(InitiateRequest::from_user_information(&user_information).is_err());
+        assert!(matches!(
+            InitiateRequest::from_ciphered_user_information_with(
+                &user_information,
+                &[0x22; 16],
+                ParseMode::Strict
+            ),
+            Err(DlmsError::Security)
+        ));
+        assert!(InitiateResponse::from_ciphered_user_information_with(
+            &user_information,
+            &key,
+            ParseMode::Strict
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_exception_response_round_trip() {
+        let exception = ExceptionResponse {
+            state_error: StateError::ServiceNotAllowed,
+            service_error: ServiceError::OperationNotPossible,
+        };
+        let bytes = exception.to_bytes().unwrap();
+        assert_eq!(bytes, vec![0xD8, 0x01, 0x01]);
+        assert_eq!(ExceptionResponse::from_bytes(&bytes).unwrap(), exception);
+
+        let exception = ExceptionResponse {
+            state_error: StateError::ServiceUnknown,
+            service_error: ServiceError::InvocationCounterError(0x0102_0304),
+        };
+        let bytes = exception.to_bytes().unwrap();
+        assert_eq!(bytes, vec![0xD8, 0x02, 0x06, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(ExceptionResponse::from_bytes(&bytes).unwrap(), exception);
+
+        assert!(ExceptionResponse::from_bytes(&[0xD8, 0x01, 0x06, 0x00]).is_err());
+        assert!(
+            ExceptionResponse::from_bytes_with(&[0xD8, 0x01, 0x01, 0x00], ParseMode::Strict)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_strict_decoding_accepts_only_the_canonical_encoding() {
+        let set = SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: [0, 0, 96, 1, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+            value: CosemData::Structure(vec![
+                CosemData::Boolean(true),
+                CosemData::OctetString(vec![0xAB; 0x90]),
+            ]),
+        });
+        let signing = GeneralSigning {
+            header: GeneralProtectionHeader {
+                transaction_id: vec![0x00, 0x00, 0x00, 0x01],
+                originator_system_title: b"HEADEND1".to_vec(),
+                recipient_system_title: b"METER001".to_vec(),
+                ..GeneralProtectionHeader::default()
+            },
+            content: set.to_bytes().unwrap(),
+            signature: vec![0x55; 64],
+        };
+        let bytes = signing.to_bytes().unwrap();
+        let decoded = GeneralSigning::from_bytes_with(&bytes, ParseMode::Strict).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        let content = SetRequest::from_bytes_with(&decoded.content, ParseMode::Strict).unwrap();
+        assert_eq!(content.to_bytes().unwrap(), decoded.content);
+
+        let ciphering = GeneralCiphering {
+            header: signing.header.clone(),
+            key_info: Some(KeyInfo::AgreedKey {
+                key_parameters: vec![0x01],
+                key_ciphered_data: vec![0xAA; 0x100],
+            }),
+            ciphered_content: bytes.clone(),
+        };
+        let ciphered = ciphering.to_bytes().unwrap();
+        let decoded = GeneralCiphering::from_bytes_with(&ciphered, ParseMode::Strict).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), ciphered);
+
+        let notification = Notification::Data(DataNotification {
+            long_invoke_id_and_priority: 1,
+            date_time: Some(vec![0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0]),
+            notification_body: CosemData::Array(vec![CosemData::Boolean(false); 3]),
+        });
+        let notified = notification.to_bytes().unwrap();
+        let decoded = Notification::from_bytes_with(&notified, ParseMode::Strict).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), notified);
+
+        // Other encodings of the same PDUs: a padding byte, a long-form
+        // length and a boolean other than 0 or 1.
+        let mut padded = bytes.clone();
+        padded.push(0x00);
+        let mut long_form = vec![bytes[0], 0x81];
+        long_form.extend_from_slice(&bytes[1..]);
+        let mut boolean = notified.clone();
+        let last = boolean.len() - 1;
+        boolean[last] = 0x02;
+        for other in [&padded, &long_form] {
+            assert!(GeneralSigning::from_bytes_with(other, ParseMode::Strict).is_err());
+            let lenient = GeneralSigning::from_bytes(other).unwrap();
+            assert_eq!(lenient.to_bytes().unwrap(), bytes);
+        }
+        assert!(Notification::from_bytes_with(&boolean, ParseMode::Strict).is_err());
+        assert!(Notification::from_bytes(&boolean).is_ok());
+
+        // Optional initiate fields: flags other than 0 and 1, and
+        // response-allowed sent with its default value.
+        let request = InitiateRequest {
+            dedicated_key: None,
+            response_allowed: true,
+            proposed_quality_of_service: None,
+            proposed_dlms_version_number: 6,
+            proposed_conformance: Conformance { value: 0x0000_1E1D },
+            client_max_receive_pdu_size: 0xFFFF,
+        };
+        let initiate = request.to_bytes().unwrap();
+        let decoded = InitiateRequest::from_bytes_with(&initiate, ParseMode::Strict).unwrap();
+        assert_eq!(decoded.to_bytes().unwrap(), initiate);
+        let explicit_default = [&[0x01, 0x00, 0x01, 0x01][..], &initiate[3..]].concat();
+        let qos_flag = [&[0x01, 0x00, 0x00, 0x02, 0x05][..], &initiate[4..]].concat();
+        for other in [&explicit_default, &qos_flag] {
+            assert!(InitiateRequest::from_bytes_with(other, ParseMode::Strict).is_err());
+            assert!(InitiateRequest::from_bytes(other).is_ok());
+        }
+        assert_eq!(
+            InitiateRequest::from_bytes(&explicit_default)
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            initiate
+        );
+    }
 }
 
 // --- Get-Response ---
@@ -511,7 +1494,30 @@ impl From<DataAccessResult> for u8 {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl From<u8> for DataAccessResult {
+    fn from(val: u8) -> Self {
+        match val {
+            0 => DataAccessResult::Success,
+            1 => DataAccessResult::HardwareFault,
+            2 => DataAccessResult::TemporaryFailure,
+            3 => DataAccessResult::ReadWriteDenied,
+            4 => DataAccessResult::ObjectUndefined,
+            5 => DataAccessResult::ObjectClassInconsistent,
+            6 => DataAccessResult::ObjectUnavailable,
+            7 => DataAccessResult::TypeUnmatched,
+            8 => DataAccessResult::ScopeOfAccessViolated,
+            9 => DataAccessResult::DataBlockUnavailable,
+            10 => DataAccessResult::LongGetAborted,
+            11 => DataAccessResult::NoLongGetInProgress,
+            12 => DataAccessResult::LongSetAborted,
+            13 => DataAccessResult::NoLongSetInProgress,
+            14 => DataAccessResult::DataBlockNumberInvalid,
+            reason => DataAccessResult::OtherReason(reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum GetDataResult {
     Data(CosemData),
     DataAccessResult(DataAccessResult),
@@ -527,7 +1533,15 @@ pub struct GetResponseNormal {
 pub struct DataBlockG {
     pub last_block: bool,
     pub block_number: u32,
-    pub raw_data: Vec<u8>,
+    pub result: DataBlockResult,
+}
+
+// A block carries either part of the encoded value or the
+// data-access-result that ends the transfer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataBlockResult {
+    RawData(Vec<u8>),
+    DataAccessResult(DataAccessResult),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -554,7 +1568,8 @@ impl GetResponse {
         let mut bytes = Vec::new();
         match self {
             GetResponse::Normal(res) => {
-                bytes.push(196); // get-response-normal
+                bytes.push(196); // get-response
+                bytes.push(1); // get-response-normal
                 bytes.push(res.invoke_id_and_priority);
                 match &res.result {
                     GetDataResult::Data(data) => {
@@ -568,48 +1583,63 @@ impl GetResponse {
                 }
             }
             GetResponse::WithList(res) => {
-                bytes.push(198); // get-response-with-list
+                bytes.push(196); // get-response
+                bytes.push(3); // get-response-with-list
                 bytes.push(res.invoke_id_and_priority);
-                bytes.push(res.result.len() as u8);
+                encode_object_count(res.result.len(), &mut bytes);
                 for item in &res.result {
-                    match item {
-                        GetDataResult::Data(data) => {
-                            bytes.push(0); // data
-                            encode_data(data, &mut bytes)?;
-                        }
-                        GetDataResult::DataAccessResult(dar) => {
-                            bytes.push(1); // data-access-result
-                            bytes.push(dar.clone().into());
-                        }
-                    }
+                    encode_get_data_result(item, &mut bytes)?;
                 }
             }
             GetResponse::WithDataBlock(res) => {
-                bytes.push(197); // get-response-with-datablock
+                bytes.push(196); // get-response
+                bytes.push(2); // get-response-with-datablock
                 bytes.push(res.invoke_id_and_priority);
                 bytes.push(res.result.last_block as u8);
                 bytes.extend_from_slice(&res.result.block_number.to_be_bytes());
-                bytes.extend_from_slice(&res.result.raw_data);
+                match &res.result.result {
+                    DataBlockResult::RawData(raw_data) => {
+                        bytes.push(0); // raw-data
+                        encode_object_count(raw_data.len(), &mut bytes);
+                        bytes.extend_from_slice(raw_data);
+                    }
+                    DataBlockResult::DataAccessResult(dar) => {
+                        bytes.push(1); // data-access-result
+                        bytes.push(dar.clone().into());
+                    }
+                }
             }
         }
         Ok(bytes)
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
-        if bytes.is_empty() {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 2 {
             return Err(DlmsError::Xdlms);
         }
-        let (tag, rest) = bytes.split_at(1);
-        match tag[0] {
-            196 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (result_type, rest) = rest.split_at(1);
-                let result = if result_type[0] == 0 {
-                    let (data, _) = decode_data(rest)?;
-                    GetDataResult::Data(data)
+        let (tag, rest) = take_octets(bytes, 2)?;
+        match (tag[0], tag[1]) {
+            (196, 1) => {
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (result_type, rest) = take_octets(rest, 1)?;
+                let (result, rest) = if result_type[0] == 0 {
+                    let (data, rest) = decode_data_with(rest, mode)?;
+                    (GetDataResult::Data(data), rest)
                 } else {
-                    let (dar, _) = rest.split_at(1);
-                    GetDataResult::DataAccessResult(match dar[0] {
+                    let (dar, rest) = take_octets(rest, 1)?;
+                    let dar = match dar[0] {
                         0 => DataAccessResult::Success,
                         1 => DataAccessResult::HardwareFault,
                         2 => DataAccessResult::TemporaryFailure,
@@ -626,70 +1656,63 @@ impl GetResponse {
                         13 => DataAccessResult::NoLongSetInProgress,
                         14 => DataAccessResult::DataBlockNumberInvalid,
                         reason => DataAccessResult::OtherReason(reason),
-                    })
-                };
-                Ok(GetResponse::Normal(GetResponseNormal {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    result,
-                }))
-            }
-            198 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (len, mut rest) = rest.split_at(1);
-                let mut result = Vec::new();
-                for _ in 0..len[0] {
-                    let (result_type, r) = rest.split_at(1);
-                    rest = r;
-                    let item = if result_type[0] == 0 {
-                        let (data, r) = decode_data(rest)?;
-                        rest = r;
-                        GetDataResult::Data(data)
-                    } else {
-                        let (dar, r) = rest.split_at(1);
-                        rest = r;
-                        GetDataResult::DataAccessResult(match dar[0] {
-                            0 => DataAccessResult::Success,
-                            1 => DataAccessResult::HardwareFault,
-                            2 => DataAccessResult::TemporaryFailure,
-                            3 => DataAccessResult::ReadWriteDenied,
-                            4 => DataAccessResult::ObjectUndefined,
-                            5 => DataAccessResult::ObjectClassInconsistent,
-                            6 => DataAccessResult::ObjectUnavailable,
-                            7 => DataAccessResult::TypeUnmatched,
-                            8 => DataAccessResult::ScopeOfAccessViolated,
-                            9 => DataAccessResult::DataBlockUnavailable,
-                            10 => DataAccessResult::LongGetAborted,
-                            11 => DataAccessResult::NoLongGetInProgress,
-                            12 => DataAccessResult::LongSetAborted,
-                            13 => DataAccessResult::NoLongSetInProgress,
-                            14 => DataAccessResult::DataBlockNumberInvalid,
-                            reason => DataAccessResult::OtherReason(reason),
-                        })
                     };
-                    result.push(item);
-                }
-                Ok(GetResponse::WithList(GetResponseWithList {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    result,
-                }))
+                    (GetDataResult::DataAccessResult(dar), rest)
+                };
+                Ok((
+                    GetResponse::Normal(GetResponseNormal {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        result,
+                    }),
+                    rest,
+                ))
             }
-            197 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (last_block, rest) = rest.split_at(1);
-                let (block_number, rest) = rest.split_at(4);
-                let raw_data = rest.to_vec();
-
-                let mut block_number_bytes = [0u8; 4];
-                block_number_bytes.copy_from_slice(block_number);
-
-                Ok(GetResponse::WithDataBlock(GetResponseWithDatablock {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    result: DataBlockG {
-                        last_block: last_block[0] != 0,
-                        block_number: u32::from_be_bytes(block_number_bytes),
-                        raw_data,
-                    },
-                }))
+            (196, 3) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (result, rest) = decode_get_data_results(rest, mode)?;
+                Ok((
+                    GetResponse::WithList(GetResponseWithList {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        result,
+                    }),
+                    rest,
+                ))
+            }
+            (196, 2) => {
+                // invoke-id, last-block, block-number and the result choice.
+                let [invoke_id_and_priority, last_block, b0, b1, b2, b3, choice, rest @ ..] = rest
+                else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (result, rest) = match choice {
+                    0 => {
+                        let (len, consumed) = decode_object_count_with(rest, mode)?;
+                        let rest = &rest[consumed..];
+                        if rest.len() < len {
+                            return Err(DlmsError::Xdlms);
+                        }
+                        let (raw_data, rest) = take_octets(rest, len)?;
+                        (DataBlockResult::RawData(raw_data.to_vec()), rest)
+                    }
+                    1 => {
+                        let (dar, rest) = rest.split_first().ok_or(DlmsError::Xdlms)?;
+                        (DataBlockResult::DataAccessResult((*dar).into()), rest)
+                    }
+                    _ => return Err(DlmsError::Xdlms),
+                };
+                Ok((
+                    GetResponse::WithDataBlock(GetResponseWithDatablock {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        result: DataBlockG {
+                            last_block: *last_block != 0,
+                            block_number: u32::from_be_bytes([*b0, *b1, *b2, *b3]),
+                            result,
+                        },
+                    }),
+                    rest,
+                ))
             }
             _ => Err(DlmsError::Xdlms),
         }
@@ -705,6 +1728,57 @@ pub struct SetRequestNormal {
     pub value: CosemData,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataBlockSA {
+    pub last_block: bool,
+    pub block_number: u32,
+    pub raw_data: Vec<u8>,
+}
+
+impl DataBlockSA {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.last_block as u8);
+        bytes.extend_from_slice(&self.block_number.to_be_bytes());
+        encode_object_count(self.raw_data.len(), bytes);
+        bytes.extend_from_slice(&self.raw_data);
+    }
+
+    fn decode(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 5 {
+            return Err(DlmsError::Xdlms);
+        }
+        let mut block_number_bytes = [0u8; 4];
+        block_number_bytes.copy_from_slice(&bytes[1..5]);
+        let (len, consumed) = decode_object_count_with(&bytes[5..], mode)?;
+        let start = 5 + consumed;
+        if bytes.len() < start + len {
+            return Err(DlmsError::Xdlms);
+        }
+        Ok((
+            DataBlockSA {
+                last_block: bytes[0] != 0,
+                block_number: u32::from_be_bytes(block_number_bytes),
+                raw_data: bytes[start..start + len].to_vec(),
+            },
+            &bytes[start + len..],
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetRequestWithFirstDatablock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub cosem_attribute_descriptor: CosemAttributeDescriptor,
+    pub access_selection: Option<SelectiveAccessDescriptor>,
+    pub datablock: DataBlockSA,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetRequestWithDatablock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub datablock: DataBlockSA,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SetRequestWithList {
     pub invoke_id_and_priority: InvokeIdAndPriority,
@@ -715,6 +1789,8 @@ pub struct SetRequestWithList {
 #[derive(Debug, Clone, PartialEq)]
 pub enum SetRequest {
     Normal(SetRequestNormal),
+    WithFirstDatablock(SetRequestWithFirstDatablock),
+    WithDatablock(SetRequestWithDatablock),
     WithList(SetRequestWithList),
 }
 
@@ -723,7 +1799,8 @@ impl SetRequest {
         let mut bytes = Vec::new();
         match self {
             SetRequest::Normal(req) => {
-                bytes.push(193); // set-request-normal
+                bytes.push(193); // set-request
+                bytes.push(1); // set-request-normal
                 bytes.push(req.invoke_id_and_priority);
                 bytes.extend_from_slice(&req.cosem_attribute_descriptor.class_id.to_be_bytes());
                 bytes.extend_from_slice(&req.cosem_attribute_descriptor.instance_id);
@@ -737,27 +1814,70 @@ impl SetRequest {
                 }
                 encode_data(&req.value, &mut bytes)?;
             }
-            _ => return Err(DlmsError::Xdlms),
+            SetRequest::WithFirstDatablock(req) => {
+                bytes.push(193); // set-request
+                bytes.push(2); // set-request-with-first-datablock
+                bytes.push(req.invoke_id_and_priority);
+                bytes.extend_from_slice(&req.cosem_attribute_descriptor.class_id.to_be_bytes());
+                bytes.extend_from_slice(&req.cosem_attribute_descriptor.instance_id);
+                bytes.push(req.cosem_attribute_descriptor.attribute_id as u8);
+                if let Some(access_selection) = &req.access_selection {
+                    bytes.push(1); // access-selector
+                    bytes.push(access_selection.access_selector);
+                    encode_data(&access_selection.access_parameters, &mut bytes)?;
+                } else {
+                    bytes.push(0); // no access-selector
+                }
+                req.datablock.encode(&mut bytes);
+            }
+            SetRequest::WithDatablock(req) => {
+                bytes.push(193); // set-request
+                bytes.push(3); // set-request-with-datablock
+                bytes.push(req.invoke_id_and_priority);
+                req.datablock.encode(&mut bytes);
+            }
+            SetRequest::WithList(req) => {
+                bytes.push(193); // set-request
+                bytes.push(4); // set-request-with-list
+                bytes.push(req.invoke_id_and_priority);
+                encode_descriptor_list(&req.attribute_descriptor_list, &mut bytes);
+                encode_object_count(req.value_list.len(), &mut bytes);
+                for value in &req.value_list {
+                    encode_data(value, &mut bytes)?;
+                }
+            }
         }
         Ok(bytes)
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
-        if bytes.is_empty() {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 2 {
             return Err(DlmsError::Xdlms);
         }
-        let (tag, rest) = bytes.split_at(1);
-        match tag[0] {
-            193 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (class_id, rest) = rest.split_at(2);
-                let (instance_id, rest) = rest.split_at(6);
-                let (attribute_id, rest) = rest.split_at(1);
-                let (has_access_selection, rest) = rest.split_at(1);
-
-                let (access_selection, rest) = if has_access_selection[0] == 1 {
-                    let (access_selector, rest) = rest.split_at(1);
-                    let (access_parameters, rest) = decode_data(rest)?;
+        let (tag, rest) = take_octets(bytes, 2)?;
+        match (tag[0], tag[1]) {
+            (193, 1) => {
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (class_id, rest) = take_octets(rest, 2)?;
+                let (instance_id, rest) = take_octets(rest, 6)?;
+                let (attribute_id, rest) = take_octets(rest, 1)?;
+                let (has_access_selection, rest) = take_octets(rest, 1)?;
+
+                let (access_selection, rest) = if presence_flag(has_access_selection[0], mode)? {
+                    let (access_selector, rest) = take_octets(rest, 1)?;
+                    let (access_parameters, rest) = decode_data_with(rest, mode)?;
                     (
                         Some(SelectiveAccessDescriptor {
                             access_selector: access_selector[0],
@@ -769,7 +1889,7 @@ impl SetRequest {
                     (None, rest)
                 };
 
-                let (value, _) = decode_data(rest)?;
+                let (value, rest) = decode_data_with(rest, mode)?;
 
                 let mut class_id_bytes = [0u8; 2];
                 class_id_bytes.copy_from_slice(class_id);
@@ -777,16 +1897,96 @@ impl SetRequest {
                 let mut instance_id_bytes = [0u8; 6];
                 instance_id_bytes.copy_from_slice(instance_id);
 
-                Ok(SetRequest::Normal(SetRequestNormal {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    cosem_attribute_descriptor: CosemAttributeDescriptor {
-                        class_id: u16::from_be_bytes(class_id_bytes),
-                        instance_id: instance_id_bytes,
-                        attribute_id: attribute_id[0] as i8,
-                    },
-                    access_selection,
-                    value,
-                }))
+                Ok((
+                    SetRequest::Normal(SetRequestNormal {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        cosem_attribute_descriptor: CosemAttributeDescriptor {
+                            class_id: u16::from_be_bytes(class_id_bytes),
+                            instance_id: instance_id_bytes,
+                            attribute_id: attribute_id[0] as i8,
+                        },
+                        access_selection,
+                        value,
+                    }),
+                    rest,
+                ))
+            }
+            (193, 2) => {
+                if rest.len() < 11 {
+                    return Err(DlmsError::Xdlms);
+                }
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (class_id, rest) = take_octets(rest, 2)?;
+                let (instance_id, rest) = take_octets(rest, 6)?;
+                let (attribute_id, rest) = take_octets(rest, 1)?;
+                let (has_access_selection, rest) = take_octets(rest, 1)?;
+
+                let (access_selection, rest) = if presence_flag(has_access_selection[0], mode)? {
+                    let (access_selector, rest) = take_octets(rest, 1)?;
+                    let (access_parameters, rest) = decode_data_with(rest, mode)?;
+                    (
+                        Some(SelectiveAccessDescriptor {
+                            access_selector: access_selector[0],
+                            access_parameters,
+                        }),
+                        rest,
+                    )
+                } else {
+                    (None, rest)
+                };
+
+                let mut class_id_bytes = [0u8; 2];
+                class_id_bytes.copy_from_slice(class_id);
+
+                let mut instance_id_bytes = [0u8; 6];
+                instance_id_bytes.copy_from_slice(instance_id);
+
+                let (datablock, rest) = DataBlockSA::decode(rest, mode)?;
+                Ok((
+                    SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        cosem_attribute_descriptor: CosemAttributeDescriptor {
+                            class_id: u16::from_be_bytes(class_id_bytes),
+                            instance_id: instance_id_bytes,
+                            attribute_id: attribute_id[0] as i8,
+                        },
+                        access_selection,
+                        datablock,
+                    }),
+                    rest,
+                ))
+            }
+            (193, 3) => {
+                if rest.is_empty() {
+                    return Err(DlmsError::Xdlms);
+                }
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (datablock, rest) = DataBlockSA::decode(rest, mode)?;
+                Ok((
+                    SetRequest::WithDatablock(SetRequestWithDatablock {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        datablock,
+                    }),
+                    rest,
+                ))
+            }
+            (193, 4) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (attribute_descriptor_list, rest) = decode_sequence(rest, mode, |bytes| {
+                    decode_descriptor_with_selection(bytes, mode)
+                })?;
+                let (value_list, rest) =
+                    decode_sequence(rest, mode, |bytes| decode_data_with(bytes, mode))?;
+                Ok((
+                    SetRequest::WithList(SetRequestWithList {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        attribute_descriptor_list,
+                        value_list,
+                    }),
+                    rest,
+                ))
             }
             _ => Err(DlmsError::Xdlms),
         }
@@ -843,6 +2043,10 @@ impl InitiateRequest {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
         if bytes.is_empty() || bytes[0] != 0x01 {
             return Err(DlmsError::Xdlms);
         }
@@ -854,10 +2058,10 @@ impl InitiateRequest {
 
         let dedicated_key_flag = bytes[index];
         index += 1;
-        let dedicated_key = if dedicated_key_flag == 0 {
+        let dedicated_key = if !initiate_flag(dedicated_key_flag, mode)? {
             None
         } else {
-            let (len, consumed) = decode_object_count(&bytes[index..])?;
+            let (len, consumed) = decode_object_count_with(&bytes[index..], mode)?;
             index += consumed;
             if bytes.len() < index + len {
                 return Err(DlmsError::Xdlms);
@@ -872,7 +2076,7 @@ impl InitiateRequest {
         }
         let response_flag = bytes[index];
         index += 1;
-        let response_allowed = if response_flag == 0 {
+        let response_allowed = if !initiate_flag(response_flag, mode)? {
             true
         } else {
             if index >= bytes.len() {
@@ -880,6 +2084,10 @@ impl InitiateRequest {
             }
             let value = bytes[index];
             index += 1;
+            // TRUE is the default, encoded by leaving the field out.
+            if mode == ParseMode::Strict && value != 0 {
+                return Err(DlmsError::Xdlms);
+            }
             value != 0
         };
 
@@ -888,7 +2096,7 @@ impl InitiateRequest {
         }
         let qos_flag = bytes[index];
         index += 1;
-        let proposed_quality_of_service = if qos_flag == 0 {
+        let proposed_quality_of_service = if !initiate_flag(qos_flag, mode)? {
             None
         } else {
             if index >= bytes.len() {
@@ -943,7 +2151,7 @@ impl InitiateRequest {
         let client_max_receive_pdu_size = u16::from_be_bytes([bytes[index], bytes[index + 1]]);
         index += 2;
 
-        if index != bytes.len() {
+        if mode == ParseMode::Strict && index != bytes.len() {
             return Err(DlmsError::Xdlms);
         }
 
@@ -967,11 +2175,28 @@ impl InitiateRequest {
     }
 
     pub fn from_user_information(bytes: &[u8]) -> Result<Self, DlmsError> {
-        let (apdu, consumed) = decode_octet_string(bytes)?;
-        if consumed != bytes.len() {
+        Self::from_user_information_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_user_information_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        let (apdu, consumed) = decode_octet_string(bytes, mode)?;
+        if mode == ParseMode::Strict && consumed != bytes.len() {
             return Err(DlmsError::Xdlms);
         }
-        InitiateRequest::from_bytes(apdu)
+        InitiateRequest::from_bytes_with(apdu, mode)
+    }
+
+    pub fn to_ciphered_user_information(&self, key: &[u8]) -> Result<Vec<u8>, DlmsError> {
+        cipher_user_information(GLO_INITIATE_REQUEST_TAG, &self.to_bytes()?, key)
+    }
+
+    pub fn from_ciphered_user_information_with(
+        bytes: &[u8],
+        key: &[u8],
+        mode: ParseMode,
+    ) -> Result<Self, DlmsError> {
+        let apdu = decipher_user_information(GLO_INITIATE_REQUEST_TAG, bytes, key, mode)?;
+        InitiateRequest::from_bytes_with(&apdu, mode)
     }
 }
 
@@ -1010,6 +2235,10 @@ impl InitiateResponse {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
         if bytes.is_empty() || bytes[0] != 0x08 {
             return Err(DlmsError::Xdlms);
         }
@@ -1021,7 +2250,7 @@ impl InitiateResponse {
 
         let qos_flag = bytes[index];
         index += 1;
-        let negotiated_quality_of_service = if qos_flag == 0 {
+        let negotiated_quality_of_service = if !initiate_flag(qos_flag, mode)? {
             None
         } else {
             if index >= bytes.len() {
@@ -1082,7 +2311,7 @@ impl InitiateResponse {
         let vaa_name = u16::from_be_bytes([bytes[index], bytes[index + 1]]);
         index += 2;
 
-        if index != bytes.len() {
+        if mode == ParseMode::Strict && index != bytes.len() {
             return Err(DlmsError::Xdlms);
         }
 
@@ -1105,184 +2334,779 @@ impl InitiateResponse {
     }
 
     pub fn from_user_information(bytes: &[u8]) -> Result<Self, DlmsError> {
-        let (apdu, consumed) = decode_octet_string(bytes)?;
-        if consumed != bytes.len() {
+        Self::from_user_information_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_user_information_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        let (apdu, consumed) = decode_octet_string(bytes, mode)?;
+        if mode == ParseMode::Strict && consumed != bytes.len() {
             return Err(DlmsError::Xdlms);
         }
-        InitiateResponse::from_bytes(apdu)
+        InitiateResponse::from_bytes_with(apdu, mode)
+    }
+
+    pub fn to_ciphered_user_information(&self, key: &[u8]) -> Result<Vec<u8>, DlmsError> {
+        cipher_user_information(GLO_INITIATE_RESPONSE_TAG, &self.to_bytes()?, key)
+    }
+
+    pub fn from_ciphered_user_information_with(
+        bytes: &[u8],
+        key: &[u8],
+        mode: ParseMode,
+    ) -> Result<Self, DlmsError> {
+        let apdu = decipher_user_information(GLO_INITIATE_RESPONSE_TAG, bytes, key, mode)?;
+        InitiateResponse::from_bytes_with(&apdu, mode)
     }
 }
 
-impl AssociationParameters {
-    pub fn to_initiate_request(&self) -> InitiateRequest {
-        InitiateRequest {
-            dedicated_key: None,
-            response_allowed: true,
-            proposed_quality_of_service: self.quality_of_service,
-            proposed_dlms_version_number: self.dlms_version,
-            proposed_conformance: self.conformance.clone(),
-            client_max_receive_pdu_size: self.max_receive_pdu_size,
-        }
+// --- glo-initiateRequest / glo-initiateResponse ---
+// In a ciphered association the initiate PDUs in the user-information of the
+// release APDUs are ciphered with the key of the association.
+const GLO_INITIATE_REQUEST_TAG: u8 = 0x21;
+const GLO_INITIATE_RESPONSE_TAG: u8 = 0x28;
+const INITIATE_REQUEST_TAG: u8 = 0x01;
+const INITIATE_RESPONSE_TAG: u8 = 0x08;
+// AES-GCM nonce and tag around the ciphered APDU.
+const GLO_CIPHERING_OVERHEAD: usize = 12 + 16;
+
+fn cipher_user_information(tag: u8, apdu: &[u8], key: &[u8]) -> Result<Vec<u8>, DlmsError> {
+    let ciphered = hls_encrypt(apdu, key).map_err(|_| DlmsError::Security)?;
+    Ok(glo_user_information(tag, &ciphered))
+}
+
+fn glo_user_information(tag: u8, ciphered: &[u8]) -> Vec<u8> {
+    let mut glo = vec![tag];
+    encode_axdr_octet_string(ciphered, &mut glo);
+    let mut buffer = Vec::with_capacity(glo.len() + 2);
+    buffer.push(0x04);
+    encode_object_count(glo.len(), &mut buffer);
+    buffer.extend_from_slice(&glo);
+    buffer
+}
+
+fn decipher_user_information(
+    tag: u8,
+    bytes: &[u8],
+    key: &[u8],
+    mode: ParseMode,
+) -> Result<Vec<u8>, DlmsError> {
+    let (glo, consumed) = decode_octet_string(bytes, mode)?;
+    if mode == ParseMode::Strict && consumed != bytes.len() {
+        return Err(DlmsError::Xdlms);
+    }
+    let [glo_tag, rest @ ..] = glo else {
+        return Err(DlmsError::Xdlms);
+    };
+    if *glo_tag != tag {
+        return Err(DlmsError::Xdlms);
     }
+    decipher_glo(rest, key, mode)
+}
 
-    pub fn to_initiate_response(&self, negotiated_conformance: Conformance) -> InitiateResponse {
-        InitiateResponse {
-            negotiated_quality_of_service: self.quality_of_service,
-            negotiated_dlms_version_number: self.dlms_version,
-            negotiated_conformance,
-            server_max_receive_pdu_size: self.max_receive_pdu_size,
-            vaa_name: 0x0007,
-        }
+fn decode_glo(bytes: &[u8], mode: ParseMode) -> Result<Vec<u8>, DlmsError> {
+    let (ciphered, rest) = decode_axdr_octet_string(bytes, mode)?;
+    if mode == ParseMode::Strict && !rest.is_empty() {
+        return Err(DlmsError::Xdlms);
+    }
+    if ciphered.len() < GLO_CIPHERING_OVERHEAD {
+        return Err(DlmsError::Security);
     }
+    Ok(ciphered)
 }
 
-// --- Set-Response ---
-#[derive(Debug, Clone, PartialEq)]
-pub struct SetResponseNormal {
-    pub invoke_id_and_priority: InvokeIdAndPriority,
-    pub result: DataAccessResult,
+fn decipher_glo(bytes: &[u8], key: &[u8], mode: ParseMode) -> Result<Vec<u8>, DlmsError> {
+    let ciphered = decode_glo(bytes, mode)?;
+    hls_decrypt(&ciphered, key).map_err(|_| DlmsError::Security)
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct SetResponseWithList {
-    pub invoke_id_and_priority: InvokeIdAndPriority,
-    pub result: Vec<DataAccessResult>,
+// Splits the user-information of a release APDU into the tag of the initiate
+// PDU it holds and the rest of that PDU.
+fn release_user_information(bytes: &[u8], mode: ParseMode) -> Result<(u8, &[u8]), DlmsError> {
+    let (apdu, consumed) = decode_octet_string(bytes, mode)?;
+    if mode == ParseMode::Strict && consumed != bytes.len() {
+        return Err(DlmsError::Xdlms);
+    }
+    match apdu {
+        [tag, rest @ ..] => Ok((*tag, rest)),
+        [] => Err(DlmsError::Xdlms),
+    }
 }
 
+// --- Release user-information ---
+// The user-information of an A-RLRQ holds an InitiateRequest and that of the
+// A-RLRE the InitiateResponse to it; a ciphered association exchanges them
+// as glo-initiateRequest / glo-initiateResponse.
 #[derive(Debug, Clone, PartialEq)]
-pub enum SetResponse {
-    Normal(SetResponseNormal),
-    WithList(SetResponseWithList),
+pub enum ReleaseRequestInformation {
+    Initiate(InitiateRequest),
+    // The ciphered InitiateRequest: nonce, ciphertext and authentication tag.
+    Ciphered(Vec<u8>),
 }
 
-impl SetResponse {
-    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
-        let mut bytes = Vec::new();
+impl ReleaseRequestInformation {
+    pub fn ciphered(request: &InitiateRequest, key: &[u8]) -> Result<Self, DlmsError> {
+        let ciphered = hls_encrypt(&request.to_bytes()?, key).map_err(|_| DlmsError::Security)?;
+        Ok(ReleaseRequestInformation::Ciphered(ciphered))
+    }
+
+    pub fn to_user_information(&self) -> Result<Vec<u8>, DlmsError> {
         match self {
-            SetResponse::Normal(res) => {
-                bytes.push(197); // set-response-normal
-                bytes.push(res.invoke_id_and_priority);
-                bytes.push(res.result.clone().into());
+            ReleaseRequestInformation::Initiate(request) => request.to_user_information(),
+            ReleaseRequestInformation::Ciphered(ciphered) => {
+                Ok(glo_user_information(GLO_INITIATE_REQUEST_TAG, ciphered))
             }
-            _ => return Err(DlmsError::Xdlms),
         }
-        Ok(bytes)
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
-        if bytes.is_empty() {
-            return Err(DlmsError::Xdlms);
-        }
-        let (tag, rest) = bytes.split_at(1);
-        match tag[0] {
-            197 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (result, _) = rest.split_at(1);
-                Ok(SetResponse::Normal(SetResponseNormal {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    result: match result[0] {
-                        0 => DataAccessResult::Success,
-                        1 => DataAccessResult::HardwareFault,
-                        2 => DataAccessResult::TemporaryFailure,
-                        3 => DataAccessResult::ReadWriteDenied,
-                        4 => DataAccessResult::ObjectUndefined,
-                        5 => DataAccessResult::ObjectClassInconsistent,
-                        6 => DataAccessResult::ObjectUnavailable,
-                        7 => DataAccessResult::TypeUnmatched,
-                        8 => DataAccessResult::ScopeOfAccessViolated,
-                        9 => DataAccessResult::DataBlockUnavailable,
-                        10 => DataAccessResult::LongGetAborted,
-                        11 => DataAccessResult::NoLongGetInProgress,
-                        12 => DataAccessResult::LongSetAborted,
-                        13 => DataAccessResult::NoLongSetInProgress,
-                        14 => DataAccessResult::DataBlockNumberInvalid,
-                        reason => DataAccessResult::OtherReason(reason),
-                    },
-                }))
+    pub fn from_user_information_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        match release_user_information(bytes, mode)? {
+            (GLO_INITIATE_REQUEST_TAG, glo) => {
+                Ok(ReleaseRequestInformation::Ciphered(decode_glo(glo, mode)?))
             }
+            (INITIATE_REQUEST_TAG, _) => Ok(ReleaseRequestInformation::Initiate(
+                InitiateRequest::from_user_information_with(bytes, mode)?,
+            )),
             _ => Err(DlmsError::Xdlms),
         }
     }
-}
 
-// --- Action-Request ---
-#[derive(Debug, Clone, PartialEq)]
-pub struct ActionRequestNormal {
-    pub invoke_id_and_priority: InvokeIdAndPriority,
-    pub cosem_method_descriptor: CosemMethodDescriptor,
-    pub method_invocation_parameters: Option<CosemData>,
+    // The InitiateRequest of the release, deciphered with the key of a
+    // ciphered association.
+    pub fn initiate_request(
+        &self,
+        key: Option<&[u8]>,
+        mode: ParseMode,
+    ) -> Result<InitiateRequest, ReleaseError> {
+        match (self, key) {
+            (ReleaseRequestInformation::Initiate(request), None) => Ok(request.clone()),
+            (ReleaseRequestInformation::Ciphered(ciphered), Some(key)) => {
+                let apdu = hls_decrypt(ciphered, key).map_err(|_| ReleaseError::Undecipherable)?;
+                InitiateRequest::from_bytes_with(&apdu, mode)
+                    .map_err(|_| ReleaseError::Undecipherable)
+            }
+            _ => Err(ReleaseError::CipheringMismatch),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct ActionRequestWithList {
-    pub invoke_id_and_priority: InvokeIdAndPriority,
-    pub cosem_method_descriptor_list: Vec<CosemMethodDescriptor>,
-    pub method_invocation_parameters: Vec<CosemData>,
+pub enum ReleaseResponseInformation {
+    Initiate(InitiateResponse),
+    // The ciphered InitiateResponse: nonce, ciphertext and authentication tag.
+    Ciphered(Vec<u8>),
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ActionRequest {
-    Normal(ActionRequestNormal),
-    WithList(ActionRequestWithList),
-}
+impl ReleaseResponseInformation {
+    pub fn ciphered(response: &InitiateResponse, key: &[u8]) -> Result<Self, DlmsError> {
+        let ciphered = hls_encrypt(&response.to_bytes()?, key).map_err(|_| DlmsError::Security)?;
+        Ok(ReleaseResponseInformation::Ciphered(ciphered))
+    }
 
-impl ActionRequest {
-    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
-        let mut bytes = Vec::new();
+    pub fn to_user_information(&self) -> Result<Vec<u8>, DlmsError> {
         match self {
-            ActionRequest::Normal(req) => {
-                bytes.push(195); // action-request-normal
-                bytes.push(req.invoke_id_and_priority);
-                bytes.extend_from_slice(&req.cosem_method_descriptor.class_id.to_be_bytes());
-                bytes.extend_from_slice(&req.cosem_method_descriptor.instance_id);
-                bytes.push(req.cosem_method_descriptor.method_id as u8);
-                if let Some(mip) = &req.method_invocation_parameters {
-                    bytes.push(1); // method-invocation-parameters
-                    encode_data(mip, &mut bytes)?;
-                } else {
-                    bytes.push(0); // no method-invocation-parameters
-                }
+            ReleaseResponseInformation::Initiate(response) => response.to_user_information(),
+            ReleaseResponseInformation::Ciphered(ciphered) => {
+                Ok(glo_user_information(GLO_INITIATE_RESPONSE_TAG, ciphered))
             }
-            _ => return Err(DlmsError::Xdlms),
         }
-        Ok(bytes)
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
-        if bytes.is_empty() {
-            return Err(DlmsError::Xdlms);
+    pub fn from_user_information_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        match release_user_information(bytes, mode)? {
+            (GLO_INITIATE_RESPONSE_TAG, glo) => {
+                Ok(ReleaseResponseInformation::Ciphered(decode_glo(glo, mode)?))
+            }
+            (INITIATE_RESPONSE_TAG, _) => Ok(ReleaseResponseInformation::Initiate(
+                InitiateResponse::from_user_information_with(bytes, mode)?,
+            )),
+            _ => Err(DlmsError::Xdlms),
         }
-        let (tag, rest) = bytes.split_at(1);
-        match tag[0] {
-            195 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (class_id, rest) = rest.split_at(2);
-                let (instance_id, rest) = rest.split_at(6);
-                let (method_id, rest) = rest.split_at(1);
-                let (has_mip, rest) = rest.split_at(1);
-
-                let method_invocation_parameters = if has_mip[0] == 1 {
-                    let (mip, _) = decode_data(rest)?;
-                    Some(mip)
-                } else {
-                    None
-                };
+    }
 
-                let mut class_id_bytes = [0u8; 2];
-                class_id_bytes.copy_from_slice(class_id);
+    pub fn initiate_response(
+        &self,
+        key: Option<&[u8]>,
+        mode: ParseMode,
+    ) -> Result<InitiateResponse, ReleaseError> {
+        match (self, key) {
+            (ReleaseResponseInformation::Initiate(response), None) => Ok(response.clone()),
+            (ReleaseResponseInformation::Ciphered(ciphered), Some(key)) => {
+                let apdu = hls_decrypt(ciphered, key).map_err(|_| ReleaseError::Undecipherable)?;
+                InitiateResponse::from_bytes_with(&apdu, mode)
+                    .map_err(|_| ReleaseError::Undecipherable)
+            }
+            _ => Err(ReleaseError::CipheringMismatch),
+        }
+    }
+}
+
+// Why the user-information of a release does not fit its association.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseError {
+    // A plain initiate PDU in a ciphered association, or a ciphered one in
+    // an association without a key.
+    CipheringMismatch,
+    // The glo-initiate PDU does not decipher with the key of the association.
+    Undecipherable,
+    // The InitiateResponse names another DLMS version or conformance than
+    // the association negotiated.
+    DlmsVersionMismatch,
+    ConformanceMismatch,
+}
+
+// --- ConfirmedServiceError ---
+// ServiceError initiate values, reported when the xDLMS context is refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitiateError {
+    Other,
+    DlmsVersionTooLow,
+    IncompatibleConformance,
+    PduSizeTooShort,
+    RefusedByTheVdeHandler,
+}
+
+impl From<InitiateError> for u8 {
+    fn from(val: InitiateError) -> Self {
+        match val {
+            InitiateError::Other => 0,
+            InitiateError::DlmsVersionTooLow => 1,
+            InitiateError::IncompatibleConformance => 2,
+            InitiateError::PduSizeTooShort => 3,
+            InitiateError::RefusedByTheVdeHandler => 4,
+        }
+    }
+}
+
+impl From<u8> for InitiateError {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => InitiateError::DlmsVersionTooLow,
+            2 => InitiateError::IncompatibleConformance,
+            3 => InitiateError::PduSizeTooShort,
+            4 => InitiateError::RefusedByTheVdeHandler,
+            _ => InitiateError::Other,
+        }
+    }
+}
+
+// Sent instead of an InitiateResponse in the user-information of an AARE
+// refusing the association. Only initiateError [1] with the initiate [6]
+// service error is supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmedServiceError {
+    InitiateError(InitiateError),
+}
+
+impl ConfirmedServiceError {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        match self {
+            ConfirmedServiceError::InitiateError(error) => {
+                Ok(vec![0x0E, 0x01, 0x06, u8::from(*error)])
+            }
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        match bytes {
+            [0x0E, 0x01, 0x06, error, rest @ ..] => {
+                if mode == ParseMode::Strict && !rest.is_empty() {
+                    return Err(DlmsError::Xdlms);
+                }
+                Ok(ConfirmedServiceError::InitiateError(InitiateError::from(
+                    *error,
+                )))
+            }
+            _ => Err(DlmsError::Xdlms),
+        }
+    }
+
+    pub fn to_user_information(&self) -> Result<Vec<u8>, DlmsError> {
+        let apdu = self.to_bytes()?;
+        let mut buffer = Vec::with_capacity(apdu.len() + 2);
+        buffer.push(0x04);
+        encode_object_count(apdu.len(), &mut buffer);
+        buffer.extend_from_slice(&apdu);
+        Ok(buffer)
+    }
+
+    pub fn from_user_information(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_user_information_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_user_information_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        let (apdu, consumed) = decode_octet_string(bytes, mode)?;
+        if mode == ParseMode::Strict && consumed != bytes.len() {
+            return Err(DlmsError::Xdlms);
+        }
+        ConfirmedServiceError::from_bytes_with(apdu, mode)
+    }
+}
+
+// --- Exception-Response ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    ServiceNotAllowed,
+    ServiceUnknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceError {
+    OperationNotPossible,
+    ServiceNotSupported,
+    OtherReason,
+    PduTooLong,
+    DecipheringError,
+    InvocationCounterError(u32),
+}
+
+// Answers a request the server could not even attempt, e.g. one arriving
+// while no application association is in a state to carry it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionResponse {
+    pub state_error: StateError,
+    pub service_error: ServiceError,
+}
+
+impl ExceptionResponse {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let state_error = match self.state_error {
+            StateError::ServiceNotAllowed => 1,
+            StateError::ServiceUnknown => 2,
+        };
+        let mut bytes = vec![0xD8, state_error];
+        match self.service_error {
+            ServiceError::OperationNotPossible => bytes.push(1),
+            ServiceError::ServiceNotSupported => bytes.push(2),
+            ServiceError::OtherReason => bytes.push(3),
+            ServiceError::PduTooLong => bytes.push(4),
+            ServiceError::DecipheringError => bytes.push(5),
+            ServiceError::InvocationCounterError(counter) => {
+                bytes.push(6);
+                bytes.extend_from_slice(&counter.to_be_bytes());
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        let [0xD8, state_error, service_error, rest @ ..] = bytes else {
+            return Err(DlmsError::Xdlms);
+        };
+        let state_error = match state_error {
+            1 => StateError::ServiceNotAllowed,
+            2 => StateError::ServiceUnknown,
+            _ => return Err(DlmsError::Xdlms),
+        };
+        let (service_error, rest) = match (service_error, rest) {
+            (1, rest) => (ServiceError::OperationNotPossible, rest),
+            (2, rest) => (ServiceError::ServiceNotSupported, rest),
+            (3, rest) => (ServiceError::OtherReason, rest),
+            (4, rest) => (ServiceError::PduTooLong, rest),
+            (5, rest) => (ServiceError::DecipheringError, rest),
+            (6, [a, b, c, d, rest @ ..]) => (
+                ServiceError::InvocationCounterError(u32::from_be_bytes([*a, *b, *c, *d])),
+                rest,
+            ),
+            _ => return Err(DlmsError::Xdlms),
+        };
+        if mode == ParseMode::Strict && !rest.is_empty() {
+            return Err(DlmsError::Xdlms);
+        }
+        Ok(ExceptionResponse {
+            state_error,
+            service_error,
+        })
+    }
+}
+
+impl AssociationParameters {
+    pub fn to_initiate_request(&self) -> InitiateRequest {
+        InitiateRequest {
+            dedicated_key: None,
+            response_allowed: true,
+            proposed_quality_of_service: self.quality_of_service,
+            proposed_dlms_version_number: self.dlms_version,
+            proposed_conformance: self.conformance.clone(),
+            client_max_receive_pdu_size: self.max_receive_pdu_size,
+        }
+    }
+
+    pub fn to_initiate_response(&self, negotiated_conformance: Conformance) -> InitiateResponse {
+        InitiateResponse {
+            negotiated_quality_of_service: self.quality_of_service,
+            negotiated_dlms_version_number: self.dlms_version,
+            negotiated_conformance,
+            server_max_receive_pdu_size: self.max_receive_pdu_size,
+            vaa_name: 0x0007,
+        }
+    }
+}
+
+// --- Set-Response ---
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetResponseNormal {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub result: DataAccessResult,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetResponseDatablock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub block_number: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetResponseLastDatablock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub result: DataAccessResult,
+    pub block_number: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetResponseWithList {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub result: Vec<DataAccessResult>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetResponse {
+    Normal(SetResponseNormal),
+    Datablock(SetResponseDatablock),
+    LastDatablock(SetResponseLastDatablock),
+    WithList(SetResponseWithList),
+}
+
+impl SetResponse {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let mut bytes = Vec::new();
+        match self {
+            SetResponse::Normal(res) => {
+                bytes.push(197); // set-response
+                bytes.push(1); // set-response-normal
+                bytes.push(res.invoke_id_and_priority);
+                bytes.push(res.result.clone().into());
+            }
+            SetResponse::Datablock(res) => {
+                bytes.push(197); // set-response
+                bytes.push(2); // set-response-datablock
+                bytes.push(res.invoke_id_and_priority);
+                bytes.extend_from_slice(&res.block_number.to_be_bytes());
+            }
+            SetResponse::LastDatablock(res) => {
+                bytes.push(197); // set-response
+                bytes.push(3); // set-response-last-datablock
+                bytes.push(res.invoke_id_and_priority);
+                bytes.push(res.result.clone().into());
+                bytes.extend_from_slice(&res.block_number.to_be_bytes());
+            }
+            SetResponse::WithList(res) => {
+                bytes.push(197); // set-response
+                bytes.push(5); // set-response-with-list
+                bytes.push(res.invoke_id_and_priority);
+                encode_object_count(res.result.len(), &mut bytes);
+                for result in &res.result {
+                    bytes.push(result.clone().into());
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 2 {
+            return Err(DlmsError::Xdlms);
+        }
+        let (tag, rest) = take_octets(bytes, 2)?;
+        match (tag[0], tag[1]) {
+            (197, 1) => {
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (result, rest) = take_octets(rest, 1)?;
+                let result = match result[0] {
+                    0 => DataAccessResult::Success,
+                    1 => DataAccessResult::HardwareFault,
+                    2 => DataAccessResult::TemporaryFailure,
+                    3 => DataAccessResult::ReadWriteDenied,
+                    4 => DataAccessResult::ObjectUndefined,
+                    5 => DataAccessResult::ObjectClassInconsistent,
+                    6 => DataAccessResult::ObjectUnavailable,
+                    7 => DataAccessResult::TypeUnmatched,
+                    8 => DataAccessResult::ScopeOfAccessViolated,
+                    9 => DataAccessResult::DataBlockUnavailable,
+                    10 => DataAccessResult::LongGetAborted,
+                    11 => DataAccessResult::NoLongGetInProgress,
+                    12 => DataAccessResult::LongSetAborted,
+                    13 => DataAccessResult::NoLongSetInProgress,
+                    14 => DataAccessResult::DataBlockNumberInvalid,
+                    reason => DataAccessResult::OtherReason(reason),
+                };
+                Ok((
+                    SetResponse::Normal(SetResponseNormal {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        result,
+                    }),
+                    rest,
+                ))
+            }
+            (197, 2) => {
+                if rest.len() < 5 {
+                    return Err(DlmsError::Xdlms);
+                }
+                let mut block_number_bytes = [0u8; 4];
+                block_number_bytes.copy_from_slice(&rest[1..5]);
+                Ok((
+                    SetResponse::Datablock(SetResponseDatablock {
+                        invoke_id_and_priority: rest[0],
+                        block_number: u32::from_be_bytes(block_number_bytes),
+                    }),
+                    &rest[5..],
+                ))
+            }
+            (197, 3) => {
+                if rest.len() < 6 {
+                    return Err(DlmsError::Xdlms);
+                }
+                let mut block_number_bytes = [0u8; 4];
+                block_number_bytes.copy_from_slice(&rest[2..6]);
+                Ok((
+                    SetResponse::LastDatablock(SetResponseLastDatablock {
+                        invoke_id_and_priority: rest[0],
+                        result: DataAccessResult::from(rest[1]),
+                        block_number: u32::from_be_bytes(block_number_bytes),
+                    }),
+                    &rest[6..],
+                ))
+            }
+            (197, 5) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (result, rest) = decode_sequence(rest, mode, |bytes| {
+                    let (result, rest) = bytes.split_first().ok_or(DlmsError::Xdlms)?;
+                    Ok((DataAccessResult::from(*result), rest))
+                })?;
+                Ok((
+                    SetResponse::WithList(SetResponseWithList {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        result,
+                    }),
+                    rest,
+                ))
+            }
+            _ => Err(DlmsError::Xdlms),
+        }
+    }
+}
+
+// --- Action-Request ---
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequestNormal {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub cosem_method_descriptor: CosemMethodDescriptor,
+    pub method_invocation_parameters: Option<CosemData>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequestWithList {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub cosem_method_descriptor_list: Vec<CosemMethodDescriptor>,
+    pub method_invocation_parameters: Vec<CosemData>,
+}
+
+// Method invocation parameters too large for one APDU, sent encoded in
+// pblocks; the first names the method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequestWithFirstPblock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub cosem_method_descriptor: CosemMethodDescriptor,
+    pub pblock: DataBlockSA,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRequestWithPblock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub pblock: DataBlockSA,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionRequest {
+    Normal(ActionRequestNormal),
+    WithFirstPblock(ActionRequestWithFirstPblock),
+    WithPblock(ActionRequestWithPblock),
+    WithList(ActionRequestWithList),
+}
+
+impl ActionRequest {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let mut bytes = Vec::new();
+        match self {
+            ActionRequest::Normal(req) => {
+                bytes.push(195); // action-request
+                bytes.push(1); // action-request-normal
+                bytes.push(req.invoke_id_and_priority);
+                bytes.extend_from_slice(&req.cosem_method_descriptor.class_id.to_be_bytes());
+                bytes.extend_from_slice(&req.cosem_method_descriptor.instance_id);
+                bytes.push(req.cosem_method_descriptor.method_id as u8);
+                if let Some(mip) = &req.method_invocation_parameters {
+                    bytes.push(1); // method-invocation-parameters
+                    encode_data(mip, &mut bytes)?;
+                } else {
+                    bytes.push(0); // no method-invocation-parameters
+                }
+            }
+            ActionRequest::WithFirstPblock(req) => {
+                bytes.push(195); // action-request
+                bytes.push(4); // action-request-with-first-pblock
+                bytes.push(req.invoke_id_and_priority);
+                bytes.extend_from_slice(&req.cosem_method_descriptor.class_id.to_be_bytes());
+                bytes.extend_from_slice(&req.cosem_method_descriptor.instance_id);
+                bytes.push(req.cosem_method_descriptor.method_id as u8);
+                req.pblock.encode(&mut bytes);
+            }
+            ActionRequest::WithPblock(req) => {
+                bytes.push(195); // action-request
+                bytes.push(6); // action-request-with-pblock
+                bytes.push(req.invoke_id_and_priority);
+                req.pblock.encode(&mut bytes);
+            }
+            ActionRequest::WithList(req) => {
+                bytes.push(195); // action-request
+                bytes.push(3); // action-request-with-list
+                bytes.push(req.invoke_id_and_priority);
+                encode_object_count(req.cosem_method_descriptor_list.len(), &mut bytes);
+                for desc in &req.cosem_method_descriptor_list {
+                    bytes.extend_from_slice(&desc.class_id.to_be_bytes());
+                    bytes.extend_from_slice(&desc.instance_id);
+                    bytes.push(desc.method_id as u8);
+                }
+                encode_object_count(req.method_invocation_parameters.len(), &mut bytes);
+                for parameters in &req.method_invocation_parameters {
+                    encode_data(parameters, &mut bytes)?;
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 2 {
+            return Err(DlmsError::Xdlms);
+        }
+        let (tag, rest) = take_octets(bytes, 2)?;
+        match (tag[0], tag[1]) {
+            (195, 1) => {
+                let (invoke_id_and_priority, rest) = take_octets(rest, 1)?;
+                let (class_id, rest) = take_octets(rest, 2)?;
+                let (instance_id, rest) = take_octets(rest, 6)?;
+                let (method_id, rest) = take_octets(rest, 1)?;
+                let (has_mip, rest) = take_octets(rest, 1)?;
+
+                let (method_invocation_parameters, rest) = if presence_flag(has_mip[0], mode)? {
+                    let (mip, rest) = decode_data_with(rest, mode)?;
+                    (Some(mip), rest)
+                } else {
+                    (None, rest)
+                };
+
+                let mut class_id_bytes = [0u8; 2];
+                class_id_bytes.copy_from_slice(class_id);
 
                 let mut instance_id_bytes = [0u8; 6];
                 instance_id_bytes.copy_from_slice(instance_id);
 
-                Ok(ActionRequest::Normal(ActionRequestNormal {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    cosem_method_descriptor: CosemMethodDescriptor {
-                        class_id: u16::from_be_bytes(class_id_bytes),
-                        instance_id: instance_id_bytes,
-                        method_id: method_id[0] as i8,
-                    },
-                    method_invocation_parameters,
-                }))
+                Ok((
+                    ActionRequest::Normal(ActionRequestNormal {
+                        invoke_id_and_priority: invoke_id_and_priority[0],
+                        cosem_method_descriptor: CosemMethodDescriptor {
+                            class_id: u16::from_be_bytes(class_id_bytes),
+                            instance_id: instance_id_bytes,
+                            method_id: method_id[0] as i8,
+                        },
+                        method_invocation_parameters,
+                    }),
+                    rest,
+                ))
+            }
+            (195, 4) => {
+                let [invoke_id_and_priority, c0, c1, i0, i1, i2, i3, i4, i5, method_id, rest @ ..] =
+                    rest
+                else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (pblock, rest) = DataBlockSA::decode(rest, mode)?;
+                Ok((
+                    ActionRequest::WithFirstPblock(ActionRequestWithFirstPblock {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        cosem_method_descriptor: CosemMethodDescriptor {
+                            class_id: u16::from_be_bytes([*c0, *c1]),
+                            instance_id: [*i0, *i1, *i2, *i3, *i4, *i5],
+                            method_id: *method_id as i8,
+                        },
+                        pblock,
+                    }),
+                    rest,
+                ))
+            }
+            (195, 6) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (pblock, rest) = DataBlockSA::decode(rest, mode)?;
+                Ok((
+                    ActionRequest::WithPblock(ActionRequestWithPblock {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        pblock,
+                    }),
+                    rest,
+                ))
+            }
+            (195, 3) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (cosem_method_descriptor_list, rest) =
+                    decode_sequence(rest, mode, decode_method_descriptor)?;
+                let (method_invocation_parameters, rest) =
+                    decode_sequence(rest, mode, |bytes| decode_data_with(bytes, mode))?;
+                Ok((
+                    ActionRequest::WithList(ActionRequestWithList {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        cosem_method_descriptor_list,
+                        method_invocation_parameters,
+                    }),
+                    rest,
+                ))
             }
             _ => Err(DlmsError::Xdlms),
         }
@@ -1327,12 +3151,93 @@ impl From<ActionResult> for u8 {
     }
 }
 
+impl From<u8> for ActionResult {
+    fn from(val: u8) -> Self {
+        match val {
+            0 => ActionResult::Success,
+            1 => ActionResult::HardwareFault,
+            2 => ActionResult::TemporaryFailure,
+            3 => ActionResult::ReadWriteDenied,
+            4 => ActionResult::ObjectUndefined,
+            5 => ActionResult::ObjectClassInconsistent,
+            6 => ActionResult::ObjectUnavailable,
+            7 => ActionResult::TypeUnmatched,
+            8 => ActionResult::ScopeOfAccessViolated,
+            9 => ActionResult::DataBlockUnavailable,
+            10 => ActionResult::LongActionAborted,
+            11 => ActionResult::NoLongActionInProgress,
+            reason => ActionResult::OtherReason(reason),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ActionResponseWithOptionalData {
     pub result: ActionResult,
     pub return_parameters: Option<GetDataResult>,
 }
 
+impl ActionResponseWithOptionalData {
+    pub fn return_data(&self) -> Option<&CosemData> {
+        match &self.return_parameters {
+            Some(GetDataResult::Data(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn data_access_result(&self) -> Option<&DataAccessResult> {
+        match &self.return_parameters {
+            Some(GetDataResult::DataAccessResult(dar)) => Some(dar),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) -> Result<(), DlmsError> {
+        bytes.push(self.result.clone().into());
+        if let Some(rp) = &self.return_parameters {
+            bytes.push(1); // return-parameters
+            match rp {
+                GetDataResult::Data(data) => {
+                    bytes.push(0); // data
+                    encode_data(data, bytes)?;
+                }
+                GetDataResult::DataAccessResult(dar) => {
+                    bytes.push(1); // data-access-result
+                    bytes.push(dar.clone().into());
+                }
+            }
+        } else {
+            bytes.push(0); // no return-parameters
+        }
+        Ok(())
+    }
+
+    fn decode(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        let [result, has_return_params, rest @ ..] = bytes else {
+            return Err(DlmsError::Xdlms);
+        };
+        // return-parameters is an optional Get-Data-Result choice.
+        let (return_parameters, rest) = match (has_return_params, rest) {
+            (0, rest) => (None, rest),
+            (_, [0, data @ ..]) => {
+                let (data, rest) = decode_data_with(data, mode)?;
+                (Some(GetDataResult::Data(data)), rest)
+            }
+            (_, [1, dar, rest @ ..]) => {
+                (Some(GetDataResult::DataAccessResult((*dar).into())), rest)
+            }
+            _ => return Err(DlmsError::Xdlms),
+        };
+        Ok((
+            Self {
+                result: (*result).into(),
+                return_parameters,
+            },
+            rest,
+        ))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ActionResponseNormal {
     pub invoke_id_and_priority: InvokeIdAndPriority,
@@ -1345,10 +3250,19 @@ pub struct ActionResponseWithList {
     pub list_of_responses: Vec<ActionResponseWithOptionalData>,
 }
 
+// Acknowledges a pblock of the method invocation parameters and asks for the
+// next one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionResponseNextPblock {
+    pub invoke_id_and_priority: InvokeIdAndPriority,
+    pub block_number: u32,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ActionResponse {
     Normal(ActionResponseNormal),
     WithList(ActionResponseWithList),
+    NextPblock(ActionResponseNextPblock),
 }
 
 impl ActionResponse {
@@ -1356,69 +3270,558 @@ impl ActionResponse {
         let mut bytes = Vec::new();
         match self {
             ActionResponse::Normal(res) => {
-                bytes.push(198); // action-response-normal
+                bytes.push(199); // action-response
+                bytes.push(1); // action-response-normal
                 bytes.push(res.invoke_id_and_priority);
-                bytes.push(res.single_response.result.clone().into());
-                if let Some(rp) = &res.single_response.return_parameters {
-                    bytes.push(1); // return-parameters
-                    match rp {
-                        GetDataResult::Data(data) => {
-                            encode_data(data, &mut bytes)?;
-                        }
-                        GetDataResult::DataAccessResult(dar) => {
-                            bytes.push(dar.clone().into());
-                        }
-                    }
-                } else {
-                    bytes.push(0); // no return-parameters
+                res.single_response.encode(&mut bytes)?;
+            }
+            ActionResponse::WithList(res) => {
+                bytes.push(199); // action-response
+                bytes.push(3); // action-response-with-list
+                bytes.push(res.invoke_id_and_priority);
+                encode_object_count(res.list_of_responses.len(), &mut bytes);
+                for response in &res.list_of_responses {
+                    response.encode(&mut bytes)?;
                 }
             }
-            _ => return Err(DlmsError::Xdlms),
+            ActionResponse::NextPblock(res) => {
+                bytes.push(199); // action-response
+                bytes.push(4); // action-response-next-pblock
+                bytes.push(res.invoke_id_and_priority);
+                bytes.extend_from_slice(&res.block_number.to_be_bytes());
+            }
         }
         Ok(bytes)
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
-        if bytes.is_empty() {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        if bytes.len() < 2 {
             return Err(DlmsError::Xdlms);
         }
-        let (tag, rest) = bytes.split_at(1);
-        match tag[0] {
-            198 => {
-                let (invoke_id_and_priority, rest) = rest.split_at(1);
-                let (result, rest) = rest.split_at(1);
-                let (has_return_params, rest) = rest.split_at(1);
-
-                let return_parameters = if has_return_params[0] == 1 {
-                    let (data, _) = decode_data(rest)?;
-                    Some(GetDataResult::Data(data))
-                } else {
-                    None
+        let (tag, rest) = take_octets(bytes, 2)?;
+        match (tag[0], tag[1]) {
+            (199, 1) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                let (single_response, rest) = ActionResponseWithOptionalData::decode(rest, mode)?;
+                Ok((
+                    ActionResponse::Normal(ActionResponseNormal {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        single_response,
+                    }),
+                    rest,
+                ))
+            }
+            (199, 3) => {
+                let [invoke_id_and_priority, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
                 };
+                let (list_of_responses, rest) = decode_sequence(rest, mode, |bytes| {
+                    ActionResponseWithOptionalData::decode(bytes, mode)
+                })?;
+                Ok((
+                    ActionResponse::WithList(ActionResponseWithList {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        list_of_responses,
+                    }),
+                    rest,
+                ))
+            }
+            (199, 4) => {
+                let [invoke_id_and_priority, b0, b1, b2, b3, rest @ ..] = rest else {
+                    return Err(DlmsError::Xdlms);
+                };
+                Ok((
+                    ActionResponse::NextPblock(ActionResponseNextPblock {
+                        invoke_id_and_priority: *invoke_id_and_priority,
+                        block_number: u32::from_be_bytes([*b0, *b1, *b2, *b3]),
+                    }),
+                    rest,
+                ))
+            }
+            _ => Err(DlmsError::Xdlms),
+        }
+    }
+}
 
-                Ok(ActionResponse::Normal(ActionResponseNormal {
-                    invoke_id_and_priority: invoke_id_and_priority[0],
-                    single_response: ActionResponseWithOptionalData {
-                        result: match result[0] {
-                            0 => ActionResult::Success,
-                            1 => ActionResult::HardwareFault,
-                            2 => ActionResult::TemporaryFailure,
-                            3 => ActionResult::ReadWriteDenied,
-                            4 => ActionResult::ObjectUndefined,
-                            5 => ActionResult::ObjectClassInconsistent,
-                            6 => ActionResult::ObjectUnavailable,
-                            7 => ActionResult::TypeUnmatched,
-                            8 => ActionResult::ScopeOfAccessViolated,
-                            9 => ActionResult::DataBlockUnavailable,
-                            10 => ActionResult::LongActionAborted,
-                            11 => ActionResult::NoLongActionInProgress,
-                            reason => ActionResult::OtherReason(reason),
-                        },
-                        return_parameters,
+// --- General-Ciphering / General-Signing ---
+const GENERAL_CIPHERING_TAG: u8 = 221;
+const GENERAL_SIGNING_TAG: u8 = 223;
+
+fn encode_axdr_octet_string(value: &[u8], buffer: &mut Vec<u8>) {
+    encode_object_count(value.len(), buffer);
+    buffer.extend_from_slice(value);
+}
+
+fn decode_axdr_octet_string(bytes: &[u8], mode: ParseMode) -> Result<(Vec<u8>, &[u8]), DlmsError> {
+    let (length, header) = decode_object_count_with(bytes, mode)?;
+    let rest = &bytes[header..];
+    if rest.len() < length {
+        return Err(DlmsError::Xdlms);
+    }
+    let (value, rest) = take_octets(rest, length)?;
+    Ok((value.to_vec(), rest))
+}
+
+// Fields shared by the general protection APDUs of end-to-end security.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GeneralProtectionHeader {
+    pub transaction_id: Vec<u8>,
+    pub originator_system_title: Vec<u8>,
+    pub recipient_system_title: Vec<u8>,
+    pub date_time: Vec<u8>,
+    pub other_information: Vec<u8>,
+}
+
+impl GeneralProtectionHeader {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        for field in [
+            &self.transaction_id,
+            &self.originator_system_title,
+            &self.recipient_system_title,
+            &self.date_time,
+            &self.other_information,
+        ] {
+            encode_axdr_octet_string(field, buffer);
+        }
+    }
+
+    fn decode(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        let (transaction_id, rest) = decode_axdr_octet_string(bytes, mode)?;
+        let (originator_system_title, rest) = decode_axdr_octet_string(rest, mode)?;
+        let (recipient_system_title, rest) = decode_axdr_octet_string(rest, mode)?;
+        let (date_time, rest) = decode_axdr_octet_string(rest, mode)?;
+        let (other_information, rest) = decode_axdr_octet_string(rest, mode)?;
+        Ok((
+            GeneralProtectionHeader {
+                transaction_id,
+                originator_system_title,
+                recipient_system_title,
+                date_time,
+                other_information,
+            },
+            rest,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyInfo {
+    // key-id: global-unicast-encryption-key (0) or global-broadcast-encryption-key (1)
+    IdentifiedKey(u8),
+    // kek-id: master-key (0)
+    WrappedKey {
+        kek_id: u8,
+        key_ciphered_data: Vec<u8>,
+    },
+    AgreedKey {
+        key_parameters: Vec<u8>,
+        key_ciphered_data: Vec<u8>,
+    },
+}
+
+impl KeyInfo {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        match self {
+            KeyInfo::IdentifiedKey(key_id) => {
+                buffer.push(0);
+                buffer.push(*key_id);
+            }
+            KeyInfo::WrappedKey {
+                kek_id,
+                key_ciphered_data,
+            } => {
+                buffer.push(1);
+                buffer.push(*kek_id);
+                encode_axdr_octet_string(key_ciphered_data, buffer);
+            }
+            KeyInfo::AgreedKey {
+                key_parameters,
+                key_ciphered_data,
+            } => {
+                buffer.push(2);
+                encode_axdr_octet_string(key_parameters, buffer);
+                encode_axdr_octet_string(key_ciphered_data, buffer);
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        match bytes {
+            [0, key_id, rest @ ..] => Ok((KeyInfo::IdentifiedKey(*key_id), rest)),
+            [1, kek_id, rest @ ..] => {
+                let (key_ciphered_data, rest) = decode_axdr_octet_string(rest, mode)?;
+                Ok((
+                    KeyInfo::WrappedKey {
+                        kek_id: *kek_id,
+                        key_ciphered_data,
+                    },
+                    rest,
+                ))
+            }
+            [2, rest @ ..] => {
+                let (key_parameters, rest) = decode_axdr_octet_string(rest, mode)?;
+                let (key_ciphered_data, rest) = decode_axdr_octet_string(rest, mode)?;
+                Ok((
+                    KeyInfo::AgreedKey {
+                        key_parameters,
+                        key_ciphered_data,
                     },
-                }))
+                    rest,
+                ))
             }
             _ => Err(DlmsError::Xdlms),
         }
     }
 }
+
+// general-ciphering: the ciphered content carries a complete APDU protected
+// end to end between the originator and the recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneralCiphering {
+    pub header: GeneralProtectionHeader,
+    pub key_info: Option<KeyInfo>,
+    pub ciphered_content: Vec<u8>,
+}
+
+impl GeneralCiphering {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let mut bytes = vec![GENERAL_CIPHERING_TAG];
+        self.header.encode(&mut bytes);
+        match &self.key_info {
+            Some(key_info) => {
+                bytes.push(1);
+                key_info.encode(&mut bytes);
+            }
+            None => bytes.push(0),
+        }
+        encode_axdr_octet_string(&self.ciphered_content, &mut bytes);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        let [GENERAL_CIPHERING_TAG, rest @ ..] = bytes else {
+            return Err(DlmsError::Xdlms);
+        };
+        let (header, rest) = GeneralProtectionHeader::decode(rest, mode)?;
+        let (key_info, rest) = match rest {
+            [0, rest @ ..] => (None, rest),
+            [1, rest @ ..] => {
+                let (key_info, rest) = KeyInfo::decode(rest, mode)?;
+                (Some(key_info), rest)
+            }
+            _ => return Err(DlmsError::Xdlms),
+        };
+        let ciphered_content = complete(decode_axdr_octet_string(rest, mode)?, mode)?;
+        Ok(GeneralCiphering {
+            header,
+            key_info,
+            ciphered_content,
+        })
+    }
+}
+
+// general-signing: the content (an APDU, possibly itself ciphered) with the
+// signature of the originator over the whole message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneralSigning {
+    pub header: GeneralProtectionHeader,
+    pub content: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl GeneralSigning {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let mut bytes = self.signed_data();
+        encode_axdr_octet_string(&self.signature, &mut bytes);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::from_bytes_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        let [GENERAL_SIGNING_TAG, rest @ ..] = bytes else {
+            return Err(DlmsError::Xdlms);
+        };
+        let (header, rest) = GeneralProtectionHeader::decode(rest, mode)?;
+        let (content, rest) = decode_axdr_octet_string(rest, mode)?;
+        let signature = complete(decode_axdr_octet_string(rest, mode)?, mode)?;
+        Ok(GeneralSigning {
+            header,
+            content,
+            signature,
+        })
+    }
+
+    // The encoding up to and including the content, which the signature covers.
+    // Checking a signature over the re-encoded message is only sound for one
+    // decoded with ParseMode::Strict, which accepts no other encoding of it.
+    pub fn signed_data(&self) -> Vec<u8> {
+        let mut bytes = vec![GENERAL_SIGNING_TAG];
+        self.header.encode(&mut bytes);
+        encode_axdr_octet_string(&self.content, &mut bytes);
+        bytes
+    }
+}
+
+// --- Data-Notification ---
+const DATA_NOTIFICATION_TAG: u8 = 0x0F;
+
+// Unsolicited data sent by a server, e.g. by a push setup object. An absent
+// date-time is encoded as an empty octet string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataNotification {
+    pub long_invoke_id_and_priority: u32,
+    pub date_time: Option<Vec<u8>>,
+    pub notification_body: CosemData,
+}
+
+impl DataNotification {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let mut bytes = vec![DATA_NOTIFICATION_TAG];
+        bytes.extend_from_slice(&self.long_invoke_id_and_priority.to_be_bytes());
+        match &self.date_time {
+            Some(date_time) => encode_axdr_octet_string(date_time, &mut bytes),
+            None => bytes.push(0),
+        }
+        encode_data(&self.notification_body, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::parse(bytes).map(|(value, _)| value)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        let [DATA_NOTIFICATION_TAG, a, b, c, d, rest @ ..] = bytes else {
+            return Err(DlmsError::Xdlms);
+        };
+        let (date_time, rest) = decode_axdr_octet_string(rest, mode)?;
+        let (notification_body, rest) = decode_data_with(rest, mode)?;
+        Ok((
+            DataNotification {
+                long_invoke_id_and_priority: u32::from_be_bytes([*a, *b, *c, *d]),
+                date_time: (!date_time.is_empty()).then_some(date_time),
+                notification_body,
+            },
+            rest,
+        ))
+    }
+}
+
+// --- Event-Notification-Request ---
+const EVENT_NOTIFICATION_REQUEST_TAG: u8 = 194;
+
+// Unconfirmed report of an attribute value sent by a server, with the time of
+// the event when known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventNotificationRequest {
+    pub time: Option<Vec<u8>>,
+    pub cosem_attribute_descriptor: CosemAttributeDescriptor,
+    pub attribute_value: CosemData,
+}
+
+impl EventNotificationRequest {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        let mut bytes = vec![EVENT_NOTIFICATION_REQUEST_TAG];
+        match &self.time {
+            Some(time) => {
+                bytes.push(1);
+                encode_axdr_octet_string(time, &mut bytes);
+            }
+            None => bytes.push(0),
+        }
+        let descriptor = &self.cosem_attribute_descriptor;
+        bytes.extend_from_slice(&descriptor.class_id.to_be_bytes());
+        bytes.extend_from_slice(&descriptor.instance_id);
+        bytes.push(descriptor.attribute_id as u8);
+        encode_data(&self.attribute_value, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::parse(bytes).map(|(value, _)| value)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        let (time, rest) = match bytes {
+            [EVENT_NOTIFICATION_REQUEST_TAG, 0, rest @ ..] => (None, rest),
+            [EVENT_NOTIFICATION_REQUEST_TAG, 1, rest @ ..] => {
+                let (time, rest) = decode_axdr_octet_string(rest, mode)?;
+                (Some(time), rest)
+            }
+            _ => return Err(DlmsError::Xdlms),
+        };
+        let [class_high, class_low, a, b, c, d, e, f, attribute_id, rest @ ..] = rest else {
+            return Err(DlmsError::Xdlms);
+        };
+        let (attribute_value, rest) = decode_data_with(rest, mode)?;
+        Ok((
+            EventNotificationRequest {
+                time,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: u16::from_be_bytes([*class_high, *class_low]),
+                    instance_id: [*a, *b, *c, *d, *e, *f],
+                    attribute_id: *attribute_id as i8,
+                },
+                attribute_value,
+            },
+            rest,
+        ))
+    }
+}
+
+// APDUs a server sends on its own rather than in answer to a request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    Data(DataNotification),
+    Event(EventNotificationRequest),
+}
+
+impl Notification {
+    pub fn is_notification(bytes: &[u8]) -> bool {
+        matches!(
+            bytes.first(),
+            Some(&DATA_NOTIFICATION_TAG) | Some(&EVENT_NOTIFICATION_REQUEST_TAG)
+        )
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DlmsError> {
+        match self {
+            Notification::Data(notification) => notification.to_bytes(),
+            Notification::Event(notification) => notification.to_bytes(),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DlmsError> {
+        Self::parse(bytes).map(|(value, _)| value)
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], mode: ParseMode) -> Result<Self, DlmsError> {
+        complete(Self::parse_with(bytes, mode)?, mode)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), DlmsError> {
+        Self::parse_with(bytes, ParseMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &[u8], mode: ParseMode) -> Result<(Self, &[u8]), DlmsError> {
+        match bytes.first() {
+            Some(&DATA_NOTIFICATION_TAG) => DataNotification::parse_with(bytes, mode)
+                .map(|(notification, rest)| (Self::Data(notification), rest)),
+            Some(&EVENT_NOTIFICATION_REQUEST_TAG) => {
+                EventNotificationRequest::parse_with(bytes, mode)
+                    .map(|(notification, rest)| (Self::Event(notification), rest))
+            }
+            _ => Err(DlmsError::Xdlms),
+        }
+    }
+}
+
+// Splits an information field holding several APDUs back to back, as sent by
+// peers pipelining requests. Only GET, SET, ACTION and notification APDUs can
+// be delimited; anything else, padding included, stays with the APDU before
+// it.
+pub fn split_apdus(bytes: &[u8]) -> Vec<&[u8]> {
+    Apdus::new(bytes).collect()
+}
+
+// The APDUs of `split_apdus` one by one, without collecting them.
+#[derive(Debug, Clone)]
+pub struct Apdus<'a> {
+    bytes: &'a [u8],
+    start: usize,
+    // End of the APDU at `start`, found while delimiting the one before.
+    end: Option<usize>,
+}
+
+impl<'a> Apdus<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            start: 0,
+            end: None,
+        }
+    }
+
+    // End of the delimitable APDU starting at `start`.
+    fn end_of(&self, start: usize) -> Option<usize> {
+        let rest = &self.bytes[start..];
+        let next = match rest[0] {
+            192 => GetRequest::parse(rest).map(|(_, rest)| rest),
+            193 => SetRequest::parse(rest).map(|(_, rest)| rest),
+            195 => ActionRequest::parse(rest).map(|(_, rest)| rest),
+            196 => GetResponse::parse(rest).map(|(_, rest)| rest),
+            197 => SetResponse::parse(rest).map(|(_, rest)| rest),
+            199 => ActionResponse::parse(rest).map(|(_, rest)| rest),
+            DATA_NOTIFICATION_TAG | EVENT_NOTIFICATION_REQUEST_TAG => {
+                Notification::parse(rest).map(|(_, rest)| rest)
+            }
+            _ => Err(DlmsError::Xdlms),
+        };
+        next.ok()
+            .map(|next| self.bytes.len() - next.len())
+            .filter(|&end| end > start)
+    }
+}
+
+impl<'a> Iterator for Apdus<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let length = self.bytes.len();
+        if self.start >= length {
+            return None;
+        }
+        let mut end = self
+            .end
+            .take()
+            .or_else(|| self.end_of(self.start))
+            .unwrap_or(length);
+        // What follows joins this APDU unless it is one itself.
+        if end < length {
+            match self.end_of(end) {
+                Some(next_end) => self.end = Some(next_end),
+                None => end = length,
+            }
+        }
+        let apdu = &self.bytes[self.start..end];
+        self.start = end;
+        Some(apdu)
+    }
+}
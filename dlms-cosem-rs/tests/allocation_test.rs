@@ -0,0 +1,88 @@
+use dlms_cosem::client::Client;
+use dlms_cosem::cosem::CosemAttributeDescriptor;
+use dlms_cosem::cosem_object::CosemObject;
+use dlms_cosem::register::Register;
+use dlms_cosem::server::Server;
+use dlms_cosem::testing::{DetachedTransport, Direction, LoopbackTransport, RecordingTransport};
+use dlms_cosem::types::CosemData;
+use dlms_cosem::xdlms::{GetRequest, GetRequestNormal};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts the allocations of the whole test binary, so this file holds a single
+// test.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_register_read_allocations() {
+    let mut server = Server::new(1, DetachedTransport, None, None);
+    let mut register = Register::new();
+    register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
+    server.register_object([1, 0, 1, 8, 0, 255], Box::new(register));
+    let transport = RecordingTransport::client(LoopbackTransport::new(server));
+    let mut client = Client::new(1, transport, None, None);
+    client.associate().expect("Association failed");
+    // The value and the scaler and unit, which a copy would allocate for.
+    for attribute_id in [2, 3] {
+        client
+            .send_get_request(GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 3,
+                    instance_id: [1, 0, 1, 8, 0, 255],
+                    attribute_id,
+                },
+                access_selection: None,
+            }))
+            .expect("get failed");
+    }
+    let get_frames: Vec<Vec<u8>> = client
+        .transport()
+        .scenario()
+        .frames
+        .iter()
+        .filter(|frame| frame.direction == Direction::ClientToServer)
+        .skip(1)
+        .map(|frame| frame.bytes.clone())
+        .collect();
+    assert_eq!(get_frames.len(), 2);
+
+    // The buffers of the server and the response grow on the first reads,
+    // after which a read is served without allocating.
+    let server = client.transport_mut().inner_mut().server_mut();
+    let mut response = Vec::new();
+    for round in 0..3 {
+        for get_frame in &get_frames {
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            let sent = server
+                .process_frame_into(get_frame, &mut response)
+                .expect("get failed");
+            let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+            assert!(sent);
+            if round > 0 {
+                assert_eq!(allocations, 0, "{allocations} allocations");
+            }
+        }
+    }
+}
@@ -126,6 +126,7 @@ fn send_frame(server: &mut Server<DummyTransport>, information: Vec<u8>) -> Vec<
         address: SERVER_ADDRESS,
         control: 0,
         information,
+        ..Default::default()
     };
     server
         .handle_frame(&frame.to_bytes().expect("hdlc frame serialization"))
@@ -145,6 +146,7 @@ fn establish_association(server: &mut Server<DummyTransport>) {
         mechanism_name: None,
         calling_authentication_value: None,
         user_information,
+        ..Default::default()
     };
 
     let response = send_frame(server, aarq.to_bytes().expect("aarq encoding"));
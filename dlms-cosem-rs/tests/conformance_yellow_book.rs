@@ -4,11 +4,13 @@ use dlms_cosem::client::Client;
 use dlms_cosem::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
 use dlms_cosem::cosem_object::CosemObject;
 use dlms_cosem::hdlc_transport::HdlcTransport;
+use dlms_cosem::profile_generic::{append_buffer_entry, ProfileGeneric};
 use dlms_cosem::register::Register;
 use dlms_cosem::server::Server;
 use dlms_cosem::types::CosemData;
 use dlms_cosem::xdlms::{
-    ActionRequest, ActionRequestNormal, GetRequest, GetRequestNormal, SetRequest, SetRequestNormal,
+    ActionRequest, ActionRequestNormal, AssociationParameters, GetRequest, GetRequestNormal,
+    SetRequest, SetRequestNormal,
 };
 use std::boxed::Box;
 use std::io::{Read, Write};
@@ -261,3 +263,70 @@ fn yellow_book_conformance_test_action_request() {
         panic!("Incorrect response type");
     }
 }
+
+#[test]
+fn yellow_book_conformance_test_get_with_block_transfer() {
+    let (server_tx, client_rx) = mpsc::channel();
+    let (client_tx, server_rx) = mpsc::channel();
+
+    let client_stream = MockStream {
+        tx: client_tx,
+        rx: client_rx,
+    };
+    let server_stream = MockStream {
+        tx: server_tx,
+        rx: server_rx,
+    };
+
+    let client_transport = HdlcTransport::new(client_stream);
+    let server_transport = HdlcTransport::new(server_stream);
+
+    let mut client = Client::new(1, client_transport, None, None);
+    client.set_association_parameters(AssociationParameters {
+        max_receive_pdu_size: 128,
+        ..AssociationParameters::default()
+    });
+    let mut server = Server::new(1, server_transport, None, None);
+
+    let instance_id = [1, 0, 99, 1, 0, 255];
+    let mut profile = ProfileGeneric::new();
+    let entries: Vec<CosemData> = (0..200u32)
+        .map(|index| {
+            CosemData::Structure(vec![
+                CosemData::DoubleLongUnsigned(index),
+                CosemData::Long64Unsigned(index as u64 * 1_000),
+            ])
+        })
+        .collect();
+    for entry in &entries {
+        append_buffer_entry(&mut profile, entry.clone()).unwrap();
+    }
+    server.register_object(instance_id, Box::new(profile));
+
+    let _server_thread = thread::spawn(move || {
+        let _ = server.run();
+    });
+
+    client.associate().expect("Association failed");
+
+    let req = GetRequest::Normal(GetRequestNormal {
+        invoke_id_and_priority: 1,
+        cosem_attribute_descriptor: CosemAttributeDescriptor {
+            class_id: 7,
+            instance_id,
+            attribute_id: 2,
+        },
+        access_selection: None,
+    });
+
+    let res = client.send_get_request(req).unwrap();
+    if let dlms_cosem::xdlms::GetResponse::Normal(res) = res {
+        if let dlms_cosem::xdlms::GetDataResult::Data(data) = res.result {
+            assert_eq!(data, CosemData::Array(entries));
+        } else {
+            panic!("Incorrect response type");
+        }
+    } else {
+        panic!("Incorrect response type");
+    }
+}
@@ -0,0 +1,73 @@
+use dlms_cosem::cosem_object;
+use dlms_cosem::cosem_object::{AttributeAccessMode, CosemObject, MethodAccessMode};
+use dlms_cosem::types::CosemData;
+
+#[cosem_object(class_id = 3, version = 0, methods(1 = reset))]
+struct Counter {
+    #[attribute(2, read_write)]
+    value: CosemData,
+    #[attribute(3, read)]
+    scaler_unit: CosemData,
+    resets: u32,
+}
+
+impl Counter {
+    fn reset(&mut self, _data: CosemData) -> Option<CosemData> {
+        self.value = self.value.zero_like();
+        self.resets += 1;
+        Some(CosemData::NullData)
+    }
+}
+
+#[test]
+fn test_generated_cosem_object() {
+    let mut counter = Counter {
+        value: CosemData::DoubleLongUnsigned(42),
+        scaler_unit: CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(30)]),
+        resets: 0,
+    };
+    assert_eq!(counter.class_id(), 3);
+    assert_eq!(counter.version(), 0);
+
+    let rights = counter.attribute_access_rights();
+    assert_eq!(
+        rights
+            .iter()
+            .map(|right| (right.attribute_id, right.access_mode))
+            .collect::<Vec<_>>(),
+        vec![
+            (2, AttributeAccessMode::ReadWrite),
+            (3, AttributeAccessMode::Read)
+        ]
+    );
+    let methods = counter.method_access_rights();
+    assert_eq!(methods.len(), 1);
+    assert_eq!(methods[0].access_mode, MethodAccessMode::Access);
+    assert!(counter.callbacks().is_none());
+
+    assert_eq!(
+        counter.get_attribute(2),
+        Some(CosemData::DoubleLongUnsigned(42))
+    );
+    assert_eq!(counter.get_attribute(4), None);
+    assert_eq!(
+        counter.set_attribute(2, CosemData::DoubleLongUnsigned(7)),
+        Some(())
+    );
+    assert_eq!(
+        counter.get_attribute(2),
+        Some(CosemData::DoubleLongUnsigned(7))
+    );
+    assert_eq!(counter.set_attribute(4, CosemData::NullData), None);
+
+    assert_eq!(
+        counter.invoke_method(1, CosemData::NullData),
+        Some(CosemData::NullData)
+    );
+    assert_eq!(
+        counter.get_attribute(2),
+        Some(CosemData::DoubleLongUnsigned(0))
+    );
+    assert_eq!(counter.resets, 1);
+    assert_eq!(counter.invoke_method(2, CosemData::NullData), None);
+}
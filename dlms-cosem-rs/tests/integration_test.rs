@@ -1,12 +1,45 @@
-use dlms_cosem::client::Client;
+use dlms_cosem::client::{date_time_within, Client, ClientError, LinkState};
+use dlms_cosem::clock::Clock;
+use dlms_cosem::cosem::{
+    CosemAttributeDescriptor, CosemMethodDescriptor, CosemObjectAttributeId, CosemObjectMethodId,
+};
+use dlms_cosem::cosem_object::{
+    AttributeAccessMode, CosemObject, MethodAccessDescriptor, MethodAccessMode,
+};
+use dlms_cosem::data::Data;
+use dlms_cosem::hdlc::{HdlcAddress, HdlcFrame};
 use dlms_cosem::hdlc_transport::HdlcTransport;
-use dlms_cosem::server::Server;
-use dlms_cosem::transport::Transport;
+use dlms_cosem::logical_device::{LogicalDeviceName, LOGICAL_DEVICE_NAME_LN};
+use dlms_cosem::multiplexed_transport::MultiplexedTransport;
+use dlms_cosem::poll_scheduler::PollScheduler;
+use dlms_cosem::register::Register;
+use dlms_cosem::scaled_value::{Rounding, ScaledValue, Unit};
+use dlms_cosem::security::{FrameCounter, MemoryFrameCounterStore};
+use dlms_cosem::security_setup::KeyId;
+use dlms_cosem::server::{
+    AccessList, AttributeChange, RejectedAttempt, Server, ServerBuilder, ServerError,
+    ServerInterface,
+};
+use dlms_cosem::server_listener::{ServerInterfaces, ServerListener};
+use dlms_cosem::testing::{
+    DetachedTransport, Direction, Fault, FaultError, FaultRule, FaultyTransport, LoopbackTransport,
+    MockMeter, MockMeterError, RecordingTransport, ReplayError, ReplayTransport, RequestKey,
+    Scenario,
+};
+use dlms_cosem::trace::{TraceLayer, TraceRecord};
+use dlms_cosem::transport::{Listener, Transport};
+use dlms_cosem::types::{CosemData, DataType};
 use dlms_cosem::wrapper_transport::WrapperTransport;
+use dlms_cosem::xdlms::{
+    ActionRequest, ActionRequestNormal, ActionRequestWithPblock, ActionResponse, ActionResult,
+    DataAccessResult, DataBlockSA, GetDataResult, GetRequest, GetRequestNormal, GetResponse,
+    InitiateError, SelectiveAccessDescriptor, SetRequest, SetRequestNormal, SetResponse,
+};
 use std::io::{Read, Write};
 use std::net::TcpListener;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 struct MockStream {
     tx: mpsc::Sender<u8>,
@@ -72,7 +105,7 @@ fn test_association() {
         .negotiated_parameters()
         .expect("expected negotiated parameters");
     assert_eq!(negotiated.negotiated_dlms_version_number, 6);
-    assert_eq!(negotiated.negotiated_conformance.value, 0x0010_0000);
+    assert_eq!(negotiated.negotiated_conformance.value, 0x0000_1C1D);
 
     client.release().expect("Release failed");
     assert!(client.negotiated_parameters().is_none());
@@ -100,3 +133,1557 @@ fn test_wrapper_transport_send_receive() {
 
     server_thread.join().unwrap();
 }
+
+fn loopback_meter() -> Server<DetachedTransport> {
+    let mut server = Server::new(1, DetachedTransport, None, None);
+    let mut register = Register::new();
+    register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
+    server.register_object([1, 0, 1, 8, 0, 255], Box::new(register));
+    server
+}
+
+fn read_energy<T: Transport>(client: &mut Client<T>) -> GetResponse
+where
+    T::Error: std::fmt::Debug,
+{
+    client
+        .send_get_request(GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 3,
+                instance_id: [1, 0, 1, 8, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+        }))
+        .expect("get failed")
+}
+
+#[test]
+fn test_loopback_scenario_is_recorded_and_replayed_against_both_sides() {
+    let transport = RecordingTransport::client(LoopbackTransport::new(loopback_meter()));
+    let mut client = Client::new(1, transport, None, None);
+    client.associate().expect("Association failed");
+    let response = read_energy(&mut client);
+    client.release().expect("Release failed");
+    assert_eq!(client.transport().scenario().frames.len(), 6);
+
+    let path = std::env::temp_dir().join(format!("dlms-scenario-{}.txt", std::process::id()));
+    client.transport().scenario().save(&path).unwrap();
+    let scenario = Scenario::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(&scenario, client.transport().scenario());
+
+    // The recorded meter answers a client repeating the flow.
+    let mut client = Client::new(1, ReplayTransport::as_server(scenario.clone()), None, None);
+    client.associate().expect("Association failed");
+    assert_eq!(read_energy(&mut client), response);
+    client.release().expect("Release failed");
+    assert!(client.transport().is_finished());
+
+    // The recorded client drives a fresh server to the same answers.
+    let mut server = Server::new(1, ReplayTransport::as_client(scenario), None, None);
+    let mut register = Register::new();
+    register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
+    server.register_object([1, 0, 1, 8, 0, 255], Box::new(register));
+    assert!(matches!(
+        server.run(),
+        Err(ServerError::TransportError(ReplayError::Exhausted))
+    ));
+}
+
+#[test]
+fn test_mock_meter_answers_from_recorded_session() {
+    let transport = RecordingTransport::client(LoopbackTransport::new(loopback_meter()));
+    let mut client = Client::new(1, transport, None, None);
+    client.associate().expect("Association failed");
+    read_energy(&mut client);
+    client.release().expect("Release failed");
+    let session = client.transport().scenario().clone();
+
+    let mut client = Client::new(1, MockMeter::new(&session), None, None);
+    client.associate().expect("Association failed");
+    // The same read is answered again, with the invoke id of the new request.
+    for invoke_id in [7, 8] {
+        let response = client
+            .send_get_request(GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: invoke_id,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 3,
+                    instance_id: [1, 0, 1, 8, 0, 255],
+                    attribute_id: 2,
+                },
+                access_selection: None,
+            }))
+            .expect("get failed");
+        let GetResponse::Normal(response) = response else {
+            panic!("expected a normal get response");
+        };
+        assert_eq!(response.invoke_id_and_priority, invoke_id);
+        assert_eq!(
+            response.result,
+            GetDataResult::Data(CosemData::Unsigned(10))
+        );
+    }
+
+    let unrecorded = client.send_get_request(GetRequest::Normal(GetRequestNormal {
+        invoke_id_and_priority: 9,
+        cosem_attribute_descriptor: CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: [1, 0, 1, 8, 0, 255],
+            attribute_id: 3,
+        },
+        access_selection: None,
+    }));
+    assert!(matches!(
+        unrecorded,
+        Err(ClientError::TransportError(MockMeterError::Unrecorded(
+            RequestKey::Get { .. }
+        )))
+    ));
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_pipelined_requests_share_one_frame() {
+    let mut server = loopback_meter();
+    let mut register = Register::new();
+    register.set_attribute(2, CosemData::Unsigned(20)).unwrap();
+    server.register_object([1, 0, 2, 8, 0, 255], Box::new(register));
+    let transport = RecordingTransport::client(LoopbackTransport::new(server));
+    let mut client = Client::new(1, transport, None, None);
+    client.associate().expect("Association failed");
+
+    let requests = [[1, 0, 1, 8, 0, 255], [1, 0, 2, 8, 0, 255]]
+        .into_iter()
+        .enumerate()
+        .map(|(index, instance_id)| {
+            GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 0xC1 + index as u8,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 3,
+                    instance_id,
+                    attribute_id: 2,
+                },
+                access_selection: None,
+            })
+        })
+        .collect();
+    let results: Vec<_> = client
+        .get_pipelined(requests)
+        .expect("pipelined get failed")
+        .into_iter()
+        .map(|response| match response {
+            GetResponse::Normal(response) => (response.invoke_id_and_priority, response.result),
+            _ => panic!("expected a normal get response"),
+        })
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            (0xC1, GetDataResult::Data(CosemData::Unsigned(10))),
+            (0xC2, GetDataResult::Data(CosemData::Unsigned(20))),
+        ]
+    );
+    // One request frame and one response frame after the association.
+    assert_eq!(client.transport().scenario().frames.len(), 4);
+}
+
+#[test]
+fn test_trace_records_share_the_correlation_id_of_their_request() {
+    let client_records = Arc::new(Mutex::new(Vec::<TraceRecord>::new()));
+    let server_records = Arc::new(Mutex::new(Vec::<TraceRecord>::new()));
+    let mut server = loopback_meter();
+    let sink = Arc::clone(&server_records);
+    server.set_on_trace(move |record| sink.lock().unwrap().push(record.clone()));
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    let sink = Arc::clone(&client_records);
+    client.on_trace(move |record| sink.lock().unwrap().push(record.clone()));
+
+    client.associate().expect("Association failed");
+    let association = client.correlation_id();
+    read_energy(&mut client);
+    let get = client.correlation_id();
+    assert_ne!(association, get);
+    let timings = client.timings();
+    assert!(timings.transport > Duration::ZERO);
+    assert_eq!(
+        timings.total(),
+        timings.encode + timings.transport + timings.decode
+    );
+
+    let layers = |records: &[TraceRecord], id| {
+        records
+            .iter()
+            .filter(|record| record.correlation_id == id)
+            .map(|record| record.layer)
+            .collect::<Vec<_>>()
+    };
+    let records = client_records.lock().unwrap();
+    assert_eq!(
+        layers(&records, association),
+        [
+            TraceLayer::Acse,
+            TraceLayer::Hdlc,
+            TraceLayer::Hdlc,
+            TraceLayer::Acse
+        ]
+    );
+    assert_eq!(
+        layers(&records, get),
+        [
+            TraceLayer::Xdlms,
+            TraceLayer::Hdlc,
+            TraceLayer::Hdlc,
+            TraceLayer::Xdlms
+        ]
+    );
+    assert!(records
+        .iter()
+        .any(|record| record.correlation_id == get && record.message.starts_with("get-response")));
+    assert_eq!(records.len(), 8);
+
+    // The server tags the frame, the request and the response frame alike.
+    let server = client.transport().server();
+    let records = server_records.lock().unwrap();
+    let last = server.correlation_id();
+    assert_eq!(
+        layers(&records, last),
+        [TraceLayer::Hdlc, TraceLayer::Xdlms, TraceLayer::Hdlc]
+    );
+    assert!(records[1].message.starts_with("aarq from client 1"));
+    assert!(server.timings().process > Duration::ZERO);
+}
+
+#[test]
+fn test_poll_scheduler_caches_values_and_reports_changes() {
+    let mut server = loopback_meter();
+    server.register_object([1, 0, 2, 8, 0, 255], Box::new(Register::new()));
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let energy = CosemAttributeDescriptor {
+        class_id: 3,
+        instance_id: [1, 0, 1, 8, 0, 255],
+        attribute_id: 2,
+    };
+    let export = CosemAttributeDescriptor {
+        instance_id: [1, 0, 2, 8, 0, 255],
+        ..energy.clone()
+    };
+    let mut scheduler = PollScheduler::new();
+    scheduler.register(energy.clone(), Duration::from_secs(60));
+    scheduler.register(export.clone(), Duration::from_secs(900));
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&changes);
+    scheduler.on_change(move |change| sink.lock().unwrap().push(change.clone()));
+
+    let start = Instant::now();
+    assert_eq!(scheduler.poll_at(&mut client, start).unwrap(), 2);
+    assert_eq!(changes.lock().unwrap().len(), 2);
+    assert_eq!(
+        scheduler.cached(&energy).map(|cached| &cached.result),
+        Some(&GetDataResult::Data(CosemData::Unsigned(10)))
+    );
+    assert_eq!(
+        scheduler.next_due(start),
+        Some(start + Duration::from_secs(60))
+    );
+
+    // Unchanged values refresh the cache without a notification.
+    let first_due = start + Duration::from_secs(60);
+    assert_eq!(scheduler.poll_at(&mut client, first_due).unwrap(), 1);
+    assert_eq!(changes.lock().unwrap().len(), 2);
+    assert_eq!(scheduler.cached(&energy).unwrap().read_at, first_due);
+
+    client
+        .transport_mut()
+        .server_mut()
+        .set_object_attribute([1, 0, 1, 8, 0, 255], 2, CosemData::Unsigned(11))
+        .unwrap();
+    let second_due = start + Duration::from_secs(120);
+    assert_eq!(scheduler.poll_at(&mut client, second_due).unwrap(), 1);
+    let changes = changes.lock().unwrap();
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[2].descriptor, energy);
+    assert_eq!(
+        changes[2].previous,
+        Some(GetDataResult::Data(CosemData::Unsigned(10)))
+    );
+    assert_eq!(
+        changes[2].current.result,
+        GetDataResult::Data(CosemData::Unsigned(11))
+    );
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_register_is_read_as_scaled_value() {
+    let mut server = loopback_meter();
+    let energy = [1, 0, 1, 8, 0, 255];
+    server
+        .set_object_attribute(energy, 2, CosemData::DoubleLongUnsigned(12345))
+        .unwrap();
+    server
+        .set_object_attribute(
+            energy,
+            3,
+            CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(30)]),
+        )
+        .unwrap();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let value = client
+        .read_scaled_value(3, energy)
+        .expect("register read failed");
+    assert_eq!(value, ScaledValue::new(12345, 0, Unit::WATT_HOUR));
+    assert_eq!(value.to_string(), "12.345 kWh");
+    assert!(matches!(
+        client.read_scaled_value(1, energy),
+        Err(ClientError::DlmsError(_))
+    ));
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_float_register_is_read_as_rounded_value() {
+    let mut server = loopback_meter();
+    let energy = [1, 0, 1, 8, 0, 255];
+    server
+        .set_object_attribute(energy, 2, CosemData::Float32(230.15))
+        .unwrap();
+    server
+        .set_object_attribute(
+            energy,
+            3,
+            CosemData::Structure(vec![CosemData::Integer(0), CosemData::Enum(35)]),
+        )
+        .unwrap();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    assert_eq!(
+        client
+            .read_rounded_value(3, energy, -1, Rounding::Nearest)
+            .expect("register read failed"),
+        ScaledValue::new(2302, -1, Unit::VOLT)
+    );
+    assert_eq!(
+        client
+            .read_rounded_value(3, energy, -1, Rounding::TowardZero)
+            .expect("register read failed"),
+        ScaledValue::new(2301, -1, Unit::VOLT)
+    );
+    assert!(matches!(
+        client.read_scaled_value(3, energy),
+        Err(ClientError::DlmsError(_))
+    ));
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_refused_initiate_is_reported_as_initiate_error() {
+    let mut client = Client::new(1, LoopbackTransport::new(loopback_meter()), None, None);
+    let mut parameters = client.association_parameters().clone();
+    parameters.dlms_version = 5;
+    client.set_association_parameters(parameters);
+    assert!(matches!(
+        client.associate(),
+        Err(ClientError::InitiateRejected(
+            InitiateError::DlmsVersionTooLow
+        ))
+    ));
+}
+
+#[test]
+fn test_server_listener_limits_connections_and_releases_on_close() {
+    let mut listener =
+        ServerListener::new(TcpListener::bind("127.0.0.1:0").unwrap(), loopback_meter());
+    listener.set_max_connections(1);
+    let addr = listener.local_addr().unwrap();
+    let server = listener.server();
+    thread::spawn(move || listener.run());
+
+    let connect = || {
+        Client::new(
+            1,
+            WrapperTransport::new(std::net::TcpStream::connect(addr).unwrap()),
+            None,
+            None,
+        )
+    };
+    let mut first = connect();
+    first.associate().expect("Association failed");
+    assert!(matches!(
+        read_energy(&mut first),
+        GetResponse::Normal(response)
+            if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+    ));
+    assert_eq!(server.lock().unwrap().connection_clients(0), vec![1]);
+
+    // The only slot is taken, so the second connection is closed unanswered.
+    assert!(matches!(
+        connect().associate(),
+        Err(ClientError::TransportError(_))
+    ));
+
+    // Dropping the socket without a release still ends the association.
+    drop(first);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !server.lock().unwrap().connection_clients(0).is_empty() {
+        assert!(Instant::now() < deadline, "association was not released");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut third = connect();
+    third.associate().expect("Association failed");
+    third.release().expect("Release failed");
+}
+
+#[test]
+fn test_server_listener_drops_connections_of_denied_peers() {
+    let mut server = loopback_meter();
+    let localhost = std::net::IpAddr::from([127, 0, 0, 1]);
+    server.set_peer_access(AccessList::Deny([localhost].into()));
+    let rejected = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&rejected);
+    server.set_on_rejected_attempt(move |attempt| log.lock().unwrap().push(attempt.clone()));
+    let mut listener = ServerListener::new(TcpListener::bind("127.0.0.1:0").unwrap(), server);
+    let addr = listener.local_addr().unwrap();
+    let accepting = thread::spawn(move || listener.accept().unwrap());
+
+    let mut client = Client::new(
+        1,
+        WrapperTransport::new(std::net::TcpStream::connect(addr).unwrap()),
+        None,
+        None,
+    );
+    assert!(!accepting.join().unwrap());
+    assert!(matches!(
+        client.associate(),
+        Err(ClientError::TransportError(_))
+    ));
+    assert_eq!(
+        *rejected.lock().unwrap(),
+        vec![RejectedAttempt::Peer(localhost)]
+    );
+}
+
+// One end of an in-memory link.
+struct ChannelConnection {
+    tx: mpsc::Sender<Vec<u8>>,
+    rx: mpsc::Receiver<Vec<u8>>,
+}
+
+impl Transport for ChannelConnection {
+    type Error = ();
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.tx.send(bytes.to_vec()).map_err(|_| ())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, Self::Error> {
+        self.rx.recv().map_err(|_| ())
+    }
+}
+
+// Accepts the server ends of the links opened by `connect`.
+struct ChannelListener(mpsc::Receiver<ChannelConnection>);
+
+impl Listener for ChannelListener {
+    type Connection = ChannelConnection;
+    type Error = ();
+
+    fn accept(&mut self) -> Result<ChannelConnection, ()> {
+        self.0.recv().map_err(|_| ())
+    }
+}
+
+#[test]
+fn test_server_listener_serves_simultaneous_connections_of_any_listener() {
+    let (accept_tx, accept_rx) = mpsc::channel();
+    let mut listener = ServerListener::new(ChannelListener(accept_rx), loopback_meter());
+    let server = listener.server();
+    let listening = thread::spawn(move || listener.run());
+
+    let connect = |client_address| {
+        let (client_tx, server_rx) = mpsc::channel();
+        let (server_tx, client_rx) = mpsc::channel();
+        accept_tx
+            .send(ChannelConnection {
+                tx: server_tx,
+                rx: server_rx,
+            })
+            .unwrap();
+        let connection = ChannelConnection {
+            tx: client_tx,
+            rx: client_rx,
+        };
+        Client::new(client_address, connection, None, None)
+    };
+    let mut reader = connect(0x20);
+    let mut configurator = connect(0x30);
+    reader.associate().expect("Association failed");
+    configurator.associate().expect("Association failed");
+    assert_eq!(server.lock().unwrap().connection_clients(0), vec![0x20]);
+    assert_eq!(server.lock().unwrap().connection_clients(1), vec![0x30]);
+    for client in [&mut reader, &mut configurator] {
+        assert!(matches!(
+            read_energy(client),
+            GetResponse::Normal(response)
+                if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+        ));
+    }
+
+    // Closing a link releases only the association made over it.
+    drop(reader);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !server.lock().unwrap().connection_clients(0).is_empty() {
+        assert!(Instant::now() < deadline, "association was not released");
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(server.lock().unwrap().connection_clients(1), vec![0x30]);
+    configurator.release().expect("Release failed");
+
+    // The listener returns once it cannot accept any more.
+    drop(accept_tx);
+    assert_eq!(listening.join().unwrap(), Err(()));
+}
+
+#[test]
+fn test_server_serves_the_connections_of_a_listener() {
+    let (accept_tx, accept_rx) = mpsc::channel();
+    let serving = thread::spawn(move || loopback_meter().serve(ChannelListener(accept_rx)));
+
+    let mut clients = [0x20, 0x30].map(|client_address| {
+        let (client, server) = channel_link();
+        accept_tx.send(server).unwrap();
+        Client::new(client_address, client, None, None)
+    });
+    for client in &mut clients {
+        client.associate().expect("Association failed");
+    }
+    for client in &mut clients {
+        assert!(matches!(
+            read_energy(client),
+            GetResponse::Normal(response)
+                if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+        ));
+        client.release().expect("Release failed");
+    }
+
+    drop(accept_tx);
+    assert_eq!(serving.join().unwrap(), Err(()));
+}
+
+#[test]
+fn test_server_listener_keeps_associations_to_their_connection() {
+    let (accept_tx, accept_rx) = mpsc::channel();
+    let mut listener = ServerListener::new(ChannelListener(accept_rx), loopback_meter());
+    thread::spawn(move || listener.run());
+    let connect = || {
+        let (client, server) = channel_link();
+        accept_tx.send(server).unwrap();
+        client
+    };
+
+    let mut owner = Client::new(1, connect(), None, None);
+    owner.associate().expect("Association failed");
+
+    // Another peer with the same client address gets no access through the
+    // association of the first, and a frame it garbles does not close its
+    // connection.
+    let mut intruder = connect();
+    intruder.send(&[0x7E, 0x00, 0x7E]).unwrap();
+    let get = GetRequest::Normal(GetRequestNormal {
+        invoke_id_and_priority: 1,
+        cosem_attribute_descriptor: CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: [1, 0, 1, 8, 0, 255],
+            attribute_id: 2,
+        },
+        access_selection: None,
+    });
+    let frame = HdlcFrame {
+        address: 1,
+        control: 0,
+        information: get.to_bytes().unwrap(),
+        ..Default::default()
+    };
+    intruder.send(&frame.to_bytes().unwrap()).unwrap();
+    let response = HdlcFrame::from_bytes(&intruder.receive().unwrap()).unwrap();
+    assert!(matches!(
+        GetResponse::from_bytes(&response.information),
+        Ok(GetResponse::Normal(response))
+            if matches!(response.result, GetDataResult::DataAccessResult(_))
+    ));
+
+    assert!(matches!(
+        read_energy(&mut owner),
+        GetResponse::Normal(response)
+            if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+    ));
+    owner.release().expect("Release failed");
+}
+
+#[test]
+fn test_server_listener_changes_clear_the_caches_of_every_connection() {
+    let mut server = loopback_meter();
+    server.set_response_cache(8, Duration::from_secs(60));
+    let (accept_tx, accept_rx) = mpsc::channel();
+    let mut listener = ServerListener::new(ChannelListener(accept_rx), server);
+    let server = listener.server();
+    thread::spawn(move || listener.run());
+    let connect = |client_address| {
+        let (client, server) = channel_link();
+        accept_tx.send(server).unwrap();
+        Client::new(client_address, client, None, None)
+    };
+    let energy_is = |response: GetResponse, value: u8| {
+        matches!(response, GetResponse::Normal(response)
+            if response.result == GetDataResult::Data(CosemData::Unsigned(value)))
+    };
+
+    let mut reader = connect(0x20);
+    let mut configurator = connect(0x30);
+    reader.associate().expect("Association failed");
+    configurator.associate().expect("Association failed");
+    assert!(energy_is(read_energy(&mut reader), 10));
+
+    // A write over another connection is seen by the reader.
+    let response = configurator
+        .send_set_request(SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 3,
+                instance_id: [1, 0, 1, 8, 0, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+            value: CosemData::Unsigned(11),
+        }))
+        .expect("set failed");
+    assert!(matches!(
+        response,
+        SetResponse::Normal(response) if response.result == DataAccessResult::Success
+    ));
+    assert!(energy_is(read_energy(&mut reader), 11));
+
+    // So is a change the host makes through the shared server.
+    server
+        .lock()
+        .unwrap()
+        .set_object_attribute([1, 0, 1, 8, 0, 255], 2, CosemData::Unsigned(12))
+        .unwrap();
+    assert!(energy_is(read_energy(&mut reader), 12));
+    reader.release().expect("Release failed");
+    configurator.release().expect("Release failed");
+}
+
+// Both ends of an in-memory link.
+fn channel_link() -> (ChannelConnection, ChannelConnection) {
+    let (client_tx, server_rx) = mpsc::channel();
+    let (server_tx, client_rx) = mpsc::channel();
+    (
+        ChannelConnection {
+            tx: client_tx,
+            rx: client_rx,
+        },
+        ChannelConnection {
+            tx: server_tx,
+            rx: server_rx,
+        },
+    )
+}
+
+#[test]
+fn test_server_interfaces_serve_their_own_clients_concurrently() {
+    let mut server = loopback_meter();
+    let established = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&established);
+    server.set_on_association_established(move |info| {
+        log.lock()
+            .unwrap()
+            .push((info.client_address, info.logical_name));
+    });
+    let mut interfaces = ServerInterfaces::new(server);
+    let server = interfaces.server();
+
+    // The optical port serves the configurator; the TCP wrapper the meter
+    // reader, with the association of the configurator.
+    let (optical, optical_port) = channel_link();
+    let (tcp, tcp_port) = channel_link();
+    interfaces.spawn(
+        ServerInterface::new(0).client(0x30, [0, 0, 40, 0, 3, 255]),
+        optical_port,
+    );
+    interfaces.spawn(
+        ServerInterface::new(1).client(0x20, [0, 0, 40, 0, 3, 255]),
+        tcp_port,
+    );
+
+    let mut configurator = Client::new(0x30, optical, None, None);
+    configurator.associate().expect("Association failed");
+    let (tx, rx) = mpsc::channel();
+    let reading = thread::spawn(move || {
+        let mut reader = Client::new(0x20, tcp, None, None);
+        reader.associate().expect("Association failed");
+        for _ in 0..20 {
+            assert!(matches!(
+                read_energy(&mut reader),
+                GetResponse::Normal(response)
+                    if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+            ));
+        }
+        tx.send(()).unwrap();
+        reader
+    });
+    for _ in 0..20 {
+        assert!(matches!(
+            read_energy(&mut configurator),
+            GetResponse::Normal(response)
+                if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+        ));
+    }
+    rx.recv().unwrap();
+    {
+        let server = server.lock().unwrap();
+        assert_eq!(server.associated_clients(), vec![0x20, 0x30]);
+        assert_eq!(server.interface_of(0x20), Some(1));
+        assert_eq!(server.interface_of(0x30), Some(0));
+    }
+    assert!(established
+        .lock()
+        .unwrap()
+        .contains(&(0x20, [0, 0, 40, 0, 3, 255])));
+
+    // Closing the TCP link releases only the association made over it.
+    drop(reading.join().unwrap());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while server.lock().unwrap().associated_clients() != vec![0x30] {
+        assert!(Instant::now() < deadline, "association was not released");
+        thread::sleep(Duration::from_millis(10));
+    }
+    configurator.release().expect("Release failed");
+    drop(configurator);
+    interfaces.join();
+}
+
+#[test]
+fn test_current_association_names_the_association_of_each_client() {
+    let partners = |client: &mut Client<LoopbackTransport<DetachedTransport>>, logical_name| {
+        let response = client
+            .send_get_request(GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 15,
+                    instance_id: logical_name,
+                    attribute_id: 3,
+                },
+                access_selection: None,
+            }))
+            .expect("get failed");
+        let GetResponse::Normal(response) = response else {
+            panic!("unexpected response {response:?}");
+        };
+        response.result
+    };
+    for (client_address, logical_name) in
+        [(0x20, [0, 0, 40, 0, 2, 255]), (0x30, [0, 0, 40, 0, 3, 255])]
+    {
+        let mut client = Client::new(
+            client_address,
+            LoopbackTransport::new(loopback_meter()),
+            None,
+            None,
+        );
+        client.associate().expect("Association failed");
+        let current = partners(&mut client, [0, 0, 40, 0, 0, 255]);
+        assert!(matches!(
+            &current,
+            GetDataResult::Data(CosemData::DoubleLongUnsigned(id)) if id >> 16 == client_address as u32
+        ));
+        assert_eq!(current, partners(&mut client, logical_name));
+        client.release().expect("Release failed");
+    }
+}
+
+#[test]
+fn test_object_list_of_hundreds_of_objects_is_read_in_blocks() {
+    let mut builder = ServerBuilder::new(1, DetachedTransport);
+    for index in 0..300u16 {
+        let [high, low] = index.to_be_bytes();
+        builder = builder.object([1, 0, 1, 8, high, low], Box::new(Register::new()));
+    }
+    // The meter reader association lists every object that is not public.
+    let mut client = Client::new(0x20, LoopbackTransport::new(builder.build()), None, None);
+    client.associate().expect("Association failed");
+    let response = client
+        .send_get_request(GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 15,
+                instance_id: [0, 0, 40, 0, 2, 255],
+                attribute_id: 2,
+            },
+            access_selection: None,
+        }))
+        .expect("get failed");
+    let GetResponse::Normal(response) = response else {
+        panic!("unexpected response {response:?}");
+    };
+    let GetDataResult::Data(CosemData::Array(entries)) = response.result else {
+        panic!("object list not readable");
+    };
+    let registers = entries
+        .iter()
+        .filter(|entry| {
+            matches!(entry, CosemData::Structure(fields)
+                if fields[0] == CosemData::LongUnsigned(3)
+                    && matches!(&fields[2], CosemData::OctetString(ln) if ln[..4] == [1, 0, 1, 8]))
+        })
+        .count();
+    assert_eq!(registers, 300);
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_ciphered_association_is_released_gracefully() {
+    let key = vec![0x5A; 16];
+    let server = ServerBuilder::new(1, DetachedTransport)
+        .key(key.clone())
+        .object([1, 0, 1, 8, 0, 255], Box::new(Register::new()))
+        .build();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, Some(key));
+    client.associate().expect("Association failed");
+    read_energy(&mut client);
+    assert_eq!(client.transport().server().associated_clients(), vec![1]);
+    client.release().expect("Release failed");
+    assert!(client.transport().server().associated_clients().is_empty());
+}
+
+#[test]
+fn test_invocation_counters_survive_a_restart() {
+    let key = vec![0x5A; 16];
+    let meter_counters = MemoryFrameCounterStore::new();
+    let head_end_counters = MemoryFrameCounterStore::new();
+    let mut counters = Vec::new();
+    for _ in 0..2 {
+        let server = ServerBuilder::new(1, DetachedTransport)
+            .key(key.clone())
+            .frame_counter(FrameCounter::new(
+                b"METER001".to_vec(),
+                KeyId::GlobalUnicastEncryption,
+                Box::new(meter_counters.clone()),
+            ))
+            .object([1, 0, 1, 8, 0, 255], Box::new(Register::new()))
+            .build();
+        let transport = RecordingTransport::client(LoopbackTransport::new(server));
+        let mut client = Client::new(1, transport, None, Some(key.clone()));
+        client.set_frame_counter(Some(FrameCounter::new(
+            b"HEADEND1".to_vec(),
+            KeyId::GlobalUnicastEncryption,
+            Box::new(head_end_counters.clone()),
+        )));
+        client.associate().expect("Association failed");
+        read_energy(&mut client);
+        client.release().expect("Release failed");
+        for frame in &client.transport().scenario().frames {
+            let system_title = match frame.direction {
+                Direction::ClientToServer => b"HEADEND1",
+                Direction::ServerToClient => b"METER001",
+            };
+            assert_eq!(&frame.bytes[..8], system_title);
+            let counter = u32::from_be_bytes(frame.bytes[8..12].try_into().unwrap());
+            counters.push((frame.direction, counter));
+        }
+    }
+
+    // The second session continues the counters of the first one.
+    for direction in [Direction::ClientToServer, Direction::ServerToClient] {
+        let sent: Vec<u32> = counters
+            .iter()
+            .filter(|(sender, _)| *sender == direction)
+            .map(|(_, counter)| *counter)
+            .collect();
+        assert_eq!(sent, (0..6).collect::<Vec<u32>>());
+    }
+}
+
+#[test]
+fn test_repeated_gets_are_answered_from_the_response_cache() {
+    let mut server = loopback_meter();
+    server.set_response_cache(8, Duration::from_secs(60));
+    let reads = Arc::new(Mutex::new(0));
+    let mut register = Register::new();
+    register.set_attribute(2, CosemData::Unsigned(10)).unwrap();
+    register.callback_handlers().set_post_read({
+        let reads = Arc::clone(&reads);
+        move |_, _, _| {
+            *reads.lock().unwrap() += 1;
+            Ok(())
+        }
+    });
+    server.register_object([1, 0, 1, 8, 0, 255], Box::new(register));
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let energy_is = |response: GetResponse, value: u8| {
+        matches!(response, GetResponse::Normal(response)
+            if response.result == GetDataResult::Data(CosemData::Unsigned(value)))
+    };
+    for _ in 0..3 {
+        assert!(energy_is(read_energy(&mut client), 10));
+    }
+    assert_eq!(*reads.lock().unwrap(), 1);
+
+    // A changed value is read again.
+    client
+        .transport_mut()
+        .server_mut()
+        .set_object_attribute([1, 0, 1, 8, 0, 255], 2, CosemData::Unsigned(11))
+        .unwrap();
+    assert!(energy_is(read_energy(&mut client), 11));
+    assert!(energy_is(read_energy(&mut client), 11));
+    assert_eq!(*reads.lock().unwrap(), 2);
+
+    // A new association starts with an empty cache.
+    client.release().expect("Release failed");
+    client.associate().expect("Association failed");
+    assert!(energy_is(read_energy(&mut client), 11));
+    assert_eq!(*reads.lock().unwrap(), 3);
+}
+
+#[test]
+fn test_watchers_see_changes_made_by_clients_and_the_host() {
+    let energy_ln = [1, 0, 1, 8, 0, 255];
+    let mut server = loopback_meter();
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    server.watch(energy_ln, 2, {
+        let changes = Arc::clone(&changes);
+        move |change: &AttributeChange| changes.lock().unwrap().push(change.clone())
+    });
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let energy = CosemAttributeDescriptor {
+        class_id: 3,
+        instance_id: energy_ln,
+        attribute_id: 2,
+    };
+    for value in [42, 42] {
+        let report = client
+            .write_verified(energy.clone(), CosemData::Unsigned(value))
+            .expect("write failed");
+        assert_eq!(report.write_result, DataAccessResult::Success);
+    }
+    client
+        .transport_mut()
+        .server_mut()
+        .set_object_attribute(energy_ln, 2, CosemData::Unsigned(43))
+        .unwrap();
+
+    // The second write left the value as it was.
+    let change = |old, new| AttributeChange {
+        logical_name: energy_ln,
+        attribute_id: 2,
+        old_value: Some(CosemData::Unsigned(old)),
+        new_value: Some(CosemData::Unsigned(new)),
+    };
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![change(10, 42), change(42, 43)]
+    );
+}
+
+#[test]
+fn test_object_list_selective_access_by_class_and_object() {
+    let mut builder = ServerBuilder::new(1, DetachedTransport)
+        .object([0, 0, 1, 0, 0, 255], Box::new(Clock::new()));
+    for index in 0..200u8 {
+        builder = builder.object([1, 0, 1, 8, 0, index], Box::new(Register::new()));
+    }
+    let mut client = Client::new(0x20, LoopbackTransport::new(builder.build()), None, None);
+    client.associate().expect("Association failed");
+    let mut read_object_list = |access_selector: u8, access_parameters: Vec<CosemData>| {
+        let response = client
+            .send_get_request(GetRequest::Normal(GetRequestNormal {
+                invoke_id_and_priority: 1,
+                cosem_attribute_descriptor: CosemAttributeDescriptor {
+                    class_id: 15,
+                    instance_id: [0, 0, 40, 0, 2, 255],
+                    attribute_id: 2,
+                },
+                access_selection: Some(SelectiveAccessDescriptor {
+                    access_selector,
+                    access_parameters: CosemData::Array(access_parameters),
+                }),
+            }))
+            .expect("get failed");
+        let GetResponse::Normal(response) = response else {
+            panic!("unexpected response {response:?}");
+        };
+        match response.result {
+            GetDataResult::Data(CosemData::Array(entries)) => Ok(entries
+                .into_iter()
+                .map(|entry| match entry {
+                    CosemData::Structure(fields) => fields[2].clone(),
+                    entry => panic!("unexpected entry {entry:?}"),
+                })
+                .collect::<Vec<_>>()),
+            GetDataResult::DataAccessResult(result) => Err(result),
+            result => panic!("unexpected result {result:?}"),
+        }
+    };
+
+    let clocks = read_object_list(1, vec![CosemData::LongUnsigned(8)]).unwrap();
+    assert_eq!(
+        clocks,
+        vec![CosemData::OctetString(vec![0, 0, 1, 0, 0, 255])]
+    );
+    let registers = read_object_list(1, vec![CosemData::LongUnsigned(3)]).unwrap();
+    assert_eq!(registers.len(), 200);
+
+    let object_id = |class_id: u16, ln: Vec<u8>| {
+        CosemData::Structure(vec![
+            CosemData::LongUnsigned(class_id),
+            CosemData::OctetString(ln),
+        ])
+    };
+    let selected = read_object_list(
+        2,
+        vec![
+            object_id(3, vec![1, 0, 1, 8, 0, 7]),
+            object_id(3, vec![1, 0, 1, 8, 0, 42]),
+            // Wrong class for the logical name.
+            object_id(1, vec![1, 0, 1, 8, 0, 9]),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        selected,
+        vec![
+            CosemData::OctetString(vec![1, 0, 1, 8, 0, 7]),
+            CosemData::OctetString(vec![1, 0, 1, 8, 0, 42]),
+        ]
+    );
+    assert_eq!(
+        read_object_list(5, Vec::new()),
+        Err(DataAccessResult::ScopeOfAccessViolated)
+    );
+}
+
+#[test]
+fn test_undeclared_access_selectors_violate_the_scope_of_access() {
+    let mut client = Client::new(1, LoopbackTransport::new(loopback_meter()), None, None);
+    client.associate().expect("Association failed");
+    let descriptor = CosemAttributeDescriptor {
+        class_id: 3,
+        instance_id: [1, 0, 1, 8, 0, 255],
+        attribute_id: 2,
+    };
+    let selection = Some(SelectiveAccessDescriptor {
+        access_selector: 1,
+        access_parameters: CosemData::NullData,
+    });
+
+    // The register declares no selectors, so the full value is not returned.
+    let response = client
+        .send_get_request(GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: descriptor.clone(),
+            access_selection: selection.clone(),
+        }))
+        .expect("get failed");
+    assert!(matches!(
+        response,
+        GetResponse::Normal(response)
+            if response.result
+                == GetDataResult::DataAccessResult(DataAccessResult::ScopeOfAccessViolated)
+    ));
+    let response = client
+        .send_set_request(SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: descriptor,
+            access_selection: selection,
+            value: CosemData::Unsigned(99),
+        }))
+        .expect("set failed");
+    assert!(matches!(
+        response,
+        SetResponse::Normal(response) if response.result == DataAccessResult::ScopeOfAccessViolated
+    ));
+    assert!(matches!(
+        read_energy(&mut client),
+        GetResponse::Normal(response)
+            if response.result == GetDataResult::Data(CosemData::Unsigned(10))
+    ));
+}
+
+// Serial bus shared by several meters: every frame reaches all of them and
+// only the addressed one answers. Silence reads as a timeout.
+struct BusTransport {
+    meters: Vec<Server<DetachedTransport>>,
+    responses: std::collections::VecDeque<Vec<u8>>,
+}
+
+impl Transport for BusTransport {
+    type Error = ();
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        for meter in &mut self.meters {
+            if let Ok(Some(response)) = meter.process_frame(bytes) {
+                self.responses.push_back(response);
+            }
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<u8>, ()> {
+        self.responses.pop_front().ok_or(())
+    }
+}
+
+#[test]
+fn test_address_scan_finds_the_meters_on_a_bus() {
+    let meters = [17, 19]
+        .into_iter()
+        .map(|physical_address| {
+            let mut meter = Server::new(1, DetachedTransport, None, None);
+            meter.set_physical_address(physical_address);
+            meter
+        })
+        .collect();
+    let transport = BusTransport {
+        meters,
+        responses: Default::default(),
+    };
+    let mut client = Client::new(0x10, transport, None, None);
+    let devices = client.scan((16..=20).map(|physical| HdlcAddress::for_device(1, physical)));
+    let found: Vec<_> = devices.iter().map(|device| device.address.lower).collect();
+    assert_eq!(found, vec![17, 19]);
+    assert!(devices.iter().all(|device| device
+        .negotiated
+        .as_ref()
+        .is_some_and(|negotiated| negotiated.negotiated_dlms_version_number == 6)));
+    assert!(client.negotiated_parameters().is_none());
+    assert!(client
+        .transport()
+        .meters
+        .iter()
+        .all(|meter| meter.associated_clients().is_empty()));
+}
+
+#[test]
+fn test_associations_to_two_servers_share_one_transport() {
+    let meters = [(1, 10), (2, 20)]
+        .into_iter()
+        .map(|(address, energy)| {
+            let mut meter = Server::new(address, DetachedTransport, None, None);
+            let mut register = Register::new();
+            register
+                .set_attribute(2, CosemData::Unsigned(energy))
+                .unwrap();
+            meter.register_object([1, 0, 1, 8, 0, 255], Box::new(register));
+            meter
+        })
+        .collect();
+    let concentrator = MultiplexedTransport::new(BusTransport {
+        meters,
+        responses: Default::default(),
+    });
+    let mut first = concentrator.client(0x10, HdlcAddress::new(1, 0x11), None);
+    let mut second = concentrator.client(0x20, HdlcAddress::new(2, 0x11), None);
+    first.associate().expect("first association failed");
+    second.associate().expect("second association failed");
+
+    let energy = |response| match response {
+        GetResponse::Normal(response) => response.result,
+        _ => panic!("unexpected response"),
+    };
+    assert_eq!(
+        energy(read_energy(&mut second)),
+        GetDataResult::Data(CosemData::Unsigned(20))
+    );
+    assert_eq!(
+        energy(read_energy(&mut first)),
+        GetDataResult::Data(CosemData::Unsigned(10))
+    );
+
+    // Releasing one association leaves the other in place.
+    first.release().expect("release failed");
+    assert!(first.negotiated_parameters().is_none());
+    assert_eq!(
+        energy(read_energy(&mut second)),
+        GetDataResult::Data(CosemData::Unsigned(20))
+    );
+    concentrator.with_transport(|bus| {
+        assert!(bus.meters[0].associated_clients().is_empty());
+        assert_eq!(bus.meters[1].associated_clients(), vec![0x20]);
+    });
+}
+
+#[test]
+fn test_server_builder_registers_logical_device_name() {
+    let name = LogicalDeviceName::new("ABC", "12345678").unwrap();
+    let server = ServerBuilder::new(1, DetachedTransport)
+        .logical_device_name(name)
+        .object([1, 0, 1, 8, 0, 255], Box::new(Register::new()))
+        .build();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+    let response = client
+        .send_get_request(GetRequest::Normal(GetRequestNormal {
+            invoke_id_and_priority: 1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: LOGICAL_DEVICE_NAME_LN,
+                attribute_id: 2,
+            },
+            access_selection: None,
+        }))
+        .expect("get failed");
+    let GetResponse::Normal(response) = response else {
+        panic!("unexpected response {response:?}");
+    };
+    let GetDataResult::Data(value) = response.result else {
+        panic!("logical device name not readable");
+    };
+    assert_eq!(LogicalDeviceName::from_cosem_data(&value), Ok(name));
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_write_verified_reports_write_and_read_back() {
+    let clock_ln = [0, 0, 1, 0, 0, 255];
+    let server = ServerBuilder::new(1, DetachedTransport)
+        .object([1, 0, 1, 8, 0, 255], Box::new(Register::new()))
+        .object(clock_ln, Box::new(Clock::new()))
+        .build();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    // Date-times travel as octet-strings.
+    client.set_write_tolerance(DataType::OctetString, date_time_within(5));
+    client.associate().expect("Association failed");
+
+    let energy = CosemAttributeDescriptor {
+        class_id: 3,
+        instance_id: [1, 0, 1, 8, 0, 255],
+        attribute_id: 2,
+    };
+    let report = client
+        .write_verified(energy, CosemData::Unsigned(42))
+        .expect("write failed");
+    assert_eq!(report.write_result, DataAccessResult::Success);
+    assert_eq!(
+        report.read_back,
+        Some(GetDataResult::Data(CosemData::Unsigned(42)))
+    );
+    assert!(report.verified);
+
+    let time = CosemData::OctetString(vec![0x07, 0xEA, 10, 17, 6, 12, 0, 0, 0, 0x80, 0, 0]);
+    let time_descriptor = |attribute_id| CosemAttributeDescriptor {
+        class_id: 8,
+        instance_id: clock_ln,
+        attribute_id,
+    };
+    assert!(
+        client
+            .write_verified(time_descriptor(2), time)
+            .expect("write failed")
+            .verified
+    );
+
+    // The clock status is read-only, so nothing is read back.
+    let report = client
+        .write_verified(time_descriptor(4), CosemData::Unsigned(0))
+        .expect("write failed");
+    assert_eq!(report.write_result, DataAccessResult::ReadWriteDenied);
+    assert_eq!(report.read_back, None);
+    assert!(!report.verified);
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_link_supervision_reports_link_state_changes() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut client = Client::new(1, LoopbackTransport::new(loopback_meter()), None, None);
+    let recorded = Arc::clone(&events);
+    client.on_link_state_change(move |state| recorded.lock().unwrap().push(state));
+    client.set_link_check_interval(Some(Duration::from_secs(30)));
+
+    let start = Instant::now();
+    assert_eq!(
+        client.supervise_link_at(start).unwrap(),
+        Some(LinkState::Up)
+    );
+    assert_eq!(
+        client
+            .supervise_link_at(start + Duration::from_secs(10))
+            .unwrap(),
+        None
+    );
+    client.associate().expect("Association failed");
+    read_energy(&mut client);
+    client.release().expect("Release failed");
+    assert_eq!(*events.lock().unwrap(), vec![LinkState::Up]);
+
+    let mut dead = Client::new(1, DetachedTransport, None, None);
+    let recorded = Arc::clone(&events);
+    dead.on_link_state_change(move |state| recorded.lock().unwrap().push(state));
+    assert_eq!(dead.check_link().unwrap(), LinkState::Down);
+    assert_eq!(dead.link_state(), Some(LinkState::Down));
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![LinkState::Up, LinkState::Down]
+    );
+}
+
+#[test]
+fn test_faulty_meter_misbehaves_by_rule() {
+    let energy = RequestKey::Get {
+        attributes: vec![CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: [1, 0, 1, 8, 0, 255],
+            attribute_id: 2,
+        }],
+        block_number: None,
+    };
+    let mut transport = FaultyTransport::client(LoopbackTransport::new(loopback_meter()));
+    transport.set_seed(7);
+    transport.add_rule(FaultRule::new(Fault::Drop).with_probability(0.0));
+    transport.add_rule(
+        FaultRule::new(Fault::TemporaryFailure)
+            .on(energy.clone())
+            .times(1),
+    );
+    transport.add_rule(
+        FaultRule::new(Fault::WrongInvokeId)
+            .on(energy.clone())
+            .times(1),
+    );
+    transport.add_rule(FaultRule::new(Fault::Drop).on(energy.clone()).times(1));
+    transport.add_rule(FaultRule::new(Fault::CorruptCrc).on(energy).times(1));
+    let mut client = Client::new(1, transport, None, None);
+    client.associate().expect("Association failed");
+
+    match read_energy(&mut client) {
+        GetResponse::Normal(response) => assert_eq!(
+            response.result,
+            GetDataResult::DataAccessResult(DataAccessResult::TemporaryFailure)
+        ),
+        other => panic!("unexpected response: {other:?}"),
+    }
+    match read_energy(&mut client) {
+        GetResponse::Normal(response) => {
+            assert_ne!(response.invoke_id_and_priority, 1);
+            assert_eq!(
+                response.result,
+                GetDataResult::Data(CosemData::Unsigned(10))
+            );
+        }
+        other => panic!("unexpected response: {other:?}"),
+    }
+    let request = GetRequest::Normal(GetRequestNormal {
+        invoke_id_and_priority: 1,
+        cosem_attribute_descriptor: CosemAttributeDescriptor {
+            class_id: 3,
+            instance_id: [1, 0, 1, 8, 0, 255],
+            attribute_id: 2,
+        },
+        access_selection: None,
+    });
+    assert!(matches!(
+        client.send_get_request(request.clone()),
+        Err(ClientError::TransportError(FaultError::Dropped))
+    ));
+    assert!(matches!(
+        client.send_get_request(request),
+        Err(ClientError::DlmsError(_))
+    ));
+
+    // Every rule is used up; the meter behaves again.
+    match read_energy(&mut client) {
+        GetResponse::Normal(response) => {
+            assert_eq!(
+                response.result,
+                GetDataResult::Data(CosemData::Unsigned(10))
+            )
+        }
+        other => panic!("unexpected response: {other:?}"),
+    }
+    let rules = client.transport().rules();
+    assert_eq!(rules[0].applied(), 0);
+    assert!(rules[1..].iter().all(|rule| rule.applied() == 1));
+}
+
+#[test]
+fn test_oversized_set_is_received_in_datablocks() {
+    let image_ln = [0, 0, 44, 0, 0, 255];
+    let server = ServerBuilder::new(1, DetachedTransport)
+        .object(
+            image_ln,
+            Box::new(Data::with_access(
+                CosemData::OctetString(Vec::new()),
+                AttributeAccessMode::ReadWrite,
+            )),
+        )
+        .build();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let image: Vec<u8> = (0..3000).map(|index| index as u8).collect();
+    let report = client
+        .write_verified(
+            CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: image_ln,
+                attribute_id: 2,
+            },
+            CosemData::OctetString(image),
+        )
+        .expect("write failed");
+    assert_eq!(report.write_result, DataAccessResult::Success);
+    assert!(report.verified);
+    client.release().expect("Release failed");
+}
+
+// Answers method 1 with the number of octets of its parameters.
+struct ParameterCounter;
+
+impl CosemObject for ParameterCounter {
+    fn class_id(&self) -> u16 {
+        1
+    }
+    fn method_access_rights(&self) -> Vec<MethodAccessDescriptor> {
+        vec![MethodAccessDescriptor::new(1, MethodAccessMode::Access)]
+    }
+    fn get_attribute(&self, _attribute_id: CosemObjectAttributeId) -> Option<CosemData> {
+        None
+    }
+    fn set_attribute(
+        &mut self,
+        _attribute_id: CosemObjectAttributeId,
+        _data: CosemData,
+    ) -> Option<()> {
+        None
+    }
+    fn invoke_method(
+        &mut self,
+        method_id: CosemObjectMethodId,
+        data: CosemData,
+    ) -> Option<CosemData> {
+        match (method_id, data) {
+            (1, CosemData::OctetString(octets)) => {
+                Some(CosemData::DoubleLongUnsigned(octets.len() as u32))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_oversized_action_parameters_are_sent_in_pblocks() {
+    let counter_ln = [0, 0, 96, 99, 1, 255];
+    let server = ServerBuilder::new(1, DetachedTransport)
+        .object(counter_ln, Box::new(ParameterCounter))
+        .build();
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let response = client
+        .send_action_request(ActionRequest::Normal(ActionRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 1,
+                instance_id: counter_ln,
+                method_id: 1,
+            },
+            method_invocation_parameters: Some(CosemData::OctetString(vec![0x5A; 3000])),
+        }))
+        .expect("action failed");
+    let ActionResponse::Normal(response) = response else {
+        panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(response.single_response.result, ActionResult::Success);
+    assert_eq!(
+        response.single_response.return_data(),
+        Some(&CosemData::DoubleLongUnsigned(3000))
+    );
+
+    // A pblock without a transfer under way is refused.
+    let response = client
+        .send_action_request(ActionRequest::WithPblock(ActionRequestWithPblock {
+            invoke_id_and_priority: 0xC1,
+            pblock: DataBlockSA {
+                last_block: true,
+                block_number: 2,
+                raw_data: vec![0x00],
+            },
+        }))
+        .expect("action failed");
+    let ActionResponse::Normal(response) = response else {
+        panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(
+        response.single_response.result,
+        ActionResult::NoLongActionInProgress
+    );
+    client.release().expect("Release failed");
+}
+
+#[test]
+fn test_long_transfers_beyond_the_limit_are_aborted() {
+    let image_ln = [0, 0, 44, 0, 0, 255];
+    let counter_ln = [0, 0, 96, 99, 1, 255];
+    let mut server = ServerBuilder::new(1, DetachedTransport)
+        .object(
+            image_ln,
+            Box::new(Data::with_access(
+                CosemData::OctetString(Vec::new()),
+                AttributeAccessMode::ReadWrite,
+            )),
+        )
+        .object(counter_ln, Box::new(ParameterCounter))
+        .build();
+    server.set_max_long_transfer_size(1000);
+    let mut client = Client::new(1, LoopbackTransport::new(server), None, None);
+    client.associate().expect("Association failed");
+
+    let response = client
+        .send_set_request(SetRequest::Normal(SetRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_attribute_descriptor: CosemAttributeDescriptor {
+                class_id: 1,
+                instance_id: image_ln,
+                attribute_id: 2,
+            },
+            access_selection: None,
+            value: CosemData::OctetString(vec![0x5A; 3000]),
+        }))
+        .expect("set failed");
+    let SetResponse::LastDatablock(response) = response else {
+        panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(response.result, DataAccessResult::LongSetAborted);
+
+    let response = client
+        .send_action_request(ActionRequest::Normal(ActionRequestNormal {
+            invoke_id_and_priority: 0xC1,
+            cosem_method_descriptor: CosemMethodDescriptor {
+                class_id: 1,
+                instance_id: counter_ln,
+                method_id: 1,
+            },
+            method_invocation_parameters: Some(CosemData::OctetString(vec![0x5A; 3000])),
+        }))
+        .expect("action failed");
+    let ActionResponse::Normal(response) = response else {
+        panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(
+        response.single_response.result,
+        ActionResult::LongActionAborted
+    );
+    client.release().expect("Release failed");
+}
@@ -0,0 +1,475 @@
+// Round-trip properties between the encoders of one side and the decoders of
+// the other: whatever the server encodes the client decodes to the same
+// value, and the other way round. Decoding is strict, so a value must also
+// come back from the exact bytes it was encoded to.
+
+use dlms_cosem::axdr::{decode_data_with, encode_data, ParseMode};
+use dlms_cosem::cosem::{CosemAttributeDescriptor, CosemMethodDescriptor};
+use dlms_cosem::types::{BitString, CosemData};
+use dlms_cosem::xdlms::{
+    ActionRequest, ActionRequestNormal, ActionRequestWithFirstPblock, ActionRequestWithList,
+    ActionRequestWithPblock, ActionResponse, ActionResponseNextPblock, ActionResponseNormal,
+    ActionResponseWithList, ActionResponseWithOptionalData, ActionResult, DataAccessResult,
+    DataBlockG, DataBlockResult, DataBlockSA, DataNotification, EventNotificationRequest,
+    ExceptionResponse, GetDataResult, GetRequest, GetRequestNext, GetRequestNormal,
+    GetRequestWithList, GetResponse, GetResponseNormal, GetResponseWithDatablock,
+    GetResponseWithList, SelectiveAccessDescriptor, ServiceError, SetRequest, SetRequestNormal,
+    SetRequestWithDatablock, SetRequestWithFirstDatablock, SetRequestWithList, SetResponse,
+    SetResponseDatablock, SetResponseLastDatablock, SetResponseNormal, SetResponseWithList,
+    StateError,
+};
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+// Data of the types the A-XDR encoder writes, nested in arrays and
+// structures.
+fn cosem_data() -> impl Strategy<Value = CosemData> {
+    let scalar = prop_oneof![
+        Just(CosemData::NullData),
+        any::<bool>().prop_map(CosemData::Boolean),
+        (0usize..40, vec(any::<u8>(), 5)).prop_map(|(len, bytes)| {
+            let bits = BitString::new(len, bytes[..len.div_ceil(8)].to_vec()).unwrap();
+            CosemData::BitString(bits)
+        }),
+        any::<i32>().prop_map(CosemData::DoubleLong),
+        any::<u32>().prop_map(CosemData::DoubleLongUnsigned),
+        vec(any::<u8>(), 0..300).prop_map(CosemData::OctetString),
+        any::<i8>().prop_map(CosemData::Integer),
+        any::<i16>().prop_map(CosemData::Long),
+        any::<u8>().prop_map(CosemData::Unsigned),
+        any::<u16>().prop_map(CosemData::LongUnsigned),
+        any::<i64>().prop_map(CosemData::Long64),
+        any::<u64>().prop_map(CosemData::Long64Unsigned),
+        any::<u8>().prop_map(CosemData::Enum),
+        any::<f32>()
+            .prop_filter("NaN never equals itself", |v| !v.is_nan())
+            .prop_map(CosemData::Float32),
+        any::<f64>()
+            .prop_filter("NaN never equals itself", |v| !v.is_nan())
+            .prop_map(CosemData::Float64),
+    ];
+    scalar.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..8).prop_map(CosemData::Array),
+            vec(inner, 0..8).prop_map(CosemData::Structure),
+        ]
+    })
+}
+
+fn attribute_descriptor() -> impl Strategy<Value = CosemAttributeDescriptor> {
+    (any::<u16>(), any::<[u8; 6]>(), any::<i8>()).prop_map(
+        |(class_id, instance_id, attribute_id)| CosemAttributeDescriptor {
+            class_id,
+            instance_id,
+            attribute_id,
+        },
+    )
+}
+
+fn method_descriptor() -> impl Strategy<Value = CosemMethodDescriptor> {
+    (any::<u16>(), any::<[u8; 6]>(), any::<i8>()).prop_map(|(class_id, instance_id, method_id)| {
+        CosemMethodDescriptor {
+            class_id,
+            instance_id,
+            method_id,
+        }
+    })
+}
+
+fn access_selection() -> impl Strategy<Value = Option<SelectiveAccessDescriptor>> {
+    option::of(
+        (any::<u8>(), cosem_data()).prop_map(|(access_selector, access_parameters)| {
+            SelectiveAccessDescriptor {
+                access_selector,
+                access_parameters,
+            }
+        }),
+    )
+}
+
+fn data_block() -> impl Strategy<Value = DataBlockSA> {
+    (any::<bool>(), any::<u32>(), vec(any::<u8>(), 0..300)).prop_map(
+        |(last_block, block_number, raw_data)| DataBlockSA {
+            last_block,
+            block_number,
+            raw_data,
+        },
+    )
+}
+
+// Results are built from their code, as each code has a single value.
+fn data_access_result() -> impl Strategy<Value = DataAccessResult> {
+    any::<u8>().prop_map(DataAccessResult::from)
+}
+
+fn get_data_result() -> impl Strategy<Value = GetDataResult> {
+    prop_oneof![
+        cosem_data().prop_map(GetDataResult::Data),
+        data_access_result().prop_map(GetDataResult::DataAccessResult),
+    ]
+}
+
+fn action_response_with_optional_data() -> impl Strategy<Value = ActionResponseWithOptionalData> {
+    (
+        any::<u8>().prop_map(ActionResult::from),
+        option::of(get_data_result()),
+    )
+        .prop_map(
+            |(result, return_parameters)| ActionResponseWithOptionalData {
+                result,
+                return_parameters,
+            },
+        )
+}
+
+fn date_time() -> impl Strategy<Value = Option<Vec<u8>>> {
+    option::of(vec(any::<u8>(), 12))
+}
+
+fn get_request() -> impl Strategy<Value = GetRequest> {
+    prop_oneof![
+        (any::<u8>(), attribute_descriptor(), access_selection()).prop_map(
+            |(invoke_id_and_priority, cosem_attribute_descriptor, access_selection)| {
+                GetRequest::Normal(GetRequestNormal {
+                    invoke_id_and_priority,
+                    cosem_attribute_descriptor,
+                    access_selection,
+                })
+            }
+        ),
+        (any::<u8>(), any::<u32>()).prop_map(|(invoke_id_and_priority, block_number)| {
+            GetRequest::Next(GetRequestNext {
+                invoke_id_and_priority,
+                block_number,
+            })
+        }),
+        (any::<u8>(), vec(attribute_descriptor(), 0..8)).prop_map(
+            |(invoke_id_and_priority, attribute_descriptor_list)| {
+                GetRequest::WithList(GetRequestWithList {
+                    invoke_id_and_priority,
+                    attribute_descriptor_list,
+                })
+            }
+        ),
+    ]
+}
+
+fn get_response() -> impl Strategy<Value = GetResponse> {
+    prop_oneof![
+        (any::<u8>(), get_data_result()).prop_map(|(invoke_id_and_priority, result)| {
+            GetResponse::Normal(GetResponseNormal {
+                invoke_id_and_priority,
+                result,
+            })
+        }),
+        (any::<u8>(), data_block()).prop_map(|(invoke_id_and_priority, block)| {
+            GetResponse::WithDataBlock(GetResponseWithDatablock {
+                invoke_id_and_priority,
+                result: DataBlockG {
+                    last_block: block.last_block,
+                    block_number: block.block_number,
+                    result: DataBlockResult::RawData(block.raw_data),
+                },
+            })
+        }),
+        (any::<u8>(), any::<u32>(), data_access_result()).prop_map(
+            |(invoke_id_and_priority, block_number, dar)| {
+                GetResponse::WithDataBlock(GetResponseWithDatablock {
+                    invoke_id_and_priority,
+                    result: DataBlockG {
+                        last_block: true,
+                        block_number,
+                        result: DataBlockResult::DataAccessResult(dar),
+                    },
+                })
+            }
+        ),
+        (any::<u8>(), vec(get_data_result(), 0..8)).prop_map(|(invoke_id_and_priority, result)| {
+            GetResponse::WithList(GetResponseWithList {
+                invoke_id_and_priority,
+                result,
+            })
+        }),
+    ]
+}
+
+fn set_request() -> impl Strategy<Value = SetRequest> {
+    prop_oneof![
+        (
+            any::<u8>(),
+            attribute_descriptor(),
+            access_selection(),
+            cosem_data()
+        )
+            .prop_map(
+                |(invoke_id_and_priority, cosem_attribute_descriptor, access_selection, value)| {
+                    SetRequest::Normal(SetRequestNormal {
+                        invoke_id_and_priority,
+                        cosem_attribute_descriptor,
+                        access_selection,
+                        value,
+                    })
+                }
+            ),
+        (
+            any::<u8>(),
+            attribute_descriptor(),
+            access_selection(),
+            data_block()
+        )
+            .prop_map(
+                |(
+                    invoke_id_and_priority,
+                    cosem_attribute_descriptor,
+                    access_selection,
+                    datablock,
+                )| {
+                    SetRequest::WithFirstDatablock(SetRequestWithFirstDatablock {
+                        invoke_id_and_priority,
+                        cosem_attribute_descriptor,
+                        access_selection,
+                        datablock,
+                    })
+                }
+            ),
+        (any::<u8>(), data_block()).prop_map(|(invoke_id_and_priority, datablock)| {
+            SetRequest::WithDatablock(SetRequestWithDatablock {
+                invoke_id_and_priority,
+                datablock,
+            })
+        }),
+        (
+            any::<u8>(),
+            vec(attribute_descriptor(), 0..8),
+            vec(cosem_data(), 0..8)
+        )
+            .prop_map(
+                |(invoke_id_and_priority, attribute_descriptor_list, value_list)| {
+                    SetRequest::WithList(SetRequestWithList {
+                        invoke_id_and_priority,
+                        attribute_descriptor_list,
+                        value_list,
+                    })
+                }
+            ),
+    ]
+}
+
+fn set_response() -> impl Strategy<Value = SetResponse> {
+    prop_oneof![
+        (any::<u8>(), data_access_result()).prop_map(|(invoke_id_and_priority, result)| {
+            SetResponse::Normal(SetResponseNormal {
+                invoke_id_and_priority,
+                result,
+            })
+        }),
+        (any::<u8>(), any::<u32>()).prop_map(|(invoke_id_and_priority, block_number)| {
+            SetResponse::Datablock(SetResponseDatablock {
+                invoke_id_and_priority,
+                block_number,
+            })
+        }),
+        (any::<u8>(), data_access_result(), any::<u32>()).prop_map(
+            |(invoke_id_and_priority, result, block_number)| {
+                SetResponse::LastDatablock(SetResponseLastDatablock {
+                    invoke_id_and_priority,
+                    result,
+                    block_number,
+                })
+            }
+        ),
+        (any::<u8>(), vec(data_access_result(), 0..8)).prop_map(
+            |(invoke_id_and_priority, result)| {
+                SetResponse::WithList(SetResponseWithList {
+                    invoke_id_and_priority,
+                    result,
+                })
+            }
+        ),
+    ]
+}
+
+fn action_request() -> impl Strategy<Value = ActionRequest> {
+    prop_oneof![
+        (any::<u8>(), method_descriptor(), option::of(cosem_data())).prop_map(
+            |(invoke_id_and_priority, cosem_method_descriptor, method_invocation_parameters)| {
+                ActionRequest::Normal(ActionRequestNormal {
+                    invoke_id_and_priority,
+                    cosem_method_descriptor,
+                    method_invocation_parameters,
+                })
+            }
+        ),
+        (any::<u8>(), method_descriptor(), data_block()).prop_map(
+            |(invoke_id_and_priority, cosem_method_descriptor, pblock)| {
+                ActionRequest::WithFirstPblock(ActionRequestWithFirstPblock {
+                    invoke_id_and_priority,
+                    cosem_method_descriptor,
+                    pblock,
+                })
+            }
+        ),
+        (any::<u8>(), data_block()).prop_map(|(invoke_id_and_priority, pblock)| {
+            ActionRequest::WithPblock(ActionRequestWithPblock {
+                invoke_id_and_priority,
+                pblock,
+            })
+        }),
+        (
+            any::<u8>(),
+            vec(method_descriptor(), 0..8),
+            vec(cosem_data(), 0..8)
+        )
+            .prop_map(
+                |(
+                    invoke_id_and_priority,
+                    cosem_method_descriptor_list,
+                    method_invocation_parameters,
+                )| {
+                    ActionRequest::WithList(ActionRequestWithList {
+                        invoke_id_and_priority,
+                        cosem_method_descriptor_list,
+                        method_invocation_parameters,
+                    })
+                }
+            ),
+    ]
+}
+
+fn action_response() -> impl Strategy<Value = ActionResponse> {
+    prop_oneof![
+        (any::<u8>(), action_response_with_optional_data()).prop_map(
+            |(invoke_id_and_priority, single_response)| {
+                ActionResponse::Normal(ActionResponseNormal {
+                    invoke_id_and_priority,
+                    single_response,
+                })
+            }
+        ),
+        (any::<u8>(), vec(action_response_with_optional_data(), 0..8)).prop_map(
+            |(invoke_id_and_priority, list_of_responses)| {
+                ActionResponse::WithList(ActionResponseWithList {
+                    invoke_id_and_priority,
+                    list_of_responses,
+                })
+            }
+        ),
+        (any::<u8>(), any::<u32>()).prop_map(|(invoke_id_and_priority, block_number)| {
+            ActionResponse::NextPblock(ActionResponseNextPblock {
+                invoke_id_and_priority,
+                block_number,
+            })
+        }),
+    ]
+}
+
+fn exception_response() -> impl Strategy<Value = ExceptionResponse> {
+    let state_error = prop_oneof![
+        Just(StateError::ServiceNotAllowed),
+        Just(StateError::ServiceUnknown),
+    ];
+    let service_error = prop_oneof![
+        Just(ServiceError::OperationNotPossible),
+        Just(ServiceError::ServiceNotSupported),
+        Just(ServiceError::OtherReason),
+        Just(ServiceError::PduTooLong),
+        Just(ServiceError::DecipheringError),
+        any::<u32>().prop_map(ServiceError::InvocationCounterError),
+    ];
+    (state_error, service_error).prop_map(|(state_error, service_error)| ExceptionResponse {
+        state_error,
+        service_error,
+    })
+}
+
+fn data_notification() -> impl Strategy<Value = DataNotification> {
+    (any::<u32>(), date_time(), cosem_data()).prop_map(
+        |(long_invoke_id_and_priority, date_time, notification_body)| DataNotification {
+            long_invoke_id_and_priority,
+            date_time,
+            notification_body,
+        },
+    )
+}
+
+fn event_notification() -> impl Strategy<Value = EventNotificationRequest> {
+    (date_time(), attribute_descriptor(), cosem_data()).prop_map(
+        |(time, cosem_attribute_descriptor, attribute_value)| EventNotificationRequest {
+            time,
+            cosem_attribute_descriptor,
+            attribute_value,
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn data_decodes_to_what_was_encoded(data in cosem_data()) {
+        let mut bytes = Vec::new();
+        encode_data(&data, &mut bytes).unwrap();
+        let (decoded, rest) = decode_data_with(&bytes, ParseMode::Strict).unwrap();
+        prop_assert_eq!(decoded, data);
+        prop_assert!(rest.is_empty());
+    }
+
+    // Bytes decoded strictly hold the only encoding of their value.
+    #[test]
+    fn strictly_decoded_data_encodes_to_its_bytes(bytes in vec(any::<u8>(), 0..64)) {
+        if let Ok((data, rest)) = decode_data_with(&bytes, ParseMode::Strict) {
+            let mut encoded = Vec::new();
+            encode_data(&data, &mut encoded).unwrap();
+            prop_assert_eq!(encoded, &bytes[..bytes.len() - rest.len()]);
+        }
+    }
+
+    #[test]
+    fn get_requests_round_trip(request in get_request()) {
+        let bytes = request.to_bytes().unwrap();
+        prop_assert_eq!(GetRequest::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), request);
+    }
+
+    #[test]
+    fn get_responses_round_trip(response in get_response()) {
+        let bytes = response.to_bytes().unwrap();
+        prop_assert_eq!(GetResponse::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), response);
+    }
+
+    #[test]
+    fn set_requests_round_trip(request in set_request()) {
+        let bytes = request.to_bytes().unwrap();
+        prop_assert_eq!(SetRequest::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), request);
+    }
+
+    #[test]
+    fn set_responses_round_trip(response in set_response()) {
+        let bytes = response.to_bytes().unwrap();
+        prop_assert_eq!(SetResponse::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), response);
+    }
+
+    #[test]
+    fn action_requests_round_trip(request in action_request()) {
+        let bytes = request.to_bytes().unwrap();
+        prop_assert_eq!(ActionRequest::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), request);
+    }
+
+    #[test]
+    fn action_responses_round_trip(response in action_response()) {
+        let bytes = response.to_bytes().unwrap();
+        prop_assert_eq!(ActionResponse::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), response);
+    }
+
+    #[test]
+    fn exception_responses_round_trip(response in exception_response()) {
+        let bytes = response.to_bytes().unwrap();
+        prop_assert_eq!(ExceptionResponse::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), response);
+    }
+
+    #[test]
+    fn notifications_round_trip(data in data_notification(), event in event_notification()) {
+        let bytes = data.to_bytes().unwrap();
+        prop_assert_eq!(DataNotification::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), data);
+        let bytes = event.to_bytes().unwrap();
+        prop_assert_eq!(EventNotificationRequest::from_bytes_with(&bytes, ParseMode::Strict).unwrap(), event);
+    }
+}